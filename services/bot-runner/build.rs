@@ -0,0 +1,12 @@
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    // data-retrieval owns the price service definition
+    let descriptors = protox::compile(
+        ["../data-retrieval/proto/prices.proto"],
+        ["../data-retrieval/proto"],
+    )?;
+    tonic_build::configure()
+        .build_server(false)
+        .compile_fds(descriptors)?;
+    println!("cargo:rerun-if-changed=../data-retrieval/proto");
+    Ok(())
+}
@@ -12,35 +12,58 @@ pub struct TokenInfo {
     pub tags: Vec<String>,
 }
 
+/// USDC's decimals, the unit of cash
+pub const USDC_DECIMALS: u8 = 6;
+
+/// Why an amount can't be expressed in raw units
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+pub enum AmountError {
+    #[error("amount cannot be negative: {0}")]
+    Negative(Decimal),
+    #[error("amount {amount} with {decimals} decimals overflows u64")]
+    Overflow { amount: Decimal, decimals: u8 },
+    #[error("amount {amount} too small for {decimals} decimals (rounds to 0)")]
+    TooSmall { amount: Decimal, decimals: u8 },
+    #[error("{0} decimals is out of range")]
+    InvalidDecimals(u8),
+    #[error("decimals unknown for {0}")]
+    UnknownDecimals(String),
+    #[error("no price for {0}")]
+    Unpriced(String),
+    #[error("nothing held of {0}")]
+    NothingHeld(String),
+}
+
 /// Convert UI amount (human readable) to raw amount (u64)
 ///
 /// # Safety
 /// - Validates amount is non-negative
 /// - Checks for overflow (amount must fit in u64)
-/// - Uses integer arithmetic only
-pub fn to_raw_amount(ui_amount: Decimal, decimals: u8) -> anyhow::Result<u64> {
+/// - Uses checked integer arithmetic only; never saturates or wraps
+pub fn to_raw_amount(ui_amount: Decimal, decimals: u8) -> Result<u64, AmountError> {
     if ui_amount < Decimal::ZERO {
-        return Err(anyhow::anyhow!("Amount cannot be negative: {}", ui_amount));
+        return Err(AmountError::Negative(ui_amount));
     }
 
-    let multiplier = Decimal::from(10u64.pow(decimals as u32));
-    let raw = ui_amount * multiplier;
+    let multiplier = 10u64
+        .checked_pow(decimals as u32)
+        .ok_or(AmountError::InvalidDecimals(decimals))?;
+    let overflow = AmountError::Overflow {
+        amount: ui_amount,
+        decimals,
+    };
+    let raw = ui_amount
+        .checked_mul(Decimal::from(multiplier))
+        .ok_or_else(|| overflow.clone())?;
 
     // Check if it fits in u64
-    let raw_u64 = raw.to_u64().ok_or_else(|| {
-        anyhow::anyhow!(
-            "Amount {} with {} decimals overflows u64",
-            ui_amount,
-            decimals
-        )
-    })?;
+    let raw_u64 = raw.to_u64().ok_or(overflow)?;
 
     if raw_u64 == 0 && ui_amount > Decimal::ZERO {
-        return Err(anyhow::anyhow!(
-            "Amount {} too small for {} decimals (rounds to 0)",
-            ui_amount,
-            decimals
-        ));
+        return Err(AmountError::TooSmall {
+            amount: ui_amount,
+            decimals,
+        });
     }
 
     Ok(raw_u64)
@@ -183,6 +206,46 @@ mod tests {
         assert!(to_raw_amount(neg, 6).is_err());
     }
 
+    #[test]
+    fn test_to_raw_amount_boundaries() {
+        // The largest raw amount fits, one unit more doesn't
+        let max = Decimal::from(u64::MAX);
+        assert_eq!(to_raw_amount(max, 0).unwrap(), u64::MAX);
+        assert!(matches!(
+            to_raw_amount(max + Decimal::ONE, 0),
+            Err(AmountError::Overflow { .. })
+        ));
+        assert!(matches!(
+            to_raw_amount(Decimal::from(20_000_000_000_000u64), USDC_DECIMALS),
+            Err(AmountError::Overflow { .. })
+        ));
+        // Past the Decimal range, not just the u64 one
+        assert!(matches!(
+            to_raw_amount(Decimal::MAX, 18),
+            Err(AmountError::Overflow { .. })
+        ));
+        // 10^20 doesn't fit a u64 multiplier
+        assert_eq!(
+            to_raw_amount(Decimal::ONE, 20),
+            Err(AmountError::InvalidDecimals(20))
+        );
+
+        // One raw unit is the smallest amount
+        assert_eq!(to_raw_amount(Decimal::new(1, 9), 9).unwrap(), 1);
+        assert!(matches!(
+            to_raw_amount(Decimal::new(9, 10), 9),
+            Err(AmountError::TooSmall { .. })
+        ));
+        assert_eq!(to_raw_amount(Decimal::ZERO, 9).unwrap(), 0);
+        assert_eq!(
+            to_raw_amount(Decimal::new(1, 28), 0),
+            Err(AmountError::TooSmall {
+                amount: Decimal::new(1, 28),
+                decimals: 0,
+            })
+        );
+    }
+
     #[test]
     fn test_from_raw_amount() {
         assert_eq!(from_raw_amount(1_000_000_000, 9), Decimal::from(1));
@@ -205,4 +268,30 @@ mod tests {
         let by_mint = get_token_info("EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v").unwrap();
         assert_eq!(by_mint.symbol, "USDC");
     }
+
+    /// Randomized checks over the full u64 range and every allowed decimals
+    #[test]
+    fn test_raw_amount_properties() {
+        use rand::{rngs::StdRng, Rng, SeedableRng};
+
+        let mut rng = StdRng::seed_from_u64(4514);
+        for _ in 0..10_000 {
+            let decimals = rng.gen_range(0..=crate::mints::MAX_DECIMALS);
+            let raw: u64 = rng.gen_range(1..=u64::MAX);
+
+            // Raw -> UI -> raw is lossless
+            let ui = from_raw_amount(raw, decimals);
+            assert_eq!(to_raw_amount(ui, decimals).unwrap(), raw);
+
+            // Dust below one raw unit is truncated, never rounded up
+            let dust = Decimal::new(rng.gen_range(0..10), decimals as u32 + 1);
+            assert_eq!(to_raw_amount(ui + dust, decimals).unwrap(), raw);
+
+            // Wrong decimals are off by whole powers of ten, e.g. a 9-decimal
+            // amount read as 6 decimals is 1000x too large
+            if decimals >= 3 {
+                assert_eq!(from_raw_amount(raw, decimals - 3), ui * Decimal::from(1000));
+            }
+        }
+    }
 }
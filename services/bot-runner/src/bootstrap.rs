@@ -0,0 +1,459 @@
+//! `bot-runner bootstrap` - idempotent droplet setup
+//!
+//! cloud-init installs the toolchain and builds this binary, then hands over
+//! to `bot-runner bootstrap`. Each phase first checks whether its work is
+//! already done, so the command can simply be re-run after a transient
+//! failure (network blip, control plane restart) instead of rebuilding the
+//! droplet. Every phase outcome is reported to the control plane.
+
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use tracing::{error, info, warn};
+
+use crate::client::{ControlPlaneClient, RunnerSecrets, WalletReport};
+use crate::config::Config;
+use crate::wallet;
+
+const CLAW_TRADER_REPO: &str = "https://github.com/janebot2026/claw-trader-cli.git";
+const SERVICE_NAME: &str = "bot-runner";
+
+/// Setup phases, in the order they run
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Phase {
+    /// claw-trader CLI installed
+    Binaries,
+    /// Agent keypair present
+    Wallet,
+    /// Secrets fetched and written for the service
+    Secrets,
+    /// systemd unit installed and enabled
+    Services,
+}
+
+impl Phase {
+    pub const ALL: [Phase; 4] = [
+        Phase::Binaries,
+        Phase::Wallet,
+        Phase::Secrets,
+        Phase::Services,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Phase::Binaries => "binaries",
+            Phase::Wallet => "wallet",
+            Phase::Secrets => "secrets",
+            Phase::Services => "services",
+        }
+    }
+}
+
+impl fmt::Display for Phase {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// What a phase did
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PhaseOutcome {
+    /// Work was done this run
+    Ok(String),
+    /// Already satisfied by an earlier run
+    Skipped(String),
+    /// Can't be completed yet, but doesn't block the rest
+    Pending(String),
+}
+
+impl PhaseOutcome {
+    fn status(&self) -> &'static str {
+        match self {
+            PhaseOutcome::Ok(_) => "ok",
+            PhaseOutcome::Skipped(_) => "skipped",
+            PhaseOutcome::Pending(_) => "pending",
+        }
+    }
+
+    fn detail(&self) -> &str {
+        match self {
+            PhaseOutcome::Ok(d) | PhaseOutcome::Skipped(d) | PhaseOutcome::Pending(d) => d,
+        }
+    }
+}
+
+/// Filesystem layout of a droplet install
+#[derive(Debug, Clone)]
+pub struct BootstrapPaths {
+    pub workspace_dir: PathBuf,
+    pub claw_trader_path: PathBuf,
+    pub claw_trader_config_dir: PathBuf,
+    /// `KEY="value"` file loaded by the systemd unit
+    pub env_file: PathBuf,
+    pub unit_path: PathBuf,
+    pub runner_binary: PathBuf,
+}
+
+impl BootstrapPaths {
+    pub fn from_env() -> Self {
+        let workspace_dir = PathBuf::from(
+            std::env::var("WORKSPACE_DIR").unwrap_or_else(|_| "/opt/trawling-traders".to_string()),
+        );
+        let claw_trader_path = std::env::var("CLAW_TRADER_PATH")
+            .map(PathBuf::from)
+            .unwrap_or_else(|_| PathBuf::from("/usr/local/bin/claw-trader"));
+        let runner_binary =
+            std::env::current_exe().unwrap_or_else(|_| PathBuf::from("/usr/local/bin/bot-runner"));
+
+        Self {
+            claw_trader_config_dir: workspace_dir.join(".config/claw-trader"),
+            env_file: workspace_dir.join(".secrets"),
+            unit_path: PathBuf::from(format!("/etc/systemd/system/{}.service", SERVICE_NAME)),
+            workspace_dir,
+            claw_trader_path,
+            runner_binary,
+        }
+    }
+}
+
+/// Run every phase, stopping at the first failure
+pub async fn run(client: &ControlPlaneClient, config: &Config) -> anyhow::Result<()> {
+    let paths = BootstrapPaths::from_env();
+    info!(
+        "Bootstrapping bot {} in {}",
+        config.bot_id,
+        paths.workspace_dir.display()
+    );
+
+    for phase in Phase::ALL {
+        let result = match phase {
+            Phase::Binaries => ensure_binaries(&paths),
+            Phase::Wallet => ensure_wallet(client, config).await,
+            Phase::Secrets => ensure_secrets(client, config, &paths).await,
+            Phase::Services => ensure_service(config, &paths),
+        };
+
+        match result {
+            Ok(outcome) => {
+                info!("[{}] {}: {}", phase, outcome.status(), outcome.detail());
+                report(client, phase, outcome.status(), outcome.detail()).await;
+            }
+            Err(e) => {
+                error!("[{}] failed: {:#}", phase, e);
+                report(client, phase, "failed", &format!("{:#}", e)).await;
+                return Err(e.context(format!("bootstrap phase '{}' failed", phase)));
+            }
+        }
+    }
+
+    info!("✓ Bootstrap complete");
+    Ok(())
+}
+
+/// Phase reports are best effort; a control plane outage shouldn't fail setup
+async fn report(client: &ControlPlaneClient, phase: Phase, status: &str, detail: &str) {
+    if let Err(e) = client
+        .report_bootstrap_phase(phase.as_str(), status, Some(detail))
+        .await
+    {
+        warn!("Could not report bootstrap phase {}: {}", phase, e);
+    }
+}
+
+fn ensure_binaries(paths: &BootstrapPaths) -> anyhow::Result<PhaseOutcome> {
+    if paths.claw_trader_path.exists() {
+        return Ok(PhaseOutcome::Skipped(format!(
+            "claw-trader present at {}",
+            paths.claw_trader_path.display()
+        )));
+    }
+
+    let src = paths.workspace_dir.join("tools/claw-trader-cli");
+    if !src.join(".git").exists() {
+        run_command(
+            Command::new("git")
+                .arg("clone")
+                .arg(CLAW_TRADER_REPO)
+                .arg(&src),
+        )?;
+    }
+    run_command(
+        Command::new("cargo")
+            .args(["build", "--release"])
+            .current_dir(&src),
+    )?;
+
+    let built = src.join("target/release/jup-cli");
+    std::fs::copy(&built, &paths.claw_trader_path).map_err(|e| {
+        anyhow::anyhow!(
+            "Failed to install {} to {}: {}",
+            built.display(),
+            paths.claw_trader_path.display(),
+            e
+        )
+    })?;
+    Ok(PhaseOutcome::Ok(format!(
+        "built claw-trader into {}",
+        paths.claw_trader_path.display()
+    )))
+}
+
+async fn ensure_wallet(
+    client: &ControlPlaneClient,
+    config: &Config,
+) -> anyhow::Result<PhaseOutcome> {
+    let (wallet, created) = wallet::load_or_create(&config.keypair_path)?;
+    wallet::verify_address(&wallet, Some(&config.wallet_address))?;
+
+    // Report right away so the address is visible before the first start;
+    // the runner reports again on startup if this doesn't get through
+    match client.report_wallet(&wallet.pubkey()).await {
+        Ok(WalletReport::Accepted) => {}
+        Ok(WalletReport::Mismatch(detail)) => anyhow::bail!(
+            "keypair {} does not match the registered wallet: {}",
+            wallet.pubkey(),
+            detail
+        ),
+        Err(e) => warn!("Could not report wallet: {}", e),
+    }
+
+    let detail = format!(
+        "wallet {} at {}",
+        wallet.pubkey(),
+        config.keypair_path.display()
+    );
+    Ok(if created {
+        PhaseOutcome::Ok(format!("generated {}", detail))
+    } else {
+        PhaseOutcome::Skipped(format!("existing {}", detail))
+    })
+}
+
+async fn ensure_secrets(
+    client: &ControlPlaneClient,
+    config: &Config,
+    paths: &BootstrapPaths,
+) -> anyhow::Result<PhaseOutcome> {
+    if paths.env_file.exists() {
+        return Ok(PhaseOutcome::Skipped(format!(
+            "secrets already written to {}",
+            paths.env_file.display()
+        )));
+    }
+
+    let token = config.bootstrap_token.as_deref().ok_or_else(|| {
+        anyhow::anyhow!("BOOTSTRAP_TOKEN not set and no secrets on disk; issue a new token")
+    })?;
+    let secrets = client.fetch_secrets(token).await?;
+
+    // The token is spent now: write everything before anything else can fail
+    if let Some(parent) = paths.env_file.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    write_private(&paths.env_file, &render_env_file(&secrets))?;
+
+    std::fs::create_dir_all(&paths.claw_trader_config_dir)?;
+    write_private(
+        &paths.claw_trader_config_dir.join("config.toml"),
+        &render_claw_trader_config(&secrets.jupiter_api_key),
+    )?;
+
+    Ok(PhaseOutcome::Ok(format!(
+        "secrets written to {}",
+        paths.env_file.display()
+    )))
+}
+
+fn ensure_service(config: &Config, paths: &BootstrapPaths) -> anyhow::Result<PhaseOutcome> {
+    let unit = render_unit(
+        config.bot_id,
+        &config.control_plane_url,
+        &config.keypair_path,
+        paths,
+    );
+    let current = std::fs::read_to_string(&paths.unit_path).ok();
+    let enabled = Command::new("systemctl")
+        .args(["is-enabled", "--quiet", SERVICE_NAME])
+        .status()
+        .map(|s| s.success())
+        .unwrap_or(false);
+
+    if current.as_deref() == Some(unit.as_str()) && enabled {
+        return Ok(PhaseOutcome::Skipped(format!(
+            "{} already enabled",
+            SERVICE_NAME
+        )));
+    }
+
+    std::fs::write(&paths.unit_path, &unit)?;
+    if !Path::new("/var/log/bot-runner.log").exists() {
+        std::fs::write("/var/log/bot-runner.log", "")?;
+    }
+    run_command(Command::new("systemctl").arg("daemon-reload"))?;
+    run_command(Command::new("systemctl").args(["enable", SERVICE_NAME]))?;
+    run_command(Command::new("systemctl").args(["restart", SERVICE_NAME]))?;
+
+    Ok(PhaseOutcome::Ok(format!(
+        "{} installed and started",
+        SERVICE_NAME
+    )))
+}
+
+fn run_command(cmd: &mut Command) -> anyhow::Result<()> {
+    let program = cmd.get_program().to_string_lossy().into_owned();
+    let status = cmd
+        .status()
+        .map_err(|e| anyhow::anyhow!("Failed to run {}: {}", program, e))?;
+    if !status.success() {
+        anyhow::bail!("{} exited with {}", program, status);
+    }
+    Ok(())
+}
+
+/// Create or replace a file readable only by its owner
+fn write_private(path: &Path, contents: &str) -> anyhow::Result<()> {
+    use std::io::Write;
+
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut file = options.open(path)?;
+    file.write_all(contents.as_bytes())?;
+    Ok(())
+}
+
+/// Quote a value for a systemd EnvironmentFile
+fn env_quote(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+fn render_env_file(secrets: &RunnerSecrets) -> String {
+    let vars = [
+        ("JUPITER_API_KEY", secrets.jupiter_api_key.as_str()),
+        ("DATA_RETRIEVAL_URL", secrets.data_retrieval_url.as_str()),
+        ("SOLANA_RPC_URL", secrets.solana_rpc_url.as_str()),
+        (
+            "CONTROL_PLANE_FAILOVER_URLS",
+            secrets.control_plane_failover_urls.as_str(),
+        ),
+        ("LLM_PROVIDER", secrets.llm_provider.as_str()),
+        ("LLM_MODEL", secrets.llm_model.as_str()),
+        ("LLM_API_KEY", secrets.llm_api_key.as_str()),
+        (
+            "TELEGRAM_BOT_TOKEN",
+            secrets.telegram_bot_token.as_deref().unwrap_or_default(),
+        ),
+        (
+            "STATE_ENCRYPTION_KEY",
+            secrets.state_encryption_key.as_deref().unwrap_or_default(),
+        ),
+    ];
+    vars.iter()
+        .map(|(key, value)| format!("{}={}\n", key, env_quote(value)))
+        .collect()
+}
+
+fn render_claw_trader_config(jupiter_api_key: &str) -> String {
+    format!(
+        r#"[api]
+ultra_base_url = "https://api.jup.ag/ultra/v1"
+api_key = "{}"
+
+[trading]
+default_slippage_bps = 50
+max_slippage_bps = 100
+confirmation_commitment = "confirmed"
+paper_trading_default = true
+
+[agent]
+enabled = true
+auto_approve = false
+"#,
+        jupiter_api_key.replace('\\', "\\\\").replace('"', "\\\"")
+    )
+}
+
+fn render_unit(
+    bot_id: uuid::Uuid,
+    control_plane_url: &str,
+    keypair_path: &Path,
+    paths: &BootstrapPaths,
+) -> String {
+    format!(
+        r#"[Unit]
+Description=Trawling Traders Bot Runner
+After=network.target
+
+[Service]
+Type=simple
+User=root
+WorkingDirectory={workspace}
+EnvironmentFile={env_file}
+Environment="BOT_ID={bot_id}"
+Environment="CONTROL_PLANE_URL={control_plane}"
+Environment="RUST_LOG=info"
+Environment="CLAW_TRADER_PATH={claw_trader}"
+Environment="CLAW_TRADER_CONFIG={claw_config}"
+Environment="AGENT_WALLET_PATH={keypair}"
+ExecStart={runner}
+Restart=always
+RestartSec=10
+StandardOutput=append:/var/log/bot-runner.log
+StandardError=append:/var/log/bot-runner.log
+
+[Install]
+WantedBy=multi-user.target
+"#,
+        workspace = paths.workspace_dir.display(),
+        env_file = paths.env_file.display(),
+        bot_id = bot_id,
+        control_plane = control_plane_url,
+        claw_trader = paths.claw_trader_path.display(),
+        claw_config = paths.claw_trader_config_dir.display(),
+        keypair = keypair_path.display(),
+        runner = paths.runner_binary.display(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_env_file_quotes_values() {
+        let secrets = RunnerSecrets {
+            jupiter_api_key: "k\"ey".to_string(),
+            solana_rpc_url: "https://rpc.example".to_string(),
+            ..Default::default()
+        };
+        let env = render_env_file(&secrets);
+        assert!(env.contains("JUPITER_API_KEY=\"k\\\"ey\"\n"));
+        assert!(env.contains("SOLANA_RPC_URL=\"https://rpc.example\"\n"));
+        assert!(env.contains("TELEGRAM_BOT_TOKEN=\"\"\n"));
+    }
+
+    #[test]
+    fn test_unit_is_stable_across_runs() {
+        let bot_id = uuid::Uuid::new_v4();
+        let keypair = Path::new("/opt/tt/.config/solana/id.json");
+        let paths = BootstrapPaths {
+            workspace_dir: PathBuf::from("/opt/tt"),
+            claw_trader_path: PathBuf::from("/usr/local/bin/claw-trader"),
+            claw_trader_config_dir: PathBuf::from("/opt/tt/.config/claw-trader"),
+            env_file: PathBuf::from("/opt/tt/.secrets"),
+            unit_path: PathBuf::from("/tmp/bot-runner.service"),
+            runner_binary: PathBuf::from("/usr/local/bin/bot-runner"),
+        };
+
+        // Re-runs compare against the installed unit, so rendering must be deterministic
+        let render = || render_unit(bot_id, "https://cp.example", keypair, &paths);
+        let unit = render();
+        assert_eq!(unit, render());
+        assert!(unit.contains("EnvironmentFile=/opt/tt/.secrets"));
+        assert!(unit.contains(&format!("Environment=\"BOT_ID={}\"", bot_id)));
+    }
+}
@@ -1,12 +1,17 @@
 //! Control Plane API Client
 
+use reqwest::header::{ACCEPT_ENCODING, CONTENT_ENCODING, CONTENT_TYPE, ETAG, IF_NONE_MATCH};
 use reqwest::{Client, Response, StatusCode};
-use serde::{Deserialize, Serialize};
-use std::time::Duration;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::io::{Read, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 use tracing::{debug, info, warn};
 use uuid::Uuid;
 
 use crate::config::BotConfig;
+use crate::handover::StateBundle;
 
 /// Maximum retry attempts for transient failures
 const MAX_RETRIES: u32 = 3;
@@ -14,49 +19,505 @@ const MAX_RETRIES: u32 = 3;
 /// Base delay for exponential backoff (doubles each retry)
 const BASE_DELAY_MS: u64 = 1000;
 
+/// Request timeout on the priority lane
+const PRIORITY_TIMEOUT_SECS: u64 = 5;
+
+/// Event types delivered immediately on the priority lane instead of waiting
+/// for the next sync (must match the control plane's `PRIORITY_EVENT_TYPES`)
+pub const CRITICAL_EVENT_TYPES: &[&str] = &[
+    "bot_shutdown",
+    "trading_halted",
+    "daily_loss_breached",
+    "drawdown_breached",
+];
+
+/// Whether an event type goes out on the priority lane
+pub fn is_critical(event_type: &str) -> bool {
+    CRITICAL_EVENT_TYPES.contains(&event_type)
+}
+
+/// Attempts, backoff and pacing for one kind of request
+#[derive(Debug, Clone, Copy)]
+struct RetryPolicy {
+    max_retries: u32,
+    /// Base delay for exponential backoff (doubles each retry)
+    base_delay_ms: u64,
+    /// Wait out rate limit hints from the bot routes
+    paced: bool,
+}
+
+/// Routine requests on the bot routes
+const DEFAULT_RETRY: RetryPolicy = RetryPolicy {
+    max_retries: MAX_RETRIES,
+    base_delay_ms: BASE_DELAY_MS,
+    paced: true,
+};
+
+/// Critical events: more, faster retries, and the priority route isn't rate
+/// limited so there's nothing to pace against
+const PRIORITY_RETRY: RetryPolicy = RetryPolicy {
+    max_retries: 5,
+    base_delay_ms: 200,
+    paced: false,
+};
+
+/// Longest we'll hold off on a rate limit hint from the control plane
+const MAX_RATE_LIMIT_WAIT_SECS: u64 = 120;
+
+/// Start pacing requests once this fraction (1/N) of the window is left
+const RATE_LIMIT_PACING_FRACTION: u64 = 10;
+
+/// Consecutive unreachable or 5xx answers from a region before failing over
+const FAILOVER_AFTER_FAILURES: u32 = 2;
+
+/// How long to stay on a failover region before checking the primary again
+const PRIMARY_RECHECK_SECS: u64 = 60;
+
+/// Response encodings we can decode, advertised on compressed routes
+const ACCEPTED_ENCODINGS: &str = "zstd, gzip";
+
+/// Request body compression for sync payloads
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Compression {
+    None,
+    #[default]
+    Gzip,
+    Zstd,
+}
+
+impl Compression {
+    /// Parse from CONTROL_PLANE_COMPRESSION value (none | gzip | zstd)
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "none" | "off" | "identity" => Some(Self::None),
+            "gzip" => Some(Self::Gzip),
+            "zstd" => Some(Self::Zstd),
+            _ => None,
+        }
+    }
+
+    /// Content-Encoding header value, if any
+    pub fn content_encoding(&self) -> Option<&'static str> {
+        match self {
+            Self::None => None,
+            Self::Gzip => Some("gzip"),
+            Self::Zstd => Some("zstd"),
+        }
+    }
+
+    /// Compress a request body
+    pub fn encode(&self, body: &[u8]) -> anyhow::Result<Vec<u8>> {
+        match self {
+            Self::None => Ok(body.to_vec()),
+            Self::Gzip => {
+                let mut encoder =
+                    flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+                encoder.write_all(body)?;
+                Ok(encoder.finish()?)
+            }
+            Self::Zstd => Ok(zstd::encode_all(body, 0)?),
+        }
+    }
+}
+
+/// Decode a response body according to its Content-Encoding
+fn decode_body(content_encoding: Option<&str>, body: &[u8]) -> anyhow::Result<Vec<u8>> {
+    match content_encoding.map(|e| e.trim().to_ascii_lowercase()) {
+        None => Ok(body.to_vec()),
+        Some(e) if e == "identity" => Ok(body.to_vec()),
+        Some(e) if e == "gzip" => {
+            let mut decoded = Vec::new();
+            flate2::read::GzDecoder::new(body).read_to_end(&mut decoded)?;
+            Ok(decoded)
+        }
+        Some(e) if e == "zstd" => Ok(zstd::decode_all(body)?),
+        Some(other) => Err(anyhow::anyhow!("Unsupported response encoding: {}", other)),
+    }
+}
+
+/// Control plane regions, and which one requests go to
+///
+/// The primary comes first. After [`FAILOVER_AFTER_FAILURES`] consecutive
+/// failures the next region takes over, wrapping back around to the primary.
+/// Requests stay on a failover region rather than drifting back the moment
+/// the primary answers once; every [`PRIMARY_RECHECK_SECS`] its health check
+/// is tried, and only a healthy primary takes requests back.
+#[derive(Debug)]
+struct Endpoints {
+    urls: Vec<String>,
+    active: usize,
+    consecutive_failures: u32,
+    /// Since the primary was last left or last failed its health check
+    off_primary_since: Option<Instant>,
+}
+
+impl Endpoints {
+    fn new(primary: &str) -> Self {
+        Self {
+            urls: vec![primary.trim_end_matches('/').to_string()],
+            active: 0,
+            consecutive_failures: 0,
+            off_primary_since: None,
+        }
+    }
+
+    /// Add a failover region after the ones already known
+    fn add(&mut self, url: &str) {
+        let url = url.trim().trim_end_matches('/');
+        if !url.is_empty() && !self.urls.iter().any(|u| u == url) {
+            self.urls.push(url.to_string());
+        }
+    }
+
+    fn active(&self) -> &str {
+        &self.urls[self.active]
+    }
+
+    fn primary(&self) -> &str {
+        &self.urls[0]
+    }
+
+    fn record_success(&mut self) {
+        self.consecutive_failures = 0;
+    }
+
+    /// Count a failure against the active region; the region taking over, if
+    /// this one has now failed too often
+    fn record_failure(&mut self, now: Instant) -> Option<&str> {
+        if self.urls.len() < 2 {
+            return None;
+        }
+        self.consecutive_failures += 1;
+        if self.consecutive_failures < FAILOVER_AFTER_FAILURES {
+            return None;
+        }
+        self.consecutive_failures = 0;
+        if self.active == 0 {
+            self.off_primary_since = Some(now);
+        }
+        self.active = (self.active + 1) % self.urls.len();
+        if self.active == 0 {
+            self.off_primary_since = None;
+        }
+        Some(self.active())
+    }
+
+    /// Whether it's time to see if the primary is back
+    fn primary_recheck_due(&self, now: Instant) -> bool {
+        self.off_primary_since.is_some_and(|since| {
+            now.saturating_duration_since(since) >= Duration::from_secs(PRIMARY_RECHECK_SECS)
+        })
+    }
+
+    /// Hold off the next primary check for another interval
+    fn defer_primary_recheck(&mut self, now: Instant) {
+        if self.active != 0 {
+            self.off_primary_since = Some(now);
+        }
+    }
+
+    fn restore_primary(&mut self) {
+        self.active = 0;
+        self.consecutive_failures = 0;
+        self.off_primary_since = None;
+    }
+}
+
 /// Client for communicating with the control plane
 pub struct ControlPlaneClient {
     client: Client,
-    base_url: String,
+    /// Short-timeout client for the priority lane
+    priority_client: Client,
+    /// Primary control plane and the regions to fail over to
+    endpoints: Mutex<Endpoints>,
     bot_id: Uuid,
+    /// Preferred request compression for sync payloads
+    compression: Compression,
+    /// Set once the control plane rejects compressed bodies (415)
+    compression_rejected: AtomicBool,
+    /// ETag of the last config received, sent as If-None-Match
+    config_etag: Mutex<Option<String>>,
+    /// No requests before this, from the control plane's rate limit headers
+    throttled_until: Mutex<Option<Instant>>,
 }
 
 impl ControlPlaneClient {
     /// Create new control plane client
     pub fn new(base_url: &str, bot_id: Uuid) -> anyhow::Result<Self> {
         let client = Client::builder().timeout(Duration::from_secs(30)).build()?;
+        let priority_client = Client::builder()
+            .timeout(Duration::from_secs(PRIORITY_TIMEOUT_SECS))
+            .build()?;
 
         Ok(Self {
             client,
-            base_url: base_url.trim_end_matches('/').to_string(),
+            priority_client,
+            endpoints: Mutex::new(Endpoints::new(base_url)),
             bot_id,
+            compression: Compression::default(),
+            compression_rejected: AtomicBool::new(false),
+            config_etag: Mutex::new(None),
+            throttled_until: Mutex::new(None),
         })
     }
 
+    /// Set request compression for sync payloads
+    pub fn with_compression(mut self, compression: Compression) -> Self {
+        self.compression = compression;
+        self
+    }
+
+    /// Add control planes in other regions to fail over to, in order
+    pub fn with_failover(self, urls: &[String]) -> Self {
+        self.add_failover(urls);
+        self
+    }
+
+    /// [`with_failover`](Self::with_failover) for a client already in use,
+    /// once the regions arrive with the runner's secrets
+    pub fn add_failover(&self, urls: &[String]) {
+        let mut endpoints = self.endpoints.lock().unwrap();
+        for url in urls {
+            endpoints.add(url);
+        }
+    }
+
+    /// Control plane requests are currently going to
+    pub fn active_url(&self) -> String {
+        self.endpoints.lock().unwrap().active().to_string()
+    }
+
+    /// Base URL for the next request
+    ///
+    /// While on a failover region, checks now and then whether the primary is
+    /// healthy again and moves back to it if so.
+    async fn base_url(&self) -> String {
+        let (active, primary) = {
+            let mut endpoints = self.endpoints.lock().unwrap();
+            let now = Instant::now();
+            if !endpoints.primary_recheck_due(now) {
+                return endpoints.active().to_string();
+            }
+            // Claim this check so concurrent requests don't all probe
+            endpoints.defer_primary_recheck(now);
+            (
+                endpoints.active().to_string(),
+                endpoints.primary().to_string(),
+            )
+        };
+
+        let url = format!("{}/v1/healthz", primary);
+        match self.priority_client.get(&url).send().await {
+            Ok(resp) if resp.status().is_success() => {
+                info!(
+                    "Primary control plane {} is healthy again, moving back from {}",
+                    primary, active
+                );
+                self.endpoints.lock().unwrap().restore_primary();
+                primary
+            }
+            _ => {
+                debug!(
+                    "Primary control plane {} still down, staying on {}",
+                    primary, active
+                );
+                active
+            }
+        }
+    }
+
+    /// Count a failed request against `base`, failing over if it's had enough
+    fn record_failure(&self, base: &str) {
+        let mut endpoints = self.endpoints.lock().unwrap();
+        // Another request may already have moved on
+        if endpoints.active() != base {
+            return;
+        }
+        if let Some(next) = endpoints.record_failure(Instant::now()) {
+            warn!(
+                "Control plane {} unreachable, failing over to {}",
+                base, next
+            );
+        }
+    }
+
+    fn record_success(&self, base: &str) {
+        let mut endpoints = self.endpoints.lock().unwrap();
+        if endpoints.active() == base {
+            endpoints.record_success();
+        }
+    }
+
+    /// URL of a bot route on `base`
+    fn bot_url(&self, base: &str, path: &str) -> String {
+        format!("{}/v1/bot/{}/{}", base, self.bot_id, path)
+    }
+
+    /// Compression to use for the next request (falls back to none once rejected)
+    fn effective_compression(&self) -> Compression {
+        if self.compression_rejected.load(Ordering::Relaxed) {
+            Compression::None
+        } else {
+            self.compression
+        }
+    }
+
+    /// POST a JSON payload with compression negotiation
+    ///
+    /// Compresses the body with the configured encoding and advertises the
+    /// encodings we can decode. If the control plane answers 415 Unsupported
+    /// Media Type, compression is disabled for this client and the request is
+    /// re-sent uncompressed.
+    async fn post_compressed<T: Serialize>(
+        &self,
+        operation: &str,
+        path: &str,
+        payload: &T,
+    ) -> anyhow::Result<Response> {
+        let json = serde_json::to_vec(payload)?;
+
+        let mut compression = self.effective_compression();
+        loop {
+            let body = compression.encode(&json)?;
+            debug!(
+                "{} payload: {} bytes raw, {} bytes on wire ({:?})",
+                operation,
+                json.len(),
+                body.len(),
+                compression
+            );
+
+            let response = self
+                .with_retry(operation, |base| {
+                    let mut request = self
+                        .client
+                        .post(self.bot_url(base, path))
+                        .header(CONTENT_TYPE, "application/json")
+                        .header(ACCEPT_ENCODING, ACCEPTED_ENCODINGS)
+                        .body(body.clone());
+                    if let Some(encoding) = compression.content_encoding() {
+                        request = request.header(CONTENT_ENCODING, encoding);
+                    }
+                    request.send()
+                })
+                .await?;
+
+            if response.status() == StatusCode::UNSUPPORTED_MEDIA_TYPE
+                && compression != Compression::None
+            {
+                warn!(
+                    "Control plane rejected {:?} request body, falling back to uncompressed",
+                    compression
+                );
+                self.compression_rejected.store(true, Ordering::Relaxed);
+                compression = Compression::None;
+                continue;
+            }
+
+            return Ok(response);
+        }
+    }
+
+    /// Read a (possibly compressed) JSON response body
+    async fn read_json<T: DeserializeOwned>(response: Response) -> anyhow::Result<T> {
+        let encoding = response
+            .headers()
+            .get(CONTENT_ENCODING)
+            .and_then(|v| v.to_str().ok())
+            .map(str::to_string);
+        let wire = response.bytes().await?;
+        let decoded = decode_body(encoding.as_deref(), &wire)?;
+        if encoding.is_some() {
+            debug!(
+                "Response body: {} bytes on wire, {} bytes decoded",
+                wire.len(),
+                decoded.len()
+            );
+        }
+        Ok(serde_json::from_slice(&decoded)?)
+    }
+
+    /// Record the rate limit hint from a response
+    fn note_rate_limit(&self, response: &Response) {
+        let now_unix = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        let hint = rate_limit_delay(response.headers(), now_unix);
+        if let Some(delay) = hint {
+            debug!("Control plane rate limit: holding off {:?}", delay);
+        }
+        *self.throttled_until.lock().unwrap() = hint.map(|delay| Instant::now() + delay);
+    }
+
+    /// Time left before the control plane's rate limit allows another request
+    fn throttle_remaining(&self) -> Duration {
+        self.throttled_until
+            .lock()
+            .unwrap()
+            .map(|until| until.saturating_duration_since(Instant::now()))
+            .unwrap_or_default()
+    }
+
     /// Execute request with retry logic for transient failures
     ///
     /// Retries up to MAX_RETRIES times with exponential backoff.
     /// Does NOT retry on 4xx client errors (except 429 Too Many Requests).
+    /// Rate limit headers stretch the wait: a 429 waits out `Retry-After`,
+    /// and requests are spaced out while the window is nearly used up.
+    /// `make_request` is given the base URL of the region to use, which
+    /// changes between attempts if the active one fails over.
     async fn with_retry<F, Fut>(&self, operation: &str, make_request: F) -> anyhow::Result<Response>
     where
-        F: Fn() -> Fut,
+        F: Fn(&str) -> Fut,
+        Fut: std::future::Future<Output = Result<Response, reqwest::Error>>,
+    {
+        self.with_retry_policy(operation, DEFAULT_RETRY, make_request)
+            .await
+    }
+
+    /// [`with_retry`](Self::with_retry) under a given [`RetryPolicy`]
+    async fn with_retry_policy<F, Fut>(
+        &self,
+        operation: &str,
+        policy: RetryPolicy,
+        make_request: F,
+    ) -> anyhow::Result<Response>
+    where
+        F: Fn(&str) -> Fut,
         Fut: std::future::Future<Output = Result<Response, reqwest::Error>>,
     {
         let mut last_error = None;
 
-        for attempt in 0..=MAX_RETRIES {
+        for attempt in 0..=policy.max_retries {
+            let throttle = if policy.paced {
+                self.throttle_remaining()
+            } else {
+                Duration::ZERO
+            };
             if attempt > 0 {
-                let delay = Duration::from_millis(BASE_DELAY_MS * (1 << (attempt - 1)));
+                let delay = Duration::from_millis(policy.base_delay_ms * (1 << (attempt - 1)))
+                    .max(throttle);
                 warn!(
                     "{} failed (attempt {}/{}), retrying in {:?}",
-                    operation, attempt, MAX_RETRIES, delay
+                    operation, attempt, policy.max_retries, delay
                 );
                 tokio::time::sleep(delay).await;
+            } else if !throttle.is_zero() {
+                debug!("{} waiting {:?} for rate limit", operation, throttle);
+                tokio::time::sleep(throttle).await;
             }
 
-            match make_request().await {
+            let base = self.base_url().await;
+            match make_request(&base).await {
                 Ok(response) => {
+                    self.note_rate_limit(&response);
                     let status = response.status();
+                    if status.is_server_error() {
+                        self.record_failure(&base);
+                    } else {
+                        self.record_success(&base);
+                    }
                     // Don't retry on client errors (4xx) except 429
                     if status.is_client_error() && status != StatusCode::TOO_MANY_REQUESTS {
                         return Ok(response);
@@ -70,13 +531,14 @@ impl ControlPlaneClient {
                 }
                 Err(e) => {
                     // Network errors are retryable
+                    self.record_failure(&base);
                     last_error = Some(anyhow::anyhow!("{} network error: {}", operation, e));
                 }
             }
         }
 
         Err(last_error.unwrap_or_else(|| {
-            anyhow::anyhow!("{} failed after {} retries", operation, MAX_RETRIES)
+            anyhow::anyhow!("{} failed after {} retries", operation, policy.max_retries)
         }))
     }
 
@@ -85,14 +547,17 @@ impl ControlPlaneClient {
         &self,
         wallet_address: Option<String>,
     ) -> anyhow::Result<RegistrationResponse> {
-        let url = format!("{}/v1/bot/{}/register", self.base_url, self.bot_id);
-
         let req = RegisterRequest {
             agent_wallet: wallet_address.unwrap_or_default(),
         };
 
         let response = self
-            .with_retry("register", || self.client.post(&url).json(&req).send())
+            .with_retry("register", |base| {
+                self.client
+                    .post(self.bot_url(base, "register"))
+                    .json(&req)
+                    .send()
+            })
             .await?;
 
         if response.status().is_success() {
@@ -110,21 +575,118 @@ impl ControlPlaneClient {
         }
     }
 
-    /// Report wallet address to control plane (for post-registration update)
-    pub async fn report_wallet(&self, wallet_address: &str) -> anyhow::Result<()> {
-        let url = format!("{}/v1/bot/{}/wallet", self.base_url, self.bot_id);
+    /// Block until a control plane answers its health check, or `wait` elapses
+    ///
+    /// Moves on to the failover regions, if any, while the primary is down.
+    pub async fn wait_until_ready(&self, wait: Duration) -> anyhow::Result<()> {
+        let deadline = tokio::time::Instant::now() + wait;
+        let mut delay = Duration::from_millis(500);
+        loop {
+            let base = self.base_url().await;
+            let url = format!("{}/v1/healthz", base);
+            let err = match self.client.get(&url).send().await {
+                Ok(resp) if resp.status().is_success() => {
+                    self.record_success(&base);
+                    return Ok(());
+                }
+                Ok(resp) => format!("status {}", resp.status()),
+                Err(e) => e.to_string(),
+            };
+            self.record_failure(&base);
+            if tokio::time::Instant::now() + delay >= deadline {
+                return Err(anyhow::anyhow!("Control plane not ready: {}", err));
+            }
+            warn!("Control plane not ready ({}), retrying in {:?}", err, delay);
+            tokio::time::sleep(delay).await;
+            delay = (delay * 2).min(Duration::from_secs(5));
+        }
+    }
+
+    /// Exchange a one-time bootstrap token for deployment secrets
+    pub async fn fetch_secrets(&self, bootstrap_token: &str) -> anyhow::Result<RunnerSecrets> {
+        let url = self.bot_url(&self.base_url().await, "secrets");
+
+        let req = SecretsRequest {
+            bootstrap_token: bootstrap_token.to_string(),
+        };
+
+        // No retry: the token is single-use, and a retried success would fail
+        let response = self.client.post(&url).json(&req).send().await?;
+
+        if response.status().is_success() {
+            Ok(response.json().await?)
+        } else {
+            let status = response.status();
+            let text = response.text().await.unwrap_or_default();
+            Err(anyhow::anyhow!(
+                "Secrets retrieval failed: {} - {}",
+                status,
+                text
+            ))
+        }
+    }
+
+    /// Report the outcome of a bootstrap phase
+    pub async fn report_bootstrap_phase(
+        &self,
+        phase: &str,
+        status: &str,
+        detail: Option<&str>,
+    ) -> anyhow::Result<()> {
+        let req = serde_json::json!({
+            "phase": phase,
+            "status": status,
+            "detail": detail,
+        });
 
+        let response = self
+            .with_retry("report_bootstrap_phase", |base| {
+                self.client
+                    .post(self.bot_url(base, "bootstrap"))
+                    .json(&req)
+                    .send()
+            })
+            .await?;
+
+        if response.status().is_success() {
+            Ok(())
+        } else {
+            let status = response.status();
+            let text = response.text().await.unwrap_or_default();
+            Err(anyhow::anyhow!(
+                "Bootstrap report failed: {} - {}",
+                status,
+                text
+            ))
+        }
+    }
+
+    /// Report wallet address to control plane (for post-registration update)
+    ///
+    /// A bot already registered to another address comes back as
+    /// `WalletReport::Mismatch` rather than an error, so callers can tell a
+    /// wrong keypair from a control plane outage.
+    pub async fn report_wallet(&self, wallet_address: &str) -> anyhow::Result<WalletReport> {
         let req = WalletReportRequest {
             wallet_address: wallet_address.to_string(),
         };
 
         let response = self
-            .with_retry("report_wallet", || self.client.post(&url).json(&req).send())
+            .with_retry("report_wallet", |base| {
+                self.client
+                    .post(self.bot_url(base, "wallet"))
+                    .json(&req)
+                    .send()
+            })
             .await?;
 
         if response.status().is_success() {
             info!("Wallet address reported: {}", wallet_address);
-            Ok(())
+            Ok(WalletReport::Accepted)
+        } else if response.status() == StatusCode::CONFLICT {
+            Ok(WalletReport::Mismatch(
+                response.text().await.unwrap_or_default(),
+            ))
         } else {
             let status = response.status();
             let text = response.text().await.unwrap_or_default();
@@ -137,18 +699,33 @@ impl ControlPlaneClient {
     }
 
     /// Poll for config updates
+    ///
+    /// Sends the last seen ETag as If-None-Match, so an unchanged config comes
+    /// back as 304 (`Ok(None)`) without the control plane rebuilding it.
     pub async fn get_config(&self) -> anyhow::Result<Option<BotConfig>> {
-        let url = format!("{}/v1/bot/{}/config", self.base_url, self.bot_id);
+        debug!("Polling config from {}", self.active_url());
 
-        debug!("Polling config from {}", url);
+        let etag = self.config_etag.lock().unwrap().clone();
 
         let response = self
-            .with_retry("get_config", || self.client.get(&url).send())
+            .with_retry("get_config", |base| {
+                let mut request = self.client.get(self.bot_url(base, "config"));
+                if let Some(etag) = &etag {
+                    request = request.header(IF_NONE_MATCH, etag);
+                }
+                request.send()
+            })
             .await?;
 
         match response.status() {
             StatusCode::OK => {
+                let new_etag = response
+                    .headers()
+                    .get(ETAG)
+                    .and_then(|v| v.to_str().ok())
+                    .map(str::to_string);
                 let config: BotConfigResponse = response.json().await?;
+                *self.config_etag.lock().unwrap() = new_etag;
                 debug!("Received config version: {}", config.version);
                 Ok(Some(BotConfig::from_response(config)?))
             }
@@ -164,10 +741,13 @@ impl ControlPlaneClient {
         }
     }
 
+    /// Forget the cached config ETag so the next poll fetches the full config
+    pub fn reset_config_etag(&self) {
+        *self.config_etag.lock().unwrap() = None;
+    }
+
     /// Acknowledge config version
     pub async fn ack_config(&self, version_id: Uuid) -> anyhow::Result<()> {
-        let url = format!("{}/v1/bot/{}/config_ack", self.base_url, self.bot_id);
-
         let req = ConfigAckRequest {
             version: format!("v{}", version_id),
             hash: version_id.to_string(),
@@ -175,7 +755,12 @@ impl ControlPlaneClient {
         };
 
         let response = self
-            .with_retry("ack_config", || self.client.post(&url).json(&req).send())
+            .with_retry("ack_config", |base| {
+                self.client
+                    .post(self.bot_url(base, "config_ack"))
+                    .json(&req)
+                    .send()
+            })
             .await?;
 
         if response.status().is_success() {
@@ -194,8 +779,6 @@ impl ControlPlaneClient {
         status: &str,
         metrics: Option<Vec<MetricInput>>,
     ) -> anyhow::Result<HeartbeatResponse> {
-        let url = format!("{}/v1/bot/{}/heartbeat", self.base_url, self.bot_id);
-
         let req = HeartbeatRequest {
             status: status.to_string(),
             timestamp: chrono::Utc::now(),
@@ -203,7 +786,12 @@ impl ControlPlaneClient {
         };
 
         let response = self
-            .with_retry("heartbeat", || self.client.post(&url).json(&req).send())
+            .with_retry("heartbeat", |base| {
+                self.client
+                    .post(self.bot_url(base, "heartbeat"))
+                    .json(&req)
+                    .send()
+            })
             .await?;
 
         if response.status().is_success() {
@@ -216,14 +804,118 @@ impl ControlPlaneClient {
         }
     }
 
+    /// Consolidated sync: heartbeat, metrics, events and state summary in one call
+    pub async fn sync(&self, req: &SyncRequest) -> anyhow::Result<SyncResponse> {
+        let response = self.post_compressed("sync", "sync", req).await?;
+
+        if response.status().is_success() {
+            let resp: SyncResponse = Self::read_json(response).await?;
+            Ok(resp)
+        } else {
+            let status = response.status();
+            let text = response.text().await.unwrap_or_default();
+            Err(anyhow::anyhow!("Sync failed: {} - {}", status, text))
+        }
+    }
+
+    /// Upload this runner's state for the droplet replacing it
+    pub async fn upload_state(&self, bundle: &StateBundle) -> anyhow::Result<()> {
+        let response = self
+            .with_retry("upload_state", |base| {
+                self.client
+                    .put(self.bot_url(base, "state"))
+                    .json(bundle)
+                    .send()
+            })
+            .await?;
+
+        if response.status().is_success() {
+            Ok(())
+        } else {
+            let status = response.status();
+            let text = response.text().await.unwrap_or_default();
+            Err(anyhow::anyhow!(
+                "State upload failed: {} - {}",
+                status,
+                text
+            ))
+        }
+    }
+
+    /// Upload a diagnostics bundle requested with `upload_diagnostics`
+    pub async fn upload_diagnostics(&self, bundle: &serde_json::Value) -> anyhow::Result<()> {
+        let response = self
+            .with_retry("upload_diagnostics", |base| {
+                self.client
+                    .put(self.bot_url(base, "artifacts/diagnostics"))
+                    .json(bundle)
+                    .send()
+            })
+            .await?;
+
+        if response.status().is_success() {
+            Ok(())
+        } else {
+            let status = response.status();
+            let text = response.text().await.unwrap_or_default();
+            Err(anyhow::anyhow!(
+                "Diagnostics upload failed: {} - {}",
+                status,
+                text
+            ))
+        }
+    }
+
+    /// State left by this bot's previous droplet, if any is waiting
+    pub async fn fetch_state(&self) -> anyhow::Result<Option<StateBundle>> {
+        let response = self
+            .with_retry("fetch_state", |base| {
+                self.client.get(self.bot_url(base, "state")).send()
+            })
+            .await?;
+
+        match response.status() {
+            StatusCode::OK => Ok(Some(Self::read_json(response).await?)),
+            StatusCode::NOT_FOUND => Ok(None),
+            status => {
+                let text = response.text().await.unwrap_or_default();
+                Err(anyhow::anyhow!("State fetch failed: {} - {}", status, text))
+            }
+        }
+    }
+
     /// Send events
     pub async fn send_events(&self, events: Vec<EventInput>) -> anyhow::Result<()> {
-        let url = format!("{}/v1/bot/{}/events", self.base_url, self.bot_id);
-
         let req = EventsBatchRequest { events };
 
+        let response = self.post_compressed("send_events", "events", &req).await?;
+
+        if response.status().is_success() {
+            Ok(())
+        } else {
+            let status = response.status();
+            let text = response.text().await.unwrap_or_default();
+            Err(anyhow::anyhow!("Events send failed: {} - {}", status, text))
+        }
+    }
+
+    /// Send critical events on the priority lane
+    ///
+    /// Goes straight to the control plane's priority route, uncompressed,
+    /// with a short timeout and more, faster retries than the sync path, so
+    /// a shutdown or halt isn't stuck behind routine batches.
+    pub async fn send_priority_events(&self, events: &[EventInput]) -> anyhow::Result<()> {
+        let req = EventsBatchRequest {
+            events: events.to_vec(),
+        };
+
         let response = self
-            .with_retry("send_events", || self.client.post(&url).json(&req).send())
+            .with_retry_policy("send_priority_events", PRIORITY_RETRY, |base| {
+                self.priority_client
+                    .post(self.bot_url(base, "events/priority"))
+                    .json(&req)
+                    .send()
+            })
             .await?;
 
         if response.status().is_success() {
@@ -231,7 +923,11 @@ impl ControlPlaneClient {
         } else {
             let status = response.status();
             let text = response.text().await.unwrap_or_default();
-            Err(anyhow::anyhow!("Events send failed: {} - {}", status, text))
+            Err(anyhow::anyhow!(
+                "Priority events send failed: {} - {}",
+                status,
+                text
+            ))
         }
     }
 }
@@ -248,6 +944,36 @@ struct WalletReportRequest {
     wallet_address: String,
 }
 
+#[derive(Debug, Clone, Serialize)]
+struct SecretsRequest {
+    bootstrap_token: String,
+}
+
+/// Deployment secrets from POST /bot/:id/secrets
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RunnerSecrets {
+    #[serde(default)]
+    pub jupiter_api_key: String,
+    #[serde(default)]
+    pub data_retrieval_url: String,
+    #[serde(default)]
+    pub solana_rpc_url: String,
+    /// Comma-separated control planes to fail over to
+    #[serde(default)]
+    pub control_plane_failover_urls: String,
+    #[serde(default)]
+    pub llm_provider: String,
+    #[serde(default)]
+    pub llm_model: String,
+    #[serde(default)]
+    pub llm_api_key: String,
+    #[serde(default)]
+    pub telegram_bot_token: Option<String>,
+    /// Hex key for encrypting the state directory (bots that opted in)
+    #[serde(default)]
+    pub state_encryption_key: Option<String>,
+}
+
 #[derive(Debug, Deserialize)]
 pub struct RegistrationResponse {
     pub bot_id: String,
@@ -255,6 +981,14 @@ pub struct RegistrationResponse {
     pub config_url: String,
 }
 
+/// Control plane's answer to a wallet report
+#[derive(Debug, Clone, PartialEq)]
+pub enum WalletReport {
+    Accepted,
+    /// The bot is registered to a different address (server message)
+    Mismatch(String),
+}
+
 #[derive(Debug, Deserialize)]
 pub struct BotConfigResponse {
     pub version_id: String,
@@ -281,12 +1015,19 @@ pub struct MetricInput {
     pub timestamp: chrono::DateTime<chrono::Utc>,
     pub equity: rust_decimal::Decimal,
     pub pnl: rust_decimal::Decimal,
+    /// Swap and network fees paid over the portfolio's lifetime
+    pub fees_usd: rust_decimal::Decimal,
+    /// Win rate, Sharpe and drawdown over the trailing window
+    pub performance: crate::performance::PerformanceStats,
 }
 
 #[derive(Debug, Deserialize)]
 pub struct HeartbeatResponse {
     pub needs_config_update: bool,
     pub message: String,
+    /// `paused` or `online` (absent from older control planes)
+    #[serde(default)]
+    pub desired_status: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -294,10 +1035,289 @@ struct EventsBatchRequest {
     events: Vec<EventInput>,
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct EventInput {
     pub event_type: String,
     pub message: String,
     pub metadata: Option<serde_json::Value>,
     pub timestamp: chrono::DateTime<chrono::Utc>,
 }
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SyncRequest {
+    pub status: String,
+    pub timestamp: chrono::DateTime<chrono::Utc>,
+    pub metrics: Vec<MetricInput>,
+    pub events: Vec<EventInput>,
+    /// Hash-chained decision journal entries since the last sync
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub journal: Vec<crate::journal::ChainedJournalEntry>,
+    pub state: Option<SyncStateSummary>,
+    /// LLM requests and tokens since the last sync
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub llm_usage: Option<LlmUsageReport>,
+    /// Fresh per sync and kept across its retries, so a region that takes
+    /// over mid-sync doesn't store it twice
+    pub sync_id: Uuid,
+}
+
+/// LLM usage accumulated between syncs
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct LlmUsageReport {
+    pub requests: u64,
+    pub prompt_tokens: u64,
+    pub completion_tokens: u64,
+    /// Model of the most recent plan that named one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+}
+
+impl LlmUsageReport {
+    /// Count one decision request; its tokens if the gateway reported them
+    pub fn record(&mut self, usage: Option<&crate::types::LlmUsage>) {
+        self.requests += 1;
+        if let Some(usage) = usage {
+            self.prompt_tokens += usage.prompt_tokens;
+            self.completion_tokens += usage.completion_tokens;
+            if usage.model.is_some() {
+                self.model = usage.model.clone();
+            }
+        }
+    }
+
+    /// Fold in a report that didn't make it to the control plane
+    pub fn merge(&mut self, unsent: LlmUsageReport) {
+        self.requests += unsent.requests;
+        self.prompt_tokens += unsent.prompt_tokens;
+        self.completion_tokens += unsent.completion_tokens;
+        if self.model.is_none() {
+            self.model = unsent.model;
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.requests == 0
+    }
+}
+
+/// Runner state summary reported on each sync
+#[derive(Debug, Clone, Serialize)]
+pub struct SyncStateSummary {
+    pub runner_status: String,
+    pub config_version_id: Option<Uuid>,
+    pub equity_usd: rust_decimal::Decimal,
+    pub cash_usd: rust_decimal::Decimal,
+    pub positions_count: i32,
+    pub trades_today: i32,
+    pub last_plan_id: Option<Uuid>,
+    /// Rung of the degradation ladder (`normal` unless something is down)
+    pub degradation_mode: String,
+    pub degraded_subsystems: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SyncResponse {
+    pub config_pending: bool,
+    pub new_version_id: Option<Uuid>,
+    #[serde(default)]
+    pub commands: Vec<BotCommand>,
+    #[serde(default)]
+    pub events_accepted: usize,
+    /// Platform-wide trading halt (see `governor`)
+    #[serde(default)]
+    pub trading_halted: bool,
+    #[serde(default)]
+    pub halt_reason: Option<String>,
+    #[serde(default)]
+    pub journal_accepted: usize,
+    /// `paused` when the owner has paused the bot, otherwise `online`
+    #[serde(default)]
+    pub desired_status: Option<String>,
+    /// Control plane region that answered, if it runs in more than one
+    #[serde(default)]
+    pub region: Option<String>,
+    /// An earlier attempt at this sync had already been stored
+    #[serde(default)]
+    pub duplicate: bool,
+}
+
+/// Command delivered by the control plane in a sync response
+#[derive(Debug, Clone, Deserialize)]
+pub struct BotCommand {
+    pub id: Uuid,
+    pub command: String,
+    #[serde(default)]
+    pub args: serde_json::Value,
+}
+
+/// How long to hold off before the next request, from rate limit headers
+///
+/// `Retry-After` (seconds) wins. Otherwise an exhausted window waits for
+/// `X-RateLimit-Reset`, and a nearly exhausted one spreads the remaining
+/// requests over what's left of it. Capped at `MAX_RATE_LIMIT_WAIT_SECS`.
+fn rate_limit_delay(headers: &reqwest::header::HeaderMap, now_unix: u64) -> Option<Duration> {
+    let number =
+        |name: &str| -> Option<u64> { headers.get(name)?.to_str().ok()?.trim().parse().ok() };
+    let cap = Duration::from_secs(MAX_RATE_LIMIT_WAIT_SECS);
+
+    if let Some(secs) = number("retry-after") {
+        return Some(Duration::from_secs(secs).min(cap));
+    }
+
+    let limit = number("x-ratelimit-limit")?;
+    let remaining = number("x-ratelimit-remaining")?;
+    let until_reset = Duration::from_secs(number("x-ratelimit-reset")?.saturating_sub(now_unix));
+    if remaining == 0 {
+        Some(until_reset.min(cap))
+    } else if remaining * RATE_LIMIT_PACING_FRACTION <= limit {
+        Some((until_reset / (remaining as u32 + 1)).min(cap))
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compression_roundtrip() {
+        let body = br#"{"events":[{"event_type":"trade_confirmed","message":"ok"}]}"#.repeat(20);
+
+        for compression in [Compression::None, Compression::Gzip, Compression::Zstd] {
+            let encoded = compression.encode(&body).unwrap();
+            let decoded = decode_body(compression.content_encoding(), &encoded).unwrap();
+            assert_eq!(decoded, body);
+            if compression != Compression::None {
+                assert!(encoded.len() < body.len());
+            }
+        }
+    }
+
+    #[test]
+    fn test_compression_parse() {
+        assert_eq!(Compression::parse("gzip"), Some(Compression::Gzip));
+        assert_eq!(Compression::parse(" ZSTD "), Some(Compression::Zstd));
+        assert_eq!(Compression::parse("none"), Some(Compression::None));
+        assert_eq!(Compression::parse("brotli"), None);
+    }
+
+    #[test]
+    fn test_endpoints_fail_over_and_stick() {
+        let t0 = Instant::now();
+        let mut endpoints = Endpoints::new("https://us.cp.example/");
+        endpoints.add("https://eu.cp.example");
+        endpoints.add(" https://us.cp.example ");
+        assert_eq!(endpoints.urls.len(), 2);
+
+        // A single blip doesn't move anything
+        assert_eq!(endpoints.record_failure(t0), None);
+        endpoints.record_success();
+        assert_eq!(endpoints.record_failure(t0), None);
+        assert_eq!(endpoints.record_failure(t0), Some("https://eu.cp.example"));
+
+        // Stays on the failover until it's time to check the primary
+        let recheck = t0 + Duration::from_secs(PRIMARY_RECHECK_SECS);
+        assert!(!endpoints.primary_recheck_due(t0 + Duration::from_secs(10)));
+        assert!(endpoints.primary_recheck_due(recheck));
+        endpoints.defer_primary_recheck(recheck);
+        assert!(!endpoints.primary_recheck_due(recheck + Duration::from_secs(10)));
+
+        endpoints.restore_primary();
+        assert_eq!(endpoints.active(), "https://us.cp.example");
+        assert!(!endpoints.primary_recheck_due(recheck + Duration::from_secs(3600)));
+
+        // Past the last region it wraps back around to the primary
+        for _ in 0..2 {
+            endpoints.record_failure(t0);
+        }
+        assert_eq!(endpoints.active(), "https://eu.cp.example");
+        endpoints.record_failure(t0);
+        assert_eq!(endpoints.record_failure(t0), Some("https://us.cp.example"));
+        assert!(!endpoints.primary_recheck_due(recheck));
+
+        // Nowhere to go with one region
+        let mut single = Endpoints::new("https://us.cp.example");
+        for _ in 0..5 {
+            assert_eq!(single.record_failure(t0), None);
+        }
+    }
+
+    #[test]
+    fn test_critical_event_types() {
+        assert!(is_critical("bot_shutdown"));
+        assert!(is_critical("daily_loss_breached"));
+        assert!(!is_critical("trade_confirmed"));
+        assert!(!is_critical("drawdown_recovered"));
+    }
+
+    #[test]
+    fn test_rate_limit_delay() {
+        use reqwest::header::{HeaderMap, HeaderValue};
+        let headers = |pairs: &[(&'static str, &'static str)]| {
+            let mut map = HeaderMap::new();
+            for (name, value) in pairs {
+                map.insert(*name, HeaderValue::from_static(value));
+            }
+            map
+        };
+        let now = 1_000_000;
+
+        assert_eq!(rate_limit_delay(&headers(&[]), now), None);
+        assert_eq!(
+            rate_limit_delay(&headers(&[("retry-after", "7")]), now),
+            Some(Duration::from_secs(7))
+        );
+        assert_eq!(
+            rate_limit_delay(&headers(&[("retry-after", "86400")]), now),
+            Some(Duration::from_secs(MAX_RATE_LIMIT_WAIT_SECS))
+        );
+
+        let window = |remaining: &'static str| {
+            headers(&[
+                ("x-ratelimit-limit", "120"),
+                ("x-ratelimit-remaining", remaining),
+                ("x-ratelimit-reset", "1000030"),
+            ])
+        };
+        // Plenty left: no pacing
+        assert_eq!(rate_limit_delay(&window("50"), now), None);
+        // Nearly used up: spread what's left over the rest of the window
+        assert_eq!(
+            rate_limit_delay(&window("2"), now),
+            Some(Duration::from_secs(10))
+        );
+        assert_eq!(
+            rate_limit_delay(&window("0"), now),
+            Some(Duration::from_secs(30))
+        );
+    }
+
+    #[test]
+    fn test_decode_rejects_unknown_encoding() {
+        assert!(decode_body(Some("br"), b"data").is_err());
+    }
+
+    #[test]
+    fn test_llm_usage_report_accumulates() {
+        let mut report = LlmUsageReport::default();
+        assert!(report.is_empty());
+        report.record(Some(&crate::types::LlmUsage {
+            model: Some("gpt-4o".to_string()),
+            prompt_tokens: 1_200,
+            completion_tokens: 300,
+        }));
+        // An older gateway reports no usage; the request still counts
+        report.record(None);
+        assert_eq!(report.requests, 2);
+        assert_eq!(report.prompt_tokens, 1_200);
+
+        // A failed sync's report is folded back in
+        let mut next = LlmUsageReport::default();
+        next.record(None);
+        next.merge(report);
+        assert_eq!(next.requests, 3);
+        assert_eq!(next.completion_tokens, 300);
+        assert_eq!(next.model.as_deref(), Some("gpt-4o"));
+    }
+}
@@ -1,26 +1,76 @@
 //! Bot Configuration
 
+use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 use uuid::Uuid;
 
-use crate::client::BotConfigResponse;
+use crate::client::{BotConfigResponse, Compression, RunnerSecrets};
+use crate::degradation::DegradationPolicy;
+
+/// Where this runner is hosted (RUNNER_MODE)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum RunnerMode {
+    /// Provisioned droplet; cloud-init bootstrap already wrote our environment
+    #[default]
+    Droplet,
+    /// Run by the bot owner (local/dev or self-hosted) against a manual bot
+    External,
+}
+
+impl RunnerMode {
+    /// Parse from RUNNER_MODE value (droplet | external)
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "droplet" => Some(Self::Droplet),
+            "external" | "local" => Some(Self::External),
+            _ => None,
+        }
+    }
+}
 
 /// Runtime configuration loaded from environment
 #[derive(Debug, Clone)]
 pub struct Config {
     pub bot_id: Uuid,
     pub control_plane_url: String,
+    /// Control planes in other regions, tried in order while the primary is
+    /// down (CONTROL_PLANE_FAILOVER_URLS, comma-separated)
+    pub control_plane_failover_urls: Vec<String>,
     pub data_retrieval_url: String,
+    /// data-retrieval's gRPC price service, preferred over the HTTP API for
+    /// price lookups when set (DATA_RETRIEVAL_GRPC_URL)
+    pub data_retrieval_grpc_url: Option<String>,
     pub solana_rpc_url: String,
     pub agent_wallet: Option<String>,
     pub keypair_path: PathBuf,
     pub wallet_address: String,
+    /// Request compression for control plane sync payloads
+    pub compression: Compression,
+    pub runner_mode: RunnerMode,
+    /// One-time token exchanged for secrets (external mode)
+    pub bootstrap_token: Option<String>,
+    /// Where fetched secrets are kept between restarts (external mode)
+    pub secrets_path: PathBuf,
+    /// Daily counters roll over at midnight in this zone (BOT_DAY_ROLLOVER_TZ)
+    pub day_rollover_tz: chrono_tz::Tz,
+    /// How sells are priced against their buys (BOT_COST_BASIS)
+    pub cost_basis: crate::portfolio::CostBasisMethod,
+    /// Hex key the state directory is encrypted with (STATE_ENCRYPTION_KEY)
+    pub state_encryption_key: Option<String>,
+    /// Paper sells beyond what's held open simulated shorts (BOT_PAPER_SHORTS)
+    pub paper_shorts: bool,
 }
 
 impl Config {
     /// Load configuration from environment variables
     pub fn from_env() -> anyhow::Result<Self> {
+        let runner_mode = match std::env::var("RUNNER_MODE") {
+            Ok(v) => RunnerMode::parse(&v)
+                .ok_or_else(|| anyhow::anyhow!("Invalid RUNNER_MODE: {}", v))?,
+            Err(_) => RunnerMode::default(),
+        };
+
         let bot_id = std::env::var("BOT_ID")
             .map_err(|_| anyhow::anyhow!("BOT_ID environment variable required"))?
             .parse::<Uuid>()
@@ -29,9 +79,17 @@ impl Config {
         let control_plane_url = std::env::var("CONTROL_PLANE_URL")
             .unwrap_or_else(|_| "http://localhost:3000".to_string());
 
+        let control_plane_failover_urls = std::env::var("CONTROL_PLANE_FAILOVER_URLS")
+            .map(|v| parse_url_list(&v))
+            .unwrap_or_default();
+
         let data_retrieval_url = std::env::var("DATA_RETRIEVAL_URL")
             .unwrap_or_else(|_| "http://localhost:8080".to_string());
 
+        let data_retrieval_grpc_url = std::env::var("DATA_RETRIEVAL_GRPC_URL")
+            .ok()
+            .filter(|u| !u.is_empty());
+
         let solana_rpc_url = std::env::var("SOLANA_RPC_URL")
             .unwrap_or_else(|_| "https://api.devnet.solana.com".to_string());
 
@@ -39,7 +97,12 @@ impl Config {
 
         let keypair_path = std::env::var("AGENT_WALLET_PATH")
             .map(PathBuf::from)
-            .unwrap_or_else(|_| PathBuf::from("/opt/trawling-traders/.config/solana/id.json"));
+            .unwrap_or_else(|_| match runner_mode {
+                RunnerMode::Droplet => {
+                    PathBuf::from("/opt/trawling-traders/.config/solana/id.json")
+                }
+                RunnerMode::External => home_dir().join(".config/solana/id.json"),
+            });
 
         let wallet_address = std::env::var("WALLET_ADDRESS")
             .or_else(|_| {
@@ -49,16 +112,104 @@ impl Config {
             })
             .unwrap_or_else(|_| "unknown".to_string());
 
+        let compression = std::env::var("CONTROL_PLANE_COMPRESSION")
+            .ok()
+            .and_then(|v| Compression::parse(&v))
+            .unwrap_or_default();
+
+        let bootstrap_token = std::env::var("BOOTSTRAP_TOKEN")
+            .ok()
+            .filter(|t| !t.is_empty());
+        let secrets_path = std::env::var("RUNNER_SECRETS_PATH")
+            .map(PathBuf::from)
+            .unwrap_or_else(|_| PathBuf::from(format!("trawler-secrets-{}.json", bot_id)));
+
+        let day_rollover_tz = match std::env::var("BOT_DAY_ROLLOVER_TZ") {
+            Ok(v) if !v.trim().is_empty() => v
+                .trim()
+                .parse::<chrono_tz::Tz>()
+                .map_err(|e| anyhow::anyhow!("Invalid BOT_DAY_ROLLOVER_TZ: {}", e))?,
+            _ => chrono_tz::UTC,
+        };
+
+        let cost_basis = match std::env::var("BOT_COST_BASIS") {
+            Ok(v) if !v.trim().is_empty() => crate::portfolio::CostBasisMethod::parse(&v)
+                .ok_or_else(|| anyhow::anyhow!("Invalid BOT_COST_BASIS: {}", v))?,
+            _ => crate::portfolio::CostBasisMethod::default(),
+        };
+
+        let paper_shorts = match std::env::var("BOT_PAPER_SHORTS") {
+            Ok(v) => match v.trim().to_ascii_lowercase().as_str() {
+                "" | "0" | "false" | "no" | "off" => false,
+                "1" | "true" | "yes" | "on" => true,
+                _ => anyhow::bail!("Invalid BOT_PAPER_SHORTS: {}", v),
+            },
+            Err(_) => false,
+        };
+
+        let state_encryption_key = std::env::var("STATE_ENCRYPTION_KEY")
+            .ok()
+            .filter(|k| !k.is_empty());
+
         Ok(Self {
             bot_id,
             control_plane_url,
+            control_plane_failover_urls,
             data_retrieval_url,
+            data_retrieval_grpc_url,
             solana_rpc_url,
             agent_wallet,
             keypair_path,
             wallet_address,
+            compression,
+            runner_mode,
+            bootstrap_token,
+            secrets_path,
+            day_rollover_tz,
+            cost_basis,
+            state_encryption_key,
+            paper_shorts,
         })
     }
+
+    /// Fill in settings from control plane secrets
+    ///
+    /// Explicit environment variables win, so a developer can point a runner
+    /// at a local data-retrieval or RPC node.
+    pub fn apply_secrets(&mut self, secrets: &RunnerSecrets) {
+        if std::env::var("DATA_RETRIEVAL_URL").is_err() && !secrets.data_retrieval_url.is_empty() {
+            self.data_retrieval_url = secrets.data_retrieval_url.clone();
+        }
+        if std::env::var("SOLANA_RPC_URL").is_err() && !secrets.solana_rpc_url.is_empty() {
+            self.solana_rpc_url = secrets.solana_rpc_url.clone();
+        }
+        if std::env::var("JUPITER_API_KEY").is_err() && !secrets.jupiter_api_key.is_empty() {
+            // Read by the executor when it builds its execution config
+            std::env::set_var("JUPITER_API_KEY", &secrets.jupiter_api_key);
+        }
+        if self.state_encryption_key.is_none() {
+            self.state_encryption_key = secrets.state_encryption_key.clone();
+        }
+        if std::env::var("CONTROL_PLANE_FAILOVER_URLS").is_err() {
+            self.control_plane_failover_urls = parse_url_list(&secrets.control_plane_failover_urls);
+        }
+    }
+}
+
+/// Comma-separated URLs, blanks dropped
+fn parse_url_list(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|url| !url.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+fn home_dir() -> PathBuf {
+    std::env::var("HOME")
+        .map(PathBuf::from)
+        .unwrap_or_else(|_| PathBuf::from("."))
 }
 
 /// Bot trading configuration
@@ -72,6 +223,17 @@ pub struct BotConfig {
     pub trading_mode: TradingMode,
     pub risk_caps: RiskCaps,
     pub execution: ExecutionConfig,
+    /// Wallet minimums checked before live trading
+    pub funding: FundingRequirements,
+    /// Stop-loss / take-profit distances enforced by the order manager
+    pub exits: ExitRules,
+    /// Per-asset exposure caps and max open positions, checked on buys
+    pub asset_limits: AssetLimits,
+    /// Session outside which no new trades open (exits still run)
+    pub trading_window: Option<TradingWindow>,
+    /// What trading continues while the gateway, claw-trader, prices or the
+    /// control plane are down
+    pub degradation: DegradationPolicy,
     pub llm_provider: String,
     pub llm_model: String,
     pub llm_api_key: String,
@@ -100,6 +262,9 @@ pub struct AssetSpec {
     #[serde(default = "default_enabled")]
     pub enabled: bool,
     pub max_allocation_pct: Option<i32>,
+    /// From the asset registry; looked up from the token list when absent
+    #[serde(default)]
+    pub decimals: Option<u8>,
 }
 
 fn default_enabled() -> bool {
@@ -116,6 +281,12 @@ impl BotConfig {
             anyhow::anyhow!("Invalid bot config JSON: {}", e)
         })?;
 
+        if let Some(window) = &config.trading_window {
+            if window.bounds().is_none() {
+                anyhow::bail!("Invalid trading window: {}", window.describe());
+            }
+        }
+
         let version_id = resp.version_id.parse().map_err(|e| {
             tracing::error!("Failed to parse version_id '{}': {}", resp.version_id, e);
             anyhow::anyhow!("Invalid version_id: {}", e)
@@ -135,6 +306,11 @@ impl BotConfig {
                 max_trades_per_day: config.agent_config.max_trades_per_day,
             },
             execution: config.execution.unwrap_or_default(),
+            funding: config.funding,
+            exits: config.exits,
+            asset_limits: config.asset_limits,
+            trading_window: config.trading_window,
+            degradation: config.degradation,
             llm_provider: config.llm_config.provider,
             llm_model: config.llm_config.model,
             llm_api_key: config.llm_config.api_key,
@@ -154,6 +330,16 @@ struct BotConfigInner {
     trading_params: TradingParamsInner,
     #[serde(rename = "execution")]
     execution: Option<ExecutionConfig>,
+    #[serde(default)]
+    funding: FundingRequirements,
+    #[serde(default)]
+    exits: ExitRules,
+    #[serde(default)]
+    asset_limits: AssetLimits,
+    #[serde(default)]
+    trading_window: Option<TradingWindow>,
+    #[serde(default)]
+    degradation: DegradationPolicy,
     #[serde(rename = "llm_config")]
     llm_config: LlmConfigInner,
     /// OpenClaw strategy configuration
@@ -262,6 +448,165 @@ pub struct RiskCaps {
     pub max_trades_per_day: i32,
 }
 
+/// Minimum wallet balances before live trading starts (set by the control plane)
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq)]
+pub struct FundingRequirements {
+    pub min_usdc: Decimal,
+    pub min_sol: Decimal,
+}
+
+impl Default for FundingRequirements {
+    fn default() -> Self {
+        Self {
+            min_usdc: Decimal::from(10),
+            min_sol: Decimal::new(5, 2),
+        }
+    }
+}
+
+/// Exit levels as fractions of a position's entry price (0.05 = 5%)
+///
+/// The trailing stop is measured from the highest price since entry
+/// instead. Zero disables that exit.
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq)]
+pub struct ExitRules {
+    pub stop_loss_pct: Decimal,
+    pub take_profit_pct: Decimal,
+    #[serde(default)]
+    pub trailing_stop_pct: Decimal,
+}
+
+impl Default for ExitRules {
+    fn default() -> Self {
+        Self {
+            stop_loss_pct: Decimal::new(5, 2),
+            take_profit_pct: Decimal::new(10, 2),
+            trailing_stop_pct: Decimal::ZERO,
+        }
+    }
+}
+
+/// Exposure cap for one asset, on top of the global position size cap
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+pub struct AssetCap {
+    /// Symbol (e.g. "SOL") or mint address
+    pub asset: String,
+    #[serde(default)]
+    pub max_exposure_usd: Option<Decimal>,
+    /// Percent of total equity (20 = 20%)
+    #[serde(default)]
+    pub max_exposure_percent: Option<Decimal>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize, PartialEq)]
+pub struct AssetLimits {
+    #[serde(default)]
+    pub max_open_positions: Option<u32>,
+    /// Cap on longs plus shorts, in percent of total equity
+    #[serde(default)]
+    pub max_gross_exposure_percent: Option<Decimal>,
+    /// Cap on longs minus shorts either way, in percent of total equity
+    #[serde(default)]
+    pub max_net_exposure_percent: Option<Decimal>,
+    /// Minutes a mint must wait after a fill before it trades again
+    #[serde(default)]
+    pub cooldown_minutes: Option<u32>,
+    #[serde(default)]
+    pub per_asset: Vec<AssetCap>,
+}
+
+impl AssetLimits {
+    /// Cap for the asset with this mint, matched by mint or (case-insensitively) by symbol
+    pub fn cap_for(&self, mint: &str, symbol: Option<&str>) -> Option<&AssetCap> {
+        self.per_asset.iter().find(|cap| {
+            cap.asset == mint || symbol.is_some_and(|s| cap.asset.eq_ignore_ascii_case(s))
+        })
+    }
+}
+
+/// UTC session in which the runner may open new trades
+///
+/// `start` and `end` are `HH:MM`; a `start` later than `end` runs past
+/// midnight, and equal times (the default) mean the whole day. `days` are
+/// the days a session opens on; empty means every day.
+#[derive(Debug, Clone, Default, Deserialize, Serialize, PartialEq)]
+pub struct TradingWindow {
+    #[serde(default)]
+    pub days: Vec<chrono::Weekday>,
+    #[serde(default)]
+    pub start: Option<String>,
+    #[serde(default)]
+    pub end: Option<String>,
+}
+
+impl TradingWindow {
+    /// Start and end as minutes past midnight, `None` if either is malformed
+    pub fn bounds(&self) -> Option<(u32, u32)> {
+        let minutes = |time: &Option<String>| -> Option<u32> {
+            let Some(time) = time else {
+                return Some(0);
+            };
+            let (hours, minutes) = time.split_once(':')?;
+            let (hours, minutes): (u32, u32) = (hours.parse().ok()?, minutes.parse().ok()?);
+            (hours < 24 && minutes < 60).then_some(hours * 60 + minutes)
+        };
+        Some((minutes(&self.start)?, minutes(&self.end)?))
+    }
+
+    fn opens_on(&self, day: chrono::Weekday) -> bool {
+        self.days.is_empty() || self.days.contains(&day)
+    }
+
+    pub fn is_open(&self, now: chrono::DateTime<chrono::Utc>) -> bool {
+        use chrono::{Datelike, Timelike};
+
+        // Malformed windows are refused when the config is loaded
+        let Some((start, end)) = self.bounds() else {
+            return true;
+        };
+        let minute = now.hour() * 60 + now.minute();
+        let today = now.weekday();
+        match start.cmp(&end) {
+            std::cmp::Ordering::Equal => self.opens_on(today),
+            std::cmp::Ordering::Less => self.opens_on(today) && (start..end).contains(&minute),
+            // Overnight: the evening of an opening day or the morning after one
+            std::cmp::Ordering::Greater => {
+                (self.opens_on(today) && minute >= start)
+                    || (self.opens_on(today.pred()) && minute < end)
+            }
+        }
+    }
+
+    /// When the next session opens after `now`
+    pub fn next_open(
+        &self,
+        now: chrono::DateTime<chrono::Utc>,
+    ) -> Option<chrono::DateTime<chrono::Utc>> {
+        use chrono::Datelike;
+
+        let (start, _) = self.bounds()?;
+        (0..=7).find_map(|offset| {
+            let day = now.date_naive() + chrono::Duration::days(offset);
+            if !self.opens_on(day.weekday()) {
+                return None;
+            }
+            let at = day.and_hms_opt(start / 60, start % 60, 0)?.and_utc();
+            (at > now).then_some(at)
+        })
+    }
+
+    /// `13:00-21:00 UTC, Mon Tue Wed Thu Fri`
+    pub fn describe(&self) -> String {
+        let time = |t: &Option<String>| t.clone().unwrap_or_else(|| "00:00".to_string());
+        let mut text = format!("{}-{} UTC", time(&self.start), time(&self.end));
+        if !self.days.is_empty() {
+            let days: Vec<String> = self.days.iter().map(|d| d.to_string()).collect();
+            text.push_str(&format!(", {}", days.join(" ")));
+        }
+        text
+    }
+}
+
 /// Execution configuration (impact, slippage, timeouts)
 #[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq)]
 pub struct ExecutionConfig {
@@ -277,6 +622,29 @@ pub struct ExecutionConfig {
     /// Quote cache TTL in seconds
     #[serde(default = "default_quote_cache_secs")]
     pub quote_cache_secs: u64,
+    /// Oldest the price behind a quote may be when it's traded on
+    #[serde(default = "default_max_price_age_secs")]
+    pub max_price_age_secs: u64,
+    /// Child swaps a large intent is split into (1 sends it whole)
+    #[serde(default = "default_twap_slices")]
+    pub twap_slices: u32,
+    /// Seconds the child swaps are spread over
+    #[serde(default = "default_twap_window_secs")]
+    pub twap_window_secs: u64,
+    /// Intents at least this percentage of the max position size are sliced
+    #[serde(default = "default_twap_min_position_pct")]
+    pub twap_min_position_pct: f64,
+    /// Shrink intents to what the market's depth absorbs within
+    /// `max_slippage_bps` before they're sent
+    #[serde(default = "default_liquidity_sizing")]
+    pub liquidity_sizing: bool,
+    /// Refuse to buy mints that aren't built in or verified in Jupiter's
+    /// token list (otherwise they're only logged)
+    #[serde(default)]
+    pub verified_mints_only: bool,
+    /// Refuse to buy tokens whose DEX pools hold less than this (USD)
+    #[serde(default = "default_min_liquidity_usd")]
+    pub min_liquidity_usd: f64,
 }
 
 impl Default for ExecutionConfig {
@@ -286,6 +654,13 @@ impl Default for ExecutionConfig {
             max_slippage_bps: default_max_slippage_bps(),
             confirm_timeout_secs: default_confirm_timeout_secs(),
             quote_cache_secs: default_quote_cache_secs(),
+            max_price_age_secs: default_max_price_age_secs(),
+            twap_slices: default_twap_slices(),
+            twap_window_secs: default_twap_window_secs(),
+            twap_min_position_pct: default_twap_min_position_pct(),
+            liquidity_sizing: default_liquidity_sizing(),
+            verified_mints_only: false,
+            min_liquidity_usd: default_min_liquidity_usd(),
         }
     }
 }
@@ -302,3 +677,136 @@ fn default_confirm_timeout_secs() -> u64 {
 fn default_quote_cache_secs() -> u64 {
     10
 }
+fn default_max_price_age_secs() -> u64 {
+    30
+}
+fn default_twap_slices() -> u32 {
+    1
+}
+fn default_twap_window_secs() -> u64 {
+    60
+}
+fn default_twap_min_position_pct() -> f64 {
+    50.0
+}
+fn default_liquidity_sizing() -> bool {
+    true
+}
+fn default_min_liquidity_usd() -> f64 {
+    10_000.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_max_price_age() {
+        let exec: ExecutionConfig = serde_json::from_value(serde_json::json!({})).unwrap();
+        assert_eq!(exec.max_price_age_secs, 30);
+
+        let now = chrono::Utc::now();
+        let quote = crate::executor::ClawTraderPrice {
+            input_mint: "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v".to_string(),
+            output_mint: "So11111111111111111111111111111111111111112".to_string(),
+            in_amount: 1_000_000,
+            out_amount: 10_000_000,
+            price_impact_pct: 0.1,
+            fee_bps: 69,
+            priced_at: now - chrono::Duration::seconds(45),
+        };
+        assert!(quote.age_secs(now) > exec.max_price_age_secs as i64);
+
+        // A clock a little behind the price source never goes negative
+        let ahead = crate::executor::ClawTraderPrice {
+            priced_at: now + chrono::Duration::seconds(2),
+            ..quote
+        };
+        assert_eq!(ahead.age_secs(now), 0);
+    }
+
+    #[test]
+    fn test_runner_mode_parse() {
+        assert_eq!(RunnerMode::parse("external"), Some(RunnerMode::External));
+        assert_eq!(RunnerMode::parse(" Droplet "), Some(RunnerMode::Droplet));
+        assert_eq!(RunnerMode::parse("kubernetes"), None);
+        assert_eq!(RunnerMode::default(), RunnerMode::Droplet);
+    }
+
+    #[test]
+    fn test_asset_cap_lookup() {
+        let limits: AssetLimits = serde_json::from_value(serde_json::json!({
+            "max_open_positions": 3,
+            "cooldown_minutes": 15,
+            "per_asset": [
+                {"asset": "sol", "max_exposure_usd": "500"},
+                {"asset": "DezXAZ8z7PnrnRJjz3wXBoRgixCa6xjnB7YaB1pPB263", "max_exposure_percent": "5"}
+            ]
+        }))
+        .unwrap();
+        assert_eq!(limits.max_open_positions, Some(3));
+        assert_eq!(limits.cooldown_minutes, Some(15));
+        assert_eq!(
+            limits
+                .cap_for("So11111111111111111111111111111111111111112", Some("SOL"))
+                .unwrap()
+                .max_exposure_usd,
+            Some(Decimal::from(500))
+        );
+        assert!(limits
+            .cap_for("DezXAZ8z7PnrnRJjz3wXBoRgixCa6xjnB7YaB1pPB263", None)
+            .is_some());
+        assert!(limits
+            .cap_for("JUPyiwrYJFskUPiHa7hkeR8VUtAeFoSYbKedZNsDvCN", Some("JUP"))
+            .is_none());
+    }
+
+    #[test]
+    fn test_trading_window() {
+        use chrono::TimeZone;
+
+        // 2026-03-02 is a Monday
+        let at = |day: u32, hour: u32, minute: u32| {
+            chrono::Utc
+                .with_ymd_and_hms(2026, 3, day, hour, minute, 0)
+                .unwrap()
+        };
+        let weekdays: TradingWindow = serde_json::from_value(serde_json::json!({
+            "days": ["mon", "tue", "wed", "thu", "fri"],
+            "start": "13:00",
+            "end": "21:00"
+        }))
+        .unwrap();
+        assert!(!weekdays.is_open(at(2, 12, 59)));
+        assert!(weekdays.is_open(at(2, 13, 0)));
+        assert!(!weekdays.is_open(at(2, 21, 0)));
+        assert!(!weekdays.is_open(at(7, 15, 0))); // Saturday
+        assert_eq!(weekdays.next_open(at(6, 22, 0)), Some(at(9, 13, 0)));
+        assert_eq!(weekdays.describe(), "13:00-21:00 UTC, Mon Tue Wed Thu Fri");
+
+        // Friday night into Saturday morning
+        let overnight = TradingWindow {
+            days: vec![chrono::Weekday::Fri],
+            start: Some("22:00".to_string()),
+            end: Some("02:00".to_string()),
+        };
+        assert!(overnight.is_open(at(6, 23, 0)));
+        assert!(overnight.is_open(at(7, 1, 59)));
+        assert!(!overnight.is_open(at(7, 2, 0)));
+        assert!(!overnight.is_open(at(7, 23, 0)));
+
+        // No times: whole days
+        let no_weekends = TradingWindow {
+            days: weekdays.days.clone(),
+            ..Default::default()
+        };
+        assert!(no_weekends.is_open(at(6, 23, 59)));
+        assert!(!no_weekends.is_open(at(8, 12, 0)));
+
+        let malformed = TradingWindow {
+            start: Some("25:00".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(malformed.bounds(), None);
+    }
+}
@@ -0,0 +1,79 @@
+//! Per-symbol cooldown between trades
+//!
+//! With `asset_limits.cooldown_minutes` set, an intent on a mint that traded
+//! less than that long ago is blocked, so the gateway can't churn one asset
+//! tick after tick. Every confirmed fill counts, exits included, but exits
+//! themselves are never held back.
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SymbolCooldowns {
+    /// Last confirmed fill per mint
+    last_trade: HashMap<String, DateTime<Utc>>,
+}
+
+impl SymbolCooldowns {
+    /// Record a confirmed fill on `mint`
+    pub fn record(&mut self, mint: &str, at: DateTime<Utc>) {
+        self.last_trade.insert(mint.to_string(), at);
+    }
+
+    /// Time left before `mint` may trade again, if it's still cooling down
+    pub fn remaining(
+        &self,
+        mint: &str,
+        cooldown_minutes: u32,
+        now: DateTime<Utc>,
+    ) -> Option<Duration> {
+        let last = self.last_trade.get(mint)?;
+        let remaining = *last + Duration::minutes(cooldown_minutes as i64) - now;
+        (remaining > Duration::zero()).then_some(remaining)
+    }
+
+    /// Forget fills older than `max_minutes`, which no longer matter
+    pub fn prune(&mut self, max_minutes: u32, now: DateTime<Utc>) {
+        let cutoff = now - Duration::minutes(max_minutes as i64);
+        self.last_trade.retain(|_, at| *at > cutoff);
+    }
+}
+
+/// `4m 12s`-style rendering for rejection messages
+pub fn format_remaining(remaining: Duration) -> String {
+    let secs = remaining.num_seconds().max(0);
+    match (secs / 60, secs % 60) {
+        (0, s) => format!("{}s", s),
+        (m, 0) => format!("{}m", m),
+        (m, s) => format!("{}m {}s", m, s),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cooldown_window() {
+        let sol = "So11111111111111111111111111111111111111112";
+        let t0 = Utc::now();
+        let mut cooldowns = SymbolCooldowns::default();
+        assert_eq!(cooldowns.remaining(sol, 15, t0), None);
+
+        cooldowns.record(sol, t0);
+        let remaining = cooldowns
+            .remaining(sol, 15, t0 + Duration::seconds(528))
+            .unwrap();
+        assert_eq!(remaining, Duration::seconds(372));
+        assert_eq!(format_remaining(remaining), "6m 12s");
+        assert_eq!(
+            cooldowns.remaining(sol, 15, t0 + Duration::minutes(15)),
+            None
+        );
+        assert_eq!(cooldowns.remaining("other", 15, t0), None);
+
+        cooldowns.prune(15, t0 + Duration::minutes(20));
+        assert_eq!(cooldowns, SymbolCooldowns::default());
+    }
+}
@@ -0,0 +1,322 @@
+//! Degradation ladder - what the runner may do while subsystems are down
+//!
+//! Four things can fail under a running bot: the OpenClaw gateway (no
+//! decisions), claw-trader (no live swaps), prices (nothing to value, size or
+//! exit against) and the control plane (no halts, commands or config). Each
+//! failure maps to one rung of the ladder through the bot config's
+//! `degradation` policy, and with several down at once the most restrictive
+//! rung wins:
+//!
+//! | mode             | entries | exits | notes                                |
+//! |------------------|---------|-------|--------------------------------------|
+//! | `normal`         | yes     | yes   |                                      |
+//! | `exit_only`      | no      | yes   | stops, targets and OpenClaw sells    |
+//! | `paper_fallback` | paper   | paper | live trades are simulated, not sent  |
+//! | `hold`           | no      | no    | resumes once the failure clears      |
+//! | `halt`           | no      | no    | stays until the owner resumes        |
+//!
+//! claw-trader only matters to live bots; a paper bot never needs it. Every
+//! change of mode is an event on the bot's timeline, and the current mode is
+//! in the state file and each sync's state summary, so the owner always
+//! knows whether real money is moving.
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::config::TradingMode;
+
+/// One rung of the ladder, least restrictive first
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum DegradedMode {
+    #[default]
+    Normal,
+    ExitOnly,
+    PaperFallback,
+    Hold,
+    Halt,
+}
+
+impl DegradedMode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DegradedMode::Normal => "normal",
+            DegradedMode::ExitOnly => "exit_only",
+            DegradedMode::PaperFallback => "paper_fallback",
+            DegradedMode::Hold => "hold",
+            DegradedMode::Halt => "halt",
+        }
+    }
+
+    /// New positions may open
+    pub fn allows_entries(&self) -> bool {
+        matches!(self, DegradedMode::Normal | DegradedMode::PaperFallback)
+    }
+
+    /// Positions may be closed
+    pub fn allows_exits(&self) -> bool {
+        *self <= DegradedMode::PaperFallback
+    }
+}
+
+impl std::fmt::Display for DegradedMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// A subsystem the runner depends on
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Subsystem {
+    Gateway,
+    ClawTrader,
+    Prices,
+    ControlPlane,
+}
+
+impl Subsystem {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Subsystem::Gateway => "gateway",
+            Subsystem::ClawTrader => "claw_trader",
+            Subsystem::Prices => "prices",
+            Subsystem::ControlPlane => "control_plane",
+        }
+    }
+}
+
+/// What was reachable on this tick
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Health {
+    pub gateway: bool,
+    pub claw_trader: bool,
+    pub prices: bool,
+    /// Last successful sync with the control plane
+    pub last_sync_at: DateTime<Utc>,
+}
+
+/// Mode per failure, from the bot config's `degradation` section
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DegradationPolicy {
+    /// No decisions from OpenClaw
+    #[serde(default = "default_no_gateway")]
+    pub no_gateway: DegradedMode,
+    /// Live bot without claw-trader; `paper_fallback` simulates its trades
+    #[serde(default = "default_no_claw_trader")]
+    pub no_claw_trader: DegradedMode,
+    /// No prices for any enabled asset
+    #[serde(default = "default_no_prices")]
+    pub no_prices: DegradedMode,
+    /// Control plane unreachable for `control_plane_grace_secs`
+    #[serde(default = "default_no_control_plane")]
+    pub no_control_plane: DegradedMode,
+    #[serde(default = "default_control_plane_grace_secs")]
+    pub control_plane_grace_secs: u64,
+}
+
+impl Default for DegradationPolicy {
+    fn default() -> Self {
+        Self {
+            no_gateway: default_no_gateway(),
+            no_claw_trader: default_no_claw_trader(),
+            no_prices: default_no_prices(),
+            no_control_plane: default_no_control_plane(),
+            control_plane_grace_secs: default_control_plane_grace_secs(),
+        }
+    }
+}
+
+fn default_no_gateway() -> DegradedMode {
+    DegradedMode::ExitOnly
+}
+fn default_no_claw_trader() -> DegradedMode {
+    DegradedMode::Hold
+}
+fn default_no_prices() -> DegradedMode {
+    DegradedMode::Hold
+}
+fn default_no_control_plane() -> DegradedMode {
+    DegradedMode::ExitOnly
+}
+fn default_control_plane_grace_secs() -> u64 {
+    300
+}
+
+/// The mode for one tick and the failures behind it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Degradation {
+    pub mode: DegradedMode,
+    pub failed: Vec<Subsystem>,
+}
+
+impl DegradationPolicy {
+    pub fn evaluate(
+        &self,
+        health: &Health,
+        trading_mode: TradingMode,
+        now: DateTime<Utc>,
+    ) -> Degradation {
+        let grace = Duration::seconds(self.control_plane_grace_secs as i64);
+        let checks = [
+            (Subsystem::Gateway, !health.gateway, self.no_gateway),
+            (
+                Subsystem::ClawTrader,
+                trading_mode == TradingMode::Live && !health.claw_trader,
+                self.no_claw_trader,
+            ),
+            (Subsystem::Prices, !health.prices, self.no_prices),
+            (
+                Subsystem::ControlPlane,
+                now - health.last_sync_at >= grace,
+                self.no_control_plane,
+            ),
+        ];
+
+        let mut degradation = Degradation {
+            mode: DegradedMode::Normal,
+            failed: Vec::new(),
+        };
+        for (subsystem, down, mode) in checks {
+            if down {
+                degradation.failed.push(subsystem);
+                degradation.mode = degradation.mode.max(mode);
+            }
+        }
+        // Simulated fills mean nothing to a paper bot
+        if trading_mode == TradingMode::Paper && degradation.mode == DegradedMode::PaperFallback {
+            degradation.mode = DegradedMode::Normal;
+        }
+        degradation
+    }
+}
+
+/// Current mode across ticks
+///
+/// `halt` latches: it holds after the failure clears, until
+/// [`DegradationMonitor::resume`] (the owner resuming the bot).
+#[derive(Debug, Clone, Default)]
+pub struct DegradationMonitor {
+    current: Option<Degradation>,
+    halted: bool,
+}
+
+impl DegradationMonitor {
+    pub fn mode(&self) -> DegradedMode {
+        if self.halted {
+            return DegradedMode::Halt;
+        }
+        self.current
+            .as_ref()
+            .map_or(DegradedMode::Normal, |d| d.mode)
+    }
+
+    pub fn failed(&self) -> &[Subsystem] {
+        self.current.as_ref().map_or(&[], |d| d.failed.as_slice())
+    }
+
+    /// Record this tick's evaluation; returns the previous mode if it changed
+    pub fn observe(&mut self, degradation: Degradation) -> Option<DegradedMode> {
+        let previous = self.mode();
+        self.halted |= degradation.mode == DegradedMode::Halt;
+        let changed_failures = self.failed() != degradation.failed.as_slice();
+        self.current = Some(degradation);
+        (self.mode() != previous || changed_failures).then_some(previous)
+    }
+
+    /// Clear a latched halt
+    pub fn resume(&mut self) -> bool {
+        std::mem::take(&mut self.halted)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn health(gateway: bool, claw_trader: bool, prices: bool, now: DateTime<Utc>) -> Health {
+        Health {
+            gateway,
+            claw_trader,
+            prices,
+            last_sync_at: now,
+        }
+    }
+
+    #[test]
+    fn test_policy_picks_most_restrictive() {
+        let policy = DegradationPolicy::default();
+        let now = Utc::now();
+        let mode = |h: Health, trading_mode| policy.evaluate(&h, trading_mode, now).mode;
+
+        assert_eq!(
+            mode(health(true, true, true, now), TradingMode::Live),
+            DegradedMode::Normal
+        );
+        assert_eq!(
+            mode(health(false, true, true, now), TradingMode::Live),
+            DegradedMode::ExitOnly
+        );
+        // claw-trader only matters live
+        assert_eq!(
+            mode(health(true, false, true, now), TradingMode::Paper),
+            DegradedMode::Normal
+        );
+        let both = policy.evaluate(&health(false, false, true, now), TradingMode::Live, now);
+        assert_eq!(both.mode, DegradedMode::Hold);
+        assert_eq!(both.failed, vec![Subsystem::Gateway, Subsystem::ClawTrader]);
+
+        // The control plane gets a grace period
+        let stale = Health {
+            last_sync_at: now - Duration::seconds(299),
+            ..health(true, true, true, now)
+        };
+        assert_eq!(mode(stale, TradingMode::Live), DegradedMode::Normal);
+        let stale = Health {
+            last_sync_at: now - Duration::seconds(300),
+            ..stale
+        };
+        assert_eq!(mode(stale, TradingMode::Live), DegradedMode::ExitOnly);
+
+        let opt_in = DegradationPolicy {
+            no_claw_trader: DegradedMode::PaperFallback,
+            ..policy
+        };
+        let h = health(true, false, true, now);
+        assert_eq!(
+            opt_in.evaluate(&h, TradingMode::Live, now).mode,
+            DegradedMode::PaperFallback
+        );
+        assert!(DegradedMode::PaperFallback.allows_entries());
+        assert!(!DegradedMode::ExitOnly.allows_entries());
+        assert!(DegradedMode::ExitOnly.allows_exits());
+        assert!(!DegradedMode::Hold.allows_exits());
+    }
+
+    #[test]
+    fn test_monitor_reports_changes_and_latches_halt() {
+        let policy = DegradationPolicy {
+            no_prices: DegradedMode::Halt,
+            ..Default::default()
+        };
+        let now = Utc::now();
+        let mut monitor = DegradationMonitor::default();
+
+        let ok = policy.evaluate(&health(true, true, true, now), TradingMode::Paper, now);
+        assert_eq!(monitor.observe(ok.clone()), None);
+
+        let no_prices = policy.evaluate(&health(true, true, false, now), TradingMode::Paper, now);
+        assert_eq!(monitor.observe(no_prices), Some(DegradedMode::Normal));
+        assert_eq!(monitor.mode(), DegradedMode::Halt);
+
+        // Prices are back, but the halt holds until resumed
+        assert_eq!(monitor.observe(ok), Some(DegradedMode::Halt));
+        assert_eq!(monitor.mode(), DegradedMode::Halt);
+        assert!(monitor.failed().is_empty());
+        assert!(monitor.resume());
+        assert_eq!(monitor.mode(), DegradedMode::Normal);
+        assert!(!monitor.resume());
+    }
+}
@@ -0,0 +1,101 @@
+//! Max drawdown check against an equity high-water mark
+//!
+//! The runner feeds in total equity after marking the portfolio to market
+//! each decision tick. Drawdown is how far equity sits below the highest
+//! value seen so far; beyond `max_drawdown_percent` new buys are blocked
+//! until equity climbs back inside the limit. Sells and exits keep working
+//! so positions can still be cut.
+
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct DrawdownTracker {
+    /// Highest total equity seen (zero before the first observation)
+    pub high_water: Decimal,
+    /// Whether drawdown is currently past the cap
+    pub breached: bool,
+}
+
+/// Change in breach state from one observation to the next
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DrawdownTransition {
+    Breached {
+        drawdown_pct: Decimal,
+        high_water: Decimal,
+        equity: Decimal,
+    },
+    Recovered {
+        drawdown_pct: Decimal,
+    },
+}
+
+impl DrawdownTracker {
+    /// Percent below the high-water mark (0 at or above it)
+    pub fn drawdown_pct(&self, equity: Decimal) -> Decimal {
+        if self.high_water <= Decimal::ZERO || equity >= self.high_water {
+            return Decimal::ZERO;
+        }
+        (self.high_water - equity) / self.high_water * Decimal::from(100)
+    }
+
+    /// Record `equity`, raising the high-water mark if it's a new high
+    pub fn observe(
+        &mut self,
+        equity: Decimal,
+        max_drawdown_percent: i32,
+    ) -> Option<DrawdownTransition> {
+        if equity > self.high_water {
+            self.high_water = equity;
+        }
+        let drawdown_pct = self.drawdown_pct(equity);
+        let over = drawdown_pct > Decimal::from(max_drawdown_percent);
+        if over == self.breached {
+            return None;
+        }
+        self.breached = over;
+        Some(if over {
+            DrawdownTransition::Breached {
+                drawdown_pct,
+                high_water: self.high_water,
+                equity,
+            }
+        } else {
+            DrawdownTransition::Recovered { drawdown_pct }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_breaches_and_recovers() {
+        let mut tracker = DrawdownTracker::default();
+        assert_eq!(tracker.observe(Decimal::from(10000), 10), None);
+        assert_eq!(tracker.observe(Decimal::from(12000), 10), None);
+        assert_eq!(tracker.high_water, Decimal::from(12000));
+
+        // 10% down is still inside the cap
+        assert_eq!(tracker.observe(Decimal::from(10800), 10), None);
+        assert_eq!(
+            tracker.observe(Decimal::from(10680), 10),
+            Some(DrawdownTransition::Breached {
+                drawdown_pct: Decimal::from(11),
+                high_water: Decimal::from(12000),
+                equity: Decimal::from(10680),
+            })
+        );
+        assert!(tracker.breached);
+        assert_eq!(tracker.observe(Decimal::from(10000), 10), None);
+
+        assert_eq!(
+            tracker.observe(Decimal::from(11400), 10),
+            Some(DrawdownTransition::Recovered {
+                drawdown_pct: Decimal::from(5),
+            })
+        );
+        assert!(!tracker.breached);
+    }
+}
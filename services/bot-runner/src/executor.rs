@@ -9,7 +9,17 @@ use tokio::process::Command as TokioCommand;
 use tokio::time::timeout;
 use tracing::{debug, error, info, warn};
 
+use crate::amount::from_raw_amount;
 use crate::config::{ExecutionConfig, TradingMode};
+use crate::price_grpc::{pb, PriceGrpcClient};
+use crate::types::{FundingSnapshot, MarketRegime, PriceQuote, Regime, SentimentSnapshot};
+
+pub const USDC_MINT: &str = "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v";
+pub const SOL_MINT: &str = "So11111111111111111111111111111111111111112";
+
+/// Solana's base fee for a single-signature transaction, used when the swap
+/// response doesn't break its fees down (and for paper fills)
+pub const BASE_NETWORK_FEE_LAMPORTS: u64 = 5_000;
 
 // ==================== QUOTE CACHE ====================
 
@@ -211,6 +221,8 @@ pub struct ExecutionData {
     pub out_amount_raw: u64,
     pub realized_price: Decimal,
     pub slippage_bps_estimate: Option<u32>,
+    /// Signature plus priority fees paid for the transaction
+    pub network_fee_lamports: u64,
 }
 
 #[derive(Debug, Clone)]
@@ -224,6 +236,8 @@ pub struct TradeError {
 #[derive(Clone)]
 pub struct TradeExecutor {
     data_retrieval_url: String,
+    /// Preferred for market prices when set; HTTP is the fallback
+    price_grpc: Option<PriceGrpcClient>,
     solana_rpc_url: String,
     http_client: reqwest::Client,
     claw_trader_path: PathBuf,
@@ -256,6 +270,7 @@ impl TradeExecutor {
 
         Ok(Self {
             data_retrieval_url: data_retrieval_url.to_string(),
+            price_grpc: None,
             solana_rpc_url: solana_rpc_url.to_string(),
             http_client: reqwest::Client::new(),
             claw_trader_path,
@@ -266,8 +281,25 @@ impl TradeExecutor {
         })
     }
 
+    /// Look market prices up over data-retrieval's gRPC service at `url`
+    pub fn with_price_grpc(mut self, url: &str) -> anyhow::Result<Self> {
+        self.price_grpc = Some(PriceGrpcClient::connect_lazy(url)?);
+        Ok(self)
+    }
+
+    /// Decimals for `mint`, looked up in the token list if not yet known
+    pub async fn mint_decimals(&self, mint: &str) -> anyhow::Result<u8> {
+        crate::mints::resolve(&self.http_client, mint).await
+    }
+
+    /// Symbol, decimals and verification of `mint`, looked up in the token
+    /// list if not yet known
+    pub async fn mint_metadata(&self, mint: &str) -> anyhow::Result<crate::mints::TokenMetadata> {
+        crate::mints::resolve_metadata(&self.http_client, mint).await
+    }
+
     /// Check if claw-trader is available
-    fn is_claw_trader_available(&self) -> bool {
+    pub fn is_claw_trader_available(&self) -> bool {
         self.claw_trader_path.exists()
     }
 
@@ -412,6 +444,7 @@ impl TradeExecutor {
                     .unwrap_or(0),
                 price_impact_pct: result["priceImpactPct"].as_f64().unwrap_or(0.0),
                 fee_bps: result["feeBps"].as_u64().unwrap_or(69),
+                priced_at: chrono::Utc::now(),
             };
             price
         };
@@ -442,6 +475,9 @@ impl TradeExecutor {
         if response.status().is_success() {
             let data: PriceResponse = response.json().await?;
             let price: f64 = data.price.parse()?;
+            let priced_at = chrono::DateTime::parse_from_rfc3339(&data.timestamp)
+                .map_err(|e| anyhow::anyhow!("Unreadable price timestamp: {}", e))?
+                .with_timezone(&chrono::Utc);
 
             return Ok(ClawTraderPrice {
                 input_mint: input_mint.to_string(),
@@ -450,12 +486,250 @@ impl TradeExecutor {
                 out_amount: (price * 1_000_000.0) as u64, // USDC has 6 decimals
                 price_impact_pct: 0.0,
                 fee_bps: 69,
+                priced_at,
             });
         }
 
         Err(anyhow::anyhow!("Price fetch failed: {}", response.status()))
     }
 
+    /// Current USD quote for an asset, with 24h change when available
+    ///
+    /// Asks data-retrieval by symbol first (it has the 24h change); if that
+    /// fails, prices one whole token against USDC via `fetch_price`.
+    pub async fn fetch_price_quote(&self, mint: &str, symbol: &str) -> anyhow::Result<PriceQuote> {
+        let (price, liquidity_usd) =
+            tokio::join!(self.fetch_market_price(symbol), self.token_liquidity(mint));
+        match price {
+            Ok(data) => {
+                let market = data.market.unwrap_or_default();
+                Ok(PriceQuote {
+                    mint: mint.to_string(),
+                    symbol: symbol.to_string(),
+                    price_usd: data.price,
+                    change_24h_pct: market.change_24h_pct,
+                    regime: market.regime,
+                    timestamp: data.timestamp,
+                    source: if data.stale {
+                        format!("{} (stale)", data.source)
+                    } else {
+                        data.source
+                    },
+                    unreliable: data.unreliable,
+                    liquidity_usd,
+                })
+            }
+            Err(e) => {
+                debug!(
+                    "data-retrieval price for {} unavailable ({}), using swap quote",
+                    symbol, e
+                );
+                let decimals = self.mint_decimals(mint).await?;
+                let quote = self
+                    .fetch_price(mint, USDC_MINT, 10u64.pow(decimals as u32))
+                    .await?;
+                if quote.in_amount == 0 {
+                    return Err(anyhow::anyhow!("Empty swap quote for {}", symbol));
+                }
+                let price_usd =
+                    from_raw_amount(quote.out_amount, self.mint_decimals(USDC_MINT).await?)
+                        / from_raw_amount(quote.in_amount, decimals);
+                Ok(PriceQuote {
+                    mint: mint.to_string(),
+                    symbol: symbol.to_string(),
+                    price_usd,
+                    change_24h_pct: None,
+                    regime: None,
+                    timestamp: chrono::Utc::now(),
+                    source: "swap_quote".to_string(),
+                    unreliable: false,
+                    liquidity_usd,
+                })
+            }
+        }
+    }
+
+    /// GetPrice over gRPC if configured, else (or if that fails)
+    /// GET /prices?symbol= on data-retrieval
+    async fn fetch_market_price(&self, symbol: &str) -> anyhow::Result<MarketPriceResponse> {
+        if let Some(grpc) = &self.price_grpc {
+            match grpc
+                .get_price(symbol)
+                .await
+                .and_then(MarketPriceResponse::try_from)
+            {
+                Ok(price) => return Ok(price),
+                Err(e) => debug!("gRPC price for {} failed ({}), using HTTP", symbol, e),
+            }
+        }
+
+        let url = format!("{}/prices", self.data_retrieval_url);
+        let response = timeout(
+            Duration::from_secs(10),
+            self.http_client
+                .get(&url)
+                .query(&[("symbol", symbol)])
+                .send(),
+        )
+        .await
+        .map_err(|_| anyhow::anyhow!("Price fetch timed out after 10 seconds"))??;
+
+        if !response.status().is_success() {
+            return Err(anyhow::anyhow!("Price fetch failed: {}", response.status()));
+        }
+        Ok(response.json().await?)
+    }
+
+    /// Depth estimate for trading `notional_usd` of `symbol` (or a mint),
+    /// from data-retrieval's `/liquidity`
+    pub async fn fetch_liquidity(
+        &self,
+        symbol: &str,
+        side: TradeSide,
+        notional_usd: Decimal,
+        max_slippage_bps: u32,
+    ) -> anyhow::Result<LiquidityEstimate> {
+        let side = match side {
+            TradeSide::Buy => "buy",
+            TradeSide::Sell => "sell",
+        };
+        let url = format!("{}/liquidity", self.data_retrieval_url);
+        let response = timeout(
+            Duration::from_secs(10),
+            self.http_client
+                .get(&url)
+                .query(&[
+                    ("symbol", symbol),
+                    ("side", side),
+                    ("notional_usd", &notional_usd.to_string()),
+                    ("max_slippage_bps", &max_slippage_bps.to_string()),
+                ])
+                .send(),
+        )
+        .await
+        .map_err(|_| anyhow::anyhow!("Liquidity fetch timed out after 10 seconds"))??;
+
+        if !response.status().is_success() {
+            return Err(anyhow::anyhow!(
+                "Liquidity fetch failed: {}",
+                response.status()
+            ));
+        }
+        let report: LiquidityResponse = response.json().await?;
+        Ok(LiquidityEstimate {
+            source: report.source,
+            slippage_bps: report.estimate.slippage_bps,
+            max_notional_usd: report.max_notional_usd.unwrap_or(notional_usd),
+        })
+    }
+
+    /// Funding rate and open interest of `symbol`'s perpetual, None when
+    /// there isn't one
+    pub async fn fetch_funding(&self, symbol: &str) -> anyhow::Result<Option<FundingSnapshot>> {
+        let url = format!("{}/derivatives/{}", self.data_retrieval_url, symbol);
+        let response = timeout(Duration::from_secs(10), self.http_client.get(&url).send())
+            .await
+            .map_err(|_| anyhow::anyhow!("Funding fetch timed out after 10 seconds"))??;
+
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        if !response.status().is_success() {
+            return Err(anyhow::anyhow!(
+                "Funding fetch failed: {}",
+                response.status()
+            ));
+        }
+        Ok(Some(response.json().await?))
+    }
+
+    /// Sentiment summary for `symbol`, None for assets data-retrieval has
+    /// no sentiment for
+    pub async fn fetch_sentiment(&self, symbol: &str) -> anyhow::Result<Option<SentimentSnapshot>> {
+        let url = format!("{}/sentiment/{}", self.data_retrieval_url, symbol);
+        let response = timeout(Duration::from_secs(10), self.http_client.get(&url).send())
+            .await
+            .map_err(|_| anyhow::anyhow!("Sentiment fetch timed out after 10 seconds"))??;
+
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        if !response.status().is_success() {
+            return Err(anyhow::anyhow!(
+                "Sentiment fetch failed: {}",
+                response.status()
+            ));
+        }
+        let summary: SentimentResponse = response.json().await?;
+        Ok(Some(SentimentSnapshot {
+            symbol: summary.symbol,
+            score: summary.score,
+            label: summary.label,
+            fear_greed: summary.fear_greed.map(|f| f.value),
+            news_posts: summary.news.map(|n| n.posts),
+        }))
+    }
+
+    /// Pool liquidity, holders and authorities of `mint` from data-retrieval
+    pub async fn fetch_token_analytics(&self, mint: &str) -> anyhow::Result<TokenAnalytics> {
+        let url = format!("{}/tokens/{}/analytics", self.data_retrieval_url, mint);
+        let response = timeout(Duration::from_secs(10), self.http_client.get(&url).send())
+            .await
+            .map_err(|_| anyhow::anyhow!("Token analytics timed out after 10 seconds"))??;
+
+        if !response.status().is_success() {
+            return Err(anyhow::anyhow!(
+                "Token analytics failed: {}",
+                response.status()
+            ));
+        }
+        Ok(response.json().await?)
+    }
+
+    /// Pool liquidity of `mint`, for tokens outside the built-in table
+    async fn token_liquidity(&self, mint: &str) -> Option<Decimal> {
+        if crate::amount::get_token_info(mint).is_some() {
+            return None;
+        }
+        match self.fetch_token_analytics(mint).await {
+            Ok(analytics) => analytics.liquidity_usd,
+            Err(e) => {
+                debug!("No liquidity for {}: {}", mint, e);
+                None
+            }
+        }
+    }
+
+    /// On-chain verdict on buying `mint`: denied below `min_liquidity_usd`,
+    /// warned about live authorities and concentrated holders
+    ///
+    /// None for built-in tokens and when the analytics can't be fetched.
+    async fn onchain_shield(&self, mint: &str) -> Option<ShieldCheck> {
+        if crate::amount::get_token_info(mint).is_some() {
+            return None;
+        }
+        let analytics = match self.fetch_token_analytics(mint).await {
+            Ok(analytics) => analytics,
+            Err(e) => {
+                debug!("No on-chain analytics for {}: {}", mint, e);
+                return None;
+            }
+        };
+        Some(analytics.shield(self.execution_config.min_liquidity_usd))
+    }
+
+    /// Why `mint` doesn't count as verified, or None if it does
+    async fn unverified_reason(&self, mint: &str) -> Option<String> {
+        match self.mint_metadata(mint).await {
+            Ok(metadata) if metadata.verified => None,
+            Ok(metadata) => Some(format!(
+                "{} ({}) isn't verified in the token list",
+                metadata.symbol, mint
+            )),
+            Err(e) => Some(format!("{} couldn't be checked: {}", mint, e)),
+        }
+    }
+
     /// Run risk check (shield) on a token
     pub async fn shield_check(&self, mint: &str) -> anyhow::Result<ShieldCheck> {
         if !self.is_claw_trader_available() {
@@ -521,6 +795,40 @@ impl TradeExecutor {
             shield_result: None,
         };
 
+        // Amounts can't be converted without both mints' decimals
+        for mint in [input_mint, output_mint] {
+            if let Err(e) = self.mint_decimals(mint).await {
+                result.stage_reached = TradeStage::Blocked;
+                result.error = Some(TradeError {
+                    stage: "quote".to_string(),
+                    code: "unknown_decimals".to_string(),
+                    message: format!("Decimals unknown for {}: {}", mint, e),
+                });
+                warn!("Refusing to trade {}: decimals unknown ({})", mint, e);
+                return result;
+            }
+        }
+
+        // Unverified tokens are refused when the config says so, and
+        // otherwise logged if their metadata is already known
+        if self.execution_config.verified_mints_only {
+            if let Some(reason) = self.unverified_reason(output_mint).await {
+                result.stage_reached = TradeStage::Blocked;
+                result.error = Some(TradeError {
+                    stage: "quote".to_string(),
+                    code: "unverified_mint".to_string(),
+                    message: reason.clone(),
+                });
+                warn!("Refusing to buy {}: {}", output_mint, reason);
+                return result;
+            }
+        } else if let Some(token) = crate::mints::metadata(output_mint).filter(|t| !t.verified) {
+            warn!(
+                "Buying {} ({}), which isn't verified in the token list",
+                token.symbol, output_mint
+            );
+        }
+
         // Run shield check first
         match self.shield_check(input_mint).await {
             Ok(shield) => {
@@ -553,6 +861,28 @@ impl TradeExecutor {
             }
         }
 
+        // Pools, holders and authorities of the token being bought
+        if let Some(onchain) = self.onchain_shield(output_mint).await {
+            if !onchain.safe {
+                result.stage_reached = TradeStage::Blocked;
+                result.error = Some(TradeError {
+                    stage: "shield".to_string(),
+                    code: "illiquid".to_string(),
+                    message: onchain.message.clone(),
+                });
+                warn!("Refusing to buy {}: {}", output_mint, onchain.message);
+                result.shield_result = Some(onchain);
+                return result;
+            }
+            if onchain.verdict == ShieldVerdict::Warn {
+                warn!("Buying {} despite {:?}", output_mint, onchain.warnings);
+                if let Some(shield) = result.shield_result.as_mut() {
+                    shield.verdict = ShieldVerdict::Warn;
+                    shield.warnings.extend(onchain.warnings);
+                }
+            }
+        }
+
         // Get price quote
         let price_quote = match self.fetch_price(input_mint, output_mint, amount).await {
             Ok(quote) => {
@@ -575,6 +905,25 @@ impl TradeExecutor {
             }
         };
 
+        // No trading on a price that's gone stale, cached or not
+        let age_secs = price_quote.age_secs(chrono::Utc::now());
+        if age_secs > self.execution_config.max_price_age_secs as i64 {
+            result.stage_reached = TradeStage::Blocked;
+            result.error = Some(TradeError {
+                stage: "quote".to_string(),
+                code: "stale_price".to_string(),
+                message: format!(
+                    "Price is {}s old, max {}s",
+                    age_secs, self.execution_config.max_price_age_secs
+                ),
+            });
+            warn!(
+                "Refusing to trade {} -> {} on a {}s old price",
+                input_mint, output_mint, age_secs
+            );
+            return result;
+        }
+
         // Check price impact against config (not hardcoded)
         if price_quote.price_impact_pct > self.execution_config.max_price_impact_pct {
             result.stage_reached = TradeStage::Blocked;
@@ -614,6 +963,66 @@ impl TradeExecutor {
         result
     }
 
+    /// Execute a trade as `slices` child swaps spread over `window`
+    ///
+    /// Children are spaced evenly, the first going out straight away, and
+    /// run through [`Self::execute_trade`] one at a time under ids
+    /// `{intent_id}-slice-{n}`. The first child that doesn't confirm stops
+    /// the rest. Returns the consolidated parent result and fill stats (see
+    /// [`crate::twap`]).
+    #[allow(clippy::too_many_arguments)]
+    pub async fn execute_twap(
+        &self,
+        intent_id: &str,
+        input_mint: &str,
+        output_mint: &str,
+        amount: u64,
+        side: TradeSide,
+        trading_mode: TradingMode,
+        slices: u32,
+        window: Duration,
+    ) -> (NormalizedTradeResult, crate::twap::TwapFill) {
+        let children = crate::twap::split_amount(amount, slices);
+        let spacing = window / children.len().max(1) as u32;
+        info!(
+            "TWAP {}: {} slices over {}s",
+            intent_id,
+            children.len(),
+            window.as_secs()
+        );
+
+        let mut results = Vec::with_capacity(children.len());
+        for (n, child_amount) in children.iter().enumerate() {
+            if n > 0 {
+                tokio::time::sleep(spacing).await;
+            }
+            let child_id = format!("{}-slice-{}", intent_id, n + 1);
+            let result = self
+                .execute_trade(
+                    &child_id,
+                    input_mint,
+                    output_mint,
+                    *child_amount,
+                    side,
+                    trading_mode,
+                )
+                .await;
+            let confirmed = result.stage_reached == TradeStage::Confirmed;
+            results.push(result);
+            if !confirmed {
+                warn!(
+                    "TWAP {} stopped at slice {}/{}",
+                    intent_id,
+                    n + 1,
+                    children.len()
+                );
+                break;
+            }
+        }
+
+        crate::twap::consolidate(intent_id, amount, children.len() as u32, &results)
+    }
+
     /// Execute a paper trade (simulated)
     async fn execute_paper_trade(
         &self,
@@ -650,6 +1059,7 @@ impl TradeExecutor {
                 Decimal::ZERO
             },
             slippage_bps_estimate: Some(self.execution_config.max_slippage_bps),
+            network_fee_lamports: BASE_NETWORK_FEE_LAMPORTS,
         };
     }
 
@@ -662,11 +1072,16 @@ impl TradeExecutor {
         amount: u64,
         price_quote: &ClawTraderPrice,
     ) {
+        // Simulating instead is the degradation policy's call, not ours
         if !self.is_claw_trader_available() {
-            warn!("claw-trader not available, falling back to paper trade");
-            return self
-                .execute_paper_trade(result, input_mint, output_mint, amount, price_quote)
-                .await;
+            result.stage_reached = TradeStage::Failed;
+            result.error = Some(TradeError {
+                stage: "swap".to_string(),
+                code: "claw_trader_unavailable".to_string(),
+                message: format!("claw-trader not found at {:?}", self.claw_trader_path),
+            });
+            warn!("claw-trader not available, live trade not sent");
+            return;
         }
 
         if !self.keypair_path.exists() {
@@ -787,6 +1202,21 @@ impl TradeExecutor {
                 0
             };
 
+            // Jupiter reports the fees it set on the transaction
+            let lamports = |key: &str| {
+                let value = &order[key];
+                value
+                    .as_u64()
+                    .or_else(|| value.as_str().and_then(|s| s.parse().ok()))
+            };
+            let network_fee_lamports = match (
+                lamports("signatureFeeLamports"),
+                lamports("prioritizationFeeLamports"),
+            ) {
+                (None, None) => BASE_NETWORK_FEE_LAMPORTS,
+                (signature, priority) => signature.unwrap_or(0) + priority.unwrap_or(0),
+            };
+
             result.stage_reached = TradeStage::Confirmed;
             result.signature = Some(tx_hash);
             result.execution = ExecutionData {
@@ -797,6 +1227,7 @@ impl TradeExecutor {
                     Decimal::ZERO
                 },
                 slippage_bps_estimate: Some(slippage_bps),
+                network_fee_lamports,
             };
         } else {
             let error_msg = swap_result["error"]["message"]
@@ -847,6 +1278,108 @@ struct PriceResponse {
     timestamp: String,
 }
 
+/// data-retrieval's `/prices` response (the fields we use)
+#[derive(Debug, Deserialize)]
+struct MarketPriceResponse {
+    price: Decimal,
+    source: String,
+    timestamp: chrono::DateTime<chrono::Utc>,
+    #[serde(default)]
+    stale: bool,
+    #[serde(default)]
+    unreliable: bool,
+    #[serde(default)]
+    market: Option<MarketChange>,
+}
+
+/// data-retrieval's `/liquidity` response (the fields we use)
+#[derive(Debug, Deserialize)]
+struct LiquidityResponse {
+    source: String,
+    estimate: LiquiditySlippage,
+    #[serde(default)]
+    max_notional_usd: Option<Decimal>,
+}
+
+#[derive(Debug, Deserialize)]
+struct LiquiditySlippage {
+    slippage_bps: f64,
+}
+
+/// data-retrieval's `/sentiment/{symbol}` response (the fields we use)
+#[derive(Debug, Deserialize)]
+struct SentimentResponse {
+    symbol: String,
+    score: f64,
+    label: String,
+    #[serde(default)]
+    fear_greed: Option<FearGreedReading>,
+    #[serde(default)]
+    news: Option<NewsReading>,
+}
+
+#[derive(Debug, Deserialize)]
+struct FearGreedReading {
+    value: u8,
+}
+
+#[derive(Debug, Deserialize)]
+struct NewsReading {
+    posts: usize,
+}
+
+/// How much of a notional the market absorbs within a slippage budget
+#[derive(Debug, Clone, PartialEq)]
+pub struct LiquidityEstimate {
+    /// `binance`, `coinbase` (order books) or `jupiter` (route quote)
+    pub source: String,
+    /// Estimated slippage of the full notional
+    pub slippage_bps: f64,
+    /// Largest notional up to the asked one within the budget
+    pub max_notional_usd: Decimal,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct MarketChange {
+    #[serde(default)]
+    change_24h_pct: Option<f64>,
+    #[serde(default)]
+    regime: Option<MarketRegime>,
+}
+
+impl TryFrom<pb::Price> for MarketPriceResponse {
+    type Error = anyhow::Error;
+
+    fn try_from(p: pb::Price) -> anyhow::Result<Self> {
+        let market = p.market.map(|m| MarketChange {
+            change_24h_pct: m.change_24h_pct,
+            regime: m.regime.and_then(|r| {
+                let regime = match r.regime() {
+                    pb::Regime::Trending => Regime::Trending,
+                    pb::Regime::Ranging => Regime::Ranging,
+                    pb::Regime::HighVolatility => Regime::HighVolatility,
+                    pb::Regime::Unspecified => return None,
+                };
+                Some(MarketRegime {
+                    regime,
+                    efficiency_ratio: r.efficiency_ratio,
+                    trend_pct: r.trend_pct,
+                    volatility_pct: r.volatility_pct,
+                })
+            }),
+        });
+        Ok(Self {
+            price: p.price.parse()?,
+            source: p.source,
+            timestamp: chrono::DateTime::from_timestamp_millis(p.timestamp_ms)
+                .ok_or_else(|| anyhow::anyhow!("Bad price timestamp {}", p.timestamp_ms))?,
+            stale: p.stale,
+            unreliable: p.unreliable,
+            market,
+        })
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct ClawTraderPrice {
     pub input_mint: String,
@@ -855,6 +1388,16 @@ pub struct ClawTraderPrice {
     pub out_amount: u64,
     pub price_impact_pct: f64,
     pub fee_bps: u64,
+    /// When the price behind the quote was observed (a cached quote keeps
+    /// its original time)
+    pub priced_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl ClawTraderPrice {
+    /// Seconds between when the price was observed and `now`
+    pub fn age_secs(&self, now: chrono::DateTime<chrono::Utc>) -> i64 {
+        (now - self.priced_at).num_seconds().max(0)
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -865,6 +1408,50 @@ pub struct ShieldCheck {
     pub message: String,
 }
 
+/// data-retrieval's `/tokens/{mint}/analytics` response (the fields we use)
+#[derive(Debug, Clone, Deserialize)]
+pub struct TokenAnalytics {
+    pub mint: String,
+    #[serde(default)]
+    pub liquidity_usd: Option<Decimal>,
+    /// `low_liquidity`, `concentrated_holders`, `mint_authority_active`,
+    /// `freeze_authority_active`
+    #[serde(default)]
+    pub flags: Vec<String>,
+}
+
+impl TokenAnalytics {
+    /// Deny below `min_liquidity_usd` of pool liquidity, otherwise warn on
+    /// any flag data-retrieval raised
+    pub fn shield(&self, min_liquidity_usd: f64) -> ShieldCheck {
+        let min = Decimal::try_from(min_liquidity_usd).unwrap_or(Decimal::ZERO);
+        if let Some(liquidity) = self.liquidity_usd.filter(|l| *l < min) {
+            return ShieldCheck {
+                safe: false,
+                verdict: ShieldVerdict::Deny,
+                warnings: self.flags.clone(),
+                message: format!(
+                    "{} pools hold ${}, under the ${} minimum",
+                    self.mint,
+                    liquidity.round_dp(0),
+                    min.round_dp(0)
+                ),
+            };
+        }
+        let verdict = if self.flags.is_empty() {
+            ShieldVerdict::Allow
+        } else {
+            ShieldVerdict::Warn
+        };
+        ShieldCheck {
+            safe: true,
+            verdict,
+            warnings: self.flags.clone(),
+            message: "OK".to_string(),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ShieldVerdict {
     Allow,
@@ -942,27 +1529,76 @@ pub fn symbol_to_mint(symbol: &str) -> Option<&'static str> {
     }
 }
 
-/// Get decimals for a token
-pub fn get_token_decimals(mint: &str) -> u8 {
-    match mint {
-        "So11111111111111111111111111111111111111112" => 9, // SOL
-        "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v" => 6, // USDC
-        "qfnqNLS3x2K5R3oCmS1NjwiKOK8Tq77pCH6zTX8mR2F" => 8, // WBTC
-        "7vfCXTUXx5WJV5JADk17DUJ4ksgau7utNKj4b963voxs" => 8, // WETH
-        "DezXAZ8z7PnrnRJjz3wXBoRgixCa6xjnB7YaB1pPB263" => 5, // BONK
-        "EKpQGSJtjMFqKZ9KQbSqL2zPQCpA5xZKN2CjeJRdQpump" => 6, // WIF
-        _ => 6,                                             // Default to 6
-    }
+/// Get decimals for a token, if known (see [`crate::mints`])
+pub fn get_token_decimals(mint: &str) -> Option<u8> {
+    crate::mints::decimals(mint)
 }
 
-/// Convert human-readable amount to raw amount
-pub fn to_raw_amount(amount: Decimal, decimals: u8) -> u64 {
-    let multiplier = Decimal::from(10u64.pow(decimals as u32));
-    (amount * multiplier).to_u64().unwrap_or(0)
-}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_grpc_price_converts_to_market_price() {
+        let price = pb::Price {
+            symbol: "SOL/USD".to_string(),
+            price: "142.12345678".to_string(),
+            source: "binance".to_string(),
+            timestamp_ms: 1_773_144_000_000,
+            confidence: Some(0.9),
+            stale: true,
+            unreliable: false,
+            market: Some(pb::Market {
+                change_24h_pct: Some(-3.5),
+                regime: Some(pb::MarketRegime {
+                    regime: pb::Regime::Ranging as i32,
+                    efficiency_ratio: 0.2,
+                    trend_pct: -0.4,
+                    volatility_pct: 2.1,
+                }),
+                ..Default::default()
+            }),
+        };
+        let converted = MarketPriceResponse::try_from(price.clone()).unwrap();
+        assert_eq!(converted.price, Decimal::new(14_212_345_678, 8));
+        assert_eq!(converted.timestamp.timestamp_millis(), 1_773_144_000_000);
+        assert!(converted.stale);
+        let market = converted.market.unwrap();
+        assert_eq!(market.change_24h_pct, Some(-3.5));
+        assert_eq!(market.regime.unwrap().regime, Regime::Ranging);
+
+        let garbled = pb::Price {
+            price: "n/a".to_string(),
+            ..price
+        };
+        assert!(MarketPriceResponse::try_from(garbled).is_err());
+    }
+
+    #[test]
+    fn test_onchain_shield() {
+        let analytics: TokenAnalytics = serde_json::from_str(
+            r#"{"mint":"Meme111","liquidity_usd":"4200.5","pools":1,"top10_holder_pct":72.0,
+                "mint_authority":null,"flags":["low_liquidity","concentrated_holders"]}"#,
+        )
+        .unwrap();
+        let denied = analytics.shield(10_000.0);
+        assert!(!denied.safe);
+        assert_eq!(denied.verdict, ShieldVerdict::Deny);
+        assert_eq!(
+            denied.message,
+            "Meme111 pools hold $4200, under the $10000 minimum"
+        );
 
-/// Convert raw amount to human-readable
-pub fn from_raw_amount(amount: u64, decimals: u8) -> Decimal {
-    let divisor = Decimal::from(10u64.pow(decimals as u32));
-    Decimal::from(amount) / divisor
+        let warned = analytics.shield(1_000.0);
+        assert!(warned.safe);
+        assert_eq!(warned.verdict, ShieldVerdict::Warn);
+        assert_eq!(warned.warnings.len(), 2);
+
+        let unknown = TokenAnalytics {
+            liquidity_usd: None,
+            flags: Vec::new(),
+            ..analytics
+        };
+        assert_eq!(unknown.shield(10_000.0).verdict, ShieldVerdict::Allow);
+    }
 }
@@ -0,0 +1,107 @@
+//! Pre-flight funding check for live trading
+//!
+//! Before the first live trade the agent wallet must hold the minimum USDC to
+//! trade with and SOL for fees. Until it does, the runner skips decision ticks
+//! and reports `insufficient_funding` so the user knows what to send.
+
+use rust_decimal::Decimal;
+use std::collections::HashMap;
+
+use crate::amount::{from_raw_amount, get_token_info};
+use crate::config::FundingRequirements;
+
+/// Wallet balances compared against the live trading minimums
+#[derive(Debug, Clone, PartialEq)]
+pub struct FundingCheck {
+    pub usdc: Decimal,
+    pub sol: Decimal,
+    pub required: FundingRequirements,
+}
+
+impl FundingCheck {
+    /// Build a check from raw on-chain holdings (mint -> raw amount)
+    pub fn from_holdings(holdings: &HashMap<String, u64>, required: FundingRequirements) -> Self {
+        let balance = |symbol: &str| {
+            get_token_info(symbol)
+                .map(|token| {
+                    from_raw_amount(
+                        holdings.get(&token.mint).copied().unwrap_or(0),
+                        token.decimals,
+                    )
+                })
+                .unwrap_or(Decimal::ZERO)
+        };
+
+        Self {
+            usdc: balance("USDC"),
+            sol: balance("SOL"),
+            required,
+        }
+    }
+
+    pub fn is_funded(&self) -> bool {
+        self.usdc >= self.required.min_usdc && self.sol >= self.required.min_sol
+    }
+
+    /// Human readable list of what is missing
+    pub fn shortfall(&self) -> String {
+        let mut missing = Vec::new();
+        if self.usdc < self.required.min_usdc {
+            missing.push(format!(
+                "{} USDC (have {}, need {})",
+                self.required.min_usdc - self.usdc,
+                self.usdc,
+                self.required.min_usdc
+            ));
+        }
+        if self.sol < self.required.min_sol {
+            missing.push(format!(
+                "{} SOL (have {}, need {})",
+                self.required.min_sol - self.sol,
+                self.sol,
+                self.required.min_sol
+            ));
+        }
+        missing.join(", ")
+    }
+
+    /// Event metadata shared by `insufficient_funding` and `funding_confirmed`
+    pub fn metadata(&self, wallet_address: &str) -> serde_json::Value {
+        serde_json::json!({
+            "wallet_address": wallet_address,
+            "usdc_balance": self.usdc.to_string(),
+            "sol_balance": self.sol.to_string(),
+            "min_usdc": self.required.min_usdc.to_string(),
+            "min_sol": self.required.min_sol.to_string(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_funding_check_against_minimums() {
+        let required = FundingRequirements {
+            min_usdc: Decimal::from(10),
+            min_sol: Decimal::new(5, 2),
+        };
+        let usdc = get_token_info("USDC").unwrap().mint;
+        let sol = get_token_info("SOL").unwrap().mint;
+
+        // 25 USDC but only 0.01 SOL
+        let mut holdings = HashMap::from([(usdc, 25_000_000u64), (sol.clone(), 10_000_000)]);
+        let check = FundingCheck::from_holdings(&holdings, required);
+        assert_eq!(check.usdc, Decimal::from(25));
+        assert!(!check.is_funded());
+        assert_eq!(check.shortfall(), "0.04 SOL (have 0.01, need 0.05)");
+
+        holdings.insert(sol, 50_000_000);
+        let check = FundingCheck::from_holdings(&holdings, required);
+        assert!(check.is_funded());
+        assert!(check.shortfall().is_empty());
+
+        assert!(!FundingCheck::from_holdings(&HashMap::new(), required).is_funded());
+    }
+}
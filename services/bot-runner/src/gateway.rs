@@ -508,7 +508,6 @@ struct ExecutionRiskConfig {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use tempfile::tempdir;
 
     #[test]
     fn test_gateway_manager_creation() {
@@ -0,0 +1,156 @@
+//! Trading governor - halts decision ticks without stopping the process
+//!
+//! Two switches can halt trading:
+//! - a local kill-switch file (`$BOT_STATE_DIR/KILL_SWITCH` unless
+//!   `BOT_KILL_SWITCH_PATH` says otherwise), for an operator on the droplet;
+//!   its contents, if any, are used as the reason
+//! - the platform-wide halt flag the control plane sends with each sync
+//!
+//! The runner keeps syncing, reconciling and reporting while halted; only
+//! new decisions stop. The local switch wins when both are set.
+
+use std::path::{Path, PathBuf};
+
+const KILL_SWITCH_FILE: &str = "KILL_SWITCH";
+
+/// What is currently halting trading
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HaltSource {
+    KillSwitch,
+    ControlPlane,
+}
+
+impl HaltSource {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            HaltSource::KillSwitch => "kill_switch",
+            HaltSource::ControlPlane => "control_plane",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Halt {
+    pub source: HaltSource,
+    pub reason: String,
+}
+
+/// Change in governor state since the last check
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GovernorTransition {
+    Halted(Halt),
+    Resumed { previous: Halt },
+}
+
+pub struct Governor {
+    kill_switch_path: PathBuf,
+    /// Reason from the control plane when it has halted trading
+    remote_halt: Option<String>,
+    /// State as of the last `check`
+    halt: Option<Halt>,
+}
+
+impl Governor {
+    pub fn new(state_dir: &Path) -> Self {
+        let kill_switch_path = std::env::var("BOT_KILL_SWITCH_PATH")
+            .map(PathBuf::from)
+            .unwrap_or_else(|_| state_dir.join(KILL_SWITCH_FILE));
+        Self::with_kill_switch_path(kill_switch_path)
+    }
+
+    pub fn with_kill_switch_path(kill_switch_path: PathBuf) -> Self {
+        Self {
+            kill_switch_path,
+            remote_halt: None,
+            halt: None,
+        }
+    }
+
+    /// Record the control plane's halt flag from the latest sync
+    pub fn set_remote_halt(&mut self, halted: bool, reason: Option<String>) {
+        self.remote_halt = halted.then(|| {
+            reason
+                .filter(|r| !r.trim().is_empty())
+                .unwrap_or_else(|| "Trading halted by the platform".to_string())
+        });
+    }
+
+    /// Whether trading was halted as of the last `check`
+    pub fn is_paused(&self) -> bool {
+        self.halt.is_some()
+    }
+
+    pub fn halt(&self) -> Option<&Halt> {
+        self.halt.as_ref()
+    }
+
+    /// Re-evaluate both switches; returns the transition if the state changed
+    pub fn check(&mut self) -> Option<GovernorTransition> {
+        let current = self.evaluate();
+        if current == self.halt {
+            return None;
+        }
+        let previous = std::mem::replace(&mut self.halt, current.clone());
+        match (current, previous) {
+            (Some(halt), _) => Some(GovernorTransition::Halted(halt)),
+            (None, Some(previous)) => Some(GovernorTransition::Resumed { previous }),
+            (None, None) => None,
+        }
+    }
+
+    fn evaluate(&self) -> Option<Halt> {
+        if let Ok(contents) = std::fs::read_to_string(&self.kill_switch_path) {
+            let reason = contents.trim();
+            return Some(Halt {
+                source: HaltSource::KillSwitch,
+                reason: if reason.is_empty() {
+                    format!("Kill switch {} present", self.kill_switch_path.display())
+                } else {
+                    reason.to_string()
+                },
+            });
+        }
+        self.remote_halt.as_ref().map(|reason| Halt {
+            source: HaltSource::ControlPlane,
+            reason: reason.clone(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_kill_switch_and_remote_halt() {
+        let dir = tempfile::tempdir().unwrap();
+        let switch = dir.path().join(KILL_SWITCH_FILE);
+        let mut governor = Governor::with_kill_switch_path(switch.clone());
+        assert_eq!(governor.check(), None);
+        assert!(!governor.is_paused());
+
+        governor.set_remote_halt(true, None);
+        let Some(GovernorTransition::Halted(halt)) = governor.check() else {
+            panic!("expected halt");
+        };
+        assert_eq!(halt.source, HaltSource::ControlPlane);
+        // No repeat transition while nothing changes
+        assert_eq!(governor.check(), None);
+
+        // The local switch takes over, with its contents as the reason
+        std::fs::write(&switch, "manual stop\n").unwrap();
+        let Some(GovernorTransition::Halted(halt)) = governor.check() else {
+            panic!("expected kill switch halt");
+        };
+        assert_eq!(halt.source, HaltSource::KillSwitch);
+        assert_eq!(halt.reason, "manual stop");
+
+        std::fs::remove_file(&switch).unwrap();
+        governor.set_remote_halt(false, None);
+        assert!(matches!(
+            governor.check(),
+            Some(GovernorTransition::Resumed { previous }) if previous.source == HaltSource::KillSwitch
+        ));
+        assert!(!governor.is_paused());
+    }
+}
@@ -0,0 +1,146 @@
+//! State handover between droplets on redeploy
+//!
+//! When the control plane sends `export_state`, the runner stops trading,
+//! cancels anything not yet submitted and uploads the files below as a
+//! [`StateBundle`]. The replacement droplet has an empty state directory, so
+//! on first boot it downloads the bundle and writes the files back before
+//! the runner loads them. Per-decision journal files aren't carried: they
+//! have already been uploaded with each sync, and only the chain head is
+//! needed to keep the chain going.
+
+use anyhow::Context;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+use uuid::Uuid;
+
+pub const BUNDLE_VERSION: u32 = 1;
+
+/// Files carried across, relative to the state directory
+const TRANSFERRED_FILES: &[&str] = &[
+    "runner_state.json",
+    "exit_levels.json",
+    "recent_events.json",
+    "KILL_SWITCH",
+    "journal/chain_head.json",
+];
+
+/// Operator-written files, kept plaintext even when state is encrypted
+const PLAINTEXT_FILES: &[&str] = &["KILL_SWITCH"];
+
+/// Marks a state directory the runner has already written to
+const STATE_MARKER: &str = "runner_state.json";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StateBundle {
+    pub version: u32,
+    pub bot_id: Uuid,
+    pub exported_at: DateTime<Utc>,
+    /// File contents by path relative to the state directory, decrypted
+    pub files: BTreeMap<String, String>,
+}
+
+impl StateBundle {
+    /// Read whichever transferred files exist in `state_dir`
+    pub fn collect(state_dir: &Path, bot_id: Uuid) -> anyhow::Result<Self> {
+        let mut files = BTreeMap::new();
+        for name in TRANSFERRED_FILES {
+            let path = state_dir.join(name);
+            if !path.exists() {
+                continue;
+            }
+            let content = crate::sealed::read_to_string(&path)
+                .with_context(|| format!("reading {}", path.display()))?;
+            files.insert(name.to_string(), content);
+        }
+        Ok(Self {
+            version: BUNDLE_VERSION,
+            bot_id,
+            exported_at: Utc::now(),
+            files,
+        })
+    }
+
+    /// Write the bundle's files into `state_dir`; returns how many were written
+    ///
+    /// Refuses bundles from another bot or a newer format. Names outside the
+    /// transferred set are ignored rather than trusted as paths.
+    pub fn restore(&self, state_dir: &Path, bot_id: Uuid) -> anyhow::Result<usize> {
+        anyhow::ensure!(
+            self.bot_id == bot_id,
+            "state bundle belongs to bot {}",
+            self.bot_id
+        );
+        anyhow::ensure!(
+            self.version <= BUNDLE_VERSION,
+            "state bundle version {} is newer than this runner",
+            self.version
+        );
+
+        let mut written = 0;
+        for (name, content) in &self.files {
+            if !TRANSFERRED_FILES.contains(&name.as_str()) {
+                tracing::warn!("Skipping unexpected file in state bundle: {}", name);
+                continue;
+            }
+            let path = state_dir.join(name);
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            let result = if PLAINTEXT_FILES.contains(&name.as_str()) {
+                std::fs::write(&path, content)
+            } else {
+                crate::sealed::write(&path, content)
+            };
+            result.with_context(|| format!("writing {}", path.display()))?;
+            written += 1;
+        }
+        Ok(written)
+    }
+}
+
+/// Whether `state_dir` already holds runner state (so nothing is restored)
+pub fn has_local_state(state_dir: &Path) -> bool {
+    state_dir.join(STATE_MARKER).exists()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bundle_round_trip() {
+        let bot_id = Uuid::new_v4();
+        let old = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(old.path().join("journal/decisions")).unwrap();
+        std::fs::write(old.path().join("runner_state.json"), r#"{"trade_count":3}"#).unwrap();
+        std::fs::write(old.path().join("journal/chain_head.json"), r#"{"seq":7}"#).unwrap();
+        std::fs::write(old.path().join("journal/decisions/a.json"), "{}").unwrap();
+        std::fs::write(old.path().join("now.json"), "{}").unwrap();
+
+        let bundle = StateBundle::collect(old.path(), bot_id).unwrap();
+        assert_eq!(
+            bundle.files.keys().collect::<Vec<_>>(),
+            ["journal/chain_head.json", "runner_state.json"]
+        );
+
+        let new = tempfile::tempdir().unwrap();
+        assert!(!has_local_state(new.path()));
+        assert_eq!(bundle.restore(new.path(), bot_id).unwrap(), 2);
+        assert!(has_local_state(new.path()));
+        assert_eq!(
+            std::fs::read_to_string(new.path().join("journal/chain_head.json")).unwrap(),
+            r#"{"seq":7}"#
+        );
+
+        // Another bot's bundle and paths outside the set are refused
+        assert!(bundle.restore(new.path(), Uuid::new_v4()).is_err());
+        let mut tampered = bundle.clone();
+        tampered
+            .files
+            .insert("../escape.json".to_string(), "{}".to_string());
+        assert_eq!(tampered.restore(new.path(), bot_id).unwrap(), 2);
+        assert!(!new.path().join("../escape.json").exists());
+    }
+}
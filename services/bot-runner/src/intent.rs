@@ -41,6 +41,12 @@ pub struct IntentRegistry {
     max_age: Duration,
 }
 
+impl Default for IntentRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl IntentRegistry {
     pub fn new() -> Self {
         Self {
@@ -50,6 +56,7 @@ impl IntentRegistry {
     }
 
     /// Create a new trade intent
+    #[allow(clippy::too_many_arguments)]
     pub fn create(
         &mut self,
         bot_id: &str,
@@ -78,6 +85,7 @@ impl IntentRegistry {
     ///
     /// Per principal engineer feedback: include mode + version to prevent
     /// incorrectly suppressing legitimate repeated trades after config changes
+    #[allow(clippy::too_many_arguments)]
     pub fn create_with_version(
         &mut self,
         bot_id: &str,
@@ -118,6 +126,7 @@ impl IntentRegistry {
     ///
     /// This prevents race conditions between find_equivalent and create
     /// that could result in duplicate intents.
+    #[allow(clippy::too_many_arguments)]
     pub fn try_create(
         &mut self,
         bot_id: &str,
@@ -184,6 +193,29 @@ impl IntentRegistry {
         }
     }
 
+    /// Fail every intent that hasn't been submitted yet
+    ///
+    /// Submitted intents are already on-chain and are left to confirm or
+    /// fail. Returns how many were cancelled.
+    pub fn cancel_pending(&mut self, reason: &str) -> usize {
+        let mut cancelled = 0;
+        for intent in self.intents.values_mut() {
+            if matches!(
+                intent.state,
+                TradeIntentState::Created
+                    | TradeIntentState::ShieldCheckPassed
+                    | TradeIntentState::QuoteObtained
+            ) {
+                intent.state = TradeIntentState::Failed {
+                    stage: "cancelled".to_string(),
+                    error: reason.to_string(),
+                };
+                cancelled += 1;
+            }
+        }
+        cancelled
+    }
+
     /// Check if an equivalent intent already exists (idempotency check)
     ///
     /// Per principal engineer feedback, equivalence now includes:
@@ -246,6 +278,7 @@ impl IntentRegistry {
     }
 
     /// Internal equivalence check logic
+    #[allow(clippy::too_many_arguments)]
     fn is_intent_equivalent(
         &self,
         intent: &TradeIntent,
@@ -288,7 +321,7 @@ impl IntentRegistry {
         match &intent.state {
             TradeIntentState::Confirmed { .. } | TradeIntentState::Failed { .. } => {
                 debug!("Found equivalent finalized intent: {}", intent.id);
-                return true;
+                true
             }
             _ => {
                 // Pending intent, check if stale
@@ -348,12 +381,54 @@ impl TradeIntentFinalization {
     }
 }
 
-use uuid;
-
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_cancel_pending() {
+        let mut registry = IntentRegistry::new();
+        let queued = registry.create(
+            "bot-123",
+            "USDC_MINT",
+            "SOL_MINT",
+            10_000_000,
+            "live",
+            "trend",
+            0.8,
+            "entry",
+        );
+        let sent = registry.create(
+            "bot-123",
+            "USDC_MINT",
+            "WIF_MINT",
+            10_000_000,
+            "live",
+            "trend",
+            0.8,
+            "entry",
+        );
+        registry
+            .update_state(
+                &sent.id.to_string(),
+                TradeIntentState::Submitted {
+                    signature: "sig".to_string(),
+                },
+            )
+            .unwrap();
+
+        assert_eq!(registry.cancel_pending("kill_switch"), 1);
+        assert!(registry
+            .get_finalization(&queued.id.to_string())
+            .unwrap()
+            .is_finalized());
+        assert!(matches!(
+            registry.get(&sent.id.to_string()).unwrap().state,
+            TradeIntentState::Submitted { .. }
+        ));
+        assert_eq!(registry.cancel_pending("kill_switch"), 0);
+    }
+
     #[test]
     fn test_intent_lifecycle() {
         let mut registry = IntentRegistry::new();
@@ -0,0 +1,397 @@
+//! Initial inventory import for wallets with pre-existing activity
+//!
+//! A wallet funded from an exchange, or reused from elsewhere, already holds
+//! tokens the runner never bought. Once per bot, before the first
+//! reconciliation, the runner reads the wallet's recent transactions over RPC
+//! and seeds a position for each holding. Assuming first-in first-out, what is
+//! held now is the most recent inflows: if every one of those was bought for
+//! USDC or USDT in a single swap, the position gets those buys as its lots and
+//! cost basis. Otherwise (a transfer in, a token-for-token swap, history older
+//! than the scan) the position is marked `unknown_cost_basis`. The result is
+//! recorded as an `initial_inventory` event so the seeded basis can be
+//! audited.
+
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::time::Duration;
+use tracing::{debug, warn};
+
+use crate::amount::from_raw_amount;
+use crate::portfolio::Lot;
+
+/// Most recent signatures scanned
+pub const MAX_SIGNATURES: usize = 100;
+
+const SOL_MINT: &str = "So11111111111111111111111111111111111111112";
+const SOL_DECIMALS: u8 = 9;
+/// Stablecoins counted as the cash side of a buy (both 6 decimals)
+const CASH_MINTS: &[&str] = &[
+    "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v", // USDC
+    "Es9vMFrzaCERmJfrF4H2FYD4KCoNkY11McCe8BenwNYB", // USDT
+];
+const CASH_DECIMALS: u32 = 6;
+
+const RPC_TIMEOUT: Duration = Duration::from_secs(15);
+
+pub fn is_cash(mint: &str) -> bool {
+    CASH_MINTS.contains(&mint)
+}
+
+/// How a successful transaction changed the wallet's balances
+#[derive(Debug, Clone, PartialEq)]
+pub struct WalletTx {
+    pub signature: String,
+    pub block_time: Option<DateTime<Utc>>,
+    /// Raw balance change and decimals by mint (native SOL under the SOL
+    /// mint, with the network fee added back)
+    pub deltas: HashMap<String, (i128, u8)>,
+}
+
+impl WalletTx {
+    /// Parse a `getTransaction` result (jsonParsed encoding); None for
+    /// failed transactions
+    pub fn from_rpc(signature: &str, wallet: &str, tx: &Value) -> Option<Self> {
+        let meta = tx.get("meta")?;
+        if !meta.get("err").is_none_or(Value::is_null) {
+            return None;
+        }
+
+        let mut deltas: HashMap<String, (i128, u8)> = HashMap::new();
+        let balances = |key: &str, sign: i128, deltas: &mut HashMap<String, (i128, u8)>| {
+            for balance in meta[key].as_array().into_iter().flatten() {
+                if balance["owner"].as_str() != Some(wallet) {
+                    continue;
+                }
+                let (Some(mint), Some(amount), Some(decimals)) = (
+                    balance["mint"].as_str(),
+                    balance["uiTokenAmount"]["amount"]
+                        .as_str()
+                        .and_then(|a| a.parse::<i128>().ok()),
+                    balance["uiTokenAmount"]["decimals"].as_u64(),
+                ) else {
+                    continue;
+                };
+                let entry = deltas
+                    .entry(mint.to_string())
+                    .or_insert((0, decimals as u8));
+                entry.0 += sign * amount;
+            }
+        };
+        balances("preTokenBalances", -1, &mut deltas);
+        balances("postTokenBalances", 1, &mut deltas);
+
+        // Native SOL: the wallet's lamports, less the fee if it paid it
+        let keys = tx["transaction"]["message"]["accountKeys"].as_array();
+        let index = keys.and_then(|keys| {
+            keys.iter()
+                .position(|k| k.as_str().or_else(|| k["pubkey"].as_str()) == Some(wallet))
+        });
+        if let Some(index) = index {
+            let lamports = |key: &str| meta[key].get(index).and_then(Value::as_i64);
+            if let (Some(pre), Some(post)) = (lamports("preBalances"), lamports("postBalances")) {
+                let fee = if index == 0 {
+                    meta["fee"].as_i64().unwrap_or(0)
+                } else {
+                    0
+                };
+                let entry = deltas
+                    .entry(SOL_MINT.to_string())
+                    .or_insert((0, SOL_DECIMALS));
+                entry.0 += i128::from(post - pre + fee);
+            }
+        }
+        deltas.retain(|_, (delta, _)| *delta != 0);
+
+        Some(Self {
+            signature: signature.to_string(),
+            block_time: tx["blockTime"]
+                .as_i64()
+                .and_then(|t| DateTime::from_timestamp(t, 0)),
+            deltas,
+        })
+    }
+
+    /// Price per unit if this was a buy of `mint` for cash and nothing else
+    fn cash_price(&self, mint: &str) -> Option<Decimal> {
+        let cash_spent: i128 = self
+            .deltas
+            .iter()
+            .filter(|(m, _)| is_cash(m))
+            .map(|(_, (delta, _))| -delta)
+            .sum();
+        let mut bought = self
+            .deltas
+            .iter()
+            .filter(|(m, (delta, _))| !is_cash(m) && *delta > 0);
+        let (bought_mint, (quantity, decimals)) = bought.next()?;
+        if bought.next().is_some() || bought_mint != mint || cash_spent <= 0 {
+            return None;
+        }
+        let cash = Decimal::try_from_i128_with_scale(cash_spent, CASH_DECIMALS).ok()?;
+        let quantity = Decimal::try_from_i128_with_scale(*quantity, u32::from(*decimals)).ok()?;
+        Some(cash / quantity)
+    }
+}
+
+/// A holding as seeded into the portfolio
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ImportedAsset {
+    pub mint: String,
+    pub quantity_raw: u64,
+    pub decimals: Option<u8>,
+    /// Buys still held, oldest first; empty when the basis is unknown
+    pub lots: Vec<Lot>,
+    /// Cost per unit across the lots (None when unknown)
+    pub avg_entry_price_usdc: Option<Decimal>,
+    /// Transactions the lots came from
+    pub signatures: Vec<String>,
+    /// Why the basis couldn't be determined
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub unknown_reason: Option<&'static str>,
+}
+
+impl ImportedAsset {
+    pub fn unknown_cost_basis(&self) -> bool {
+        self.avg_entry_price_usdc.is_none()
+    }
+}
+
+/// Reconstruct the cost basis of each non-cash holding from `history`
+///
+/// `history` is newest first, as `getSignaturesForAddress` returns it.
+pub fn build(holdings: &HashMap<String, u64>, history: &[WalletTx]) -> Vec<ImportedAsset> {
+    let mut assets: Vec<ImportedAsset> = holdings
+        .iter()
+        .filter(|(mint, quantity)| !is_cash(mint) && **quantity > 0)
+        .map(|(mint, &quantity_raw)| seed(mint, quantity_raw, history))
+        .collect();
+    assets.sort_by(|a, b| a.mint.cmp(&b.mint));
+    assets
+}
+
+fn seed(mint: &str, quantity_raw: u64, history: &[WalletTx]) -> ImportedAsset {
+    let mut asset = ImportedAsset {
+        mint: mint.to_string(),
+        quantity_raw,
+        decimals: None,
+        lots: Vec::new(),
+        avg_entry_price_usdc: None,
+        signatures: Vec::new(),
+        unknown_reason: None,
+    };
+
+    let mut remaining = u128::from(quantity_raw);
+    let mut lots = Vec::new();
+    for tx in history {
+        if remaining == 0 {
+            break;
+        }
+        let Some(&(delta, decimals)) = tx.deltas.get(mint).filter(|(d, _)| *d > 0) else {
+            continue;
+        };
+        asset.decimals = Some(decimals);
+        let Some(price) = tx.cash_price(mint) else {
+            asset.unknown_reason = Some("not_bought_for_cash");
+            return asset;
+        };
+        let take = remaining.min(delta as u128);
+        remaining -= take;
+        lots.push(Lot {
+            quantity_raw: take as u64,
+            price_usdc: price,
+        });
+        asset.signatures.push(tx.signature.clone());
+    }
+    if remaining > 0 {
+        asset.unknown_reason = Some("history_incomplete");
+        return asset;
+    }
+
+    let Some(decimals) = asset.decimals else {
+        return asset;
+    };
+    lots.reverse();
+    let cost: Decimal = lots
+        .iter()
+        .map(|lot| from_raw_amount(lot.quantity_raw, decimals) * lot.price_usdc)
+        .sum();
+    asset.avg_entry_price_usdc = Some(cost / from_raw_amount(quantity_raw, decimals));
+    asset.lots = lots;
+    asset.signatures.reverse();
+    asset
+}
+
+async fn rpc(
+    http: &reqwest::Client,
+    url: &str,
+    method: &str,
+    params: Value,
+) -> anyhow::Result<Value> {
+    let response: Value = tokio::time::timeout(
+        RPC_TIMEOUT,
+        http.post(url)
+            .json(&serde_json::json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "method": method,
+                "params": params,
+            }))
+            .send(),
+    )
+    .await
+    .map_err(|_| anyhow::anyhow!("{} timed out", method))??
+    .error_for_status()?
+    .json()
+    .await?;
+    if let Some(error) = response.get("error") {
+        anyhow::bail!("{} failed: {}", method, error);
+    }
+    Ok(response["result"].clone())
+}
+
+/// The wallet's most recent transactions, newest first
+///
+/// History stops at the first transaction that can't be fetched, since
+/// skipping one would credit its inflows to older buys.
+pub async fn fetch_history(
+    http: &reqwest::Client,
+    rpc_url: &str,
+    wallet: &str,
+) -> anyhow::Result<Vec<WalletTx>> {
+    let signatures = rpc(
+        http,
+        rpc_url,
+        "getSignaturesForAddress",
+        serde_json::json!([wallet, { "limit": MAX_SIGNATURES }]),
+    )
+    .await?;
+
+    let mut history = Vec::new();
+    for entry in signatures.as_array().into_iter().flatten() {
+        let Some(signature) = entry["signature"].as_str() else {
+            continue;
+        };
+        if !entry["err"].is_null() {
+            continue;
+        }
+        let tx = rpc(
+            http,
+            rpc_url,
+            "getTransaction",
+            serde_json::json!([signature, {
+                "encoding": "jsonParsed",
+                "maxSupportedTransactionVersion": 0,
+            }]),
+        )
+        .await;
+        match tx {
+            Ok(tx) => history.extend(WalletTx::from_rpc(signature, wallet, &tx)),
+            Err(e) => {
+                warn!("Wallet history import stops at {}: {}", signature, e);
+                break;
+            }
+        }
+    }
+    debug!("Fetched {} transactions of wallet history", history.len());
+    Ok(history)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WALLET: &str = "Wa11et1111111111111111111111111111111111111";
+    const USDC: &str = "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v";
+    const BONK: &str = "DezXAZ8z7PnrnRJjz3wXBoRgixCa6xjnB7YaB1pPB263";
+
+    fn token(mint: &str, amount: i64, decimals: u8) -> Value {
+        serde_json::json!({
+            "owner": WALLET,
+            "mint": mint,
+            "uiTokenAmount": { "amount": amount.to_string(), "decimals": decimals },
+        })
+    }
+
+    fn tx(deltas: &[(&str, i128, u8)]) -> WalletTx {
+        WalletTx {
+            signature: format!("sig{}", deltas.len()),
+            block_time: None,
+            deltas: deltas
+                .iter()
+                .map(|(mint, delta, decimals)| (mint.to_string(), (*delta, *decimals)))
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_parse_rpc_transaction() {
+        // $50 of USDC for 2 SOL, with a 5000 lamport fee paid by the wallet
+        let raw = serde_json::json!({
+            "blockTime": 1_700_000_000,
+            "meta": {
+                "err": null,
+                "fee": 5000,
+                "preBalances": [1_000_000_000i64, 0],
+                "postBalances": [2_999_995_000i64, 0],
+                "preTokenBalances": [token(USDC, 80_000_000, 6)],
+                "postTokenBalances": [token(USDC, 30_000_000, 6), {
+                    "owner": "someone-else", "mint": BONK,
+                    "uiTokenAmount": { "amount": "5", "decimals": 5 },
+                }],
+            },
+            "transaction": { "message": { "accountKeys": [
+                { "pubkey": WALLET }, { "pubkey": "pool" },
+            ]}},
+        });
+        let parsed = WalletTx::from_rpc("abc", WALLET, &raw).unwrap();
+        assert_eq!(parsed.deltas.len(), 2);
+        assert_eq!(parsed.deltas[USDC], (-50_000_000, 6));
+        assert_eq!(parsed.deltas[SOL_MINT], (2_000_000_000, 9));
+        assert_eq!(parsed.cash_price(SOL_MINT), Some(Decimal::from(25)));
+        assert!(parsed.block_time.is_some());
+
+        let mut failed = raw.clone();
+        failed["meta"]["err"] = serde_json::json!({ "InstructionError": [0, "Custom"] });
+        assert!(WalletTx::from_rpc("abc", WALLET, &failed).is_none());
+    }
+
+    #[test]
+    fn test_seed_cost_basis_from_recent_buys() {
+        // Newest first: a $30 buy of 1 SOL, then a $40 buy of 2 SOL; a sell
+        // in between doesn't matter, what's held is the newest inflows
+        let history = [
+            tx(&[(SOL_MINT, 1_000_000_000, 9), (USDC, -30_000_000, 6)]),
+            tx(&[(SOL_MINT, -500_000_000, 9), (USDC, 10_000_000, 6)]),
+            tx(&[(SOL_MINT, 2_000_000_000, 9), (USDC, -40_000_000, 6)]),
+        ];
+        let holdings = HashMap::from([
+            (SOL_MINT.to_string(), 2_000_000_000),
+            (USDC.to_string(), 5_000_000),
+        ]);
+        let assets = build(&holdings, &history);
+        assert_eq!(assets.len(), 1);
+        let sol = &assets[0];
+        assert!(!sol.unknown_cost_basis());
+        // 1 SOL at $20 from the older buy, then 1 SOL at $30
+        assert_eq!(
+            sol.lots.iter().map(|l| l.price_usdc).collect::<Vec<_>>(),
+            [Decimal::from(20), Decimal::from(30)]
+        );
+        assert_eq!(sol.avg_entry_price_usdc, Some(Decimal::from(25)));
+
+        // A transfer in among the held inflows, or too little history
+        let transferred = [tx(&[(BONK, 100, 5)])];
+        let holdings = HashMap::from([(BONK.to_string(), 100)]);
+        let bonk = &build(&holdings, &transferred)[0];
+        assert!(bonk.unknown_cost_basis());
+        assert_eq!(bonk.unknown_reason, Some("not_bought_for_cash"));
+
+        let holdings = HashMap::from([(SOL_MINT.to_string(), 4_000_000_000)]);
+        let sol = &build(&holdings, &history)[0];
+        assert_eq!(sol.unknown_reason, Some("history_incomplete"));
+        assert!(sol.lots.is_empty());
+    }
+}
@@ -0,0 +1,216 @@
+//! Hash-chained decision journal
+//!
+//! Each journal entry is wrapped with a sequence number, the hash of the
+//! previous entry and its own hash over `prev_hash`, the plan hash and the
+//! canonical JSON of the entry. Entries are uploaded with each sync and the
+//! control plane checks the links, so rewriting or dropping history on the
+//! droplet shows up as a break in the chain.
+//!
+//! The chain head (last seq and hash) is kept in `journal/chain_head.json`.
+//! The hashing here must match `control_plane::journal`.
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+use tracing::warn;
+
+use crate::types::DecisionJournalEntry;
+
+/// `prev_hash` of the first entry in a bot's chain
+pub const GENESIS_HASH: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
+/// A journal entry linked into the bot's chain
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChainedJournalEntry {
+    pub seq: u64,
+    pub prev_hash: String,
+    pub hash: String,
+    pub entry: DecisionJournalEntry,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ChainHead {
+    seq: u64,
+    hash: String,
+}
+
+/// JSON with object keys sorted at every level, so both ends hash the same bytes
+pub fn canonical_json(value: &serde_json::Value) -> String {
+    match value {
+        serde_json::Value::Object(map) => {
+            let mut keys: Vec<&String> = map.keys().collect();
+            keys.sort();
+            let fields: Vec<String> = keys
+                .into_iter()
+                .map(|k| {
+                    format!(
+                        "{}:{}",
+                        serde_json::Value::String(k.clone()),
+                        canonical_json(&map[k])
+                    )
+                })
+                .collect();
+            format!("{{{}}}", fields.join(","))
+        }
+        serde_json::Value::Array(items) => {
+            let items: Vec<String> = items.iter().map(canonical_json).collect();
+            format!("[{}]", items.join(","))
+        }
+        other => other.to_string(),
+    }
+}
+
+/// Hash linking an entry to its predecessor
+pub fn entry_hash(prev_hash: &str, plan_hash: &str, entry: &serde_json::Value) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(prev_hash.as_bytes());
+    hasher.update(b"\n");
+    hasher.update(plan_hash.as_bytes());
+    hasher.update(b"\n");
+    hasher.update(canonical_json(entry).as_bytes());
+    hex::encode(hasher.finalize())
+}
+
+/// Appends entries to the chain and persists its head
+pub struct JournalChain {
+    head_path: PathBuf,
+    head: ChainHead,
+}
+
+impl JournalChain {
+    /// Resume the chain from `journal_dir`, or start a new one
+    pub fn load(journal_dir: &Path) -> Self {
+        let head_path = journal_dir.join("chain_head.json");
+        let head = match crate::sealed::read_to_string(&head_path) {
+            Ok(raw) => serde_json::from_str(&raw).unwrap_or_else(|e| {
+                // Starting over is visible to the control plane as a chain break
+                warn!(
+                    "Journal chain head unreadable ({}), starting a new chain",
+                    e
+                );
+                ChainHead {
+                    seq: 0,
+                    hash: GENESIS_HASH.to_string(),
+                }
+            }),
+            Err(_) => ChainHead {
+                seq: 0,
+                hash: GENESIS_HASH.to_string(),
+            },
+        };
+        Self { head_path, head }
+    }
+
+    pub fn head_seq(&self) -> u64 {
+        self.head.seq
+    }
+
+    /// Link `entry` after the current head and persist the new head
+    pub fn append(&mut self, entry: DecisionJournalEntry) -> anyhow::Result<ChainedJournalEntry> {
+        let value = serde_json::to_value(&entry)?;
+        let hash = entry_hash(&self.head.hash, &entry.plan_hash, &value);
+        let chained = ChainedJournalEntry {
+            seq: self.head.seq + 1,
+            prev_hash: self.head.hash.clone(),
+            hash: hash.clone(),
+            entry,
+        };
+
+        let next = ChainHead {
+            seq: chained.seq,
+            hash,
+        };
+        crate::sealed::write(&self.head_path, serde_json::to_string(&next)?)?;
+        self.head = next;
+        Ok(chained)
+    }
+}
+
+/// Check that `entries` link up from `prev_hash`; returns the first bad seq
+pub fn verify_chain(entries: &[ChainedJournalEntry], prev_hash: &str) -> Result<(), u64> {
+    let mut prev = prev_hash.to_string();
+    for chained in entries {
+        let value = serde_json::to_value(&chained.entry).map_err(|_| chained.seq)?;
+        let expected = entry_hash(&prev, &chained.entry.plan_hash, &value);
+        if chained.prev_hash != prev || chained.hash != expected {
+            return Err(chained.seq);
+        }
+        prev = chained.hash.clone();
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{IntentValidation, OpenClawIntent, TradeAction};
+    use rust_decimal::Decimal;
+    use uuid::Uuid;
+
+    fn entry(plan_hash: &str) -> DecisionJournalEntry {
+        let intent = OpenClawIntent {
+            intent_id: Uuid::new_v4(),
+            action: TradeAction::Buy,
+            input_mint: "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v".to_string(),
+            output_mint: "So11111111111111111111111111111111111111112".to_string(),
+            amount_usd: Decimal::from(25),
+            confidence: 0.8,
+            rationale: "trend".to_string(),
+        };
+        DecisionJournalEntry {
+            intent_id: intent.intent_id,
+            plan_id: Uuid::new_v4(),
+            plan_hash: plan_hash.to_string(),
+            validation: IntentValidation {
+                intent: intent.clone(),
+                approved: true,
+                rejection_reason: None,
+                blocked_by: None,
+                details: None,
+            },
+            intent,
+            execution: None,
+            timestamp: chrono::Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_hash_is_stable_across_services() {
+        // Same vector as control_plane::journal's test
+        let value = serde_json::json!({"b": [1, {"d": "x", "c": null}], "a": "1.50"});
+        assert_eq!(
+            canonical_json(&value),
+            r#"{"a":"1.50","b":[1,{"c":null,"d":"x"}]}"#
+        );
+        assert_eq!(
+            entry_hash(GENESIS_HASH, "plan", &value),
+            "f7c34c0f66867cd1d98fe9120c371574168fe6f3e74f16560d116a0bc4d6404c"
+        );
+    }
+
+    #[test]
+    fn test_chain_links_and_detects_edits() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut chain = JournalChain::load(dir.path());
+        let first = chain.append(entry("p1")).unwrap();
+        let second = chain.append(entry("p2")).unwrap();
+        assert_eq!(first.prev_hash, GENESIS_HASH);
+        assert_eq!(second.prev_hash, first.hash);
+        assert!(verify_chain(&[first.clone(), second.clone()], GENESIS_HASH).is_ok());
+
+        // The head survives a restart
+        let mut resumed = JournalChain::load(dir.path());
+        assert_eq!(resumed.head_seq(), 2);
+        let third = resumed.append(entry("p3")).unwrap();
+        assert_eq!(third.prev_hash, second.hash);
+
+        let mut edited = first.clone();
+        edited.entry.intent.amount_usd = Decimal::from(2500);
+        assert_eq!(
+            verify_chain(&[edited, second.clone()], GENESIS_HASH),
+            Err(1)
+        );
+        // A dropped entry breaks the link of the one after it
+        assert_eq!(verify_chain(&[first, third], GENESIS_HASH), Err(3));
+    }
+}
@@ -18,16 +18,35 @@
 #![allow(dead_code)]
 
 pub mod amount;
+pub mod bootstrap;
 pub mod client;
 pub mod config;
+pub mod cooldown;
+pub mod degradation;
+pub mod drawdown;
 pub mod executor;
+pub mod funding;
 pub mod gateway;
+pub mod governor;
+pub mod handover;
 pub mod intent;
+pub mod inventory;
+pub mod journal;
+pub mod log_level;
+pub mod mints;
 pub mod openclaw;
+pub mod orders;
+pub mod performance;
 pub mod portfolio;
+pub mod price_grpc;
+pub mod recent_events;
 pub mod reconciler;
+pub mod rollover;
 pub mod runner;
+pub mod sealed;
+pub mod twap;
 pub mod types;
+pub mod wallet;
 
 // Re-export main types for convenience
 pub use client::{ControlPlaneClient, EventInput, MetricInput};
@@ -42,11 +61,12 @@ pub use executor::{
 pub use gateway::GatewayManager;
 pub use intent::{IntentRegistry, TradeIntent, TradeIntentState};
 pub use openclaw::OpenClawClient;
-pub use portfolio::{Portfolio, PortfolioSnapshot, Position};
+pub use portfolio::{Portfolio, PortfolioSnapshot, Position, PositionSide};
 pub use runner::BotRunner;
 pub mod state;
 pub use types::{
-    DecisionContext, DecisionJournalEntry, DecisionPlan, GatewayHealth, Holding,
-    IntentValidation, LastTradeOutcome, OpenClawIntent, PriceQuote, RiskRails, RunnerState,
-    RunnerStatus, TradeAction, TradeEvent,
+    DecisionContext, DecisionJournalEntry, DecisionPlan, FundingSnapshot, GatewayHealth, Holding,
+    IntentValidation, LastTradeOutcome, MarketMetadata, MarketRegime, OpenClawIntent, Positioning,
+    PriceQuote, Regime, RiskRails, RunnerState, RunnerStatus, SentimentSnapshot, TradeAction,
+    TradeEvent,
 };
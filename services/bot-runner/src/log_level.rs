@@ -0,0 +1,122 @@
+//! Runtime log level control
+//!
+//! The tracing filter sits behind a reload handle so the control plane can
+//! turn up logging on a live droplet (`set_log_level` command) without SSH or
+//! a restart. Raised levels are temporary: after the TTL the filter falls back
+//! to the startup default, so a forgotten `debug` doesn't fill the disk.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{info, warn};
+use tracing_subscriber::{layer::SubscriberExt, reload, util::SubscriberInitExt, EnvFilter};
+
+/// TTL used when the command doesn't give one
+pub const DEFAULT_LOG_LEVEL_TTL_SECS: u64 = 900;
+/// Longest a raised level may stay in place
+pub const MAX_LOG_LEVEL_TTL_SECS: u64 = 4 * 3600;
+
+/// Handle for changing the active tracing filter
+#[derive(Clone)]
+pub struct LogLevelControl {
+    handle: reload::Handle<EnvFilter, tracing_subscriber::Registry>,
+    default_directives: String,
+    /// Bumped on every change so a stale revert timer doesn't undo a newer one
+    generation: Arc<AtomicU64>,
+}
+
+impl LogLevelControl {
+    /// Install the global subscriber (RUST_LOG, or `info`) and return its control
+    pub fn init() -> Self {
+        let default_directives = std::env::var("RUST_LOG")
+            .ok()
+            .filter(|d| EnvFilter::try_new(d).is_ok())
+            .unwrap_or_else(|| "info".to_string());
+        let (filter, handle) = reload::Layer::new(EnvFilter::new(&default_directives));
+        tracing_subscriber::registry()
+            .with(filter)
+            .with(tracing_subscriber::fmt::layer())
+            .init();
+        Self::new(handle, default_directives)
+    }
+
+    pub fn new(
+        handle: reload::Handle<EnvFilter, tracing_subscriber::Registry>,
+        default_directives: String,
+    ) -> Self {
+        Self {
+            handle,
+            default_directives,
+            generation: Arc::new(AtomicU64::new(0)),
+        }
+    }
+
+    /// Apply `directives` (e.g. `debug` or `info,bot_runner::executor=trace`)
+    /// for `ttl`, then revert to the default
+    ///
+    /// Returns the TTL actually applied (clamped to `MAX_LOG_LEVEL_TTL_SECS`).
+    pub fn set(&self, directives: &str, ttl: Duration) -> anyhow::Result<Duration> {
+        let filter = EnvFilter::try_new(directives)
+            .map_err(|e| anyhow::anyhow!("Invalid log filter '{}': {}", directives, e))?;
+        let ttl = ttl.min(Duration::from_secs(MAX_LOG_LEVEL_TTL_SECS));
+
+        self.handle.reload(filter)?;
+        let generation = self.generation.fetch_add(1, Ordering::SeqCst) + 1;
+        info!("Log filter set to '{}' for {}s", directives, ttl.as_secs());
+
+        let control = self.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(ttl).await;
+            if control.generation.load(Ordering::SeqCst) == generation {
+                control.reset();
+            }
+        });
+        Ok(ttl)
+    }
+
+    /// Go back to the startup filter
+    pub fn reset(&self) {
+        self.generation.fetch_add(1, Ordering::SeqCst);
+        match self.handle.reload(EnvFilter::new(&self.default_directives)) {
+            Ok(()) => info!("Log filter reverted to '{}'", self.default_directives),
+            Err(e) => warn!("Failed to revert log filter: {}", e),
+        }
+    }
+
+    /// Currently active filter directives
+    pub fn current(&self) -> String {
+        self.handle
+            .with_current(|filter| filter.to_string())
+            .unwrap_or_else(|_| self.default_directives.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_set_reverts_after_ttl() {
+        // Layer kept alive so the handle stays valid (not installed globally)
+        let (_layer, handle) =
+            reload::Layer::<_, tracing_subscriber::Registry>::new(EnvFilter::new("info"));
+        let control = LogLevelControl::new(handle, "info".to_string());
+
+        assert!(control
+            .set("not a [valid filter", Duration::from_secs(1))
+            .is_err());
+        assert_eq!(control.current(), "info");
+
+        control.set("debug", Duration::from_millis(200)).unwrap();
+        assert_eq!(control.current(), "debug");
+
+        // A newer change isn't undone by the older timer
+        control
+            .set("bot_runner=trace", Duration::from_millis(600))
+            .unwrap();
+        tokio::time::sleep(Duration::from_millis(350)).await;
+        assert_eq!(control.current(), "bot_runner=trace");
+        tokio::time::sleep(Duration::from_millis(400)).await;
+        assert_eq!(control.current(), "info");
+    }
+}
@@ -11,81 +11,229 @@
 // Allow dead code during early development - scaffolding for future features
 #![allow(dead_code)]
 
+use std::path::Path;
 use std::sync::Arc;
 use tracing::{info, warn};
 
 mod amount;
+mod bootstrap;
 mod client;
 mod config;
+mod cooldown;
+mod degradation;
+mod drawdown;
 mod executor;
+mod funding;
 mod gateway;
+mod governor;
+mod handover;
 mod intent;
+mod inventory;
+mod journal;
+mod log_level;
+mod mints;
 mod openclaw;
+mod orders;
+mod performance;
 mod portfolio;
+mod price_grpc;
+mod recent_events;
 mod reconciler;
+mod rollover;
 mod runner;
+mod sealed;
+mod state;
+mod twap;
 mod types;
+mod wallet;
 
-pub use client::ControlPlaneClient;
+pub use client::{ControlPlaneClient, RunnerSecrets, WalletReport};
 pub use config::BotConfig;
-pub use config::Config;
+pub use config::{Config, RunnerMode};
 pub use portfolio::Portfolio;
 pub use runner::BotRunner;
 
 /// Bot runner entry point
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
-    // Initialize logging
-    tracing_subscriber::fmt()
-        .with_max_level(tracing::Level::INFO)
-        .init();
+    // Initialize logging (filter adjustable at runtime via `set_log_level`)
+    let log_level = log_level::LogLevelControl::init();
 
-    info!("Starting Bot Runner...");
+    // `bot-runner bootstrap` runs droplet setup and exits
+    let bootstrap = std::env::args().nth(1).as_deref() == Some("bootstrap");
+    info!(
+        "Starting Bot Runner{}...",
+        if bootstrap { " (bootstrap)" } else { "" }
+    );
 
     // Load configuration from environment
-    let config = Config::from_env()?;
+    let mut config = Config::from_env()?;
     info!(
-        "Bot ID: {}, Control Plane: {}",
-        config.bot_id, config.control_plane_url
+        "Bot ID: {}, Control Plane: {}, Mode: {:?}",
+        config.bot_id, config.control_plane_url, config.runner_mode
     );
 
     // Create control plane client
-    let client = Arc::new(ControlPlaneClient::new(
-        &config.control_plane_url,
-        config.bot_id,
-    )?);
+    let client = Arc::new(
+        ControlPlaneClient::new(&config.control_plane_url, config.bot_id)?
+            .with_compression(config.compression)
+            .with_failover(&config.control_plane_failover_urls),
+    );
+
+    // Wait for the control plane (it may still be starting in compose/CI)
+    let wait_secs = std::env::var("CONTROL_PLANE_WAIT_SECS")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(60);
+    client
+        .wait_until_ready(std::time::Duration::from_secs(wait_secs))
+        .await?;
+
+    if bootstrap {
+        return bootstrap::run(&client, &config).await;
+    }
+
+    if config.runner_mode == RunnerMode::External {
+        load_external_secrets(&client, &mut config).await?;
+    }
+
+    // Load or create the agent keypair; its pubkey is the wallet we trade as
+    let wallet = load_wallet(&config)?;
+    config.wallet_address = wallet.pubkey();
 
     // Register with control plane (if not already registered)
-    register_bot(&client).await?;
+    register_bot(&client, &config.wallet_address).await?;
+
+    init_state_encryption(&config)?;
+    restore_handover_state(&client, &config).await;
 
     // Create and run bot runner
-    let runner = BotRunner::new(client, config);
+    let runner = BotRunner::new(client, config).with_log_level_control(log_level);
     runner.run().await
 }
 
-async fn register_bot(client: &ControlPlaneClient) -> anyhow::Result<()> {
-    // Get wallet address if available
-    let wallet = std::env::var("AGENT_WALLET").ok();
-
-    match client.register(wallet.clone()).await {
-        Ok(_) => {
-            info!("✓ Bot registered with control plane");
-            // Also report wallet separately if we have it
-            if let Some(addr) = wallet {
-                if let Err(e) = client.report_wallet(&addr).await {
-                    warn!("Wallet report failed (non-critical): {}", e);
-                }
-            }
-            Ok(())
+/// Load secrets for a user-run runner
+///
+/// The bootstrap token only works once, so the secrets it returns are saved
+/// to `secrets_path` and reused on later starts.
+async fn load_external_secrets(
+    client: &ControlPlaneClient,
+    config: &mut Config,
+) -> anyhow::Result<()> {
+    let secrets = if config.secrets_path.exists() {
+        let raw = std::fs::read_to_string(&config.secrets_path)?;
+        serde_json::from_str::<RunnerSecrets>(&raw).map_err(|e| {
+            anyhow::anyhow!(
+                "Invalid secrets file {}: {}",
+                config.secrets_path.display(),
+                e
+            )
+        })?
+    } else if let Some(token) = config.bootstrap_token.as_deref() {
+        let secrets = client.fetch_secrets(token).await?;
+        save_secrets(&config.secrets_path, &secrets)?;
+        info!("✓ Secrets saved to {}", config.secrets_path.display());
+        secrets
+    } else {
+        warn!("External mode without BOOTSTRAP_TOKEN or saved secrets; using environment only");
+        return Ok(());
+    };
+
+    config.apply_secrets(&secrets);
+    client.add_failover(&config.control_plane_failover_urls);
+    Ok(())
+}
+
+fn save_secrets(path: &Path, secrets: &RunnerSecrets) -> anyhow::Result<()> {
+    use std::io::Write;
+
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut file = options.open(path)?;
+    file.write_all(serde_json::to_string_pretty(secrets)?.as_bytes())?;
+    Ok(())
+}
+
+/// Load the keypair, checking it against any address set in the environment
+fn load_wallet(config: &Config) -> anyhow::Result<wallet::Wallet> {
+    let (wallet, _) = wallet::load_or_create(&config.keypair_path)?;
+    wallet::verify_address(&wallet, Some(&config.wallet_address))?;
+    info!(
+        "Agent wallet: {} ({})",
+        wallet.pubkey(),
+        config.keypair_path.display()
+    );
+    Ok(wallet)
+}
+
+/// Set up state file encryption before anything touches the state directory
+///
+/// An encrypted snapshot that can't be opened stops the runner: starting
+/// fresh would overwrite it, and with it the day's trade count.
+fn init_state_encryption(config: &Config) -> anyhow::Result<()> {
+    sealed::init(config.state_encryption_key.as_deref())?;
+    if sealed::is_active() {
+        info!("State directory encryption enabled");
+    }
+
+    let snapshot = runner::state_dir().join("runner_state.json");
+    if sealed::is_sealed(&snapshot) {
+        if let Err(e) = sealed::read_to_string(&snapshot) {
+            anyhow::bail!(
+                "Can't open encrypted state {}: {}. Check STATE_ENCRYPTION_KEY",
+                snapshot.display(),
+                e
+            );
         }
+    }
+    Ok(())
+}
+
+/// Pick up the state a previous droplet exported before a redeploy
+///
+/// Only into an empty state directory; any failure means a cold start rather
+/// than refusing to run.
+async fn restore_handover_state(client: &ControlPlaneClient, config: &Config) {
+    let state_dir = runner::state_dir();
+    if handover::has_local_state(&state_dir) {
+        return;
+    }
+    match client.fetch_state().await {
+        Ok(Some(bundle)) => match bundle.restore(&state_dir, config.bot_id) {
+            Ok(files) => info!(
+                "✓ Restored {} state files exported at {}",
+                files, bundle.exported_at
+            ),
+            Err(e) => warn!("Could not restore exported state, starting fresh: {}", e),
+        },
+        Ok(None) => {}
+        Err(e) => warn!("Could not fetch exported state, starting fresh: {}", e),
+    }
+}
+
+async fn register_bot(client: &ControlPlaneClient, wallet: &str) -> anyhow::Result<()> {
+    match client.register(Some(wallet.to_string())).await {
+        Ok(_) => info!("✓ Bot registered with control plane"),
+        // Already registered is OK
+        Err(e) => warn!("Registration response: {}", e),
+    }
+
+    // The control plane keeps the first wallet a bot reports; refuse to run
+    // with a keypair that doesn't match it
+    match client.report_wallet(wallet).await {
+        Ok(WalletReport::Accepted) => Ok(()),
+        Ok(WalletReport::Mismatch(detail)) => Err(anyhow::anyhow!(
+            "Keypair {} does not match the registered wallet: {}",
+            wallet,
+            detail
+        )),
         Err(e) => {
-            // Already registered is OK, but still try to report wallet
-            warn!("Registration response: {}", e);
-            if let Some(addr) = wallet {
-                if let Err(e) = client.report_wallet(&addr).await {
-                    warn!("Wallet report failed (non-critical): {}", e);
-                }
-            }
+            warn!("Wallet report failed (non-critical): {}", e);
             Ok(())
         }
     }
@@ -0,0 +1,238 @@
+//! Token metadata by mint: decimals, symbol, name, logo and verification
+//!
+//! Raw amounts only mean something with the mint's decimals, so nothing
+//! guesses them: a mint is known from the built-in token table, from the
+//! config's asset universe, or from Jupiter's token API, and whatever is
+//! learned is cached for the life of the process. The executor refuses to
+//! trade a mint whose decimals can't be resolved.
+//!
+//! A Jupiter lookup also brings the token's symbol, name, logo and whether
+//! Jupiter lists it as verified, so holdings found on chain get a real
+//! symbol and buys of unverified mints can be flagged or refused.
+
+use std::collections::HashMap;
+use std::sync::{OnceLock, RwLock};
+use std::time::Duration;
+use tracing::{info, warn};
+
+/// Jupiter's token API (override with `JUPITER_TOKENS_URL`)
+pub const DEFAULT_TOKEN_API_URL: &str = "https://lite-api.jup.ag/tokens/v2";
+
+/// Largest decimals that still fit a whole token's raw amount in a u64
+pub const MAX_DECIMALS: u8 = 18;
+
+const LOOKUP_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// What's known about a mint
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TokenMetadata {
+    pub mint: String,
+    pub symbol: String,
+    pub name: Option<String>,
+    pub decimals: u8,
+    pub logo_uri: Option<String>,
+    /// Built in, or verified in Jupiter's token list
+    pub verified: bool,
+}
+
+fn cache() -> &'static RwLock<HashMap<String, u8>> {
+    static CACHE: OnceLock<RwLock<HashMap<String, u8>>> = OnceLock::new();
+    CACHE.get_or_init(|| RwLock::new(HashMap::new()))
+}
+
+fn metadata_cache() -> &'static RwLock<HashMap<String, TokenMetadata>> {
+    static CACHE: OnceLock<RwLock<HashMap<String, TokenMetadata>>> = OnceLock::new();
+    CACHE.get_or_init(|| RwLock::new(HashMap::new()))
+}
+
+/// Decimals for `mint` if already known, without any lookup
+pub fn decimals(mint: &str) -> Option<u8> {
+    if let Some(info) = crate::amount::get_token_info(mint).filter(|t| t.mint == mint) {
+        return Some(info.decimals);
+    }
+    cache().read().ok()?.get(mint).copied()
+}
+
+/// Metadata for `mint` if it's built in or was looked up, without any lookup
+pub fn metadata(mint: &str) -> Option<TokenMetadata> {
+    if let Some(info) = crate::amount::get_token_info(mint).filter(|t| t.mint == mint) {
+        return Some(TokenMetadata {
+            mint: info.mint,
+            symbol: info.symbol,
+            name: None,
+            decimals: info.decimals,
+            logo_uri: None,
+            verified: true,
+        });
+    }
+    metadata_cache().read().ok()?.get(mint).cloned()
+}
+
+/// Symbol for `mint` if known, without any lookup
+pub fn symbol(mint: &str) -> Option<String> {
+    metadata(mint).map(|t| t.symbol)
+}
+
+/// Record decimals from a trusted source (the asset universe, a lookup)
+///
+/// Implausible values are ignored, and a mint in the built-in table keeps
+/// the table's value.
+pub fn register(mint: &str, decimals: u8) {
+    if decimals > MAX_DECIMALS {
+        warn!("Ignoring {} decimals for {}", decimals, mint);
+        return;
+    }
+    if let Some(known) = crate::amount::get_token_info(mint).filter(|t| t.mint == mint) {
+        if known.decimals != decimals {
+            warn!(
+                "{} reported with {} decimals, keeping {}",
+                mint, decimals, known.decimals
+            );
+        }
+        return;
+    }
+    if let Ok(mut cache) = cache().write() {
+        cache.insert(mint.to_string(), decimals);
+    }
+}
+
+#[derive(Debug, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TokenEntry {
+    id: String,
+    decimals: u8,
+    #[serde(default)]
+    symbol: Option<String>,
+    #[serde(default)]
+    name: Option<String>,
+    #[serde(default)]
+    icon: Option<String>,
+    #[serde(default)]
+    is_verified: Option<bool>,
+    #[serde(default)]
+    tags: Vec<String>,
+}
+
+impl TokenEntry {
+    fn into_metadata(self) -> TokenMetadata {
+        let verified = self
+            .is_verified
+            .unwrap_or_else(|| self.tags.iter().any(|t| t == "verified"));
+        TokenMetadata {
+            symbol: self
+                .symbol
+                .filter(|s| !s.is_empty())
+                .unwrap_or_else(|| "UNKNOWN".to_string()),
+            mint: self.id,
+            name: self.name.filter(|n| !n.is_empty()),
+            decimals: self.decimals,
+            logo_uri: self.icon.filter(|i| !i.is_empty()),
+            verified,
+        }
+    }
+}
+
+/// Decimals for `mint`, asking Jupiter's token API when it isn't known yet
+pub async fn resolve(http: &reqwest::Client, mint: &str) -> anyhow::Result<u8> {
+    if let Some(decimals) = decimals(mint) {
+        return Ok(decimals);
+    }
+    Ok(resolve_metadata(http, mint).await?.decimals)
+}
+
+/// Metadata for `mint`, asking Jupiter's token API when it isn't known yet
+pub async fn resolve_metadata(http: &reqwest::Client, mint: &str) -> anyhow::Result<TokenMetadata> {
+    if let Some(metadata) = metadata(mint) {
+        return Ok(metadata);
+    }
+
+    let base =
+        std::env::var("JUPITER_TOKENS_URL").unwrap_or_else(|_| DEFAULT_TOKEN_API_URL.to_string());
+    let response = tokio::time::timeout(
+        LOOKUP_TIMEOUT,
+        http.get(format!("{}/search", base.trim_end_matches('/')))
+            .query(&[("query", mint)])
+            .send(),
+    )
+    .await
+    .map_err(|_| anyhow::anyhow!("Token lookup for {} timed out", mint))??
+    .error_for_status()?;
+    let entries: Vec<TokenEntry> = response.json().await?;
+
+    let entry = entries
+        .into_iter()
+        .find(|t| t.id == mint)
+        .ok_or_else(|| anyhow::anyhow!("Mint {} not in the token list", mint))?;
+    if entry.decimals > MAX_DECIMALS {
+        anyhow::bail!("Mint {} reports {} decimals", mint, entry.decimals);
+    }
+    let metadata = entry.into_metadata();
+    info!(
+        "Resolved {} to {} ({} decimals{})",
+        mint,
+        metadata.symbol,
+        metadata.decimals,
+        if metadata.verified {
+            ""
+        } else {
+            ", unverified"
+        }
+    );
+    register(mint, metadata.decimals);
+    if let Ok(mut cache) = metadata_cache().write() {
+        cache.insert(mint.to_string(), metadata.clone());
+    }
+    Ok(metadata)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decimals_lookup() {
+        let sol = "So11111111111111111111111111111111111111112";
+        assert_eq!(decimals(sol), Some(9));
+        // Symbols aren't mints
+        assert_eq!(decimals("SOL"), None);
+
+        // The table wins over a conflicting registration
+        register(sol, 6);
+        assert_eq!(decimals(sol), Some(9));
+
+        let mint = "TestMint1111111111111111111111111111111111";
+        assert_eq!(decimals(mint), None);
+        register(mint, 19);
+        assert_eq!(decimals(mint), None);
+        register(mint, 9);
+        assert_eq!(decimals(mint), Some(9));
+    }
+
+    #[test]
+    fn test_token_entry_metadata() {
+        let entries: Vec<TokenEntry> = serde_json::from_str(
+            r#"[{"id":"JUPyiwrYJFskUPiHa7hkeR8VUtAeFoSYbKedZNsDvCN","name":"Jupiter","symbol":"JUP",
+                "icon":"https://static.jup.ag/jup/icon.png","decimals":6,"isVerified":true,
+                "tags":["verified","strict"]},
+                {"id":"Meme111111111111111111111111111111111111111","symbol":"","decimals":9,
+                "tags":["unknown"]}]"#,
+        )
+        .unwrap();
+        let mut entries = entries.into_iter();
+
+        let jup = entries.next().unwrap().into_metadata();
+        assert_eq!(jup.symbol, "JUP");
+        assert_eq!(jup.name.as_deref(), Some("Jupiter"));
+        assert_eq!(jup.decimals, 6);
+        assert!(jup.logo_uri.is_some() && jup.verified);
+
+        let meme = entries.next().unwrap().into_metadata();
+        assert_eq!(meme.symbol, "UNKNOWN");
+        assert!(!meme.verified && meme.logo_uri.is_none());
+
+        let sol = metadata("So11111111111111111111111111111111111111112").unwrap();
+        assert_eq!((sol.symbol.as_str(), sol.decimals), ("SOL", 9));
+        assert!(sol.verified);
+        assert_eq!(symbol("SOL"), None);
+    }
+}
@@ -0,0 +1,360 @@
+//! Stop-loss / take-profit order management
+//!
+//! Every position with a known entry price gets a stop and a target derived
+//! from the config's `ExitRules`, plus a trailing stop that follows the
+//! position's high-water price when enabled. The runner checks them against
+//! live prices on each decision tick and sells crossed positions directly,
+//! without a round-trip through OpenClaw. With a path the levels are
+//! mirrored to disk so open positions keep their exits across restarts.
+
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use tracing::{info, warn};
+
+use crate::config::ExitRules;
+use crate::portfolio::Portfolio;
+
+/// Why a position is being sold
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExitReason {
+    StopLoss,
+    TakeProfit,
+    /// Retraced from the high since entry
+    TrailingStop,
+    /// The owner's kill switch asked for every position to be closed
+    Flatten,
+}
+
+impl ExitReason {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ExitReason::StopLoss => "stop_loss",
+            ExitReason::TakeProfit => "take_profit",
+            ExitReason::TrailingStop => "trailing_stop",
+            ExitReason::Flatten => "flatten",
+        }
+    }
+
+    /// Event queued for the control plane when the exit fires
+    pub fn event_type(&self) -> &'static str {
+        match self {
+            ExitReason::StopLoss => "stop_triggered",
+            ExitReason::TakeProfit => "take_profit_triggered",
+            ExitReason::TrailingStop => "trailing_stop_triggered",
+            ExitReason::Flatten => "position_flattened",
+        }
+    }
+}
+
+/// Armed exit levels for one position
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExitLevels {
+    pub symbol: String,
+    /// Entry price the levels were derived from
+    pub entry_price: Decimal,
+    /// Sell once the price is at or below this (None when disabled)
+    pub stop_price: Option<Decimal>,
+    /// Sell once the price is at or above this (None when disabled)
+    pub target_price: Option<Decimal>,
+    /// Sell once the price falls this fraction below the position's high
+    /// (None when disabled)
+    #[serde(default)]
+    pub trailing_pct: Option<Decimal>,
+    pub armed_at: DateTime<Utc>,
+}
+
+impl ExitLevels {
+    fn new(symbol: &str, entry_price: Decimal, rules: ExitRules) -> Self {
+        let stop_price = (rules.stop_loss_pct > Decimal::ZERO)
+            .then(|| entry_price * (Decimal::ONE - rules.stop_loss_pct));
+        let target_price = (rules.take_profit_pct > Decimal::ZERO)
+            .then(|| entry_price * (Decimal::ONE + rules.take_profit_pct));
+        let trailing_pct =
+            (rules.trailing_stop_pct > Decimal::ZERO).then_some(rules.trailing_stop_pct);
+        Self {
+            symbol: symbol.to_string(),
+            entry_price,
+            stop_price,
+            target_price,
+            trailing_pct,
+            armed_at: Utc::now(),
+        }
+    }
+
+    fn same_levels(&self, other: &Self) -> bool {
+        self.entry_price == other.entry_price
+            && self.stop_price == other.stop_price
+            && self.target_price == other.target_price
+            && self.trailing_pct == other.trailing_pct
+    }
+
+    /// Current trailing stop for a position whose high so far is `high_water`
+    pub fn trailing_stop_price(&self, high_water: Decimal) -> Option<Decimal> {
+        self.trailing_pct
+            .map(|pct| high_water * (Decimal::ONE - pct))
+    }
+
+    /// The level `price` has crossed, if any
+    ///
+    /// Stops win over the target, and the fixed stop is reported ahead of
+    /// the trailing one when both are hit.
+    fn crossed(&self, price: Decimal, high_water: Decimal) -> Option<(ExitReason, Decimal)> {
+        if let Some(stop) = self.stop_price.filter(|stop| price <= *stop) {
+            return Some((ExitReason::StopLoss, stop));
+        }
+        if let Some(trail) = self
+            .trailing_stop_price(high_water)
+            .filter(|trail| price <= *trail)
+        {
+            return Some((ExitReason::TrailingStop, trail));
+        }
+        self.target_price
+            .filter(|target| price >= *target)
+            .map(|target| (ExitReason::TakeProfit, target))
+    }
+}
+
+/// A whole position to sell, and why
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExitOrder {
+    pub mint: String,
+    pub symbol: String,
+    pub reason: ExitReason,
+    /// The level that was crossed (the mark price when flattening)
+    pub level: Decimal,
+    /// Price that crossed it
+    pub price: Decimal,
+    pub entry_price: Decimal,
+    /// Whole position, in the token's raw units
+    pub quantity_raw: u64,
+}
+
+pub struct OrderManager {
+    /// Levels by mint
+    levels: HashMap<String, ExitLevels>,
+    path: Option<PathBuf>,
+}
+
+impl OrderManager {
+    /// Manager with no levels armed, loaded from `path` if given
+    pub fn new(path: Option<PathBuf>) -> Self {
+        let levels = path
+            .as_ref()
+            .and_then(|p| crate::sealed::read_to_string(p).ok())
+            .and_then(|raw| match serde_json::from_str(&raw) {
+                Ok(levels) => Some(levels),
+                Err(e) => {
+                    warn!("Ignoring unreadable exit levels file: {}", e);
+                    None
+                }
+            })
+            .unwrap_or_default();
+        Self { levels, path }
+    }
+
+    pub fn levels(&self, mint: &str) -> Option<&ExitLevels> {
+        self.levels.get(mint)
+    }
+
+    /// Line the armed levels up with the portfolio
+    ///
+    /// Arms new positions, re-arms ones whose entry price (or the rules)
+    /// changed and drops closed ones. Positions without a cost basis, such
+    /// as holdings the reconciler found on-chain, and paper shorts are left
+    /// unmanaged.
+    pub fn sync(&mut self, portfolio: &Portfolio, rules: ExitRules) {
+        let before = self.levels.len();
+        self.levels.retain(|mint, _| {
+            portfolio.get_position(mint).is_some_and(|pos| {
+                pos.quantity_raw > 0 && !pos.unknown_cost_basis && !pos.is_short()
+            })
+        });
+        let mut changed = self.levels.len() != before;
+
+        for (mint, pos) in &portfolio.positions {
+            if pos.quantity_raw == 0
+                || pos.unknown_cost_basis
+                || pos.is_short()
+                || pos.avg_entry_price_usdc <= Decimal::ZERO
+            {
+                continue;
+            }
+            let armed = ExitLevels::new(&pos.symbol, pos.avg_entry_price_usdc, rules);
+            if self.levels.get(mint).is_some_and(|l| l.same_levels(&armed)) {
+                continue;
+            }
+            info!(
+                "Exit levels for {}: entry {}, stop {:?}, target {:?}, trailing {:?}",
+                armed.symbol,
+                armed.entry_price,
+                armed.stop_price,
+                armed.target_price,
+                armed.trailing_pct
+            );
+            self.levels.insert(mint.clone(), armed);
+            changed = true;
+        }
+
+        if changed {
+            self.persist();
+        }
+    }
+
+    /// Exit orders for positions whose price crossed a level
+    ///
+    /// Positions without a price in `prices` are skipped for this tick.
+    pub fn evaluate(
+        &self,
+        portfolio: &Portfolio,
+        prices: &HashMap<String, Decimal>,
+    ) -> Vec<ExitOrder> {
+        let mut orders: Vec<ExitOrder> = self
+            .levels
+            .iter()
+            .filter_map(|(mint, levels)| {
+                let price = *prices.get(mint)?;
+                let position = portfolio.get_position(mint)?;
+                let high_water = position.high_water_price_usdc.unwrap_or(price).max(price);
+                let (reason, level) = levels.crossed(price, high_water)?;
+                Some(ExitOrder {
+                    mint: mint.clone(),
+                    symbol: levels.symbol.clone(),
+                    reason,
+                    level,
+                    price,
+                    entry_price: levels.entry_price,
+                    quantity_raw: position.quantity_raw,
+                })
+            })
+            .collect();
+        // Stops first, then a stable order for the journal
+        orders.sort_by(|a, b| {
+            (a.reason == ExitReason::TakeProfit, &a.mint)
+                .cmp(&(b.reason == ExitReason::TakeProfit, &b.mint))
+        });
+        orders
+    }
+
+    /// Exit orders closing every open long position, managed or not
+    ///
+    /// Uses each position's last mark; positions never priced go out at zero
+    /// and are sold at whatever the swap returns.
+    pub fn flatten_orders(&self, portfolio: &Portfolio) -> Vec<ExitOrder> {
+        let mut orders: Vec<ExitOrder> = portfolio
+            .positions
+            .values()
+            .filter(|pos| pos.quantity_raw > 0 && !pos.is_short())
+            .map(|pos| {
+                let price = pos.current_price_usdc.unwrap_or(Decimal::ZERO);
+                ExitOrder {
+                    mint: pos.mint.clone(),
+                    symbol: pos.symbol.clone(),
+                    reason: ExitReason::Flatten,
+                    level: price,
+                    price,
+                    entry_price: pos.avg_entry_price_usdc,
+                    quantity_raw: pos.quantity_raw,
+                }
+            })
+            .collect();
+        orders.sort_by(|a, b| a.mint.cmp(&b.mint));
+        orders
+    }
+
+    fn persist(&self) {
+        let Some(path) = &self.path else {
+            return;
+        };
+        let written = serde_json::to_string(&self.levels)
+            .map_err(anyhow::Error::from)
+            .and_then(|json| crate::sealed::write(path, json).map_err(anyhow::Error::from));
+        if let Err(e) = written {
+            warn!("Failed to persist exit levels: {}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SOL: &str = "So11111111111111111111111111111111111111112";
+
+    #[test]
+    fn test_arms_and_triggers_exits() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("exit_levels.json");
+        let rules = ExitRules {
+            stop_loss_pct: Decimal::new(5, 2),
+            take_profit_pct: Decimal::new(10, 2),
+            trailing_stop_pct: Decimal::ZERO,
+        };
+
+        let mut portfolio = Portfolio::new(Decimal::from(10000));
+        portfolio.update_position(SOL, "SOL", 2_000_000_000, Decimal::from(100), 9);
+        let mut orders = OrderManager::new(Some(path.clone()));
+        orders.sync(&portfolio, rules);
+
+        let levels = orders.levels(SOL).unwrap();
+        assert_eq!(levels.stop_price, Some(Decimal::from(95)));
+        assert_eq!(levels.target_price, Some(Decimal::from(110)));
+
+        let at = |price: i64| HashMap::from([(SOL.to_string(), Decimal::from(price))]);
+        assert!(orders.evaluate(&portfolio, &at(100)).is_empty());
+        let stop = orders.evaluate(&portfolio, &at(94));
+        assert_eq!(stop.len(), 1);
+        assert_eq!(stop[0].reason, ExitReason::StopLoss);
+        assert_eq!(stop[0].quantity_raw, 2_000_000_000);
+        assert_eq!(
+            orders.evaluate(&portfolio, &at(110))[0].reason,
+            ExitReason::TakeProfit
+        );
+
+        // Levels survive a restart and follow a new entry price
+        let mut restored = OrderManager::new(Some(path));
+        assert_eq!(restored.levels(SOL), orders.levels(SOL));
+        portfolio.update_position(SOL, "SOL", 4_000_000_000, Decimal::from(120), 9);
+        restored.sync(&portfolio, rules);
+        assert_eq!(
+            restored.levels(SOL).unwrap().entry_price,
+            Decimal::from(110)
+        );
+
+        // A trailing stop follows the high since entry
+        let trailing = ExitRules {
+            take_profit_pct: Decimal::ZERO,
+            trailing_stop_pct: Decimal::new(5, 2),
+            ..rules
+        };
+        restored.sync(&portfolio, trailing);
+        portfolio.mark_to_market(&at(130));
+        assert_eq!(
+            portfolio.get_position(SOL).unwrap().high_water_price_usdc,
+            Some(Decimal::from(130))
+        );
+        portfolio.mark_to_market(&at(125));
+        assert!(restored.evaluate(&portfolio, &at(125)).is_empty());
+        let trail = restored.evaluate(&portfolio, &at(123));
+        assert_eq!(trail[0].reason, ExitReason::TrailingStop);
+        assert_eq!(trail[0].level, Decimal::new(1235, 1));
+
+        // Flattening sells everything at the last mark
+        let flatten = restored.flatten_orders(&portfolio);
+        assert_eq!(flatten.len(), 1);
+        assert_eq!(flatten[0].reason, ExitReason::Flatten);
+        assert_eq!(flatten[0].quantity_raw, 4_000_000_000);
+
+        // Closed positions and unknown cost basis aren't managed
+        portfolio.close_position(SOL, 9);
+        restored.sync(&portfolio, rules);
+        assert!(restored.levels(SOL).is_none());
+        portfolio.update_position(SOL, "SOL", 1_000_000_000, Decimal::from(100), 9);
+        portfolio.positions.get_mut(SOL).unwrap().unknown_cost_basis = true;
+        restored.sync(&portfolio, rules);
+        assert!(restored.levels(SOL).is_none());
+    }
+}
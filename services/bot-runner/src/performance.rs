@@ -0,0 +1,216 @@
+//! Rolling performance statistics for heartbeat metrics
+//!
+//! Over a trailing window the runner keeps the realized PnL of each sell
+//! booked against a known cost basis, and each UTC day's closing, highest and
+//! lowest equity (plus the deepest drop within the day) as observed every
+//! decision tick. From those it reports win rate, average win and loss, a
+//! Sharpe ratio of daily returns and the window's max drawdown with every
+//! metric point, so the control plane doesn't have to replay events.
+
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, VecDeque};
+
+/// Trailing window the statistics cover
+pub const WINDOW_DAYS: i64 = 30;
+
+/// Days a year for annualizing; crypto trades every day
+const TRADING_DAYS_PER_YEAR: f64 = 365.0;
+
+/// Equity seen over one UTC day
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+struct DayEquity {
+    close: Decimal,
+    high: Decimal,
+    low: Decimal,
+    /// Deepest drop below the day's running high, in percent
+    max_drawdown_pct: Decimal,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PerformanceTracker {
+    /// Realized PnL of closed trades in the window, oldest first
+    closes: VecDeque<(DateTime<Utc>, Decimal)>,
+    days: BTreeMap<NaiveDate, DayEquity>,
+}
+
+/// Statistics over the trailing [`WINDOW_DAYS`]
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PerformanceStats {
+    pub window_days: i64,
+    pub closed_trades: u32,
+    /// Share of closed trades with a positive PnL (None without any)
+    pub win_rate: Option<f64>,
+    pub avg_win_usd: Option<Decimal>,
+    /// Negative: the average losing trade's PnL
+    pub avg_loss_usd: Option<Decimal>,
+    /// Annualized Sharpe ratio of daily equity returns, zero risk-free rate
+    /// (None until there are two returns that vary)
+    pub sharpe_ratio: Option<f64>,
+    /// Deepest peak-to-trough drop in the window, in percent
+    pub max_drawdown_pct: Decimal,
+}
+
+impl PerformanceTracker {
+    /// Record a closed trade's realized PnL
+    pub fn record_close(&mut self, at: DateTime<Utc>, realized_pnl: Decimal) {
+        self.closes.push_back((at, realized_pnl));
+        self.prune(at);
+    }
+
+    /// Record total equity after the portfolio was marked to market
+    pub fn observe_equity(&mut self, at: DateTime<Utc>, equity: Decimal) {
+        let day = self.days.entry(at.date_naive()).or_insert(DayEquity {
+            close: equity,
+            high: equity,
+            low: equity,
+            max_drawdown_pct: Decimal::ZERO,
+        });
+        day.close = equity;
+        day.high = day.high.max(equity);
+        day.low = day.low.min(equity);
+        day.max_drawdown_pct = day.max_drawdown_pct.max(drawdown_pct(day.high, equity));
+        self.prune(at);
+    }
+
+    fn prune(&mut self, now: DateTime<Utc>) {
+        let cutoff = now - Duration::days(WINDOW_DAYS);
+        while self.closes.front().is_some_and(|(at, _)| *at <= cutoff) {
+            self.closes.pop_front();
+        }
+        let first_day = cutoff.date_naive();
+        self.days.retain(|day, _| *day > first_day);
+    }
+
+    /// Statistics as of `now`
+    pub fn stats(&self, now: DateTime<Utc>) -> PerformanceStats {
+        let cutoff = now - Duration::days(WINDOW_DAYS);
+        let pnls: Vec<Decimal> = self
+            .closes
+            .iter()
+            .filter(|(at, _)| *at > cutoff)
+            .map(|(_, pnl)| *pnl)
+            .collect();
+        let wins: Vec<Decimal> = pnls
+            .iter()
+            .copied()
+            .filter(|p| *p > Decimal::ZERO)
+            .collect();
+        let losses: Vec<Decimal> = pnls
+            .iter()
+            .copied()
+            .filter(|p| *p < Decimal::ZERO)
+            .collect();
+        let average = |values: &[Decimal]| {
+            let total: Decimal = values.iter().sum();
+            (!values.is_empty()).then(|| (total / Decimal::from(values.len())).round_dp(2))
+        };
+
+        let days: Vec<&DayEquity> = self
+            .days
+            .iter()
+            .filter(|(day, _)| **day > cutoff.date_naive())
+            .map(|(_, equity)| equity)
+            .collect();
+
+        // A drop across days runs from an earlier day's high to a later low
+        let mut max_drawdown_pct = Decimal::ZERO;
+        let mut peak: Option<Decimal> = None;
+        for day in &days {
+            max_drawdown_pct = max_drawdown_pct.max(day.max_drawdown_pct);
+            if let Some(peak) = peak {
+                max_drawdown_pct = max_drawdown_pct.max(drawdown_pct(peak, day.low));
+            }
+            peak = Some(peak.map_or(day.high, |p| p.max(day.high)));
+        }
+
+        PerformanceStats {
+            window_days: WINDOW_DAYS,
+            closed_trades: pnls.len() as u32,
+            win_rate: (!pnls.is_empty()).then(|| wins.len() as f64 / pnls.len() as f64),
+            avg_win_usd: average(&wins),
+            avg_loss_usd: average(&losses),
+            sharpe_ratio: sharpe(&days),
+            max_drawdown_pct: max_drawdown_pct.round_dp(4),
+        }
+    }
+}
+
+/// Percent `equity` sits below `peak` (0 at or above it)
+fn drawdown_pct(peak: Decimal, equity: Decimal) -> Decimal {
+    if peak <= Decimal::ZERO || equity >= peak {
+        return Decimal::ZERO;
+    }
+    (peak - equity) / peak * Decimal::from(100)
+}
+
+/// Annualized Sharpe ratio of close-to-close returns
+fn sharpe(days: &[&DayEquity]) -> Option<f64> {
+    let returns: Vec<f64> = days
+        .windows(2)
+        .filter(|pair| pair[0].close > Decimal::ZERO)
+        .filter_map(|pair| ((pair[1].close - pair[0].close) / pair[0].close).to_f64())
+        .collect();
+    if returns.len() < 2 {
+        return None;
+    }
+    let n = returns.len() as f64;
+    let mean = returns.iter().sum::<f64>() / n;
+    let variance = returns.iter().map(|r| (r - mean).powi(2)).sum::<f64>() / (n - 1.0);
+    let std_dev = variance.sqrt();
+    (std_dev > 0.0).then(|| mean / std_dev * TRADING_DAYS_PER_YEAR.sqrt())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn at(day: i64, hour: i64) -> DateTime<Utc> {
+        Utc.timestamp_opt(day * 86_400 + hour * 3600, 0).unwrap()
+    }
+
+    #[test]
+    fn test_rolling_stats() {
+        let mut tracker = PerformanceTracker::default();
+        let empty = tracker.stats(at(0, 0));
+        assert_eq!((empty.closed_trades, empty.win_rate), (0, None));
+        assert_eq!(empty.sharpe_ratio, None);
+
+        tracker.record_close(at(1, 0), Decimal::from(30));
+        tracker.record_close(at(1, 1), Decimal::from(-10));
+        tracker.record_close(at(2, 0), Decimal::from(10));
+        tracker.record_close(at(2, 1), Decimal::ZERO);
+
+        // 10000 -> 11000 -> 9900, back to 10450 the same day, then a new high
+        tracker.observe_equity(at(1, 0), Decimal::from(10_000));
+        tracker.observe_equity(at(2, 0), Decimal::from(11_000));
+        tracker.observe_equity(at(3, 0), Decimal::from(9_900));
+        tracker.observe_equity(at(3, 6), Decimal::from(10_450));
+        tracker.observe_equity(at(4, 0), Decimal::from(12_000));
+
+        let stats = tracker.stats(at(4, 0));
+        assert_eq!(stats.closed_trades, 4);
+        assert_eq!(stats.win_rate, Some(0.5));
+        assert_eq!(stats.avg_win_usd, Some(Decimal::from(20)));
+        assert_eq!(stats.avg_loss_usd, Some(Decimal::from(-10)));
+        assert_eq!(stats.max_drawdown_pct, Decimal::from(10));
+        // Returns of +10%, -5% and about +14.8% average positive
+        assert!(stats.sharpe_ratio.unwrap() > 0.0);
+
+        // A 20% drop within one day counts too
+        tracker.observe_equity(at(5, 0), Decimal::from(12_500));
+        tracker.observe_equity(at(5, 1), Decimal::from(10_000));
+        assert_eq!(tracker.stats(at(5, 1)).max_drawdown_pct, Decimal::from(20));
+
+        // Everything ages out of the window
+        let later = at(5 + WINDOW_DAYS, 2);
+        tracker.observe_equity(later, Decimal::from(10_000));
+        let stats = tracker.stats(later);
+        assert_eq!((stats.closed_trades, stats.win_rate), (0, None));
+        assert_eq!(stats.max_drawdown_pct, Decimal::ZERO);
+        assert_eq!(tracker.days.len(), 1);
+    }
+}
@@ -1,4 +1,17 @@
 //! Portfolio tracking - Position and cash management
+//!
+//! Confirmed sells are booked through [`Portfolio::realize_sell`], which
+//! prices what was sold against its cost basis (average cost, or the oldest
+//! lots first with [`CostBasisMethod::Fifo`]) and nets out the swap fee.
+//! Fees on buys (swap and Solana network fees) are charged to realized PnL
+//! when paid through [`Portfolio::charge_fees`], and every fee is tallied per
+//! position, per trading day and over the portfolio's lifetime.
+//!
+//! Positions are signed. A [`PositionSide::Short`] is tokens sold that were
+//! never held, against a virtual borrow: only a paper portfolio with
+//! [`Portfolio::allow_shorts`] set opens one, and buying the mint covers it
+//! before anything is held long. [`Portfolio::check_invariants`] spells out
+//! what every position must satisfy.
 
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
@@ -14,6 +27,90 @@ pub struct Portfolio {
     pub positions: HashMap<String, Position>,
     /// Last update timestamp
     pub last_updated: chrono::DateTime<chrono::Utc>,
+    /// Realized PnL over the portfolio's lifetime, net of fees
+    #[serde(default)]
+    pub realized_pnl_usd: Decimal,
+    /// Swap and network fees paid since the last day rollover
+    #[serde(default)]
+    pub fees_today_usd: Decimal,
+    /// Swap and network fees paid over the portfolio's lifetime
+    #[serde(default)]
+    pub fees_total_usd: Decimal,
+    /// Sells beyond what's held open shorts (paper trading only)
+    #[serde(default)]
+    pub allow_shorts: bool,
+}
+
+/// Which way a position faces
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PositionSide {
+    /// Tokens held
+    #[default]
+    Long,
+    /// Tokens owed: sold against a virtual borrow and bought back to close
+    Short,
+}
+
+/// How a sell's cost basis is measured
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CostBasisMethod {
+    /// The position's average entry price
+    #[default]
+    #[serde(rename = "average")]
+    AverageCost,
+    /// The oldest buys are sold first
+    Fifo,
+}
+
+impl CostBasisMethod {
+    /// Parse from BOT_COST_BASIS (average | fifo)
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "average" | "avg" => Some(Self::AverageCost),
+            "fifo" => Some(Self::Fifo),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::AverageCost => "average",
+            Self::Fifo => "fifo",
+        }
+    }
+}
+
+/// Quantity bought in one fill, for FIFO cost basis
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Lot {
+    pub quantity_raw: u64,
+    pub price_usdc: Decimal,
+}
+
+/// A sell booked against its cost basis
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ClosedTrade {
+    pub mint: String,
+    pub symbol: String,
+    /// Quantity sold out of the tracked position
+    pub quantity: Decimal,
+    /// Cost per unit of what was sold
+    pub entry_price: Decimal,
+    pub exit_price: Decimal,
+    pub cost_basis_usd: Decimal,
+    pub proceeds_usd: Decimal,
+    pub fees_usd: Decimal,
+    /// Proceeds minus cost basis and fees
+    pub realized_pnl_usd: Decimal,
+    pub method: CostBasisMethod,
+    /// Nothing of the position is left
+    pub fully_closed: bool,
+    /// The position came from reconciliation, so the basis is a guess
+    pub unknown_cost_basis: bool,
+    /// Short: the entry was the sale and the exit the buy back
+    pub side: PositionSide,
 }
 
 /// A single position
@@ -29,6 +126,75 @@ pub struct Position {
     /// Per principal engineer feedback: tag positions with unknown cost basis
     #[serde(default)]
     pub unknown_cost_basis: bool,
+    /// Highest price seen since entry, for trailing stops
+    #[serde(default)]
+    pub high_water_price_usdc: Option<Decimal>,
+    /// Buys still held, oldest first (empty for reconciled positions)
+    #[serde(default)]
+    pub lots: Vec<Lot>,
+    /// Fees paid on the position's buys and partial sells so far
+    #[serde(default)]
+    pub fees_usd: Decimal,
+    /// For a short, `quantity_raw` is owed, the entry price is what it was
+    /// sold at and the lots are its short sales
+    #[serde(default)]
+    pub side: PositionSide,
+}
+
+impl Position {
+    pub fn is_short(&self) -> bool {
+        self.side == PositionSide::Short
+    }
+
+    /// Quantity in tokens, negative for a short
+    pub fn signed_quantity(&self, decimals: u8) -> Decimal {
+        let quantity = crate::amount::from_raw_amount(self.quantity_raw, decimals);
+        match self.side {
+            PositionSide::Long => quantity,
+            PositionSide::Short => -quantity,
+        }
+    }
+
+    /// Take `quantity_raw` off the oldest lots; returns the cost of what the
+    /// lots covered and how much of `quantity_raw` they covered
+    fn consume_lots(&mut self, mut quantity_raw: u64, decimals: u8) -> (Decimal, u64) {
+        let mut cost = Decimal::ZERO;
+        let mut covered = 0;
+        while quantity_raw > 0 {
+            let Some(lot) = self.lots.first_mut() else {
+                break;
+            };
+            let take = lot.quantity_raw.min(quantity_raw);
+            cost += crate::amount::from_raw_amount(take, decimals) * lot.price_usdc;
+            covered += take;
+            quantity_raw -= take;
+            lot.quantity_raw -= take;
+            if lot.quantity_raw == 0 {
+                self.lots.remove(0);
+            }
+        }
+        (cost, covered)
+    }
+
+    /// Average price of the remaining lots, when they account for the whole position
+    fn lots_average(&self, decimals: u8) -> Option<Decimal> {
+        let held: u64 = self.lots.iter().map(|l| l.quantity_raw).sum();
+        if held == 0 || held != self.quantity_raw {
+            return None;
+        }
+        let cost: Decimal = self
+            .lots
+            .iter()
+            .map(|l| crate::amount::from_raw_amount(l.quantity_raw, decimals) * l.price_usdc)
+            .sum();
+        Some(cost / crate::amount::from_raw_amount(held, decimals))
+    }
+
+    fn raise_high_water(&mut self, price: Decimal) {
+        if self.high_water_price_usdc.is_none_or(|high| price > high) {
+            self.high_water_price_usdc = Some(price);
+        }
+    }
 }
 
 /// Portfolio snapshot for reporting
@@ -37,19 +203,29 @@ pub struct PortfolioSnapshot {
     pub cash_usdc: Decimal,
     pub positions: Vec<PositionSnapshot>,
     pub total_equity: Decimal,
+    /// Sum of the positions' market values, longs and shorts alike
+    pub gross_exposure: Decimal,
+    /// Longs' market value minus shorts'
+    pub net_exposure: Decimal,
     pub unrealized_pnl: Decimal,
     pub realized_pnl: Decimal,
+    pub fees_today: Decimal,
+    pub fees_total: Decimal,
 }
 
 #[derive(Debug, Clone, Serialize)]
 pub struct PositionSnapshot {
     pub symbol: String,
     pub mint: String,
+    pub side: PositionSide,
+    /// Negative for a short
     pub quantity: Decimal,
     pub avg_entry: Decimal,
     pub current_price: Decimal,
+    /// Negative for a short (what buying it back would cost)
     pub market_value: Decimal,
     pub unrealized_pnl: Decimal,
+    pub fees_usd: Decimal,
 }
 
 impl Portfolio {
@@ -62,6 +238,10 @@ impl Portfolio {
             cash_usdc_raw: cash_raw,
             positions: HashMap::new(),
             last_updated: chrono::Utc::now(),
+            realized_pnl_usd: Decimal::ZERO,
+            fees_today_usd: Decimal::ZERO,
+            fees_total_usd: Decimal::ZERO,
+            allow_shorts: false,
         }
     }
 
@@ -103,11 +283,18 @@ impl Portfolio {
                 let total_qty = crate::amount::from_raw_amount(new_quantity_raw, decimals);
 
                 pos.avg_entry_price_usdc = total_cost / total_qty;
+                pos.lots.push(Lot {
+                    quantity_raw: added_qty,
+                    price_usdc,
+                });
+            } else {
+                // If reducing, keep same avg entry
+                pos.consume_lots(old_qty - new_quantity_raw, decimals);
             }
-            // If reducing, keep same avg entry
 
             pos.quantity_raw = new_quantity_raw;
             pos.current_price_usdc = Some(price_usdc);
+            pos.raise_high_water(price_usdc);
             pos.last_updated = now;
 
             debug!(
@@ -129,6 +316,13 @@ impl Portfolio {
                     current_price_usdc: Some(price_usdc),
                     last_updated: now,
                     unknown_cost_basis: false, // Known from trade execution
+                    high_water_price_usdc: Some(price_usdc),
+                    lots: vec![Lot {
+                        quantity_raw: new_quantity_raw,
+                        price_usdc,
+                    }],
+                    fees_usd: Decimal::ZERO,
+                    side: PositionSide::Long,
                 },
             );
 
@@ -160,11 +354,326 @@ impl Portfolio {
         None
     }
 
+    /// Charge fees paid outside a sell (a buy's swap and network fees) to
+    /// realized PnL, and to `mint`'s position if there is one
+    pub fn charge_fees(&mut self, mint: &str, fees_usd: Decimal) {
+        if let Some(pos) = self.positions.get_mut(mint) {
+            pos.fees_usd += fees_usd;
+        }
+        self.realized_pnl_usd -= fees_usd;
+        self.tally_fees(fees_usd);
+    }
+
+    fn tally_fees(&mut self, fees_usd: Decimal) {
+        self.fees_today_usd += fees_usd;
+        self.fees_total_usd += fees_usd;
+    }
+
+    /// Start a new trading day's fee tally; returns the finished day's fees
+    pub fn reset_daily_fees(&mut self) -> Decimal {
+        std::mem::take(&mut self.fees_today_usd)
+    }
+
+    /// Book a confirmed sell of `sold_raw` that returned `proceeds_usd`
+    ///
+    /// Shrinks (or removes) the position and adds the realized PnL to
+    /// [`Portfolio::realized_pnl_usd`]. Selling more than is tracked only
+    /// books the tracked part, with proceeds and fees scaled to match.
+    /// Returns `None` without a position to sell from, leaving the fees to
+    /// [`Portfolio::charge_fees`].
+    pub fn realize_sell(
+        &mut self,
+        mint: &str,
+        sold_raw: u64,
+        proceeds_usd: Decimal,
+        fees_usd: Decimal,
+        decimals: u8,
+        method: CostBasisMethod,
+    ) -> Option<ClosedTrade> {
+        let pos = self.positions.get_mut(mint).filter(|p| !p.is_short())?;
+        let sold = sold_raw.min(pos.quantity_raw);
+        if sold == 0 {
+            return None;
+        }
+        let quantity = crate::amount::from_raw_amount(sold, decimals);
+        let share = Decimal::from(sold) / Decimal::from(sold_raw);
+        let (proceeds, fees) = (proceeds_usd * share, fees_usd * share);
+
+        // Lots are consumed either way so they stay in step with the quantity
+        let (lot_cost, covered) = pos.consume_lots(sold, decimals);
+        let cost_basis = match method {
+            CostBasisMethod::AverageCost => quantity * pos.avg_entry_price_usdc,
+            CostBasisMethod::Fifo => {
+                lot_cost
+                    + crate::amount::from_raw_amount(sold - covered, decimals)
+                        * pos.avg_entry_price_usdc
+            }
+        };
+        pos.quantity_raw -= sold;
+        if method == CostBasisMethod::Fifo {
+            if let Some(average) = pos.lots_average(decimals) {
+                pos.avg_entry_price_usdc = average;
+            }
+        }
+        let exit_price = proceeds / quantity;
+        pos.current_price_usdc = Some(exit_price);
+        pos.last_updated = chrono::Utc::now();
+        pos.fees_usd += fees;
+
+        let closed = ClosedTrade {
+            mint: mint.to_string(),
+            symbol: pos.symbol.clone(),
+            quantity,
+            entry_price: cost_basis / quantity,
+            exit_price,
+            cost_basis_usd: cost_basis,
+            proceeds_usd: proceeds,
+            fees_usd: fees,
+            realized_pnl_usd: proceeds - cost_basis - fees,
+            method,
+            fully_closed: pos.quantity_raw == 0,
+            unknown_cost_basis: pos.unknown_cost_basis,
+            side: PositionSide::Long,
+        };
+        if closed.fully_closed {
+            self.positions.remove(mint);
+        }
+        self.realized_pnl_usd += closed.realized_pnl_usd;
+        self.tally_fees(fees);
+        self.last_updated = chrono::Utc::now();
+
+        info!(
+            "Sold {} {} at {} | Realized PnL: {} ({} fees, {})",
+            closed.quantity,
+            closed.symbol,
+            closed.exit_price.round_dp(6),
+            closed.realized_pnl_usd.round_dp(2),
+            closed.fees_usd.round_dp(2),
+            method.as_str()
+        );
+        Some(closed)
+    }
+
+    /// Book a paper sell of `sold_raw` that isn't held, opening or adding to
+    /// a short for `proceeds_usd`
+    ///
+    /// False (and nothing booked) unless shorts are allowed and the mint
+    /// isn't held long; sell the long side through
+    /// [`Portfolio::realize_sell`] first. Fees go through
+    /// [`Portfolio::charge_fees`] as for a buy.
+    pub fn sell_short(
+        &mut self,
+        mint: &str,
+        symbol: &str,
+        sold_raw: u64,
+        proceeds_usd: Decimal,
+        decimals: u8,
+    ) -> bool {
+        if !self.allow_shorts || sold_raw == 0 {
+            return false;
+        }
+        let price = proceeds_usd / crate::amount::from_raw_amount(sold_raw, decimals);
+        let now = chrono::Utc::now();
+        let pos = self
+            .positions
+            .entry(mint.to_string())
+            .or_insert_with(|| Position {
+                mint: mint.to_string(),
+                symbol: symbol.to_string(),
+                quantity_raw: 0,
+                avg_entry_price_usdc: price,
+                current_price_usdc: None,
+                last_updated: now,
+                unknown_cost_basis: false,
+                high_water_price_usdc: None,
+                lots: Vec::new(),
+                fees_usd: Decimal::ZERO,
+                side: PositionSide::Short,
+            });
+        if !pos.is_short() && pos.quantity_raw > 0 {
+            return false;
+        }
+        pos.side = PositionSide::Short;
+        let owed = pos.quantity_raw.saturating_add(sold_raw);
+        pos.avg_entry_price_usdc = (crate::amount::from_raw_amount(pos.quantity_raw, decimals)
+            * pos.avg_entry_price_usdc
+            + proceeds_usd)
+            / crate::amount::from_raw_amount(owed, decimals);
+        pos.quantity_raw = owed;
+        pos.lots.push(Lot {
+            quantity_raw: sold_raw,
+            price_usdc: price,
+        });
+        pos.current_price_usdc = Some(price);
+        pos.last_updated = now;
+        self.last_updated = now;
+
+        info!(
+            "Short {} {} at {} | Owed: {}",
+            crate::amount::from_raw_amount(sold_raw, decimals),
+            symbol,
+            price.round_dp(6),
+            crate::amount::from_raw_amount(owed, decimals)
+        );
+        true
+    }
+
+    /// Book a confirmed buy of `bought_raw` for `cost_usd` against a short
+    ///
+    /// The mirror of [`Portfolio::realize_sell`]: PnL is what the covered
+    /// tokens were sold short for, minus `cost_usd` and fees. Buying more
+    /// than is owed only books the owed part (with cost and fees scaled to
+    /// match); the rest is the caller's to add as a long. `None` without a
+    /// short to cover.
+    pub fn cover_short(
+        &mut self,
+        mint: &str,
+        bought_raw: u64,
+        cost_usd: Decimal,
+        fees_usd: Decimal,
+        decimals: u8,
+        method: CostBasisMethod,
+    ) -> Option<ClosedTrade> {
+        let pos = self.positions.get_mut(mint).filter(|p| p.is_short())?;
+        let covered = bought_raw.min(pos.quantity_raw);
+        if covered == 0 {
+            return None;
+        }
+        let quantity = crate::amount::from_raw_amount(covered, decimals);
+        let share = Decimal::from(covered) / Decimal::from(bought_raw);
+        let (cost, fees) = (cost_usd * share, fees_usd * share);
+
+        let (lot_proceeds, lots_covered) = pos.consume_lots(covered, decimals);
+        let entry_proceeds = match method {
+            CostBasisMethod::AverageCost => quantity * pos.avg_entry_price_usdc,
+            CostBasisMethod::Fifo => {
+                lot_proceeds
+                    + crate::amount::from_raw_amount(covered - lots_covered, decimals)
+                        * pos.avg_entry_price_usdc
+            }
+        };
+        pos.quantity_raw -= covered;
+        if method == CostBasisMethod::Fifo {
+            if let Some(average) = pos.lots_average(decimals) {
+                pos.avg_entry_price_usdc = average;
+            }
+        }
+        let exit_price = cost / quantity;
+        pos.current_price_usdc = Some(exit_price);
+        pos.last_updated = chrono::Utc::now();
+        pos.fees_usd += fees;
+
+        // The short's entry is its sale and its exit the buy back
+        let closed = ClosedTrade {
+            mint: mint.to_string(),
+            symbol: pos.symbol.clone(),
+            quantity,
+            entry_price: entry_proceeds / quantity,
+            exit_price,
+            cost_basis_usd: cost,
+            proceeds_usd: entry_proceeds,
+            fees_usd: fees,
+            realized_pnl_usd: entry_proceeds - cost - fees,
+            method,
+            fully_closed: pos.quantity_raw == 0,
+            unknown_cost_basis: false,
+            side: PositionSide::Short,
+        };
+        if closed.fully_closed {
+            self.positions.remove(mint);
+        }
+        self.realized_pnl_usd += closed.realized_pnl_usd;
+        self.tally_fees(fees);
+        self.last_updated = chrono::Utc::now();
+
+        info!(
+            "Covered {} {} at {} | Realized PnL: {} ({} fees, {})",
+            closed.quantity,
+            closed.symbol,
+            closed.exit_price.round_dp(6),
+            closed.realized_pnl_usd.round_dp(2),
+            closed.fees_usd.round_dp(2),
+            method.as_str()
+        );
+        Some(closed)
+    }
+
+    /// Buy back every short at its last mark (no fees) and stop allowing
+    /// new ones, e.g. when the bot leaves paper trading
+    ///
+    /// Shorts on mints with unknown decimals can't be valued and are
+    /// dropped as they stand.
+    pub fn settle_shorts(&mut self, method: CostBasisMethod) -> Vec<ClosedTrade> {
+        let shorts: Vec<(String, u64, Decimal)> = self
+            .positions
+            .values()
+            .filter(|p| p.is_short())
+            .map(|p| {
+                let price = p.current_price_usdc.unwrap_or(p.avg_entry_price_usdc);
+                (p.mint.clone(), p.quantity_raw, price)
+            })
+            .collect();
+        let mut closed = Vec::new();
+        for (mint, owed_raw, price) in shorts {
+            let Some(decimals) = crate::mints::decimals(&mint) else {
+                self.positions.remove(&mint);
+                continue;
+            };
+            let cost = crate::amount::from_raw_amount(owed_raw, decimals) * price;
+            if let Some(trade) =
+                self.cover_short(&mint, owed_raw, cost, Decimal::ZERO, decimals, method)
+            {
+                let cost_raw = crate::amount::to_raw_amount(cost, 6).unwrap_or(u64::MAX);
+                self.cash_usdc_raw = self.cash_usdc_raw.saturating_sub(cost_raw);
+                closed.push(trade);
+            }
+        }
+        self.allow_shorts = false;
+        closed
+    }
+
+    /// Check what every position must satisfy, naming the first violation
+    ///
+    /// - it's stored under its own mint (so a mint is long or short, never both)
+    /// - its lots never add up to more than its quantity
+    /// - a short exists only while shorts are allowed, has a known sale
+    ///   price, and owes something (covered shorts are removed)
+    pub fn check_invariants(&self) -> Result<(), String> {
+        for (mint, pos) in &self.positions {
+            if pos.mint != *mint {
+                return Err(format!("position {} stored under {}", pos.mint, mint));
+            }
+            let in_lots = pos
+                .lots
+                .iter()
+                .try_fold(0u64, |sum, lot| sum.checked_add(lot.quantity_raw));
+            if in_lots.is_none_or(|held| held > pos.quantity_raw) {
+                return Err(format!(
+                    "{}: lots exceed the position's {} raw",
+                    pos.symbol, pos.quantity_raw
+                ));
+            }
+            if pos.is_short() {
+                if !self.allow_shorts {
+                    return Err(format!("{}: short while shorts are disabled", pos.symbol));
+                }
+                if pos.unknown_cost_basis || pos.avg_entry_price_usdc <= Decimal::ZERO {
+                    return Err(format!("{}: short without a sale price", pos.symbol));
+                }
+                if pos.quantity_raw == 0 {
+                    return Err(format!("{}: short owes nothing", pos.symbol));
+                }
+            }
+        }
+        Ok(())
+    }
+
     /// Update current prices for all positions
     pub fn mark_to_market(&mut self, prices: &HashMap<String, Decimal>) {
         for (mint, pos) in &mut self.positions {
             if let Some(price) = prices.get(mint) {
                 pos.current_price_usdc = Some(*price);
+                pos.raise_high_water(*price);
             }
         }
         self.last_updated = chrono::Utc::now();
@@ -178,29 +687,42 @@ impl Portfolio {
             .positions
             .values()
             .filter_map(|pos| {
-                let decimals = crate::amount::get_token_info(&pos.mint)
-                    .map(|t| t.decimals)
-                    .unwrap_or(6);
+                // Left out rather than valued with guessed decimals
+                let decimals = crate::mints::decimals(&pos.mint)?;
 
-                let qty = crate::amount::from_raw_amount(pos.quantity_raw, decimals);
+                let qty = pos.signed_quantity(decimals);
                 let current_price = pos.current_price_usdc?;
                 let market_value = qty * current_price;
                 let cost_basis = qty * pos.avg_entry_price_usdc;
                 let unrealized = market_value - cost_basis;
 
+                // Holdings imported before their token was looked up
+                let symbol = match pos.symbol.as_str() {
+                    "UNKNOWN" => {
+                        crate::mints::symbol(&pos.mint).unwrap_or_else(|| pos.symbol.clone())
+                    }
+                    _ => pos.symbol.clone(),
+                };
+
                 Some(PositionSnapshot {
-                    symbol: pos.symbol.clone(),
+                    symbol,
                     mint: pos.mint.clone(),
+                    side: pos.side,
                     quantity: qty,
                     avg_entry: pos.avg_entry_price_usdc,
                     current_price,
                     market_value,
                     unrealized_pnl: unrealized,
+                    fees_usd: pos.fees_usd,
                 })
             })
             .collect();
 
         let positions_value: Decimal = position_snapshots.iter().map(|p| p.market_value).sum();
+        let gross_exposure: Decimal = position_snapshots
+            .iter()
+            .map(|p| p.market_value.abs())
+            .sum();
 
         let unrealized_pnl: Decimal = position_snapshots.iter().map(|p| p.unrealized_pnl).sum();
 
@@ -208,8 +730,12 @@ impl Portfolio {
             cash_usdc: cash,
             positions: position_snapshots,
             total_equity: cash + positions_value,
+            gross_exposure,
+            net_exposure: positions_value,
             unrealized_pnl,
-            realized_pnl: Decimal::ZERO, // Tracked separately
+            realized_pnl: self.realized_pnl_usd,
+            fees_today: self.fees_today_usd,
+            fees_total: self.fees_total_usd,
         }
     }
 
@@ -227,7 +753,7 @@ impl Portfolio {
     pub fn can_sell(&self, mint: &str, amount_raw: u64) -> bool {
         self.positions
             .get(mint)
-            .map(|p| p.quantity_raw >= amount_raw)
+            .map(|p| !p.is_short() && p.quantity_raw >= amount_raw)
             .unwrap_or(false)
     }
 }
@@ -275,4 +801,164 @@ mod tests {
         assert_eq!(snapshot.positions.len(), 1);
         assert_eq!(snapshot.positions[0].unrealized_pnl, Decimal::from(20)); // $20 gain
     }
+
+    #[test]
+    fn test_realize_sell_cost_basis() {
+        let sol = "So11111111111111111111111111111111111111112";
+        let buy_two_lots = || {
+            let mut portfolio = Portfolio::new(Decimal::from(10000));
+            portfolio.update_position(sol, "SOL", 1_000_000_000, Decimal::from(100), 9);
+            portfolio.update_position(sol, "SOL", 2_000_000_000, Decimal::from(200), 9);
+            portfolio
+        };
+
+        // Average cost 150; selling 1 SOL for $250 with $1 of fees
+        let mut average = buy_two_lots();
+        let closed = average
+            .realize_sell(
+                sol,
+                1_000_000_000,
+                Decimal::from(250),
+                Decimal::ONE,
+                9,
+                CostBasisMethod::AverageCost,
+            )
+            .unwrap();
+        assert_eq!(closed.cost_basis_usd, Decimal::from(150));
+        assert_eq!(closed.realized_pnl_usd, Decimal::from(99));
+        assert!(!closed.fully_closed);
+        assert_eq!(
+            average.get_position(sol).unwrap().avg_entry_price_usdc,
+            Decimal::from(150)
+        );
+
+        // FIFO sells the $100 lot first, leaving the $200 one
+        let mut fifo = buy_two_lots();
+        let closed = fifo
+            .realize_sell(
+                sol,
+                1_000_000_000,
+                Decimal::from(250),
+                Decimal::ONE,
+                9,
+                CostBasisMethod::Fifo,
+            )
+            .unwrap();
+        assert_eq!(closed.entry_price, Decimal::from(100));
+        assert_eq!(closed.realized_pnl_usd, Decimal::from(149));
+        assert_eq!(
+            fifo.get_position(sol).unwrap().avg_entry_price_usdc,
+            Decimal::from(200)
+        );
+
+        // Over-selling books only what was held
+        let closed = fifo
+            .realize_sell(
+                sol,
+                2_000_000_000,
+                Decimal::from(300),
+                Decimal::ZERO,
+                9,
+                CostBasisMethod::Fifo,
+            )
+            .unwrap();
+        assert_eq!(closed.quantity, Decimal::ONE);
+        assert_eq!(closed.realized_pnl_usd, Decimal::from(-50));
+        assert!(closed.fully_closed);
+        assert!(fifo.get_position(sol).is_none());
+        assert_eq!(fifo.realized_pnl_usd, Decimal::from(99));
+        assert_eq!(fifo.snapshot().realized_pnl, Decimal::from(99));
+        assert_eq!(
+            fifo.realize_sell(
+                sol,
+                1,
+                Decimal::ONE,
+                Decimal::ZERO,
+                9,
+                CostBasisMethod::Fifo
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn test_fee_accounting() {
+        let sol = "So11111111111111111111111111111111111111112";
+        let mut portfolio = Portfolio::new(Decimal::from(10000));
+        portfolio.update_position(sol, "SOL", 2_000_000_000, Decimal::from(100), 9);
+        // Buy fees come straight off realized PnL
+        portfolio.charge_fees(sol, Decimal::new(15, 1));
+        assert_eq!(portfolio.realized_pnl_usd, Decimal::new(-15, 1));
+
+        portfolio
+            .realize_sell(
+                sol,
+                1_000_000_000,
+                Decimal::from(110),
+                Decimal::ONE,
+                9,
+                CostBasisMethod::AverageCost,
+            )
+            .unwrap();
+        let snapshot = portfolio.snapshot();
+        assert_eq!(snapshot.realized_pnl, Decimal::new(75, 1));
+        assert_eq!(snapshot.fees_today, Decimal::new(25, 1));
+        assert_eq!(snapshot.positions[0].fees_usd, Decimal::new(25, 1));
+
+        assert_eq!(portfolio.reset_daily_fees(), Decimal::new(25, 1));
+        assert_eq!(portfolio.fees_today_usd, Decimal::ZERO);
+        assert_eq!(portfolio.fees_total_usd, Decimal::new(25, 1));
+    }
+
+    #[test]
+    fn test_paper_short_and_cover() {
+        let sol = "So11111111111111111111111111111111111111112";
+        let mut portfolio = Portfolio::new(Decimal::from(10000));
+        assert!(!portfolio.sell_short(sol, "SOL", 2_000_000_000, Decimal::from(200), 9));
+
+        portfolio.allow_shorts = true;
+        assert!(portfolio.sell_short(sol, "SOL", 2_000_000_000, Decimal::from(200), 9));
+        assert!(!portfolio.can_sell(sol, 1));
+        portfolio.mark_to_market(&HashMap::from([(sol.to_string(), Decimal::from(90))]));
+
+        // Signed: the short is negative exposure, in profit as the price falls
+        let snapshot = portfolio.snapshot();
+        assert_eq!(snapshot.positions[0].side, PositionSide::Short);
+        assert_eq!(snapshot.positions[0].quantity, Decimal::from(-2));
+        assert_eq!(snapshot.positions[0].unrealized_pnl, Decimal::from(20));
+        assert_eq!(snapshot.gross_exposure, Decimal::from(180));
+        assert_eq!(snapshot.net_exposure, Decimal::from(-180));
+        assert!(portfolio.check_invariants().is_ok());
+
+        // Buying back half: sold at 100, bought at 90, 1 in fees
+        let closed = portfolio
+            .cover_short(
+                sol,
+                1_000_000_000,
+                Decimal::from(90),
+                Decimal::ONE,
+                9,
+                CostBasisMethod::Fifo,
+            )
+            .unwrap();
+        assert_eq!(closed.side, PositionSide::Short);
+        assert_eq!(closed.realized_pnl_usd, Decimal::from(9));
+        assert!(!closed.fully_closed);
+        assert_eq!(
+            portfolio.get_position(sol).unwrap().quantity_raw,
+            1_000_000_000
+        );
+
+        // A short left behind once shorts are switched off is a violation;
+        // settling buys it back at the mark
+        portfolio.allow_shorts = false;
+        assert!(portfolio.check_invariants().is_err());
+        portfolio.allow_shorts = true;
+        let settled = portfolio.settle_shorts(CostBasisMethod::Fifo);
+        assert_eq!(settled[0].realized_pnl_usd, Decimal::from(10));
+        assert!(portfolio.positions.is_empty());
+        assert!(!portfolio.allow_shorts);
+        assert_eq!(portfolio.cash_usdc_raw, 9_910_000_000);
+        assert!(portfolio.check_invariants().is_ok());
+    }
 }
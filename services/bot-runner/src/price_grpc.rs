@@ -0,0 +1,80 @@
+//! gRPC client for data-retrieval's price service
+//!
+//! Used for price lookups when DATA_RETRIEVAL_GRPC_URL is set. The channel
+//! connects lazily and stays open for the runner's lifetime, reconnecting on
+//! its own; callers fall back to the HTTP API when a call fails.
+
+use std::time::Duration;
+
+use tonic::transport::{Channel, Endpoint};
+
+pub mod pb {
+    tonic::include_proto!("trawling.prices.v1");
+}
+
+use pb::price_service_client::PriceServiceClient;
+
+/// Longest a single lookup may take before falling back to HTTP
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(2);
+
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(2);
+
+#[derive(Clone)]
+pub struct PriceGrpcClient {
+    client: PriceServiceClient<Channel>,
+}
+
+impl PriceGrpcClient {
+    /// Client for `url` (`http://data-retrieval:50051`); nothing is dialled
+    /// until the first call
+    pub fn connect_lazy(url: &str) -> anyhow::Result<Self> {
+        let channel = Endpoint::from_shared(url.to_string())?
+            .connect_timeout(CONNECT_TIMEOUT)
+            .timeout(REQUEST_TIMEOUT)
+            .tcp_nodelay(true)
+            .connect_lazy();
+        Ok(Self {
+            client: PriceServiceClient::new(channel),
+        })
+    }
+
+    /// Current USD price of `symbol`, with its market summary
+    pub async fn get_price(&self, symbol: &str) -> anyhow::Result<pb::Price> {
+        let response = self
+            .client
+            .clone()
+            .get_price(pb::PriceRequest {
+                symbol: symbol.to_string(),
+                quote: "USD".to_string(),
+            })
+            .await?;
+        Ok(response.into_inner())
+    }
+
+    /// Current USD prices of `symbols`, keyed by canonical symbol
+    pub async fn get_batch(&self, symbols: Vec<String>) -> anyhow::Result<pb::BatchResponse> {
+        let response = self
+            .client
+            .clone()
+            .get_batch(pb::BatchRequest { symbols })
+            .await?;
+        Ok(response.into_inner())
+    }
+
+    /// Streamed USD prices of `symbols` until the stream is dropped
+    pub async fn stream_prices(
+        &self,
+        symbols: Vec<String>,
+    ) -> anyhow::Result<tonic::Streaming<pb::StreamEvent>> {
+        // The timeout covers the call, not how long the stream stays open
+        let response = self
+            .client
+            .clone()
+            .stream_prices(pb::StreamRequest {
+                symbols,
+                quote: "USD".to_string(),
+            })
+            .await?;
+        Ok(response.into_inner())
+    }
+}
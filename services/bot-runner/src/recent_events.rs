@@ -0,0 +1,117 @@
+//! Ring buffer of recent trade outcomes for the decision context
+//!
+//! The runner records each intent's outcome (confirmed, failed, blocked) here
+//! and sends the newest ones to OpenClaw as `recent_events`, so the agent
+//! sees how its last calls played out. With a path the buffer is mirrored to
+//! disk and survives restarts.
+
+use std::collections::VecDeque;
+use std::path::PathBuf;
+use tracing::warn;
+
+use crate::types::TradeEvent;
+
+/// Outcomes kept in the buffer
+pub const RECENT_EVENTS_CAPACITY: usize = 50;
+
+pub struct RecentEvents {
+    events: VecDeque<TradeEvent>,
+    capacity: usize,
+    path: Option<PathBuf>,
+}
+
+impl RecentEvents {
+    /// Buffer holding up to `capacity` events, loaded from `path` if given
+    pub fn new(capacity: usize, path: Option<PathBuf>) -> Self {
+        let mut events: VecDeque<TradeEvent> = path
+            .as_ref()
+            .and_then(|p| crate::sealed::read_to_string(p).ok())
+            .and_then(|raw| match serde_json::from_str(&raw) {
+                Ok(events) => Some(events),
+                Err(e) => {
+                    warn!("Ignoring unreadable recent events file: {}", e);
+                    None
+                }
+            })
+            .unwrap_or_default();
+        while events.len() > capacity {
+            events.pop_front();
+        }
+        Self {
+            events,
+            capacity,
+            path,
+        }
+    }
+
+    /// Add an event, dropping the oldest once full
+    pub fn push(&mut self, event: TradeEvent) {
+        if self.capacity == 0 {
+            return;
+        }
+        if self.events.len() == self.capacity {
+            self.events.pop_front();
+        }
+        self.events.push_back(event);
+
+        if let Some(path) = &self.path {
+            let written = serde_json::to_string(&self.events)
+                .map_err(anyhow::Error::from)
+                .and_then(|json| crate::sealed::write(path, json).map_err(anyhow::Error::from));
+            if let Err(e) = written {
+                warn!("Failed to persist recent events: {}", e);
+            }
+        }
+    }
+
+    /// The newest `n` events, oldest first
+    pub fn latest(&self, n: usize) -> Vec<TradeEvent> {
+        let skip = self.events.len().saturating_sub(n);
+        self.events.iter().skip(skip).cloned().collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.events.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(symbol: &str) -> TradeEvent {
+        TradeEvent {
+            timestamp: chrono::Utc::now(),
+            event_type: "trade_confirmed".to_string(),
+            symbol: symbol.to_string(),
+            side: Some("Buy".to_string()),
+            amount_usd: None,
+            outcome: Some("confirmed".to_string()),
+        }
+    }
+
+    #[test]
+    fn test_bounded_and_persisted() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("recent_events.json");
+        let mut buffer = RecentEvents::new(3, Some(path.clone()));
+        assert!(buffer.is_empty());
+        for symbol in ["A", "B", "C", "D"] {
+            buffer.push(event(symbol));
+        }
+        assert_eq!(buffer.len(), 3);
+        let symbols = |events: Vec<TradeEvent>| -> Vec<String> {
+            events.into_iter().map(|e| e.symbol).collect()
+        };
+        assert_eq!(symbols(buffer.latest(2)), ["C", "D"]);
+        assert_eq!(symbols(buffer.latest(10)), ["B", "C", "D"]);
+
+        // Reloads from disk, trimmed to the new capacity
+        let reloaded = RecentEvents::new(2, Some(path));
+        assert_eq!(symbols(reloaded.latest(10)), ["C", "D"]);
+    }
+}
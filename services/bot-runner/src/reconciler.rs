@@ -95,7 +95,20 @@ impl HoldingsReconciler {
         let on_chain_holdings = self.fetch_on_chain_holdings().await?;
 
         // Compare with internal portfolio
-        let result = self.compare_balances(portfolio, &on_chain_holdings);
+        let mut result = self.compare_balances(portfolio, &on_chain_holdings);
+
+        // Look up tokens we've never seen so they're tracked with a symbol
+        // and real decimals
+        for new in result
+            .new_on_chain
+            .iter_mut()
+            .filter(|n| n.symbol.is_none())
+        {
+            match self.executor.mint_metadata(&new.mint).await {
+                Ok(token) => new.symbol = Some(token.symbol),
+                Err(e) => debug!("No metadata for {}: {}", new.mint, e),
+            }
+        }
 
         // Log summary
         info!(
@@ -118,8 +131,8 @@ impl HoldingsReconciler {
         Ok(result)
     }
 
-    /// Fetch on-chain holdings via claw-trader
-    async fn fetch_on_chain_holdings(&self) -> anyhow::Result<HashMap<String, u64>> {
+    /// Fetch on-chain holdings via claw-trader (mint -> raw amount, SOL included)
+    pub async fn fetch_on_chain_holdings(&self) -> anyhow::Result<HashMap<String, u64>> {
         // Use claw-trader holdings command
         let result = self
             .executor
@@ -206,8 +219,8 @@ impl HoldingsReconciler {
         let mut new_on_chain = Vec::new();
         for (mint, &amount) in on_chain {
             if !portfolio.positions.contains_key(mint) && amount > 0 {
-                // Try to get symbol
-                let symbol = crate::amount::get_token_info(mint).map(|t| t.symbol);
+                // Symbols of mints not yet looked up are filled in by reconcile()
+                let symbol = crate::mints::symbol(mint);
 
                 new_on_chain.push(NewBalance {
                     mint: mint.clone(),
@@ -272,6 +285,10 @@ impl HoldingsReconciler {
                     current_price_usdc: None,
                     last_updated: chrono::Utc::now(),
                     unknown_cost_basis: true, // Flag for PnL handling
+                    high_water_price_usdc: None,
+                    lots: Vec::new(),
+                    fees_usd: rust_decimal::Decimal::ZERO,
+                    side: crate::portfolio::PositionSide::Long,
                 },
             );
         }
@@ -0,0 +1,197 @@
+//! Trading-day boundaries for the daily counters
+//!
+//! `max_trades_per_day` and `max_daily_loss_usd` count against the current
+//! trading day, which starts at midnight in `BOT_DAY_ROLLOVER_TZ` (UTC by
+//! default). The runner checks for a rollover on every sync and decision
+//! tick and resets its counters when the day changes.
+//!
+//! At each rollover the runner also takes the day's official close, a
+//! [`DailyMark`]: equity, cash and every position at the prices the portfolio
+//! was last marked at. It travels in the `day_rollover` event, so it survives
+//! restarts in the outbox, and the control plane keeps it as the bot's
+//! canonical record for the day.
+
+use chrono::{DateTime, NaiveDate, Utc};
+use chrono_tz::Tz;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+use crate::portfolio::PortfolioSnapshot;
+
+pub struct DayRollover {
+    tz: Tz,
+    /// Trading day the counters currently belong to
+    current_day: NaiveDate,
+}
+
+impl DayRollover {
+    /// Track days in `tz`, starting from the day `last_seen` falls on
+    ///
+    /// Pass the time the counters were last saved so a runner restarted
+    /// after midnight rolls the restored counters over straight away.
+    pub fn new(tz: Tz, last_seen: DateTime<Utc>) -> Self {
+        Self {
+            tz,
+            current_day: last_seen.with_timezone(&tz).date_naive(),
+        }
+    }
+
+    pub fn tz(&self) -> Tz {
+        self.tz
+    }
+
+    pub fn current_day(&self) -> NaiveDate {
+        self.current_day
+    }
+
+    /// Move to the day `now` falls on; returns the day that just ended
+    pub fn check(&mut self, now: DateTime<Utc>) -> Option<NaiveDate> {
+        let today = now.with_timezone(&self.tz).date_naive();
+        if today > self.current_day {
+            Some(std::mem::replace(&mut self.current_day, today))
+        } else {
+            None
+        }
+    }
+}
+
+/// One position in a [`DailyMark`]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MarkedPosition {
+    pub mint: String,
+    pub symbol: String,
+    /// Negative for a short
+    pub quantity: Decimal,
+    /// Price the position was valued at
+    pub price_usd: Decimal,
+    pub value_usd: Decimal,
+}
+
+/// End-of-day snapshot of the portfolio for the trading day that just ended
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DailyMark {
+    pub day: NaiveDate,
+    pub timezone: String,
+    pub equity_usd: Decimal,
+    pub cash_usd: Decimal,
+    pub realized_pnl_usd: Decimal,
+    pub unrealized_pnl_usd: Decimal,
+    pub fees_usd: Decimal,
+    pub trades: u32,
+    /// When the portfolio's prices were last marked to market
+    pub prices_marked_at: DateTime<Utc>,
+    pub positions: Vec<MarkedPosition>,
+}
+
+impl DailyMark {
+    /// Close `day` from a portfolio snapshot and the day's counters
+    pub fn close(
+        day: NaiveDate,
+        tz: Tz,
+        snapshot: &PortfolioSnapshot,
+        prices_marked_at: DateTime<Utc>,
+        trades: u32,
+        realized_pnl_usd: Decimal,
+        fees_usd: Decimal,
+    ) -> Self {
+        let mut positions: Vec<MarkedPosition> = snapshot
+            .positions
+            .iter()
+            .map(|p| MarkedPosition {
+                mint: p.mint.clone(),
+                symbol: p.symbol.clone(),
+                quantity: p.quantity,
+                price_usd: p.current_price,
+                value_usd: p.market_value,
+            })
+            .collect();
+        positions.sort_by(|a, b| a.mint.cmp(&b.mint));
+        Self {
+            day,
+            timezone: tz.name().to_string(),
+            equity_usd: snapshot.total_equity,
+            cash_usd: snapshot.cash_usdc,
+            realized_pnl_usd,
+            unrealized_pnl_usd: snapshot.unrealized_pnl,
+            fees_usd,
+            trades,
+            prices_marked_at,
+            positions,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::portfolio::Portfolio;
+    use chrono::TimeZone;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_rolls_over_at_local_midnight() {
+        let at = |h: u32, m: u32| Utc.with_ymd_and_hms(2026, 3, 10, h, m, 0).unwrap();
+
+        let mut utc = DayRollover::new(chrono_tz::UTC, at(12, 0));
+        assert_eq!(utc.check(at(23, 59)), None);
+        let next_day = Utc.with_ymd_and_hms(2026, 3, 11, 0, 0, 1).unwrap();
+        assert_eq!(
+            utc.check(next_day),
+            Some(NaiveDate::from_ymd_opt(2026, 3, 10).unwrap())
+        );
+        assert_eq!(utc.check(next_day), None);
+
+        // 04:00 UTC is midnight in New York (EDT, UTC-4)
+        let mut ny = DayRollover::new(chrono_tz::America::New_York, at(3, 0));
+        assert_eq!(
+            ny.current_day(),
+            NaiveDate::from_ymd_opt(2026, 3, 9).unwrap()
+        );
+        assert_eq!(
+            ny.check(at(4, 30)),
+            Some(NaiveDate::from_ymd_opt(2026, 3, 9).unwrap())
+        );
+        // The clock going backwards never un-rolls a day
+        assert_eq!(ny.check(at(3, 0)), None);
+    }
+
+    #[test]
+    fn test_daily_mark_values_positions_at_marked_prices() {
+        let sol = crate::executor::SOL_MINT;
+        let mut portfolio = Portfolio::new(Decimal::from(1_000));
+        portfolio.update_position(sol, "SOL", 2_000_000_000, Decimal::from(100), 9);
+        portfolio.mark_to_market(&HashMap::from([(sol.to_string(), Decimal::from(120))]));
+        let snapshot = portfolio.snapshot();
+        let marked_at = Utc.with_ymd_and_hms(2026, 3, 10, 23, 58, 0).unwrap();
+        let day = NaiveDate::from_ymd_opt(2026, 3, 10).unwrap();
+
+        let mark = DailyMark::close(
+            day,
+            chrono_tz::UTC,
+            &snapshot,
+            marked_at,
+            3,
+            Decimal::from(-12),
+            Decimal::ONE,
+        );
+        assert_eq!(mark.timezone, "UTC");
+        assert_eq!(mark.equity_usd, snapshot.total_equity);
+        assert_eq!(mark.unrealized_pnl_usd, Decimal::from(40));
+        assert_eq!(mark.trades, 3);
+        assert_eq!(
+            mark.positions,
+            vec![MarkedPosition {
+                mint: sol.to_string(),
+                symbol: "SOL".to_string(),
+                quantity: Decimal::from(2),
+                price_usd: Decimal::from(120),
+                value_usd: Decimal::from(240),
+            }]
+        );
+
+        // Round-trips through the event metadata
+        let json = serde_json::to_value(&mark).unwrap();
+        assert_eq!(json["day"], "2026-03-10");
+        assert_eq!(serde_json::from_value::<DailyMark>(json).unwrap(), mark);
+    }
+}
@@ -1,34 +1,58 @@
 //! Bot Runner - Main orchestration loop
 //!
 //! Executes trading decisions from OpenClaw gateway and enforces risk rails.
-use rust_decimal::prelude::ToPrimitive;
 use rust_decimal::Decimal;
 
 use std::collections::HashMap;
 use std::path::PathBuf;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 use std::time::Duration;
 use tokio::signal;
 use tokio::time::interval;
 use tracing::{debug, error, info, warn};
 
-use crate::client::{ControlPlaneClient, EventInput, MetricInput};
+use crate::amount::{to_raw_amount, AmountError, USDC_DECIMALS};
+use crate::client::{
+    BotCommand, ControlPlaneClient, EventInput, LlmUsageReport, MetricInput, SyncRequest,
+    SyncStateSummary,
+};
 use crate::config::{BotConfig, Config, TradingMode};
-use crate::executor::{NormalizedTradeResult, TradeExecutor, TradeSide};
+use crate::cooldown::{format_remaining, SymbolCooldowns};
+use crate::degradation::{DegradationMonitor, DegradedMode, Health};
+use crate::drawdown::{DrawdownTracker, DrawdownTransition};
+use crate::executor::{NormalizedTradeResult, TradeExecutor, TradeSide, TradeStage, USDC_MINT};
+use crate::funding::FundingCheck;
 use crate::gateway::GatewayManager;
+use crate::governor::{Governor, GovernorTransition};
+use crate::handover::StateBundle;
 use crate::intent::IntentRegistry;
+use crate::inventory;
+use crate::journal::{ChainedJournalEntry, JournalChain};
+use crate::log_level::{LogLevelControl, DEFAULT_LOG_LEVEL_TTL_SECS};
 use crate::openclaw::OpenClawClient;
-use crate::portfolio::{Portfolio, PortfolioSnapshot};
+use crate::orders::{ExitOrder, OrderManager};
+use crate::performance::PerformanceTracker;
+use crate::portfolio::{ClosedTrade, Portfolio, PortfolioSnapshot, PositionSide};
+use crate::recent_events::{RecentEvents, RECENT_EVENTS_CAPACITY};
 use crate::reconciler::HoldingsReconciler;
+use crate::rollover::{DailyMark, DayRollover};
+use crate::state::{PersistedState, StateStore};
+use crate::twap::TwapFill;
 use crate::types::{
     DecisionContext, DecisionJournalEntry, ExecutionOutcome, Holding, IntentValidation,
-    LastTradeOutcome, OpenClawIntent, PortfolioSnapshot as OcPortfolioSnapshot, PriceQuote,
-    RiskRails, RunnerState, RunnerStatus, TradeAction, TradeEvent,
+    LastTradeOutcome, MarketMetadata, OpenClawIntent, PortfolioSnapshot as OcPortfolioSnapshot,
+    PriceQuote, RiskRails, RunnerState, RunnerStatus, TradeAction, TradeEvent,
 };
 
 /// State directory for runner files
 const DEFAULT_STATE_DIR: &str = "/opt/bot-runner/state";
 
+/// Maximum events held while the control plane is unreachable (oldest dropped first)
+const MAX_OUTBOX_EVENTS: usize = 500;
+
+/// Trade outcomes included in each decision context
+const CONTEXT_RECENT_EVENTS: usize = 10;
+
 /// Main bot runner that manages the trading loop
 pub struct BotRunner {
     client: Arc<ControlPlaneClient>,
@@ -53,22 +77,86 @@ pub struct BotRunner {
     last_trade_outcome: Option<LastTradeOutcome>,
     /// Daily realized PnL tracking
     realized_pnl_today: Decimal,
+    /// Events waiting to be delivered on the next sync
+    outbox: Vec<EventInput>,
+    /// Critical events the priority lane couldn't deliver, moved into the
+    /// outbox at the next sync
+    priority_fallback: Arc<Mutex<Vec<EventInput>>>,
+    /// Whether the live funding pre-flight has passed since switching to live
+    live_funded: bool,
+    /// Last funding check result (to avoid repeating identical events)
+    last_funding_check: Option<FundingCheck>,
+    /// Tracing filter handle for `set_log_level` (None when not installed)
+    log_level: Option<LogLevelControl>,
+    /// Snapshot of counters, portfolio and outbox kept across restarts
+    state_store: StateStore,
+    /// Kill switch and platform halt checked before each decision tick
+    governor: Governor,
+    /// Hash chain over the decision journal
+    journal: JournalChain,
+    /// Journal entries waiting to be uploaded on the next sync
+    journal_outbox: Vec<ChainedJournalEntry>,
+    /// Owner paused the bot from the app (desired status from the control plane)
+    owner_paused: bool,
+    /// Recent trade outcomes for the decision context
+    recent_events: RecentEvents,
+    /// Trading day that `trade_count` and `realized_pnl_today` belong to
+    day_rollover: DayRollover,
+    /// Stop-loss / take-profit levels for open positions
+    orders: OrderManager,
+    /// `flatten_positions` command waiting to run at the end of this sync
+    flatten_requested: Option<uuid::Uuid>,
+    /// `export_state` command waiting to run at the end of this sync
+    export_requested: Option<uuid::Uuid>,
+    /// `upload_diagnostics` command waiting to run at the end of this sync
+    diagnostics_requested: Option<uuid::Uuid>,
+    /// State was exported for a redeploy; this droplet no longer trades
+    handed_over: bool,
+    /// Equity high-water mark; buys stop while drawdown is past the cap
+    drawdown: DrawdownTracker,
+    /// Last fill per mint, for `asset_limits.cooldown_minutes`
+    cooldowns: SymbolCooldowns,
+    /// Outside the config's trading window on the last tick
+    window_closed: bool,
+    /// Last SOL price quoted, for pricing network fees
+    sol_price_usd: Option<Decimal>,
+    /// This tick's quotes whose sources disagreed, by mint; nothing trades on them
+    unreliable_quotes: HashMap<String, PriceQuote>,
+    /// Trailing closes and equity behind the statistics sent with metrics
+    performance: PerformanceTracker,
+    /// Pre-existing wallet holdings have been seeded into the portfolio
+    inventory_imported: bool,
+    /// Rung of the degradation ladder as of the last decision tick
+    degradation: DegradationMonitor,
+    /// Last sync the control plane answered (process start until then)
+    last_sync_at: chrono::DateTime<chrono::Utc>,
+    /// LLM usage not yet reported to the control plane
+    llm_usage: LlmUsageReport,
+}
+
+/// State directory from `BOT_STATE_DIR`, or the droplet default
+pub fn state_dir() -> PathBuf {
+    std::env::var("BOT_STATE_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(|_| PathBuf::from(DEFAULT_STATE_DIR))
+}
+
+/// The asset an intent trades: what a buy goes into or a sell comes out of
+fn traded_mint(intent: &OpenClawIntent) -> &str {
+    match intent.action {
+        TradeAction::Sell => &intent.input_mint,
+        _ => &intent.output_mint,
+    }
 }
 
 impl BotRunner {
     /// Create new bot runner
     pub fn new(client: Arc<ControlPlaneClient>, config: Config) -> Self {
-        // Initialize portfolio with starting cash
-        let portfolio = Portfolio::new(Decimal::from(10000));
-
         // Initialize OpenClaw components
         let openclaw_client = OpenClawClient::new();
         let gateway_manager = GatewayManager::new();
 
-        // State directory from env or default
-        let state_dir = std::env::var("BOT_STATE_DIR")
-            .map(PathBuf::from)
-            .unwrap_or_else(|_| PathBuf::from(DEFAULT_STATE_DIR));
+        let state_dir = state_dir();
 
         // Ensure state directories exist
         if let Err(e) = std::fs::create_dir_all(&state_dir) {
@@ -78,36 +166,94 @@ impl BotRunner {
             warn!("Failed to create journal dir: {}", e);
         }
 
+        // Pick up where the last process left off, or start with fresh cash
+        let state_store = StateStore::new(&state_dir);
+        let governor = Governor::new(&state_dir);
+        let journal = JournalChain::load(&state_dir.join("journal"));
+        let recent_events = RecentEvents::new(
+            RECENT_EVENTS_CAPACITY,
+            Some(state_dir.join("recent_events.json")),
+        );
+        let saved = match state_store.load(config.bot_id) {
+            Some(saved) => {
+                info!(
+                    "Restored runner state from {} ({} trades today, {} events queued)",
+                    saved.saved_at,
+                    saved.trade_count,
+                    saved.outbox.len()
+                );
+                saved
+            }
+            None => PersistedState::fresh(config.bot_id),
+        };
+        let day_rollover = DayRollover::new(config.day_rollover_tz, saved.saved_at);
+        let orders = OrderManager::new(Some(state_dir.join("exit_levels.json")));
+
         Self {
             client,
             config,
             current_config: None,
             executor: None,
             intent_registry: IntentRegistry::new(),
-            portfolio,
+            portfolio: saved.portfolio,
             reconciler: None,
-            trade_count: 0,
+            trade_count: saved.trade_count,
             openclaw_client,
             gateway_manager,
             state_dir,
-            status: RunnerStatus::Idle,
+            status: if saved.owner_paused {
+                RunnerStatus::Paused
+            } else {
+                RunnerStatus::Idle
+            },
             last_plan_id: None,
             last_trade_outcome: None,
-            realized_pnl_today: Decimal::ZERO,
+            realized_pnl_today: saved.realized_pnl_today,
+            outbox: saved.outbox,
+            priority_fallback: Arc::new(Mutex::new(Vec::new())),
+            live_funded: false,
+            last_funding_check: None,
+            log_level: None,
+            state_store,
+            governor,
+            journal,
+            journal_outbox: saved.journal_outbox,
+            owner_paused: saved.owner_paused,
+            recent_events,
+            day_rollover,
+            orders,
+            flatten_requested: None,
+            export_requested: None,
+            diagnostics_requested: None,
+            handed_over: false,
+            drawdown: saved.drawdown,
+            cooldowns: saved.cooldowns,
+            performance: saved.performance,
+            inventory_imported: saved.inventory_imported,
+            window_closed: false,
+            sol_price_usd: None,
+            unreliable_quotes: HashMap::new(),
+            degradation: DegradationMonitor::default(),
+            last_sync_at: chrono::Utc::now(),
+            llm_usage: LlmUsageReport::default(),
         }
     }
 
+    /// Let `set_log_level` commands adjust this process's tracing filter
+    pub fn with_log_level_control(mut self, control: LogLevelControl) -> Self {
+        self.log_level = Some(control);
+        self
+    }
+
     /// Run the main bot loop with graceful shutdown handling
     pub async fn run(mut self) -> anyhow::Result<()> {
         info!("Bot runner starting main loop...");
         info!("Keypair path: {:?}", self.config.keypair_path);
         info!("Wallet address: {}", self.config.wallet_address);
 
-        // Config polling interval (30 seconds)
-        let mut config_interval = interval(Duration::from_secs(30));
-
-        // Heartbeat interval (30 seconds)
-        let mut heartbeat_interval = interval(Duration::from_secs(30));
+        // Sync interval (30 seconds) - heartbeat, metrics and events in one call;
+        // config is only fetched when the sync response says one is pending
+        let mut sync_interval = interval(Duration::from_secs(30));
 
         // Trading interval (60 seconds - check for signals every minute)
         let mut trading_interval = interval(Duration::from_secs(60));
@@ -126,8 +272,7 @@ impl BotRunner {
         // Run main loop with shutdown handling
         let shutdown_reason = self
             .run_main_loop(
-                &mut config_interval,
-                &mut heartbeat_interval,
+                &mut sync_interval,
                 &mut trading_interval,
                 &mut reconcile_interval,
                 &mut cleanup_interval,
@@ -143,8 +288,7 @@ impl BotRunner {
     /// Main loop separated for cleaner shutdown handling
     async fn run_main_loop(
         &mut self,
-        config_interval: &mut tokio::time::Interval,
-        heartbeat_interval: &mut tokio::time::Interval,
+        sync_interval: &mut tokio::time::Interval,
         trading_interval: &mut tokio::time::Interval,
         reconcile_interval: &mut tokio::time::Interval,
         cleanup_interval: &mut tokio::time::Interval,
@@ -156,20 +300,18 @@ impl BotRunner {
                     info!("Received SIGINT, initiating graceful shutdown...");
                     return "SIGINT".to_string();
                 }
-                _ = config_interval.tick() => {
-                    if let Err(e) = self.poll_config().await {
-                        error!("Config poll error: {}", e);
-                    }
-                }
-                _ = heartbeat_interval.tick() => {
-                    if let Err(e) = self.send_heartbeat().await {
-                        error!("Heartbeat error: {}", e);
+                _ = sync_interval.tick() => {
+                    self.check_day_rollover();
+                    if let Err(e) = self.sync_with_control_plane().await {
+                        error!("Sync error: {}", e);
                     }
+                    self.persist_state();
                 }
                 _ = trading_interval.tick() => {
                     if let Err(e) = self.decision_tick().await {
                         error!("Decision tick error: {}", e);
                     }
+                    self.persist_state();
                 }
                 _ = reconcile_interval.tick() => {
                     if let Err(e) = self.reconcile_holdings().await {
@@ -184,7 +326,7 @@ impl BotRunner {
     }
 
     /// Perform graceful shutdown: send final events and cleanup
-    async fn graceful_shutdown(&self, reason: &str) -> anyhow::Result<()> {
+    async fn graceful_shutdown(&mut self, reason: &str) -> anyhow::Result<()> {
         info!("Performing graceful shutdown...");
 
         // Send shutdown event to control plane
@@ -198,21 +340,59 @@ impl BotRunner {
             timestamp: chrono::Utc::now(),
         };
 
-        if let Err(e) = self.client.send_events(vec![event]).await {
-            warn!("Failed to send shutdown event: {}", e);
+        // Straight out on the priority lane; the final sync is the fallback
+        let sent = self
+            .client
+            .send_priority_events(std::slice::from_ref(&event))
+            .await;
+        if let Err(e) = sent {
+            warn!("Failed to send shutdown event on the priority lane: {}", e);
+            self.push_outbox(event);
         }
 
-        // Send final heartbeat
-        if let Err(e) = self.send_heartbeat().await {
-            warn!("Failed to send final heartbeat: {}", e);
+        // Final sync flushes any queued events
+        if let Err(e) = self.sync_with_control_plane().await {
+            warn!("Failed to send final sync: {}", e);
         }
+        self.persist_state();
 
         info!("Graceful shutdown complete");
         Ok(())
     }
 
+    /// Snapshot the state that must survive a restart (failures are only logged)
+    fn persist_state(&self) {
+        let state = PersistedState {
+            bot_id: self.config.bot_id,
+            trade_count: self.trade_count,
+            realized_pnl_today: self.realized_pnl_today,
+            portfolio: self.portfolio.clone(),
+            outbox: self.outbox.clone(),
+            journal_outbox: self.journal_outbox.clone(),
+            owner_paused: self.owner_paused,
+            drawdown: self.drawdown,
+            cooldowns: self.cooldowns.clone(),
+            performance: self.performance.clone(),
+            inventory_imported: self.inventory_imported,
+            saved_at: chrono::Utc::now(),
+        };
+        if let Err(e) = self.state_store.save(&state) {
+            warn!("Failed to persist runner state: {}", e);
+        }
+    }
+
     /// Poll for config updates
     async fn poll_config(&mut self) -> anyhow::Result<()> {
+        let result = self.fetch_and_apply_config().await;
+        if result.is_err() {
+            // Make sure a failed apply is retried with a full fetch, not a 304
+            self.client.reset_config_etag();
+        }
+        result
+    }
+
+    /// Fetch config (conditionally) and apply it if the version changed
+    async fn fetch_and_apply_config(&mut self) -> anyhow::Result<()> {
         match self.client.get_config().await? {
             Some(config) => {
                 // Check if config changed
@@ -251,7 +431,11 @@ impl BotRunner {
                 &self.config.solana_rpc_url,
                 self.config.keypair_path.clone(),
                 config.execution, // Pass execution config
-            ) {
+            )
+            .and_then(|executor| match &self.config.data_retrieval_grpc_url {
+                Some(url) => executor.with_price_grpc(url),
+                None => Ok(executor),
+            }) {
                 Ok(executor) => {
                     // Initialize reconciler with same executor
                     let reconciler = HoldingsReconciler::new(
@@ -311,7 +495,36 @@ impl BotRunner {
             })),
             timestamp: chrono::Utc::now(),
         };
-        self.client.send_events(vec![event]).await.ok();
+        self.queue_event(event);
+
+        // Switching into live mode re-runs the funding pre-flight
+        let was_live = self
+            .current_config
+            .as_ref()
+            .is_some_and(|c| c.trading_mode == TradingMode::Live);
+        if config.trading_mode == TradingMode::Live && !was_live {
+            self.live_funded = false;
+            self.last_funding_check = None;
+        }
+
+        for asset in &config.asset_universe {
+            if let Some(decimals) = asset.decimals {
+                crate::mints::register(&asset.mint, decimals);
+            }
+        }
+
+        // Simulated shorts only exist in paper mode
+        let allow_shorts = self.config.paper_shorts && config.trading_mode == TradingMode::Paper;
+        if self.portfolio.allow_shorts && !allow_shorts {
+            for closed in self.portfolio.settle_shorts(self.config.cost_basis) {
+                warn!(
+                    "Settled paper short on {} at {} leaving paper mode",
+                    closed.symbol, closed.exit_price
+                );
+                self.realized_pnl_today += closed.realized_pnl_usd;
+            }
+        }
+        self.portfolio.allow_shorts = allow_shorts;
 
         self.current_config = Some(config);
         Ok(())
@@ -321,13 +534,17 @@ impl BotRunner {
     async fn reconcile_holdings(&mut self) -> anyhow::Result<()> {
         // Take ownership of reconciler temporarily to avoid borrow issues
         if let Some(mut reconciler) = self.reconciler.take() {
+            if !self.inventory_imported {
+                self.import_inventory(&reconciler).await;
+            }
+
             info!("Running holdings reconciliation...");
 
             match reconciler.reconcile(&self.portfolio).await {
                 Ok(result) => {
                     // Send portfolio snapshot
                     let snapshot = self.portfolio.snapshot();
-                    self.send_portfolio_snapshot(&snapshot).await;
+                    self.send_portfolio_snapshot(&snapshot);
 
                     // Apply corrections if significant discrepancies
                     if !result.discrepancies.is_empty() || !result.missing_on_chain.is_empty() {
@@ -349,8 +566,114 @@ impl BotRunner {
         Ok(())
     }
 
-    /// Send portfolio snapshot to control plane
-    async fn send_portfolio_snapshot(&self, snapshot: &PortfolioSnapshot) {
+    /// Seed positions for holdings the wallet had before this bot, once
+    ///
+    /// Cost basis comes from the wallet's recent history where it can (see
+    /// `inventory`); everything else goes in as `unknown_cost_basis`, the
+    /// way reconciliation would have added it. If the holdings can't be read
+    /// the import is retried on the next reconciliation.
+    async fn import_inventory(&mut self, reconciler: &HoldingsReconciler) {
+        let holdings = match reconciler.fetch_on_chain_holdings().await {
+            Ok(holdings) => holdings,
+            Err(e) => {
+                warn!("Inventory import deferred, holdings unavailable: {}", e);
+                return;
+            }
+        };
+        let holdings: HashMap<String, u64> = holdings
+            .into_iter()
+            .filter(|(mint, _)| !self.portfolio.positions.contains_key(mint))
+            .collect();
+
+        let (history, history_error) = if holdings.keys().all(|m| inventory::is_cash(m)) {
+            (Vec::new(), None)
+        } else {
+            let http = reqwest::Client::new();
+            match inventory::fetch_history(
+                &http,
+                &self.config.solana_rpc_url,
+                &self.config.wallet_address,
+            )
+            .await
+            {
+                Ok(history) => (history, None),
+                Err(e) => {
+                    warn!("Wallet history unavailable, cost basis unknown: {}", e);
+                    (Vec::new(), Some(e.to_string()))
+                }
+            }
+        };
+
+        let assets = inventory::build(&holdings, &history);
+        let mut imported = Vec::new();
+        for asset in &assets {
+            if let Some(decimals) = asset.decimals {
+                crate::mints::register(&asset.mint, decimals);
+            }
+            let known = self
+                .get_symbol_for_mint(&asset.mint)
+                .or_else(|| crate::mints::symbol(&asset.mint));
+            let symbol = match (known, &self.executor) {
+                (Some(symbol), _) => symbol,
+                (None, Some(executor)) => executor
+                    .mint_metadata(&asset.mint)
+                    .await
+                    .map(|t| t.symbol)
+                    .unwrap_or_else(|_| "UNKNOWN".to_string()),
+                (None, None) => "UNKNOWN".to_string(),
+            };
+            let basis = if asset.unknown_cost_basis() {
+                "unknown"
+            } else {
+                "from history"
+            };
+            info!(
+                "Importing pre-existing holding {} = {} (cost basis {})",
+                symbol, asset.quantity_raw, basis
+            );
+            self.portfolio.positions.insert(
+                asset.mint.clone(),
+                crate::portfolio::Position {
+                    mint: asset.mint.clone(),
+                    symbol: symbol.clone(),
+                    quantity_raw: asset.quantity_raw,
+                    avg_entry_price_usdc: asset.avg_entry_price_usdc.unwrap_or(Decimal::ZERO),
+                    current_price_usdc: None,
+                    last_updated: chrono::Utc::now(),
+                    unknown_cost_basis: asset.unknown_cost_basis(),
+                    high_water_price_usdc: None,
+                    lots: asset.lots.clone(),
+                    fees_usd: Decimal::ZERO,
+                    side: crate::portfolio::PositionSide::Long,
+                },
+            );
+            let mut entry = serde_json::to_value(asset).unwrap_or_default();
+            entry["symbol"] = serde_json::json!(symbol);
+            entry["unknown_cost_basis"] = serde_json::json!(asset.unknown_cost_basis());
+            imported.push(entry);
+        }
+
+        let unknown = assets.iter().filter(|a| a.unknown_cost_basis()).count();
+        self.queue_event(EventInput {
+            event_type: "initial_inventory".to_string(),
+            message: format!(
+                "Imported {} pre-existing holdings ({} with unknown cost basis)",
+                assets.len(),
+                unknown
+            ),
+            metadata: Some(serde_json::json!({
+                "assets": imported,
+                "transactions_scanned": history.len(),
+                "history_error": history_error,
+            })),
+            timestamp: chrono::Utc::now(),
+        });
+        self.inventory_imported = true;
+        self.persist_state();
+    }
+
+    /// Queue portfolio snapshot for the control plane
+    fn send_portfolio_snapshot(&mut self, snapshot: &PortfolioSnapshot) {
         let metadata = serde_json::json!({
             "cash_usdc": snapshot.cash_usdc.to_string(),
             "total_equity": snapshot.total_equity.to_string(),
@@ -370,13 +693,160 @@ impl BotRunner {
             timestamp: chrono::Utc::now(),
         };
 
-        if let Err(e) = self.client.send_events(vec![event]).await {
-            warn!("Failed to send portfolio snapshot: {}", e);
+        self.queue_event(event);
+    }
+
+    /// Reset the daily counters once the trading day has ended
+    ///
+    /// Queues a `day_rollover` event summarising the finished day, with
+    /// the day's official close under `mark`.
+    fn check_day_rollover(&mut self) {
+        let Some(ended) = self.day_rollover.check(chrono::Utc::now()) else {
+            return;
+        };
+        let snapshot = self.portfolio.snapshot();
+        let equity = snapshot.total_equity;
+        let fees = self.portfolio.reset_daily_fees();
+        let mark = DailyMark::close(
+            ended,
+            self.day_rollover.tz(),
+            &snapshot,
+            self.portfolio.last_updated,
+            self.trade_count,
+            self.realized_pnl_today,
+            fees,
+        );
+        info!(
+            "Trading day {} ended: {} trades, realized PnL {}, fees {}",
+            ended,
+            self.trade_count,
+            self.realized_pnl_today,
+            fees.round_dp(2)
+        );
+        self.queue_event(EventInput {
+            event_type: "day_rollover".to_string(),
+            message: format!(
+                "Day {} closed with {} trades and {} realized PnL",
+                ended, self.trade_count, self.realized_pnl_today
+            ),
+            metadata: Some(serde_json::json!({
+                "day": ended.to_string(),
+                "timezone": self.day_rollover.tz().name(),
+                "trades": self.trade_count,
+                "realized_pnl_usd": self.realized_pnl_today.to_string(),
+                "fees_usd": fees.round_dp(6).to_string(),
+                "equity_usd": equity.to_string(),
+                "new_day": self.day_rollover.current_day().to_string(),
+                "mark": mark,
+            })),
+            timestamp: chrono::Utc::now(),
+        });
+        self.trade_count = 0;
+        self.realized_pnl_today = Decimal::ZERO;
+        self.persist_state();
+    }
+
+    /// Apply governor changes; returns true while trading is halted
+    fn check_governor(&mut self) -> bool {
+        match self.governor.check() {
+            Some(GovernorTransition::Halted(halt)) => {
+                warn!("Trading halted ({}): {}", halt.source.as_str(), halt.reason);
+                self.status = RunnerStatus::Paused;
+                self.write_state_file().ok();
+                self.queue_event(EventInput {
+                    event_type: "trading_halted".to_string(),
+                    message: format!("Trading halted: {}", halt.reason),
+                    metadata: Some(serde_json::json!({
+                        "source": halt.source.as_str(),
+                        "reason": halt.reason,
+                    })),
+                    timestamp: chrono::Utc::now(),
+                });
+            }
+            Some(GovernorTransition::Resumed { previous }) => {
+                info!("Trading resumed ({} cleared)", previous.source.as_str());
+                if !self.owner_paused {
+                    self.status = RunnerStatus::Idle;
+                }
+                self.write_state_file().ok();
+                self.queue_event(EventInput {
+                    event_type: "trading_resumed".to_string(),
+                    message: "Trading resumed".to_string(),
+                    metadata: Some(serde_json::json!({
+                        "previous_source": previous.source.as_str(),
+                        "previous_reason": previous.reason,
+                    })),
+                    timestamp: chrono::Utc::now(),
+                });
+            }
+            None => {}
+        }
+        self.governor.is_paused()
+    }
+
+    /// Follow the owner's pause/resume from the control plane
+    fn apply_desired_status(&mut self, desired: &str) {
+        let pause = match desired {
+            "paused" => true,
+            "online" => false,
+            other => {
+                warn!("Ignoring unknown desired status '{}'", other);
+                return;
+            }
+        };
+        if pause == self.owner_paused {
+            return;
+        }
+
+        let from = self.status;
+        self.owner_paused = pause;
+        if !pause && self.degradation.resume() {
+            info!("Degradation halt cleared by owner");
         }
+        self.status = if pause || self.governor.is_paused() {
+            RunnerStatus::Paused
+        } else {
+            RunnerStatus::Idle
+        };
+        info!(
+            "Bot {} by owner (status {} -> {})",
+            if pause { "paused" } else { "resumed" },
+            from,
+            self.status
+        );
+        self.write_state_file().ok();
+        self.queue_event(EventInput {
+            event_type: "status_change".to_string(),
+            message: if pause {
+                "Trading paused by owner".to_string()
+            } else {
+                "Trading resumed by owner".to_string()
+            },
+            metadata: Some(serde_json::json!({
+                "from": from.to_string(),
+                "to": self.status.to_string(),
+                "desired_status": desired,
+            })),
+            timestamp: chrono::Utc::now(),
+        });
     }
 
     /// Run one decision tick - request decision from OpenClaw and execute
     async fn decision_tick(&mut self) -> anyhow::Result<()> {
+        self.check_day_rollover();
+        if self.check_governor() {
+            debug!("Trading halted by governor, skipping decision tick");
+            return Ok(());
+        }
+        if self.owner_paused {
+            debug!("Bot paused by owner, skipping decision tick");
+            return Ok(());
+        }
+        if self.handed_over {
+            debug!("State handed over for redeploy, skipping decision tick");
+            return Ok(());
+        }
+
         // Check if we have config and executor
         let config = match &self.current_config {
             Some(c) => c.clone(),
@@ -386,6 +856,47 @@ impl BotRunner {
             }
         };
 
+        if self.executor.is_none() {
+            warn!("No executor initialized");
+            return Ok(());
+        }
+
+        // Live trading waits until the wallet holds the minimum balances
+        if config.trading_mode == TradingMode::Live && !self.check_live_funding(&config).await {
+            return Ok(());
+        }
+
+        // Live quotes for the enabled asset universe, shared by exits and the agent
+        let recent_prices = self.get_recent_prices().await;
+        if let Some(sol) = recent_prices.get(crate::executor::SOL_MINT) {
+            self.sol_price_usd = Some(sol.price_usd);
+        }
+        self.unreliable_quotes = recent_prices
+            .iter()
+            .filter(|(_, quote)| quote.unreliable)
+            .map(|(mint, quote)| (mint.clone(), quote.clone()))
+            .collect();
+
+        // Whatever is down decides what may trade this tick
+        let gateway_up = self.openclaw_client.is_available().await;
+        let mode = self.check_degradation(&config, gateway_up, &recent_prices);
+        if !mode.allows_exits() {
+            debug!("Trading degraded to {}, skipping decision tick", mode);
+            return Ok(());
+        }
+
+        // Exits only reduce risk, so they run before the daily trade limit
+        self.run_exit_orders(&config, &recent_prices).await;
+        self.check_drawdown(&config);
+        self.performance
+            .observe_equity(chrono::Utc::now(), self.portfolio.snapshot().total_equity);
+
+        // Outside the trading window nothing new opens
+        if !self.check_trading_window(&config) {
+            debug!("Outside the trading window, skipping decision tick");
+            return Ok(());
+        }
+
         // Check daily trade limit
         let max_trades = config.risk_caps.max_trades_per_day as u32;
         if self.trade_count >= max_trades {
@@ -396,13 +907,9 @@ impl BotRunner {
             return Ok(());
         }
 
-        if self.executor.is_none() {
-            warn!("No executor initialized");
-            return Ok(());
-        }
-
-        // Check if OpenClaw gateway is available
-        if !self.openclaw_client.is_available().await {
+        // No gateway, no decisions (buys are blocked in validation when
+        // the mode is exit-only)
+        if !gateway_up {
             debug!("OpenClaw gateway not available, skipping tick");
             return Ok(());
         }
@@ -412,7 +919,8 @@ impl BotRunner {
         self.write_state_file().ok();
 
         // Build decision context
-        let context = self.build_decision_context(&config).await?;
+        let market_metadata = self.get_market_metadata().await;
+        let context = self.build_decision_context(&config, recent_prices, market_metadata)?;
 
         // Write context to file for debugging
         self.write_context_file(&context).ok();
@@ -435,6 +943,7 @@ impl BotRunner {
         );
 
         self.last_plan_id = Some(plan.plan_id);
+        self.llm_usage.record(plan.usage.as_ref());
 
         // Update status
         self.status = RunnerStatus::Executing;
@@ -464,12 +973,13 @@ impl BotRunner {
                 self.write_journal_entry(&journal_entry).ok();
 
                 // Emit blocked event
-                self.emit_intent_blocked(&intent, &validation).await;
+                self.emit_intent_blocked(intent, &validation);
+                self.record_trade_outcome(intent, "trade_blocked", "blocked");
                 continue;
             }
 
             // Execute approved intent
-            let result = self.execute_openclaw_intent(intent, &config).await;
+            let (result, twap) = self.execute_openclaw_intent(intent, &config).await;
 
             // Update journal with execution result
             let mut final_entry = journal_entry;
@@ -481,8 +991,18 @@ impl BotRunner {
             });
             self.write_journal_entry(&final_entry).ok();
 
-            // Update trade count and state
+            // Update trade count and state; a sliced intent counts once
+            let mut closed = None;
             if result.stage_reached == crate::executor::TradeStage::Confirmed {
+                // A partial TWAP fill is booked for what actually filled
+                let filled = match &twap {
+                    Some(fill) if fill.is_partial() => OpenClawIntent {
+                        amount_usd: intent.amount_usd * fill.fill_ratio(),
+                        ..intent.clone()
+                    },
+                    _ => intent.clone(),
+                };
+                closed = self.apply_fill(&filled, &result);
                 self.trade_count += 1;
                 self.last_trade_outcome = Some(LastTradeOutcome {
                     intent_id: intent.intent_id,
@@ -495,7 +1015,17 @@ impl BotRunner {
             }
 
             // Emit trade events
-            self.emit_openclaw_trade_events(intent, &result, &config).await;
+            self.emit_openclaw_trade_events(intent, &result, &config, twap.as_ref());
+            if let Some(closed) = &closed {
+                self.emit_trade_closed(intent, closed);
+            }
+            let (event_type, outcome) = match result.stage_reached {
+                crate::executor::TradeStage::Confirmed => ("trade_confirmed", "confirmed"),
+                crate::executor::TradeStage::Blocked => ("trade_blocked", "blocked"),
+                crate::executor::TradeStage::Submitted => ("trade_submitted", "submitted"),
+                crate::executor::TradeStage::Failed => ("trade_failed", "failed"),
+            };
+            self.record_trade_outcome(intent, event_type, outcome);
         }
 
         // Update status back to idle
@@ -505,91 +1035,855 @@ impl BotRunner {
         Ok(())
     }
 
-    /// Build decision context to send to OpenClaw
-    async fn build_decision_context(&self, config: &BotConfig) -> anyhow::Result<DecisionContext> {
-        let snapshot = self.portfolio.snapshot();
-
-        // Build portfolio snapshot for OpenClaw
-        let portfolio = OcPortfolioSnapshot {
-            equity_usd: snapshot.total_equity,
-            cash_usd: snapshot.cash_usdc,
-            positions_count: snapshot.positions.len(),
-            unrealized_pnl_usd: snapshot.unrealized_pnl,
-            realized_pnl_today_usd: self.realized_pnl_today,
-            trades_today: self.trade_count as i32,
+    /// Evaluate the degradation policy for this tick; returns the mode
+    ///
+    /// A change of mode, or of what's down, goes out as a
+    /// `degradation_changed` event.
+    fn check_degradation(
+        &mut self,
+        config: &BotConfig,
+        gateway_up: bool,
+        prices: &HashMap<String, PriceQuote>,
+    ) -> DegradedMode {
+        let now = chrono::Utc::now();
+        let health = Health {
+            gateway: gateway_up,
+            claw_trader: self
+                .executor
+                .as_ref()
+                .is_some_and(|e| e.is_claw_trader_available()),
+            prices: !prices.is_empty() || !config.asset_universe.iter().any(|a| a.enabled),
+            last_sync_at: self.last_sync_at,
+        };
+        let degradation = config
+            .degradation
+            .evaluate(&health, config.trading_mode, now);
+        let Some(previous) = self.degradation.observe(degradation) else {
+            return self.degradation.mode();
         };
 
-        // Build holdings list from position snapshots
-        let holdings: Vec<Holding> = snapshot
-            .positions
+        let mode = self.degradation.mode();
+        let failed = self.degraded_subsystems();
+        if mode == DegradedMode::Normal {
+            info!("Degradation cleared ({} -> normal)", previous);
+        } else {
+            warn!(
+                "Trading degraded to {} (was {}): {} down",
+                mode,
+                previous,
+                failed.join(", ")
+            );
+        }
+        self.write_state_file().ok();
+        self.queue_event(EventInput {
+            event_type: "degradation_changed".to_string(),
+            message: if failed.is_empty() {
+                format!("Trading mode {}", mode)
+            } else {
+                format!("Trading mode {}: {} down", mode, failed.join(", "))
+            },
+            metadata: Some(serde_json::json!({
+                "mode": mode,
+                "previous_mode": previous,
+                "failed": failed,
+                "trading_mode": config.trading_mode,
+            })),
+            timestamp: now,
+        });
+        mode
+    }
+
+    fn degraded_subsystems(&self) -> Vec<String> {
+        self.degradation
+            .failed()
             .iter()
-            .map(|pos| Holding {
-                mint: pos.mint.clone(),
-                symbol: pos.symbol.clone(),
-                quantity: pos.quantity,
-                value_usd: pos.market_value,
-                avg_entry_price: Some(pos.avg_entry),
-            })
-            .collect();
+            .map(|s| s.as_str().to_string())
+            .collect()
+    }
 
-        // Build recent prices from executor cache (if available)
-        let recent_prices = self.get_recent_prices().await;
+    /// Mode trades actually run in; the paper fallback simulates live ones
+    fn execution_mode(&self, config: &BotConfig) -> TradingMode {
+        if self.degradation.mode() == DegradedMode::PaperFallback {
+            TradingMode::Paper
+        } else {
+            config.trading_mode
+        }
+    }
 
-        // Build risk rails
-        let risk_rails = RiskRails {
-            max_position_size_percent: config.risk_caps.max_position_size_percent,
-            max_daily_loss_usd: config.risk_caps.max_daily_loss_usd,
-            max_drawdown_percent: config.risk_caps.max_drawdown_percent,
-            max_trades_per_day: config.risk_caps.max_trades_per_day,
-            governor_paused: false, // TODO: Check governor state
-        };
+    /// Sell positions that crossed their stop-loss or take-profit level
+    ///
+    /// Exits go straight to the executor instead of through OpenClaw and are
+    /// journaled like any other intent, with a nil plan id.
+    async fn run_exit_orders(&mut self, config: &BotConfig, quotes: &HashMap<String, PriceQuote>) {
+        let prices: HashMap<String, Decimal> = quotes
+            .iter()
+            .map(|(mint, quote)| (mint.clone(), quote.price_usd))
+            .collect();
+        self.portfolio.mark_to_market(&prices);
+        self.orders.sync(&self.portfolio, config.exits);
 
-        // Get recent events (last 10)
-        let recent_events = self.get_recent_events();
+        let orders = self.orders.evaluate(&self.portfolio, &prices);
+        self.execute_exit_orders(orders, config).await;
+    }
 
-        Ok(DecisionContext {
-            bot_id: self.config.bot_id,
-            timestamp: chrono::Utc::now(),
-            portfolio,
-            holdings,
-            recent_prices,
-            risk_rails,
-            recent_events,
-            config_version: config.version_id.to_string(),
-        })
+    /// Track equity against its high-water mark and report cap crossings
+    ///
+    /// Expects the portfolio to have just been marked to market.
+    fn check_drawdown(&mut self, config: &BotConfig) {
+        let equity = self.portfolio.snapshot().total_equity;
+        let cap = config.risk_caps.max_drawdown_percent;
+        let Some(transition) = self.drawdown.observe(equity, cap) else {
+            return;
+        };
+        let event = match transition {
+            DrawdownTransition::Breached {
+                drawdown_pct,
+                high_water,
+                equity,
+            } => {
+                warn!(
+                    "Drawdown {}% exceeds the {}% cap, blocking buys",
+                    drawdown_pct.round_dp(2),
+                    cap
+                );
+                EventInput {
+                    event_type: "drawdown_breached".to_string(),
+                    message: format!(
+                        "Drawdown {}% exceeds the {}% cap; new buys are blocked",
+                        drawdown_pct.round_dp(2),
+                        cap
+                    ),
+                    metadata: Some(serde_json::json!({
+                        "drawdown_pct": drawdown_pct.round_dp(4),
+                        "max_drawdown_percent": cap,
+                        "high_water": high_water,
+                        "equity": equity,
+                    })),
+                    timestamp: chrono::Utc::now(),
+                }
+            }
+            DrawdownTransition::Recovered { drawdown_pct } => {
+                info!("Drawdown back within the {}% cap", cap);
+                EventInput {
+                    event_type: "drawdown_recovered".to_string(),
+                    message: format!(
+                        "Drawdown back to {}%, within the {}% cap; buys allowed again",
+                        drawdown_pct.round_dp(2),
+                        cap
+                    ),
+                    metadata: Some(serde_json::json!({
+                        "drawdown_pct": drawdown_pct.round_dp(4),
+                        "max_drawdown_percent": cap,
+                    })),
+                    timestamp: chrono::Utc::now(),
+                }
+            }
+        };
+        self.queue_event(event);
+        self.persist_state();
     }
 
-    /// Validate intent against hard risk rails
-    fn validate_intent(&self, intent: &OpenClawIntent, config: &BotConfig) -> IntentValidation {
-        // Check trade limit
-        let max_trades = config.risk_caps.max_trades_per_day as u32;
-        if self.trade_count >= max_trades {
-            return IntentValidation {
-                intent: intent.clone(),
-                approved: false,
-                rejection_reason: Some(format!(
-                    "Daily trade limit reached ({}/{})",
-                    self.trade_count, max_trades
-                )),
-                blocked_by: Some("max_trades_per_day".to_string()),
-            };
+    /// Sell each order's position for USDC, journaling and reporting each
+    async fn execute_exit_orders(&mut self, orders: Vec<ExitOrder>, config: &BotConfig) {
+        if orders.is_empty() {
+            return;
         }
+        for order in orders {
+            let decimals = match &self.executor {
+                Some(executor) => executor.mint_decimals(&order.mint).await,
+                None => Err(anyhow::anyhow!("no executor")),
+            };
+            let decimals = match decimals {
+                Ok(decimals) => decimals,
+                Err(e) => {
+                    warn!(
+                        "Skipping exit for {}: decimals unknown ({})",
+                        order.symbol, e
+                    );
+                    continue;
+                }
+            };
+            let quantity = crate::amount::from_raw_amount(order.quantity_raw, decimals);
+            let intent = OpenClawIntent {
+                intent_id: uuid::Uuid::new_v4(),
+                action: TradeAction::Sell,
+                input_mint: order.mint.clone(),
+                output_mint: USDC_MINT.to_string(),
+                amount_usd: (quantity * order.price).round_dp(2),
+                rationale: format!(
+                    "{} {} at {} (level {}, entry {})",
+                    order.reason.as_str(),
+                    order.symbol,
+                    order.price,
+                    order.level,
+                    order.entry_price
+                ),
+                confidence: 1.0,
+            };
+            warn!("Exit triggered: {}", intent.rationale);
+            self.emit_exit_triggered(&order, &intent);
 
-        // Check position size limit
-        let snapshot = self.portfolio.snapshot();
-        let max_position_value = snapshot.total_equity
-            * Decimal::from(config.risk_caps.max_position_size_percent)
-            / Decimal::from(100);
+            self.status = RunnerStatus::Executing;
+            self.write_state_file().ok();
 
-        if intent.amount_usd > max_position_value {
-            return IntentValidation {
+            let result = self.execute_exit_order(&order, &intent, config).await;
+            self.write_journal_entry(&DecisionJournalEntry {
+                intent_id: intent.intent_id,
+                plan_id: uuid::Uuid::nil(),
+                plan_hash: order.reason.as_str().to_string(),
                 intent: intent.clone(),
-                approved: false,
-                rejection_reason: Some(format!(
-                    "Amount ${} exceeds max position size ${}",
+                validation: IntentValidation {
+                    intent: intent.clone(),
+                    approved: true,
+                    rejection_reason: None,
+                    blocked_by: None,
+                    details: None,
+                },
+                execution: Some(ExecutionOutcome {
+                    stage: format!("{:?}", result.stage_reached),
+                    signature: result.signature.clone(),
+                    out_amount: Some(result.execution.out_amount_raw),
+                    error: result.error.as_ref().map(|e| e.message.clone()),
+                }),
+                timestamp: chrono::Utc::now(),
+            })
+            .ok();
+
+            let mut closed = None;
+            if result.stage_reached == TradeStage::Confirmed {
+                closed = self.apply_fill(&intent, &result);
+                self.trade_count += 1;
+                self.last_trade_outcome = Some(LastTradeOutcome {
+                    intent_id: intent.intent_id,
+                    stage: format!("{:?}", result.stage_reached),
+                    symbol: order.symbol.clone(),
+                    side: format!("{:?}", intent.action),
+                    amount_usd: intent.amount_usd,
+                    timestamp: chrono::Utc::now(),
+                });
+            }
+
+            self.emit_openclaw_trade_events(&intent, &result, config, None);
+            if let Some(closed) = &closed {
+                self.emit_trade_closed(&intent, closed);
+            }
+            let (event_type, outcome) = match result.stage_reached {
+                TradeStage::Confirmed => ("trade_confirmed", "confirmed"),
+                TradeStage::Blocked => ("trade_blocked", "blocked"),
+                TradeStage::Submitted => ("trade_submitted", "submitted"),
+                TradeStage::Failed => ("trade_failed", "failed"),
+            };
+            self.record_trade_outcome(&intent, event_type, outcome);
+        }
+
+        // Drop levels for whatever just closed
+        self.orders.sync(&self.portfolio, config.exits);
+        self.status = RunnerStatus::Idle;
+        self.persist_state();
+    }
+
+    /// Sell a whole position for USDC
+    async fn execute_exit_order(
+        &self,
+        order: &ExitOrder,
+        intent: &OpenClawIntent,
+        config: &BotConfig,
+    ) -> NormalizedTradeResult {
+        let executor = self.executor.as_ref().unwrap();
+        executor
+            .execute_trade(
+                &intent.intent_id.to_string(),
+                &order.mint,
+                USDC_MINT,
+                order.quantity_raw,
+                TradeSide::Sell,
+                self.execution_mode(config),
+            )
+            .await
+    }
+
+    /// Queue the event announcing a stop-loss or take-profit exit
+    fn emit_exit_triggered(&mut self, order: &ExitOrder, intent: &OpenClawIntent) {
+        self.queue_event(EventInput {
+            event_type: order.reason.event_type().to_string(),
+            message: format!(
+                "{} {} at {} ({} level {})",
+                match order.reason {
+                    crate::orders::ExitReason::StopLoss => "Stop loss hit for",
+                    crate::orders::ExitReason::TakeProfit => "Take profit hit for",
+                    crate::orders::ExitReason::TrailingStop => "Trailing stop hit for",
+                    crate::orders::ExitReason::Flatten => "Flattening",
+                },
+                order.symbol,
+                order.price,
+                order.reason.as_str(),
+                order.level
+            ),
+            metadata: Some(serde_json::json!({
+                "intent_id": intent.intent_id.to_string(),
+                "mint": order.mint,
+                "symbol": order.symbol,
+                "reason": order.reason.as_str(),
+                "level": order.level.to_string(),
+                "price": order.price.to_string(),
+                "entry_price": order.entry_price.to_string(),
+                "quantity_raw": order.quantity_raw,
+                "amount_usd": intent.amount_usd.to_string(),
+            })),
+            timestamp: chrono::Utc::now(),
+        });
+    }
+
+    /// Book a confirmed swap, raising `daily_loss_breached` when the fill
+    /// takes today's realized PnL past `max_daily_loss_usd`
+    fn apply_fill(
+        &mut self,
+        intent: &OpenClawIntent,
+        result: &NormalizedTradeResult,
+    ) -> Option<ClosedTrade> {
+        let before = self.realized_pnl_today;
+        let closed = self.book_fill(intent, result);
+        if let Some(config) = &self.current_config {
+            let max_daily_loss = Decimal::from(config.risk_caps.max_daily_loss_usd);
+            if before >= -max_daily_loss && self.realized_pnl_today < -max_daily_loss {
+                warn!(
+                    "Daily loss ${} exceeds the ${} cap, blocking trades",
+                    -self.realized_pnl_today, max_daily_loss
+                );
+                self.queue_event(EventInput {
+                    event_type: "daily_loss_breached".to_string(),
+                    message: format!(
+                        "Daily loss ${} exceeds the ${} cap; trading is blocked until the next day",
+                        (-self.realized_pnl_today).round_dp(2),
+                        max_daily_loss
+                    ),
+                    metadata: Some(serde_json::json!({
+                        "realized_pnl_today_usd": self.realized_pnl_today.round_dp(6).to_string(),
+                        "max_daily_loss_usd": config.risk_caps.max_daily_loss_usd,
+                    })),
+                    timestamp: chrono::Utc::now(),
+                });
+            }
+        }
+        closed
+    }
+
+    /// Book a confirmed swap into the portfolio
+    ///
+    /// Buys add to the output token's position at `amount_usd / quantity`;
+    /// sells shrink the input token's position and realize PnL against its
+    /// cost basis, net of the quoted swap fee, which is returned for the
+    /// `trade_closed` event. The reconciler still corrects any drift
+    /// against the chain.
+    fn book_fill(
+        &mut self,
+        intent: &OpenClawIntent,
+        result: &NormalizedTradeResult,
+    ) -> Option<ClosedTrade> {
+        if intent.action != TradeAction::Hold {
+            let now = chrono::Utc::now();
+            let cooldown = self
+                .current_config
+                .as_ref()
+                .and_then(|c| c.asset_limits.cooldown_minutes)
+                .unwrap_or(0);
+            self.cooldowns.prune(cooldown, now);
+            self.cooldowns.record(traded_mint(intent), now);
+        }
+        match intent.action {
+            TradeAction::Buy => {
+                // The executor resolved both mints before trading
+                let Some(decimals) = crate::executor::get_token_decimals(&intent.output_mint)
+                else {
+                    error!(
+                        "Fill on {} with unknown decimals not booked",
+                        intent.output_mint
+                    );
+                    return None;
+                };
+                let bought = result.execution.out_amount_raw;
+                let quantity = crate::amount::from_raw_amount(bought, decimals);
+                if quantity <= Decimal::ZERO {
+                    return None;
+                }
+                let symbol = self
+                    .get_symbol_for_mint(&intent.output_mint)
+                    .unwrap_or_else(|| intent.output_mint.clone());
+                let (swap_fee, network_fee) = self.fill_fees(intent, result);
+                let fees = swap_fee + network_fee;
+
+                // A buy covers a short before anything is held long
+                let owed = self
+                    .portfolio
+                    .get_position(&intent.output_mint)
+                    .filter(|p| p.is_short())
+                    .map(|p| p.quantity_raw)
+                    .unwrap_or(0);
+                let covered = self.portfolio.cover_short(
+                    &intent.output_mint,
+                    bought,
+                    intent.amount_usd,
+                    fees,
+                    decimals,
+                    self.config.cost_basis,
+                );
+                let long_raw = bought - owed.min(bought);
+                if let Some(covered) = &covered {
+                    self.realized_pnl_today += covered.realized_pnl_usd;
+                }
+                if long_raw > 0 {
+                    let held = self
+                        .portfolio
+                        .get_position(&intent.output_mint)
+                        .map(|p| p.quantity_raw)
+                        .unwrap_or(0);
+                    self.portfolio.update_position(
+                        &intent.output_mint,
+                        &symbol,
+                        held.saturating_add(long_raw),
+                        intent.amount_usd / quantity,
+                        decimals,
+                    );
+                    let long_fees = fees * Decimal::from(long_raw) / Decimal::from(bought);
+                    self.portfolio.charge_fees(&intent.output_mint, long_fees);
+                    self.realized_pnl_today -= long_fees;
+                }
+                let cash = self
+                    .portfolio
+                    .cash_usdc_raw
+                    .saturating_sub(result.quote.in_amount);
+                self.portfolio.update_cash(cash, "buy filled");
+                covered
+            }
+            TradeAction::Sell => {
+                let Some(decimals) = crate::executor::get_token_decimals(&intent.input_mint) else {
+                    error!(
+                        "Fill on {} with unknown decimals not booked",
+                        intent.input_mint
+                    );
+                    return None;
+                };
+                // Sells settle in USDC
+                let proceeds = crate::amount::from_raw_amount(result.execution.out_amount_raw, 6);
+                let (swap_fee, network_fee) = self.fill_fees(intent, result);
+                let fees = swap_fee + network_fee;
+                let sold = result.quote.in_amount;
+                let held = self
+                    .portfolio
+                    .get_position(&intent.input_mint)
+                    .filter(|p| !p.is_short())
+                    .map(|p| p.quantity_raw)
+                    .unwrap_or(0);
+                let closed = self.portfolio.realize_sell(
+                    &intent.input_mint,
+                    sold,
+                    proceeds,
+                    fees,
+                    decimals,
+                    self.config.cost_basis,
+                );
+                if let Some(closed) = &closed {
+                    self.realized_pnl_today += closed.realized_pnl_usd;
+                }
+                // What wasn't held goes short when shorts are allowed; its
+                // share of the fees is charged like a buy's
+                let unheld = sold - held.min(sold);
+                if unheld > 0 {
+                    let share = Decimal::from(unheld) / Decimal::from(sold);
+                    let symbol = self
+                        .get_symbol_for_mint(&intent.input_mint)
+                        .unwrap_or_else(|| intent.input_mint.clone());
+                    self.portfolio.sell_short(
+                        &intent.input_mint,
+                        &symbol,
+                        unheld,
+                        proceeds * share,
+                        decimals,
+                    );
+                    self.portfolio.charge_fees(&intent.input_mint, fees * share);
+                    self.realized_pnl_today -= fees * share;
+                }
+                let cash = self
+                    .portfolio
+                    .cash_usdc_raw
+                    .saturating_add(result.execution.out_amount_raw);
+                self.portfolio.update_cash(cash, "sell filled");
+                closed
+            }
+            TradeAction::Hold => None,
+        }
+    }
+
+    /// Swap fee (`fee_bps` of the USDC side) and network fee of a confirmed
+    /// fill, in USD
+    ///
+    /// The network fee is priced at the last SOL quote; before one has been
+    /// seen it counts as zero.
+    fn fill_fees(
+        &self,
+        intent: &OpenClawIntent,
+        result: &NormalizedTradeResult,
+    ) -> (Decimal, Decimal) {
+        let cash_raw = match intent.action {
+            TradeAction::Sell => result.execution.out_amount_raw,
+            _ => result.quote.in_amount,
+        };
+        let swap_fee = crate::amount::from_raw_amount(cash_raw, 6)
+            * Decimal::from(result.quote.fee_bps)
+            / Decimal::from(10_000);
+        let network_fee = self
+            .sol_price_usd
+            .map(|price| {
+                crate::amount::from_raw_amount(result.execution.network_fee_lamports, 9) * price
+            })
+            .unwrap_or_default();
+        (swap_fee, network_fee)
+    }
+
+    /// Queue the `trade_closed` event for a sell (or short cover) booked
+    /// against its cost basis and count it toward the rolling performance stats
+    fn emit_trade_closed(&mut self, intent: &OpenClawIntent, closed: &ClosedTrade) {
+        // A guessed basis would skew the win rate
+        if !closed.unknown_cost_basis {
+            self.performance
+                .record_close(chrono::Utc::now(), closed.realized_pnl_usd);
+        }
+        let pnl = closed.realized_pnl_usd.round_dp(2);
+        self.queue_event(EventInput {
+            event_type: "trade_closed".to_string(),
+            message: format!(
+                "{} {} {} at {}: {}{} realized",
+                match closed.side {
+                    PositionSide::Long => "Sold",
+                    PositionSide::Short => "Covered",
+                },
+                closed.quantity,
+                closed.symbol,
+                closed.exit_price.round_dp(6),
+                if pnl.is_sign_negative() { "-$" } else { "+$" },
+                pnl.abs()
+            ),
+            metadata: Some(serde_json::json!({
+                "intent_id": intent.intent_id.to_string(),
+                "mint": closed.mint,
+                "symbol": closed.symbol,
+                "quantity": closed.quantity.to_string(),
+                "entry_price": closed.entry_price.round_dp(8).to_string(),
+                "exit_price": closed.exit_price.round_dp(8).to_string(),
+                "cost_basis_usd": closed.cost_basis_usd.round_dp(6).to_string(),
+                "proceeds_usd": closed.proceeds_usd.round_dp(6).to_string(),
+                "fees_usd": closed.fees_usd.round_dp(6).to_string(),
+                "realized_pnl_usd": closed.realized_pnl_usd.round_dp(6).to_string(),
+                "realized_pnl_today_usd": self.realized_pnl_today.round_dp(6).to_string(),
+                "cost_basis_method": closed.method.as_str(),
+                "fully_closed": closed.fully_closed,
+                "unknown_cost_basis": closed.unknown_cost_basis,
+                "side": closed.side,
+            })),
+            timestamp: chrono::Utc::now(),
+        });
+    }
+
+    /// Funding pre-flight for live mode; true once the wallet is funded
+    ///
+    /// Re-checked every tick until it passes. `insufficient_funding` is only
+    /// emitted when the balances differ from the previous check.
+    async fn check_live_funding(&mut self, config: &BotConfig) -> bool {
+        if self.live_funded {
+            return true;
+        }
+        let Some(reconciler) = &self.reconciler else {
+            return false;
+        };
+
+        let holdings = match reconciler.fetch_on_chain_holdings().await {
+            Ok(holdings) => holdings,
+            Err(e) => {
+                warn!("Funding check failed: {}", e);
+                return false;
+            }
+        };
+        let check = FundingCheck::from_holdings(&holdings, config.funding);
+        let metadata = check.metadata(&self.config.wallet_address);
+
+        if check.is_funded() {
+            info!(
+                "✓ Wallet funded ({} USDC, {} SOL), live trading enabled",
+                check.usdc, check.sol
+            );
+            self.live_funded = true;
+            self.status = RunnerStatus::Idle;
+            self.queue_event(EventInput {
+                event_type: "funding_confirmed".to_string(),
+                message: format!(
+                    "Wallet funded with {} USDC and {} SOL",
+                    check.usdc, check.sol
+                ),
+                metadata: Some(metadata),
+                timestamp: chrono::Utc::now(),
+            });
+        } else {
+            self.status = RunnerStatus::AwaitingFunding;
+            if self.last_funding_check.as_ref() != Some(&check) {
+                warn!(
+                    "Live trading blocked, wallet {} needs {}",
+                    self.config.wallet_address,
+                    check.shortfall()
+                );
+                self.queue_event(EventInput {
+                    event_type: "insufficient_funding".to_string(),
+                    message: format!(
+                        "Live trading blocked until {} is funded: send {}",
+                        self.config.wallet_address,
+                        check.shortfall()
+                    ),
+                    metadata: Some(metadata),
+                    timestamp: chrono::Utc::now(),
+                });
+            }
+        }
+        self.write_state_file().ok();
+
+        self.last_funding_check = Some(check);
+        self.live_funded
+    }
+
+    /// Build decision context to send to OpenClaw
+    fn build_decision_context(
+        &self,
+        config: &BotConfig,
+        recent_prices: HashMap<String, PriceQuote>,
+        market_metadata: Option<MarketMetadata>,
+    ) -> anyhow::Result<DecisionContext> {
+        let snapshot = self.portfolio.snapshot();
+
+        // Build portfolio snapshot for OpenClaw
+        let portfolio = OcPortfolioSnapshot {
+            equity_usd: snapshot.total_equity,
+            cash_usd: snapshot.cash_usdc,
+            positions_count: snapshot.positions.len(),
+            unrealized_pnl_usd: snapshot.unrealized_pnl,
+            realized_pnl_today_usd: self.realized_pnl_today,
+            trades_today: self.trade_count as i32,
+        };
+
+        // Build holdings list from position snapshots
+        let holdings: Vec<Holding> = snapshot
+            .positions
+            .iter()
+            .map(|pos| Holding {
+                mint: pos.mint.clone(),
+                symbol: pos.symbol.clone(),
+                quantity: pos.quantity,
+                value_usd: pos.market_value,
+                avg_entry_price: Some(pos.avg_entry),
+            })
+            .collect();
+
+        // Build risk rails
+        let risk_rails = RiskRails {
+            max_position_size_percent: config.risk_caps.max_position_size_percent,
+            max_daily_loss_usd: config.risk_caps.max_daily_loss_usd,
+            max_drawdown_percent: config.risk_caps.max_drawdown_percent,
+            max_trades_per_day: config.risk_caps.max_trades_per_day,
+            governor_paused: self.governor.is_paused(),
+        };
+
+        let recent_events = self.recent_events.latest(CONTEXT_RECENT_EVENTS);
+
+        Ok(DecisionContext {
+            bot_id: self.config.bot_id,
+            timestamp: chrono::Utc::now(),
+            portfolio,
+            holdings,
+            recent_prices,
+            risk_rails,
+            recent_events,
+            config_version: config.version_id.to_string(),
+            market_metadata,
+        })
+    }
+
+    /// Whether the trading window is open, with an event when that changes
+    fn check_trading_window(&mut self, config: &BotConfig) -> bool {
+        let Some(window) = &config.trading_window else {
+            self.window_closed = false;
+            return true;
+        };
+        let now = chrono::Utc::now();
+        let open = window.is_open(now);
+        if open != self.window_closed {
+            // No change since the last tick
+            return open;
+        }
+        self.window_closed = !open;
+
+        let event = if open {
+            info!("Trading window {} opened", window.describe());
+            EventInput {
+                event_type: "trading_window_opened".to_string(),
+                message: format!("Trading window {} is open", window.describe()),
+                metadata: Some(serde_json::json!({ "window": window })),
+                timestamp: now,
+            }
+        } else {
+            let opens_at = window.next_open(now);
+            info!(
+                "Trading window {} closed; next opens {:?}",
+                window.describe(),
+                opens_at
+            );
+            EventInput {
+                event_type: "trading_window_closed".to_string(),
+                message: format!(
+                    "Outside trading window {}; no new trades until it opens",
+                    window.describe()
+                ),
+                metadata: Some(serde_json::json!({
+                    "reason_code": "window_closed",
+                    "window": window,
+                    "opens_at": opens_at,
+                })),
+                timestamp: now,
+            }
+        };
+        self.queue_event(event);
+        open
+    }
+
+    /// Validate intent against hard risk rails
+    fn validate_intent(&self, intent: &OpenClawIntent, config: &BotConfig) -> IntentValidation {
+        // Nothing opens outside the trading window (exit orders aren't intents)
+        let now = chrono::Utc::now();
+        if let Some(window) = config.trading_window.as_ref().filter(|w| !w.is_open(now)) {
+            return IntentValidation {
+                intent: intent.clone(),
+                approved: false,
+                rejection_reason: Some(format!("Outside trading window {}", window.describe())),
+                blocked_by: Some("window_closed".to_string()),
+                details: Some(serde_json::json!({
+                    "opens_at": window.next_open(now),
+                })),
+            };
+        }
+
+        // Degraded to exit-only (or worse), only sells go through
+        let mode = self.degradation.mode();
+        if intent.action == TradeAction::Buy && !mode.allows_entries() {
+            let failed = self.degraded_subsystems();
+            return IntentValidation {
+                intent: intent.clone(),
+                approved: false,
+                rejection_reason: Some(format!("Trading degraded to {}", mode)),
+                blocked_by: Some("degraded".to_string()),
+                details: Some(serde_json::json!({
+                    "mode": mode,
+                    "failed": failed,
+                })),
+            };
+        }
+
+        // Sources that disagree don't give a price worth trading at, either way
+        if let Some(quote) = self.unreliable_quotes.get(traded_mint(intent)) {
+            return IntentValidation {
+                intent: intent.clone(),
+                approved: false,
+                rejection_reason: Some(format!(
+                    "Price sources for {} disagree; not trading until they agree",
+                    quote.symbol
+                )),
+                blocked_by: Some("price_quality".to_string()),
+                details: Some(serde_json::json!({
+                    "symbol": quote.symbol,
+                    "price_usd": quote.price_usd,
+                    "price_source": quote.source,
+                })),
+            };
+        }
+
+        // Buys stay inside the universe the control plane resolved; anything
+        // held from outside it can still be sold. An empty universe comes
+        // from a control plane that doesn't resolve one.
+        if intent.action == TradeAction::Buy
+            && !config.asset_universe.is_empty()
+            && !config
+                .asset_universe
+                .iter()
+                .any(|a| a.enabled && a.mint == intent.output_mint)
+        {
+            return IntentValidation {
+                intent: intent.clone(),
+                approved: false,
+                rejection_reason: Some(format!(
+                    "{} is not in the bot's asset universe",
+                    intent.output_mint
+                )),
+                blocked_by: Some("asset_not_in_universe".to_string()),
+                details: None,
+            };
+        }
+
+        // Check trade limit
+        let max_trades = config.risk_caps.max_trades_per_day as u32;
+        if self.trade_count >= max_trades {
+            return IntentValidation {
+                intent: intent.clone(),
+                approved: false,
+                rejection_reason: Some(format!(
+                    "Daily trade limit reached ({}/{})",
+                    self.trade_count, max_trades
+                )),
+                blocked_by: Some("max_trades_per_day".to_string()),
+                details: None,
+            };
+        }
+
+        // Past the drawdown cap only sells go through
+        let snapshot = self.portfolio.snapshot();
+        if intent.action == TradeAction::Buy && self.drawdown.breached {
+            return IntentValidation {
+                intent: intent.clone(),
+                approved: false,
+                rejection_reason: Some(format!(
+                    "Drawdown {}% exceeds max drawdown {}%",
+                    self.drawdown
+                        .drawdown_pct(snapshot.total_equity)
+                        .round_dp(2),
+                    config.risk_caps.max_drawdown_percent
+                )),
+                blocked_by: Some("max_drawdown_percent".to_string()),
+                details: None,
+            };
+        }
+
+        if let Some(blocked) = self.check_cooldown(intent, config) {
+            return blocked;
+        }
+
+        if intent.action == TradeAction::Buy {
+            if let Some(blocked) = self.check_asset_limits(intent, config, &snapshot) {
+                return blocked;
+            }
+        }
+        if let Some(blocked) = self.check_exposure_limits(intent, config, &snapshot) {
+            return blocked;
+        }
+
+        // Check position size limit
+        let max_position_value = snapshot.total_equity
+            * Decimal::from(config.risk_caps.max_position_size_percent)
+            / Decimal::from(100);
+
+        if intent.amount_usd > max_position_value {
+            return IntentValidation {
+                intent: intent.clone(),
+                approved: false,
+                rejection_reason: Some(format!(
+                    "Amount ${} exceeds max position size ${}",
                     intent.amount_usd, max_position_value
                 )),
                 blocked_by: Some("max_position_size_percent".to_string()),
+                details: None,
             };
         }
 
@@ -604,6 +1898,20 @@ impl BotRunner {
                     -self.realized_pnl_today, max_daily_loss
                 )),
                 blocked_by: Some("max_daily_loss_usd".to_string()),
+                details: None,
+            };
+        }
+
+        // The amount has to convert to a raw amount of the input mint
+        if let Err(e) = self.intent_in_amount(intent) {
+            return IntentValidation {
+                intent: intent.clone(),
+                approved: false,
+                rejection_reason: Some(format!("Can't size ${} trade: {}", intent.amount_usd, e)),
+                blocked_by: Some("sizing_error".to_string()),
+                details: Some(serde_json::json!({
+                    "sizing_error": e.to_string(),
+                })),
             };
         }
 
@@ -613,15 +1921,324 @@ impl BotRunner {
             approved: true,
             rejection_reason: None,
             blocked_by: None,
+            details: None,
+        }
+    }
+
+    /// One fill per mint per `asset_limits.cooldown_minutes`
+    fn check_cooldown(
+        &self,
+        intent: &OpenClawIntent,
+        config: &BotConfig,
+    ) -> Option<IntentValidation> {
+        let minutes = config.asset_limits.cooldown_minutes.filter(|m| *m > 0)?;
+        if intent.action == TradeAction::Hold {
+            return None;
+        }
+        let mint = traded_mint(intent);
+        let remaining = self
+            .cooldowns
+            .remaining(mint, minutes, chrono::Utc::now())?;
+        let symbol = self
+            .get_symbol_for_mint(mint)
+            .unwrap_or_else(|| mint.to_string());
+        Some(IntentValidation {
+            intent: intent.clone(),
+            approved: false,
+            rejection_reason: Some(format!(
+                "{} is cooling down: {} remaining (one trade per {}m)",
+                symbol,
+                format_remaining(remaining),
+                minutes
+            )),
+            blocked_by: Some("cooldown_minutes".to_string()),
+            details: Some(serde_json::json!({
+                "cooldown_minutes": minutes,
+                "cooldown_remaining_secs": remaining.num_seconds(),
+            })),
+        })
+    }
+
+    /// Max open positions and the bought asset's exposure caps
+    fn check_asset_limits(
+        &self,
+        intent: &OpenClawIntent,
+        config: &BotConfig,
+        snapshot: &PortfolioSnapshot,
+    ) -> Option<IntentValidation> {
+        let limits = &config.asset_limits;
+        let blocked = |reason: String, code: &str| IntentValidation {
+            intent: intent.clone(),
+            approved: false,
+            rejection_reason: Some(reason),
+            blocked_by: Some(code.to_string()),
+            details: None,
+        };
+        let holds = |mint: &str| {
+            self.portfolio
+                .get_position(mint)
+                .is_some_and(|p| p.quantity_raw > 0)
+        };
+
+        if let Some(max) = limits.max_open_positions {
+            let open = self
+                .portfolio
+                .positions
+                .values()
+                .filter(|p| p.quantity_raw > 0)
+                .count();
+            if !holds(&intent.output_mint) && open >= max as usize {
+                return Some(blocked(
+                    format!("Max open positions reached ({}/{})", open, max),
+                    "max_open_positions",
+                ));
+            }
         }
+
+        let symbol = self.get_symbol_for_mint(&intent.output_mint);
+        let cap = limits.cap_for(&intent.output_mint, symbol.as_deref())?;
+        let held: Decimal = snapshot
+            .positions
+            .iter()
+            .filter(|p| p.mint == intent.output_mint)
+            .map(|p| p.market_value)
+            .sum();
+        let exposure = held + intent.amount_usd;
+        if let Some(max_usd) = cap.max_exposure_usd {
+            if exposure > max_usd {
+                return Some(blocked(
+                    format!(
+                        "{} exposure would be ${} (max ${})",
+                        cap.asset,
+                        exposure.round_dp(2),
+                        max_usd
+                    ),
+                    "max_asset_exposure_usd",
+                ));
+            }
+        }
+        if let Some(max_percent) = cap.max_exposure_percent {
+            let max_value = snapshot.total_equity * max_percent / Decimal::from(100);
+            if exposure > max_value {
+                return Some(blocked(
+                    format!(
+                        "{} exposure would be ${} (max {}% of equity, ${})",
+                        cap.asset,
+                        exposure.round_dp(2),
+                        max_percent,
+                        max_value.round_dp(2)
+                    ),
+                    "max_asset_exposure_percent",
+                ));
+            }
+        }
+        None
+    }
+
+    /// Gross and net exposure caps, on the portfolio as it would be after
+    /// the trade
+    ///
+    /// Only trades that raise a measure past its cap are blocked, so one
+    /// that brings exposure back down always goes through.
+    fn check_exposure_limits(
+        &self,
+        intent: &OpenClawIntent,
+        config: &BotConfig,
+        snapshot: &PortfolioSnapshot,
+    ) -> Option<IntentValidation> {
+        let limits = &config.asset_limits;
+        if limits.max_gross_exposure_percent.is_none() && limits.max_net_exposure_percent.is_none()
+        {
+            return None;
+        }
+        let mint = traded_mint(intent);
+        let value: Decimal = snapshot
+            .positions
+            .iter()
+            .filter(|p| p.mint == mint)
+            .map(|p| p.market_value)
+            .sum();
+        let after = match intent.action {
+            TradeAction::Buy => value + intent.amount_usd,
+            TradeAction::Sell if self.portfolio.allow_shorts => value - intent.amount_usd,
+            TradeAction::Sell => (value - intent.amount_usd).max(Decimal::ZERO),
+            TradeAction::Hold => return None,
+        };
+        let gross = snapshot.gross_exposure - value.abs() + after.abs();
+        let net = snapshot.net_exposure - value + after;
+        let equity = snapshot.total_equity;
+        let checks = [
+            (
+                limits.max_gross_exposure_percent,
+                snapshot.gross_exposure,
+                gross,
+                "gross",
+                "max_gross_exposure_percent",
+            ),
+            (
+                limits.max_net_exposure_percent,
+                snapshot.net_exposure.abs(),
+                net.abs(),
+                "net",
+                "max_net_exposure_percent",
+            ),
+        ];
+        for (max_percent, before, after, label, code) in checks {
+            let Some(max_percent) = max_percent else {
+                continue;
+            };
+            let max_value = equity * max_percent / Decimal::from(100);
+            if after > max_value && after > before {
+                return Some(IntentValidation {
+                    intent: intent.clone(),
+                    approved: false,
+                    rejection_reason: Some(format!(
+                        "{} exposure would be ${} (max {}% of equity, ${})",
+                        label,
+                        after.round_dp(2),
+                        max_percent,
+                        max_value.round_dp(2)
+                    )),
+                    blocked_by: Some(code.to_string()),
+                    details: Some(serde_json::json!({
+                        "gross_exposure_usd": gross.round_dp(2).to_string(),
+                        "net_exposure_usd": net.round_dp(2).to_string(),
+                    })),
+                });
+            }
+        }
+        None
+    }
+
+    /// Raw amount of the input mint an intent spends
+    ///
+    /// A buy spends `amount_usd` of USDC. A sell spends that much of the held
+    /// token at its last price (its entry price if it hasn't been marked yet),
+    /// capped at what's held.
+    fn intent_in_amount(&self, intent: &OpenClawIntent) -> Result<u64, AmountError> {
+        if intent.action != TradeAction::Sell {
+            return to_raw_amount(intent.amount_usd, USDC_DECIMALS);
+        }
+        let mint = &intent.input_mint;
+        let position = self
+            .portfolio
+            .get_position(mint)
+            .filter(|p| p.quantity_raw > 0)
+            .ok_or_else(|| AmountError::NothingHeld(mint.clone()))?;
+        if position.is_short() && !self.portfolio.allow_shorts {
+            return Err(AmountError::NothingHeld(mint.clone()));
+        }
+        let price = position
+            .current_price_usdc
+            .unwrap_or(position.avg_entry_price_usdc);
+        if price <= Decimal::ZERO {
+            return Err(AmountError::Unpriced(mint.clone()));
+        }
+        let decimals = crate::mints::decimals(mint)
+            .ok_or_else(|| AmountError::UnknownDecimals(mint.clone()))?;
+        let quantity = intent
+            .amount_usd
+            .checked_div(price)
+            .ok_or(AmountError::Overflow {
+                amount: intent.amount_usd,
+                decimals,
+            })?;
+        let raw = to_raw_amount(quantity, decimals)?;
+        // A paper portfolio that allows shorts sells past what it holds
+        if self.portfolio.allow_shorts {
+            return Ok(raw);
+        }
+        Ok(raw.min(position.quantity_raw))
+    }
+
+    /// Shrink an intent to what the market absorbs within `max_slippage_bps`
+    ///
+    /// Asks data-retrieval for a depth estimate of the traded asset. Without
+    /// one the intent goes out as sized, still subject to the impact check on
+    /// its quote; an intent the market can't take any of is blocked.
+    async fn size_for_liquidity(
+        &self,
+        intent: &OpenClawIntent,
+        side: TradeSide,
+        config: &BotConfig,
+    ) -> Result<OpenClawIntent, NormalizedTradeResult> {
+        let execution = &config.execution;
+        let Some(executor) = self
+            .executor
+            .as_ref()
+            .filter(|_| execution.liquidity_sizing)
+        else {
+            return Ok(intent.clone());
+        };
+        let mint = traded_mint(intent);
+        let symbol = self
+            .get_symbol_for_mint(mint)
+            .unwrap_or_else(|| mint.to_string());
+        let estimate = match executor
+            .fetch_liquidity(&symbol, side, intent.amount_usd, execution.max_slippage_bps)
+            .await
+        {
+            Ok(estimate) => estimate,
+            Err(e) => {
+                debug!(
+                    "No liquidity estimate for {} ({}), sending intent as sized",
+                    symbol, e
+                );
+                return Ok(intent.clone());
+            }
+        };
+
+        let sized = estimate.max_notional_usd.min(intent.amount_usd).round_dp(2);
+        if sized <= Decimal::ZERO {
+            warn!(
+                "Intent {} blocked: {} can't absorb any of ${} within {} bps ({})",
+                intent.intent_id,
+                symbol,
+                intent.amount_usd,
+                execution.max_slippage_bps,
+                estimate.source
+            );
+            return Err(NormalizedTradeResult {
+                stage_reached: TradeStage::Blocked,
+                error: Some(crate::executor::TradeError {
+                    stage: "sizing".to_string(),
+                    code: "insufficient_liquidity".to_string(),
+                    message: format!(
+                        "{} depth can't absorb ${} within {} bps",
+                        symbol, intent.amount_usd, execution.max_slippage_bps
+                    ),
+                }),
+                ..Default::default()
+            });
+        }
+        if sized < intent.amount_usd {
+            info!(
+                "Sizing intent {} down from ${} to ${}: {} estimates {:.1} bps of slippage on {}",
+                intent.intent_id,
+                intent.amount_usd,
+                sized,
+                estimate.source,
+                estimate.slippage_bps,
+                symbol
+            );
+        }
+        Ok(OpenClawIntent {
+            amount_usd: sized,
+            ..intent.clone()
+        })
     }
 
     /// Execute an OpenClaw intent
+    ///
+    /// Intents are first sized down to the market's depth (see
+    /// [`Self::size_for_liquidity`]). Large intents go out as TWAP slices
+    /// (see [`crate::twap`]), with their fill stats returned alongside the
+    /// consolidated result.
     async fn execute_openclaw_intent(
         &mut self,
         intent: &OpenClawIntent,
         config: &BotConfig,
-    ) -> NormalizedTradeResult {
+    ) -> (NormalizedTradeResult, Option<TwapFill>) {
         let executor = self.executor.as_ref().unwrap();
 
         // Determine trade side from action
@@ -630,63 +2247,172 @@ impl BotRunner {
             TradeAction::Sell => TradeSide::Sell,
             TradeAction::Hold => {
                 // Hold means no trade - return empty result
-                return NormalizedTradeResult::default();
+                return (NormalizedTradeResult::default(), None);
+            }
+        };
+
+        let intent = &match self.size_for_liquidity(intent, side, config).await {
+            Ok(sized) => sized,
+            Err(blocked) => return (blocked, None),
+        };
+
+        let in_amount = match self.intent_in_amount(intent) {
+            Ok(amount) => amount,
+            Err(e) => {
+                warn!("Can't size intent {}: {}", intent.intent_id, e);
+                let result = NormalizedTradeResult {
+                    stage_reached: TradeStage::Blocked,
+                    error: Some(crate::executor::TradeError {
+                        stage: "sizing".to_string(),
+                        code: "sizing_error".to_string(),
+                        message: e.to_string(),
+                    }),
+                    ..Default::default()
+                };
+                return (result, None);
             }
         };
 
-        // Convert USD amount to raw amount
-        let usdc_decimals = 6u8;
-        let in_amount =
-            (intent.amount_usd * Decimal::from(10u64.pow(usdc_decimals as u32))).to_u64().unwrap_or(0);
+        let execution = &config.execution;
+        if execution.twap_slices > 1 {
+            let max_position_value = self.portfolio.snapshot().total_equity
+                * Decimal::from(config.risk_caps.max_position_size_percent)
+                / Decimal::from(100);
+            let threshold = Decimal::try_from(execution.twap_min_position_pct)
+                .unwrap_or(Decimal::ONE_HUNDRED)
+                * max_position_value
+                / Decimal::from(100);
+            if max_position_value > Decimal::ZERO && intent.amount_usd >= threshold {
+                let (result, fill) = executor
+                    .execute_twap(
+                        &intent.intent_id.to_string(),
+                        &intent.input_mint,
+                        &intent.output_mint,
+                        in_amount,
+                        side,
+                        self.execution_mode(config),
+                        execution.twap_slices,
+                        std::time::Duration::from_secs(execution.twap_window_secs),
+                    )
+                    .await;
+                return (result, Some(fill));
+            }
+        }
 
         // Execute trade
-        executor
+        let result = executor
             .execute_trade(
                 &intent.intent_id.to_string(),
                 &intent.input_mint,
                 &intent.output_mint,
                 in_amount,
                 side,
-                config.trading_mode,
+                self.execution_mode(config),
             )
-            .await
+            .await;
+        (result, None)
     }
 
-    /// Get recent prices for assets (from executor cache or fresh fetch)
+    /// Fresh quotes for every enabled asset, fetched concurrently
+    ///
+    /// Assets whose price can't be fetched are left out rather than sent to
+    /// the agent with a made-up price.
     async fn get_recent_prices(&self) -> HashMap<String, PriceQuote> {
         let mut prices = HashMap::new();
+        let (Some(config), Some(executor)) = (&self.current_config, &self.executor) else {
+            return prices;
+        };
 
-        // Get prices for configured asset universe
-        if let Some(config) = &self.current_config {
-            for asset in &config.asset_universe {
-                if !asset.enabled {
-                    continue;
+        let mut fetches = tokio::task::JoinSet::new();
+        for asset in config.asset_universe.iter().filter(|a| a.enabled) {
+            let executor = executor.clone();
+            let mint = asset.mint.clone();
+            let symbol = asset.symbol.clone();
+            fetches.spawn(async move {
+                let quote = executor.fetch_price_quote(&mint, &symbol).await;
+                (symbol, quote)
+            });
+        }
+
+        while let Some(joined) = fetches.join_next().await {
+            match joined {
+                Ok((_, Ok(quote))) => {
+                    prices.insert(quote.mint.clone(), quote);
                 }
+                Ok((symbol, Err(e))) => warn!("No price for {}: {}", symbol, e),
+                Err(e) => warn!("Price fetch task failed: {}", e),
+            }
+        }
 
-                // Try to get cached price from executor
-                // For now, return empty - executor will fetch on demand
-                prices.insert(
-                    asset.mint.clone(),
-                    PriceQuote {
-                        mint: asset.mint.clone(),
-                        symbol: asset.symbol.clone(),
-                        price_usd: Decimal::ZERO, // Will be fetched by OpenClaw
-                        change_24h_pct: None,
-                        timestamp: chrono::Utc::now(),
-                        source: "pending".to_string(),
-                    },
+        prices
+    }
+
+    /// Funding, open interest and sentiment for every enabled asset that
+    /// has them
+    ///
+    /// Optional context, so failures are only logged and None is returned
+    /// when no asset had any.
+    async fn get_market_metadata(&self) -> Option<MarketMetadata> {
+        let (Some(config), Some(executor)) = (&self.current_config, &self.executor) else {
+            return None;
+        };
+
+        let mut fetches = tokio::task::JoinSet::new();
+        for asset in config.asset_universe.iter().filter(|a| a.enabled) {
+            let executor = executor.clone();
+            let mint = asset.mint.clone();
+            let symbol = asset.symbol.clone();
+            fetches.spawn(async move {
+                let (funding, sentiment) = tokio::join!(
+                    executor.fetch_funding(&symbol),
+                    executor.fetch_sentiment(&symbol)
                 );
+                (mint, symbol, funding, sentiment)
+            });
+        }
+
+        let mut metadata = MarketMetadata::default();
+        while let Some(joined) = fetches.join_next().await {
+            let (mint, symbol, funding, sentiment) = match joined {
+                Ok(fetched) => fetched,
+                Err(e) => {
+                    warn!("Market metadata task failed: {}", e);
+                    continue;
+                }
+            };
+            match funding {
+                Ok(Some(funding)) => {
+                    metadata.funding.insert(mint.clone(), funding);
+                }
+                Ok(None) => {}
+                Err(e) => debug!("No funding for {}: {}", symbol, e),
+            }
+            match sentiment {
+                Ok(Some(sentiment)) => {
+                    metadata.sentiment.insert(mint, sentiment);
+                }
+                Ok(None) => {}
+                Err(e) => debug!("No sentiment for {}: {}", symbol, e),
             }
         }
 
-        prices
+        (!metadata.is_empty()).then_some(metadata)
     }
 
-    /// Get recent trade events for context
-    fn get_recent_events(&self) -> Vec<TradeEvent> {
-        // Return empty for now - events are stored in control plane
-        // Could be populated from local event cache
-        Vec::new()
+    /// Remember an intent's outcome for later decision contexts
+    fn record_trade_outcome(&mut self, intent: &OpenClawIntent, event_type: &str, outcome: &str) {
+        let mint = traded_mint(intent);
+        let symbol = self
+            .get_symbol_for_mint(mint)
+            .unwrap_or_else(|| mint.to_string());
+        self.recent_events.push(TradeEvent {
+            timestamp: chrono::Utc::now(),
+            event_type: event_type.to_string(),
+            symbol,
+            side: Some(intent.action.to_string()),
+            amount_usd: Some(intent.amount_usd),
+            outcome: Some(outcome.to_string()),
+        });
     }
 
     /// Get symbol for a mint address
@@ -712,12 +2438,14 @@ impl BotRunner {
             last_trade_outcome: self.last_trade_outcome.clone(),
             portfolio_equity_usd: snapshot.total_equity,
             positions_count: snapshot.positions.len(),
+            degradation: self.degradation.mode(),
+            degraded_subsystems: self.degraded_subsystems(),
             updated_at: chrono::Utc::now(),
         };
 
         let path = self.state_dir.join("now.json");
         let content = serde_json::to_string_pretty(&state)?;
-        std::fs::write(path, content)?;
+        crate::sealed::write(path, content)?;
 
         Ok(())
     }
@@ -726,24 +2454,32 @@ impl BotRunner {
     fn write_context_file(&self, context: &DecisionContext) -> anyhow::Result<()> {
         let path = self.state_dir.join("decision_context.json");
         let content = serde_json::to_string_pretty(context)?;
-        std::fs::write(path, content)?;
+        crate::sealed::write(path, content)?;
         Ok(())
     }
 
-    /// Write journal entry for decision
-    fn write_journal_entry(&self, entry: &DecisionJournalEntry) -> anyhow::Result<()> {
+    /// Chain a journal entry, write it out and queue it for upload
+    fn write_journal_entry(&mut self, entry: &DecisionJournalEntry) -> anyhow::Result<()> {
+        let chained = self.journal.append(entry.clone())?;
         let path = self
             .state_dir
             .join("journal/decisions")
             .join(format!("{}.json", entry.intent_id));
-        let content = serde_json::to_string_pretty(entry)?;
-        std::fs::write(path, content)?;
+        let content = serde_json::to_string_pretty(&chained)?;
+        crate::sealed::write(path, content)?;
+
+        if self.journal_outbox.len() >= MAX_OUTBOX_EVENTS {
+            // The control plane will see the gap as a chain break
+            warn!("Journal outbox full, dropping oldest entry");
+            self.journal_outbox.remove(0);
+        }
+        self.journal_outbox.push(chained);
         Ok(())
     }
 
     /// Emit event when intent is blocked
-    async fn emit_intent_blocked(&self, intent: &OpenClawIntent, validation: &IntentValidation) {
-        let event = EventInput {
+    fn emit_intent_blocked(&mut self, intent: &OpenClawIntent, validation: &IntentValidation) {
+        let mut event = EventInput {
             event_type: "trade_blocked".to_string(),
             message: validation
                 .rejection_reason
@@ -760,15 +2496,22 @@ impl BotRunner {
             })),
             timestamp: chrono::Utc::now(),
         };
-        self.client.send_events(vec![event]).await.ok();
+        // Merge the rail's own context (e.g. `cooldown_remaining_secs`)
+        if let Some(serde_json::Value::Object(details)) = &validation.details {
+            if let Some(serde_json::Value::Object(metadata)) = event.metadata.as_mut() {
+                metadata.extend(details.clone());
+            }
+        }
+        self.queue_event(event);
     }
 
     /// Emit trade events for OpenClaw intent execution
-    async fn emit_openclaw_trade_events(
-        &self,
+    fn emit_openclaw_trade_events(
+        &mut self,
         intent: &OpenClawIntent,
         result: &NormalizedTradeResult,
         config: &BotConfig,
+        twap: Option<&TwapFill>,
     ) {
         use crate::executor::TradeStage;
 
@@ -790,7 +2533,7 @@ impl BotRunner {
             })),
             timestamp: chrono::Utc::now(),
         };
-        self.client.send_events(vec![created_event]).await.ok();
+        self.queue_event(created_event);
 
         // Emit stage-specific event
         match result.stage_reached {
@@ -816,7 +2559,7 @@ impl BotRunner {
                     })),
                     timestamp: chrono::Utc::now(),
                 };
-                self.client.send_events(vec![blocked_event]).await.ok();
+                self.queue_event(blocked_event);
             }
 
             TradeStage::Submitted => {
@@ -834,10 +2577,11 @@ impl BotRunner {
                     })),
                     timestamp: chrono::Utc::now(),
                 };
-                self.client.send_events(vec![submitted_event]).await.ok();
+                self.queue_event(submitted_event);
             }
 
             TradeStage::Confirmed => {
+                let (swap_fee, network_fee) = self.fill_fees(intent, result);
                 let confirmed_event = EventInput {
                     event_type: "trade_confirmed".to_string(),
                     message: format!("Trade confirmed: {:?}", result.signature),
@@ -851,11 +2595,15 @@ impl BotRunner {
                         "executed_price": result.execution.realized_price.to_string(),
                         "price_impact_pct": result.quote.price_impact_pct,
                         "slippage_bps": result.execution.slippage_bps_estimate,
-                        "mode": format!("{:?}", config.trading_mode),
+                        "fee_bps": result.quote.fee_bps,
+                        "fee_usd": (swap_fee + network_fee).round_dp(6).to_string(),
+                        "network_fee_lamports": result.execution.network_fee_lamports,
+                        "mode": format!("{:?}", result.trading_mode),
+                        "twap": twap,
                     })),
                     timestamp: chrono::Utc::now(),
                 };
-                self.client.send_events(vec![confirmed_event]).await.ok();
+                self.queue_event(confirmed_event);
             }
 
             TradeStage::Failed => {
@@ -882,35 +2630,367 @@ impl BotRunner {
                     })),
                     timestamp: chrono::Utc::now(),
                 };
-                self.client.send_events(vec![failed_event]).await.ok();
+                self.queue_event(failed_event);
+            }
+        }
+    }
+
+    /// Queue an event for delivery on the next sync
+    ///
+    /// Critical events (see [`crate::client::is_critical`]) skip the batch and
+    /// go out right away on the priority lane; if that fails they rejoin the
+    /// outbox and ride the next sync.
+    fn queue_event(&mut self, event: EventInput) {
+        if crate::client::is_critical(&event.event_type) {
+            if let Ok(handle) = tokio::runtime::Handle::try_current() {
+                let client = self.client.clone();
+                let fallback = self.priority_fallback.clone();
+                handle.spawn(async move {
+                    let sent = client
+                        .send_priority_events(std::slice::from_ref(&event))
+                        .await;
+                    if let Err(e) = sent {
+                        warn!(
+                            "Priority delivery of {} failed, deferring to sync: {}",
+                            event.event_type, e
+                        );
+                        fallback.lock().unwrap().push(event);
+                    }
+                });
+                return;
             }
         }
+        self.push_outbox(event);
+    }
+
+    /// Add an event to the next sync's batch
+    fn push_outbox(&mut self, event: EventInput) {
+        if self.outbox.len() >= MAX_OUTBOX_EVENTS {
+            warn!(
+                "Event outbox full ({} events), dropping oldest",
+                MAX_OUTBOX_EVENTS
+            );
+            self.outbox.remove(0);
+        }
+        self.outbox.push(event);
     }
 
-    /// Send heartbeat with metrics
-    async fn send_heartbeat(&self) -> anyhow::Result<()> {
+    /// Sync with control plane: heartbeat, metrics, queued events and state summary
+    async fn sync_with_control_plane(&mut self) -> anyhow::Result<()> {
         let status = if self.current_config.is_some() {
             "online"
         } else {
             "configuring"
         };
 
+        let deferred = std::mem::take(&mut *self.priority_fallback.lock().unwrap());
+        for event in deferred {
+            self.push_outbox(event);
+        }
+
         // Get portfolio snapshot for metrics
         let snapshot = self.portfolio.snapshot();
 
-        // Build metrics
-        let metrics = Some(vec![MetricInput {
+        let req = SyncRequest {
+            status: status.to_string(),
             timestamp: chrono::Utc::now(),
-            equity: snapshot.total_equity,
-            pnl: snapshot.unrealized_pnl + snapshot.realized_pnl,
-        }]);
+            metrics: vec![MetricInput {
+                timestamp: chrono::Utc::now(),
+                equity: snapshot.total_equity,
+                pnl: snapshot.unrealized_pnl + snapshot.realized_pnl,
+                fees_usd: snapshot.fees_total,
+                performance: self.performance.stats(chrono::Utc::now()),
+            }],
+            events: std::mem::take(&mut self.outbox),
+            journal: std::mem::take(&mut self.journal_outbox),
+            state: Some(SyncStateSummary {
+                runner_status: self.status.to_string(),
+                config_version_id: self.current_config.as_ref().map(|c| c.version_id),
+                equity_usd: snapshot.total_equity,
+                cash_usd: snapshot.cash_usdc,
+                positions_count: snapshot.positions.len() as i32,
+                trades_today: self.trade_count as i32,
+                last_plan_id: self.last_plan_id,
+                degradation_mode: self.degradation.mode().to_string(),
+                degraded_subsystems: self.degraded_subsystems(),
+            }),
+            llm_usage: (!self.llm_usage.is_empty()).then(|| std::mem::take(&mut self.llm_usage)),
+            sync_id: uuid::Uuid::new_v4(),
+        };
 
-        let response = self.client.heartbeat(status, metrics).await?;
+        let response = match self.client.sync(&req).await {
+            Ok(response) => response,
+            Err(e) => {
+                // Put events back so they go out with the next sync
+                let mut events = req.events;
+                events.append(&mut self.outbox);
+                let overflow = events.len().saturating_sub(MAX_OUTBOX_EVENTS);
+                events.drain(..overflow);
+                self.outbox = events;
+                let mut journal = req.journal;
+                journal.append(&mut self.journal_outbox);
+                let overflow = journal.len().saturating_sub(MAX_OUTBOX_EVENTS);
+                journal.drain(..overflow);
+                self.journal_outbox = journal;
+                if let Some(usage) = req.llm_usage {
+                    self.llm_usage.merge(usage);
+                }
+                return Err(e);
+            }
+        };
 
-        if response.needs_config_update {
+        self.last_sync_at = chrono::Utc::now();
+
+        debug!(
+            "Sync complete via {}: {} events accepted, config_pending={}{}",
+            response.region.as_deref().unwrap_or("control plane"),
+            response.events_accepted,
+            response.config_pending,
+            if response.duplicate {
+                " (already stored)"
+            } else {
+                ""
+            }
+        );
+
+        for command in &response.commands {
+            self.handle_command(command);
+        }
+        if let Some(command_id) = self.flatten_requested.take() {
+            self.flatten_positions(command_id).await;
+        }
+        if let Some(command_id) = self.export_requested.take() {
+            self.export_state(command_id).await;
+        }
+        if let Some(command_id) = self.diagnostics_requested.take() {
+            self.upload_diagnostics(command_id).await;
+        }
+
+        if let Some(desired) = &response.desired_status {
+            self.apply_desired_status(desired);
+        }
+
+        // Halts take effect now rather than waiting for the next tick
+        self.governor
+            .set_remote_halt(response.trading_halted, response.halt_reason.clone());
+        self.check_governor();
+
+        if response.config_pending {
             info!("Control plane indicates config update needed");
+            self.poll_config().await?;
         }
 
         Ok(())
     }
+
+    /// Handle a command delivered in a sync response
+    fn handle_command(&mut self, command: &BotCommand) {
+        match command.command.as_str() {
+            "set_log_level" => self.set_log_level(command),
+            "cancel_intents" => self.cancel_intents(command),
+            // Trades can't run from here; the sync picks it up once commands are handled
+            "flatten_positions" => self.flatten_requested = Some(command.id),
+            "export_state" => self.export_requested = Some(command.id),
+            "upload_diagnostics" => self.diagnostics_requested = Some(command.id),
+            _ => warn!(
+                "Ignoring unsupported command {} ({}): {}",
+                command.command, command.id, command.args
+            ),
+        }
+    }
+
+    /// `cancel_intents {reason}`: drop every intent that hasn't been submitted
+    fn cancel_intents(&mut self, command: &BotCommand) {
+        let reason = command
+            .args
+            .get("reason")
+            .and_then(|v| v.as_str())
+            .unwrap_or("owner request");
+        let cancelled = self.intent_registry.cancel_pending(reason);
+        warn!("Cancelled {} pending intents ({})", cancelled, reason);
+        self.queue_event(EventInput {
+            event_type: "intents_cancelled".to_string(),
+            message: format!("Cancelled {} pending intents: {}", cancelled, reason),
+            metadata: Some(serde_json::json!({
+                "command_id": command.id.to_string(),
+                "reason": reason,
+                "cancelled": cancelled,
+            })),
+            timestamp: chrono::Utc::now(),
+        });
+    }
+
+    /// `flatten_positions`: sell every open position for USDC
+    ///
+    /// Runs even while the owner has paused the bot, since that's how the
+    /// kill switch asks for it; a governor halt still blocks it.
+    async fn flatten_positions(&mut self, command_id: uuid::Uuid) {
+        let failure = if self.governor.is_paused() {
+            Some("trading is halted by the governor")
+        } else if self.handed_over {
+            Some("state was handed over for a redeploy")
+        } else if self.executor.is_none() {
+            Some("no executor initialized")
+        } else {
+            None
+        };
+        let config = match (failure, &self.current_config) {
+            (None, Some(config)) => config.clone(),
+            (failure, _) => {
+                let reason = failure.unwrap_or("no config loaded");
+                warn!("flatten_positions ({}) failed: {}", command_id, reason);
+                self.queue_event(EventInput {
+                    event_type: "command_failed".to_string(),
+                    message: format!("flatten_positions failed: {}", reason),
+                    metadata: Some(serde_json::json!({
+                        "command_id": command_id.to_string(),
+                        "command": "flatten_positions",
+                    })),
+                    timestamp: chrono::Utc::now(),
+                });
+                return;
+            }
+        };
+
+        let orders = self.orders.flatten_orders(&self.portfolio);
+        warn!("Flattening {} positions ({})", orders.len(), command_id);
+        self.execute_exit_orders(orders, &config).await;
+        self.status = if self.owner_paused {
+            RunnerStatus::Paused
+        } else {
+            RunnerStatus::Idle
+        };
+        self.write_state_file().ok();
+    }
+
+    /// `export_state`: hand this runner's state to the droplet replacing it
+    ///
+    /// Trading stops for good first, so nothing changes after the snapshot
+    /// and the old and new droplets never trade at the same time.
+    async fn export_state(&mut self, command_id: uuid::Uuid) {
+        self.handed_over = true;
+        self.status = RunnerStatus::Paused;
+        let cancelled = self.intent_registry.cancel_pending("redeploy");
+        self.persist_state();
+
+        let uploaded = match StateBundle::collect(&self.state_dir, self.config.bot_id) {
+            Ok(bundle) => {
+                let files = bundle.files.len();
+                self.client.upload_state(&bundle).await.map(|()| files)
+            }
+            Err(e) => Err(e),
+        };
+        match uploaded {
+            Ok(files) => info!(
+                "Exported {} state files for redeploy ({} pending intents cancelled)",
+                files, cancelled
+            ),
+            Err(e) => {
+                warn!("export_state ({}) failed: {}", command_id, e);
+                self.queue_event(EventInput {
+                    event_type: "command_failed".to_string(),
+                    message: format!("export_state failed: {}", e),
+                    metadata: Some(serde_json::json!({
+                        "command_id": command_id.to_string(),
+                        "command": "export_state",
+                    })),
+                    timestamp: chrono::Utc::now(),
+                });
+            }
+        }
+    }
+
+    /// `upload_diagnostics`: send a snapshot of runtime state for support
+    ///
+    /// Carries the same state files as a handover plus what only lives in
+    /// memory (status, portfolio, queued uploads), without touching trading.
+    async fn upload_diagnostics(&mut self, command_id: uuid::Uuid) {
+        let files = match StateBundle::collect(&self.state_dir, self.config.bot_id) {
+            Ok(bundle) => bundle.files,
+            Err(e) => {
+                warn!("Diagnostics: couldn't read state files: {}", e);
+                Default::default()
+            }
+        };
+        let bundle = serde_json::json!({
+            "bot_id": self.config.bot_id,
+            "collected_at": chrono::Utc::now(),
+            "runner_version": env!("CARGO_PKG_VERSION"),
+            "status": self.status,
+            "config_version": self.current_config.as_ref().map(|c| c.version),
+            "handed_over": self.handed_over,
+            "owner_paused": self.owner_paused,
+            "live_funded": self.live_funded,
+            "trade_count": self.trade_count,
+            "realized_pnl_today": self.realized_pnl_today,
+            "drawdown": self.drawdown,
+            "portfolio": self.portfolio,
+            "outbox_len": self.outbox.len(),
+            "journal_outbox_len": self.journal_outbox.len(),
+            "files": files,
+        });
+
+        match self.client.upload_diagnostics(&bundle).await {
+            Ok(()) => info!("Uploaded diagnostics ({})", command_id),
+            Err(e) => {
+                warn!("upload_diagnostics ({}) failed: {}", command_id, e);
+                self.queue_event(EventInput {
+                    event_type: "command_failed".to_string(),
+                    message: format!("upload_diagnostics failed: {}", e),
+                    metadata: Some(serde_json::json!({
+                        "command_id": command_id.to_string(),
+                        "command": "upload_diagnostics",
+                    })),
+                    timestamp: chrono::Utc::now(),
+                });
+            }
+        }
+    }
+
+    /// `set_log_level {level, ttl_secs}`: change the tracing filter until the TTL lapses
+    fn set_log_level(&mut self, command: &BotCommand) {
+        let level = command.args.get("level").and_then(|v| v.as_str());
+        let ttl = command
+            .args
+            .get("ttl_secs")
+            .and_then(|v| v.as_u64())
+            .unwrap_or(DEFAULT_LOG_LEVEL_TTL_SECS);
+
+        let result = match (&self.log_level, level) {
+            (None, _) => Err(anyhow::anyhow!("log level control not installed")),
+            (_, None) => Err(anyhow::anyhow!("missing 'level' argument")),
+            (Some(control), Some(level)) => control.set(level, Duration::from_secs(ttl)),
+        };
+
+        let event = match result {
+            Ok(applied) => EventInput {
+                event_type: "log_level_changed".to_string(),
+                message: format!(
+                    "Log filter set to '{}' for {}s",
+                    level.unwrap_or_default(),
+                    applied.as_secs()
+                ),
+                metadata: Some(serde_json::json!({
+                    "command_id": command.id.to_string(),
+                    "level": level,
+                    "ttl_secs": applied.as_secs(),
+                })),
+                timestamp: chrono::Utc::now(),
+            },
+            Err(e) => {
+                warn!("set_log_level ({}) failed: {}", command.id, e);
+                EventInput {
+                    event_type: "command_failed".to_string(),
+                    message: format!("set_log_level failed: {}", e),
+                    metadata: Some(serde_json::json!({
+                        "command_id": command.id.to_string(),
+                        "command": command.command,
+                        "args": command.args,
+                    })),
+                    timestamp: chrono::Utc::now(),
+                }
+            }
+        };
+        self.queue_event(event);
+    }
 }
@@ -0,0 +1,208 @@
+//! Optional at-rest encryption of the state directory
+//!
+//! The runner's state files (runner_state.json, now.json, the decision
+//! journal, exit levels, recent events) hold strategy and holdings data. For
+//! bots that opt in, the control plane delivers a per-bot key with the
+//! bootstrap secrets (`STATE_ENCRYPTION_KEY`, 64 hex characters) and every
+//! state file is written as AES-256-GCM ciphertext behind a short magic
+//! header. Reads accept both forms, so turning encryption on migrates files as
+//! they're next written, and without a key everything stays plaintext.
+//!
+//! Operator-facing files such as `KILL_SWITCH` are left alone.
+
+use aes_gcm::{
+    aead::{Aead, KeyInit},
+    Aes256Gcm, Nonce,
+};
+use rand::Rng;
+use std::io;
+use std::path::Path;
+use std::sync::OnceLock;
+
+/// Leads every encrypted file; plaintext JSON can't start with it
+const MAGIC: &[u8] = b"TTSEAL1\n";
+
+/// AES-256-GCM nonce size (96 bits)
+const NONCE_SIZE: usize = 12;
+
+/// Encrypts and decrypts state files, or passes them through without a key
+pub struct Sealer {
+    cipher: Option<Aes256Gcm>,
+}
+
+impl Sealer {
+    /// No encryption: files are written and read as they are
+    pub fn plaintext() -> Self {
+        Self { cipher: None }
+    }
+
+    /// Encrypt with a 32-byte key given as hex
+    pub fn from_hex(key_hex: &str) -> anyhow::Result<Self> {
+        let key = hex::decode(key_hex.trim())
+            .map_err(|e| anyhow::anyhow!("State encryption key is not hex: {}", e))?;
+        anyhow::ensure!(
+            key.len() == 32,
+            "State encryption key must be 32 bytes (64 hex chars), got {} bytes",
+            key.len()
+        );
+        let cipher = Aes256Gcm::new_from_slice(&key)
+            .map_err(|e| anyhow::anyhow!("Invalid state encryption key: {}", e))?;
+        Ok(Self {
+            cipher: Some(cipher),
+        })
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.cipher.is_some()
+    }
+
+    /// `plaintext` as written to disk
+    pub fn seal(&self, plaintext: &[u8]) -> io::Result<Vec<u8>> {
+        let Some(cipher) = &self.cipher else {
+            return Ok(plaintext.to_vec());
+        };
+        let mut nonce = [0u8; NONCE_SIZE];
+        rand::thread_rng().fill(&mut nonce);
+        let ciphertext = cipher
+            .encrypt(Nonce::from_slice(&nonce), plaintext)
+            .map_err(|_| io::Error::other("state encryption failed"))?;
+
+        let mut sealed = Vec::with_capacity(MAGIC.len() + NONCE_SIZE + ciphertext.len());
+        sealed.extend_from_slice(MAGIC);
+        sealed.extend_from_slice(&nonce);
+        sealed.extend_from_slice(&ciphertext);
+        Ok(sealed)
+    }
+
+    /// File contents back as plaintext; plaintext files pass through
+    pub fn open(&self, data: Vec<u8>) -> io::Result<Vec<u8>> {
+        let Some(body) = data.strip_prefix(MAGIC) else {
+            return Ok(data);
+        };
+        let Some(cipher) = &self.cipher else {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "file is encrypted and no state encryption key is set",
+            ));
+        };
+        if body.len() < NONCE_SIZE {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "encrypted file is truncated",
+            ));
+        }
+        let (nonce, ciphertext) = body.split_at(NONCE_SIZE);
+        cipher
+            .decrypt(Nonce::from_slice(nonce), ciphertext)
+            .map_err(|_| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "decryption failed (wrong key or tampered file)",
+                )
+            })
+    }
+
+    pub fn read_to_string(&self, path: &Path) -> io::Result<String> {
+        let plaintext = self.open(std::fs::read(path)?)?;
+        String::from_utf8(plaintext).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+
+    pub fn write(&self, path: &Path, contents: &[u8]) -> io::Result<()> {
+        std::fs::write(path, self.seal(contents)?)
+    }
+}
+
+static SEALER: OnceLock<Sealer> = OnceLock::new();
+
+/// Set the process-wide key (None for plaintext); only the first call counts
+///
+/// Must run before anything reads the state directory.
+pub fn init(key_hex: Option<&str>) -> anyhow::Result<()> {
+    let sealer = match key_hex.filter(|k| !k.trim().is_empty()) {
+        Some(key) => Sealer::from_hex(key)?,
+        None => Sealer::plaintext(),
+    };
+    let _ = SEALER.set(sealer);
+    Ok(())
+}
+
+fn current() -> &'static Sealer {
+    SEALER.get_or_init(Sealer::plaintext)
+}
+
+/// Whether state files are encrypted when written
+pub fn is_active() -> bool {
+    current().is_active()
+}
+
+/// Whether the file at `path` is encrypted
+pub fn is_sealed(path: &Path) -> bool {
+    use std::io::Read;
+
+    let mut header = [0u8; MAGIC.len()];
+    std::fs::File::open(path)
+        .and_then(|mut file| file.read_exact(&mut header))
+        .is_ok_and(|_| header == MAGIC)
+}
+
+/// Read a state file, decrypting it if needed
+pub fn read_to_string(path: impl AsRef<Path>) -> io::Result<String> {
+    current().read_to_string(path.as_ref())
+}
+
+/// Write a state file, encrypted when a key is set
+pub fn write(path: impl AsRef<Path>, contents: impl AsRef<[u8]>) -> io::Result<()> {
+    current().write(path.as_ref(), contents.as_ref())
+}
+
+/// `contents` as they'd be written to disk, for callers writing themselves
+pub fn seal(contents: &[u8]) -> io::Result<Vec<u8>> {
+    current().seal(contents)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEST_KEY: &str = "0123456789abcdef0123456789abcdef0123456789abcdef0123456789abcdef";
+
+    #[test]
+    fn test_sealed_round_trip_and_migration() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("now.json");
+        let sealer = Sealer::from_hex(TEST_KEY).unwrap();
+        let plain = Sealer::plaintext();
+
+        // Files written before encryption was turned on still read
+        plain.write(&path, br#"{"status":"running"}"#).unwrap();
+        assert!(!is_sealed(&path));
+        assert_eq!(
+            sealer.read_to_string(&path).unwrap(),
+            r#"{"status":"running"}"#
+        );
+
+        sealer.write(&path, br#"{"cash":"1000"}"#).unwrap();
+        assert!(is_sealed(&path));
+        let raw = std::fs::read(&path).unwrap();
+        assert!(!String::from_utf8_lossy(&raw).contains("cash"));
+        assert_eq!(sealer.read_to_string(&path).unwrap(), r#"{"cash":"1000"}"#);
+
+        // Fresh nonce per write
+        assert_ne!(sealer.seal(b"x").unwrap(), sealer.seal(b"x").unwrap());
+
+        // No key, the wrong key or a flipped byte all fail rather than
+        // returning garbage
+        assert_eq!(
+            plain.read_to_string(&path).unwrap_err().kind(),
+            io::ErrorKind::InvalidData
+        );
+        let other = Sealer::from_hex(&"ab".repeat(32)).unwrap();
+        assert!(other.read_to_string(&path).is_err());
+        let mut tampered = raw.clone();
+        *tampered.last_mut().unwrap() ^= 0xff;
+        assert!(sealer.open(tampered).is_err());
+
+        assert!(Sealer::from_hex("abcd").is_err());
+        assert!(Sealer::from_hex("not hex").is_err());
+    }
+}
@@ -1,9 +1,21 @@
-//! State Management - Write "chatty" state files for observability
+//! State Management - Write "chatty" state files for observability, and
+//! persist the runner state that must survive a restart
 
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::io::Write;
+use std::path::{Path, PathBuf};
 use tokio::fs;
-use tracing::{debug, error};
+use tracing::{debug, warn};
+use uuid::Uuid;
+
+use crate::client::EventInput;
+use crate::cooldown::SymbolCooldowns;
+use crate::drawdown::DrawdownTracker;
+use crate::journal::ChainedJournalEntry;
+use crate::performance::PerformanceTracker;
+use crate::portfolio::Portfolio;
 
 /// Manages state files for observability
 pub struct StateManager {
@@ -50,8 +62,190 @@ impl StateManager {
     ) -> anyhow::Result<()> {
         let path = self.state_dir.join("now.json");
         let json = serde_json::to_string_pretty(state)?;
-        fs::write(&path, json).await?;
+        fs::write(&path, crate::sealed::seal(json.as_bytes())?).await?;
         debug!("Wrote state/now.json");
         Ok(())
     }
 }
+
+// Durable runner state
+//
+// Daily counters, the paper portfolio and undelivered events live in memory,
+// so without this a crash or restart would reset `trade_count` and let the
+// bot trade past `max_trades_per_day`. The snapshot is rewritten after every
+// decision tick and sync, via a temp file renamed into place so a crash
+// mid-write leaves the previous snapshot intact.
+
+const STATE_FILE: &str = "runner_state.json";
+
+/// Snapshot of the runner fields that must survive a restart
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PersistedState {
+    pub bot_id: Uuid,
+    pub trade_count: u32,
+    pub realized_pnl_today: Decimal,
+    pub portfolio: Portfolio,
+    /// Events not yet accepted by the control plane
+    #[serde(default)]
+    pub outbox: Vec<EventInput>,
+    /// Journal entries not yet uploaded
+    #[serde(default)]
+    pub journal_outbox: Vec<ChainedJournalEntry>,
+    /// Owner pause, so a restart doesn't trade before the first sync
+    #[serde(default)]
+    pub owner_paused: bool,
+    /// Equity high-water mark for the max drawdown check
+    #[serde(default)]
+    pub drawdown: DrawdownTracker,
+    /// Last fill per mint for the per-symbol cooldown
+    #[serde(default)]
+    pub cooldowns: SymbolCooldowns,
+    /// Trailing closes and daily equity for the heartbeat's statistics
+    #[serde(default)]
+    pub performance: PerformanceTracker,
+    /// Pre-existing wallet holdings have been imported (see `inventory`)
+    #[serde(default)]
+    pub inventory_imported: bool,
+    pub saved_at: DateTime<Utc>,
+}
+
+impl PersistedState {
+    /// State for a bot with no snapshot: no trades, starting paper cash
+    pub fn fresh(bot_id: Uuid) -> Self {
+        Self {
+            bot_id,
+            trade_count: 0,
+            realized_pnl_today: Decimal::ZERO,
+            portfolio: Portfolio::new(Decimal::from(10000)),
+            outbox: Vec::new(),
+            journal_outbox: Vec::new(),
+            owner_paused: false,
+            drawdown: DrawdownTracker::default(),
+            cooldowns: SymbolCooldowns::default(),
+            performance: PerformanceTracker::default(),
+            inventory_imported: false,
+            saved_at: Utc::now(),
+        }
+    }
+}
+
+/// Reads and writes the snapshot in the runner's state directory
+pub struct StateStore {
+    path: PathBuf,
+}
+
+impl StateStore {
+    pub fn new(state_dir: &Path) -> Self {
+        Self {
+            path: state_dir.join(STATE_FILE),
+        }
+    }
+
+    /// Load the snapshot for `bot_id`
+    ///
+    /// A missing file, one written for a different bot, or one that can't be
+    /// parsed all mean starting fresh; unreadable files are moved aside (not
+    /// deleted) for inspection.
+    pub fn load(&self, bot_id: Uuid) -> Option<PersistedState> {
+        let raw = match crate::sealed::read_to_string(&self.path) {
+            Ok(raw) => raw,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return None,
+            Err(e) => {
+                warn!("Failed to read {}: {}", self.path.display(), e);
+                return None;
+            }
+        };
+
+        match serde_json::from_str::<PersistedState>(&raw) {
+            Ok(state) if state.bot_id == bot_id => Some(state),
+            Ok(state) => {
+                warn!(
+                    "Ignoring saved state for bot {} (running as {})",
+                    state.bot_id, bot_id
+                );
+                None
+            }
+            Err(e) => {
+                let aside = self.path.with_extension("json.corrupt");
+                warn!(
+                    "Saved state {} is unreadable ({}), moving it to {}",
+                    self.path.display(),
+                    e,
+                    aside.display()
+                );
+                let _ = std::fs::rename(&self.path, aside);
+                None
+            }
+        }
+    }
+
+    /// Atomically replace the snapshot
+    pub fn save(&self, state: &PersistedState) -> anyhow::Result<()> {
+        let tmp = self.path.with_extension("json.tmp");
+        let contents = crate::sealed::seal(serde_json::to_string(state)?.as_bytes())?;
+        let mut file = std::fs::File::create(&tmp)?;
+        file.write_all(&contents)?;
+        file.sync_all()?;
+        std::fs::rename(&tmp, &self.path)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_state_round_trip_and_recovery() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = StateStore::new(dir.path());
+        let bot_id = Uuid::new_v4();
+        assert!(store.load(bot_id).is_none());
+
+        let state = PersistedState {
+            bot_id,
+            trade_count: 7,
+            realized_pnl_today: Decimal::new(-1250, 2),
+            portfolio: Portfolio::new(Decimal::from(500)),
+            outbox: vec![EventInput {
+                event_type: "trade_confirmed".to_string(),
+                message: "ok".to_string(),
+                metadata: None,
+                timestamp: Utc::now(),
+            }],
+            journal_outbox: Vec::new(),
+            owner_paused: true,
+            drawdown: DrawdownTracker {
+                high_water: Decimal::from(600),
+                breached: true,
+            },
+            cooldowns: {
+                let mut cooldowns = SymbolCooldowns::default();
+                cooldowns.record("So11111111111111111111111111111111111111112", Utc::now());
+                cooldowns
+            },
+            performance: PerformanceTracker::default(),
+            inventory_imported: true,
+            saved_at: Utc::now(),
+        };
+        store.save(&state).unwrap();
+
+        let loaded = store.load(bot_id).unwrap();
+        assert_eq!(loaded.trade_count, 7);
+        assert_eq!(loaded.realized_pnl_today, Decimal::new(-1250, 2));
+        assert_eq!(loaded.portfolio.cash_usdc_raw, 500_000_000);
+        assert_eq!(loaded.outbox.len(), 1);
+        assert!(loaded.owner_paused);
+        assert!(loaded.inventory_imported);
+        assert_eq!(loaded.drawdown, state.drawdown);
+        assert_eq!(loaded.cooldowns, state.cooldowns);
+
+        // Another bot's state isn't picked up
+        assert!(store.load(Uuid::new_v4()).is_none());
+
+        // A corrupt file is moved aside rather than crashing startup
+        std::fs::write(dir.path().join(STATE_FILE), "{not json").unwrap();
+        assert!(store.load(bot_id).is_none());
+        assert!(dir.path().join("runner_state.json.corrupt").exists());
+    }
+}
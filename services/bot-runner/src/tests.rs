@@ -102,5 +102,6 @@ mod tests {
         assert_eq!(exec.max_slippage_bps, 100);
         assert_eq!(exec.confirm_timeout_secs, 60);
         assert_eq!(exec.quote_cache_secs, 10);
+        assert_eq!(exec.twap_slices, 1);
     }
 }
@@ -0,0 +1,245 @@
+//! TWAP execution for large intents
+//!
+//! An intent close to the max position size moves the price when it goes
+//! out as one swap. With `execution.twap_slices` above 1, intents at least
+//! `execution.twap_min_position_pct` of the max position size are split into
+//! that many child swaps spaced evenly over `execution.twap_window_secs`
+//! (see [`crate::executor::TradeExecutor::execute_twap`]).
+//!
+//! The children are consolidated back into one result for the parent, so
+//! journaling, booking and the daily trade limit see a single trade. The
+//! first child that doesn't confirm stops the rest; what filled before it is
+//! kept as a partial fill.
+
+use rust_decimal::Decimal;
+use serde::Serialize;
+
+use crate::executor::{NormalizedTradeResult, TradeStage};
+
+/// Aggregate fill statistics for a sliced intent
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct TwapFill {
+    pub slices_planned: u32,
+    pub slices_filled: u32,
+    /// Raw input amount of the parent intent
+    pub requested_in: u64,
+    /// Raw input amount the confirmed slices spent
+    pub filled_in: u64,
+    /// Raw output amount the confirmed slices received
+    pub filled_out: u64,
+    /// Output per input over all confirmed slices
+    pub avg_price: Decimal,
+    /// Worst price impact quoted for any slice
+    pub max_price_impact_pct: f64,
+    pub signatures: Vec<String>,
+    /// Error code of the slice that stopped the rest, if one did
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stopped_by: Option<String>,
+}
+
+impl TwapFill {
+    /// Some but not all of the parent filled
+    pub fn is_partial(&self) -> bool {
+        self.filled_in > 0 && self.filled_in < self.requested_in
+    }
+
+    /// Share of the requested input that filled (0-1)
+    pub fn fill_ratio(&self) -> Decimal {
+        if self.requested_in == 0 {
+            return Decimal::ZERO;
+        }
+        Decimal::from(self.filled_in) / Decimal::from(self.requested_in)
+    }
+}
+
+/// Split `amount` into `slices` near-equal children, largest first
+///
+/// Slices that would be empty are dropped, so tiny amounts come back as fewer
+/// (or one) children.
+pub fn split_amount(amount: u64, slices: u32) -> Vec<u64> {
+    let slices = u64::from(slices.max(1));
+    let (base, remainder) = (amount / slices, amount % slices);
+    (0..slices)
+        .map(|i| base + u64::from(i < remainder))
+        .filter(|&child| child > 0)
+        .collect()
+}
+
+/// Consolidate child results into the parent's
+///
+/// Confirmed if any child confirmed, with amounts and fees summed over the
+/// confirmed children. Otherwise the parent takes the stage and error of the
+/// child that stopped it.
+pub fn consolidate(
+    intent_id: &str,
+    requested_in: u64,
+    slices_planned: u32,
+    children: &[NormalizedTradeResult],
+) -> (NormalizedTradeResult, TwapFill) {
+    let mut fill = TwapFill {
+        slices_planned,
+        requested_in,
+        ..Default::default()
+    };
+    let Some(first) = children.first() else {
+        let result = NormalizedTradeResult {
+            intent_id: intent_id.to_string(),
+            ..Default::default()
+        };
+        return (result, fill);
+    };
+
+    let mut result = NormalizedTradeResult {
+        intent_id: intent_id.to_string(),
+        input_mint: first.input_mint.clone(),
+        output_mint: first.output_mint.clone(),
+        side: first.side,
+        trading_mode: first.trading_mode,
+        shield_result: first.shield_result.clone(),
+        ..Default::default()
+    };
+    result.quote.fee_bps = first.quote.fee_bps;
+
+    for child in children {
+        fill.max_price_impact_pct = fill.max_price_impact_pct.max(child.quote.price_impact_pct);
+        if child.stage_reached != TradeStage::Confirmed {
+            fill.stopped_by = Some(
+                child
+                    .error
+                    .as_ref()
+                    .map(|e| e.code.clone())
+                    .unwrap_or_else(|| format!("{:?}", child.stage_reached).to_lowercase()),
+            );
+            if fill.slices_filled == 0 {
+                result.stage_reached = child.stage_reached;
+                result.error = child.error.clone();
+                result.quote = child.quote.clone();
+            }
+            break;
+        }
+        fill.slices_filled += 1;
+        fill.filled_in += child.quote.in_amount;
+        fill.filled_out += child.execution.out_amount_raw;
+        result.quote.expected_out += child.quote.expected_out;
+        result.execution.network_fee_lamports += child.execution.network_fee_lamports;
+        result.execution.slippage_bps_estimate = result
+            .execution
+            .slippage_bps_estimate
+            .max(child.execution.slippage_bps_estimate);
+        if let Some(signature) = &child.signature {
+            fill.signatures.push(signature.clone());
+        }
+    }
+
+    if fill.slices_filled > 0 {
+        if fill.filled_in > 0 {
+            fill.avg_price = Decimal::from(fill.filled_out) / Decimal::from(fill.filled_in);
+        }
+        result.stage_reached = TradeStage::Confirmed;
+        result.signature = fill.signatures.last().cloned();
+        result.quote.in_amount = fill.filled_in;
+        result.quote.price_impact_pct = fill.max_price_impact_pct;
+        result.execution.out_amount_raw = fill.filled_out;
+        result.execution.realized_price = fill.avg_price;
+    }
+    (result, fill)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::executor::{ExecutionData, QuoteData, TradeError};
+
+    fn child(
+        stage: TradeStage,
+        in_amount: u64,
+        out_amount: u64,
+        impact: f64,
+    ) -> NormalizedTradeResult {
+        NormalizedTradeResult {
+            intent_id: "parent-slice".to_string(),
+            stage_reached: stage,
+            signature: (stage == TradeStage::Confirmed).then(|| format!("sig-{}", in_amount)),
+            quote: QuoteData {
+                in_amount,
+                expected_out: out_amount,
+                price_impact_pct: impact,
+                fee_bps: 30,
+            },
+            execution: ExecutionData {
+                out_amount_raw: if stage == TradeStage::Confirmed {
+                    out_amount
+                } else {
+                    0
+                },
+                realized_price: Decimal::ZERO,
+                slippage_bps_estimate: Some(10),
+                network_fee_lamports: 5_000,
+            },
+            error: (stage != TradeStage::Confirmed).then(|| TradeError {
+                stage: "quote".to_string(),
+                code: "impact_too_high".to_string(),
+                message: "Price impact too high".to_string(),
+            }),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_split_amount() {
+        assert_eq!(split_amount(10, 3), vec![4, 3, 3]);
+        assert_eq!(split_amount(9, 3), vec![3, 3, 3]);
+        assert_eq!(split_amount(2, 4), vec![1, 1]);
+        assert_eq!(split_amount(7, 0), vec![7]);
+        assert!(split_amount(0, 3).is_empty());
+        assert_eq!(split_amount(1_000_001, 4).iter().sum::<u64>(), 1_000_001);
+    }
+
+    #[test]
+    fn test_consolidate_fills() {
+        let children = [
+            child(TradeStage::Confirmed, 400, 800, 0.4),
+            child(TradeStage::Confirmed, 300, 570, 0.6),
+            child(TradeStage::Confirmed, 300, 630, 0.5),
+        ];
+        let (result, fill) = consolidate("parent", 1_000, 3, &children);
+        assert_eq!(result.intent_id, "parent");
+        assert_eq!(result.stage_reached, TradeStage::Confirmed);
+        assert_eq!(result.quote.in_amount, 1_000);
+        assert_eq!(result.execution.out_amount_raw, 2_000);
+        assert_eq!(result.execution.realized_price, Decimal::from(2));
+        assert_eq!(result.execution.network_fee_lamports, 15_000);
+        assert_eq!(result.quote.price_impact_pct, 0.6);
+        assert_eq!(result.signature.as_deref(), Some("sig-300"));
+        assert_eq!(fill.slices_filled, 3);
+        assert_eq!(fill.signatures.len(), 3);
+        assert!(!fill.is_partial());
+        assert!(fill.stopped_by.is_none());
+
+        // A blocked slice stops the rest and leaves a partial fill
+        let children = [
+            child(TradeStage::Confirmed, 400, 800, 0.4),
+            child(TradeStage::Blocked, 300, 570, 2.5),
+        ];
+        let (result, fill) = consolidate("parent", 1_000, 3, &children);
+        assert_eq!(result.stage_reached, TradeStage::Confirmed);
+        assert_eq!(result.quote.in_amount, 400);
+        assert!(result.error.is_none());
+        assert!(fill.is_partial());
+        assert_eq!(fill.fill_ratio(), Decimal::new(4, 1));
+        assert_eq!(fill.max_price_impact_pct, 2.5);
+        assert_eq!(fill.stopped_by.as_deref(), Some("impact_too_high"));
+
+        // Nothing filled: the parent is blocked like its first slice
+        let (result, fill) = consolidate(
+            "parent",
+            1_000,
+            3,
+            &[child(TradeStage::Blocked, 400, 800, 2.5)],
+        );
+        assert_eq!(result.stage_reached, TradeStage::Blocked);
+        assert_eq!(result.error.unwrap().code, "impact_too_high");
+        assert_eq!(fill.slices_filled, 0);
+        assert!(!fill.is_partial());
+    }
+}
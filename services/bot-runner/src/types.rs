@@ -27,6 +27,73 @@ pub struct DecisionContext {
     pub recent_events: Vec<TradeEvent>,
     /// Current config version hash
     pub config_version: String,
+    /// Derivatives data for tradeable assets, when data-retrieval has any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub market_metadata: Option<MarketMetadata>,
+}
+
+/// Market data beyond spot prices
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MarketMetadata {
+    /// Perpetual funding and open interest by mint, for assets with a perpetual
+    #[serde(default)]
+    pub funding: HashMap<String, FundingSnapshot>,
+    /// Fear & Greed and news sentiment by mint, for crypto assets
+    #[serde(default)]
+    pub sentiment: HashMap<String, SentimentSnapshot>,
+}
+
+impl MarketMetadata {
+    pub fn is_empty(&self) -> bool {
+        self.funding.is_empty() && self.sentiment.is_empty()
+    }
+}
+
+/// Sentiment summary for an asset from data-retrieval
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SentimentSnapshot {
+    /// Token symbol
+    pub symbol: String,
+    /// -1 (bearish) to 1 (bullish), from the Fear & Greed index and news votes
+    pub score: f64,
+    /// `bearish`, `neutral` or `bullish`
+    pub label: String,
+    /// Fear & Greed index (0-100), market-wide
+    #[serde(default)]
+    pub fear_greed: Option<u8>,
+    /// News posts behind the score (absent without a news source)
+    #[serde(default)]
+    pub news_posts: Option<usize>,
+}
+
+/// Perpetual funding rate and open interest for an asset
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FundingSnapshot {
+    /// Token symbol
+    pub symbol: String,
+    /// Last funding rate per 8h period, as a fraction (0.0001 = 0.01%)
+    pub funding_rate: f64,
+    /// Funding rate over a year, in percent
+    pub funding_annualized_pct: f64,
+    /// Which side is paying to hold its position
+    pub positioning: Positioning,
+    /// Premium of the perpetual over spot
+    pub basis_bps: f64,
+    /// Open interest in USD
+    pub open_interest_usd: Decimal,
+    /// Next funding settlement
+    pub next_funding_time: DateTime<Utc>,
+}
+
+/// Crowding read from the funding rate
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Positioning {
+    /// Longs paying well above the neutral rate
+    CrowdedLong,
+    Neutral,
+    /// Shorts paying longs
+    CrowdedShort,
 }
 
 /// Portfolio snapshot for decision context
@@ -72,10 +139,43 @@ pub struct PriceQuote {
     pub price_usd: Decimal,
     /// 24h change percentage
     pub change_24h_pct: Option<f64>,
+    /// Market regime from data-retrieval's hourly candles, when it has them
+    #[serde(default)]
+    pub regime: Option<MarketRegime>,
     /// Quote timestamp
     pub timestamp: DateTime<Utc>,
     /// Data source
     pub source: String,
+    /// data-retrieval's sources disagreed past its spread limit
+    #[serde(default)]
+    pub unreliable: bool,
+    /// USD in the token's DEX pools (tokens outside the built-in table only)
+    #[serde(default)]
+    pub liquidity_usd: Option<Decimal>,
+}
+
+/// How an asset has been trading lately
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Regime {
+    /// Moving steadily one way (`trend_pct` says which)
+    Trending,
+    /// Chopping sideways
+    Ranging,
+    /// Swinging far more than usual
+    HighVolatility,
+}
+
+/// Regime classification with the statistics behind it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MarketRegime {
+    pub regime: Regime,
+    /// Net move over the path travelled by the last day's closes (0-1)
+    pub efficiency_ratio: f64,
+    /// Percent change over the last day
+    pub trend_pct: f64,
+    /// Daily volatility of recent returns, in percent
+    pub volatility_pct: f64,
 }
 
 /// Risk constraints enforced by runner
@@ -123,6 +223,20 @@ pub struct DecisionPlan {
     pub explanations: Vec<String>,
     /// Suggestions for user (optional)
     pub suggestions: Vec<String>,
+    /// Tokens the LLM spent on this plan (absent from older gateways)
+    #[serde(default)]
+    pub usage: Option<LlmUsage>,
+}
+
+/// Token counts for one LLM call, as reported by the gateway
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct LlmUsage {
+    #[serde(default)]
+    pub model: Option<String>,
+    #[serde(default)]
+    pub prompt_tokens: u64,
+    #[serde(default)]
+    pub completion_tokens: u64,
 }
 
 /// Trade intent from OpenClaw
@@ -174,6 +288,9 @@ pub struct IntentValidation {
     pub rejection_reason: Option<String>,
     /// Which rail blocked it
     pub blocked_by: Option<String>,
+    /// Rail-specific context added to the `trade_blocked` event
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub details: Option<serde_json::Value>,
 }
 
 /// Current runner state for now.json
@@ -191,6 +308,12 @@ pub struct RunnerState {
     pub portfolio_equity_usd: Decimal,
     /// Number of positions
     pub positions_count: usize,
+    /// Rung of the degradation ladder the runner is on
+    #[serde(default)]
+    pub degradation: crate::degradation::DegradedMode,
+    /// Subsystems down as of the last decision tick
+    #[serde(default)]
+    pub degraded_subsystems: Vec<String>,
     /// Last updated
     pub updated_at: DateTime<Utc>,
 }
@@ -207,6 +330,9 @@ pub enum RunnerStatus {
     Executing,
     /// Paused by governor
     Paused,
+    /// Live mode, waiting for the wallet to be funded
+    #[serde(rename = "awaiting_funding")]
+    AwaitingFunding,
     /// Error state
     Error,
 }
@@ -218,6 +344,7 @@ impl std::fmt::Display for RunnerStatus {
             RunnerStatus::Deciding => write!(f, "deciding"),
             RunnerStatus::Executing => write!(f, "executing"),
             RunnerStatus::Paused => write!(f, "paused"),
+            RunnerStatus::AwaitingFunding => write!(f, "awaiting_funding"),
             RunnerStatus::Error => write!(f, "error"),
         }
     }
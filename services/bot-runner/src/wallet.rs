@@ -0,0 +1,156 @@
+//! Agent wallet keypair
+//!
+//! The runner owns its Solana keypair rather than relying on claw-trader to
+//! create one as a side effect. Keypairs use the Solana CLI file format (a
+//! JSON array of the 64 secret+public key bytes), so claw-trader and
+//! `solana-keygen` can read the same file.
+
+use ed25519_dalek::SigningKey;
+use std::io::Write;
+use std::path::Path;
+use tracing::info;
+
+/// An ed25519 keypair loaded from (or written to) a Solana keypair file
+pub struct Wallet {
+    signing_key: SigningKey,
+}
+
+impl Wallet {
+    /// Generate a new random keypair
+    pub fn generate() -> Self {
+        Self {
+            signing_key: SigningKey::generate(&mut rand::rngs::OsRng),
+        }
+    }
+
+    /// Parse a Solana keypair file's contents
+    ///
+    /// Rejects files whose public half doesn't match the secret key.
+    pub fn from_json(raw: &str) -> anyhow::Result<Self> {
+        let bytes: Vec<u8> = serde_json::from_str(raw)
+            .map_err(|e| anyhow::anyhow!("Keypair is not a JSON byte array: {}", e))?;
+        let bytes: [u8; 64] = bytes
+            .try_into()
+            .map_err(|b: Vec<u8>| anyhow::anyhow!("Keypair must be 64 bytes, got {}", b.len()))?;
+        let signing_key = SigningKey::from_keypair_bytes(&bytes)
+            .map_err(|_| anyhow::anyhow!("Keypair public key does not match its secret key"))?;
+        Ok(Self { signing_key })
+    }
+
+    /// Serialize in the Solana keypair file format
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(&self.signing_key.to_keypair_bytes().to_vec())
+            .expect("byte array serializes")
+    }
+
+    /// Base58 public key, i.e. the wallet address
+    pub fn pubkey(&self) -> String {
+        bs58::encode(self.signing_key.verifying_key().as_bytes()).into_string()
+    }
+
+    /// Read the keypair at `path`
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let raw = std::fs::read_to_string(path)
+            .map_err(|e| anyhow::anyhow!("Failed to read keypair {}: {}", path.display(), e))?;
+        Self::from_json(&raw).map_err(|e| e.context(format!("Invalid keypair {}", path.display())))
+    }
+
+    /// Write the keypair to `path` (owner-only), refusing to overwrite
+    pub fn save(&self, path: &Path) -> anyhow::Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        let mut options = std::fs::OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(0o600);
+        }
+        let mut file = options
+            .open(path)
+            .map_err(|e| anyhow::anyhow!("Failed to create keypair {}: {}", path.display(), e))?;
+        file.write_all(self.to_json().as_bytes())?;
+        Ok(())
+    }
+}
+
+/// Load the keypair at `path`, generating one if the file doesn't exist
+///
+/// Returns the wallet and whether it was newly created.
+pub fn load_or_create(path: &Path) -> anyhow::Result<(Wallet, bool)> {
+    if path.exists() {
+        return Ok((Wallet::load(path)?, false));
+    }
+
+    let wallet = Wallet::generate();
+    wallet.save(path)?;
+    info!(
+        "Generated agent wallet {} at {}",
+        wallet.pubkey(),
+        path.display()
+    );
+    Ok((wallet, true))
+}
+
+/// Check the keypair against an address configured elsewhere
+///
+/// `None` or the "unknown" placeholder means nothing to check against.
+pub fn verify_address(wallet: &Wallet, expected: Option<&str>) -> anyhow::Result<()> {
+    match expected {
+        Some(addr) if !addr.is_empty() && addr != "unknown" && addr != wallet.pubkey() => {
+            Err(anyhow::anyhow!(
+                "Keypair pubkey {} does not match configured wallet address {}",
+                wallet.pubkey(),
+                addr
+            ))
+        }
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_load_or_create_round_trips() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("solana/id.json");
+
+        let (created, is_new) = load_or_create(&path).unwrap();
+        assert!(is_new);
+        let (loaded, is_new) = load_or_create(&path).unwrap();
+        assert!(!is_new);
+        assert_eq!(created.pubkey(), loaded.pubkey());
+
+        // 32-byte keys encode to 32-44 base58 characters
+        let decoded = bs58::decode(loaded.pubkey()).into_vec().unwrap();
+        assert_eq!(decoded.len(), 32);
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+    }
+
+    #[test]
+    fn test_rejects_mismatched_keypair_and_address() {
+        let wallet = Wallet::generate();
+        let mut bytes: Vec<u8> = serde_json::from_str(&wallet.to_json()).unwrap();
+        assert_eq!(bytes.len(), 64);
+
+        // Corrupt the public half
+        bytes[63] ^= 0xff;
+        assert!(Wallet::from_json(&serde_json::to_string(&bytes).unwrap()).is_err());
+        assert!(Wallet::from_json("[1,2,3]").is_err());
+
+        assert!(verify_address(&wallet, None).is_ok());
+        assert!(verify_address(&wallet, Some("unknown")).is_ok());
+        assert!(verify_address(&wallet, Some(&wallet.pubkey())).is_ok());
+        assert!(verify_address(&wallet, Some(&Wallet::generate().pubkey())).is_err());
+    }
+}
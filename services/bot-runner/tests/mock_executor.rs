@@ -132,7 +132,7 @@ impl MockTradeExecutor {
         result
     }
 
-    fn calculate_mock_swap(&self, input_mint: &str, output_mint: &str, amount: u64) -> (u64, f64) {
+    fn calculate_mock_swap(&self, _input_mint: &str, output_mint: &str, amount: u64) -> (u64, f64) {
         // USDC is 6 decimals
         let usdc_decimals = 1_000_000.0;
         let amount_usdc = amount as f64 / usdc_decimals;
@@ -175,6 +175,7 @@ impl MockTradeExecutor {
             out_amount_raw: out_amount,
             realized_price,
             slippage_bps_estimate: Some(self.execution_config.max_slippage_bps),
+            network_fee_lamports: bot_runner::executor::BASE_NETWORK_FEE_LAMPORTS,
         };
     }
 
@@ -219,6 +220,7 @@ mod tests {
             max_slippage_bps: 100,
             confirm_timeout_secs: 60,
             quote_cache_secs: 10,
+            ..Default::default()
         };
 
         let executor = MockTradeExecutor::new(config);
@@ -245,6 +247,7 @@ mod tests {
             max_slippage_bps: 100,
             confirm_timeout_secs: 60,
             quote_cache_secs: 10,
+            ..Default::default()
         };
 
         // Use custom oracle that always returns high impact
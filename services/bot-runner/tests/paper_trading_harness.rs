@@ -3,14 +3,18 @@
 //! Validates the full trading loop:
 //! config → signal → intent → shield/quote → execute (paper) → events + portfolio
 
+#[allow(dead_code)]
 mod mock_executor;
 
 use bot_runner::{
     client::{EventInput, MetricInput},
-    config::{AssetFocus, BotConfig, ExecutionConfig, Persona, RiskCaps, TradingMode},
+    config::{
+        AssetFocus, AssetLimits, BotConfig, ExecutionConfig, ExitRules, FundingRequirements,
+        Persona, RiskCaps, TradingMode,
+    },
     executor::{TradeError, TradeSide, TradeStage},
     intent::{IntentRegistry, TradeIntentState},
-    portfolio::{Portfolio, Position},
+    portfolio::{Portfolio, Position, PositionSide},
 };
 use mock_executor::{MockPriceOracle, MockTradeExecutor};
 use rust_decimal::Decimal;
@@ -26,6 +30,12 @@ pub struct MockControlPlane {
     config_acked: Arc<Mutex<Vec<Uuid>>>,
 }
 
+impl Default for MockControlPlane {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl MockControlPlane {
     pub fn new() -> Self {
         Self {
@@ -77,9 +87,17 @@ pub fn create_test_config(version: i32) -> BotConfig {
             max_slippage_bps: 100,
             confirm_timeout_secs: 60,
             quote_cache_secs: 10,
+            ..Default::default()
         },
+        funding: FundingRequirements::default(),
+        exits: ExitRules::default(),
+        asset_limits: AssetLimits::default(),
+        trading_window: None,
+        degradation: Default::default(),
         llm_provider: "test".to_string(),
+        llm_model: "test".to_string(),
         llm_api_key: "test".to_string(),
+        telegram_bot_token: None,
         strategy_preset: "conservative".to_string(),
         strategy_params: serde_json::json!({}),
         asset_universe: vec![],
@@ -139,6 +157,7 @@ async fn test_trade_blocked_by_impact() {
             max_slippage_bps: 100,
             confirm_timeout_secs: 60,
             quote_cache_secs: 10,
+            ..Default::default()
         },
         ..create_test_config(1)
     };
@@ -421,6 +440,10 @@ fn test_reconciler_unknown_cost_basis() {
             current_price_usdc: None,
             last_updated: Utc::now(),
             unknown_cost_basis: true,
+            high_water_price_usdc: None,
+            lots: Vec::new(),
+            fees_usd: Decimal::ZERO,
+            side: PositionSide::Long,
         },
     );
 
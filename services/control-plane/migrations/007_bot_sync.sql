@@ -0,0 +1,23 @@
+-- Migration: 007_bot_sync.sql
+-- Purpose: Support the consolidated POST /bot/:id/sync endpoint
+-- Stores the runner's last reported state summary and a queue of commands
+-- delivered to the bot in sync responses.
+
+ALTER TABLE bots ADD COLUMN IF NOT EXISTS last_sync_state JSONB;
+ALTER TABLE bots ADD COLUMN IF NOT EXISTS last_sync_at TIMESTAMPTZ;
+
+CREATE TABLE IF NOT EXISTS bot_commands (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    bot_id UUID NOT NULL REFERENCES bots(id) ON DELETE CASCADE,
+    command TEXT NOT NULL,                         -- e.g. 'set_log_level'
+    args JSONB NOT NULL DEFAULT '{}'::jsonb,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    delivered_at TIMESTAMPTZ                       -- NULL until handed to the bot
+);
+
+-- Pending command lookup on every sync
+CREATE INDEX IF NOT EXISTS idx_bot_commands_pending ON bot_commands(bot_id, created_at)
+    WHERE delivered_at IS NULL;
+
+COMMENT ON TABLE bot_commands IS 'Commands queued for a bot, delivered once via the sync response';
+COMMENT ON COLUMN bots.last_sync_state IS 'Runner state summary from the most recent sync call';
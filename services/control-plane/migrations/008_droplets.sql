@@ -0,0 +1,25 @@
+-- Migration: 008_droplets.sql
+-- Purpose: Track droplet lifecycle for per-bot infrastructure cost accounting
+-- One row per droplet ever created. The monthly list price is snapshotted at
+-- creation so later provider price changes don't rewrite historical costs.
+
+CREATE TABLE IF NOT EXISTS droplets (
+    id BIGINT PRIMARY KEY,                         -- DigitalOcean droplet id
+    bot_id UUID REFERENCES bots(id) ON DELETE SET NULL,
+    name TEXT NOT NULL,
+    region TEXT NOT NULL,
+    size TEXT NOT NULL,
+    monthly_price_usd NUMERIC(10, 2),              -- NULL if size missing from pricing table
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    destroyed_at TIMESTAMPTZ                       -- NULL while running
+);
+
+CREATE INDEX IF NOT EXISTS idx_droplets_bot ON droplets(bot_id);
+CREATE INDEX IF NOT EXISTS idx_droplets_active ON droplets(created_at) WHERE destroyed_at IS NULL;
+
+COMMENT ON TABLE droplets IS 'Droplet lifecycle history used for infrastructure cost accounting';
+
+-- Whether users can see the infra cost of their own bots
+INSERT INTO platform_config (key, value, encrypted, description, category) VALUES
+    ('show_infra_cost_to_users', 'false', FALSE, 'Expose estimated droplet cost on GET /bots/:id/infra-cost', 'provisioning')
+ON CONFLICT (key) DO NOTHING;
@@ -0,0 +1,17 @@
+-- Migration: 009_provisioning_audit.sql
+-- Purpose: Audit trail for destructive actions taken by droplet reconciliation
+-- (destroying orphaned droplets, flagging bots whose droplet disappeared).
+
+CREATE TABLE IF NOT EXISTS provisioning_audit_log (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    action TEXT NOT NULL,                          -- e.g. 'destroy_orphan_droplet'
+    bot_id UUID,                                   -- no FK: the bot may be deleted later
+    droplet_id BIGINT,
+    reason TEXT NOT NULL,
+    actor TEXT NOT NULL DEFAULT 'reconciler',
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_provisioning_audit_time ON provisioning_audit_log(created_at DESC);
+
+COMMENT ON TABLE provisioning_audit_log IS 'Destructive provisioning actions taken automatically by the control plane';
@@ -0,0 +1,12 @@
+-- Migration: 010_manual_provisioning.sql
+-- Purpose: Bots whose runner is operated by the user (local/dev or self-hosted)
+-- 'manual' bots never get a droplet; the owner fetches credentials and starts
+-- bot-runner with RUNNER_MODE=external.
+
+DO $$ BEGIN
+    CREATE TYPE provisioning_mode AS ENUM ('droplet', 'manual');
+EXCEPTION WHEN duplicate_object THEN null;
+END $$;
+
+ALTER TABLE bots
+    ADD COLUMN IF NOT EXISTS provisioning provisioning_mode NOT NULL DEFAULT 'droplet';
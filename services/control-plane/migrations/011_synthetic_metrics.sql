@@ -0,0 +1,8 @@
+-- Migration: 011_synthetic_metrics.sql
+-- Purpose: Flag metric points reconstructed by the equity backfill
+-- Points rebuilt from the trade ledger while a runner was offline are stored
+-- alongside real ones so charts stay continuous, but must stay distinguishable.
+
+ALTER TABLE metrics ADD COLUMN IF NOT EXISTS synthetic BOOLEAN NOT NULL DEFAULT FALSE;
+
+COMMENT ON COLUMN metrics.synthetic IS 'TRUE for points reconstructed from the trade ledger, not reported by the bot';
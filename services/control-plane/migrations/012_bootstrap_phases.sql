@@ -0,0 +1,9 @@
+-- Migration: 012_bootstrap_phases.sql
+-- Purpose: Track droplet bootstrap progress reported by `bot-runner bootstrap`
+-- One entry per phase ('binaries', 'wallet', 'secrets', 'services') holding
+-- the latest status, detail and report time, so a stuck droplet shows which
+-- phase to retry.
+
+ALTER TABLE bots ADD COLUMN IF NOT EXISTS bootstrap_phases JSONB NOT NULL DEFAULT '{}'::jsonb;
+
+COMMENT ON COLUMN bots.bootstrap_phases IS 'Latest bootstrap phase status: {phase: {status, detail, at}}';
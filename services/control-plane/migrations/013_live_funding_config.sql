@@ -0,0 +1,9 @@
+-- Migration: 013_live_funding_config.sql
+-- Purpose: Minimum wallet balances checked before a bot may trade live
+-- The runner blocks live trading until the agent wallet holds both amounts;
+-- GET /bots/:id/funding shows users what to send.
+
+INSERT INTO platform_config (key, value, encrypted, description, category) VALUES
+    ('live_min_usdc', '10', FALSE, 'Minimum USDC in the agent wallet before live trading starts', 'trading'),
+    ('live_min_sol', '0.05', FALSE, 'Minimum SOL (fees and rent) in the agent wallet before live trading starts', 'trading')
+ON CONFLICT (key) DO NOTHING;
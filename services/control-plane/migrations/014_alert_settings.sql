@@ -0,0 +1,13 @@
+-- Migration: 014_alert_settings.sql
+-- Purpose: Per-user alert fatigue controls
+-- Quiet hours are UTC and wrap past midnight when start > end; critical
+-- alerts are still delivered during them. NULL max_alerts_per_hour falls back
+-- to the platform default.
+
+CREATE TABLE IF NOT EXISTS alert_settings (
+    user_id UUID PRIMARY KEY REFERENCES users(id) ON DELETE CASCADE,
+    quiet_start_hour SMALLINT CHECK (quiet_start_hour BETWEEN 0 AND 23),
+    quiet_end_hour SMALLINT CHECK (quiet_end_hour BETWEEN 0 AND 23),
+    max_alerts_per_hour INTEGER CHECK (max_alerts_per_hour > 0),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
@@ -0,0 +1,28 @@
+-- Migration: 015_alerts.sql
+-- Purpose: Keep fired alerts so users can review and acknowledge them
+-- Repeats of an unresolved alert (same group_key) fold into one row; the
+-- control plane resolves rows once the condition clears (bot heartbeats
+-- again, config applied).
+
+CREATE TABLE IF NOT EXISTS alerts (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    group_key TEXT NOT NULL,
+    alert_type TEXT NOT NULL,
+    bot_id UUID REFERENCES bots(id) ON DELETE CASCADE,
+    user_id UUID REFERENCES users(id) ON DELETE CASCADE,
+    severity TEXT NOT NULL CHECK (severity IN ('info', 'warning', 'critical')),
+    state TEXT NOT NULL DEFAULT 'open' CHECK (state IN ('open', 'acknowledged', 'resolved')),
+    title TEXT NOT NULL,
+    message TEXT NOT NULL,
+    count INTEGER NOT NULL DEFAULT 1,
+    suppressed INTEGER NOT NULL DEFAULT 0,
+    first_fired_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    last_fired_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    acknowledged_at TIMESTAMPTZ,
+    resolved_at TIMESTAMPTZ
+);
+
+-- At most one unresolved alert per key; repeats update it
+CREATE UNIQUE INDEX IF NOT EXISTS idx_alerts_unresolved_key ON alerts(group_key) WHERE state <> 'resolved';
+CREATE INDEX IF NOT EXISTS idx_alerts_user_fired ON alerts(user_id, last_fired_at DESC);
+CREATE INDEX IF NOT EXISTS idx_alerts_bot_unresolved ON alerts(bot_id) WHERE state <> 'resolved';
@@ -0,0 +1,6 @@
+-- Migration: 016_events_intent_index.sql
+-- Purpose: Let trade event compaction find every event of an intent
+-- Trade events carry their intent id in metadata; compaction folds the
+-- intent's trail into its terminal event once it is old enough.
+
+CREATE INDEX IF NOT EXISTS idx_events_intent_id ON events ((metadata->>'intent_id'));
@@ -0,0 +1,10 @@
+-- Migration: 017_trading_halt.sql
+-- Purpose: Platform-wide trading halt
+-- While trading_halted is 'true', every bot's sync response tells its runner
+-- to stop making decisions (the runner keeps syncing and reconciling).
+-- Set via PATCH /v1/admin/config.
+
+INSERT INTO platform_config (key, value, encrypted, description, category) VALUES
+    ('trading_halted', 'false', FALSE, 'Halt trading on all bots (runners skip decision ticks)', 'trading'),
+    ('trading_halt_reason', '', FALSE, 'Reason shown in bot events while trading is halted', 'trading')
+ON CONFLICT (key) DO NOTHING;
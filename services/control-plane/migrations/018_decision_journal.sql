@@ -0,0 +1,21 @@
+-- Migration: 018_decision_journal.sql
+-- Purpose: Hash-chained decision journal uploaded by runners
+-- Each entry links to the previous one by hash; chain_valid records whether
+-- it extended the stored chain when it arrived. GET /bots/:id/journal/verify
+-- re-checks the whole chain.
+
+CREATE TABLE IF NOT EXISTS decision_journal (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    bot_id UUID NOT NULL REFERENCES bots(id) ON DELETE CASCADE,
+    seq BIGINT NOT NULL,
+    intent_id UUID,
+    plan_hash TEXT NOT NULL,
+    prev_hash TEXT NOT NULL,
+    entry_hash TEXT NOT NULL,
+    entry JSONB NOT NULL,
+    chain_valid BOOLEAN NOT NULL,
+    received_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (bot_id, seq)
+);
+
+CREATE INDEX IF NOT EXISTS idx_decision_journal_intent ON decision_journal(intent_id);
@@ -0,0 +1,16 @@
+-- Migration: 019_share_links.sql
+-- Purpose: Opt-in public performance pages
+-- A bot owner can mint an unguessable token that unlocks a read-only,
+-- sanitized performance view at GET /v1/public/perf/:token. Only the token's
+-- SHA-256 is stored; revoking sets revoked_at and the link stops working.
+
+CREATE TABLE IF NOT EXISTS bot_share_links (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    bot_id UUID NOT NULL REFERENCES bots(id) ON DELETE CASCADE,
+    token_hash TEXT NOT NULL UNIQUE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    revoked_at TIMESTAMPTZ
+);
+
+-- One live link per bot
+CREATE UNIQUE INDEX IF NOT EXISTS idx_bot_share_links_active ON bot_share_links(bot_id) WHERE revoked_at IS NULL;
@@ -0,0 +1,35 @@
+-- Migration: 021_billing_sync.sql
+-- Purpose: Keep subscriptions in step with Stripe / Cedros Pay webhooks
+-- Provider events are stored once by their event id (providers retry, so
+-- duplicates are expected). A past-due subscription keeps its plan for
+-- payment_grace_period_hours; after that its live bots are paused and
+-- marked billing_paused_at so they can be resumed once payment recovers.
+
+ALTER TABLE subscriptions ADD COLUMN IF NOT EXISTS external_id TEXT;
+ALTER TABLE subscriptions ADD COLUMN IF NOT EXISTS past_due_since TIMESTAMPTZ;
+ALTER TABLE subscriptions ADD COLUMN IF NOT EXISTS updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW();
+
+CREATE INDEX IF NOT EXISTS idx_subscriptions_external_id ON subscriptions(external_id) WHERE external_id IS NOT NULL;
+CREATE INDEX IF NOT EXISTS idx_subscriptions_past_due ON subscriptions(past_due_since) WHERE past_due_since IS NOT NULL;
+
+ALTER TABLE bots ADD COLUMN IF NOT EXISTS billing_paused_at TIMESTAMPTZ;
+
+CREATE TABLE IF NOT EXISTS payment_events (
+    -- Provider's event id, prefixed with the provider (stripe:evt_..., cedros_pay:...)
+    event_id TEXT PRIMARY KEY,
+    provider TEXT NOT NULL,
+    event_type TEXT NOT NULL,
+    user_id UUID REFERENCES users(id) ON DELETE SET NULL,
+    payload JSONB NOT NULL,
+    received_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    -- Why the event couldn't be applied (unknown customer, etc.)
+    error TEXT
+);
+
+CREATE INDEX IF NOT EXISTS idx_payment_events_user ON payment_events(user_id, received_at DESC);
+
+INSERT INTO platform_config (key, value, encrypted, description, category) VALUES
+    ('stripe_webhook_secret', '', TRUE, 'Stripe webhook signing secret (whsec_...)', 'billing'),
+    ('cedros_pay_webhook_secret', '', TRUE, 'Cedros Pay callback HMAC secret', 'billing'),
+    ('payment_grace_period_hours', '72', FALSE, 'Hours a past-due subscription keeps its plan before live bots are paused', 'billing')
+ON CONFLICT (key) DO NOTHING;
@@ -0,0 +1,15 @@
+-- Migration: 022_kill_switch.sql
+-- Purpose: Account-level kill switch
+-- A row here means the user engaged POST /v1/me/kill-switch: all of their
+-- bots were paused, pending intents cancelled (and positions optionally
+-- flattened) through bot_commands. Bots can't be resumed, redeployed or
+-- created until DELETE /v1/me/kill-switch removes the row.
+
+CREATE TABLE IF NOT EXISTS kill_switches (
+    user_id UUID PRIMARY KEY REFERENCES users(id) ON DELETE CASCADE,
+    engaged_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    reason TEXT,
+    flatten BOOLEAN NOT NULL DEFAULT FALSE,
+    -- Cedros session that engaged it
+    session_id UUID
+);
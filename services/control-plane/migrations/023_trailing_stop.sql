@@ -0,0 +1,7 @@
+-- Migration: 023_trailing_stop.sql
+-- Purpose: Optional trailing stop per config version
+-- Percent below the highest price since entry at which the runner sells a
+-- position; NULL leaves trailing stops off.
+
+ALTER TABLE config_versions ADD COLUMN IF NOT EXISTS trailing_stop_percent INTEGER
+    CHECK (trailing_stop_percent BETWEEN 1 AND 50);
@@ -0,0 +1,15 @@
+-- Migration: 024_state_handover.sql
+-- Purpose: Carry a runner's local state across a redeploy
+-- The old runner uploads its state files when asked with `export_state`; the
+-- replacement droplet downloads them once on first boot. One snapshot per
+-- bot, replaced by each export.
+
+CREATE TABLE IF NOT EXISTS bot_state_snapshots (
+    bot_id UUID PRIMARY KEY REFERENCES bots(id) ON DELETE CASCADE,
+    bundle JSONB NOT NULL,
+    size_bytes INTEGER NOT NULL,
+    exported_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    restored_at TIMESTAMPTZ                        -- NULL until a runner downloads it
+);
+
+COMMENT ON TABLE bot_state_snapshots IS 'Runner state exported before a redeploy, restored by the next droplet';
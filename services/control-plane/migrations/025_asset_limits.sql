@@ -0,0 +1,6 @@
+-- Migration: 025_asset_limits.sql
+-- Purpose: Per-asset exposure caps and a max open positions cap per config
+-- {"max_open_positions": 5, "per_asset": [{"asset": "SOL", "max_exposure_usd": 500,
+-- "max_exposure_percent": 20}]}; NULL means no limits beyond the risk caps.
+
+ALTER TABLE config_versions ADD COLUMN IF NOT EXISTS asset_limits JSONB;
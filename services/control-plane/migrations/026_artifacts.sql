@@ -0,0 +1,31 @@
+-- Migration: 026_artifacts.sql
+-- Purpose: Large bot artifacts live in S3-compatible object storage
+-- Journal exports, diagnostic bundles and state snapshots are uploaded to the
+-- configured bucket; each row records the object key and when the lifecycle
+-- task may delete it. Downloads go through presigned URLs.
+
+CREATE TABLE IF NOT EXISTS artifacts (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    bot_id UUID NOT NULL REFERENCES bots(id) ON DELETE CASCADE,
+    kind TEXT NOT NULL CHECK (kind IN ('journal_export', 'diagnostics', 'state_snapshot')),
+    object_key TEXT NOT NULL UNIQUE,
+    content_type TEXT NOT NULL,
+    size_bytes BIGINT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    expires_at TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_artifacts_bot ON artifacts(bot_id, created_at DESC);
+CREATE INDEX IF NOT EXISTS idx_artifacts_expires ON artifacts(expires_at);
+
+-- With storage configured the snapshot body lives in the bucket instead
+ALTER TABLE bot_state_snapshots ALTER COLUMN bundle DROP NOT NULL;
+ALTER TABLE bot_state_snapshots ADD COLUMN IF NOT EXISTS object_key TEXT;
+
+INSERT INTO platform_config (key, value, encrypted, description, category) VALUES
+    ('object_storage_endpoint', '', FALSE, 'S3-compatible endpoint for artifacts (e.g. https://nyc3.digitaloceanspaces.com); unset keeps state snapshots in Postgres', 'storage'),
+    ('object_storage_bucket', '', FALSE, 'Bucket holding bot artifacts', 'storage'),
+    ('object_storage_region', 'us-east-1', FALSE, 'Signing region for the bucket', 'storage'),
+    ('object_storage_access_key', '', FALSE, 'Access key ID for the bucket', 'storage'),
+    ('object_storage_secret_key', '', TRUE, 'Secret access key for the bucket', 'storage')
+ON CONFLICT (key) DO NOTHING;
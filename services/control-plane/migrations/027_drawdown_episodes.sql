@@ -0,0 +1,31 @@
+-- Migration: 027_drawdown_episodes.sql
+-- Purpose: Drawdown episodes detected from the equity series
+-- An episode runs from an equity peak to the first point back at or above
+-- it; recovered_at stays NULL while it's still open. Episodes shallower than
+-- the detector's threshold aren't stored. The cursor table holds each bot's
+-- running peak and trough so detection resumes where the last pass stopped.
+
+CREATE TABLE IF NOT EXISTS drawdown_episodes (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    bot_id UUID NOT NULL REFERENCES bots(id) ON DELETE CASCADE,
+    started_at TIMESTAMPTZ NOT NULL,               -- the peak
+    peak_equity NUMERIC NOT NULL,
+    trough_at TIMESTAMPTZ NOT NULL,
+    trough_equity NUMERIC NOT NULL,
+    recovered_at TIMESTAMPTZ,                      -- NULL while still in drawdown
+    depth_pct NUMERIC NOT NULL,
+    UNIQUE (bot_id, started_at)
+);
+
+CREATE INDEX IF NOT EXISTS idx_drawdown_episodes_bot ON drawdown_episodes(bot_id, started_at DESC);
+
+CREATE TABLE IF NOT EXISTS drawdown_cursors (
+    bot_id UUID PRIMARY KEY REFERENCES bots(id) ON DELETE CASCADE,
+    peak_at TIMESTAMPTZ NOT NULL,
+    peak_equity NUMERIC NOT NULL,
+    trough_at TIMESTAMPTZ NOT NULL,
+    trough_equity NUMERIC NOT NULL,
+    scanned_to TIMESTAMPTZ NOT NULL                -- last metric timestamp fed to the detector
+);
+
+COMMENT ON TABLE drawdown_episodes IS 'Peak-to-recovery drawdowns for shading the equity curve';
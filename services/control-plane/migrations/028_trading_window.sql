@@ -0,0 +1,6 @@
+-- Migration: 028_trading_window.sql
+-- Purpose: Optional trading session window per config version
+-- {"days": ["mon", "tue", "wed", "thu", "fri"], "start": "13:00", "end": "21:00"}
+-- in UTC; outside it the runner opens no new trades. NULL trades around the clock.
+
+ALTER TABLE config_versions ADD COLUMN IF NOT EXISTS trading_window JSONB;
@@ -0,0 +1,6 @@
+-- Migration: 029_metric_fees.sql
+-- Purpose: Cumulative fees (swap fees plus Solana network fees, in USD) the
+-- runner reported with each metric point. NULL for points from runners that
+-- predate fee reporting and for synthetic points.
+
+ALTER TABLE metrics ADD COLUMN IF NOT EXISTS fees_usd DECIMAL(20, 8);
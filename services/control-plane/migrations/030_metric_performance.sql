@@ -0,0 +1,6 @@
+-- Migration: 030_metric_performance.sql
+-- Purpose: Rolling performance statistics (win rate, average win and loss,
+-- 30-day Sharpe ratio, max drawdown) the runner computed when it reported
+-- each metric point. NULL for points from older runners and synthetic points.
+
+ALTER TABLE metrics ADD COLUMN IF NOT EXISTS performance JSONB;
@@ -0,0 +1,7 @@
+-- Migration: 031_state_encryption.sql
+-- Purpose: Opt-in at-rest encryption of the runner's state directory. When
+-- set, the bootstrap secrets include a per-bot key derived from
+-- SECRETS_ENCRYPTION_KEY, so a redeployed droplet gets the same key.
+
+ALTER TABLE bots
+    ADD COLUMN IF NOT EXISTS encrypt_state BOOLEAN NOT NULL DEFAULT FALSE;
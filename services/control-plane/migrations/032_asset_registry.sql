@@ -0,0 +1,9 @@
+-- Migration: 032_asset_registry.sql
+-- Purpose: Curated list of tradable assets that bot asset focuses resolve
+-- against. The value is a JSON list of {symbol, mint, decimals, focus}
+-- entries; while it is empty the control plane's built-in list is used.
+-- Set via PATCH /v1/admin/config.
+
+INSERT INTO platform_config (key, value, encrypted, description, category) VALUES
+    ('asset_registry', '', FALSE, 'Tradable assets as JSON [{symbol, mint, decimals, focus}]; empty uses the built-in list', 'trading')
+ON CONFLICT (key) DO NOTHING;
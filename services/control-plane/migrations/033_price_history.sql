@@ -0,0 +1,19 @@
+-- Migration: 033_price_history.sql
+-- Purpose: Canonical price history. data-retrieval records candles here on a
+-- schedule (and via `data-retrieval backfill`); backtests and charts read
+-- them instead of querying third-party APIs at request time.
+
+CREATE TABLE IF NOT EXISTS price_candles (
+    asset TEXT NOT NULL,
+    quote TEXT NOT NULL,
+    -- 1m, 5m, 15m, 30m, 1h, 4h, 1d, 1w
+    timeframe TEXT NOT NULL,
+    bucket_start TIMESTAMPTZ NOT NULL,
+    open NUMERIC NOT NULL,
+    high NUMERIC NOT NULL,
+    low NUMERIC NOT NULL,
+    close NUMERIC NOT NULL,
+    volume NUMERIC NOT NULL DEFAULT 0,
+    recorded_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (asset, quote, timeframe, bucket_start)
+);
@@ -0,0 +1,16 @@
+-- Migration: 034_symbol_registry.sql
+-- Purpose: data-retrieval's symbol registry overrides. Each row sets how one
+-- symbol is priced (asset class, preferred sources, mint and decimals) and
+-- replaces the built-in or file entry for it. Edited through
+-- data-retrieval's /admin/symbols API.
+
+CREATE TABLE IF NOT EXISTS symbol_registry (
+    symbol TEXT PRIMARY KEY,
+    -- crypto, stock, etf, metal, fx
+    asset_class TEXT NOT NULL,
+    -- Source names to ask; empty asks every source for the class
+    sources TEXT[] NOT NULL DEFAULT '{}',
+    decimals SMALLINT,
+    mint TEXT,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
@@ -0,0 +1,10 @@
+-- Migration: 035_keeper.sql
+-- Purpose: Deadlines for the keeper, which alerts on configs left pending
+-- and bots left provisioning, then marks them failed after
+-- stuck_fail_after_minutes.
+
+INSERT INTO platform_config (key, value, encrypted, description, category) VALUES
+    ('stuck_config_deadline_minutes', '60', FALSE, 'Minutes a config may stay pending before the owner is alerted', 'provisioning'),
+    ('stuck_provisioning_deadline_minutes', '120', FALSE, 'Minutes a bot may stay provisioning before the owner is alerted', 'provisioning'),
+    ('stuck_fail_after_minutes', '720', FALSE, 'Minutes after which a stuck config is marked failed (or a manual bot stuck provisioning marked error)', 'provisioning')
+ON CONFLICT (key) DO NOTHING;
@@ -0,0 +1,16 @@
+-- Migration: 036_user_preferences.sql
+-- Purpose: Per-user defaults for new bots and alert delivery
+-- default_region and default_persona fill in bot creation when unset (NULL
+-- falls back to the platform's droplet_region and a required persona).
+-- notification_channels lists where alerts go; timezone and display_currency
+-- are passed along with each alert for rendering.
+
+CREATE TABLE IF NOT EXISTS user_preferences (
+    user_id UUID PRIMARY KEY REFERENCES users(id) ON DELETE CASCADE,
+    default_region TEXT,
+    default_persona persona,
+    notification_channels TEXT[] NOT NULL DEFAULT ARRAY['webhook'],
+    display_currency TEXT NOT NULL DEFAULT 'USD',
+    timezone TEXT NOT NULL DEFAULT 'UTC',
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
@@ -0,0 +1,21 @@
+-- Migration: 037_llm_usage.sql
+-- Purpose: LLM requests and tokens reported by runners, per bot and day
+-- estimated_cost_usd is computed at ingest from the control plane's price
+-- table; requests for unpriced providers/models are counted in
+-- unpriced_requests and add nothing to the cost.
+
+CREATE TABLE IF NOT EXISTS llm_usage (
+    bot_id UUID NOT NULL REFERENCES bots(id) ON DELETE CASCADE,
+    day DATE NOT NULL,
+    provider TEXT NOT NULL,
+    model TEXT NOT NULL DEFAULT '',
+    requests BIGINT NOT NULL DEFAULT 0,
+    prompt_tokens BIGINT NOT NULL DEFAULT 0,
+    completion_tokens BIGINT NOT NULL DEFAULT 0,
+    estimated_cost_usd NUMERIC(14, 6) NOT NULL DEFAULT 0,
+    unpriced_requests BIGINT NOT NULL DEFAULT 0,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (bot_id, day, provider, model)
+);
+
+CREATE INDEX IF NOT EXISTS idx_llm_usage_day ON llm_usage (day);
@@ -0,0 +1,29 @@
+-- Migration: 038_daily_marks.sql
+-- Purpose: Official end-of-day close per bot and trading day
+-- Taken by the runner at its day rollover and sent in the `day_rollover`
+-- event. The first mark received for a day is the canonical one; later
+-- copies (outbox redelivery) are ignored. `issues` lists what didn't add up
+-- against the intraday metrics when the mark arrived.
+
+CREATE TABLE IF NOT EXISTS daily_marks (
+    bot_id UUID NOT NULL REFERENCES bots(id) ON DELETE CASCADE,
+    day DATE NOT NULL,
+    timezone TEXT NOT NULL,
+    equity_usd NUMERIC(20, 8) NOT NULL,
+    cash_usd NUMERIC(20, 8) NOT NULL,
+    realized_pnl_usd NUMERIC(20, 8) NOT NULL,
+    unrealized_pnl_usd NUMERIC(20, 8) NOT NULL,
+    fees_usd NUMERIC(20, 8) NOT NULL DEFAULT 0,
+    trades INTEGER NOT NULL DEFAULT 0,
+    positions JSONB NOT NULL DEFAULT '[]',
+    prices_marked_at TIMESTAMPTZ NOT NULL,
+    marked_at TIMESTAMPTZ NOT NULL,
+    -- Latest metric at or before marked_at, if one was recent enough
+    intraday_equity_usd NUMERIC(20, 8),
+    issues TEXT[] NOT NULL DEFAULT '{}',
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (bot_id, day)
+);
+
+CREATE INDEX IF NOT EXISTS idx_daily_marks_flagged
+    ON daily_marks (bot_id, day) WHERE cardinality(issues) > 0;
@@ -0,0 +1,17 @@
+-- Migration: 039_sync_receipts.sql
+-- Purpose: One row per sync payload a bot delivered
+-- A runner that loses a control-plane region mid-sync retries the same
+-- payload (same `sync_id`) against the next region. Every region writes to
+-- this database, so the receipt is what keeps the retry from storing the
+-- metrics, events and LLM usage a second time. Pruned after a day.
+
+CREATE TABLE IF NOT EXISTS sync_receipts (
+    bot_id UUID NOT NULL REFERENCES bots(id) ON DELETE CASCADE,
+    sync_id UUID NOT NULL,
+    -- CONTROL_PLANE_REGION of the instance that stored the payload
+    region TEXT,
+    received_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (bot_id, sync_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_sync_receipts_received_at ON sync_receipts (received_at);
@@ -0,0 +1,20 @@
+-- Migration: 040_abuse_events.sql
+-- Purpose: Anti-abuse actions taken on public routes
+-- `subject` is who it was taken against (`ip:<addr>` or `user:<uuid>`).
+-- `kind` is `auth_backoff` (repeated auth failures started slowing an IP
+-- down), `blocked` (a temporary block was placed) or `unblocked` (an admin
+-- lifted one). Blocks themselves are held in memory by each instance.
+
+CREATE TABLE IF NOT EXISTS abuse_events (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    subject TEXT NOT NULL,
+    route_class TEXT NOT NULL,
+    kind TEXT NOT NULL,
+    reason TEXT NOT NULL,
+    blocked_until TIMESTAMPTZ,
+    actor TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_abuse_events_created_at ON abuse_events (created_at DESC);
+CREATE INDEX IF NOT EXISTS idx_abuse_events_subject ON abuse_events (subject, created_at DESC);
@@ -0,0 +1,6 @@
+-- Migration: 041_events_keyset_index.sql
+-- Purpose: Keyset paging of a bot's events
+-- GET /bots/:id/events pages newest first on (created_at, id); the id breaks
+-- ties between events recorded in the same microsecond.
+
+CREATE INDEX IF NOT EXISTS idx_events_bot_created_id ON events (bot_id, created_at DESC, id DESC);
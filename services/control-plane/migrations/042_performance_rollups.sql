@@ -0,0 +1,21 @@
+-- Migration: 042_performance_rollups.sql
+-- Purpose: Daily and weekly performance per bot
+-- Folded hourly from metrics and trade_confirmed events by the rollup task.
+-- `period` is 'day' (UTC) or 'week' (ISO, starting `period_start`, a
+-- Monday). Rows are kept after the raw metrics and events are purged.
+
+CREATE TABLE IF NOT EXISTS performance_rollups (
+    bot_id UUID NOT NULL REFERENCES bots(id) ON DELETE CASCADE,
+    period TEXT NOT NULL CHECK (period IN ('day', 'week')),
+    period_start DATE NOT NULL,
+    start_equity NUMERIC(20, 8) NOT NULL,
+    end_equity NUMERIC(20, 8) NOT NULL,
+    pnl_usd NUMERIC(20, 8) NOT NULL,
+    trade_count INTEGER NOT NULL DEFAULT 0,
+    closed_trades INTEGER NOT NULL DEFAULT 0,
+    wins INTEGER NOT NULL DEFAULT 0,
+    fees_usd NUMERIC(20, 8) NOT NULL DEFAULT 0,
+    max_drawdown_pct NUMERIC(10, 4) NOT NULL DEFAULT 0,
+    computed_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (bot_id, period, period_start)
+);
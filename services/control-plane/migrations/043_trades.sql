@@ -0,0 +1,36 @@
+-- Migration: 043_trades.sql
+-- Purpose: Trade history derived from trade events
+-- One row per bot and intent, filled at ingest: the fill columns from the
+-- intent's trade_confirmed event, the realized columns from its
+-- trade_closed. Either event may arrive first, so every column the other
+-- one sets is nullable. `recorded_at` is the first event's time and orders
+-- GET /bots/:id/trades. Rows are kept after the events are purged.
+
+CREATE TABLE IF NOT EXISTS trades (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    bot_id UUID NOT NULL REFERENCES bots(id) ON DELETE CASCADE,
+    intent_id UUID NOT NULL,
+    side TEXT,
+    mint TEXT,
+    symbol TEXT,
+    input_mint TEXT,
+    output_mint TEXT,
+    quantity NUMERIC(38, 12),
+    notional_usd NUMERIC(20, 8),
+    executed_price NUMERIC(38, 12),
+    signature TEXT,
+    mode TEXT,
+    price_impact_pct DOUBLE PRECISION,
+    slippage_bps DOUBLE PRECISION,
+    fees_usd NUMERIC(20, 8),
+    realized_pnl_usd NUMERIC(20, 8),
+    cost_basis_usd NUMERIC(20, 8),
+    entry_price NUMERIC(38, 12),
+    fully_closed BOOLEAN,
+    executed_at TIMESTAMPTZ,
+    closed_at TIMESTAMPTZ,
+    recorded_at TIMESTAMPTZ NOT NULL,
+    UNIQUE (bot_id, intent_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_trades_bot_recorded ON trades (bot_id, recorded_at DESC, id DESC);
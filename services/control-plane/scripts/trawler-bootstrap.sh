@@ -28,7 +28,6 @@ TOOLCHAIN_EXTRA_APT_PACKAGES="${TOOLCHAIN_EXTRA_APT_PACKAGES:-}"
 WORKSPACE_DIR="${WORKSPACE_DIR:-/opt/trawling-traders}"
 KEYPAIR_DIR="$WORKSPACE_DIR/.config/solana"
 KEYPAIR_PATH="$KEYPAIR_DIR/id.json"
-SECRETS_FILE="$WORKSPACE_DIR/.secrets"
 
 # Downrigger configuration
 DOWNRIGGER_REPO_URL="${DOWNRIGGER_REPO_URL:-https://github.com/janebot2026/downrigger.git}"
@@ -42,12 +41,12 @@ echo "Node.js: v$TOOLCHAIN_NODE_MAJOR LTS"
 echo "Date: $(date)"
 
 # Update system
-echo "=== [1/12] Updating System ==="
+echo "=== [1/10] Updating System ==="
 apt-get update
 apt-get upgrade -y
 
 # Install base dependencies
-echo "=== [2/12] Installing Base Dependencies ==="
+echo "=== [2/10] Installing Base Dependencies ==="
 apt-get install -y \
     curl \
     wget \
@@ -70,7 +69,7 @@ if [ -n "$TOOLCHAIN_EXTRA_APT_PACKAGES" ]; then
 fi
 
 # Install Node.js (modern LTS version)
-echo "=== [3/12] Installing Node.js $TOOLCHAIN_NODE_MAJOR LTS ==="
+echo "=== [3/10] Installing Node.js $TOOLCHAIN_NODE_MAJOR LTS ==="
 if command -v node >/dev/null 2>&1; then
     NODE_MAJOR=$(node -v 2>/dev/null | sed 's/^v\([0-9]*\).*/\1/')
 else
@@ -89,7 +88,7 @@ echo "NPM version: $(npm -v)"
 
 # Install pnpm via corepack (modern package manager)
 if [ "$TOOLCHAIN_INSTALL_PNPM" = "true" ]; then
-    echo "=== [4/12] Installing pnpm via corepack ==="
+    echo "=== [4/10] Installing pnpm via corepack ==="
     corepack enable
     if [ -n "$TOOLCHAIN_PNPM_VERSION" ]; then
         corepack prepare "pnpm@$TOOLCHAIN_PNPM_VERSION" --activate
@@ -98,28 +97,28 @@ if [ "$TOOLCHAIN_INSTALL_PNPM" = "true" ]; then
     fi
     echo "pnpm version: $(pnpm -v)"
 else
-    echo "=== [4/12] Skipping pnpm installation ==="
+    echo "=== [4/10] Skipping pnpm installation ==="
 fi
 
 # Install Rust
 if [ "$TOOLCHAIN_INSTALL_RUST" = "true" ]; then
-    echo "=== [5/12] Installing Rust ($TOOLCHAIN_RUST_TOOLCHAIN) ==="
+    echo "=== [5/10] Installing Rust ($TOOLCHAIN_RUST_TOOLCHAIN) ==="
     curl --proto '=https' --tlsv1.2 -sSf https://sh.rustup.rs | sh -s -- -y --profile minimal --default-toolchain "$TOOLCHAIN_RUST_TOOLCHAIN"
     source "$HOME/.cargo/env"
     echo "Rust version: $(rustc --version)"
     echo "Cargo version: $(cargo --version)"
 else
-    echo "=== [5/12] Skipping Rust installation ==="
+    echo "=== [5/10] Skipping Rust installation ==="
 fi
 
 # Create workspace directory
-echo "=== [6/12] Setting up workspace ==="
+echo "=== [6/10] Setting up workspace ==="
 mkdir -p "$WORKSPACE_DIR"
 mkdir -p "$KEYPAIR_DIR"
 cd "$WORKSPACE_DIR"
 
 # Install downrigger (trading-focused agent setup tool)
-echo "=== [7/12] Installing downrigger ==="
+echo "=== [7/10] Installing downrigger ==="
 DOWNRIGGER_DIR="$WORKSPACE_DIR/tools/downrigger"
 mkdir -p "$(dirname "$DOWNRIGGER_DIR")"
 
@@ -142,7 +141,7 @@ fi
 )
 
 # Run downrigger init
-echo "=== [8/12] Running downrigger init ==="
+echo "=== [8/10] Running downrigger init ==="
 (
     cd "$DOWNRIGGER_DIR"
     node bin/downrigger.js init \
@@ -156,127 +155,34 @@ echo "=== [8/12] Running downrigger init ==="
         --skip-heartbeat
 ) || echo "WARN: downrigger init failed, continuing..."
 
-# Install claw-trader-cli
-echo "=== [9/12] Installing claw-trader-cli ==="
-git clone https://github.com/janebot2026/claw-trader-cli.git "$WORKSPACE_DIR/tools/claw-trader-cli"
-(
-    cd "$WORKSPACE_DIR/tools/claw-trader-cli"
-    source "$HOME/.cargo/env"
-    cargo build --release
-    cp target/release/jup-cli /usr/local/bin/
-    ln -sf /usr/local/bin/jup-cli /usr/local/bin/claw-trader
-)
-
-# Fetch secrets from control plane (one-time bootstrap)
-echo "=== [10/12] Fetching secrets from control plane ==="
-SECRETS_JSON=""
-for i in {1..5}; do
-    SECRETS_JSON=$(curl -sf -X POST "$CONTROL_PLANE_URL/v1/bot/$BOT_ID/secrets" \
-        -H "Content-Type: application/json" \
-        -d "{\"bootstrap_token\":\"$BOOTSTRAP_TOKEN\"}") && break
-    echo "Secrets fetch attempt $i failed, retrying in 5s..."
-    sleep 5
-done
-
-if [ -z "$SECRETS_JSON" ]; then
-    echo "FATAL: Failed to fetch secrets from control plane"
-    exit 1
+# Install bot-runner (re-running pulls and rebuilds in place)
+echo "=== [9/10] Installing bot-runner ==="
+RUNNER_SRC="$WORKSPACE_DIR/trawling-traders"
+if [ ! -d "$RUNNER_SRC/.git" ]; then
+    git clone https://github.com/janebot2026/trawling-traders.git "$RUNNER_SRC"
+else
+    git -C "$RUNNER_SRC" pull --ff-only || echo "WARN: bot-runner pull failed, building existing checkout"
 fi
-
-# Parse and store secrets securely
-JUPITER_API_KEY=$(echo "$SECRETS_JSON" | jq -r '.jupiter_api_key // empty')
-DATA_RETRIEVAL_URL=$(echo "$SECRETS_JSON" | jq -r '.data_retrieval_url // empty')
-SOLANA_RPC_URL=$(echo "$SECRETS_JSON" | jq -r '.solana_rpc_url // empty')
-LLM_PROVIDER=$(echo "$SECRETS_JSON" | jq -r '.llm_provider // empty')
-LLM_MODEL=$(echo "$SECRETS_JSON" | jq -r '.llm_model // empty')
-LLM_API_KEY=$(echo "$SECRETS_JSON" | jq -r '.llm_api_key // empty')
-TELEGRAM_BOT_TOKEN=$(echo "$SECRETS_JSON" | jq -r '.telegram_bot_token // empty')
-
-cat > "$SECRETS_FILE" << EOFSECRETS
-JUPITER_API_KEY=$JUPITER_API_KEY
-DATA_RETRIEVAL_URL=$DATA_RETRIEVAL_URL
-SOLANA_RPC_URL=$SOLANA_RPC_URL
-LLM_PROVIDER=$LLM_PROVIDER
-LLM_MODEL=$LLM_MODEL
-LLM_API_KEY=$LLM_API_KEY
-TELEGRAM_BOT_TOKEN=$TELEGRAM_BOT_TOKEN
-EOFSECRETS
-chmod 600 "$SECRETS_FILE"
-
-# Clear bootstrap token from environment (one-time use)
-unset BOOTSTRAP_TOKEN
-
-# Configure claw-trader-cli
-echo "Configuring claw-trader-cli..."
-mkdir -p "$WORKSPACE_DIR/.config/claw-trader"
-cat > "$WORKSPACE_DIR/.config/claw-trader/config.toml" << EOF
-[api]
-ultra_base_url = "https://api.jup.ag/ultra/v1"
-api_key = "$JUPITER_API_KEY"
-
-[trading]
-default_slippage_bps = 50
-max_slippage_bps = 100
-confirmation_commitment = "confirmed"
-paper_trading_default = true
-
-[agent]
-enabled = true
-auto_approve = false
-EOF
-chmod 600 "$WORKSPACE_DIR/.config/claw-trader/config.toml"
-
-# Install bot-runner
-echo "=== [11/12] Installing bot-runner ==="
-git clone https://github.com/janebot2026/trawling-traders.git "$WORKSPACE_DIR/trawling-traders"
 (
-    cd "$WORKSPACE_DIR/trawling-traders/services/bot-runner"
+    cd "$RUNNER_SRC/services/bot-runner"
     source "$HOME/.cargo/env"
     cargo build --release
     cp target/release/bot-runner /usr/local/bin/
 )
 
-# Create systemd service
-echo "=== [12/12] Creating systemd service ==="
-cat > /etc/systemd/system/bot-runner.service << EOFSERVICE
-[Unit]
-Description=Trawling Traders Bot Runner
-After=network.target
-
-[Service]
-Type=simple
-User=root
-WorkingDirectory=$WORKSPACE_DIR
-EnvironmentFile=$SECRETS_FILE
-Environment="BOT_ID=$BOT_ID"
-Environment="CONTROL_PLANE_URL=$CONTROL_PLANE_URL"
-Environment="RUST_LOG=info"
-Environment="CLAW_TRADER_PATH=/usr/local/bin/claw-trader"
-Environment="CLAW_TRADER_CONFIG=$WORKSPACE_DIR/.config/claw-trader"
-Environment="AGENT_WALLET_PATH=$KEYPAIR_PATH"
-ExecStart=/usr/local/bin/bot-runner
-Restart=always
-RestartSec=10
-StandardOutput=append:/var/log/bot-runner.log
-StandardError=append:/var/log/bot-runner.log
+# Remaining setup (claw-trader, wallet, secrets, systemd) is idempotent and
+# reports each phase to the control plane. After a failure, fix the cause and
+# re-run this command instead of rebuilding the droplet.
+echo "=== [10/10] Running bot-runner bootstrap ==="
+export WORKSPACE_DIR
+export CLAW_TRADER_PATH=/usr/local/bin/claw-trader
+export AGENT_WALLET_PATH="$KEYPAIR_PATH"
+# cargo is needed if claw-trader still has to be built
+[ -f "$HOME/.cargo/env" ] && source "$HOME/.cargo/env"
+/usr/local/bin/bot-runner bootstrap
 
-[Install]
-WantedBy=multi-user.target
-EOFSERVICE
-
-# Prepare log file
-touch /var/log/bot-runner.log
-
-# Register bot with control plane
-echo "Registering bot with control plane..."
-curl -X POST "$CONTROL_PLANE_URL/v1/bot/$BOT_ID/register" \
-    -H "Content-Type: application/json" \
-    -d '{}' || echo "Registration may have failed, bot-runner will retry"
-
-# Enable and start services
-systemctl daemon-reload
-systemctl enable bot-runner
-systemctl start bot-runner
+# Clear bootstrap token from environment (one-time use)
+unset BOOTSTRAP_TOKEN
 
 echo "=== Trawling Traders Bot Setup Complete ==="
 echo "Bot ID: $BOT_ID"
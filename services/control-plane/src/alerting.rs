@@ -1,10 +1,18 @@
 //! Alerting module for threshold-based notifications
+//!
+//! Every alert is recorded into a group keyed by alert type and subject (bot,
+//! droplet or component). Repeats inside the grouping window only bump the
+//! group's counter, and new groups are held back during the owner's quiet
+//! hours or once they hit their hourly cap. Suppressed alerts still count
+//! towards the group, so alert history shows what was held back.
 
+use chrono::{DateTime, Timelike, Utc};
 use rust_decimal::Decimal;
-use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
 use std::sync::Arc;
 use tokio::sync::RwLock;
-use tracing::{error, info, warn};
+use tracing::{debug, error, info, warn};
 
 /// Alert severity levels
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -56,6 +64,10 @@ pub enum AlertType {
         error_rate: f64,
         threshold: f64,
     },
+    /// Tagged droplet with no bot referencing it (destroyed by reconciliation)
+    OrphanedDroplet { droplet_id: i64, name: String },
+    /// Bot claims a droplet the provider no longer has
+    MissingDroplet { bot_id: String, droplet_id: i64 },
 
     /// Bot lifecycle alerts
     BotOffline {
@@ -78,6 +90,279 @@ pub enum AlertType {
         current_dd: Decimal,
         limit: Decimal,
     },
+
+    /// Billing alerts
+    SubscriptionChanged {
+        user_id: String,
+        status: String,
+        detail: String,
+    },
+    /// Month-to-date LLM spend crossed a share of the plan's included budget
+    LlmBudget {
+        user_id: String,
+        spent_usd: Decimal,
+        budget_usd: Decimal,
+        threshold_pct: u32,
+    },
+}
+
+impl AlertType {
+    /// Stable snake_case name of the alert type
+    pub fn kind(&self) -> &'static str {
+        match self {
+            AlertType::DailyLossLimit { .. } => "daily_loss_limit",
+            AlertType::MaxDrawdown { .. } => "max_drawdown",
+            AlertType::PositionSize { .. } => "position_size",
+            AlertType::ProvisionFailure { .. } => "provision_failure",
+            AlertType::OrphanedBot { .. } => "orphaned_bot",
+            AlertType::HighErrorRate { .. } => "high_error_rate",
+            AlertType::OrphanedDroplet { .. } => "orphaned_droplet",
+            AlertType::MissingDroplet { .. } => "missing_droplet",
+            AlertType::BotOffline { .. } => "bot_offline",
+            AlertType::ConfigMismatch { .. } => "config_mismatch",
+            AlertType::RepeatedTradeFailed { .. } => "repeated_trade_failed",
+            AlertType::DrawdownBreach { .. } => "drawdown_breach",
+            AlertType::SubscriptionChanged { .. } => "subscription_changed",
+            AlertType::LlmBudget { .. } => "llm_budget",
+        }
+    }
+
+    /// Bot the alert is about, if any
+    pub fn bot_id(&self) -> Option<&str> {
+        match self {
+            AlertType::DailyLossLimit { bot_id, .. }
+            | AlertType::MaxDrawdown { bot_id, .. }
+            | AlertType::PositionSize { bot_id, .. }
+            | AlertType::ProvisionFailure { bot_id, .. }
+            | AlertType::OrphanedBot { bot_id, .. }
+            | AlertType::MissingDroplet { bot_id, .. }
+            | AlertType::BotOffline { bot_id, .. }
+            | AlertType::ConfigMismatch { bot_id, .. }
+            | AlertType::RepeatedTradeFailed { bot_id, .. }
+            | AlertType::DrawdownBreach { bot_id, .. } => Some(bot_id),
+            AlertType::HighErrorRate { .. }
+            | AlertType::OrphanedDroplet { .. }
+            | AlertType::SubscriptionChanged { .. }
+            | AlertType::LlmBudget { .. } => None,
+        }
+    }
+
+    /// Alerts with the same key collapse into one group
+    pub fn group_key(&self) -> String {
+        let subject = match self {
+            AlertType::HighErrorRate { component, .. } => component.clone(),
+            AlertType::OrphanedDroplet { droplet_id, .. } => droplet_id.to_string(),
+            // Per status, so a recovery isn't folded into the failure before it
+            AlertType::SubscriptionChanged {
+                user_id, status, ..
+            } => format!("{}:{}", user_id, status),
+            AlertType::LlmBudget {
+                user_id,
+                threshold_pct,
+                ..
+            } => format!("{}:{}", user_id, threshold_pct),
+            _ => self.bot_id().unwrap_or_default().to_string(),
+        };
+        alert_key(self.kind(), &subject)
+    }
+
+    /// Title and message for logs and alert history
+    pub fn describe(&self) -> (String, String) {
+        match self {
+            AlertType::DailyLossLimit {
+                bot_id,
+                current_loss,
+                limit,
+            } => (
+                format!("Daily Loss Limit Exceeded [{}]", bot_id),
+                format!("Current: {}%, Limit: {}%", current_loss, limit),
+            ),
+            AlertType::MaxDrawdown {
+                bot_id,
+                current_dd,
+                limit,
+            } => (
+                format!("Max Drawdown Breached [{}]", bot_id),
+                format!("Current: {}%, Limit: {}%", current_dd, limit),
+            ),
+            AlertType::PositionSize {
+                bot_id,
+                current_pct,
+                limit,
+            } => (
+                format!("Position Size Warning [{}]", bot_id),
+                format!("Current: {}%, Limit: {}%", current_pct, limit),
+            ),
+            AlertType::ProvisionFailure { bot_id, attempt } => (
+                format!("Provision Failure [{}]", bot_id),
+                format!("Failed {} times", attempt),
+            ),
+            AlertType::OrphanedBot {
+                bot_id,
+                status,
+                duration_secs,
+            } => (
+                format!("Orphaned Bot [{}]", bot_id),
+                format!("Status: {}, Duration: {}s", status, duration_secs),
+            ),
+            AlertType::HighErrorRate {
+                component,
+                error_rate,
+                threshold,
+            } => (
+                format!("High Error Rate [{}]", component),
+                format!("Current: {}%, Threshold: {}%", error_rate, threshold),
+            ),
+            AlertType::OrphanedDroplet { droplet_id, name } => (
+                format!("Orphaned Droplet [{}]", droplet_id),
+                format!("Destroyed untracked droplet {}", name),
+            ),
+            AlertType::MissingDroplet { bot_id, droplet_id } => (
+                format!("Missing Droplet [{}]", bot_id),
+                format!("Droplet {} no longer exists; bot marked error", droplet_id),
+            ),
+            AlertType::BotOffline {
+                bot_id,
+                last_heartbeat,
+            } => {
+                let last = last_heartbeat
+                    .map(|h| h.to_string())
+                    .unwrap_or_else(|| "unknown".to_string());
+                (
+                    format!("Bot Offline [{}]", bot_id),
+                    format!("Last heartbeat: {}", last),
+                )
+            }
+            AlertType::ConfigMismatch {
+                bot_id,
+                desired,
+                applied,
+            } => (
+                format!("Config Mismatch [{}]", bot_id),
+                format!("Desired: {}, Applied: {}", desired, applied),
+            ),
+            AlertType::RepeatedTradeFailed {
+                bot_id,
+                consecutive_fails,
+            } => (
+                format!("Repeated Trade Failures [{}]", bot_id),
+                format!("{} consecutive failed trades", consecutive_fails),
+            ),
+            AlertType::DrawdownBreach {
+                bot_id,
+                current_dd,
+                limit,
+            } => (
+                format!("Drawdown Breach [{}]", bot_id),
+                format!("Current: {}%, Limit: {}%", current_dd, limit),
+            ),
+            AlertType::SubscriptionChanged { status, detail, .. } => {
+                (format!("Subscription {}", status), detail.clone())
+            }
+            AlertType::LlmBudget {
+                spent_usd,
+                budget_usd,
+                threshold_pct,
+                ..
+            } => (
+                format!("LLM Budget {}% Used", threshold_pct),
+                format!(
+                    "Estimated ${} of ${} included this month",
+                    spent_usd.round_dp(2),
+                    budget_usd
+                ),
+            ),
+        }
+    }
+}
+
+/// Key shared by an alert's cooldown, group and stored row
+pub fn alert_key(kind: &str, subject: &str) -> String {
+    format!("{}:{}", kind, subject)
+}
+
+/// Why a recorded alert was not delivered
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SuppressReason {
+    /// Same type and subject fired within the grouping window
+    Grouped,
+    /// Owner's quiet hours (critical alerts are still delivered)
+    QuietHours,
+    /// Owner already received `max_alerts_per_hour` alerts
+    RateLimited,
+}
+
+/// Outcome of recording an alert
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AlertDecision {
+    Deliver,
+    Suppressed(SuppressReason),
+}
+
+/// Per-user delivery settings (hours are UTC)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct UserAlertSettings {
+    /// Start of quiet hours; wraps past midnight when after `quiet_end_hour`
+    pub quiet_start_hour: Option<u8>,
+    pub quiet_end_hour: Option<u8>,
+    /// Overrides `AlertConfig::max_alerts_per_hour`
+    pub max_alerts_per_hour: Option<u32>,
+}
+
+impl UserAlertSettings {
+    pub fn in_quiet_hours(&self, hour: u32) -> bool {
+        match (self.quiet_start_hour, self.quiet_end_hour) {
+            (Some(start), Some(end)) if start != end => {
+                let (start, end) = (start as u32, end as u32);
+                if start < end {
+                    hour >= start && hour < end
+                } else {
+                    hour >= start || hour < end
+                }
+            }
+            _ => false,
+        }
+    }
+}
+
+/// Alerts of one type and subject collapsed within the grouping window
+#[derive(Debug, Clone, Serialize)]
+pub struct AlertGroup {
+    pub group_key: String,
+    pub alert_type: &'static str,
+    pub bot_id: Option<String>,
+    pub user_id: Option<String>,
+    pub severity: &'static str,
+    pub title: String,
+    /// Message of the most recent occurrence
+    pub message: String,
+    pub first_fired_at: DateTime<Utc>,
+    pub last_fired_at: DateTime<Utc>,
+    /// Total occurrences, delivered or not
+    pub count: u32,
+    pub delivered: u32,
+    pub suppressed: u32,
+    pub last_suppressed_reason: Option<SuppressReason>,
+}
+
+#[derive(sqlx::FromRow)]
+struct AlertSettingsRow {
+    user_id: uuid::Uuid,
+    quiet_start_hour: Option<i16>,
+    quiet_end_hour: Option<i16>,
+    max_alerts_per_hour: Option<i32>,
+}
+
+/// Alert groups and recent deliveries
+#[derive(Debug, Default)]
+struct AlertLedger {
+    /// Groups still inside their window, by group key
+    open: HashMap<String, AlertGroup>,
+    /// Groups whose window passed, oldest first
+    closed: VecDeque<AlertGroup>,
+    /// Delivery times in the last hour per user ("" for platform alerts)
+    deliveries: HashMap<String, VecDeque<DateTime<Utc>>>,
 }
 
 /// Alert configuration thresholds
@@ -95,6 +380,12 @@ pub struct AlertConfig {
     pub error_rate_threshold_pct: f64,
     /// Bot offline threshold (seconds since last heartbeat)
     pub offline_threshold_secs: i64,
+    /// Repeats of the same alert within this window join one group
+    pub group_window_secs: i64,
+    /// Delivered alerts per user per hour (unless the user sets their own)
+    pub max_alerts_per_hour: u32,
+    /// Closed groups kept for alert history
+    pub history_limit: usize,
 }
 
 impl Default for AlertConfig {
@@ -106,6 +397,9 @@ impl Default for AlertConfig {
             provision_failure_threshold: 3,
             error_rate_threshold_pct: 5.0,
             offline_threshold_secs: 300, // 5 minutes
+            group_window_secs: 900,      // 15 minutes
+            max_alerts_per_hour: 20,
+            history_limit: 500,
         }
     }
 }
@@ -118,6 +412,12 @@ pub struct AlertManager {
     alert_state: Arc<RwLock<HashMap<String, AlertState>>>,
     /// Track consecutive trade failures per bot
     trade_failures: Arc<RwLock<HashMap<String, u32>>>,
+    /// Alert groups and delivery counts for dedup and caps
+    ledger: Arc<RwLock<AlertLedger>>,
+    /// Quiet hours and caps by user id
+    user_settings: Arc<RwLock<HashMap<String, UserAlertSettings>>>,
+    /// When set, alerts are persisted to the `alerts` table
+    store: Option<sqlx::PgPool>,
 }
 
 #[derive(Debug, Clone)]
@@ -133,7 +433,223 @@ impl AlertManager {
             config,
             alert_state: Arc::new(RwLock::new(HashMap::new())),
             trade_failures: Arc::new(RwLock::new(HashMap::new())),
+            ledger: Arc::new(RwLock::new(AlertLedger::default())),
+            user_settings: Arc::new(RwLock::new(HashMap::new())),
+            store: None,
+        }
+    }
+
+    /// Persist alerts (and their resolution) to the `alerts` table
+    pub fn with_store(mut self, pool: sqlx::PgPool) -> Self {
+        self.store = Some(pool);
+        self
+    }
+
+    /// Record an alert into its group and decide whether to deliver it
+    pub async fn record(
+        &self,
+        alert: &AlertType,
+        severity: AlertSeverity,
+        user_id: Option<&str>,
+        now: DateTime<Utc>,
+    ) -> AlertDecision {
+        let key = alert.group_key();
+        let (title, message) = alert.describe();
+        let mut ledger = self.ledger.write().await;
+
+        if let Some(group) = ledger.open.get_mut(&key) {
+            if (now - group.last_fired_at).num_seconds() < self.config.group_window_secs {
+                group.count += 1;
+                group.suppressed += 1;
+                group.last_suppressed_reason = Some(SuppressReason::Grouped);
+                group.last_fired_at = now;
+                group.message = message;
+                return AlertDecision::Suppressed(SuppressReason::Grouped);
+            }
+        }
+        if let Some(expired) = ledger.open.remove(&key) {
+            ledger.closed.push_back(expired);
+            while ledger.closed.len() > self.config.history_limit {
+                ledger.closed.pop_front();
+            }
+        }
+
+        let settings = match user_id {
+            Some(user) => self
+                .user_settings
+                .read()
+                .await
+                .get(user)
+                .copied()
+                .unwrap_or_default(),
+            None => UserAlertSettings::default(),
+        };
+        let cap = settings
+            .max_alerts_per_hour
+            .unwrap_or(self.config.max_alerts_per_hour) as usize;
+
+        let recent = ledger
+            .deliveries
+            .entry(user_id.unwrap_or_default().to_string())
+            .or_default();
+        while recent
+            .front()
+            .is_some_and(|t| (now - *t).num_seconds() >= 3600)
+        {
+            recent.pop_front();
         }
+
+        let decision = if severity != AlertSeverity::Critical && settings.in_quiet_hours(now.hour())
+        {
+            AlertDecision::Suppressed(SuppressReason::QuietHours)
+        } else if recent.len() >= cap {
+            AlertDecision::Suppressed(SuppressReason::RateLimited)
+        } else {
+            recent.push_back(now);
+            AlertDecision::Deliver
+        };
+
+        let suppressed_reason = match decision {
+            AlertDecision::Deliver => None,
+            AlertDecision::Suppressed(reason) => Some(reason),
+        };
+        ledger.open.insert(
+            key.clone(),
+            AlertGroup {
+                group_key: key,
+                alert_type: alert.kind(),
+                bot_id: alert.bot_id().map(str::to_string),
+                user_id: user_id.map(str::to_string),
+                severity: severity.as_str(),
+                title,
+                message,
+                first_fired_at: now,
+                last_fired_at: now,
+                count: 1,
+                delivered: u32::from(suppressed_reason.is_none()),
+                suppressed: u32::from(suppressed_reason.is_some()),
+                last_suppressed_reason: suppressed_reason,
+            },
+        );
+        decision
+    }
+
+    /// Record an alert and log it unless it was suppressed
+    pub async fn notify(
+        &self,
+        alert: &AlertType,
+        severity: AlertSeverity,
+        user_id: Option<&str>,
+    ) -> AlertDecision {
+        let decision = self.record(alert, severity, user_id, Utc::now()).await;
+        if let Some(pool) = &self.store {
+            if let Err(e) = store_alert(pool, alert, severity, user_id, decision).await {
+                warn!("Failed to store alert {}: {}", alert.group_key(), e);
+            }
+        }
+        match decision {
+            AlertDecision::Deliver => self.fire_alert(alert, severity).await,
+            AlertDecision::Suppressed(reason) => {
+                debug!("Alert {} suppressed ({:?})", alert.group_key(), reason)
+            }
+        }
+        decision
+    }
+
+    /// Resolve a bot's alerts of the given kinds once their condition clears
+    ///
+    /// Clears cooldowns and closes open groups so the next occurrence fires
+    /// immediately, and marks stored alerts resolved.
+    pub async fn resolve_for_bot(&self, bot_id: &str, kinds: &[&str]) {
+        {
+            let mut state = self.alert_state.write().await;
+            let mut ledger = self.ledger.write().await;
+            for kind in kinds {
+                let key = alert_key(kind, bot_id);
+                state.remove(&key);
+                if let Some(group) = ledger.open.remove(&key) {
+                    ledger.closed.push_back(group);
+                }
+            }
+            while ledger.closed.len() > self.config.history_limit {
+                ledger.closed.pop_front();
+            }
+        }
+
+        let Some(pool) = &self.store else {
+            return;
+        };
+        let Ok(bot_uuid) = uuid::Uuid::parse_str(bot_id) else {
+            return;
+        };
+        let kinds: Vec<String> = kinds.iter().map(|k| k.to_string()).collect();
+        match sqlx::query(
+            "UPDATE alerts SET state = 'resolved', resolved_at = NOW() \
+             WHERE bot_id = $1 AND alert_type = ANY($2) AND state <> 'resolved'",
+        )
+        .bind(bot_uuid)
+        .bind(&kinds)
+        .execute(pool)
+        .await
+        {
+            Ok(r) if r.rows_affected() > 0 => {
+                info!("Resolved {} alert(s) for bot {}", r.rows_affected(), bot_id)
+            }
+            Ok(_) => {}
+            Err(e) => warn!("Failed to resolve alerts for bot {}: {}", bot_id, e),
+        }
+    }
+
+    /// Alert groups, most recently fired first (optionally one user's only)
+    pub async fn history(&self, user_id: Option<&str>) -> Vec<AlertGroup> {
+        let ledger = self.ledger.read().await;
+        let mut groups: Vec<AlertGroup> = ledger
+            .open
+            .values()
+            .chain(ledger.closed.iter())
+            .filter(|g| user_id.is_none() || g.user_id.as_deref() == user_id)
+            .cloned()
+            .collect();
+        groups.sort_by_key(|g| std::cmp::Reverse(g.last_fired_at));
+        groups
+    }
+
+    pub async fn user_settings(&self, user_id: &str) -> UserAlertSettings {
+        self.user_settings
+            .read()
+            .await
+            .get(user_id)
+            .copied()
+            .unwrap_or_default()
+    }
+
+    pub async fn set_user_settings(&self, user_id: &str, settings: UserAlertSettings) {
+        self.user_settings
+            .write()
+            .await
+            .insert(user_id.to_string(), settings);
+    }
+
+    /// Load every user's settings from `alert_settings`
+    pub async fn load_user_settings(&self, pool: &sqlx::PgPool) -> anyhow::Result<usize> {
+        let rows: Vec<AlertSettingsRow> = sqlx::query_as(
+            "SELECT user_id, quiet_start_hour, quiet_end_hour, max_alerts_per_hour FROM alert_settings",
+        )
+        .fetch_all(pool)
+        .await?;
+
+        let mut settings = self.user_settings.write().await;
+        for row in &rows {
+            settings.insert(
+                row.user_id.to_string(),
+                UserAlertSettings {
+                    quiet_start_hour: row.quiet_start_hour.map(|h| h as u8),
+                    quiet_end_hour: row.quiet_end_hour.map(|h| h as u8),
+                    max_alerts_per_hour: row.max_alerts_per_hour.map(|m| m as u32),
+                },
+            );
+        }
+        Ok(rows.len())
     }
 
     /// Check if an alert should fire (rate limiting)
@@ -177,7 +693,7 @@ impl AlertManager {
 
         // Check daily loss
         if daily_pnl < Decimal::ZERO && daily_pnl.abs() >= self.config.daily_loss_threshold_pct {
-            let key = format!("daily_loss:{}", bot_id);
+            let key = alert_key("daily_loss_limit", bot_id);
             if self.should_fire(&key, 3600).await {
                 // 1 hour cooldown
                 alerts.push(AlertType::DailyLossLimit {
@@ -191,7 +707,7 @@ impl AlertManager {
 
         // Check max drawdown
         if max_drawdown >= self.config.max_drawdown_threshold_pct {
-            let key = format!("max_dd:{}", bot_id);
+            let key = alert_key("max_drawdown", bot_id);
             if self.should_fire(&key, 1800).await {
                 // 30 min cooldown
                 alerts.push(AlertType::MaxDrawdown {
@@ -205,7 +721,7 @@ impl AlertManager {
 
         // Check position size
         if position_size_pct >= self.config.position_size_threshold_pct {
-            let key = format!("position_size:{}", bot_id);
+            let key = alert_key("position_size", bot_id);
             if self.should_fire(&key, 600).await {
                 // 10 min cooldown
                 alerts.push(AlertType::PositionSize {
@@ -223,7 +739,7 @@ impl AlertManager {
     /// Check provision failures
     pub async fn check_provision_failure(&self, bot_id: &str, attempt: u32) -> Option<AlertType> {
         if attempt >= self.config.provision_failure_threshold {
-            let key = format!("provision_fail:{}", bot_id);
+            let key = alert_key("provision_failure", bot_id);
             if self.should_fire(&key, 300).await {
                 // 5 min cooldown
                 self.record_fired(key).await;
@@ -245,7 +761,7 @@ impl AlertManager {
         if let Some(last) = last_heartbeat {
             let elapsed = chrono::Utc::now().signed_duration_since(last);
             if elapsed.num_seconds() > self.config.offline_threshold_secs {
-                let key = format!("offline:{}", bot_id);
+                let key = alert_key("bot_offline", bot_id);
                 if self.should_fire(&key, 900).await {
                     // 15 min cooldown
                     self.record_fired(key).await;
@@ -267,7 +783,7 @@ impl AlertManager {
         applied: &str,
     ) -> Option<AlertType> {
         if desired != applied {
-            let key = format!("config_mismatch:{}", bot_id);
+            let key = alert_key("config_mismatch", bot_id);
             if self.should_fire(&key, 1800).await {
                 // 30 min cooldown
                 self.record_fired(key).await;
@@ -283,87 +799,7 @@ impl AlertManager {
 
     /// Fire an alert (logs for now, can extend to webhook/email)
     pub async fn fire_alert(&self, alert: &AlertType, severity: AlertSeverity) {
-        let (title, message) = match alert {
-            AlertType::DailyLossLimit {
-                bot_id,
-                current_loss,
-                limit,
-            } => (
-                format!("Daily Loss Limit Exceeded [{}]", bot_id),
-                format!("Current: {}%, Limit: {}%", current_loss, limit),
-            ),
-            AlertType::MaxDrawdown {
-                bot_id,
-                current_dd,
-                limit,
-            } => (
-                format!("Max Drawdown Breached [{}]", bot_id),
-                format!("Current: {}%, Limit: {}%", current_dd, limit),
-            ),
-            AlertType::PositionSize {
-                bot_id,
-                current_pct,
-                limit,
-            } => (
-                format!("Position Size Warning [{}]", bot_id),
-                format!("Current: {}%, Limit: {}%", current_pct, limit),
-            ),
-            AlertType::ProvisionFailure { bot_id, attempt } => (
-                format!("Provision Failure [{}]", bot_id),
-                format!("Failed {} times", attempt),
-            ),
-            AlertType::OrphanedBot {
-                bot_id,
-                status,
-                duration_secs,
-            } => (
-                format!("Orphaned Bot [{}]", bot_id),
-                format!("Status: {}, Duration: {}s", status, duration_secs),
-            ),
-            AlertType::HighErrorRate {
-                component,
-                error_rate,
-                threshold,
-            } => (
-                format!("High Error Rate [{}]", component),
-                format!("Current: {}%, Threshold: {}%", error_rate, threshold),
-            ),
-            AlertType::BotOffline {
-                bot_id,
-                last_heartbeat,
-            } => {
-                let last = last_heartbeat
-                    .map(|h| h.to_string())
-                    .unwrap_or_else(|| "unknown".to_string());
-                (
-                    format!("Bot Offline [{}]", bot_id),
-                    format!("Last heartbeat: {}", last),
-                )
-            }
-            AlertType::ConfigMismatch {
-                bot_id,
-                desired,
-                applied,
-            } => (
-                format!("Config Mismatch [{}]", bot_id),
-                format!("Desired: {}, Applied: {}", desired, applied),
-            ),
-            AlertType::RepeatedTradeFailed {
-                bot_id,
-                consecutive_fails,
-            } => (
-                format!("Repeated Trade Failures [{}]", bot_id),
-                format!("{} consecutive failed trades", consecutive_fails),
-            ),
-            AlertType::DrawdownBreach {
-                bot_id,
-                current_dd,
-                limit,
-            } => (
-                format!("Drawdown Breach [{}]", bot_id),
-                format!("Current: {}%, Limit: {}%", current_dd, limit),
-            ),
-        };
+        let (title, message) = alert.describe();
 
         match severity {
             AlertSeverity::Info => {
@@ -392,7 +828,7 @@ impl AlertManager {
 
         // Alert on 3+ consecutive failures
         if current_count >= 3 {
-            let key = format!("trade_fails:{}", bot_id);
+            let key = alert_key("repeated_trade_failed", bot_id);
             if self.should_fire(&key, 600).await {
                 // 10 min cooldown
                 self.record_fired(key).await;
@@ -420,6 +856,43 @@ impl AlertManager {
     }
 }
 
+/// Insert an alert, or fold it into the unresolved row with the same key
+async fn store_alert(
+    pool: &sqlx::PgPool,
+    alert: &AlertType,
+    severity: AlertSeverity,
+    user_id: Option<&str>,
+    decision: AlertDecision,
+) -> Result<(), sqlx::Error> {
+    let (title, message) = alert.describe();
+    let parse = |id: Option<&str>| id.and_then(|id| uuid::Uuid::parse_str(id).ok());
+    let suppressed = i32::from(decision != AlertDecision::Deliver);
+
+    sqlx::query(
+        r#"
+        INSERT INTO alerts (group_key, alert_type, bot_id, user_id, severity, title, message, suppressed)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+        ON CONFLICT (group_key) WHERE state <> 'resolved' DO UPDATE SET
+            count = alerts.count + 1,
+            suppressed = alerts.suppressed + EXCLUDED.suppressed,
+            severity = EXCLUDED.severity,
+            message = EXCLUDED.message,
+            last_fired_at = NOW()
+        "#,
+    )
+    .bind(alert.group_key())
+    .bind(alert.kind())
+    .bind(parse(alert.bot_id()))
+    .bind(parse(user_id))
+    .bind(severity.as_str())
+    .bind(title)
+    .bind(message)
+    .bind(suppressed)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
 /// Spawn a background task to periodically check for offline bots
 pub fn spawn_offline_checker(pool: sqlx::PgPool, alert_manager: AlertManager) {
     tokio::spawn(async move {
@@ -429,22 +902,28 @@ pub fn spawn_offline_checker(pool: sqlx::PgPool, alert_manager: AlertManager) {
             interval.tick().await;
 
             // Find bots that haven't heartbeated recently
-            let bots =
-                sqlx::query_as::<_, (uuid::Uuid, Option<chrono::DateTime<chrono::Utc>>, String)>(
-                    "SELECT id, last_heartbeat_at, status FROM bots WHERE status = 'online'",
-                )
-                .fetch_all(&pool)
-                .await;
+            let bots = sqlx::query_as::<
+                _,
+                (
+                    uuid::Uuid,
+                    Option<chrono::DateTime<chrono::Utc>>,
+                    uuid::Uuid,
+                ),
+            >(
+                "SELECT id, last_heartbeat_at, user_id FROM bots WHERE status = 'online'"
+            )
+            .fetch_all(&pool)
+            .await;
 
             match bots {
                 Ok(bots) => {
-                    for (bot_id, last_hb, _status) in bots {
+                    for (bot_id, last_hb, user_id) in bots {
                         if let Some(alert) = alert_manager
                             .check_bot_offline(&bot_id.to_string(), last_hb)
                             .await
                         {
                             alert_manager
-                                .fire_alert(&alert, AlertSeverity::Warning)
+                                .notify(&alert, AlertSeverity::Warning, Some(&user_id.to_string()))
                                 .await;
                         }
                     }
@@ -456,3 +935,133 @@ pub fn spawn_offline_checker(pool: sqlx::PgPool, alert_manager: AlertManager) {
         }
     });
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn offline(bot_id: &str) -> AlertType {
+        AlertType::BotOffline {
+            bot_id: bot_id.to_string(),
+            last_heartbeat: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_groups_repeats_and_caps_per_user() {
+        let manager = AlertManager::new(AlertConfig {
+            max_alerts_per_hour: 2,
+            ..AlertConfig::default()
+        });
+        let t0 = Utc.with_ymd_and_hms(2026, 3, 1, 12, 0, 0).unwrap();
+        let user = Some("user-1");
+
+        assert_eq!(
+            manager
+                .record(&offline("a"), AlertSeverity::Warning, user, t0)
+                .await,
+            AlertDecision::Deliver
+        );
+        // Same bot and type inside the window collapses into the group
+        assert_eq!(
+            manager
+                .record(
+                    &offline("a"),
+                    AlertSeverity::Warning,
+                    user,
+                    t0 + chrono::Duration::minutes(5)
+                )
+                .await,
+            AlertDecision::Suppressed(SuppressReason::Grouped)
+        );
+        assert_eq!(
+            manager
+                .record(&offline("b"), AlertSeverity::Warning, user, t0)
+                .await,
+            AlertDecision::Deliver
+        );
+        // Third distinct alert in the hour hits the cap
+        assert_eq!(
+            manager
+                .record(&offline("c"), AlertSeverity::Warning, user, t0)
+                .await,
+            AlertDecision::Suppressed(SuppressReason::RateLimited)
+        );
+
+        let history = manager.history(user).await;
+        assert_eq!(history.len(), 3);
+        let a = history
+            .iter()
+            .find(|g| g.group_key == "bot_offline:a")
+            .unwrap();
+        assert_eq!((a.count, a.delivered, a.suppressed), (2, 1, 1));
+
+        // After the window a new group starts and the old one stays in history
+        let later = t0 + chrono::Duration::hours(2);
+        assert_eq!(
+            manager
+                .record(&offline("a"), AlertSeverity::Warning, user, later)
+                .await,
+            AlertDecision::Deliver
+        );
+        assert_eq!(manager.history(user).await.len(), 4);
+        assert!(manager.history(Some("user-2")).await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_resolve_reopens_alert() {
+        let manager = AlertManager::new(AlertConfig::default());
+        let stale = Some(Utc::now() - chrono::Duration::hours(1));
+
+        assert!(manager.check_bot_offline("a", stale).await.is_some());
+        // Still in cooldown
+        assert!(manager.check_bot_offline("a", stale).await.is_none());
+        manager
+            .notify(&offline("a"), AlertSeverity::Warning, None)
+            .await;
+
+        // Heartbeat clears the cooldown and closes the group
+        manager.resolve_for_bot("a", &["bot_offline"]).await;
+        assert!(manager.check_bot_offline("a", stale).await.is_some());
+        assert_eq!(
+            manager
+                .notify(&offline("a"), AlertSeverity::Warning, None)
+                .await,
+            AlertDecision::Deliver
+        );
+    }
+
+    #[tokio::test]
+    async fn test_quiet_hours_hold_back_non_critical() {
+        let quiet = UserAlertSettings {
+            quiet_start_hour: Some(22),
+            quiet_end_hour: Some(7),
+            max_alerts_per_hour: None,
+        };
+        assert!(quiet.in_quiet_hours(23) && quiet.in_quiet_hours(3));
+        assert!(!quiet.in_quiet_hours(7) && !quiet.in_quiet_hours(12));
+
+        let manager = AlertManager::new(AlertConfig::default());
+        manager.set_user_settings("user-1", quiet).await;
+        let night = Utc.with_ymd_and_hms(2026, 3, 1, 2, 0, 0).unwrap();
+
+        assert_eq!(
+            manager
+                .record(&offline("a"), AlertSeverity::Warning, Some("user-1"), night)
+                .await,
+            AlertDecision::Suppressed(SuppressReason::QuietHours)
+        );
+        assert_eq!(
+            manager
+                .record(
+                    &offline("b"),
+                    AlertSeverity::Critical,
+                    Some("user-1"),
+                    night
+                )
+                .await,
+            AlertDecision::Deliver
+        );
+    }
+}
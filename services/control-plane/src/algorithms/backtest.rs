@@ -0,0 +1,653 @@
+//! Backtest engine - runs an algorithm over historical candles
+//!
+//! Long-only and one position at a time, like a single-asset bot: a buy
+//! signal opens a position while flat and a sell signal closes it. A signal
+//! on one candle's close fills `latency_secs` later, at a price walked from
+//! the open toward the close of the candle that time falls in. Every fill is
+//! moved against the trade by the slippage (a flat part, price impact that
+//! grows with size, and a share of the candle's range), charged the swap fee
+//! on its notional and a flat network fee. The stop loss
+//! and take profit (from the signal, or the algorithm's percentages) are
+//! checked against each candle's low and high, stop first, and fill at the
+//! level or a gapped open. Signal fills go through the runner's risk rails
+//! in its order (trades per UTC day, drawdown for buys, position size, daily
+//! realized loss); exits never do.
+
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use rust_decimal::Decimal;
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+use super::{Algorithm, Candle, MarketContext, Position, SignalType};
+use crate::models::RiskCaps;
+
+/// Simulation settings besides the algorithm and its history
+#[derive(Debug, Clone)]
+pub struct BacktestConfig {
+    pub symbol: String,
+    pub initial_cash: Decimal,
+    /// Adverse price move applied to every fill
+    pub slippage_bps: u32,
+    /// Charged on each fill's notional (Jupiter platform fee and the like)
+    pub fee_bps: u32,
+    pub risk_caps: RiskCaps,
+    pub execution: ExecutionModel,
+}
+
+/// Frictions on top of the flat slippage and fee; the default is none
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct ExecutionModel {
+    /// From the signal candle's close to the fill
+    pub latency_secs: u32,
+    /// Extra slippage for every $10k of notional
+    pub impact_bps_per_10k: u32,
+    /// Percent of the fill candle's high-low range added as slippage
+    pub volatility_slippage_pct: u32,
+    /// Base plus priority fee per fill
+    pub network_fee_usd: Decimal,
+}
+
+/// Why a simulated fill happened
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FillReason {
+    Signal,
+    StopLoss,
+    TakeProfit,
+}
+
+/// One simulated fill
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct BacktestTrade {
+    pub timestamp: chrono::DateTime<chrono::Utc>,
+    pub side: SignalType,
+    pub reason: FillReason,
+    /// Fill price after slippage
+    pub price: Decimal,
+    pub quantity: Decimal,
+    pub notional_usd: Decimal,
+    /// Swap fee plus network fee
+    pub fee_usd: Decimal,
+    /// What slippage cost against the unslipped price
+    pub slippage_usd: Decimal,
+    /// Round trip PnL net of both fills' fees (sells only)
+    pub realized_pnl_usd: Option<Decimal>,
+}
+
+/// Equity at a candle's close
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct EquityPoint {
+    pub timestamp: chrono::DateTime<chrono::Utc>,
+    pub equity: Decimal,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct BacktestReport {
+    pub initial_cash_usd: Decimal,
+    /// Cash plus any open position at the last close
+    pub final_equity_usd: Decimal,
+    pub total_return_pct: Decimal,
+    pub max_drawdown_pct: Decimal,
+    /// Round trips closed
+    pub closed_trades: i64,
+    pub win_rate: Option<f64>,
+    pub fees_usd: Decimal,
+    pub slippage_usd: Decimal,
+    /// Signal fills the risk caps stopped, by `blocked_by` code
+    pub blocked: BTreeMap<String, i64>,
+    pub trades: Vec<BacktestTrade>,
+    pub equity_curve: Vec<EquityPoint>,
+}
+
+struct Holding {
+    quantity: Decimal,
+    entry_price: Decimal,
+    /// What was paid, fee included
+    cost: Decimal,
+    stop_loss: Option<Decimal>,
+    take_profit: Option<Decimal>,
+}
+
+struct Sim<'a> {
+    config: &'a BacktestConfig,
+    cash: Decimal,
+    holding: Option<Holding>,
+    trades: Vec<BacktestTrade>,
+    blocked: BTreeMap<String, i64>,
+    fees: Decimal,
+    slippage: Decimal,
+    high_water: Decimal,
+    max_drawdown_pct: Decimal,
+    day: Option<NaiveDate>,
+    day_trades: i32,
+    day_realized: Decimal,
+}
+
+impl Sim<'_> {
+    fn bps(&self, bps: u32) -> Decimal {
+        Decimal::from(bps) / Decimal::from(10_000)
+    }
+
+    /// Slippage rate for a fill of `notional` in `candle`
+    fn slippage(&self, candle: &Candle, notional: Decimal) -> Decimal {
+        let execution = &self.config.execution;
+        let impact = Decimal::from(execution.impact_bps_per_10k) * notional / Decimal::from(10_000);
+        let range = if candle.open > Decimal::ZERO {
+            (candle.high - candle.low) / candle.open
+        } else {
+            Decimal::ZERO
+        };
+        self.bps(self.config.slippage_bps)
+            + impact / Decimal::from(10_000)
+            + range * Decimal::from(execution.volatility_slippage_pct) / Decimal::from(100)
+    }
+
+    fn equity(&self, price: Decimal) -> Decimal {
+        self.cash
+            + self
+                .holding
+                .as_ref()
+                .map_or(Decimal::ZERO, |h| h.quantity * price)
+    }
+
+    fn drawdown_pct(&self, equity: Decimal) -> Decimal {
+        if self.high_water <= Decimal::ZERO || equity >= self.high_water {
+            return Decimal::ZERO;
+        }
+        (self.high_water - equity) / self.high_water * Decimal::from(100)
+    }
+
+    fn roll(&mut self, candle: &Candle) {
+        let day = candle.timestamp.date_naive();
+        if self.day != Some(day) {
+            self.day = Some(day);
+            self.day_trades = 0;
+            self.day_realized = Decimal::ZERO;
+        }
+    }
+
+    /// The cap a signal fill at `price` would break, if any
+    fn check_caps(
+        &self,
+        side: SignalType,
+        notional: Decimal,
+        price: Decimal,
+    ) -> Option<&'static str> {
+        let caps = &self.config.risk_caps;
+        let equity = self.equity(price);
+        if self.day_trades >= caps.max_trades_per_day {
+            return Some("max_trades_per_day");
+        }
+        if side == SignalType::Buy {
+            if self.drawdown_pct(equity) > Decimal::from(caps.max_drawdown_percent) {
+                return Some("max_drawdown_percent");
+            }
+            let max_position =
+                equity * Decimal::from(caps.max_position_size_percent) / Decimal::from(100);
+            if notional > max_position {
+                return Some("max_position_size_percent");
+            }
+        }
+        if self.day_realized < -Decimal::from(caps.max_daily_loss_usd) {
+            return Some("max_daily_loss_usd");
+        }
+        None
+    }
+
+    fn buy(
+        &mut self,
+        candle: &Candle,
+        at: Decimal,
+        target_pct: Decimal,
+        levels: (Option<Decimal>, Option<Decimal>),
+    ) {
+        let fee_rate = self.bps(self.config.fee_bps);
+        let network_fee = self.config.execution.network_fee_usd;
+        let notional = (self.equity(at) * target_pct)
+            .min((self.cash - network_fee) / (Decimal::ONE + fee_rate));
+        let price = at * (Decimal::ONE + self.slippage(candle, notional));
+        if notional <= Decimal::ZERO || price <= Decimal::ZERO {
+            return;
+        }
+        if let Some(code) = self.check_caps(SignalType::Buy, notional, at) {
+            *self.blocked.entry(code.to_string()).or_default() += 1;
+            return;
+        }
+        let fee = notional * fee_rate + network_fee;
+        let quantity = notional / price;
+        self.cash -= notional + fee;
+        self.holding = Some(Holding {
+            quantity,
+            entry_price: price,
+            cost: notional + fee,
+            stop_loss: levels.0,
+            take_profit: levels.1,
+        });
+        self.record(BacktestTrade {
+            timestamp: candle.timestamp,
+            side: SignalType::Buy,
+            reason: FillReason::Signal,
+            price,
+            quantity,
+            notional_usd: notional,
+            fee_usd: fee,
+            slippage_usd: (price - at) * quantity,
+            realized_pnl_usd: None,
+        });
+    }
+
+    fn sell(&mut self, candle: &Candle, at: Decimal, reason: FillReason) {
+        let Some(holding) = self.holding.as_ref() else {
+            return;
+        };
+        let price = at * (Decimal::ONE - self.slippage(candle, holding.quantity * at));
+        let notional = holding.quantity * price;
+        if reason == FillReason::Signal {
+            if let Some(code) = self.check_caps(SignalType::Sell, notional, at) {
+                *self.blocked.entry(code.to_string()).or_default() += 1;
+                return;
+            }
+        }
+        let Some(holding) = self.holding.take() else {
+            return;
+        };
+        let fee = notional * self.bps(self.config.fee_bps) + self.config.execution.network_fee_usd;
+        let pnl = notional - fee - holding.cost;
+        self.cash += notional - fee;
+        self.day_realized += pnl;
+        self.record(BacktestTrade {
+            timestamp: candle.timestamp,
+            side: SignalType::Sell,
+            reason,
+            price,
+            quantity: holding.quantity,
+            notional_usd: notional,
+            fee_usd: fee,
+            slippage_usd: (at - price) * holding.quantity,
+            realized_pnl_usd: Some(pnl),
+        });
+    }
+
+    fn record(&mut self, trade: BacktestTrade) {
+        self.fees += trade.fee_usd;
+        self.slippage += trade.slippage_usd;
+        self.day_trades += 1;
+        self.trades.push(trade);
+    }
+
+    /// Stop loss first: with only OHLC there's no telling which came first
+    fn check_exits(&mut self, candle: &Candle) {
+        let Some(holding) = self.holding.as_ref() else {
+            return;
+        };
+        if let Some(stop) = holding.stop_loss.filter(|stop| candle.low <= *stop) {
+            self.sell(candle, stop.min(candle.open), FillReason::StopLoss);
+        } else if let Some(target) = holding.take_profit.filter(|tp| candle.high >= *tp) {
+            self.sell(candle, target.max(candle.open), FillReason::TakeProfit);
+        }
+    }
+}
+
+/// Candle length, from the shortest gap between timestamps
+fn candle_interval(candles: &[Candle]) -> Duration {
+    candles
+        .windows(2)
+        .map(|pair| pair[1].timestamp - pair[0].timestamp)
+        .filter(|gap| *gap > Duration::zero())
+        .min()
+        .unwrap_or_else(|| Duration::hours(1))
+}
+
+/// Price at `at` within `candle`, walked linearly from its open to its close
+fn price_at(candle: &Candle, at: DateTime<Utc>, interval: Duration) -> Decimal {
+    let elapsed = (at - candle.timestamp).num_seconds().max(0);
+    let frac =
+        Decimal::from(elapsed.min(interval.num_seconds())) / Decimal::from(interval.num_seconds());
+    candle.open + (candle.close - candle.open) * frac
+}
+
+/// A signal waiting for its fill time
+struct PendingFill {
+    side: SignalType,
+    target_pct: Decimal,
+    levels: (Option<Decimal>, Option<Decimal>),
+    due: DateTime<Utc>,
+}
+
+/// Run `algorithm` over `candles` (oldest first)
+pub fn run(
+    algorithm: &dyn Algorithm,
+    candles: &[Candle],
+    config: &BacktestConfig,
+) -> BacktestReport {
+    let params = algorithm.parameters();
+    let mut sim = Sim {
+        config,
+        cash: config.initial_cash,
+        holding: None,
+        trades: Vec::new(),
+        blocked: BTreeMap::new(),
+        fees: Decimal::ZERO,
+        slippage: Decimal::ZERO,
+        high_water: config.initial_cash,
+        max_drawdown_pct: Decimal::ZERO,
+        day: None,
+        day_trades: 0,
+        day_realized: Decimal::ZERO,
+    };
+    let interval = candle_interval(candles);
+    let latency = Duration::seconds(config.execution.latency_secs.into());
+    let mut equity_curve = Vec::with_capacity(candles.len());
+    let mut pending: Option<PendingFill> = None;
+
+    for (i, candle) in candles.iter().enumerate() {
+        sim.roll(candle);
+
+        // An earlier signal fills once its latency has passed
+        if let Some(fill) = pending.take() {
+            if fill.due < candle.timestamp + interval {
+                let at = price_at(candle, fill.due, interval);
+                match fill.side {
+                    SignalType::Buy => sim.buy(candle, at, fill.target_pct, fill.levels),
+                    SignalType::Sell => sim.sell(candle, at, FillReason::Signal),
+                    SignalType::Hold => {}
+                }
+            } else {
+                pending = Some(fill);
+            }
+        }
+        sim.check_exits(candle);
+
+        let equity = sim.equity(candle.close);
+        sim.high_water = sim.high_water.max(equity);
+        sim.max_drawdown_pct = sim.max_drawdown_pct.max(sim.drawdown_pct(equity));
+        equity_curve.push(EquityPoint {
+            timestamp: candle.timestamp,
+            equity: equity.round_dp(6),
+        });
+
+        let ctx = MarketContext {
+            symbol: config.symbol.clone(),
+            current_price: candle.close,
+            candles: candles[..=i].to_vec(),
+            position: sim.holding.as_ref().map(|h| Position {
+                symbol: config.symbol.clone(),
+                quantity: h.quantity,
+                entry_price: h.entry_price,
+                unrealized_pnl: h.quantity * candle.close - h.cost,
+            }),
+            portfolio_value: equity,
+            risk_caps: config.risk_caps,
+            // Funding and sentiment aren't recorded with candle history
+            funding: None,
+            sentiment: None,
+        };
+        let signal = algorithm.generate_signal(&ctx);
+        if !signal.is_actionable(params.min_confidence) {
+            continue;
+        }
+        let due = candle.timestamp + interval + latency;
+        pending = match (signal.signal_type, sim.holding.is_some()) {
+            (SignalType::Buy, false) => {
+                // Levels relative to roughly where the buy will fill
+                let entry = candles.get(i + 1).map_or(candle.close, |next| next.open);
+                let stop = match signal.stop_loss {
+                    Some(stop) => stop * entry / candle.close,
+                    None => entry * (Decimal::ONE - params.stop_loss_pct),
+                };
+                let target = match signal.take_profit {
+                    Some(target) => target * entry / candle.close,
+                    None => entry * (Decimal::ONE + params.take_profit_pct),
+                };
+                Some(PendingFill {
+                    side: SignalType::Buy,
+                    target_pct: signal.suggested_position_pct,
+                    levels: (Some(stop), Some(target)),
+                    due,
+                })
+            }
+            (SignalType::Sell, true) => Some(PendingFill {
+                side: SignalType::Sell,
+                target_pct: Decimal::ZERO,
+                levels: (None, None),
+                due,
+            }),
+            _ => None,
+        };
+    }
+
+    let final_equity = candles
+        .last()
+        .map_or(config.initial_cash, |c| sim.equity(c.close));
+    let closes: Vec<Decimal> = sim
+        .trades
+        .iter()
+        .filter_map(|t| t.realized_pnl_usd)
+        .collect();
+    let wins = closes.iter().filter(|pnl| **pnl > Decimal::ZERO).count();
+    BacktestReport {
+        initial_cash_usd: config.initial_cash,
+        final_equity_usd: final_equity.round_dp(2),
+        total_return_pct: if config.initial_cash > Decimal::ZERO {
+            ((final_equity - config.initial_cash) / config.initial_cash * Decimal::from(100))
+                .round_dp(4)
+        } else {
+            Decimal::ZERO
+        },
+        max_drawdown_pct: sim.max_drawdown_pct.round_dp(4),
+        closed_trades: closes.len() as i64,
+        win_rate: (!closes.is_empty()).then(|| wins as f64 / closes.len() as f64),
+        fees_usd: sim.fees.round_dp(6),
+        slippage_usd: sim.slippage.round_dp(6),
+        blocked: sim.blocked,
+        trades: sim
+            .trades
+            .into_iter()
+            .map(|t| BacktestTrade {
+                price: t.price.round_dp(8),
+                quantity: t.quantity.round_dp(8),
+                notional_usd: t.notional_usd.round_dp(6),
+                fee_usd: t.fee_usd.round_dp(6),
+                slippage_usd: t.slippage_usd.round_dp(6),
+                realized_pnl_usd: t.realized_pnl_usd.map(|p| p.round_dp(6)),
+                ..t
+            })
+            .collect(),
+        equity_curve,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithms::{AlgorithmParams, Signal};
+    use crate::models::AlgorithmMode;
+    use chrono::{TimeZone, Utc};
+
+    /// Buys on the candles in `buys`, sells on those in `sells`
+    struct Scripted {
+        buys: Vec<usize>,
+        sells: Vec<usize>,
+    }
+
+    impl Algorithm for Scripted {
+        fn name(&self) -> &str {
+            "Scripted"
+        }
+
+        fn mode(&self) -> AlgorithmMode {
+            AlgorithmMode::Trend
+        }
+
+        fn generate_signal(&self, ctx: &MarketContext) -> Signal {
+            let i = ctx.candles.len() - 1;
+            let (symbol, price, name) = (ctx.symbol.clone(), ctx.current_price, "Scripted".into());
+            if self.buys.contains(&i) {
+                Signal::buy(symbol, price, Decimal::ONE, name, "test".into())
+            } else if self.sells.contains(&i) {
+                Signal::sell(symbol, price, Decimal::ONE, name, "test".into())
+            } else {
+                Signal::hold(symbol, price, name)
+            }
+        }
+
+        fn parameters(&self) -> AlgorithmParams {
+            AlgorithmParams::default()
+        }
+
+        fn update_parameters(&mut self, _params: AlgorithmParams) {}
+    }
+
+    fn candle(hour: i64, open: i64, high: i64, low: i64, close: i64) -> Candle {
+        Candle {
+            timestamp: Utc.timestamp_opt(hour * 3600, 0).unwrap(),
+            open: Decimal::from(open),
+            high: Decimal::from(high),
+            low: Decimal::from(low),
+            close: Decimal::from(close),
+            volume: Decimal::ONE,
+        }
+    }
+
+    #[test]
+    fn test_fills_exits_and_caps() {
+        let candles = [
+            candle(0, 100, 100, 100, 100),
+            candle(1, 100, 105, 99, 104),
+            candle(2, 104, 106, 103, 105),
+            candle(3, 110, 110, 108, 110),
+            // Drops through the 5% stop under the second entry
+            candle(4, 110, 111, 90, 95),
+            candle(5, 95, 96, 94, 95),
+        ];
+        let config = BacktestConfig {
+            symbol: "SOL".into(),
+            initial_cash: Decimal::from(10_000),
+            slippage_bps: 100,
+            fee_bps: 10,
+            risk_caps: RiskCaps {
+                max_position_size_percent: 10,
+                ..RiskCaps::default()
+            },
+            execution: ExecutionModel::default(),
+        };
+        let algo = Scripted {
+            buys: vec![0, 3],
+            sells: vec![2],
+        };
+        let report = run(&algo, &candles, &config);
+
+        // Buy $1000 at 100 + 1% on candle 1, sell at 110 - 1% on candle 3
+        let buy = &report.trades[0];
+        assert_eq!(
+            (buy.side, buy.reason),
+            (SignalType::Buy, FillReason::Signal)
+        );
+        assert_eq!(buy.price, Decimal::from(101));
+        assert_eq!(buy.notional_usd, Decimal::from(1000));
+        assert_eq!(buy.fee_usd, Decimal::ONE);
+        let sell = &report.trades[1];
+        assert_eq!(sell.price, Decimal::new(1089, 1));
+        assert!(sell.realized_pnl_usd.unwrap() > Decimal::ZERO);
+
+        // Re-entry signalled on candle 3 fills at candle 4's open, where the
+        // stop (5% under the fill) is hit in the same candle
+        let stop = &report.trades[3];
+        assert_eq!(stop.reason, FillReason::StopLoss);
+        assert!(stop.realized_pnl_usd.unwrap() < Decimal::ZERO);
+        assert_eq!(report.closed_trades, 2);
+        assert_eq!(report.win_rate, Some(0.5));
+        assert_eq!(report.equity_curve.len(), candles.len());
+        assert!(report.blocked.is_empty());
+
+        // One trade a day blocks the signal sell, but the take profit at 110
+        // still exits in the same candle; the re-entry is blocked too
+        let capped = BacktestConfig {
+            risk_caps: RiskCaps {
+                max_trades_per_day: 1,
+                ..config.risk_caps
+            },
+            ..config.clone()
+        };
+        let report = run(&algo, &candles, &capped);
+        assert_eq!(report.trades.len(), 2);
+        assert_eq!(report.trades[1].reason, FillReason::TakeProfit);
+        assert_eq!(report.blocked.get("max_trades_per_day"), Some(&2));
+
+        // A 5% position cap blocks the 10% buys outright
+        let capped = BacktestConfig {
+            risk_caps: RiskCaps {
+                max_position_size_percent: 5,
+                ..config.risk_caps
+            },
+            ..config
+        };
+        let report = run(&algo, &candles, &capped);
+        assert!(report.trades.is_empty());
+        assert_eq!(report.blocked.get("max_position_size_percent"), Some(&2));
+    }
+
+    #[test]
+    fn test_execution_model() {
+        let candles = [
+            candle(0, 100, 100, 100, 100),
+            candle(1, 100, 120, 100, 110),
+            candle(2, 110, 110, 110, 110),
+            candle(3, 130, 130, 130, 130),
+        ];
+        let config = BacktestConfig {
+            symbol: "SOL".into(),
+            initial_cash: Decimal::from(10_000),
+            slippage_bps: 0,
+            fee_bps: 0,
+            risk_caps: RiskCaps {
+                max_position_size_percent: 10,
+                ..RiskCaps::default()
+            },
+            execution: ExecutionModel {
+                latency_secs: 1800,
+                impact_bps_per_10k: 0,
+                volatility_slippage_pct: 10,
+                network_fee_usd: Decimal::ONE,
+            },
+        };
+        let algo = Scripted {
+            buys: vec![0],
+            sells: vec![],
+        };
+
+        // Half a candle late: halfway from 100 to 110, plus 10% of the 20%
+        // range as slippage
+        let report = run(&algo, &candles, &config);
+        let buy = &report.trades[0];
+        assert_eq!(buy.timestamp, candles[1].timestamp);
+        assert_eq!(buy.price, Decimal::new(1071, 1));
+        assert_eq!(buy.fee_usd, Decimal::ONE);
+        assert!(buy.slippage_usd > Decimal::ZERO);
+        assert_eq!(
+            report.slippage_usd,
+            report
+                .trades
+                .iter()
+                .map(|t| t.slippage_usd)
+                .sum::<Decimal>()
+        );
+
+        // Two candles late fills at candle 3's open; 100 bps per $10k of
+        // impact on the $1000 notional is 0.1%
+        let late = BacktestConfig {
+            execution: ExecutionModel {
+                latency_secs: 7200,
+                impact_bps_per_10k: 100,
+                volatility_slippage_pct: 0,
+                network_fee_usd: Decimal::ZERO,
+            },
+            ..config
+        };
+        let report = run(&algo, &candles, &late);
+        let buy = &report.trades[0];
+        assert_eq!(buy.timestamp, candles[3].timestamp);
+        assert_eq!(buy.price, Decimal::new(13013, 2));
+        assert_eq!(buy.fee_usd, Decimal::ZERO);
+    }
+}
@@ -167,8 +167,9 @@ impl Algorithm for MeanReversionAlgorithm {
 
         // Oversold condition - Buy signal
         if rsi < oversold {
-            let confidence =
-                (oversold - rsi) / oversold * (Decimal::ONE + distance_factor) / Decimal::from(2);
+            let confidence = (oversold - rsi) / oversold * (Decimal::ONE + distance_factor)
+                / Decimal::from(2)
+                * ctx.tilt(&self.params, true);
 
             let stop_loss = price * (Decimal::ONE - self.params.stop_loss_pct);
             let take_profit = price * (Decimal::ONE + self.params.take_profit_pct);
@@ -189,14 +190,19 @@ impl Algorithm for MeanReversionAlgorithm {
             .with_take_profit(take_profit)
             .with_position_size(self.params.max_position_pct)
             .with_metadata("rsi", serde_json::json!(rsi))
-            .with_metadata("bollinger", serde_json::json!(bollinger));
+            .with_metadata("bollinger", serde_json::json!(bollinger))
+            .with_metadata(
+                "funding_rate",
+                serde_json::json!(ctx.funding.map(|f| f.rate)),
+            );
         }
 
         // Overbought condition - Sell signal
         if rsi > overbought {
             let confidence = (rsi - overbought) / (Decimal::from(100) - overbought)
                 * (Decimal::ONE + distance_factor)
-                / Decimal::from(2);
+                / Decimal::from(2)
+                * ctx.tilt(&self.params, false);
 
             return Signal::sell(
                 ctx.symbol.clone(),
@@ -211,7 +217,11 @@ impl Algorithm for MeanReversionAlgorithm {
                 ),
             )
             .with_metadata("rsi", serde_json::json!(rsi))
-            .with_metadata("bollinger", serde_json::json!(bollinger));
+            .with_metadata("bollinger", serde_json::json!(bollinger))
+            .with_metadata(
+                "funding_rate",
+                serde_json::json!(ctx.funding.map(|f| f.rate)),
+            );
         }
 
         // No extreme condition - hold
@@ -226,3 +236,76 @@ impl Algorithm for MeanReversionAlgorithm {
         self.params = params;
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithms::{Funding, SignalType};
+    use crate::models::RiskCaps;
+    use chrono::{TimeZone, Utc};
+
+    fn falling(funding: Option<Funding>) -> MarketContext {
+        let candles: Vec<Candle> = (0..30)
+            .map(|i| {
+                let close = Decimal::from(200 - i * 2);
+                Candle {
+                    timestamp: Utc.timestamp_opt(i * 3600, 0).unwrap(),
+                    open: close + Decimal::ONE,
+                    high: close + Decimal::TWO,
+                    low: close,
+                    close,
+                    volume: Decimal::ONE,
+                }
+            })
+            .collect();
+        MarketContext {
+            symbol: "SOL".to_string(),
+            current_price: candles.last().unwrap().close,
+            candles,
+            position: None,
+            portfolio_value: Decimal::from(10_000),
+            risk_caps: RiskCaps::default(),
+            funding,
+            sentiment: None,
+        }
+    }
+
+    #[test]
+    fn test_funding_tilts_confidence() {
+        let algorithm = MeanReversionAlgorithm::new(AlgorithmParams::default());
+        let plain = algorithm.generate_signal(&falling(None));
+        assert_eq!(plain.signal_type, SignalType::Buy);
+
+        // Buying an oversold asset while shorts pay to stay short
+        let against = algorithm.generate_signal(&falling(Some(Funding { rate: -0.0005 })));
+        // Buying while longs already pay to stay long
+        let with = algorithm.generate_signal(&falling(Some(Funding { rate: 0.0005 })));
+        let neutral = algorithm.generate_signal(&falling(Some(Funding { rate: 0.0001 })));
+
+        assert!(against.confidence > plain.confidence);
+        assert!(with.confidence < plain.confidence);
+        assert_eq!(neutral.confidence, plain.confidence);
+    }
+
+    #[test]
+    fn test_sentiment_only_counts_with_a_weight() {
+        let plain = MeanReversionAlgorithm::new(AlgorithmParams::default());
+        let mut bullish = falling(None);
+        bullish.sentiment = Some(0.5);
+        assert_eq!(
+            plain.generate_signal(&bullish).confidence,
+            plain.generate_signal(&falling(None)).confidence
+        );
+
+        let weighted = MeanReversionAlgorithm::new(AlgorithmParams {
+            extra: serde_json::json!({"sentiment_weight": 0.2}),
+            ..AlgorithmParams::default()
+        });
+        let base = weighted.generate_signal(&falling(None)).confidence;
+        // A buy with sentiment at 0.5 and weight 0.2 gains 10%
+        assert_eq!(
+            weighted.generate_signal(&bullish).confidence,
+            base * Decimal::new(11, 1)
+        );
+    }
+}
@@ -7,8 +7,10 @@ use crate::models::{AlgorithmMode, Persona, RiskCaps, Strictness};
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 
+pub mod backtest;
 pub mod breakout;
 pub mod mean_reversion;
+pub mod monte_carlo;
 pub mod signal;
 pub mod trend;
 
@@ -50,6 +52,57 @@ pub struct MarketContext {
     pub portfolio_value: Decimal,
     /// Risk configuration
     pub risk_caps: RiskCaps,
+    /// Perpetual funding for the asset, when data-retrieval has it
+    pub funding: Option<Funding>,
+    /// Sentiment from -1 (bearish) to 1 (bullish), when data-retrieval has it
+    pub sentiment: Option<f64>,
+}
+
+impl MarketContext {
+    /// Confidence multiplier for a signal on the given side from funding
+    /// and, for params with a `sentiment_weight` (QuantLite), sentiment
+    pub fn tilt(&self, params: &AlgorithmParams, buying: bool) -> Decimal {
+        let mut tilt = self.funding.map_or(Decimal::ONE, |f| f.tilt(buying));
+        let weight = params
+            .extra
+            .get("sentiment_weight")
+            .and_then(|v| v.as_f64())
+            .unwrap_or(0.0);
+        if let Some(score) = self.sentiment.filter(|_| weight > 0.0) {
+            let lean = if buying { score } else { -score }.clamp(-1.0, 1.0);
+            let factor = Decimal::try_from((1.0 + weight * lean).max(0.0)).unwrap_or(Decimal::ONE);
+            tilt *= factor.round_dp(4);
+        }
+        tilt
+    }
+}
+
+/// Funding paid on the asset's perpetual, read as a positioning signal
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Funding {
+    /// Last funding rate per 8h period, as a fraction (0.0001 = 0.01%)
+    pub rate: f64,
+}
+
+impl Funding {
+    /// Rate beyond which one side counts as crowded (matches data-retrieval)
+    pub const CROWDED_RATE: f64 = 0.0003;
+
+    /// Confidence multiplier for a signal on the given side
+    ///
+    /// Joining the crowded side means paying funding into a squeeze, so it
+    /// is discounted; leaning against it gets a small boost.
+    pub fn tilt(&self, buying: bool) -> Decimal {
+        let crowded_long = self.rate >= Self::CROWDED_RATE;
+        let crowded_short = self.rate <= -Self::CROWDED_RATE;
+        if (buying && crowded_long) || (!buying && crowded_short) {
+            Decimal::new(8, 1)
+        } else if crowded_long || crowded_short {
+            Decimal::new(11, 1)
+        } else {
+            Decimal::ONE
+        }
+    }
 }
 
 /// Price candle data
@@ -130,7 +183,7 @@ impl AlgorithmFactory {
     }
 
     /// Get default parameters for a persona
-    fn params_for_persona(
+    pub fn params_for_persona(
         persona: Persona,
         strictness: Strictness,
         risk_caps: &RiskCaps,
@@ -201,6 +254,7 @@ impl AlgorithmFactory {
                 "reversion_rsi_oversold": 20,
                 "reversion_rsi_overbought": 80,
                 "breakout_volume_threshold": 1.2,
+                "sentiment_weight": 0.2,
                 "custom_indicators": [],
                 "multi_timeframe": false,
             }),
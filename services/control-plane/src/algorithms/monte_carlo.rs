@@ -0,0 +1,177 @@
+//! Monte Carlo robustness - how much of a backtest result is luck
+//!
+//! One backtest is one ordering of one set of trades. Bootstrapping resamples
+//! the closed round trips (with replacement, as returns on the equity they
+//! were taken with) into many alternative sequences of the same length, and
+//! reports the spread of terminal equity and max drawdown across them. A
+//! strategy whose P5 drawdown is far worse than the backtest's own got a
+//! kind ordering.
+//!
+//! Drawdown is measured trade to trade, so it misses dips inside a trade;
+//! compare it against other bootstraps rather than the backtest's
+//! candle-by-candle figure.
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+use serde::Serialize;
+
+use super::backtest::BacktestReport;
+
+/// Bootstrapped sequences when the request doesn't say
+pub const DEFAULT_RUNS: usize = 1000;
+/// Most sequences one analysis may run
+pub const MAX_RUNS: usize = 10_000;
+
+/// 5th, 50th and 95th percentile of a distribution
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Percentiles {
+    pub p5: Decimal,
+    pub p50: Decimal,
+    pub p95: Decimal,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MonteCarloReport {
+    pub runs: usize,
+    /// Round trips each sequence draws (the backtest's closed trades)
+    pub trades_per_run: usize,
+    pub seed: u64,
+    pub terminal_equity_usd: Percentiles,
+    pub max_drawdown_pct: Percentiles,
+    /// Share of sequences ending below the initial cash
+    pub probability_of_loss: f64,
+}
+
+/// Returns of the closed round trips, each on the equity before it closed
+fn trade_returns(report: &BacktestReport) -> Vec<f64> {
+    let mut equity = report.initial_cash_usd.to_f64().unwrap_or(0.0);
+    let mut returns = Vec::new();
+    for pnl in report.trades.iter().filter_map(|t| t.realized_pnl_usd) {
+        let pnl = pnl.to_f64().unwrap_or(0.0);
+        if equity > 0.0 {
+            returns.push(pnl / equity);
+        }
+        equity += pnl;
+    }
+    returns
+}
+
+/// Nearest-rank percentile of sorted values
+fn percentile(sorted: &[f64], pct: usize) -> f64 {
+    let rank = (sorted.len() * pct).div_ceil(100).max(1);
+    sorted[rank - 1]
+}
+
+fn percentiles(mut values: Vec<f64>, dp: u32) -> Percentiles {
+    values.sort_by(f64::total_cmp);
+    let at = |pct| {
+        Decimal::from_f64_retain(percentile(&values, pct))
+            .unwrap_or_default()
+            .round_dp(dp)
+    };
+    Percentiles {
+        p5: at(5),
+        p50: at(50),
+        p95: at(95),
+    }
+}
+
+/// Bootstrap `runs` trade sequences from a backtest
+///
+/// None when the backtest closed no trades. The same `seed` gives the same
+/// result.
+pub fn bootstrap(report: &BacktestReport, runs: usize, seed: u64) -> Option<MonteCarloReport> {
+    let returns = trade_returns(report);
+    if returns.is_empty() || runs == 0 {
+        return None;
+    }
+    let initial = report.initial_cash_usd.to_f64().unwrap_or(0.0);
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut terminal = Vec::with_capacity(runs);
+    let mut drawdowns = Vec::with_capacity(runs);
+
+    for _ in 0..runs {
+        let (mut equity, mut high_water, mut max_drawdown) = (initial, initial, 0.0_f64);
+        for _ in 0..returns.len() {
+            equity = (equity * (1.0 + returns[rng.gen_range(0..returns.len())])).max(0.0);
+            high_water = high_water.max(equity);
+            if high_water > 0.0 {
+                max_drawdown = max_drawdown.max((high_water - equity) / high_water * 100.0);
+            }
+        }
+        terminal.push(equity);
+        drawdowns.push(max_drawdown);
+    }
+
+    let losses = terminal.iter().filter(|equity| **equity < initial).count();
+    Some(MonteCarloReport {
+        runs,
+        trades_per_run: returns.len(),
+        seed,
+        terminal_equity_usd: percentiles(terminal, 2),
+        max_drawdown_pct: percentiles(drawdowns, 4),
+        probability_of_loss: losses as f64 / runs as f64,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithms::backtest::{BacktestTrade, FillReason};
+    use crate::algorithms::SignalType;
+    use std::collections::BTreeMap;
+
+    fn report(pnls: &[i64]) -> BacktestReport {
+        let trades = pnls
+            .iter()
+            .map(|pnl| BacktestTrade {
+                timestamp: chrono::Utc::now(),
+                side: SignalType::Sell,
+                reason: FillReason::Signal,
+                price: Decimal::ONE,
+                quantity: Decimal::ONE,
+                notional_usd: Decimal::ONE,
+                fee_usd: Decimal::ZERO,
+                slippage_usd: Decimal::ZERO,
+                realized_pnl_usd: Some(Decimal::from(*pnl)),
+            })
+            .collect();
+        BacktestReport {
+            initial_cash_usd: Decimal::from(1000),
+            final_equity_usd: Decimal::from(1000 + pnls.iter().sum::<i64>()),
+            total_return_pct: Decimal::ZERO,
+            max_drawdown_pct: Decimal::ZERO,
+            closed_trades: pnls.len() as i64,
+            win_rate: None,
+            fees_usd: Decimal::ZERO,
+            slippage_usd: Decimal::ZERO,
+            blocked: BTreeMap::new(),
+            trades,
+            equity_curve: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_bootstrap_distribution() {
+        assert!(bootstrap(&report(&[]), 100, 1).is_none());
+
+        // Every trade +10%: every sequence is the same
+        let winners = bootstrap(&report(&[100, 110, 121]), 200, 7).unwrap();
+        assert_eq!(winners.trades_per_run, 3);
+        assert_eq!(winners.terminal_equity_usd.p5, Decimal::new(1331, 0));
+        assert_eq!(winners.terminal_equity_usd.p95, Decimal::new(1331, 0));
+        assert_eq!(winners.max_drawdown_pct.p95, Decimal::ZERO);
+        assert_eq!(winners.probability_of_loss, 0.0);
+
+        // A mix spreads out, ordered P5 <= P50 <= P95, and a seed repeats
+        let mixed = report(&[100, -200, 50, -30, 80, 40]);
+        let first = bootstrap(&mixed, 500, 42).unwrap();
+        let equity = &first.terminal_equity_usd;
+        assert!(equity.p5 < equity.p50 && equity.p50 < equity.p95);
+        assert!(first.max_drawdown_pct.p95 > first.max_drawdown_pct.p5);
+        assert!(first.probability_of_loss > 0.0 && first.probability_of_loss < 1.0);
+        assert_eq!(bootstrap(&mixed, 500, 42).unwrap(), first);
+    }
+}
@@ -190,6 +190,7 @@ impl Algorithm for TrendFollowingAlgorithm {
         // Bullish crossover: fast crosses above slow
         if let (Some(pf), Some(ps)) = (prev_fast, prev_slow) {
             if ema_fast > ema_slow && pf <= ps {
+                let confidence = (confidence * ctx.tilt(&self.params, true)).min(Decimal::ONE);
                 let stop_loss = ctx.current_price * (Decimal::ONE - self.params.stop_loss_pct);
                 let take_profit = ctx.current_price * (Decimal::ONE + self.params.take_profit_pct);
 
@@ -210,11 +211,16 @@ impl Algorithm for TrendFollowingAlgorithm {
                 .with_position_size(self.params.max_position_pct)
                 .with_metadata("ema_fast", serde_json::json!(ema_fast))
                 .with_metadata("ema_slow", serde_json::json!(ema_slow))
-                .with_metadata("adx", serde_json::json!(adx));
+                .with_metadata("adx", serde_json::json!(adx))
+                .with_metadata(
+                    "funding_rate",
+                    serde_json::json!(ctx.funding.map(|f| f.rate)),
+                );
             }
 
             // Bearish crossover: fast crosses below slow
             if ema_fast < ema_slow && pf >= ps {
+                let confidence = (confidence * ctx.tilt(&self.params, false)).min(Decimal::ONE);
                 return Signal::sell(
                     ctx.symbol.clone(),
                     ctx.current_price,
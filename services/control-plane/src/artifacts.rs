@@ -0,0 +1,202 @@
+//! Bot artifacts kept in object storage
+//!
+//! Each artifact row points at an object in the bucket (see
+//! [`crate::storage`]) and carries an `expires_at` taken from its kind's
+//! lifecycle policy. Owners list them and get presigned download URLs from
+//! `/bots/:id/artifacts`; the lifecycle task deletes expired objects along
+//! with their rows.
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use tracing::{error, info, warn};
+use uuid::Uuid;
+
+use crate::secrets::SecretsManager;
+use crate::storage::ObjectStore;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ArtifactKind {
+    /// Decision journal exported on request
+    JournalExport,
+    /// Runner diagnostics uploaded after `upload_diagnostics`
+    Diagnostics,
+    /// Runner state carried across a redeploy
+    StateSnapshot,
+}
+
+/// How long each kind is kept before the lifecycle task removes it
+pub const LIFECYCLE: &[(ArtifactKind, i64)] = &[
+    (ArtifactKind::JournalExport, 7),
+    (ArtifactKind::Diagnostics, 14),
+    (ArtifactKind::StateSnapshot, 30),
+];
+
+impl ArtifactKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ArtifactKind::JournalExport => "journal_export",
+            ArtifactKind::Diagnostics => "diagnostics",
+            ArtifactKind::StateSnapshot => "state_snapshot",
+        }
+    }
+
+    pub fn retention_days(&self) -> i64 {
+        LIFECYCLE
+            .iter()
+            .find(|(kind, _)| kind == self)
+            .map(|(_, days)| *days)
+            .unwrap_or(7)
+    }
+
+    /// Object key for a new artifact: `bots/{bot_id}/{kind}/{timestamp}-{id}.{ext}`
+    pub fn object_key(&self, bot_id: Uuid, id: Uuid, now: DateTime<Utc>, ext: &str) -> String {
+        format!(
+            "bots/{}/{}/{}-{}.{}",
+            bot_id,
+            self.as_str(),
+            now.format("%Y%m%dT%H%M%SZ"),
+            id,
+            ext
+        )
+    }
+}
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct Artifact {
+    pub id: Uuid,
+    pub bot_id: Uuid,
+    pub kind: String,
+    #[serde(skip_serializing)]
+    pub object_key: String,
+    pub content_type: String,
+    pub size_bytes: i64,
+    pub created_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+}
+
+/// Upload `body` and record it as an artifact of `bot_id`
+pub async fn store(
+    pool: &sqlx::PgPool,
+    store: &ObjectStore,
+    bot_id: Uuid,
+    kind: ArtifactKind,
+    ext: &str,
+    content_type: &str,
+    body: Vec<u8>,
+) -> anyhow::Result<Artifact> {
+    let id = Uuid::new_v4();
+    let now = Utc::now();
+    let object_key = kind.object_key(bot_id, id, now, ext);
+    let size_bytes = body.len() as i64;
+    store.put(&object_key, body, content_type).await?;
+
+    let artifact = sqlx::query_as::<_, Artifact>(
+        "INSERT INTO artifacts (id, bot_id, kind, object_key, content_type, size_bytes, created_at, expires_at) \
+         VALUES ($1, $2, $3, $4, $5, $6, $7, $8) RETURNING *",
+    )
+    .bind(id)
+    .bind(bot_id)
+    .bind(kind.as_str())
+    .bind(&object_key)
+    .bind(content_type)
+    .bind(size_bytes)
+    .bind(now)
+    .bind(now + Duration::days(kind.retention_days()))
+    .fetch_one(pool)
+    .await;
+    match artifact {
+        Ok(artifact) => Ok(artifact),
+        Err(e) => {
+            // Don't leave an object nothing points at
+            if let Err(cleanup) = store.delete(&object_key).await {
+                warn!(
+                    "Failed to remove unrecorded object {}: {}",
+                    object_key, cleanup
+                );
+            }
+            Err(e.into())
+        }
+    }
+}
+
+/// Delete up to `limit` expired artifacts; returns how many were removed
+///
+/// Rows are only dropped once their object is gone, so a failed delete is
+/// retried on the next pass.
+pub async fn expire_artifacts(
+    pool: &sqlx::PgPool,
+    store: &ObjectStore,
+    limit: i64,
+) -> anyhow::Result<usize> {
+    let expired: Vec<(Uuid, String)> = sqlx::query_as(
+        "SELECT id, object_key FROM artifacts WHERE expires_at <= NOW() ORDER BY expires_at LIMIT $1",
+    )
+    .bind(limit)
+    .fetch_all(pool)
+    .await?;
+
+    let mut removed = Vec::with_capacity(expired.len());
+    for (id, object_key) in expired {
+        match store.delete(&object_key).await {
+            Ok(()) => removed.push(id),
+            Err(e) => warn!("Failed to delete expired artifact {}: {}", object_key, e),
+        }
+    }
+    if !removed.is_empty() {
+        sqlx::query("DELETE FROM artifacts WHERE id = ANY($1)")
+            .bind(&removed)
+            .execute(pool)
+            .await?;
+    }
+    Ok(removed.len())
+}
+
+/// Spawn the hourly lifecycle pass (a no-op while storage isn't configured)
+pub fn spawn_lifecycle_task(pool: sqlx::PgPool, secrets: SecretsManager) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(std::time::Duration::from_secs(3600));
+
+        loop {
+            interval.tick().await;
+
+            let Some(store) = ObjectStore::from_config(&pool, &secrets).await else {
+                continue;
+            };
+            match expire_artifacts(&pool, &store, 500).await {
+                Ok(0) => {}
+                Ok(n) => info!("Artifact lifecycle: removed {} expired artifacts", n),
+                Err(e) => error!("Artifact lifecycle pass failed: {}", e),
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_object_keys_and_retention() {
+        let bot_id = Uuid::nil();
+        let id = Uuid::from_u128(1);
+        let now = Utc.with_ymd_and_hms(2026, 3, 1, 12, 30, 0).unwrap();
+        assert_eq!(
+            ArtifactKind::JournalExport.object_key(bot_id, id, now, "jsonl"),
+            format!(
+                "bots/{}/journal_export/20260301T123000Z-{}.jsonl",
+                bot_id, id
+            )
+        );
+        // Every kind has a policy
+        for kind in [
+            ArtifactKind::JournalExport,
+            ArtifactKind::Diagnostics,
+            ArtifactKind::StateSnapshot,
+        ] {
+            assert!(LIFECYCLE.iter().any(|(k, _)| *k == kind));
+        }
+        assert_eq!(ArtifactKind::Diagnostics.retention_days(), 14);
+    }
+}
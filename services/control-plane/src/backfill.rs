@@ -0,0 +1,465 @@
+//! Equity curve backfill for gaps in a bot's metrics
+//!
+//! A runner that is offline (droplet down, network partition) reports no
+//! metrics, so its equity curve has holes that flatten drawdown and return
+//! calculations. When the bot comes back we rebuild the missing stretch from
+//! the trade ledger (`trade_confirmed` events) and historical prices, and insert
+//! the result as `synthetic` metric points.
+//!
+//! Holdings are replayed from the ledger and marked at historical prices. Cash
+//! is anchored so the rebuilt equity matches the last real point at the start
+//! of the gap; anything the ledger can't explain (purged events, transfers)
+//! ends up in that cash figure rather than as a jump in the curve.
+
+use chrono::{DateTime, Duration, Utc};
+use rust_decimal::Decimal;
+use serde::Serialize;
+use std::collections::HashMap;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use crate::models::{try_bigdecimal_from_decimal, try_decimal_from_bigdecimal};
+use data_retrieval::{CoinGeckoClient, TimeFrame};
+
+/// Metrics further apart than this count as a gap (runner syncs every 30s)
+pub const GAP_THRESHOLD_SECS: i64 = 600;
+/// Spacing of synthetic points inside a gap
+pub const BACKFILL_STEP_SECS: i64 = 900;
+/// Oldest gap we try to rebuild (hourly price history only goes back a week)
+pub const MAX_BACKFILL_DAYS: i64 = 7;
+
+/// Stablecoin mints, valued at $1 and treated as cash
+const CASH_MINTS: &[&str] = &[
+    "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v", // USDC
+    "Es9vMFrzaCERmJfrF4H2FYD4KCoNkY11McCe8BenwNYB", // USDT
+];
+
+/// Price symbol and decimals for a token the runner trades
+pub(crate) fn known_mint(mint: &str) -> Option<(&'static str, u32)> {
+    data_retrieval::symbol::mint_info(mint).map(|(symbol, decimals)| (symbol, decimals.into()))
+}
+
+pub(crate) fn is_cash(mint: &str) -> bool {
+    CASH_MINTS.contains(&mint)
+}
+
+/// One equity sample
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EquityPoint {
+    pub timestamp: DateTime<Utc>,
+    pub equity: Decimal,
+    pub pnl: Decimal,
+}
+
+/// A confirmed swap from the ledger, amounts in token units
+#[derive(Debug, Clone, PartialEq)]
+pub struct LedgerTrade {
+    pub timestamp: DateTime<Utc>,
+    pub input_mint: String,
+    pub in_amount: Decimal,
+    pub output_mint: String,
+    pub out_amount: Decimal,
+}
+
+impl LedgerTrade {
+    /// Parse a `trade_confirmed` event's metadata (raw amounts, known mints only)
+    pub fn from_event(timestamp: DateTime<Utc>, metadata: &serde_json::Value) -> Option<Self> {
+        let raw = |key: &str| {
+            let value = metadata.get(key)?;
+            value
+                .as_u64()
+                .or_else(|| value.as_str().and_then(|s| s.parse().ok()))
+        };
+        let input_mint = metadata.get("input_mint")?.as_str()?.to_string();
+        let output_mint = metadata.get("output_mint")?.as_str()?.to_string();
+        let (_, in_decimals) = known_mint(&input_mint)?;
+        let (_, out_decimals) = known_mint(&output_mint)?;
+
+        Some(Self {
+            timestamp,
+            in_amount: Decimal::from(raw("in_amount")?) / Decimal::from(10u64.pow(in_decimals)),
+            out_amount: Decimal::from(raw("out_amount")?) / Decimal::from(10u64.pow(out_decimals)),
+            input_mint,
+            output_mint,
+        })
+    }
+}
+
+/// Historical prices per symbol, sorted by time
+#[derive(Debug, Default)]
+pub struct PriceHistory {
+    series: HashMap<String, Vec<(DateTime<Utc>, Decimal)>>,
+}
+
+impl PriceHistory {
+    pub fn insert(&mut self, symbol: &str, mut points: Vec<(DateTime<Utc>, Decimal)>) {
+        points.sort_by_key(|(ts, _)| *ts);
+        self.series.insert(symbol.to_string(), points);
+    }
+
+    /// Latest known price at or before `at`
+    pub fn price_at(&self, symbol: &str, at: DateTime<Utc>) -> Option<Decimal> {
+        let points = self.series.get(symbol)?;
+        let idx = points.partition_point(|(ts, _)| *ts <= at);
+        idx.checked_sub(1).map(|i| points[i].1)
+    }
+}
+
+/// Pairs of consecutive points more than `threshold` apart
+pub fn find_gaps(points: &[EquityPoint], threshold: Duration) -> Vec<(EquityPoint, DateTime<Utc>)> {
+    points
+        .windows(2)
+        .filter(|w| w[1].timestamp - w[0].timestamp > threshold)
+        .map(|w| (w[0], w[1].timestamp))
+        .collect()
+}
+
+/// Non-cash token balances after replaying every trade up to and including `at`
+fn holdings_at(trades: &[LedgerTrade], at: DateTime<Utc>) -> HashMap<String, Decimal> {
+    let mut holdings: HashMap<String, Decimal> = HashMap::new();
+    let mut cash = Decimal::ZERO;
+    for trade in trades.iter().filter(|t| t.timestamp <= at) {
+        apply_trade(&mut holdings, &mut cash, trade);
+    }
+    holdings
+}
+
+fn apply_trade(holdings: &mut HashMap<String, Decimal>, cash: &mut Decimal, trade: &LedgerTrade) {
+    if is_cash(&trade.input_mint) {
+        *cash -= trade.in_amount;
+    } else {
+        *holdings.entry(trade.input_mint.clone()).or_default() -= trade.in_amount;
+    }
+    if is_cash(&trade.output_mint) {
+        *cash += trade.out_amount;
+    } else {
+        *holdings.entry(trade.output_mint.clone()).or_default() += trade.out_amount;
+    }
+}
+
+/// Market value of non-cash holdings, or None if any price is missing
+fn mark(
+    holdings: &HashMap<String, Decimal>,
+    prices: &PriceHistory,
+    at: DateTime<Utc>,
+) -> Option<Decimal> {
+    holdings
+        .iter()
+        .filter(|(_, qty)| !qty.is_zero())
+        .map(|(mint, qty)| {
+            let (symbol, _) = known_mint(mint)?;
+            Some(*qty * prices.price_at(symbol, at)?)
+        })
+        .sum()
+}
+
+/// Rebuild equity between `anchor` (last real point) and `end` (first point after the gap)
+///
+/// Returns no points if the holdings can't be priced over the whole gap; a
+/// partial curve would be worse than the visible hole.
+pub fn reconstruct_gap(
+    anchor: EquityPoint,
+    end: DateTime<Utc>,
+    trades: &[LedgerTrade],
+    prices: &PriceHistory,
+    step: Duration,
+) -> Vec<EquityPoint> {
+    let mut holdings = holdings_at(trades, anchor.timestamp);
+    let Some(marked) = mark(&holdings, prices, anchor.timestamp) else {
+        return Vec::new();
+    };
+    let mut cash = anchor.equity - marked;
+
+    let mut gap_trades = trades
+        .iter()
+        .filter(|t| t.timestamp > anchor.timestamp && t.timestamp < end)
+        .peekable();
+
+    let mut points = Vec::new();
+    let mut at = anchor.timestamp + step;
+    while at < end {
+        while let Some(trade) = gap_trades.next_if(|t| t.timestamp <= at) {
+            apply_trade(&mut holdings, &mut cash, trade);
+        }
+        let Some(marked) = mark(&holdings, prices, at) else {
+            return Vec::new();
+        };
+        let equity = cash + marked;
+        points.push(EquityPoint {
+            timestamp: at,
+            equity,
+            pnl: anchor.pnl + (equity - anchor.equity),
+        });
+        at += step;
+    }
+    points
+}
+
+/// Outcome of a backfill run
+#[derive(Debug, Default, Serialize)]
+pub struct BackfillReport {
+    pub gaps_found: usize,
+    pub gaps_filled: usize,
+    pub points_inserted: usize,
+}
+
+#[derive(sqlx::FromRow)]
+struct MetricRow {
+    timestamp: DateTime<Utc>,
+    equity: bigdecimal::BigDecimal,
+    pnl: bigdecimal::BigDecimal,
+}
+
+impl MetricRow {
+    fn point(&self) -> Option<EquityPoint> {
+        Some(EquityPoint {
+            timestamp: self.timestamp,
+            equity: try_decimal_from_bigdecimal(&self.equity)?,
+            pnl: try_decimal_from_bigdecimal(&self.pnl)?,
+        })
+    }
+}
+
+/// Hourly USD closes for each symbol the trades touch
+async fn fetch_price_history(trades: &[LedgerTrade]) -> PriceHistory {
+    let client = CoinGeckoClient::new(std::env::var("COINGECKO_API_KEY").ok());
+    let mut symbols: Vec<&str> = trades
+        .iter()
+        .flat_map(|t| [t.input_mint.as_str(), t.output_mint.as_str()])
+        .filter(|m| !is_cash(m))
+        .filter_map(|m| known_mint(m).map(|(symbol, _)| symbol))
+        .collect();
+    symbols.sort_unstable();
+    symbols.dedup();
+
+    let mut history = PriceHistory::default();
+    for symbol in symbols {
+        match client
+            .get_candles(
+                symbol,
+                "usd",
+                TimeFrame::Hour1,
+                24 * MAX_BACKFILL_DAYS as usize,
+            )
+            .await
+        {
+            Ok(candles) => history.insert(
+                symbol,
+                candles
+                    .into_iter()
+                    .map(|c| (c.timestamp, c.close))
+                    .collect(),
+            ),
+            Err(e) => warn!("No price history for {}: {}", symbol, e),
+        }
+    }
+    history
+}
+
+/// Find and fill every gap in a bot's metrics since `since`
+///
+/// Existing synthetic points are replaced, so re-running is safe.
+pub async fn backfill_bot(
+    pool: &sqlx::PgPool,
+    bot_id: Uuid,
+    since: DateTime<Utc>,
+) -> anyhow::Result<BackfillReport> {
+    let since = since.max(Utc::now() - Duration::days(MAX_BACKFILL_DAYS));
+    let rows: Vec<MetricRow> = sqlx::query_as(
+        r#"
+        SELECT timestamp, equity, pnl FROM metrics
+        WHERE bot_id = $1 AND NOT synthetic
+        AND timestamp >= (
+            SELECT COALESCE(MAX(timestamp), $2) FROM metrics
+            WHERE bot_id = $1 AND NOT synthetic AND timestamp <= $2
+        )
+        ORDER BY timestamp
+        "#,
+    )
+    .bind(bot_id)
+    .bind(since)
+    .fetch_all(pool)
+    .await?;
+
+    let points: Vec<EquityPoint> = rows.iter().filter_map(MetricRow::point).collect();
+    let gaps = find_gaps(&points, Duration::seconds(GAP_THRESHOLD_SECS));
+    let mut report = BackfillReport {
+        gaps_found: gaps.len(),
+        ..Default::default()
+    };
+    if gaps.is_empty() {
+        return Ok(report);
+    }
+
+    let last_end = gaps.iter().map(|(_, end)| *end).max().unwrap_or(since);
+    let trade_rows: Vec<(DateTime<Utc>, Option<serde_json::Value>)> = sqlx::query_as(
+        r#"
+        SELECT created_at, metadata FROM events
+        WHERE bot_id = $1 AND event_type::text = 'trade_confirmed' AND created_at < $2
+        ORDER BY created_at
+        "#,
+    )
+    .bind(bot_id)
+    .bind(last_end)
+    .fetch_all(pool)
+    .await?;
+    let trades: Vec<LedgerTrade> = trade_rows
+        .iter()
+        .filter_map(|(ts, meta)| LedgerTrade::from_event(*ts, meta.as_ref()?))
+        .collect();
+    let prices = fetch_price_history(&trades).await;
+
+    for (anchor, end) in gaps {
+        let synthetic = reconstruct_gap(
+            anchor,
+            end,
+            &trades,
+            &prices,
+            Duration::seconds(BACKFILL_STEP_SECS),
+        );
+        if synthetic.is_empty() {
+            warn!(
+                "Could not rebuild metrics gap for bot {} ({} to {})",
+                bot_id, anchor.timestamp, end
+            );
+            continue;
+        }
+
+        let mut tx = pool.begin().await?;
+        sqlx::query(
+            "DELETE FROM metrics WHERE bot_id = $1 AND synthetic AND timestamp > $2 AND timestamp < $3",
+        )
+        .bind(bot_id)
+        .bind(anchor.timestamp)
+        .bind(end)
+        .execute(&mut *tx)
+        .await?;
+        for point in &synthetic {
+            sqlx::query(
+                "INSERT INTO metrics (bot_id, timestamp, equity, pnl, synthetic) VALUES ($1, $2, $3, $4, TRUE)",
+            )
+            .bind(bot_id)
+            .bind(point.timestamp)
+            .bind(try_bigdecimal_from_decimal(&point.equity))
+            .bind(try_bigdecimal_from_decimal(&point.pnl))
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await?;
+
+        report.gaps_filled += 1;
+        report.points_inserted += synthetic.len();
+    }
+
+    info!(
+        "Backfilled bot {}: {}/{} gaps, {} synthetic points",
+        bot_id, report.gaps_filled, report.gaps_found, report.points_inserted
+    );
+    Ok(report)
+}
+
+/// Backfill in the background after a bot resumes syncing
+pub fn spawn_gap_backfill(pool: sqlx::PgPool, bot_id: Uuid, since: DateTime<Utc>) {
+    tokio::spawn(async move {
+        if let Err(e) = backfill_bot(&pool, bot_id, since).await {
+            warn!("Metrics backfill for bot {} failed: {}", bot_id, e);
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    const SOL: &str = "So11111111111111111111111111111111111111112";
+    const USDC: &str = "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v";
+
+    fn at(minutes: i64) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2026, 5, 1, 0, 0, 0).unwrap() + Duration::minutes(minutes)
+    }
+
+    fn point(minutes: i64, equity: i64) -> EquityPoint {
+        EquityPoint {
+            timestamp: at(minutes),
+            equity: Decimal::from(equity),
+            pnl: Decimal::from(equity - 1000),
+        }
+    }
+
+    #[test]
+    fn test_ledger_trade_from_event() {
+        let meta = serde_json::json!({
+            "input_mint": USDC, "output_mint": SOL,
+            "in_amount": 100_000_000u64, "out_amount": 1_000_000_000u64,
+        });
+        let trade = LedgerTrade::from_event(at(0), &meta).unwrap();
+        assert_eq!(trade.in_amount, Decimal::from(100));
+        assert_eq!(trade.out_amount, Decimal::ONE);
+
+        let unknown = serde_json::json!({
+            "input_mint": USDC, "output_mint": "unknown",
+            "in_amount": 1, "out_amount": 1,
+        });
+        assert!(LedgerTrade::from_event(at(0), &unknown).is_none());
+    }
+
+    #[test]
+    fn test_find_gaps_and_reconstruct() {
+        let points = vec![point(0, 1000), point(1, 1000), point(60, 1100)];
+        let gaps = find_gaps(&points, Duration::seconds(GAP_THRESHOLD_SECS));
+        assert_eq!(gaps, vec![(point(1, 1000), at(60))]);
+
+        // Held 2 SOL before the gap, bought 1 more with USDC during it
+        let trades = vec![
+            LedgerTrade {
+                timestamp: at(-10),
+                input_mint: USDC.to_string(),
+                in_amount: Decimal::from(200),
+                output_mint: SOL.to_string(),
+                out_amount: Decimal::from(2),
+            },
+            LedgerTrade {
+                timestamp: at(20),
+                input_mint: USDC.to_string(),
+                in_amount: Decimal::from(110),
+                output_mint: SOL.to_string(),
+                out_amount: Decimal::ONE,
+            },
+        ];
+        let mut prices = PriceHistory::default();
+        prices.insert(
+            "SOL",
+            vec![(at(-60), Decimal::from(100)), (at(30), Decimal::from(120))],
+        );
+
+        let rebuilt = reconstruct_gap(
+            gaps[0].0,
+            gaps[0].1,
+            &trades,
+            &prices,
+            Duration::minutes(15),
+        );
+        let equities: Vec<Decimal> = rebuilt.iter().map(|p| p.equity).collect();
+        // Cash anchors to 800; after the buy 690 cash + 3 SOL
+        assert_eq!(
+            equities,
+            vec![
+                Decimal::from(1000),
+                Decimal::from(1050),
+                Decimal::from(1050)
+            ]
+        );
+        assert_eq!(rebuilt[1].pnl, Decimal::from(50));
+        assert!(rebuilt
+            .iter()
+            .all(|p| p.timestamp > at(1) && p.timestamp < at(60)));
+
+        // No price for a held token -> nothing rather than a wrong curve
+        let empty = PriceHistory::default();
+        assert!(
+            reconstruct_gap(gaps[0].0, gaps[0].1, &trades, &empty, Duration::minutes(15))
+                .is_empty()
+        );
+    }
+}
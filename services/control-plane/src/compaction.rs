@@ -0,0 +1,353 @@
+//! Compaction of old trade events
+//!
+//! Each trade intent leaves a trail of events (`trade_intent_created`, then
+//! `trade_submitted` and a terminal `trade_confirmed` / `trade_failed` /
+//! `trade_blocked`), each carrying full JSON metadata including the LLM
+//! rationale. Once events are older than `after_days` we fold the intent's
+//! trail into its terminal event, keeping only the ledger fields, and delete
+//! the intermediate rows. The terminal events stay, so the audit trail of what
+//! was traded (and what was blocked or failed, and why) survives until the
+//! retention cleanup, and `trade_confirmed` still parses as a ledger trade. `trade_closed`
+//! events are realized PnL records and are never compacted.
+
+use chrono::{DateTime, Duration, Utc};
+use std::collections::HashMap;
+use tracing::{error, info};
+use uuid::Uuid;
+
+/// Terminal events are the ledger; everything else in a trail folds into them
+const TERMINAL_EVENTS: &[&str] = &["trade_confirmed", "trade_failed", "trade_blocked"];
+
+/// Trade events left alone: each `trade_closed` is a realized PnL record
+const KEPT_EVENTS: &[&str] = &["trade_closed"];
+
+/// Metadata kept on compacted events
+const LEDGER_FIELDS: &[&str] = &[
+    "intent_id",
+    "signature",
+    "input_mint",
+    "output_mint",
+    "in_amount",
+    "out_amount",
+    "executed_price",
+    "slippage_bps",
+    "price_impact_pct",
+    "fee_bps",
+    "fee_usd",
+    "reason_code",
+    "error_code",
+    "stage",
+    "mode",
+];
+
+/// Fields worth carrying over from `trade_intent_created`
+const INTENT_FIELDS: &[&str] = &["action", "amount_usd", "confidence", "source", "mode"];
+
+#[derive(Debug, Clone)]
+pub struct CompactionConfig {
+    /// Trade events older than this are compacted
+    pub after_days: i64,
+    /// Events loaded per pass
+    pub batch_size: i64,
+}
+
+impl Default for CompactionConfig {
+    fn default() -> Self {
+        Self {
+            after_days: 7,
+            batch_size: 2000,
+        }
+    }
+}
+
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct TradeEventRow {
+    pub id: Uuid,
+    pub bot_id: Uuid,
+    pub event_type: String,
+    pub metadata: Option<serde_json::Value>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// What to do with one intent's trail (or one event without an intent)
+#[derive(Debug, Default, PartialEq)]
+pub struct CompactionPlan {
+    /// Events to rewrite with compacted metadata
+    pub rewrite: Vec<(Uuid, serde_json::Value)>,
+    pub delete: Vec<Uuid>,
+}
+
+fn pick(
+    metadata: Option<&serde_json::Value>,
+    fields: &[&str],
+    into: &mut serde_json::Map<String, serde_json::Value>,
+) {
+    let Some(obj) = metadata.and_then(|m| m.as_object()) else {
+        return;
+    };
+    for field in fields {
+        if let Some(value) = obj.get(*field) {
+            if !value.is_null() {
+                into.insert(field.to_string(), value.clone());
+            }
+        }
+    }
+}
+
+/// Plan compaction of events sharing one intent id (or a single lone event)
+pub fn plan_trail(events: &[TradeEventRow]) -> CompactionPlan {
+    let mut plan = CompactionPlan::default();
+    let terminal = events
+        .iter()
+        .filter(|e| TERMINAL_EVENTS.contains(&e.event_type.as_str()))
+        .max_by_key(|e| e.created_at);
+
+    let Some(terminal) = terminal else {
+        // Trail never finished (or isn't a trail); just slim each event down
+        for event in events {
+            let mut compacted = serde_json::Map::new();
+            pick(event.metadata.as_ref(), LEDGER_FIELDS, &mut compacted);
+            pick(event.metadata.as_ref(), INTENT_FIELDS, &mut compacted);
+            compacted.insert("compacted".to_string(), true.into());
+            plan.rewrite.push((event.id, compacted.into()));
+        }
+        return plan;
+    };
+
+    let mut compacted = serde_json::Map::new();
+    for event in events
+        .iter()
+        .filter(|e| e.event_type == "trade_intent_created")
+    {
+        pick(event.metadata.as_ref(), INTENT_FIELDS, &mut compacted);
+    }
+    // Terminal fields win over the intent's (e.g. mode)
+    pick(terminal.metadata.as_ref(), LEDGER_FIELDS, &mut compacted);
+    compacted.insert("compacted".to_string(), true.into());
+    plan.rewrite.push((terminal.id, compacted.into()));
+
+    plan.delete = events
+        .iter()
+        .filter(|e| e.id != terminal.id)
+        .map(|e| e.id)
+        .collect();
+    plan
+}
+
+fn intent_id(row: &TradeEventRow) -> Option<String> {
+    row.metadata
+        .as_ref()
+        .and_then(|m| m.get("intent_id"))
+        .and_then(|v| v.as_str())
+        .map(str::to_string)
+}
+
+/// Compact one batch of old trade events; returns (rewritten, deleted)
+///
+/// Trails are found through their terminal event, so an intent is compacted
+/// together with its outcome. Non-terminal events whose trail never finished
+/// are slimmed in place once they're twice the cutoff age.
+pub async fn compact_trade_events(
+    pool: &sqlx::PgPool,
+    config: &CompactionConfig,
+) -> anyhow::Result<(u64, u64)> {
+    let cutoff = Utc::now() - Duration::days(config.after_days);
+    let terminal_types: Vec<String> = TERMINAL_EVENTS.iter().map(|t| t.to_string()).collect();
+    // Neither folded into a trail nor slimmed as a stuck event
+    let skipped_types: Vec<String> = TERMINAL_EVENTS
+        .iter()
+        .chain(KEPT_EVENTS)
+        .map(|t| t.to_string())
+        .collect();
+
+    let terminals: Vec<TradeEventRow> = sqlx::query_as(
+        r#"
+        SELECT id, bot_id, event_type::text AS event_type, metadata, created_at FROM events
+        WHERE event_type::text = ANY($1)
+          AND created_at < $2
+          AND NOT COALESCE(metadata ? 'compacted', FALSE)
+        ORDER BY created_at
+        LIMIT $3
+        "#,
+    )
+    .bind(&terminal_types)
+    .bind(cutoff)
+    .bind(config.batch_size)
+    .fetch_all(pool)
+    .await?;
+
+    let intent_ids: Vec<String> = terminals.iter().filter_map(intent_id).collect();
+    let trail_rows: Vec<TradeEventRow> = if intent_ids.is_empty() {
+        Vec::new()
+    } else {
+        sqlx::query_as(
+            r#"
+            SELECT id, bot_id, event_type::text AS event_type, metadata, created_at FROM events
+            WHERE metadata->>'intent_id' = ANY($1)
+              AND NOT (event_type::text = ANY($2))
+              AND NOT COALESCE(metadata ? 'compacted', FALSE)
+            "#,
+        )
+        .bind(&intent_ids)
+        .bind(&skipped_types)
+        .fetch_all(pool)
+        .await?
+    };
+
+    let stuck: Vec<TradeEventRow> = sqlx::query_as(
+        r#"
+        SELECT id, bot_id, event_type::text AS event_type, metadata, created_at FROM events
+        WHERE event_type::text LIKE 'trade\_%'
+          AND NOT (event_type::text = ANY($1))
+          AND created_at < $2
+          AND NOT COALESCE(metadata ? 'compacted', FALSE)
+        LIMIT $3
+        "#,
+    )
+    .bind(&skipped_types)
+    .bind(cutoff - Duration::days(config.after_days))
+    .bind(config.batch_size)
+    .fetch_all(pool)
+    .await?;
+
+    let mut plans = Vec::new();
+    let mut trails: HashMap<(Uuid, String), Vec<TradeEventRow>> = HashMap::new();
+    for row in terminals.into_iter().chain(trail_rows) {
+        match intent_id(&row) {
+            Some(intent) => trails.entry((row.bot_id, intent)).or_default().push(row),
+            None => plans.push(plan_trail(std::slice::from_ref(&row))),
+        }
+    }
+    plans.extend(trails.values().map(|trail| plan_trail(trail)));
+    // Trail rows may also match the stuck query; those are already planned
+    let planned: std::collections::HashSet<Uuid> = plans
+        .iter()
+        .flat_map(|p| {
+            p.rewrite
+                .iter()
+                .map(|(id, _)| *id)
+                .chain(p.delete.iter().copied())
+        })
+        .collect();
+    plans.extend(
+        stuck
+            .iter()
+            .filter(|row| !planned.contains(&row.id))
+            .map(|row| plan_trail(std::slice::from_ref(row))),
+    );
+
+    let mut tx = pool.begin().await?;
+    let (mut rewritten, mut deleted) = (0u64, 0u64);
+    for plan in plans {
+        for (id, metadata) in plan.rewrite {
+            rewritten += sqlx::query("UPDATE events SET metadata = $1 WHERE id = $2")
+                .bind(metadata)
+                .bind(id)
+                .execute(&mut *tx)
+                .await?
+                .rows_affected();
+        }
+        if !plan.delete.is_empty() {
+            deleted += sqlx::query("DELETE FROM events WHERE id = ANY($1)")
+                .bind(&plan.delete)
+                .execute(&mut *tx)
+                .await?
+                .rows_affected();
+        }
+    }
+    tx.commit().await?;
+
+    Ok((rewritten, deleted))
+}
+
+/// Spawn a background task that compacts old trade events hourly
+pub fn spawn_compaction_task(pool: sqlx::PgPool) {
+    tokio::spawn(async move {
+        let config = CompactionConfig::default();
+        let mut interval = tokio::time::interval(std::time::Duration::from_secs(3600));
+
+        loop {
+            interval.tick().await;
+
+            // Drain the backlog a batch at a time (bounded per tick)
+            let (mut rewritten, mut deleted) = (0, 0);
+            for _ in 0..20 {
+                match compact_trade_events(&pool, &config).await {
+                    Ok((0, 0)) => break,
+                    Ok((r, d)) => {
+                        rewritten += r;
+                        deleted += d;
+                    }
+                    Err(e) => {
+                        error!("Trade event compaction failed: {}", e);
+                        break;
+                    }
+                }
+            }
+            if rewritten > 0 || deleted > 0 {
+                info!(
+                    "Trade event compaction: slimmed {} events, removed {} (>{}d)",
+                    rewritten, deleted, config.after_days
+                );
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backfill::LedgerTrade;
+
+    fn event(event_type: &str, metadata: serde_json::Value, secs: i64) -> TradeEventRow {
+        TradeEventRow {
+            id: Uuid::new_v4(),
+            bot_id: Uuid::nil(),
+            event_type: event_type.to_string(),
+            metadata: Some(metadata),
+            created_at: DateTime::from_timestamp(1_700_000_000 + secs, 0).unwrap(),
+        }
+    }
+
+    #[test]
+    fn test_trail_folds_into_terminal_event() {
+        let sol = "So11111111111111111111111111111111111111112";
+        let usdc = "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v";
+        let created = event(
+            "trade_intent_created",
+            serde_json::json!({"intent_id": "i1", "action": "Buy", "amount_usd": "50",
+                "rationale": "a very long explanation", "mode": "Paper"}),
+            0,
+        );
+        let submitted = event(
+            "trade_submitted",
+            serde_json::json!({"intent_id": "i1", "expected_out": 1}),
+            1,
+        );
+        let confirmed = event(
+            "trade_confirmed",
+            serde_json::json!({"intent_id": "i1", "signature": "sig", "input_mint": usdc,
+                "output_mint": sol, "in_amount": 50_000_000u64, "out_amount": 333_000_000u64,
+                "mode": "Live"}),
+            2,
+        );
+        let trail = [created.clone(), submitted.clone(), confirmed.clone()];
+
+        let plan = plan_trail(&trail);
+        assert_eq!(plan.delete, vec![created.id, submitted.id]);
+        let (id, metadata) = &plan.rewrite[0];
+        assert_eq!(*id, confirmed.id);
+        assert_eq!(metadata["action"], "Buy");
+        assert_eq!(metadata["mode"], "Live");
+        assert_eq!(metadata["compacted"], true);
+        assert!(metadata.get("rationale").is_none());
+
+        // Compacted confirmations still count as ledger trades
+        assert!(LedgerTrade::from_event(confirmed.created_at, metadata).is_some());
+
+        // An unfinished trail is slimmed in place
+        let plan = plan_trail(&[created]);
+        assert!(plan.delete.is_empty());
+        assert!(plan.rewrite[0].1.get("rationale").is_none());
+    }
+}
@@ -1,7 +1,9 @@
 //! Platform configuration helper
 //!
 //! Reads configuration values from the platform_config database table.
-//! Handles decryption of encrypted values using SecretsManager.
+//! Handles decryption of encrypted values using SecretsManager. Plain values
+//! are read through the typed, cached `settings::Settings`; these helpers are
+//! for secrets and one-off reads.
 
 use crate::secrets::SecretsManager;
 use sqlx::PgPool;
@@ -51,10 +53,24 @@ pub async fn get_config_decrypted(
 }
 
 /// Get a configuration value with a default fallback
+///
+/// Service URLs (see `keys::ENV_OVERRIDABLE`) fall back to their environment
+/// variable before the default, so containers can be wired up via env alone.
 pub async fn get_config_or(pool: &PgPool, key: &str, default: &str) -> String {
-    get_config(pool, key)
-        .await
-        .unwrap_or_else(|| default.to_string())
+    match get_config(pool, key).await {
+        Some(value) => value,
+        None => env_fallback(key).unwrap_or_else(|| default.to_string()),
+    }
+}
+
+/// Environment value for an overridable key (`data_retrieval_url` -> `DATA_RETRIEVAL_URL`)
+pub(crate) fn env_fallback(key: &str) -> Option<String> {
+    if !keys::ENV_OVERRIDABLE.contains(&key) {
+        return None;
+    }
+    std::env::var(key.to_ascii_uppercase())
+        .ok()
+        .filter(|v| !v.is_empty())
 }
 
 /// Get a decrypted configuration value with a default fallback
@@ -76,15 +92,33 @@ pub mod keys {
     pub const DROPLET_REGION: &str = "droplet_region";
     pub const DROPLET_SIZE: &str = "droplet_size";
     pub const DROPLET_IMAGE: &str = "droplet_image";
+    pub const SHOW_INFRA_COST_TO_USERS: &str = "show_infra_cost_to_users";
 
     // Trading
     pub const JUPITER_API_KEY: &str = "jupiter_api_key";
     pub const SOLANA_RPC_URL: &str = "solana_rpc_url";
     pub const DEFAULT_SLIPPAGE_BPS: &str = "default_slippage_bps";
+    pub const PAPER_TRADING_DEFAULT: &str = "paper_trading_default";
+    pub const LIVE_MIN_USDC: &str = "live_min_usdc";
+    pub const LIVE_MIN_SOL: &str = "live_min_sol";
+    pub const TRADING_HALTED: &str = "trading_halted";
+    pub const TRADING_HALT_REASON: &str = "trading_halt_reason";
+    pub const ASSET_REGISTRY: &str = "asset_registry";
 
     // Services
     pub const CONTROL_PLANE_URL: &str = "control_plane_url";
     pub const DATA_RETRIEVAL_URL: &str = "data_retrieval_url";
+    /// Comma-separated control planes in other regions, tried in order when
+    /// `control_plane_url` is down
+    pub const CONTROL_PLANE_FAILOVER_URLS: &str = "control_plane_failover_urls";
+
+    /// Non-secret keys that may come from the environment when unset in the DB
+    pub const ENV_OVERRIDABLE: &[&str] = &[
+        CONTROL_PLANE_URL,
+        CONTROL_PLANE_FAILOVER_URLS,
+        DATA_RETRIEVAL_URL,
+        SOLANA_RPC_URL,
+    ];
 
     // Alerting
     pub const DISCORD_WEBHOOK_URL: &str = "discord_webhook_url";
@@ -92,8 +126,25 @@ pub mod keys {
     pub const ALERT_EMAIL_TO: &str = "alert_email_to";
     pub const ALERTS_ENABLED: &str = "alerts_enabled";
 
+    // Billing
+    pub const STRIPE_WEBHOOK_SECRET: &str = "stripe_webhook_secret";
+    pub const CEDROS_PAY_WEBHOOK_SECRET: &str = "cedros_pay_webhook_secret";
+    pub const PAYMENT_GRACE_PERIOD_HOURS: &str = "payment_grace_period_hours";
+
+    // Object storage
+    pub const OBJECT_STORAGE_ENDPOINT: &str = "object_storage_endpoint";
+    pub const OBJECT_STORAGE_BUCKET: &str = "object_storage_bucket";
+    pub const OBJECT_STORAGE_REGION: &str = "object_storage_region";
+    pub const OBJECT_STORAGE_ACCESS_KEY: &str = "object_storage_access_key";
+    pub const OBJECT_STORAGE_SECRET_KEY: &str = "object_storage_secret_key";
+
     // Limits
     pub const MAX_BOTS_PER_USER: &str = "max_bots_per_user";
     pub const MAX_CONCURRENT_PROVISIONS: &str = "max_concurrent_provisions";
     pub const RATE_LIMIT_REQUESTS_PER_MINUTE: &str = "rate_limit_requests_per_minute";
+
+    // Keeper
+    pub const STUCK_CONFIG_DEADLINE_MINUTES: &str = "stuck_config_deadline_minutes";
+    pub const STUCK_PROVISIONING_DEADLINE_MINUTES: &str = "stuck_provisioning_deadline_minutes";
+    pub const STUCK_FAIL_AFTER_MINUTES: &str = "stuck_fail_after_minutes";
 }
@@ -0,0 +1,226 @@
+//! Portable bot configuration documents
+//!
+//! A bot's strategy configuration as a versioned YAML document, so users can
+//! keep configs in git, share them, or move a bot between environments.
+//! Secrets (the LLM API key, channel tokens) never leave the control plane:
+//! an imported bot starts without them and gets them through the usual
+//! config endpoints.
+//!
+//! Documents are checked on import against the same rules as `POST /bots`,
+//! and unknown fields are rejected so a typo doesn't silently fall back to a
+//! default.
+
+use serde::{Deserialize, Serialize};
+
+use crate::models::{
+    validate_trailing_stop, AlgorithmMode, AssetFocus, AssetLimits, ConfigVersion,
+    CreateBotRequest, Persona, RiskCaps, Strictness, TradingMode, TradingWindow,
+};
+
+/// Schema version written on export; imports must match it
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// Longest bot name a document may carry (as for `CreateBotRequest`)
+const MAX_NAME_LEN: usize = 100;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ConfigDocument {
+    pub schema_version: u32,
+    pub name: String,
+    pub persona: Persona,
+    pub algorithm_mode: AlgorithmMode,
+    pub asset_focus: AssetFocus,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub custom_assets: Option<Vec<String>>,
+    #[serde(default)]
+    pub strictness: Strictness,
+    pub trading_mode: TradingMode,
+    pub risk_caps: RiskCaps,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub trailing_stop_percent: Option<i32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub asset_limits: Option<AssetLimits>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub trading_window: Option<TradingWindow>,
+    pub llm_provider: String,
+}
+
+impl ConfigDocument {
+    /// Document for a stored config version
+    pub fn from_version(config: &ConfigVersion) -> Result<Self, String> {
+        fn column<T: serde::de::DeserializeOwned>(
+            name: &str,
+            value: &Option<serde_json::Value>,
+        ) -> Result<Option<T>, String> {
+            value
+                .clone()
+                .filter(|v| !v.is_null())
+                .map(serde_json::from_value)
+                .transpose()
+                .map_err(|e| format!("Stored {} is invalid: {}", name, e))
+        }
+
+        Ok(Self {
+            schema_version: SCHEMA_VERSION,
+            name: config.name.clone(),
+            persona: config.persona,
+            algorithm_mode: config.algorithm_mode,
+            asset_focus: config.asset_focus,
+            custom_assets: column("custom_assets", &config.custom_assets)?,
+            strictness: config.strictness,
+            trading_mode: config.trading_mode,
+            risk_caps: RiskCaps {
+                max_position_size_percent: config.max_position_size_percent,
+                max_daily_loss_usd: config.max_daily_loss_usd,
+                max_drawdown_percent: config.max_drawdown_percent,
+                max_trades_per_day: config.max_trades_per_day,
+            },
+            trailing_stop_percent: config.trailing_stop_percent,
+            asset_limits: column("asset_limits", &config.asset_limits)?,
+            trading_window: column("trading_window", &config.trading_window)?,
+            llm_provider: config.llm_provider.clone(),
+        })
+    }
+
+    pub fn to_yaml(&self) -> Result<String, String> {
+        serde_yaml::to_string(self).map_err(|e| e.to_string())
+    }
+
+    /// Parse and validate a document
+    ///
+    /// The asset universe isn't checked here; it depends on the registry of
+    /// the environment the document is imported into.
+    pub fn from_yaml(yaml: &str) -> Result<Self, String> {
+        let doc: Self =
+            serde_yaml::from_str(yaml).map_err(|e| format!("Invalid config document: {}", e))?;
+        doc.validate()?;
+        Ok(doc)
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if self.schema_version != SCHEMA_VERSION {
+            return Err(format!(
+                "Unsupported schema_version {} (expected {})",
+                self.schema_version, SCHEMA_VERSION
+            ));
+        }
+        if self.name.trim().is_empty() || self.name.chars().count() > MAX_NAME_LEN {
+            return Err(format!("name must be 1-{} characters", MAX_NAME_LEN));
+        }
+        if self.llm_provider.is_empty() {
+            return Err("llm_provider must not be empty".to_string());
+        }
+        self.risk_caps
+            .validate()
+            .map_err(|e| format!("Invalid risk caps: {}", e))?;
+        validate_trailing_stop(self.trailing_stop_percent)?;
+        if let Some(limits) = &self.asset_limits {
+            limits
+                .validate()
+                .map_err(|e| format!("Invalid asset limits: {}", e))?;
+        }
+        if let Some(window) = &self.trading_window {
+            window
+                .validate()
+                .map_err(|e| format!("Invalid trading window: {}", e))?;
+        }
+        Ok(())
+    }
+
+    /// Request creating a bot from the document, without any secrets
+    pub fn into_create_request(self) -> CreateBotRequest {
+        CreateBotRequest {
+            name: self.name,
+            persona: Some(self.persona),
+            algorithm_mode: self.algorithm_mode,
+            asset_focus: self.asset_focus,
+            strictness: self.strictness,
+            trading_mode: self.trading_mode,
+            risk_caps: self.risk_caps,
+            trailing_stop_percent: self.trailing_stop_percent,
+            asset_limits: self.asset_limits,
+            trading_window: self.trading_window,
+            llm_provider: self.llm_provider,
+            llm_model: None,
+            llm_api_key: None,
+            custom_assets: self.custom_assets,
+            telegram_enabled: false,
+            telegram_bot_token: None,
+            provisioning: Default::default(),
+            encrypt_state: false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use uuid::Uuid;
+
+    fn version() -> ConfigVersion {
+        ConfigVersion {
+            id: Uuid::new_v4(),
+            bot_id: Uuid::new_v4(),
+            version: 3,
+            name: "Momentum".to_string(),
+            persona: Persona::Tweaker,
+            asset_focus: AssetFocus::Custom,
+            custom_assets: Some(serde_json::json!(["SOL", "JUP"])),
+            algorithm_mode: AlgorithmMode::Trend,
+            strictness: Strictness::High,
+            max_position_size_percent: 10,
+            max_daily_loss_usd: 100,
+            max_drawdown_percent: 15,
+            max_trades_per_day: 8,
+            trading_mode: TradingMode::Paper,
+            llm_provider: "openai".to_string(),
+            encrypted_llm_api_key: "secret".to_string(),
+            created_at: Utc::now(),
+            trailing_stop_percent: Some(5),
+            asset_limits: Some(serde_json::json!({"max_open_positions": 3})),
+            trading_window: None,
+        }
+    }
+
+    #[test]
+    fn test_round_trip_without_secrets() {
+        let doc = ConfigDocument::from_version(&version()).unwrap();
+        let yaml = doc.to_yaml().unwrap();
+        assert!(yaml.contains("schema_version: 1"));
+        assert!(!yaml.contains("secret"));
+        assert!(!yaml.contains("trading_window"));
+
+        let parsed = ConfigDocument::from_yaml(&yaml).unwrap();
+        assert_eq!(parsed, doc);
+        let req = parsed.into_create_request();
+        assert_eq!(req.custom_assets, Some(vec!["SOL".into(), "JUP".into()]));
+        assert_eq!(req.asset_limits.unwrap().max_open_positions, Some(3));
+        assert!(req.llm_api_key.is_none());
+    }
+
+    #[test]
+    fn test_import_is_validated() {
+        let yaml = ConfigDocument::from_version(&version())
+            .unwrap()
+            .to_yaml()
+            .unwrap();
+
+        let future = yaml.replace("schema_version: 1", "schema_version: 2");
+        assert!(ConfigDocument::from_yaml(&future)
+            .unwrap_err()
+            .contains("schema_version"));
+
+        let unknown = format!("{}max_leverage: 5\n", yaml);
+        assert!(ConfigDocument::from_yaml(&unknown).is_err());
+
+        let risky = yaml.replace("max_drawdown_percent: 15", "max_drawdown_percent: 90");
+        assert!(ConfigDocument::from_yaml(&risky)
+            .unwrap_err()
+            .starts_with("Invalid risk caps"));
+
+        let stop = yaml.replace("trailing_stop_percent: 5", "trailing_stop_percent: 80");
+        assert!(ConfigDocument::from_yaml(&stop).is_err());
+    }
+}
@@ -0,0 +1,321 @@
+//! Official end-of-day marks
+//!
+//! At its day rollover the runner closes the finished trading day: equity,
+//! cash, PnL and every position at the price it was valued at. The mark comes
+//! in the `day_rollover` event's `mark` and is stored in `daily_marks`, one
+//! row per bot and day; the first one received is canonical. Daily figures
+//! (the close-to-close drawdowns on `GET /bots/:id/daily-marks`) read these
+//! rather than the intraday metrics.
+//!
+//! A mark is checked against itself and against the intraday series when it
+//! arrives. Anything that doesn't add up is recorded in `issues` and the mark
+//! is served as `flagged`:
+//! - `equity_mismatch`: cash plus positions isn't the equity reported
+//! - `intraday_divergence`: the last metric before the mark differs from it
+//!   by more than [`MAX_INTRADAY_DIVERGENCE_PCT`]
+//! - `stale_prices`: the positions were priced over [`MAX_PRICE_AGE_SECS`]
+//!   before the close
+
+use bigdecimal::BigDecimal;
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use tracing::{error, warn};
+use uuid::Uuid;
+
+use crate::models::{try_bigdecimal_from_decimal, try_decimal_from_bigdecimal};
+
+/// Gap between the mark and the last intraday equity that flags it
+pub const MAX_INTRADAY_DIVERGENCE_PCT: Decimal = Decimal::ONE;
+
+/// Oldest the last mark-to-market may be at the close
+pub const MAX_PRICE_AGE_SECS: i64 = 3600;
+
+/// How far before the mark an intraday metric still counts as its comparison
+const INTRADAY_LOOKBACK_MINUTES: i64 = 60;
+
+/// Slack for rounding when adding cash and positions back up to equity
+const EQUITY_TOLERANCE_USD: Decimal = Decimal::from_parts(1, 0, 0, false, 2);
+
+/// One position in a mark, as the runner priced it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MarkedPosition {
+    pub mint: String,
+    pub symbol: String,
+    /// Negative for a short
+    pub quantity: Decimal,
+    pub price_usd: Decimal,
+    pub value_usd: Decimal,
+}
+
+/// The runner's close for one trading day
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DailyMark {
+    pub day: NaiveDate,
+    pub timezone: String,
+    pub equity_usd: Decimal,
+    pub cash_usd: Decimal,
+    pub realized_pnl_usd: Decimal,
+    pub unrealized_pnl_usd: Decimal,
+    #[serde(default)]
+    pub fees_usd: Decimal,
+    #[serde(default)]
+    pub trades: u32,
+    pub prices_marked_at: DateTime<Utc>,
+    #[serde(default)]
+    pub positions: Vec<MarkedPosition>,
+}
+
+/// A stored mark with what was found when it was checked
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct StoredMark {
+    #[serde(flatten)]
+    pub mark: DailyMark,
+    pub marked_at: DateTime<Utc>,
+    pub intraday_equity_usd: Option<Decimal>,
+    pub issues: Vec<String>,
+    pub flagged: bool,
+}
+
+/// What doesn't add up in `mark`, closed at `marked_at`, given the last
+/// intraday equity before it
+pub fn check(
+    mark: &DailyMark,
+    marked_at: DateTime<Utc>,
+    intraday_equity: Option<Decimal>,
+) -> Vec<String> {
+    let mut issues = Vec::new();
+
+    let positions: Decimal = mark.positions.iter().map(|p| p.value_usd).sum();
+    if (mark.cash_usd + positions - mark.equity_usd).abs() > EQUITY_TOLERANCE_USD {
+        issues.push("equity_mismatch".to_string());
+    }
+
+    if let Some(intraday) = intraday_equity {
+        let base = mark.equity_usd.abs().max(intraday.abs());
+        if base > Decimal::ZERO
+            && (mark.equity_usd - intraday).abs() / base * Decimal::from(100)
+                > MAX_INTRADAY_DIVERGENCE_PCT
+        {
+            issues.push("intraday_divergence".to_string());
+        }
+    }
+
+    if !mark.positions.is_empty()
+        && marked_at - mark.prices_marked_at > Duration::seconds(MAX_PRICE_AGE_SECS)
+    {
+        issues.push("stale_prices".to_string());
+    }
+
+    issues
+}
+
+/// Store the mark carried by a `day_rollover` event, if it has one
+///
+/// Ingest never fails on a bad mark; it's logged and dropped.
+pub async fn record_from_event(
+    pool: &sqlx::PgPool,
+    bot_id: Uuid,
+    metadata: Option<&serde_json::Value>,
+    marked_at: DateTime<Utc>,
+) {
+    let Some(value) = metadata.and_then(|m| m.get("mark")) else {
+        return;
+    };
+    let mark: DailyMark = match serde_json::from_value(value.clone()) {
+        Ok(mark) => mark,
+        Err(e) => {
+            warn!("Bot {} sent an unreadable daily mark: {}", bot_id, e);
+            return;
+        }
+    };
+    if let Err(e) = record(pool, bot_id, &mark, marked_at).await {
+        error!(
+            "Failed to store daily mark {} for bot {}: {}",
+            mark.day, bot_id, e
+        );
+    }
+}
+
+/// Check and store a mark; returns its issues, or None if the day was
+/// already marked
+pub async fn record(
+    pool: &sqlx::PgPool,
+    bot_id: Uuid,
+    mark: &DailyMark,
+    marked_at: DateTime<Utc>,
+) -> Result<Option<Vec<String>>, sqlx::Error> {
+    let intraday: Option<BigDecimal> = sqlx::query_scalar(
+        "SELECT equity FROM metrics \
+         WHERE bot_id = $1 AND timestamp <= $2 AND timestamp > $3 \
+         ORDER BY timestamp DESC LIMIT 1",
+    )
+    .bind(bot_id)
+    .bind(marked_at)
+    .bind(marked_at - Duration::minutes(INTRADAY_LOOKBACK_MINUTES))
+    .fetch_optional(pool)
+    .await?;
+    let intraday = intraday.as_ref().and_then(try_decimal_from_bigdecimal);
+    let issues = check(mark, marked_at, intraday);
+
+    let inserted = sqlx::query(
+        r#"
+        INSERT INTO daily_marks
+            (bot_id, day, timezone, equity_usd, cash_usd, realized_pnl_usd, unrealized_pnl_usd,
+             fees_usd, trades, positions, prices_marked_at, marked_at, intraday_equity_usd, issues)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14)
+        ON CONFLICT (bot_id, day) DO NOTHING
+        "#,
+    )
+    .bind(bot_id)
+    .bind(mark.day)
+    .bind(&mark.timezone)
+    .bind(try_bigdecimal_from_decimal(&mark.equity_usd))
+    .bind(try_bigdecimal_from_decimal(&mark.cash_usd))
+    .bind(try_bigdecimal_from_decimal(&mark.realized_pnl_usd))
+    .bind(try_bigdecimal_from_decimal(&mark.unrealized_pnl_usd))
+    .bind(try_bigdecimal_from_decimal(&mark.fees_usd))
+    .bind(mark.trades as i32)
+    .bind(serde_json::to_value(&mark.positions).unwrap_or_default())
+    .bind(mark.prices_marked_at)
+    .bind(marked_at)
+    .bind(intraday.as_ref().and_then(try_bigdecimal_from_decimal))
+    .bind(&issues)
+    .execute(pool)
+    .await?;
+
+    if inserted.rows_affected() == 0 {
+        return Ok(None);
+    }
+    if !issues.is_empty() {
+        warn!(
+            "Daily mark {} for bot {} flagged: {}",
+            mark.day,
+            bot_id,
+            issues.join(", ")
+        );
+    }
+    Ok(Some(issues))
+}
+
+#[derive(sqlx::FromRow)]
+struct MarkRow {
+    day: NaiveDate,
+    timezone: String,
+    equity_usd: BigDecimal,
+    cash_usd: BigDecimal,
+    realized_pnl_usd: BigDecimal,
+    unrealized_pnl_usd: BigDecimal,
+    fees_usd: BigDecimal,
+    trades: i32,
+    positions: serde_json::Value,
+    prices_marked_at: DateTime<Utc>,
+    marked_at: DateTime<Utc>,
+    intraday_equity_usd: Option<BigDecimal>,
+    issues: Vec<String>,
+}
+
+impl MarkRow {
+    fn stored(self) -> Option<StoredMark> {
+        let flagged = !self.issues.is_empty();
+        Some(StoredMark {
+            mark: DailyMark {
+                day: self.day,
+                timezone: self.timezone,
+                equity_usd: try_decimal_from_bigdecimal(&self.equity_usd)?,
+                cash_usd: try_decimal_from_bigdecimal(&self.cash_usd)?,
+                realized_pnl_usd: try_decimal_from_bigdecimal(&self.realized_pnl_usd)?,
+                unrealized_pnl_usd: try_decimal_from_bigdecimal(&self.unrealized_pnl_usd)?,
+                fees_usd: try_decimal_from_bigdecimal(&self.fees_usd)?,
+                trades: self.trades.max(0) as u32,
+                prices_marked_at: self.prices_marked_at,
+                positions: serde_json::from_value(self.positions).unwrap_or_default(),
+            },
+            marked_at: self.marked_at,
+            intraday_equity_usd: self
+                .intraday_equity_usd
+                .as_ref()
+                .and_then(try_decimal_from_bigdecimal),
+            issues: self.issues,
+            flagged,
+        })
+    }
+}
+
+/// Marks for days on or after `since`, oldest first
+pub async fn marks_since(
+    pool: &sqlx::PgPool,
+    bot_id: Uuid,
+    since: NaiveDate,
+) -> Result<Vec<StoredMark>, sqlx::Error> {
+    let rows: Vec<MarkRow> = sqlx::query_as(
+        "SELECT day, timezone, equity_usd, cash_usd, realized_pnl_usd, unrealized_pnl_usd, \
+         fees_usd, trades, positions, prices_marked_at, marked_at, intraday_equity_usd, issues \
+         FROM daily_marks WHERE bot_id = $1 AND day >= $2 ORDER BY day",
+    )
+    .bind(bot_id)
+    .bind(since)
+    .fetch_all(pool)
+    .await?;
+    Ok(rows.into_iter().filter_map(MarkRow::stored).collect())
+}
+
+/// Close-to-close drawdown episodes over `marks` (oldest first)
+pub fn drawdowns(
+    marks: &[StoredMark],
+    now: DateTime<Utc>,
+) -> Vec<crate::drawdowns::DrawdownEpisode> {
+    let closes: Vec<(DateTime<Utc>, Decimal)> = marks
+        .iter()
+        .map(|m| (m.marked_at, m.mark.equity_usd))
+        .collect();
+    crate::drawdowns::detect_episodes(&closes, now)
+        .into_iter()
+        .filter(|e| e.depth_pct >= crate::drawdowns::MIN_DEPTH_PCT)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_check_flags_what_does_not_add_up() {
+        let marked_at = Utc.with_ymd_and_hms(2026, 3, 11, 0, 0, 30).unwrap();
+        let mark = DailyMark {
+            day: NaiveDate::from_ymd_opt(2026, 3, 10).unwrap(),
+            timezone: "UTC".to_string(),
+            equity_usd: Decimal::from(1_240),
+            cash_usd: Decimal::from(1_000),
+            realized_pnl_usd: Decimal::ZERO,
+            unrealized_pnl_usd: Decimal::from(40),
+            fees_usd: Decimal::ZERO,
+            trades: 1,
+            prices_marked_at: marked_at - Duration::seconds(50),
+            positions: vec![MarkedPosition {
+                mint: "So11111111111111111111111111111111111111112".to_string(),
+                symbol: "SOL".to_string(),
+                quantity: Decimal::from(2),
+                price_usd: Decimal::from(120),
+                value_usd: Decimal::from(240),
+            }],
+        };
+        assert!(check(&mark, marked_at, Some(Decimal::from(1_238))).is_empty());
+        assert!(check(&mark, marked_at, None).is_empty());
+
+        assert_eq!(
+            check(&mark, marked_at, Some(Decimal::from(1_180))),
+            vec!["intraday_divergence"]
+        );
+        let off = DailyMark {
+            equity_usd: Decimal::from(1_300),
+            prices_marked_at: marked_at - Duration::hours(3),
+            ..mark.clone()
+        };
+        assert_eq!(
+            check(&off, marked_at, None),
+            vec!["equity_mismatch", "stale_prices"]
+        );
+    }
+}
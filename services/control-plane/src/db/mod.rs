@@ -1,5 +1,6 @@
 use sqlx::{postgres::PgPoolOptions, Pool, Postgres};
 use std::time::Duration;
+use tracing::warn;
 
 pub type Db = Pool<Postgres>;
 
@@ -12,3 +13,23 @@ pub async fn init_db(database_url: &str) -> anyhow::Result<Db> {
 
     Ok(pool)
 }
+
+/// Connect, retrying with backoff until `wait` has elapsed
+///
+/// In compose/CI the control plane often starts before Postgres accepts
+/// connections; this gates startup on the database instead of crashing.
+pub async fn init_db_with_retry(database_url: &str, wait: Duration) -> anyhow::Result<Db> {
+    let deadline = tokio::time::Instant::now() + wait;
+    let mut delay = Duration::from_millis(500);
+    loop {
+        match init_db(database_url).await {
+            Ok(pool) => return Ok(pool),
+            Err(e) if tokio::time::Instant::now() + delay < deadline => {
+                warn!("Database not ready ({}), retrying in {:?}", e, delay);
+                tokio::time::sleep(delay).await;
+                delay = (delay * 2).min(Duration::from_secs(5));
+            }
+            Err(e) => return Err(e),
+        }
+    }
+}
@@ -0,0 +1,376 @@
+//! Drawdown episodes from the equity series
+//!
+//! An episode starts at an equity peak, bottoms out at its trough and ends at
+//! the first point back at or above the peak. [`DrawdownTracker`] walks the
+//! metrics in timestamp order and is persisted per bot between passes, so
+//! each pass only reads metrics newer than the last one it saw. Episodes
+//! shallower than [`MIN_DEPTH_PCT`] are treated as noise and never stored.
+//! Points that arrive with a timestamp older than the cursor (late backfill)
+//! aren't revisited.
+
+use bigdecimal::BigDecimal;
+use chrono::{DateTime, Duration, Utc};
+use rust_decimal::Decimal;
+use serde::Serialize;
+use tracing::{error, info};
+use uuid::Uuid;
+
+use crate::models::{try_bigdecimal_from_decimal, try_decimal_from_bigdecimal};
+
+/// Shallowest drawdown worth recording, in percent of the peak
+pub const MIN_DEPTH_PCT: Decimal = Decimal::ONE;
+
+/// Metrics read per query while catching a bot up
+const SCAN_BATCH: i64 = 5_000;
+
+/// A drawdown from `started_at` (the peak) until `recovered_at`
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DrawdownEpisode {
+    pub started_at: DateTime<Utc>,
+    pub peak_equity: Decimal,
+    pub trough_at: DateTime<Utc>,
+    pub trough_equity: Decimal,
+    /// `None` while equity is still under the peak
+    pub recovered_at: Option<DateTime<Utc>>,
+    /// Peak-to-trough loss in percent of the peak
+    pub depth_pct: Decimal,
+    /// Peak to recovery, or to now for an open episode
+    pub duration_secs: i64,
+}
+
+fn depth_pct(peak: Decimal, trough: Decimal) -> Decimal {
+    if peak <= Decimal::ZERO {
+        return Decimal::ZERO;
+    }
+    ((peak - trough) / peak * Decimal::from(100)).round_dp(2)
+}
+
+/// Running peak and trough of one bot's equity
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DrawdownTracker {
+    pub peak_at: DateTime<Utc>,
+    pub peak_equity: Decimal,
+    pub trough_at: DateTime<Utc>,
+    pub trough_equity: Decimal,
+}
+
+impl DrawdownTracker {
+    pub fn new(at: DateTime<Utc>, equity: Decimal) -> Self {
+        Self {
+            peak_at: at,
+            peak_equity: equity,
+            trough_at: at,
+            trough_equity: equity,
+        }
+    }
+
+    fn episode(&self, recovered_at: Option<DateTime<Utc>>, now: DateTime<Utc>) -> DrawdownEpisode {
+        DrawdownEpisode {
+            started_at: self.peak_at,
+            peak_equity: self.peak_equity,
+            trough_at: self.trough_at,
+            trough_equity: self.trough_equity,
+            recovered_at,
+            depth_pct: depth_pct(self.peak_equity, self.trough_equity),
+            duration_secs: (recovered_at.unwrap_or(now) - self.peak_at).num_seconds(),
+        }
+    }
+
+    /// Feed the next point; returns the episode this point recovered from,
+    /// if it was deep enough to keep
+    pub fn push(&mut self, at: DateTime<Utc>, equity: Decimal) -> Option<DrawdownEpisode> {
+        if equity >= self.peak_equity {
+            let recovered = self.open_episode(at).map(|_| self.episode(Some(at), at));
+            *self = Self::new(at, equity);
+            return recovered;
+        }
+        if equity < self.trough_equity {
+            self.trough_at = at;
+            self.trough_equity = equity;
+        }
+        None
+    }
+
+    /// The drawdown in progress, if it's deep enough to keep
+    pub fn open_episode(&self, now: DateTime<Utc>) -> Option<DrawdownEpisode> {
+        let episode = self.episode(None, now);
+        (episode.depth_pct >= MIN_DEPTH_PCT).then_some(episode)
+    }
+}
+
+/// Every episode in `points` (oldest first), the open one last
+pub fn detect_episodes(
+    points: &[(DateTime<Utc>, Decimal)],
+    now: DateTime<Utc>,
+) -> Vec<DrawdownEpisode> {
+    let Some(&(first_at, first_equity)) = points.first() else {
+        return Vec::new();
+    };
+    let mut tracker = DrawdownTracker::new(first_at, first_equity);
+    let mut episodes: Vec<DrawdownEpisode> = points[1..]
+        .iter()
+        .filter_map(|&(at, equity)| tracker.push(at, equity))
+        .collect();
+    episodes.extend(tracker.open_episode(now));
+    episodes
+}
+
+#[derive(sqlx::FromRow)]
+struct EpisodeRow {
+    started_at: DateTime<Utc>,
+    peak_equity: BigDecimal,
+    trough_at: DateTime<Utc>,
+    trough_equity: BigDecimal,
+    recovered_at: Option<DateTime<Utc>>,
+    depth_pct: BigDecimal,
+}
+
+impl EpisodeRow {
+    fn episode(self, now: DateTime<Utc>) -> Option<DrawdownEpisode> {
+        Some(DrawdownEpisode {
+            started_at: self.started_at,
+            peak_equity: try_decimal_from_bigdecimal(&self.peak_equity)?,
+            trough_at: self.trough_at,
+            trough_equity: try_decimal_from_bigdecimal(&self.trough_equity)?,
+            recovered_at: self.recovered_at,
+            depth_pct: try_decimal_from_bigdecimal(&self.depth_pct)?,
+            duration_secs: (self.recovered_at.unwrap_or(now) - self.started_at).num_seconds(),
+        })
+    }
+}
+
+#[derive(sqlx::FromRow)]
+struct CursorRow {
+    peak_at: DateTime<Utc>,
+    peak_equity: BigDecimal,
+    trough_at: DateTime<Utc>,
+    trough_equity: BigDecimal,
+    scanned_to: DateTime<Utc>,
+}
+
+/// Episodes that were open at any point since `since`, oldest first
+pub async fn episodes_since(
+    pool: &sqlx::PgPool,
+    bot_id: Uuid,
+    since: DateTime<Utc>,
+) -> anyhow::Result<Vec<DrawdownEpisode>> {
+    let rows: Vec<EpisodeRow> = sqlx::query_as(
+        "SELECT started_at, peak_equity, trough_at, trough_equity, recovered_at, depth_pct \
+         FROM drawdown_episodes \
+         WHERE bot_id = $1 AND (recovered_at IS NULL OR recovered_at > $2) \
+         ORDER BY started_at",
+    )
+    .bind(bot_id)
+    .bind(since)
+    .fetch_all(pool)
+    .await?;
+    let now = Utc::now();
+    Ok(rows
+        .into_iter()
+        .filter_map(|row| row.episode(now))
+        .collect())
+}
+
+async fn upsert_episode(
+    pool: &sqlx::PgPool,
+    bot_id: Uuid,
+    episode: &DrawdownEpisode,
+) -> anyhow::Result<()> {
+    sqlx::query(
+        r#"
+        INSERT INTO drawdown_episodes
+            (bot_id, started_at, peak_equity, trough_at, trough_equity, recovered_at, depth_pct)
+        VALUES ($1, $2, $3, $4, $5, $6, $7)
+        ON CONFLICT (bot_id, started_at) DO UPDATE SET
+            trough_at = EXCLUDED.trough_at,
+            trough_equity = EXCLUDED.trough_equity,
+            recovered_at = EXCLUDED.recovered_at,
+            depth_pct = EXCLUDED.depth_pct
+        "#,
+    )
+    .bind(bot_id)
+    .bind(episode.started_at)
+    .bind(try_bigdecimal_from_decimal(&episode.peak_equity))
+    .bind(episode.trough_at)
+    .bind(try_bigdecimal_from_decimal(&episode.trough_equity))
+    .bind(episode.recovered_at)
+    .bind(try_bigdecimal_from_decimal(&episode.depth_pct))
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Feed a bot's new metrics through its tracker and store what it finds;
+/// returns how many metrics were read
+pub async fn refresh_bot(pool: &sqlx::PgPool, bot_id: Uuid) -> anyhow::Result<usize> {
+    let cursor: Option<CursorRow> = sqlx::query_as(
+        "SELECT peak_at, peak_equity, trough_at, trough_equity, scanned_to \
+         FROM drawdown_cursors WHERE bot_id = $1",
+    )
+    .bind(bot_id)
+    .fetch_optional(pool)
+    .await?;
+    let mut scanned_to = cursor.as_ref().map(|c| c.scanned_to);
+    let mut tracker = cursor.and_then(|c| {
+        Some(DrawdownTracker {
+            peak_at: c.peak_at,
+            peak_equity: try_decimal_from_bigdecimal(&c.peak_equity)?,
+            trough_at: c.trough_at,
+            trough_equity: try_decimal_from_bigdecimal(&c.trough_equity)?,
+        })
+    });
+
+    let mut read = 0;
+    loop {
+        let rows: Vec<(DateTime<Utc>, BigDecimal)> = sqlx::query_as(
+            "SELECT timestamp, equity FROM metrics \
+             WHERE bot_id = $1 AND ($2::timestamptz IS NULL OR timestamp > $2) \
+             ORDER BY timestamp LIMIT $3",
+        )
+        .bind(bot_id)
+        .bind(scanned_to)
+        .bind(SCAN_BATCH)
+        .fetch_all(pool)
+        .await?;
+        let Some((last_at, _)) = rows.last() else {
+            break;
+        };
+        scanned_to = Some(*last_at);
+        read += rows.len();
+
+        for (at, equity) in &rows {
+            let Some(equity) = try_decimal_from_bigdecimal(equity) else {
+                continue;
+            };
+            let Some(tracker) = tracker.as_mut() else {
+                tracker = Some(DrawdownTracker::new(*at, equity));
+                continue;
+            };
+            if let Some(episode) = tracker.push(*at, equity) {
+                upsert_episode(pool, bot_id, &episode).await?;
+            }
+        }
+        if (rows.len() as i64) < SCAN_BATCH {
+            break;
+        }
+    }
+
+    let Some(tracker) = tracker.filter(|_| read > 0) else {
+        return Ok(0);
+    };
+    if let Some(open) = tracker.open_episode(Utc::now()) {
+        upsert_episode(pool, bot_id, &open).await?;
+    }
+    sqlx::query(
+        r#"
+        INSERT INTO drawdown_cursors (bot_id, peak_at, peak_equity, trough_at, trough_equity, scanned_to)
+        VALUES ($1, $2, $3, $4, $5, $6)
+        ON CONFLICT (bot_id) DO UPDATE SET
+            peak_at = EXCLUDED.peak_at,
+            peak_equity = EXCLUDED.peak_equity,
+            trough_at = EXCLUDED.trough_at,
+            trough_equity = EXCLUDED.trough_equity,
+            scanned_to = EXCLUDED.scanned_to
+        "#,
+    )
+    .bind(bot_id)
+    .bind(tracker.peak_at)
+    .bind(try_bigdecimal_from_decimal(&tracker.peak_equity))
+    .bind(tracker.trough_at)
+    .bind(try_bigdecimal_from_decimal(&tracker.trough_equity))
+    .bind(scanned_to)
+    .execute(pool)
+    .await?;
+    Ok(read)
+}
+
+/// Spawn the detection pass over bots that reported metrics recently
+pub fn spawn_detection_task(pool: sqlx::PgPool) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(std::time::Duration::from_secs(600));
+
+        loop {
+            interval.tick().await;
+
+            let since = Utc::now() - Duration::hours(1);
+            let bots: Vec<Uuid> = match sqlx::query_scalar(
+                "SELECT DISTINCT bot_id FROM metrics WHERE timestamp > $1",
+            )
+            .bind(since)
+            .fetch_all(&pool)
+            .await
+            {
+                Ok(bots) => bots,
+                Err(e) => {
+                    error!("Drawdown detection: failed to list bots: {}", e);
+                    continue;
+                }
+            };
+
+            let mut read = 0;
+            for bot_id in bots {
+                match refresh_bot(&pool, bot_id).await {
+                    Ok(n) => read += n,
+                    Err(e) => error!("Drawdown detection failed for bot {}: {}", bot_id, e),
+                }
+            }
+            if read > 0 {
+                info!("Drawdown detection: scanned {} metrics", read);
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_detect_episodes() {
+        let t = |hour: i64| Utc.timestamp_opt(hour * 3600, 0).unwrap();
+        let points: Vec<(DateTime<Utc>, Decimal)> = [
+            (0, 1000),
+            (1, 1100),
+            (2, 990), // 10% off the 1100 peak
+            (3, 1050),
+            (4, 1100), // back at the peak
+            (5, 1095), // under the 1% threshold
+            (6, 1200),
+            (7, 900), // 25%, still open
+            (8, 950),
+        ]
+        .iter()
+        .map(|&(h, e)| (t(h), Decimal::from(e)))
+        .collect();
+
+        let episodes = detect_episodes(&points, t(10));
+        assert_eq!(episodes.len(), 2);
+
+        let first = &episodes[0];
+        assert_eq!((first.started_at, first.trough_at), (t(1), t(2)));
+        assert_eq!(first.recovered_at, Some(t(4)));
+        assert_eq!(first.depth_pct, Decimal::from(10));
+        assert_eq!(first.duration_secs, 3 * 3600);
+
+        let open = &episodes[1];
+        assert_eq!(open.peak_equity, Decimal::from(1200));
+        assert_eq!(open.trough_equity, Decimal::from(900));
+        assert_eq!(open.recovered_at, None);
+        assert_eq!(open.depth_pct, Decimal::from(25));
+        assert_eq!(open.duration_secs, 4 * 3600);
+
+        // A tracker restored from its cursor finds the same recovery
+        let mut resumed = DrawdownTracker {
+            peak_at: t(1),
+            peak_equity: Decimal::from(1100),
+            trough_at: t(2),
+            trough_equity: Decimal::from(990),
+        };
+        assert_eq!(resumed.push(t(3), Decimal::from(1050)), None);
+        assert_eq!(
+            resumed.push(t(4), Decimal::from(1100)).as_ref(),
+            Some(first)
+        );
+    }
+}
@@ -0,0 +1,239 @@
+//! Droplet lifecycle tracking and infrastructure cost accounting
+//!
+//! Every droplet we create gets a row in `droplets` with its size, region and
+//! the monthly list price at creation time. Costs are estimates built from
+//! that snapshot: DigitalOcean bills by the started hour at monthly / 672,
+//! capped at the monthly price.
+
+use bigdecimal::BigDecimal;
+use chrono::{DateTime, Duration, Utc};
+use rust_decimal::Decimal;
+use serde::Serialize;
+use std::collections::HashMap;
+use tracing::warn;
+use uuid::Uuid;
+
+use crate::models::{try_bigdecimal_from_decimal, try_decimal_from_bigdecimal};
+
+/// Billable hours in a DigitalOcean month (hourly rate = monthly / 672)
+pub const HOURS_PER_BILLING_MONTH: i64 = 672;
+
+/// Monthly list price (USD) for the droplet sizes we provision
+pub fn monthly_price_usd(size: &str) -> Option<Decimal> {
+    let cents = match size {
+        "s-1vcpu-512mb-10gb" => 400,
+        "s-1vcpu-1gb" => 600,
+        "s-1vcpu-2gb" => 1200,
+        "s-2vcpu-2gb" => 1800,
+        "s-2vcpu-4gb" => 2400,
+        "s-4vcpu-8gb" => 4800,
+        _ => return None,
+    };
+    Some(Decimal::new(cents, 2))
+}
+
+/// Estimated cost of a droplet priced at `monthly` running from `start` to `end`
+///
+/// Partial hours bill as full hours; each 672-hour block is capped at the
+/// monthly price.
+pub fn runtime_cost_usd(monthly: Decimal, start: DateTime<Utc>, end: DateTime<Utc>) -> Decimal {
+    let secs = (end - start).num_seconds();
+    if secs <= 0 {
+        return Decimal::ZERO;
+    }
+    let hours = (secs + 3599) / 3600;
+    let full_months = hours / HOURS_PER_BILLING_MONTH;
+    let remaining_hours = hours % HOURS_PER_BILLING_MONTH;
+
+    monthly * Decimal::from(full_months)
+        + monthly * Decimal::from(remaining_hours) / Decimal::from(HOURS_PER_BILLING_MONTH)
+}
+
+/// Record a newly created droplet
+pub async fn record_droplet_created(
+    pool: &sqlx::PgPool,
+    bot_id: Uuid,
+    droplet_id: i64,
+    name: &str,
+    region: &str,
+    size: &str,
+) -> anyhow::Result<()> {
+    let price = monthly_price_usd(size);
+    if price.is_none() {
+        warn!(
+            "No pricing for droplet size '{}'; droplet {} will not be costed",
+            size, droplet_id
+        );
+    }
+
+    sqlx::query(
+        r#"
+        INSERT INTO droplets (id, bot_id, name, region, size, monthly_price_usd)
+        VALUES ($1, $2, $3, $4, $5, $6)
+        ON CONFLICT (id) DO NOTHING
+        "#,
+    )
+    .bind(droplet_id)
+    .bind(bot_id)
+    .bind(name)
+    .bind(region)
+    .bind(size)
+    .bind(price.as_ref().and_then(try_bigdecimal_from_decimal))
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Mark a droplet as destroyed (no-op if already marked or never recorded)
+pub async fn record_droplet_destroyed(pool: &sqlx::PgPool, droplet_id: i64) -> anyhow::Result<()> {
+    sqlx::query("UPDATE droplets SET destroyed_at = NOW() WHERE id = $1 AND destroyed_at IS NULL")
+        .bind(droplet_id)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+/// Infrastructure cost attributed to one bot
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct BotInfraCost {
+    pub bot_id: Uuid,
+    pub user_id: Option<Uuid>,
+    pub active_droplets: i64,
+    /// Monthly list price of the droplets currently running
+    pub monthly_run_rate_usd: Decimal,
+    pub last_30d_cost_usd: Decimal,
+    pub lifetime_cost_usd: Decimal,
+}
+
+impl BotInfraCost {
+    fn empty(bot_id: Uuid, user_id: Option<Uuid>) -> Self {
+        Self {
+            bot_id,
+            user_id,
+            active_droplets: 0,
+            monthly_run_rate_usd: Decimal::ZERO,
+            last_30d_cost_usd: Decimal::ZERO,
+            lifetime_cost_usd: Decimal::ZERO,
+        }
+    }
+}
+
+#[derive(Debug, sqlx::FromRow)]
+struct DropletCostRow {
+    bot_id: Uuid,
+    user_id: Option<Uuid>,
+    monthly_price_usd: Option<BigDecimal>,
+    created_at: DateTime<Utc>,
+    destroyed_at: Option<DateTime<Utc>>,
+}
+
+/// Fold droplet rows into per-bot costs as of `now`
+fn accumulate_costs(rows: Vec<DropletCostRow>, now: DateTime<Utc>) -> Vec<BotInfraCost> {
+    let window_start = now - Duration::days(30);
+    let mut by_bot: HashMap<Uuid, BotInfraCost> = HashMap::new();
+
+    for row in rows {
+        let cost = by_bot
+            .entry(row.bot_id)
+            .or_insert_with(|| BotInfraCost::empty(row.bot_id, row.user_id));
+        let monthly = row
+            .monthly_price_usd
+            .as_ref()
+            .and_then(try_decimal_from_bigdecimal)
+            .unwrap_or(Decimal::ZERO);
+        let end = row.destroyed_at.unwrap_or(now);
+
+        if row.destroyed_at.is_none() {
+            cost.active_droplets += 1;
+            cost.monthly_run_rate_usd += monthly;
+        }
+        cost.lifetime_cost_usd += runtime_cost_usd(monthly, row.created_at, end);
+        cost.last_30d_cost_usd += runtime_cost_usd(monthly, row.created_at.max(window_start), end);
+    }
+
+    let mut costs: Vec<BotInfraCost> = by_bot.into_values().collect();
+    costs.sort_by_key(|c| std::cmp::Reverse(c.monthly_run_rate_usd));
+    costs
+}
+
+const DROPLET_COST_QUERY: &str = r#"
+    SELECT d.bot_id, b.user_id, d.monthly_price_usd, d.created_at, d.destroyed_at
+    FROM droplets d
+    LEFT JOIN bots b ON b.id = d.bot_id
+    WHERE d.bot_id IS NOT NULL
+"#;
+
+/// Infrastructure cost for a single bot
+pub async fn bot_infra_cost(pool: &sqlx::PgPool, bot_id: Uuid) -> anyhow::Result<BotInfraCost> {
+    let rows: Vec<DropletCostRow> =
+        sqlx::query_as(&format!("{} AND d.bot_id = $1", DROPLET_COST_QUERY))
+            .bind(bot_id)
+            .fetch_all(pool)
+            .await?;
+
+    Ok(accumulate_costs(rows, Utc::now())
+        .pop()
+        .unwrap_or_else(|| BotInfraCost::empty(bot_id, None)))
+}
+
+/// Infrastructure cost for every bot that has had a droplet, highest run rate first
+pub async fn all_bot_infra_costs(pool: &sqlx::PgPool) -> anyhow::Result<Vec<BotInfraCost>> {
+    let rows: Vec<DropletCostRow> = sqlx::query_as(DROPLET_COST_QUERY).fetch_all(pool).await?;
+    Ok(accumulate_costs(rows, Utc::now()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn at(hour: i64) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2026, 1, 1, 0, 0, 0).unwrap() + Duration::hours(hour)
+    }
+
+    #[test]
+    fn test_runtime_cost_bills_started_hours_and_caps_monthly() {
+        let monthly = monthly_price_usd("s-1vcpu-2gb").unwrap();
+
+        assert_eq!(runtime_cost_usd(monthly, at(0), at(0)), Decimal::ZERO);
+        // One second into the hour bills the whole hour
+        let one_hour = runtime_cost_usd(monthly, at(0), at(0) + Duration::seconds(1));
+        assert_eq!(one_hour, monthly / Decimal::from(HOURS_PER_BILLING_MONTH));
+        // 672 hours is exactly one billing month
+        let month = runtime_cost_usd(monthly, at(0), at(HOURS_PER_BILLING_MONTH));
+        assert_eq!(month, monthly);
+    }
+
+    #[test]
+    fn test_accumulate_costs_per_bot() {
+        let bot = Uuid::new_v4();
+        let monthly = try_bigdecimal_from_decimal(&Decimal::new(1200, 2));
+        let now = at(24 * 60);
+        let rows = vec![
+            // Destroyed long before the 30 day window
+            DropletCostRow {
+                bot_id: bot,
+                user_id: None,
+                monthly_price_usd: monthly.clone(),
+                created_at: at(0),
+                destroyed_at: Some(at(HOURS_PER_BILLING_MONTH)),
+            },
+            // Still running
+            DropletCostRow {
+                bot_id: bot,
+                user_id: None,
+                monthly_price_usd: monthly,
+                created_at: now - Duration::hours(HOURS_PER_BILLING_MONTH),
+                destroyed_at: None,
+            },
+        ];
+
+        let costs = accumulate_costs(rows, now);
+        assert_eq!(costs.len(), 1);
+        assert_eq!(costs[0].active_droplets, 1);
+        assert_eq!(costs[0].monthly_run_rate_usd, Decimal::new(1200, 2));
+        assert_eq!(costs[0].lifetime_cost_usd, Decimal::new(2400, 2));
+        assert_eq!(costs[0].last_30d_cost_usd, Decimal::new(1200, 2));
+    }
+}
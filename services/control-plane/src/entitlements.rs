@@ -0,0 +1,441 @@
+//! Subscription entitlements and the billing sync worker
+//!
+//! `subscription_middleware` resolves each user's plan through
+//! `EntitlementCache`. Payment webhooks (`handlers::billing`) update the
+//! `subscriptions` row and publish a `SubscriptionChange`; the worker started
+//! by `spawn_sync_worker` reloads the entitlement, pauses or resumes live
+//! bots, and tells the user what changed. It also sweeps periodically so a
+//! grace period runs out even if no further webhook arrives.
+//!
+//! A past-due subscription keeps its plan for `payment_grace_period_hours`.
+//! Once that passes (or the subscription is cancelled) the user drops to the
+//! free tier: live bots are paused and marked `billing_paused_at`, and are
+//! resumed automatically when a payment brings the subscription back.
+
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
+use tokio::sync::broadcast;
+use tracing::{error, info, warn};
+use uuid::Uuid;
+
+use crate::alerting::{AlertSeverity, AlertType};
+use crate::middleware::subscription::SubscriptionTier;
+use crate::AppState;
+
+/// How long a cached entitlement is trusted without a webhook
+const CACHE_TTL: Duration = Duration::from_secs(60);
+
+/// How often expired grace periods are swept
+const SWEEP_INTERVAL_SECS: u64 = 300;
+
+/// Mirrors the `subscription_status` enum
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SubscriptionStatus {
+    Active,
+    PastDue,
+    Cancelled,
+}
+
+impl SubscriptionStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SubscriptionStatus::Active => "active",
+            SubscriptionStatus::PastDue => "past_due",
+            SubscriptionStatus::Cancelled => "cancelled",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "active" => Some(SubscriptionStatus::Active),
+            "past_due" => Some(SubscriptionStatus::PastDue),
+            "cancelled" => Some(SubscriptionStatus::Cancelled),
+            _ => None,
+        }
+    }
+}
+
+/// What a user's subscription currently allows
+#[derive(Debug, Clone)]
+pub struct Entitlement {
+    pub tier: SubscriptionTier,
+    /// None without a current subscription
+    pub status: Option<SubscriptionStatus>,
+    pub expires_at: Option<DateTime<Utc>>,
+    /// Past due but still inside the grace period, until this time
+    pub grace_until: Option<DateTime<Utc>>,
+}
+
+impl Entitlement {
+    fn free() -> Self {
+        Self {
+            tier: SubscriptionTier::Free,
+            status: None,
+            expires_at: None,
+            grace_until: None,
+        }
+    }
+}
+
+/// The plan a subscription row grants at `now`, and the end of its grace period
+///
+/// Past-due rows without `past_due_since` (written before billing sync
+/// existed) get no grace.
+pub fn resolve_tier(
+    status: SubscriptionStatus,
+    max_bots: i32,
+    past_due_since: Option<DateTime<Utc>>,
+    now: DateTime<Utc>,
+    grace: chrono::Duration,
+) -> (SubscriptionTier, Option<DateTime<Utc>>) {
+    let paid = if max_bots >= 20 {
+        SubscriptionTier::Enterprise
+    } else {
+        SubscriptionTier::Pro
+    };
+    match status {
+        SubscriptionStatus::Active => (paid, None),
+        SubscriptionStatus::PastDue => match past_due_since.map(|since| since + grace) {
+            Some(until) if now < until => (paid, Some(until)),
+            _ => (SubscriptionTier::Free, None),
+        },
+        SubscriptionStatus::Cancelled => (SubscriptionTier::Free, None),
+    }
+}
+
+/// Read a user's entitlement from the `subscriptions` table, past-due
+/// subscriptions keeping their plan for `grace`
+pub async fn load(
+    pool: &sqlx::PgPool,
+    user_id: Uuid,
+    grace: chrono::Duration,
+) -> Result<Entitlement, sqlx::Error> {
+    let row = sqlx::query_as::<_, (String, i32, DateTime<Utc>, Option<DateTime<Utc>>)>(
+        r#"
+        SELECT status::text, max_bots, current_period_end, past_due_since
+        FROM subscriptions
+        WHERE user_id = $1 AND current_period_end > NOW()
+        ORDER BY current_period_end DESC
+        LIMIT 1
+        "#,
+    )
+    .bind(user_id)
+    .fetch_optional(pool)
+    .await?;
+
+    let Some((status, max_bots, period_end, past_due_since)) = row else {
+        return Ok(Entitlement::free());
+    };
+    let Some(status) = SubscriptionStatus::parse(&status) else {
+        return Ok(Entitlement::free());
+    };
+    let (tier, grace_until) = resolve_tier(status, max_bots, past_due_since, Utc::now(), grace);
+    Ok(Entitlement {
+        tier,
+        status: Some(status),
+        expires_at: Some(period_end),
+        grace_until,
+    })
+}
+
+/// A subscription status change applied from a payment webhook
+#[derive(Debug, Clone)]
+pub struct SubscriptionChange {
+    pub user_id: Uuid,
+    pub previous: Option<SubscriptionStatus>,
+    pub status: SubscriptionStatus,
+}
+
+/// Short-lived cache of entitlements, invalidated by payment webhooks
+#[derive(Clone)]
+pub struct EntitlementCache {
+    entries: Arc<RwLock<HashMap<Uuid, (Entitlement, Instant)>>>,
+    changes: broadcast::Sender<SubscriptionChange>,
+}
+
+impl Default for EntitlementCache {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl EntitlementCache {
+    pub fn new() -> Self {
+        let (changes, _) = broadcast::channel(256);
+        Self {
+            entries: Arc::new(RwLock::new(HashMap::new())),
+            changes,
+        }
+    }
+
+    pub fn get(&self, user_id: Uuid) -> Option<Entitlement> {
+        let entries = self.entries.read().unwrap();
+        entries
+            .get(&user_id)
+            .filter(|(_, at)| at.elapsed() < CACHE_TTL)
+            .map(|(e, _)| e.clone())
+    }
+
+    pub fn insert(&self, user_id: Uuid, entitlement: Entitlement) {
+        let mut entries = self.entries.write().unwrap();
+        entries.retain(|_, (_, at)| at.elapsed() < CACHE_TTL);
+        entries.insert(user_id, (entitlement, Instant::now()));
+    }
+
+    pub fn invalidate(&self, user_id: Uuid) {
+        self.entries.write().unwrap().remove(&user_id);
+    }
+
+    /// Drop the cached entitlement and hand the change to the sync worker
+    pub fn publish(&self, change: SubscriptionChange) {
+        self.invalidate(change.user_id);
+        // No receiver only means the worker isn't running (tests, tooling)
+        let _ = self.changes.send(change);
+    }
+
+    fn subscribe(&self) -> broadcast::Receiver<SubscriptionChange> {
+        self.changes.subscribe()
+    }
+}
+
+/// Apply subscription changes as they arrive and sweep expired grace periods
+pub fn spawn_sync_worker(state: Arc<AppState>) {
+    let mut changes = state.entitlements.subscribe();
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(SWEEP_INTERVAL_SECS));
+        loop {
+            tokio::select! {
+                change = changes.recv() => match change {
+                    Ok(change) => {
+                        if let Err(e) = apply_change(&state, &change).await {
+                            error!("Entitlement sync failed for {}: {}", change.user_id, e);
+                        }
+                    }
+                    Err(broadcast::error::RecvError::Lagged(n)) => {
+                        warn!("Entitlement sync skipped {} changes; sweeping", n);
+                        sweep_expired_grace(&state).await;
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                },
+                _ = interval.tick() => sweep_expired_grace(&state).await,
+            }
+        }
+    });
+}
+
+async fn apply_change(state: &AppState, change: &SubscriptionChange) -> Result<(), sqlx::Error> {
+    let grace = state.settings.current().billing.grace_period();
+    let entitlement = load(&state.db, change.user_id, grace).await?;
+    state
+        .entitlements
+        .insert(change.user_id, entitlement.clone());
+    let (paused, resumed) = enforce(state, change.user_id, &entitlement).await?;
+
+    let detail = match (change.status, entitlement.grace_until) {
+        (SubscriptionStatus::PastDue, Some(until)) => format!(
+            "Payment failed. Live trading continues until {} UTC; update your payment method to keep it running.",
+            until.format("%Y-%m-%d %H:%M")
+        ),
+        (SubscriptionStatus::PastDue, None) => {
+            format!("Payment is past due. {} live bot(s) paused.", paused)
+        }
+        (SubscriptionStatus::Cancelled, _) => {
+            format!("Subscription ended. {} live bot(s) paused.", paused)
+        }
+        (SubscriptionStatus::Active, _) if change.previous == Some(SubscriptionStatus::Active) => {
+            format!("Plan updated to {:?}.", entitlement.tier)
+        }
+        (SubscriptionStatus::Active, _) if resumed > 0 => {
+            format!("Subscription active. {} live bot(s) resumed.", resumed)
+        }
+        (SubscriptionStatus::Active, _) => format!("Subscription active ({:?}).", entitlement.tier),
+    };
+    notify(state, change.user_id, change.status, detail).await;
+    Ok(())
+}
+
+/// Pause live bots of users whose grace period has run out
+async fn sweep_expired_grace(state: &AppState) {
+    let grace_hours = state.settings.current().billing.payment_grace_period_hours as i32;
+    let users: Vec<(Uuid,)> = match sqlx::query_as(
+        r#"
+        SELECT DISTINCT s.user_id
+        FROM subscriptions s
+        JOIN bots b ON b.user_id = s.user_id
+        JOIN config_versions cv ON cv.id = b.desired_version_id
+        WHERE s.status = 'past_due'
+          AND s.past_due_since < NOW() - make_interval(hours => $1)
+          AND b.status = 'online'
+          AND cv.trading_mode = 'live'
+        "#,
+    )
+    .bind(grace_hours)
+    .fetch_all(&state.db)
+    .await
+    {
+        Ok(users) => users,
+        Err(e) => {
+            error!("Failed to find expired grace periods: {}", e);
+            return;
+        }
+    };
+
+    for (user_id,) in users {
+        state.entitlements.invalidate(user_id);
+        let grace = state.settings.current().billing.grace_period();
+        let result = match load(&state.db, user_id, grace).await {
+            Ok(entitlement) => enforce(state, user_id, &entitlement).await,
+            Err(e) => Err(e),
+        };
+        match result {
+            Ok((paused, _)) if paused > 0 => {
+                notify(
+                    state,
+                    user_id,
+                    SubscriptionStatus::PastDue,
+                    format!(
+                        "Grace period ended without payment. {} live bot(s) paused.",
+                        paused
+                    ),
+                )
+                .await;
+            }
+            Ok(_) => {}
+            Err(e) => error!("Failed to pause bots for {}: {}", user_id, e),
+        }
+    }
+}
+
+/// Pause or resume the user's live bots to match their plan
+///
+/// Returns how many bots were paused and resumed.
+async fn enforce(
+    state: &AppState,
+    user_id: Uuid,
+    entitlement: &Entitlement,
+) -> Result<(usize, usize), sqlx::Error> {
+    if entitlement.tier.has_feature("live_trading") {
+        let resumed: Vec<(Uuid,)> = sqlx::query_as(
+            "UPDATE bots SET status = 'online', billing_paused_at = NULL, updated_at = NOW() \
+             WHERE user_id = $1 AND billing_paused_at IS NOT NULL AND status = 'paused' \
+               AND NOT EXISTS (SELECT 1 FROM kill_switches k WHERE k.user_id = bots.user_id) \
+             RETURNING id",
+        )
+        .bind(user_id)
+        .fetch_all(&state.db)
+        .await?;
+        for (bot_id,) in &resumed {
+            record_status_event(state, *bot_id, "Resumed: subscription payment received").await;
+        }
+        if !resumed.is_empty() {
+            info!(
+                "Resumed {} billing-paused bots for {}",
+                resumed.len(),
+                user_id
+            );
+        }
+        return Ok((0, resumed.len()));
+    }
+
+    // The runner sees `desired_status: paused` on its next sync
+    let paused: Vec<(Uuid,)> = sqlx::query_as(
+        r#"
+        UPDATE bots b SET status = 'paused', billing_paused_at = NOW(), updated_at = NOW()
+        FROM config_versions cv
+        WHERE cv.id = b.desired_version_id
+          AND cv.trading_mode = 'live'
+          AND b.user_id = $1
+          AND b.status = 'online'
+        RETURNING b.id
+        "#,
+    )
+    .bind(user_id)
+    .fetch_all(&state.db)
+    .await?;
+    for (bot_id,) in &paused {
+        record_status_event(state, *bot_id, "Paused: subscription payment is past due").await;
+    }
+    if !paused.is_empty() {
+        info!(
+            "Paused {} live bots for {} (billing)",
+            paused.len(),
+            user_id
+        );
+    }
+    Ok((paused.len(), 0))
+}
+
+async fn record_status_event(state: &AppState, bot_id: Uuid, message: &str) {
+    if let Err(e) = sqlx::query(
+        "INSERT INTO events (bot_id, event_type, message, metadata) VALUES ($1, $2, $3, $4)",
+    )
+    .bind(bot_id)
+    .bind("status_change")
+    .bind(message)
+    .bind(serde_json::json!({"reason": "billing"}))
+    .execute(&state.db)
+    .await
+    {
+        warn!("Failed to record billing event for bot {}: {}", bot_id, e);
+    }
+}
+
+async fn notify(state: &AppState, user_id: Uuid, status: SubscriptionStatus, detail: String) {
+    let severity = match status {
+        SubscriptionStatus::Active => AlertSeverity::Info,
+        SubscriptionStatus::PastDue | SubscriptionStatus::Cancelled => AlertSeverity::Warning,
+    };
+    let user_id = user_id.to_string();
+    let alert = AlertType::SubscriptionChanged {
+        user_id: user_id.clone(),
+        status: status.as_str().to_string(),
+        detail,
+    };
+    crate::webhook::fire_alert_with_webhook(
+        &state.alerts,
+        &state.webhooks,
+        &alert,
+        severity,
+        Some(&user_id),
+    )
+    .await;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_past_due_keeps_plan_during_grace() {
+        let now = Utc::now();
+        let grace = chrono::Duration::hours(72);
+
+        assert_eq!(
+            resolve_tier(SubscriptionStatus::Active, 20, None, now, grace),
+            (SubscriptionTier::Enterprise, None)
+        );
+
+        let since = now - chrono::Duration::hours(10);
+        assert_eq!(
+            resolve_tier(SubscriptionStatus::PastDue, 4, Some(since), now, grace),
+            (SubscriptionTier::Pro, Some(since + grace))
+        );
+
+        // Grace over, or no record of when it started
+        let since = now - chrono::Duration::hours(73);
+        assert_eq!(
+            resolve_tier(SubscriptionStatus::PastDue, 4, Some(since), now, grace).0,
+            SubscriptionTier::Free
+        );
+        assert_eq!(
+            resolve_tier(SubscriptionStatus::PastDue, 4, None, now, grace).0,
+            SubscriptionTier::Free
+        );
+        assert_eq!(
+            resolve_tier(SubscriptionStatus::Cancelled, 20, None, now, grace).0,
+            SubscriptionTier::Free
+        );
+    }
+}
@@ -0,0 +1,167 @@
+//! Paging and filtering of a bot's event history
+//!
+//! `GET /bots/:id/events` pages newest first with a keyset cursor on
+//! `(created_at, id)` rather than an offset, so a page costs the same months
+//! back as it does today and events arriving while someone pages don't shift
+//! or repeat rows. The cursor is opaque to clients: the position of the last
+//! event served, base64 encoded, handed back as `next_cursor`.
+//!
+//! Events carry no stored severity; it follows from the event type
+//! ([`SEVERITY_SQL`]): failures are `error`, blocked trades, halts and
+//! breached limits `warning`, everything else `info`.
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD as BASE64, Engine};
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+use crate::db::Db;
+use crate::models::Event;
+
+/// Severities an event can have, least severe first
+pub const SEVERITIES: [&str; 3] = ["info", "warning", "error"];
+
+/// Default page size
+pub const DEFAULT_PAGE_SIZE: i64 = 100;
+
+/// Largest page a client can ask for
+pub const MAX_PAGE_SIZE: i64 = 500;
+
+/// The severity of an `events` row, as an SQL expression
+pub const SEVERITY_SQL: &str = "CASE \
+     WHEN event_type::text IN ('error', 'journal_chain_broken') \
+       OR event_type::text LIKE '%\\_failed' THEN 'error' \
+     WHEN event_type::text IN ('stop_triggered', 'trade_blocked', 'trading_halted', \
+       'insufficient_funding', 'bot_shutdown') \
+       OR event_type::text LIKE '%\\_breached' THEN 'warning' \
+     ELSE 'info' END";
+
+/// Position of an event in the newest-first order
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EventCursor {
+    pub created_at: DateTime<Utc>,
+    pub id: Uuid,
+}
+
+impl EventCursor {
+    pub fn of(event: &Event) -> Self {
+        Self {
+            created_at: event.created_at,
+            id: event.id,
+        }
+    }
+
+    pub fn encode(&self) -> String {
+        BASE64.encode(format!(
+            "{}|{}",
+            self.created_at.timestamp_micros(),
+            self.id
+        ))
+    }
+
+    /// None for anything [`EventCursor::encode`] didn't produce
+    pub fn decode(cursor: &str) -> Option<Self> {
+        let raw = String::from_utf8(BASE64.decode(cursor).ok()?).ok()?;
+        let (micros, id) = raw.split_once('|')?;
+        Some(Self {
+            created_at: DateTime::from_timestamp_micros(micros.parse().ok()?)?,
+            id: Uuid::parse_str(id).ok()?,
+        })
+    }
+}
+
+/// Which events to return, and from where
+#[derive(Debug, Clone, Default)]
+pub struct EventFilter {
+    /// Any of these types (all types when empty)
+    pub event_types: Vec<String>,
+    pub severity: Option<String>,
+    /// Inclusive
+    pub since: Option<DateTime<Utc>>,
+    /// Exclusive
+    pub until: Option<DateTime<Utc>>,
+    /// Only events older than this one
+    pub after: Option<EventCursor>,
+    pub limit: i64,
+}
+
+/// `trade_opened,trade_closed` as its types, ignoring blanks
+pub fn parse_event_types(raw: &str) -> Vec<String> {
+    raw.split(',')
+        .map(str::trim)
+        .filter(|t| !t.is_empty())
+        .map(String::from)
+        .collect()
+}
+
+/// One page of a bot's events, newest first, and the cursor of the next
+///
+/// The cursor is None on the last page.
+pub async fn list_events(
+    db: &Db,
+    bot_id: Uuid,
+    filter: &EventFilter,
+) -> Result<(Vec<Event>, Option<String>), sqlx::Error> {
+    let limit = filter.limit.clamp(1, MAX_PAGE_SIZE);
+    // One extra row says whether another page follows
+    let mut events = sqlx::query_as::<_, Event>(&format!(
+        "SELECT *, {severity} AS severity FROM events \
+         WHERE bot_id = $1 \
+         AND (cardinality($2::text[]) = 0 OR event_type::text = ANY($2)) \
+         AND ($3::text IS NULL OR {severity} = $3) \
+         AND ($4::timestamptz IS NULL OR created_at >= $4) \
+         AND ($5::timestamptz IS NULL OR created_at < $5) \
+         AND ($6::timestamptz IS NULL OR (created_at, id) < ($6, $7)) \
+         ORDER BY created_at DESC, id DESC \
+         LIMIT $8",
+        severity = SEVERITY_SQL
+    ))
+    .bind(bot_id)
+    .bind(&filter.event_types)
+    .bind(&filter.severity)
+    .bind(filter.since)
+    .bind(filter.until)
+    .bind(filter.after.map(|c| c.created_at))
+    .bind(filter.after.map(|c| c.id))
+    .bind(limit + 1)
+    .fetch_all(db)
+    .await?;
+
+    let next_cursor = if events.len() as i64 > limit {
+        events.truncate(limit as usize);
+        events.last().map(|e| EventCursor::of(e).encode())
+    } else {
+        None
+    };
+    Ok((events, next_cursor))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cursor_round_trip() {
+        let cursor = EventCursor {
+            created_at: DateTime::from_timestamp_micros(1_773_144_000_123_456).unwrap(),
+            id: Uuid::new_v4(),
+        };
+        let encoded = cursor.encode();
+        assert!(encoded
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_'));
+        assert_eq!(EventCursor::decode(&encoded), Some(cursor));
+
+        assert_eq!(EventCursor::decode("not a cursor"), None);
+        assert_eq!(EventCursor::decode(&BASE64.encode("12|not-a-uuid")), None);
+        assert_eq!(EventCursor::decode(&BASE64.encode("soon|")), None);
+    }
+
+    #[test]
+    fn test_parse_event_types() {
+        assert_eq!(
+            parse_event_types("trade_opened, trade_closed,,"),
+            vec!["trade_opened", "trade_closed"]
+        );
+        assert!(parse_event_types(" ").is_empty());
+    }
+}
@@ -1,7 +1,7 @@
 //! Admin handlers for platform configuration management
 
 use axum::{
-    extract::{ConnectInfo, State},
+    extract::{ConnectInfo, Path, Query, State},
     http::StatusCode,
     Extension, Json,
 };
@@ -12,7 +12,7 @@ use std::net::SocketAddr;
 use std::sync::Arc;
 use tracing::info;
 
-use crate::{middleware::AdminContext, models::*, AppState};
+use crate::{backfill, droplets, middleware::AdminContext, models::*, AppState};
 
 const MASKED_VALUE: &str = "********";
 
@@ -57,6 +57,7 @@ pub async fn list_config(
     Ok(Json(ConfigListResponse {
         configs: entries,
         categories,
+        settings: (*state.settings.current()).clone(),
     }))
 }
 
@@ -136,6 +137,14 @@ pub async fn update_config(
             }
         };
 
+        if let Err(e) = crate::settings::validate(&update.key, &update.value) {
+            failed.push(ConfigUpdateError {
+                key: update.key,
+                error: e,
+            });
+            continue;
+        }
+
         // Encrypt value if needed
         let new_value = if config.encrypted && !update.value.is_empty() {
             match state.secrets.encrypt(&update.value) {
@@ -202,6 +211,7 @@ pub async fn update_config(
         updated.push(update.key);
     }
 
+    reload_settings(&state, !updated.is_empty()).await;
     Ok(Json(UpdateConfigResponse { updated, failed }))
 }
 
@@ -308,6 +318,11 @@ pub async fn sync_env_to_db(
         ("jupiter_api_key", "JUPITER_API_KEY", true),
         ("solana_rpc_url", "SOLANA_RPC_URL", false),
         ("control_plane_url", "CONTROL_PLANE_URL", false),
+        (
+            "control_plane_failover_urls",
+            "CONTROL_PLANE_FAILOVER_URLS",
+            false,
+        ),
         ("data_retrieval_url", "DATA_RETRIEVAL_URL", false),
         ("discord_webhook_url", "DISCORD_ALERT_WEBHOOK", true),
         ("email_webhook_url", "EMAIL_ALERT_WEBHOOK", true),
@@ -368,9 +383,20 @@ pub async fn sync_env_to_db(
         }
     }
 
+    reload_settings(&state, !updated.is_empty()).await;
     Ok(Json(UpdateConfigResponse { updated, failed }))
 }
 
+/// Apply config edits here now rather than at the next settings refresh
+async fn reload_settings(state: &AppState, edited: bool) {
+    if !edited {
+        return;
+    }
+    if let Err(e) = state.settings.reload(&state.db).await {
+        tracing::error!("Failed to reload platform settings after edit: {}", e);
+    }
+}
+
 // ============================================================================
 // Dashboard KPIs
 // ============================================================================
@@ -380,6 +406,9 @@ pub struct KpisResponse {
     pub bots: BotKpis,
     pub users: UserKpis,
     pub events: EventKpis,
+    pub infra: InfraKpis,
+    /// Bots stuck past the keeper's deadlines
+    pub stuck: crate::keeper::StuckCounts,
     pub system: SystemKpis,
 }
 
@@ -400,6 +429,13 @@ pub struct EventKpis {
     pub last_7d: i64,
 }
 
+#[derive(Debug, Serialize)]
+pub struct InfraKpis {
+    pub active_droplets: i64,
+    pub monthly_run_rate_usd: rust_decimal::Decimal,
+    pub last_30d_cost_usd: rust_decimal::Decimal,
+}
+
 #[derive(Debug, Serialize)]
 pub struct SystemKpis {
     pub uptime_secs: u64,
@@ -442,6 +478,20 @@ pub async fn get_kpis(
     .await
     .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
 
+    let infra_costs = droplets::all_bot_infra_costs(&state.db)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    let infra = InfraKpis {
+        active_droplets: infra_costs.iter().map(|c| c.active_droplets).sum(),
+        monthly_run_rate_usd: infra_costs.iter().map(|c| c.monthly_run_rate_usd).sum(),
+        last_30d_cost_usd: infra_costs.iter().map(|c| c.last_30d_cost_usd).sum(),
+    };
+
+    let keeper = crate::keeper::KeeperConfig::from(&state.settings.current().provisioning);
+    let stuck = crate::keeper::stuck_counts(&state.db, &keeper)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
     let snapshot = state.metrics.snapshot().await;
 
     Ok(Json(KpisResponse {
@@ -454,6 +504,8 @@ pub async fn get_kpis(
             last_24h: events_24h,
             last_7d: events_7d,
         },
+        infra,
+        stuck,
         system: SystemKpis {
             uptime_secs: snapshot.uptime_secs,
             counters: snapshot.counters,
@@ -462,6 +514,199 @@ pub async fn get_kpis(
     }))
 }
 
+// ============================================================================
+// Infrastructure Costs
+// ============================================================================
+
+/// GET /admin/infra/costs - Per-bot droplet cost, highest run rate first (returns array)
+pub async fn get_infra_costs(
+    State(state): State<Arc<AppState>>,
+    Extension(admin): Extension<AdminContext>,
+) -> Result<Json<Vec<droplets::BotInfraCost>>, (StatusCode, String)> {
+    info!("Admin {} fetching infra costs", admin.admin_id);
+
+    let costs = droplets::all_bot_infra_costs(&state.db)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    Ok(Json(costs))
+}
+
+// ============================================================================
+// LLM Usage
+// ============================================================================
+
+#[derive(Debug, serde::Deserialize)]
+pub struct LlmUsageRollupParams {
+    /// Days of history (default 30, max 90)
+    pub days: Option<i64>,
+}
+
+#[derive(Debug, serde::Serialize)]
+pub struct LlmUsageRollup {
+    pub since: chrono::NaiveDate,
+    pub total: crate::llm_usage::LlmUsageRow,
+    /// Per provider and model, costliest first
+    pub by_provider: Vec<crate::llm_usage::LlmUsageRow>,
+    /// The 20 bots with the highest estimated spend
+    pub top_bots: Vec<crate::llm_usage::BotLlmSpend>,
+}
+
+/// GET /admin/llm-usage - Platform LLM usage by provider and top bots
+pub async fn get_llm_usage(
+    State(state): State<Arc<AppState>>,
+    Extension(admin): Extension<AdminContext>,
+    Query(params): Query<LlmUsageRollupParams>,
+) -> Result<Json<LlmUsageRollup>, (StatusCode, String)> {
+    info!("Admin {} fetching LLM usage", admin.admin_id);
+
+    let days = params.days.unwrap_or(30).clamp(1, 90);
+    let since = (chrono::Utc::now() - chrono::Duration::days(days - 1)).date_naive();
+    let by_provider = crate::llm_usage::usage_by_provider(&state.db, since)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    let top_bots = crate::llm_usage::top_bots(&state.db, since, 20)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    Ok(Json(LlmUsageRollup {
+        since,
+        total: crate::llm_usage::total(&by_provider),
+        by_provider,
+        top_bots,
+    }))
+}
+
+// ============================================================================
+// Metrics Backfill
+// ============================================================================
+
+#[derive(Debug, serde::Deserialize)]
+pub struct BackfillParams {
+    /// Start of the scan window (defaults to the oldest backfillable day)
+    pub since: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+/// POST /admin/bots/:id/backfill-metrics - Rebuild gaps in a bot's equity curve
+pub async fn backfill_bot_metrics(
+    State(state): State<Arc<AppState>>,
+    Extension(admin): Extension<AdminContext>,
+    Path(bot_id): Path<uuid::Uuid>,
+    Query(params): Query<BackfillParams>,
+) -> Result<Json<backfill::BackfillReport>, (StatusCode, String)> {
+    info!(
+        "Admin {} backfilling metrics for bot {}",
+        admin.admin_id, bot_id
+    );
+
+    let since = params.since.unwrap_or_else(|| {
+        chrono::Utc::now() - chrono::Duration::days(backfill::MAX_BACKFILL_DAYS)
+    });
+    let report = backfill::backfill_bot(&state.db, bot_id, since)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    Ok(Json(report))
+}
+
+// ============================================================================
+// Log Levels
+// ============================================================================
+
+#[derive(Debug, serde::Deserialize)]
+pub struct SetLogLevelRequest {
+    /// Tracing filter directives, e.g. `debug` or `info,control_plane::handlers=trace`
+    pub level: String,
+    /// How long the change lasts before reverting (default 15 minutes, max 4 hours)
+    pub ttl_secs: Option<u64>,
+}
+
+fn log_level_control(
+    state: &AppState,
+) -> Result<&crate::log_level::LogLevelControl, (StatusCode, String)> {
+    state.log_level.as_ref().ok_or((
+        StatusCode::SERVICE_UNAVAILABLE,
+        "Runtime log level control is not enabled".to_string(),
+    ))
+}
+
+/// GET /admin/log-level - The control plane's active tracing filter
+pub async fn get_log_level(
+    State(state): State<Arc<AppState>>,
+    Extension(_admin): Extension<AdminContext>,
+) -> Result<Json<crate::log_level::LogLevelStatus>, (StatusCode, String)> {
+    Ok(Json(log_level_control(&state)?.status()))
+}
+
+/// PUT /admin/log-level - Change the control plane's tracing filter for a while
+pub async fn set_log_level(
+    State(state): State<Arc<AppState>>,
+    Extension(admin): Extension<AdminContext>,
+    Json(req): Json<SetLogLevelRequest>,
+) -> Result<Json<crate::log_level::LogLevelStatus>, (StatusCode, String)> {
+    let ttl = req.ttl_secs.unwrap_or(crate::log_level::DEFAULT_TTL_SECS);
+    info!(
+        "Admin {} setting log level to '{}' for {}s",
+        admin.admin_id, req.level, ttl
+    );
+
+    let status = log_level_control(&state)?
+        .set(&req.level, std::time::Duration::from_secs(ttl))
+        .map_err(|e| (StatusCode::BAD_REQUEST, e))?;
+    Ok(Json(status))
+}
+
+#[derive(Debug, Serialize)]
+pub struct QueuedCommandResponse {
+    pub command_id: uuid::Uuid,
+    pub command: String,
+    pub args: serde_json::Value,
+}
+
+/// POST /admin/bots/:id/log-level - Queue `set_log_level` for a bot's next sync
+pub async fn set_bot_log_level(
+    State(state): State<Arc<AppState>>,
+    Extension(admin): Extension<AdminContext>,
+    Path(bot_id): Path<uuid::Uuid>,
+    Json(req): Json<SetLogLevelRequest>,
+) -> Result<Json<QueuedCommandResponse>, (StatusCode, String)> {
+    // The runner validates the filter itself; catch typos before queueing
+    if tracing_subscriber::EnvFilter::try_new(&req.level).is_err() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!("Invalid log filter '{}'", req.level),
+        ));
+    }
+    let args = serde_json::json!({
+        "level": req.level,
+        "ttl_secs": req
+            .ttl_secs
+            .unwrap_or(crate::log_level::DEFAULT_TTL_SECS)
+            .min(crate::log_level::MAX_TTL_SECS),
+    });
+
+    let command_id: Option<uuid::Uuid> = sqlx::query_scalar(
+        "INSERT INTO bot_commands (bot_id, command, args) \
+         SELECT id, 'set_log_level', $2 FROM bots WHERE id = $1 RETURNING id",
+    )
+    .bind(bot_id)
+    .bind(&args)
+    .fetch_optional(&state.db)
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    let command_id = command_id.ok_or((StatusCode::NOT_FOUND, "Bot not found".to_string()))?;
+
+    info!(
+        "Admin {} queued set_log_level {} for bot {}",
+        admin.admin_id, args, bot_id
+    );
+    Ok(Json(QueuedCommandResponse {
+        command_id,
+        command: "set_log_level".to_string(),
+        args,
+    }))
+}
+
 // ============================================================================
 // Provisioning Queue
 // ============================================================================
@@ -474,6 +719,8 @@ pub struct ProvisioningEntry {
     pub user_id: uuid::Uuid,
     pub created_at: chrono::DateTime<chrono::Utc>,
     pub updated_at: chrono::DateTime<chrono::Utc>,
+    /// Latest status per bootstrap phase, as reported by the runner
+    pub bootstrap_phases: serde_json::Value,
 }
 
 /// GET /admin/provisioning/queue - Current provisioning queue (returns array)
@@ -484,7 +731,7 @@ pub async fn get_provisioning_queue(
     info!("Admin {} fetching provisioning queue", admin.admin_id);
 
     let queue: Vec<ProvisioningEntry> = sqlx::query_as(
-        "SELECT id, name, user_id, created_at, updated_at FROM bots WHERE status = 'provisioning' ORDER BY created_at ASC",
+        "SELECT id, name, user_id, created_at, updated_at, bootstrap_phases FROM bots WHERE status = 'provisioning' AND provisioning = 'droplet' ORDER BY created_at ASC",
     )
     .fetch_all(&state.db)
     .await
@@ -493,6 +740,130 @@ pub async fn get_provisioning_queue(
     Ok(Json(queue))
 }
 
+/// GET /admin/alerts - Alert groups across all users, with suppressed counts
+pub async fn get_alert_history(
+    State(state): State<Arc<AppState>>,
+    Extension(admin): Extension<AdminContext>,
+) -> Json<Vec<crate::alerting::AlertGroup>> {
+    info!("Admin {} fetching alert history", admin.admin_id);
+    Json(state.alerts.history(None).await)
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub struct AbuseParams {
+    /// How far back to list events (default 24, max 720)
+    pub hours: Option<i64>,
+}
+
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct AbuseEvent {
+    pub id: uuid::Uuid,
+    pub subject: String,
+    pub route_class: String,
+    pub kind: String,
+    pub reason: String,
+    pub blocked_until: Option<chrono::DateTime<chrono::Utc>>,
+    pub actor: Option<String>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AbuseOverview {
+    /// Blocks in force on this instance
+    pub active_blocks: Vec<crate::middleware::abuse::ActiveBlock>,
+    /// Backoffs, blocks and lifts across all instances, newest first
+    pub events: Vec<AbuseEvent>,
+}
+
+/// GET /admin/abuse - Active blocks and recent anti-abuse actions
+pub async fn get_abuse(
+    State(state): State<Arc<AppState>>,
+    Extension(admin): Extension<AdminContext>,
+    Query(params): Query<AbuseParams>,
+) -> Result<Json<AbuseOverview>, (StatusCode, String)> {
+    info!("Admin {} fetching abuse events", admin.admin_id);
+
+    let hours = params.hours.unwrap_or(24).clamp(1, 720);
+    let events: Vec<AbuseEvent> = sqlx::query_as(
+        "SELECT id, subject, route_class, kind, reason, blocked_until, actor, created_at \
+         FROM abuse_events WHERE created_at > NOW() - INTERVAL '1 hour' * $1 \
+         ORDER BY created_at DESC LIMIT 500",
+    )
+    .bind(hours)
+    .fetch_all(&state.db)
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    Ok(Json(AbuseOverview {
+        active_blocks: state.abuse.active_blocks().await,
+        events,
+    }))
+}
+
+/// DELETE /admin/abuse/blocks/:subject - Lift a temporary block early
+///
+/// Only lifts it on the instance that answers; others let theirs expire.
+pub async fn lift_abuse_block(
+    State(state): State<Arc<AppState>>,
+    Extension(admin): Extension<AdminContext>,
+    Path(subject): Path<String>,
+) -> Result<StatusCode, (StatusCode, String)> {
+    let Some(block) = state
+        .abuse
+        .active_blocks()
+        .await
+        .into_iter()
+        .find(|b| b.subject == subject)
+    else {
+        return Err((StatusCode::NOT_FOUND, format!("{} is not blocked", subject)));
+    };
+    state.abuse.unblock(&subject).await;
+
+    crate::middleware::abuse::record_event(
+        &state.db,
+        &subject,
+        block.route_class,
+        "unblocked",
+        "lifted by an admin",
+        None,
+        Some(&admin.admin_id.to_string()),
+    )
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    info!("Admin {} lifted the block on {}", admin.admin_id, subject);
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct ProvisioningAuditEntry {
+    pub id: uuid::Uuid,
+    pub action: String,
+    pub bot_id: Option<uuid::Uuid>,
+    pub droplet_id: Option<i64>,
+    pub reason: String,
+    pub actor: String,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// GET /admin/provisioning/audit - Destructive reconciliation actions (returns array)
+pub async fn get_provisioning_audit(
+    State(state): State<Arc<AppState>>,
+    Extension(admin): Extension<AdminContext>,
+) -> Result<Json<Vec<ProvisioningAuditEntry>>, (StatusCode, String)> {
+    info!("Admin {} fetching provisioning audit", admin.admin_id);
+
+    let entries: Vec<ProvisioningAuditEntry> = sqlx::query_as(
+        "SELECT id, action, bot_id, droplet_id, reason, actor, created_at \
+         FROM provisioning_audit_log ORDER BY created_at DESC LIMIT 200",
+    )
+    .fetch_all(&state.db)
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    Ok(Json(entries))
+}
+
 // ============================================================================
 // Audit Log (cedros-login audit_logs table)
 // ============================================================================
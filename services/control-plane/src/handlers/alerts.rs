@@ -0,0 +1,93 @@
+//! Alert history and acknowledgement for the signed-in user
+
+use axum::{
+    extract::{Extension, Path, Query, State},
+    http::StatusCode,
+    Json,
+};
+use std::sync::Arc;
+use tracing::info;
+use uuid::Uuid;
+
+use crate::{middleware::AuthContext, models::*, AppState};
+
+const ALERT_STATES: [&str; 3] = ["open", "acknowledged", "resolved"];
+
+fn user_uuid(auth: &AuthContext) -> Result<Uuid, (StatusCode, String)> {
+    Uuid::parse_str(&auth.user_id)
+        .map_err(|_| (StatusCode::BAD_REQUEST, "Invalid user ID".to_string()))
+}
+
+/// GET /alerts - The user's alerts, most recently fired first
+pub async fn list_alerts(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Query(query): Query<AlertsQuery>,
+) -> Result<Json<AlertsResponse>, (StatusCode, String)> {
+    let user_id = user_uuid(&auth)?;
+    if let Some(s) = &query.state {
+        if !ALERT_STATES.contains(&s.as_str()) {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                format!("state must be one of {}", ALERT_STATES.join(", ")),
+            ));
+        }
+    }
+    let limit = query.limit.unwrap_or(100).clamp(1, 500);
+
+    let alerts = sqlx::query_as::<_, Alert>(
+        "SELECT * FROM alerts WHERE user_id = $1 AND ($2::text IS NULL OR state = $2) \
+         ORDER BY last_fired_at DESC LIMIT $3",
+    )
+    .bind(user_id)
+    .bind(&query.state)
+    .bind(limit)
+    .fetch_all(&state.db)
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    Ok(Json(AlertsResponse { alerts }))
+}
+
+/// POST /alerts/:id/ack - Acknowledge an open alert
+///
+/// Acknowledged alerts stop re-firing until their condition clears.
+/// Acknowledging twice is a no-op; resolved alerts can't be acknowledged.
+pub async fn acknowledge_alert(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Path(alert_id): Path<Uuid>,
+) -> Result<Json<Alert>, (StatusCode, String)> {
+    let user_id = user_uuid(&auth)?;
+
+    let alert = sqlx::query_as::<_, Alert>("SELECT * FROM alerts WHERE id = $1 AND user_id = $2")
+        .bind(alert_id)
+        .bind(user_id)
+        .fetch_optional(&state.db)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+        .ok_or((StatusCode::NOT_FOUND, "Alert not found".to_string()))?;
+
+    match alert.state.as_str() {
+        "acknowledged" => return Ok(Json(alert)),
+        "resolved" => {
+            return Err((StatusCode::CONFLICT, "Alert already resolved".to_string()));
+        }
+        _ => {}
+    }
+
+    let alert = sqlx::query_as::<_, Alert>(
+        "UPDATE alerts SET state = 'acknowledged', acknowledged_at = NOW() \
+         WHERE id = $1 AND state = 'open' RETURNING *",
+    )
+    .bind(alert_id)
+    .fetch_optional(&state.db)
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+    .ok_or((StatusCode::CONFLICT, "Alert state changed".to_string()))?;
+
+    state.alerts.acknowledge(&alert.group_key).await;
+    info!("User {} acknowledged alert {}", auth.user_id, alert_id);
+
+    Ok(Json(alert))
+}
@@ -0,0 +1,242 @@
+//! Bot artifacts: journal exports, diagnostics and state snapshots
+//!
+//! Artifact bytes live in object storage; these handlers list what a bot
+//! has, create exports and hand out short-lived presigned download URLs.
+//! Everything except listing needs storage configured and answers 503
+//! otherwise.
+
+use axum::{
+    extract::{Extension, Path, State},
+    http::StatusCode,
+    Json,
+};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::info;
+use uuid::Uuid;
+
+use crate::{
+    artifacts::{self, Artifact, ArtifactKind},
+    handlers::{admin::QueuedCommandResponse, bots::get_authorized_bot},
+    middleware::AuthContext,
+    storage::ObjectStore,
+    AppState,
+};
+
+/// How long a download URL stays valid
+const DOWNLOAD_URL_TTL: Duration = Duration::from_secs(15 * 60);
+
+/// Largest accepted diagnostics upload, as serialized JSON
+const MAX_DIAGNOSTICS_BYTES: usize = 1_500_000;
+
+#[derive(Debug, Serialize)]
+pub struct ArtifactsResponse {
+    pub artifacts: Vec<Artifact>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ArtifactDownload {
+    pub artifact: Artifact,
+    pub url: String,
+    pub url_expires_at: DateTime<Utc>,
+}
+
+pub(crate) async fn require_store(state: &AppState) -> Result<ObjectStore, (StatusCode, String)> {
+    ObjectStore::from_config(&state.db, &state.secrets)
+        .await
+        .ok_or((
+            StatusCode::SERVICE_UNAVAILABLE,
+            "Object storage is not configured".to_string(),
+        ))
+}
+
+fn download(store: &ObjectStore, artifact: Artifact) -> ArtifactDownload {
+    let now = Utc::now();
+    // Never outlive the object itself
+    let ttl = (artifact.expires_at - now)
+        .to_std()
+        .unwrap_or_default()
+        .min(DOWNLOAD_URL_TTL);
+    ArtifactDownload {
+        url: store.presigned_get_url(&artifact.object_key, ttl, now),
+        url_expires_at: now + chrono::Duration::from_std(ttl).unwrap_or_default(),
+        artifact,
+    }
+}
+
+/// GET /bots/:id/artifacts - Artifacts that haven't expired, newest first
+pub async fn list_artifacts(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Path(bot_id): Path<Uuid>,
+) -> Result<Json<ArtifactsResponse>, (StatusCode, String)> {
+    let _bot = get_authorized_bot(&state.db, &auth, bot_id).await?;
+
+    let artifacts = sqlx::query_as::<_, Artifact>(
+        "SELECT * FROM artifacts WHERE bot_id = $1 AND expires_at > NOW() \
+         ORDER BY created_at DESC LIMIT 100",
+    )
+    .bind(bot_id)
+    .fetch_all(&state.db)
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    Ok(Json(ArtifactsResponse { artifacts }))
+}
+
+/// GET /bots/:id/artifacts/:artifact_id/download - Presigned URL for one artifact
+pub async fn download_artifact(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Path((bot_id, artifact_id)): Path<(Uuid, Uuid)>,
+) -> Result<Json<ArtifactDownload>, (StatusCode, String)> {
+    let _bot = get_authorized_bot(&state.db, &auth, bot_id).await?;
+
+    let artifact = sqlx::query_as::<_, Artifact>(
+        "SELECT * FROM artifacts WHERE id = $1 AND bot_id = $2 AND expires_at > NOW()",
+    )
+    .bind(artifact_id)
+    .bind(bot_id)
+    .fetch_optional(&state.db)
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+    .ok_or((StatusCode::NOT_FOUND, "Artifact not found".to_string()))?;
+
+    let store = require_store(&state).await?;
+    Ok(Json(download(&store, artifact)))
+}
+
+/// POST /bots/:id/journal/export - Write the decision journal to storage as JSONL
+///
+/// One line per entry in `seq` order, in the shape the runner uploads
+/// (`seq`, `prev_hash`, `hash`, `entry`), so the file can be re-verified
+/// offline.
+pub async fn export_journal(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Path(bot_id): Path<Uuid>,
+) -> Result<Json<ArtifactDownload>, (StatusCode, String)> {
+    let _bot = get_authorized_bot(&state.db, &auth, bot_id).await?;
+    let store = require_store(&state).await?;
+
+    let rows = sqlx::query_as::<_, crate::journal::JournalRow>(
+        "SELECT seq, prev_hash, entry_hash, entry FROM decision_journal \
+         WHERE bot_id = $1 ORDER BY seq",
+    )
+    .bind(bot_id)
+    .fetch_all(&state.db)
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    if rows.is_empty() {
+        return Err((
+            StatusCode::NOT_FOUND,
+            "Bot has no journal entries".to_string(),
+        ));
+    }
+
+    let mut body = Vec::new();
+    for row in &rows {
+        let line = serde_json::json!({
+            "seq": row.seq,
+            "prev_hash": row.prev_hash,
+            "hash": row.entry_hash,
+            "entry": row.entry,
+        });
+        body.extend_from_slice(line.to_string().as_bytes());
+        body.push(b'\n');
+    }
+
+    let artifact = artifacts::store(
+        &state.db,
+        &store,
+        bot_id,
+        ArtifactKind::JournalExport,
+        "jsonl",
+        "application/x-ndjson",
+        body,
+    )
+    .await
+    .map_err(|e| (StatusCode::BAD_GATEWAY, e.to_string()))?;
+    info!(
+        "Exported {} journal entries for bot {} to {}",
+        rows.len(),
+        bot_id,
+        artifact.object_key
+    );
+    Ok(Json(download(&store, artifact)))
+}
+
+/// POST /bots/:id/diagnostics - Ask the runner to upload a diagnostics bundle
+///
+/// The bundle shows up under `/bots/:id/artifacts` after the bot's next sync.
+pub async fn request_diagnostics(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Path(bot_id): Path<Uuid>,
+) -> Result<Json<QueuedCommandResponse>, (StatusCode, String)> {
+    let _bot = get_authorized_bot(&state.db, &auth, bot_id).await?;
+    // Fail now rather than after the runner has done the work
+    require_store(&state).await?;
+
+    let args = serde_json::json!({});
+    let command_id: Uuid = sqlx::query_scalar(
+        "INSERT INTO bot_commands (bot_id, command, args) \
+         VALUES ($1, 'upload_diagnostics', $2) RETURNING id",
+    )
+    .bind(bot_id)
+    .bind(&args)
+    .fetch_one(&state.db)
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    Ok(Json(QueuedCommandResponse {
+        command_id,
+        command: "upload_diagnostics".to_string(),
+        args,
+    }))
+}
+
+/// PUT /bot/:id/artifacts/diagnostics - Runner uploads a diagnostics bundle
+pub async fn upload_diagnostics(
+    State(state): State<Arc<AppState>>,
+    Path(bot_id): Path<Uuid>,
+    Json(bundle): Json<serde_json::Value>,
+) -> Result<StatusCode, (StatusCode, String)> {
+    let body = serde_json::to_vec(&bundle).map_err(|e| (StatusCode::BAD_REQUEST, e.to_string()))?;
+    if body.len() > MAX_DIAGNOSTICS_BYTES {
+        return Err((
+            StatusCode::PAYLOAD_TOO_LARGE,
+            format!(
+                "Diagnostics bundle is limited to {} bytes",
+                MAX_DIAGNOSTICS_BYTES
+            ),
+        ));
+    }
+    let exists: bool = sqlx::query_scalar("SELECT EXISTS (SELECT 1 FROM bots WHERE id = $1)")
+        .bind(bot_id)
+        .fetch_one(&state.db)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    if !exists {
+        return Err((StatusCode::NOT_FOUND, "Bot not found".to_string()));
+    }
+    let store = require_store(&state).await?;
+
+    let artifact = artifacts::store(
+        &state.db,
+        &store,
+        bot_id,
+        ArtifactKind::Diagnostics,
+        "json",
+        "application/json",
+        body,
+    )
+    .await
+    .map_err(|e| (StatusCode::BAD_GATEWAY, e.to_string()))?;
+    info!(
+        "Bot {} uploaded {} bytes of diagnostics",
+        bot_id, artifact.size_bytes
+    );
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -0,0 +1,286 @@
+//! Backtest endpoint - run a strategy config over historical candles
+
+use axum::{extract::State, http::StatusCode, Json};
+use rust_decimal::Decimal;
+use std::sync::Arc;
+
+use crate::{
+    algorithms::{
+        backtest::{self, BacktestConfig, BacktestReport, ExecutionModel},
+        monte_carlo::{self, MonteCarloReport},
+        AlgorithmFactory, AlgorithmParams, Candle,
+    },
+    models::*,
+    AppState,
+};
+
+/// Most candles one backtest may run over
+pub const MAX_BACKTEST_CANDLES: usize = 2000;
+const DEFAULT_INITIAL_CASH_USD: i64 = 10_000;
+const DEFAULT_SLIPPAGE_BPS: u32 = 50;
+const DEFAULT_FEE_BPS: u32 = 10;
+const MAX_COST_BPS: u32 = 1000;
+/// Signal to fill, roughly a decision tick plus a Jupiter quote and swap
+const DEFAULT_LATENCY_SECS: u32 = 5;
+const MAX_LATENCY_SECS: u32 = 3600;
+const DEFAULT_IMPACT_BPS_PER_10K: u32 = 5;
+const DEFAULT_VOLATILITY_SLIPPAGE_PCT: u32 = 5;
+/// Base fee plus a typical priority fee at ~$150 SOL
+const DEFAULT_NETWORK_FEE_CENTS: i64 = 1;
+const MAX_NETWORK_FEE_USD: i64 = 10;
+
+#[derive(Debug, serde::Deserialize)]
+pub struct BacktestRequest {
+    /// Asset to test, e.g. `SOL` (any form `SymbolId` parses, or a mint)
+    pub symbol: String,
+    /// History to run over, oldest first; when omitted, the recorded price
+    /// history is used, or CoinGecko if too little is recorded
+    #[serde(default)]
+    pub candles: Option<Vec<Candle>>,
+    /// Candle size to fetch (default `Hour1`)
+    #[serde(default)]
+    pub timeframe: Option<TimeFrame>,
+    pub persona: Persona,
+    pub algorithm_mode: AlgorithmMode,
+    pub strictness: Strictness,
+    pub risk_caps: RiskCaps,
+    pub initial_cash_usd: Option<Decimal>,
+    pub slippage_bps: Option<u32>,
+    pub fee_bps: Option<u32>,
+    /// Seconds from the signal candle's close to the fill
+    pub latency_secs: Option<u32>,
+    /// Extra slippage per $10k of notional
+    pub impact_bps_per_10k: Option<u32>,
+    /// Percent of the fill candle's range added as slippage
+    pub volatility_slippage_pct: Option<u32>,
+    /// Base plus priority fee per fill
+    pub network_fee_usd: Option<Decimal>,
+    /// Bootstrap the closed trades into a distribution of outcomes
+    #[serde(default)]
+    pub monte_carlo: Option<MonteCarloRequest>,
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub struct MonteCarloRequest {
+    /// Sequences to draw (default 1000)
+    pub runs: Option<usize>,
+    /// For a repeatable result; random when omitted
+    pub seed: Option<u64>,
+}
+
+#[derive(Debug, serde::Serialize)]
+pub struct BacktestResponse {
+    pub symbol: String,
+    pub algorithm: String,
+    pub parameters: AlgorithmParams,
+    pub candles: usize,
+    pub from: chrono::DateTime<chrono::Utc>,
+    pub to: chrono::DateTime<chrono::Utc>,
+    pub slippage_bps: u32,
+    pub fee_bps: u32,
+    pub execution: ExecutionModel,
+    #[serde(flatten)]
+    pub report: BacktestReport,
+    /// None unless requested, or when no trades closed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub monte_carlo: Option<MonteCarloReport>,
+}
+
+/// POST /backtest - Run a persona/algorithm/risk caps config over history
+pub async fn run_backtest(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<BacktestRequest>,
+) -> Result<Json<BacktestResponse>, (StatusCode, String)> {
+    req.risk_caps
+        .validate()
+        .map_err(|e| (StatusCode::BAD_REQUEST, e))?;
+    let symbol = data_retrieval::SymbolId::parse(&req.symbol)
+        .base()
+        .to_string();
+    let initial_cash = req
+        .initial_cash_usd
+        .unwrap_or_else(|| Decimal::from(DEFAULT_INITIAL_CASH_USD));
+    if initial_cash <= Decimal::ZERO || initial_cash > Decimal::from(10_000_000) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "initial_cash_usd must be above 0 and at most 10000000".to_string(),
+        ));
+    }
+    let slippage_bps = req.slippage_bps.unwrap_or(DEFAULT_SLIPPAGE_BPS);
+    let fee_bps = req.fee_bps.unwrap_or(DEFAULT_FEE_BPS);
+    if slippage_bps > MAX_COST_BPS || fee_bps > MAX_COST_BPS {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!("slippage_bps and fee_bps must be at most {}", MAX_COST_BPS),
+        ));
+    }
+    let execution = execution_model(&req)?;
+    let monte_carlo = match &req.monte_carlo {
+        Some(mc) => {
+            let runs = mc.runs.unwrap_or(monte_carlo::DEFAULT_RUNS);
+            if runs == 0 || runs > monte_carlo::MAX_RUNS {
+                return Err((
+                    StatusCode::BAD_REQUEST,
+                    format!("monte_carlo.runs must be 1 to {}", monte_carlo::MAX_RUNS),
+                ));
+            }
+            Some((runs, mc.seed.unwrap_or_else(rand::random)))
+        }
+        None => None,
+    };
+
+    let mut candles = match req.candles {
+        Some(candles) => {
+            if candles.len() > MAX_BACKTEST_CANDLES {
+                return Err((
+                    StatusCode::BAD_REQUEST,
+                    format!("At most {} candles per backtest", MAX_BACKTEST_CANDLES),
+                ));
+            }
+            candles
+        }
+        None => {
+            let timeframe = req.timeframe.unwrap_or(TimeFrame::Hour1);
+            match recorded_candles(&state, &symbol, timeframe).await {
+                Some(candles) => candles,
+                None => fetch_candles(&symbol, timeframe).await?,
+            }
+        }
+    };
+    candles.sort_by_key(|c| c.timestamp);
+    if candles.len() < 2 {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "A backtest needs at least 2 candles".to_string(),
+        ));
+    }
+
+    let algorithm = AlgorithmFactory::create(
+        req.algorithm_mode,
+        req.persona,
+        req.strictness,
+        req.risk_caps,
+    );
+    let config = BacktestConfig {
+        symbol: symbol.clone(),
+        initial_cash,
+        slippage_bps,
+        fee_bps,
+        risk_caps: req.risk_caps,
+        execution: execution.clone(),
+    };
+    // Each step re-runs the algorithm over the history so far; keep that off
+    // the async workers
+    let (algorithm, candles, report, monte_carlo) = tokio::task::spawn_blocking(move || {
+        let report = backtest::run(algorithm.as_ref(), &candles, &config);
+        let monte_carlo =
+            monte_carlo.and_then(|(runs, seed)| monte_carlo::bootstrap(&report, runs, seed));
+        (algorithm, candles, report, monte_carlo)
+    })
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    Ok(Json(BacktestResponse {
+        symbol,
+        algorithm: algorithm.name().to_string(),
+        parameters: algorithm.parameters(),
+        candles: candles.len(),
+        from: candles[0].timestamp,
+        to: candles[candles.len() - 1].timestamp,
+        slippage_bps,
+        fee_bps,
+        execution,
+        report,
+        monte_carlo,
+    }))
+}
+
+/// Execution frictions from the request, realistic defaults for unset ones
+fn execution_model(req: &BacktestRequest) -> Result<ExecutionModel, (StatusCode, String)> {
+    let model = ExecutionModel {
+        latency_secs: req.latency_secs.unwrap_or(DEFAULT_LATENCY_SECS),
+        impact_bps_per_10k: req.impact_bps_per_10k.unwrap_or(DEFAULT_IMPACT_BPS_PER_10K),
+        volatility_slippage_pct: req
+            .volatility_slippage_pct
+            .unwrap_or(DEFAULT_VOLATILITY_SLIPPAGE_PCT),
+        network_fee_usd: req
+            .network_fee_usd
+            .unwrap_or_else(|| Decimal::new(DEFAULT_NETWORK_FEE_CENTS, 2)),
+    };
+    if model.latency_secs > MAX_LATENCY_SECS {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!("latency_secs must be at most {}", MAX_LATENCY_SECS),
+        ));
+    }
+    if model.impact_bps_per_10k > MAX_COST_BPS || model.volatility_slippage_pct > 100 {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!(
+                "impact_bps_per_10k must be at most {} and volatility_slippage_pct at most 100",
+                MAX_COST_BPS
+            ),
+        ));
+    }
+    if model.network_fee_usd < Decimal::ZERO
+        || model.network_fee_usd > Decimal::from(MAX_NETWORK_FEE_USD)
+    {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!("network_fee_usd must be 0 to {}", MAX_NETWORK_FEE_USD),
+        ));
+    }
+    Ok(model)
+}
+
+/// Newest [`MAX_BACKTEST_CANDLES`] USD candles recorded by data-retrieval
+///
+/// None when fewer than two are recorded (or the table can't be read).
+pub(crate) async fn recorded_candles(
+    state: &AppState,
+    symbol: &str,
+    timeframe: TimeFrame,
+) -> Option<Vec<Candle>> {
+    let store = data_retrieval::history::HistoryStore::new(state.db.clone());
+    let candles = store
+        .candles(symbol, "USD", timeframe, None, None, MAX_BACKTEST_CANDLES)
+        .await
+        .map_err(|e| tracing::warn!("Price history unavailable for {}: {}", symbol, e))
+        .ok()?;
+    (candles.len() >= 2).then(|| candles.into_iter().map(to_algorithm_candle).collect())
+}
+
+fn to_algorithm_candle(c: data_retrieval::Candle) -> Candle {
+    Candle {
+        timestamp: c.timestamp,
+        open: c.open,
+        high: c.high,
+        low: c.low,
+        close: c.close,
+        volume: c.volume,
+    }
+}
+
+/// USD candles for `symbol` from CoinGecko, newest [`MAX_BACKTEST_CANDLES`]
+pub(crate) async fn fetch_candles(
+    symbol: &str,
+    timeframe: TimeFrame,
+) -> Result<Vec<Candle>, (StatusCode, String)> {
+    let client = data_retrieval::CoinGeckoClient::new(std::env::var("COINGECKO_API_KEY").ok());
+    let mut candles: Vec<Candle> = client
+        .get_candles(symbol, "usd", timeframe, MAX_BACKTEST_CANDLES)
+        .await
+        .map_err(|e| {
+            (
+                StatusCode::BAD_GATEWAY,
+                format!("No price history for {}: {}", symbol, e),
+            )
+        })?
+        .into_iter()
+        .map(to_algorithm_candle)
+        .collect();
+    if candles.len() > MAX_BACKTEST_CANDLES {
+        candles.drain(..candles.len() - MAX_BACKTEST_CANDLES);
+    }
+    Ok(candles)
+}
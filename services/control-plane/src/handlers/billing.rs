@@ -0,0 +1,439 @@
+//! Inbound payment provider webhooks
+//!
+//! Stripe and Cedros Pay report subscription changes here. Each event is
+//! verified, stored once in `payment_events` (providers retry, so repeats are
+//! acknowledged and dropped), applied to the user's `subscriptions` row, and
+//! handed to the entitlement sync worker to pause or resume bots.
+
+use axum::{
+    body::Bytes,
+    extract::State,
+    http::{HeaderMap, StatusCode},
+};
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use serde::Deserialize;
+use sha2::Sha256;
+use std::sync::Arc;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use crate::{
+    config::{self, keys},
+    entitlements::{SubscriptionChange, SubscriptionStatus},
+    middleware::subscription::SubscriptionTier,
+    AppState,
+};
+
+const STRIPE_SIGNATURE_HEADER: &str = "stripe-signature";
+const CEDROS_SIGNATURE_HEADER: &str = "x-cedros-signature";
+
+/// A provider event reduced to what the subscription row cares about
+#[derive(Debug, Clone)]
+struct BillingUpdate {
+    provider: &'static str,
+    event_id: String,
+    event_type: String,
+    /// Provider's subscription id
+    external_id: Option<String>,
+    /// From metadata set at checkout; otherwise found via `external_id`
+    user_id: Option<Uuid>,
+    /// None for events that don't change the subscription
+    status: Option<SubscriptionStatus>,
+    tier: Option<SubscriptionTier>,
+    period_end: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct StripeEvent {
+    id: String,
+    #[serde(rename = "type")]
+    event_type: String,
+    data: StripeEventData,
+}
+
+#[derive(Debug, Deserialize)]
+struct StripeEventData {
+    object: serde_json::Value,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CedrosPayEvent {
+    event_id: String,
+    event_type: String,
+    subscription_id: Option<String>,
+}
+
+/// POST /billing/webhooks/stripe
+pub async fn stripe_webhook(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<StatusCode, (StatusCode, String)> {
+    let secret = webhook_secret(&state, keys::STRIPE_WEBHOOK_SECRET).await?;
+    let signature = headers
+        .get(STRIPE_SIGNATURE_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .ok_or((StatusCode::BAD_REQUEST, "Missing signature".to_string()))?;
+    crate::webhook::verify_signature(&secret, signature, &body, Utc::now().timestamp())
+        .map_err(|e| (StatusCode::BAD_REQUEST, format!("Invalid signature: {}", e)))?;
+
+    let event: StripeEvent = serde_json::from_slice(&body)
+        .map_err(|e| (StatusCode::BAD_REQUEST, format!("Invalid event: {}", e)))?;
+    apply_update(&state, parse_stripe_event(&event), &body).await
+}
+
+/// POST /billing/webhooks/cedros-pay
+pub async fn cedros_pay_webhook(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<StatusCode, (StatusCode, String)> {
+    let secret = webhook_secret(&state, keys::CEDROS_PAY_WEBHOOK_SECRET).await?;
+    let signature = headers
+        .get(CEDROS_SIGNATURE_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .ok_or((StatusCode::BAD_REQUEST, "Missing signature".to_string()))?;
+    if !verify_cedros_signature(&secret, signature, &body) {
+        return Err((StatusCode::BAD_REQUEST, "Invalid signature".to_string()));
+    }
+
+    let event: CedrosPayEvent = serde_json::from_slice(&body)
+        .map_err(|e| (StatusCode::BAD_REQUEST, format!("Invalid event: {}", e)))?;
+    apply_update(&state, parse_cedros_event(&event), &body).await
+}
+
+async fn webhook_secret(state: &AppState, key: &str) -> Result<String, (StatusCode, String)> {
+    config::get_config_decrypted(&state.db, &state.secrets, key)
+        .await
+        .ok_or((
+            StatusCode::SERVICE_UNAVAILABLE,
+            format!("{} is not configured", key),
+        ))
+}
+
+/// Cedros Pay signs the raw body: `sha256=<hex hmac-sha256>`
+fn verify_cedros_signature(secret: &str, header: &str, body: &[u8]) -> bool {
+    let Some(sig) = header
+        .strip_prefix("sha256=")
+        .and_then(|s| hex::decode(s).ok())
+    else {
+        return false;
+    };
+    let Ok(mut mac) = Hmac::<Sha256>::new_from_slice(secret.as_bytes()) else {
+        return false;
+    };
+    mac.update(body);
+    mac.verify_slice(&sig).is_ok()
+}
+
+fn stripe_status(status: &str) -> Option<SubscriptionStatus> {
+    match status {
+        "active" | "trialing" => Some(SubscriptionStatus::Active),
+        "past_due" | "unpaid" => Some(SubscriptionStatus::PastDue),
+        "canceled" | "incomplete_expired" => Some(SubscriptionStatus::Cancelled),
+        // incomplete / paused: nothing to apply yet
+        _ => None,
+    }
+}
+
+fn parse_tier(tier: &str) -> Option<SubscriptionTier> {
+    match tier.to_ascii_lowercase().as_str() {
+        "pro" => Some(SubscriptionTier::Pro),
+        "enterprise" => Some(SubscriptionTier::Enterprise),
+        _ => None,
+    }
+}
+
+fn parse_stripe_event(event: &StripeEvent) -> BillingUpdate {
+    let object = &event.data.object;
+    let str_at = |path: &str| object.pointer(path).and_then(|v| v.as_str());
+    let mut update = BillingUpdate {
+        provider: "stripe",
+        event_id: format!("stripe:{}", event.id),
+        event_type: event.event_type.clone(),
+        external_id: None,
+        user_id: None,
+        status: None,
+        tier: None,
+        period_end: None,
+    };
+
+    match event.event_type.as_str() {
+        "customer.subscription.created"
+        | "customer.subscription.updated"
+        | "customer.subscription.deleted" => {
+            update.external_id = str_at("/id").map(String::from);
+            update.user_id = str_at("/metadata/user_id").and_then(|s| Uuid::parse_str(s).ok());
+            update.tier = str_at("/metadata/tier").and_then(parse_tier);
+            update.period_end = object
+                .get("current_period_end")
+                .and_then(|v| v.as_i64())
+                .and_then(|t| DateTime::from_timestamp(t, 0));
+            update.status = if event.event_type == "customer.subscription.deleted" {
+                Some(SubscriptionStatus::Cancelled)
+            } else {
+                str_at("/status").and_then(stripe_status)
+            };
+        }
+        "invoice.payment_failed" | "invoice.paid" => {
+            update.external_id = str_at("/subscription").map(String::from);
+            update.user_id = str_at("/subscription_details/metadata/user_id")
+                .and_then(|s| Uuid::parse_str(s).ok());
+            update.status = Some(if event.event_type == "invoice.paid" {
+                SubscriptionStatus::Active
+            } else {
+                SubscriptionStatus::PastDue
+            });
+        }
+        _ => {}
+    }
+    update
+}
+
+fn parse_cedros_event(event: &CedrosPayEvent) -> BillingUpdate {
+    let status = match event.event_type.as_str() {
+        "subscription.created" | "subscription.updated" | "subscription.renewed" => {
+            Some(SubscriptionStatus::Active)
+        }
+        "subscription.payment_failed" => Some(SubscriptionStatus::PastDue),
+        "subscription.cancelled" => Some(SubscriptionStatus::Cancelled),
+        _ => None,
+    };
+    BillingUpdate {
+        provider: "cedros_pay",
+        event_id: format!("cedros_pay:{}", event.event_id),
+        event_type: event.event_type.clone(),
+        external_id: event.subscription_id.clone(),
+        user_id: None,
+        status,
+        tier: None,
+        period_end: None,
+    }
+}
+
+/// Record the event and apply it to the subscription row
+///
+/// Events that can't be applied (unknown subscription, no period end for a
+/// new one) are acknowledged with their error stored, so the provider
+/// doesn't retry them forever.
+async fn apply_update(
+    state: &AppState,
+    update: BillingUpdate,
+    payload: &[u8],
+) -> Result<StatusCode, (StatusCode, String)> {
+    let payload: serde_json::Value = serde_json::from_slice(payload)
+        .map_err(|e| (StatusCode::BAD_REQUEST, format!("Invalid event: {}", e)))?;
+    let inserted = sqlx::query(
+        "INSERT INTO payment_events (event_id, provider, event_type, payload) \
+         VALUES ($1, $2, $3, $4) ON CONFLICT (event_id) DO NOTHING",
+    )
+    .bind(&update.event_id)
+    .bind(update.provider)
+    .bind(&update.event_type)
+    .bind(&payload)
+    .execute(&state.db)
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    if inserted.rows_affected() == 0 {
+        info!("Payment event {} already processed", update.event_id);
+        return Ok(StatusCode::OK);
+    }
+
+    let Some(status) = update.status else {
+        return Ok(StatusCode::OK);
+    };
+
+    let user_id = match update.user_id {
+        Some(user_id) => Some(user_id),
+        None => match &update.external_id {
+            Some(external_id) => sqlx::query_scalar::<_, Uuid>(
+                "SELECT user_id FROM subscriptions WHERE external_id = $1 LIMIT 1",
+            )
+            .bind(external_id)
+            .fetch_optional(&state.db)
+            .await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?,
+            None => None,
+        },
+    };
+    let Some(user_id) = user_id else {
+        record_error(
+            state,
+            &update.event_id,
+            None,
+            "No user for this subscription",
+        )
+        .await;
+        return Ok(StatusCode::OK);
+    };
+
+    let mut tx = state
+        .db
+        .begin()
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    let current: Option<(Uuid, String)> = sqlx::query_as(
+        "SELECT id, status::text FROM subscriptions WHERE user_id = $1 \
+         ORDER BY current_period_end DESC LIMIT 1 FOR UPDATE",
+    )
+    .bind(user_id)
+    .fetch_optional(&mut *tx)
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    let previous = match current {
+        Some((subscription_id, previous)) => {
+            // past_due_since starts the grace period and survives repeated failures
+            sqlx::query(
+                r#"
+                UPDATE subscriptions SET
+                    status = $2::subscription_status,
+                    past_due_since = CASE WHEN $2 = 'past_due' THEN COALESCE(past_due_since, NOW()) END,
+                    max_bots = COALESCE($3, max_bots),
+                    current_period_end = COALESCE($4, current_period_end),
+                    external_id = COALESCE($5, external_id),
+                    updated_at = NOW()
+                WHERE id = $1
+                "#,
+            )
+            .bind(subscription_id)
+            .bind(status.as_str())
+            .bind(update.tier.map(|t| t.max_bots()))
+            .bind(update.period_end)
+            .bind(&update.external_id)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+            SubscriptionStatus::parse(&previous)
+        }
+        None => {
+            let Some(period_end) = update.period_end else {
+                drop(tx);
+                record_error(
+                    state,
+                    &update.event_id,
+                    Some(user_id),
+                    "No subscription to update",
+                )
+                .await;
+                return Ok(StatusCode::OK);
+            };
+            let tier = update.tier.unwrap_or(SubscriptionTier::Pro);
+            let result = sqlx::query(
+                r#"
+                INSERT INTO subscriptions
+                    (user_id, status, max_bots, current_period_end, external_id, past_due_since)
+                VALUES ($1, $2::subscription_status, $3, $4, $5,
+                        CASE WHEN $2 = 'past_due' THEN NOW() END)
+                "#,
+            )
+            .bind(user_id)
+            .bind(status.as_str())
+            .bind(tier.max_bots())
+            .bind(period_end)
+            .bind(&update.external_id)
+            .execute(&mut *tx)
+            .await;
+            if let Err(sqlx::Error::Database(e)) = &result {
+                if e.is_foreign_key_violation() {
+                    drop(tx);
+                    record_error(state, &update.event_id, None, "Unknown user in metadata").await;
+                    return Ok(StatusCode::OK);
+                }
+            }
+            result.map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+            None
+        }
+    };
+
+    sqlx::query("UPDATE payment_events SET user_id = $2 WHERE event_id = $1")
+        .bind(&update.event_id)
+        .bind(user_id)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    tx.commit()
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    info!(
+        "Subscription for {} is now {} ({} {})",
+        user_id,
+        status.as_str(),
+        update.provider,
+        update.event_type
+    );
+    state.entitlements.publish(SubscriptionChange {
+        user_id,
+        previous,
+        status,
+    });
+    Ok(StatusCode::OK)
+}
+
+async fn record_error(state: &AppState, event_id: &str, user_id: Option<Uuid>, error: &str) {
+    warn!("Payment event {} not applied: {}", event_id, error);
+    if let Err(e) =
+        sqlx::query("UPDATE payment_events SET error = $2, user_id = $3 WHERE event_id = $1")
+            .bind(event_id)
+            .bind(error)
+            .bind(user_id)
+            .execute(&state.db)
+            .await
+    {
+        warn!("Failed to record payment event error: {}", e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_provider_events() {
+        let event: StripeEvent = serde_json::from_value(serde_json::json!({
+            "id": "evt_1",
+            "type": "customer.subscription.updated",
+            "data": {"object": {
+                "id": "sub_9",
+                "status": "past_due",
+                "current_period_end": 1767225600,
+                "metadata": {
+                    "user_id": "0b7c6f1e-2d0a-4e63-8a4f-3c1e5b9d2a10",
+                    "tier": "enterprise"
+                }
+            }}
+        }))
+        .unwrap();
+        let update = parse_stripe_event(&event);
+        assert_eq!(update.event_id, "stripe:evt_1");
+        assert_eq!(update.external_id.as_deref(), Some("sub_9"));
+        assert!(update.user_id.is_some());
+        assert_eq!(update.status, Some(SubscriptionStatus::PastDue));
+        assert_eq!(update.tier, Some(SubscriptionTier::Enterprise));
+        assert_eq!(update.period_end.unwrap().timestamp(), 1767225600);
+
+        let event: StripeEvent = serde_json::from_value(serde_json::json!({
+            "id": "evt_2",
+            "type": "customer.created",
+            "data": {"object": {}}
+        }))
+        .unwrap();
+        assert_eq!(parse_stripe_event(&event).status, None);
+
+        let body =
+            br#"{"eventId":"e1","eventType":"subscription.payment_failed","subscriptionId":"s1"}"#;
+        let mut mac = Hmac::<Sha256>::new_from_slice(b"cedros").unwrap();
+        mac.update(body);
+        let header = format!("sha256={}", hex::encode(mac.finalize().into_bytes()));
+        assert!(verify_cedros_signature("cedros", &header, body));
+        assert!(!verify_cedros_signature("other", &header, body));
+
+        let event: CedrosPayEvent = serde_json::from_slice(body).unwrap();
+        let update = parse_cedros_event(&event);
+        assert_eq!(update.status, Some(SubscriptionStatus::PastDue));
+        assert_eq!(update.external_id.as_deref(), Some("s1"));
+    }
+}
@@ -1,31 +1,46 @@
 //! Bot handlers for the control plane
 
 use axum::{
-    extract::{Extension, Path, State},
-    http::StatusCode,
+    extract::{Extension, Path, Query, State},
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
     Json,
 };
 use chrono::Utc;
+use std::collections::HashMap;
 use std::sync::Arc;
 use tracing::{error, info, warn};
 use uuid::Uuid;
 
 use crate::{
+    config_document::ConfigDocument,
     db::Db,
-    middleware::AuthContext,
+    events,
+    handlers::handover,
+    metric_series::{self, MetricsRange, Resolution},
+    middleware::subscription::SubscriptionContext,
+    middleware::{AuthContext, KillSwitchStatus},
     models::User,
     models::*,
     observability::{metrics, Logger},
-    AppState,
+    preferences::{self, UpdatePreferencesRequest, UserPreferences},
+    provisioning, universe, AppState,
 };
 
+/// Hard cap on bots per user, on top of the subscription tier limit
+const MAX_BOTS_PER_USER: i64 = 4;
+/// Region recorded for bots with no droplet
+const MANUAL_REGION: &str = "external";
+/// Typical time from droplet creation to the runner registering
+const ESTIMATED_BOOT_SECS: i64 = 240;
+
 /// Helper: Get bot with authorization check
 ///
 /// Validates that:
 /// 1. The user_id from auth context is valid
 /// 2. The bot exists
 /// 3. The authenticated user owns the bot
-async fn get_authorized_bot(
+pub(crate) async fn get_authorized_bot(
     db: &sqlx::PgPool,
     auth: &AuthContext,
     bot_id: Uuid,
@@ -70,22 +85,65 @@ pub async fn list_bots(
 }
 
 /// POST /bots - Create a new bot
+///
+/// With `?dry_run=true` nothing is created; the response is a
+/// `ProvisioningPlan` describing the checks and the droplet that would be used.
 pub async fn create_bot(
     State(state): State<Arc<AppState>>,
     Extension(auth): Extension<AuthContext>,
+    Extension(sub): Extension<SubscriptionContext>,
+    Extension(kill_switch): Extension<KillSwitchStatus>,
+    Query(params): Query<CreateBotParams>,
     Json(req): Json<CreateBotRequest>,
-) -> Result<Json<Bot>, (StatusCode, String)> {
+) -> Result<Response, (StatusCode, String)> {
+    let user_id = Uuid::parse_str(&auth.user_id)
+        .map_err(|_| (StatusCode::BAD_REQUEST, "Invalid user ID".to_string()))?;
+    kill_switch.ensure_released()?;
+
+    // Fill in what the request leaves to the owner's preferences
+    let prefs = preferences::load(&state.db, user_id)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    let Some(persona) = req.persona.or(prefs.default_persona) else {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "persona is required (or set default_persona in /me/preferences)".to_string(),
+        ));
+    };
+
+    if params.dry_run {
+        let plan = plan_bot_creation(&state, user_id, &sub, &req, &prefs).await?;
+        return Ok(Json(plan).into_response());
+    }
+
     if let Err(errors) = req.validate() {
         return Err((StatusCode::BAD_REQUEST, errors.to_string()));
     }
 
-    let user_id = Uuid::parse_str(&auth.user_id)
-        .map_err(|_| (StatusCode::BAD_REQUEST, "Invalid user ID".to_string()))?;
-
     // Validate risk caps are within safe ranges (before starting transaction)
     req.risk_caps
         .validate()
         .map_err(|e| (StatusCode::BAD_REQUEST, format!("Invalid risk caps: {}", e)))?;
+    validate_trailing_stop(req.trailing_stop_percent).map_err(|e| (StatusCode::BAD_REQUEST, e))?;
+    if let Some(limits) = &req.asset_limits {
+        limits.validate().map_err(|e| {
+            (
+                StatusCode::BAD_REQUEST,
+                format!("Invalid asset limits: {}", e),
+            )
+        })?;
+    }
+    if let Some(window) = &req.trading_window {
+        window.validate().map_err(|e| {
+            (
+                StatusCode::BAD_REQUEST,
+                format!("Invalid trading window: {}", e),
+            )
+        })?;
+    }
+    let registry = universe::registry(&state.db).await;
+    universe::validate_focus(&registry, req.asset_focus, req.custom_assets.as_deref())
+        .map_err(|e| (StatusCode::BAD_REQUEST, e))?;
 
     // Use transaction to prevent race condition between count check and insert
     let mut tx = state
@@ -103,7 +161,7 @@ pub async fn create_bot(
     .await
     .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
 
-    if bot_count >= 4 {
+    if bot_count >= MAX_BOTS_PER_USER {
         // Rollback not strictly needed since we're returning, but explicit
         let _ = tx.rollback().await;
         return Err((
@@ -112,6 +170,15 @@ pub async fn create_bot(
         ));
     }
 
+    let manual = req.provisioning == ProvisioningMode::Manual;
+    let region = if manual {
+        MANUAL_REGION.to_string()
+    } else {
+        match prefs.default_region {
+            Some(region) => region,
+            None => state.settings.current().provisioning.droplet_region.clone(),
+        }
+    };
     let config_id = Uuid::new_v4();
     let custom_assets_json = req.custom_assets.map(|a| serde_json::to_value(a).unwrap());
 
@@ -121,15 +188,15 @@ pub async fn create_bot(
             id, bot_id, version, name, persona, asset_focus, custom_assets,
             algorithm_mode, strictness, max_position_size_percent, max_daily_loss_usd,
             max_drawdown_percent, max_trades_per_day, trading_mode, llm_provider,
-            encrypted_llm_api_key
-        ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16)
+            encrypted_llm_api_key, trailing_stop_percent, asset_limits, trading_window
+        ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, $18, $19)
         "#,
     )
     .bind(config_id)
     .bind(Uuid::nil())
     .bind(1)
     .bind(&req.name)
-    .bind(req.persona)
+    .bind(persona)
     .bind(req.asset_focus)
     .bind(custom_assets_json)
     .bind(req.algorithm_mode)
@@ -146,6 +213,17 @@ pub async fn create_bot(
             .map(|k| state.secrets.encrypt(k).unwrap_or_default())
             .unwrap_or_default(),
     )
+    .bind(req.trailing_stop_percent)
+    .bind(
+        req.asset_limits
+            .as_ref()
+            .map(|l| serde_json::to_value(l).unwrap()),
+    )
+    .bind(
+        req.trading_window
+            .as_ref()
+            .map(|w| serde_json::to_value(w).unwrap()),
+    )
     .execute(&mut *tx)
     .await
     .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
@@ -158,17 +236,21 @@ pub async fn create_bot(
     let bot = sqlx::query_as::<_, Bot>(
         r#"
         INSERT INTO bots (
-            id, user_id, name, status, persona, region, desired_version_id, config_status, bootstrap_token
-        ) VALUES ($1, $2, $3, 'provisioning', $4, 'nyc1', $5, 'pending', $6)
+            id, user_id, name, status, persona, region, desired_version_id, config_status,
+            bootstrap_token, provisioning, encrypt_state
+        ) VALUES ($1, $2, $3, 'provisioning', $4, $5, $6, 'pending', $7, $8, $9)
         RETURNING *
-        "#
+        "#,
     )
     .bind(bot_id)
     .bind(user_id)
     .bind(&req.name)
-    .bind(req.persona)
+    .bind(persona)
+    .bind(&region)
     .bind(config_id)
     .bind(&bootstrap_token)
+    .bind(req.provisioning)
+    .bind(req.encrypt_state)
     .fetch_one(&mut *tx)
     .await
     .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
@@ -185,13 +267,17 @@ pub async fn create_bot(
         .await
         .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
 
-    // Clone state for async task
-    let pool = state.db.clone();
-    let secrets = state.secrets.clone();
-    let semaphore = state.droplet_semaphore.clone();
-    let metrics = state.metrics.clone();
+    if manual {
+        info!(
+            "Created manual bot {} for user {}, awaiting external runner",
+            bot_id, user_id
+        );
+        return Ok(Json(bot).into_response());
+    }
+
+    let state = Arc::clone(&state);
     tokio::spawn(async move {
-        spawn_bot_droplet(bot_id, req.name.clone(), pool, secrets, metrics, semaphore).await;
+        spawn_bot_droplet(bot_id, req.name.clone(), state).await;
     });
 
     info!(
@@ -199,21 +285,203 @@ pub async fn create_bot(
         bot_id, user_id
     );
 
-    Ok(Json(bot))
+    Ok(Json(bot).into_response())
+}
+
+fn plan_check(name: &'static str, result: Result<(), String>) -> PlanCheck {
+    match result {
+        Ok(()) => PlanCheck {
+            name,
+            passed: true,
+            detail: None,
+        },
+        Err(detail) => PlanCheck {
+            name,
+            passed: false,
+            detail: Some(detail),
+        },
+    }
+}
+
+/// Run every pre-flight check for a bot creation without writing anything
+async fn plan_bot_creation(
+    state: &AppState,
+    user_id: Uuid,
+    sub: &SubscriptionContext,
+    req: &CreateBotRequest,
+    prefs: &UserPreferences,
+) -> Result<ProvisioningPlan, (StatusCode, String)> {
+    use crate::config::{self, keys};
+
+    let mut checks = Vec::new();
+
+    // Config validation (same rules as a real create)
+    let config_result = req.validate().map_err(|e| e.to_string()).and_then(|_| {
+        req.risk_caps
+            .validate()
+            .map_err(|e| format!("Invalid risk caps: {}", e))
+    });
+    let registry = universe::registry(&state.db).await;
+    let config_result = config_result
+        .and_then(|_| validate_trailing_stop(req.trailing_stop_percent))
+        .and_then(|_| match &req.asset_limits {
+            Some(limits) => limits
+                .validate()
+                .map_err(|e| format!("Invalid asset limits: {}", e)),
+            None => Ok(()),
+        })
+        .and_then(|_| match &req.trading_window {
+            Some(window) => window
+                .validate()
+                .map_err(|e| format!("Invalid trading window: {}", e)),
+            None => Ok(()),
+        })
+        .and_then(|_| {
+            universe::validate_focus(&registry, req.asset_focus, req.custom_assets.as_deref())
+        });
+    checks.push(plan_check("config", config_result));
+
+    // Subscription entitlements
+    let bot_count: i64 = sqlx::query_scalar(
+        "SELECT COUNT(*) FROM bots WHERE user_id = $1 AND status != 'destroying'",
+    )
+    .bind(user_id)
+    .fetch_one(&state.db)
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    let bot_limit = (sub.tier.max_bots() as i64).min(MAX_BOTS_PER_USER);
+    checks.push(plan_check(
+        "bot_limit",
+        if bot_count < bot_limit {
+            Ok(())
+        } else {
+            Err(format!(
+                "{} of {} bots already in use",
+                bot_count, bot_limit
+            ))
+        },
+    ));
+    checks.push(plan_check(
+        "live_trading",
+        if req.trading_mode != TradingMode::Live || sub.tier.has_feature("live_trading") {
+            Ok(())
+        } else {
+            Err("Live trading requires a Pro subscription".to_string())
+        },
+    ));
+    checks.push(plan_check(
+        "trades_per_day",
+        if req.risk_caps.max_trades_per_day <= sub.tier.max_trades_per_day() {
+            Ok(())
+        } else {
+            Err(format!(
+                "Plan allows at most {} trades per day",
+                sub.tier.max_trades_per_day()
+            ))
+        },
+    ));
+
+    if req.provisioning == ProvisioningMode::Manual {
+        // Nothing to provision; ready as soon as the owner starts the runner
+        return Ok(ProvisioningPlan {
+            would_succeed: checks.iter().all(|c| c.passed),
+            checks,
+            region: MANUAL_REGION.to_string(),
+            size: String::new(),
+            image: String::new(),
+            estimated_monthly_cost_usd: None,
+            queue_position: 0,
+            estimated_ready_secs: 0,
+            estimated_ready_at: Utc::now(),
+        });
+    }
+
+    // Provider: token, quota, region/size availability
+    let settings = state.settings.current();
+    let mut spec = provisioning::droplet_spec(&settings);
+    if let Some(region) = &prefs.default_region {
+        spec.region = region.clone();
+    }
+    let token = config::get_config_decrypted(&state.db, &state.secrets, keys::DIGITALOCEAN_TOKEN)
+        .await
+        .filter(|t| !t.is_empty());
+    match token {
+        None => checks.push(plan_check(
+            "do_token",
+            Err("DigitalOcean token not configured".to_string()),
+        )),
+        Some(token) => {
+            match provisioning::fetch_account_quota(&token).await {
+                Err(e) => checks.push(plan_check("do_token", Err(e.to_string()))),
+                Ok(quota) => {
+                    checks.push(plan_check(
+                        "do_token",
+                        if quota.status == "active" {
+                            Ok(())
+                        } else {
+                            Err(format!("Account status is '{}'", quota.status))
+                        },
+                    ));
+                    checks.push(plan_check(
+                        "do_quota",
+                        if quota.droplets_in_use < quota.droplet_limit {
+                            Ok(())
+                        } else {
+                            Err(format!(
+                                "{} of {} droplets in use",
+                                quota.droplets_in_use, quota.droplet_limit
+                            ))
+                        },
+                    ));
+                    let region_result =
+                        match provisioning::region_offers_size(&token, &spec.region, &spec.size)
+                            .await
+                        {
+                            Ok(true) => Ok(()),
+                            Ok(false) => Err(format!(
+                                "Region {} is not offering size {}",
+                                spec.region, spec.size
+                            )),
+                            Err(e) => Err(e.to_string()),
+                        };
+                    checks.push(plan_check("region", region_result));
+                }
+            }
+        }
+    }
+
+    // Ready-time estimate from the current provisioning backlog
+    let queue_position: i64 = sqlx::query_scalar(
+        "SELECT COUNT(*) FROM bots WHERE status = 'provisioning' AND provisioning = 'droplet'",
+    )
+    .fetch_one(&state.db)
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    let concurrent = i64::from(settings.limits.max_concurrent_provisions.max(1));
+    let estimated_ready_secs = ESTIMATED_BOOT_SECS * (queue_position / concurrent + 1);
+
+    Ok(ProvisioningPlan {
+        would_succeed: checks.iter().all(|c| c.passed),
+        checks,
+        estimated_monthly_cost_usd: crate::droplets::monthly_price_usd(&spec.size),
+        region: spec.region,
+        size: spec.size,
+        image: spec.image,
+        queue_position,
+        estimated_ready_secs,
+        estimated_ready_at: Utc::now() + chrono::Duration::seconds(estimated_ready_secs),
+    })
 }
 
 /// Spawn bot droplet on DigitalOcean using claw-spawn
 ///
-/// Uses semaphore for concurrency control (max 3 concurrent provisions)
+/// Uses semaphore for concurrency control (`max_concurrent_provisions`)
 /// and retry with exponential backoff for DO API calls
-async fn spawn_bot_droplet(
-    bot_id: Uuid,
-    bot_name: String,
-    pool: Db,
-    secrets: crate::SecretsManager,
-    metrics: crate::MetricsCollector,
-    semaphore: Arc<tokio::sync::Semaphore>,
-) {
+async fn spawn_bot_droplet(bot_id: Uuid, bot_name: String, state: Arc<AppState>) {
+    let pool = state.db.clone();
+    let secrets = &state.secrets;
+    let metrics = &state.metrics;
+    let semaphore = state.droplet_semaphore.clone();
     use crate::config::{self, keys};
 
     // Track in-flight metric before acquiring permit
@@ -244,7 +512,7 @@ async fn spawn_bot_droplet(
 
     // Get DO token from platform_config (encrypted)
     let do_token =
-        match config::get_config_decrypted(&pool, &secrets, keys::DIGITALOCEAN_TOKEN).await {
+        match config::get_config_decrypted(&pool, secrets, keys::DIGITALOCEAN_TOKEN).await {
             Some(token) if !token.is_empty() => token,
             _ => {
                 warn!(
@@ -268,13 +536,8 @@ async fn spawn_bot_droplet(
     let id_str = bot_id.to_string();
     let droplet_name = format!("trawler-{}", &id_str[..8.min(id_str.len())]);
 
-    // Get control plane URL from platform_config
-    let control_plane_url = config::get_config_or(
-        &pool,
-        keys::CONTROL_PLANE_URL,
-        "https://api.trawlingtraders.com",
-    )
-    .await;
+    let settings = state.settings.current();
+    let control_plane_url = settings.services.control_plane_url.clone();
 
     // Fetch bot's bootstrap token from database
     let bootstrap_token = match sqlx::query_scalar::<_, Option<String>>(
@@ -313,15 +576,31 @@ async fn spawn_bot_droplet(
         &user_data_config,
     );
 
+    // The bot's region was picked at creation (the owner's default_region, if set)
+    let mut spec = provisioning::droplet_spec(&settings);
+    match sqlx::query_scalar::<_, String>("SELECT region FROM bots WHERE id = $1")
+        .bind(bot_id)
+        .fetch_optional(&pool)
+        .await
+    {
+        Ok(Some(region)) => spec.region = region,
+        Ok(None) => {}
+        Err(e) => warn!("Failed to load region for bot {}: {}", bot_id, e),
+    }
     let droplet_req = claw_spawn::domain::DropletCreateRequest {
         name: droplet_name,
-        region: "nyc3".to_string(),
-        size: "s-1vcpu-2gb".to_string(),
-        image: "ubuntu-22-04-x64".to_string(),
+        region: spec.region,
+        size: spec.size,
+        image: spec.image,
         user_data,
-        tags: vec!["trawling-traders".to_string(), format!("bot-{}", bot_id)],
+        tags: vec![
+            provisioning::DROPLET_TAG.to_string(),
+            format!("bot-{}", bot_id),
+        ],
     };
 
+    let (region, size) = (droplet_req.region.clone(), droplet_req.size.clone());
+
     match do_client.create_droplet(droplet_req).await {
         Ok(droplet) => {
             info!(
@@ -329,6 +608,22 @@ async fn spawn_bot_droplet(
                 bot_id, droplet.name, droplet.id
             );
 
+            if let Err(e) = crate::droplets::record_droplet_created(
+                &pool,
+                bot_id,
+                droplet.id,
+                &droplet.name,
+                &region,
+                &size,
+            )
+            .await
+            {
+                error!(
+                    "Failed to record droplet {} for bot {}: {}",
+                    droplet.id, bot_id, e
+                );
+            }
+
             // Update bot with droplet_id
             if let Err(e) = sqlx::query(
                 "UPDATE bots SET droplet_id = $1, status = 'online', updated_at = NOW() WHERE id = $2"
@@ -381,6 +676,7 @@ async fn destroy_bot_droplet(
     match do_client.destroy_droplet(droplet_id).await {
         Ok(_) => {
             info!("Bot {}: Destroyed droplet {}", bot_id, droplet_id);
+            mark_droplet_destroyed(&pool, droplet_id).await;
 
             // Mark bot as destroyed
             if let Err(e) = sqlx::query(
@@ -397,6 +693,7 @@ async fn destroy_bot_droplet(
                 "Bot {}: Droplet {} already destroyed or not found",
                 bot_id, droplet_id
             );
+            mark_droplet_destroyed(&pool, droplet_id).await;
         }
         Err(e) => {
             error!(
@@ -412,17 +709,17 @@ async fn redeploy_bot_droplet(
     bot_id: Uuid,
     bot_name: String,
     old_droplet_id: Option<i64>,
-    pool: Db,
-    secrets: crate::SecretsManager,
-    metrics: crate::MetricsCollector,
-    semaphore: Arc<tokio::sync::Semaphore>,
+    state: Arc<AppState>,
 ) {
     use crate::config::{self, keys};
 
+    let pool = state.db.clone();
+    let secrets = &state.secrets;
+
     // Destroy old droplet if exists
     if let Some(droplet_id) = old_droplet_id {
         let do_token =
-            match config::get_config_decrypted(&pool, &secrets, keys::DIGITALOCEAN_TOKEN).await {
+            match config::get_config_decrypted(&pool, secrets, keys::DIGITALOCEAN_TOKEN).await {
                 Some(token) if !token.is_empty() => token,
                 _ => {
                     warn!(
@@ -435,7 +732,9 @@ async fn redeploy_bot_droplet(
             };
 
         if let Ok(do_client) = claw_spawn::infrastructure::DigitalOceanClient::new(do_token) {
-            let _ = do_client.destroy_droplet(droplet_id).await;
+            if do_client.destroy_droplet(droplet_id).await.is_ok() {
+                mark_droplet_destroyed(&pool, droplet_id).await;
+            }
             info!(
                 "Bot {}: Destroyed old droplet {} for redeploy",
                 bot_id, droplet_id
@@ -450,7 +749,14 @@ async fn redeploy_bot_droplet(
         .await;
 
     // Spawn new droplet with retry logic
-    spawn_bot_droplet(bot_id, bot_name, pool, secrets, metrics, semaphore).await;
+    spawn_bot_droplet(bot_id, bot_name, state).await;
+}
+
+/// Helper: Close out a droplet's cost record, logging rather than failing
+async fn mark_droplet_destroyed(pool: &Db, droplet_id: i64) {
+    if let Err(e) = crate::droplets::record_droplet_destroyed(pool, droplet_id).await {
+        error!("Failed to record destroy of droplet {}: {}", droplet_id, e);
+    }
 }
 
 /// Helper: Update bot status with error message
@@ -482,6 +788,225 @@ pub async fn get_bot(
     Ok(Json(BotResponse { bot, config }))
 }
 
+/// GET /bots/:id/infra-cost - Estimated droplet cost for the bot
+///
+/// Hidden (404) unless `show_infra_cost_to_users` is enabled.
+pub async fn get_infra_cost(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Path(bot_id): Path<Uuid>,
+) -> Result<Json<InfraCostResponse>, (StatusCode, String)> {
+    if !state
+        .settings
+        .current()
+        .provisioning
+        .show_infra_cost_to_users
+    {
+        return Err((StatusCode::NOT_FOUND, "Not found".to_string()));
+    }
+
+    let _bot = get_authorized_bot(&state.db, &auth, bot_id).await?;
+
+    let cost = crate::droplets::bot_infra_cost(&state.db, bot_id)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    Ok(Json(InfraCostResponse {
+        bot_id,
+        monthly_cost_usd: cost.monthly_run_rate_usd,
+        last_30d_cost_usd: cost.last_30d_cost_usd,
+    }))
+}
+
+/// Query params for GET /bots/:id/llm-usage
+#[derive(Debug, serde::Deserialize)]
+pub struct LlmUsageParams {
+    /// Days of history (default 30, max 90)
+    pub days: Option<i64>,
+}
+
+/// Response for GET /bots/:id/llm-usage
+#[derive(Debug, serde::Serialize)]
+pub struct LlmUsageResponse {
+    pub bot_id: Uuid,
+    /// Per day, provider and model, newest first
+    pub days: Vec<crate::llm_usage::LlmUsageRow>,
+    pub total: crate::llm_usage::LlmUsageRow,
+    /// Estimated spend of all the owner's bots this month
+    pub month_to_date_cost_usd: rust_decimal::Decimal,
+    pub included_budget_usd: rust_decimal::Decimal,
+}
+
+/// GET /bots/:id/llm-usage - LLM requests, tokens and estimated cost
+pub async fn get_llm_usage(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Extension(sub): Extension<SubscriptionContext>,
+    Path(bot_id): Path<Uuid>,
+    Query(params): Query<LlmUsageParams>,
+) -> Result<Json<LlmUsageResponse>, (StatusCode, String)> {
+    let bot = get_authorized_bot(&state.db, &auth, bot_id).await?;
+
+    let now = Utc::now();
+    let days = params.days.unwrap_or(30).clamp(1, 90);
+    let since = (now - chrono::Duration::days(days - 1)).date_naive();
+    let rows = crate::llm_usage::bot_usage(&state.db, bot_id, since)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    let month_to_date_cost_usd = crate::llm_usage::month_to_date_cost(&state.db, bot.user_id, now)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    Ok(Json(LlmUsageResponse {
+        bot_id,
+        total: crate::llm_usage::total(&rows),
+        days: rows,
+        month_to_date_cost_usd,
+        included_budget_usd: sub.tier.included_llm_budget_usd(),
+    }))
+}
+
+/// Query params for GET /bots/:id/daily-marks
+#[derive(Debug, serde::Deserialize)]
+pub struct DailyMarksParams {
+    /// Days of history (default 30, max 366)
+    pub days: Option<i64>,
+}
+
+/// Response for GET /bots/:id/daily-marks
+#[derive(Debug, serde::Serialize)]
+pub struct DailyMarksResponse {
+    pub bot_id: Uuid,
+    /// Oldest first
+    pub marks: Vec<crate::daily_marks::StoredMark>,
+    /// Drawdowns from close to close over these marks
+    pub drawdowns: Vec<crate::drawdowns::DrawdownEpisode>,
+    /// Marks with issues
+    pub flagged: usize,
+}
+
+/// GET /bots/:id/daily-marks - Official end-of-day closes
+pub async fn get_daily_marks(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Path(bot_id): Path<Uuid>,
+    Query(params): Query<DailyMarksParams>,
+) -> Result<Json<DailyMarksResponse>, (StatusCode, String)> {
+    get_authorized_bot(&state.db, &auth, bot_id).await?;
+
+    let now = Utc::now();
+    let days = params.days.unwrap_or(30).clamp(1, 366);
+    let since = (now - chrono::Duration::days(days)).date_naive();
+    let marks = crate::daily_marks::marks_since(&state.db, bot_id, since)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    Ok(Json(DailyMarksResponse {
+        bot_id,
+        drawdowns: crate::daily_marks::drawdowns(&marks, now),
+        flagged: marks.iter().filter(|m| m.flagged).count(),
+        marks,
+    }))
+}
+
+/// GET /bots/:id/funding - Where to send funds before the bot can trade live
+///
+/// The runner checks its balances when live mode is applied and reports
+/// `insufficient_funding` / `funding_confirmed`; the latest one sets `status`.
+pub async fn get_funding_instructions(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Path(bot_id): Path<Uuid>,
+) -> Result<Json<FundingInstructionsResponse>, (StatusCode, String)> {
+    let bot = get_authorized_bot(&state.db, &auth, bot_id).await?;
+    let required = state.settings.current().trading.funding_requirements();
+
+    let last_check: Option<(String, Option<serde_json::Value>, chrono::DateTime<Utc>)> =
+        sqlx::query_as(
+            r#"
+            SELECT event_type::text, metadata, created_at FROM events
+            WHERE bot_id = $1
+              AND event_type::text IN ('insufficient_funding', 'funding_confirmed')
+            ORDER BY created_at DESC
+            LIMIT 1
+            "#,
+        )
+        .bind(bot_id)
+        .fetch_optional(&state.db)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    let status = match last_check.as_ref().map(|(kind, _, _)| kind.as_str()) {
+        Some("funding_confirmed") => "funded",
+        Some(_) => "insufficient",
+        None => "unknown",
+    };
+
+    let instructions = match &bot.agent_wallet {
+        Some(address) => format!(
+            "Send at least {} USDC and {} SOL (for fees) to {} on Solana mainnet. \
+             The bot starts live trading once its next balance check passes.",
+            required.min_usdc, required.min_sol, address
+        ),
+        None => {
+            "The bot has not reported its wallet yet; check back once it is online.".to_string()
+        }
+    };
+
+    Ok(Json(FundingInstructionsResponse {
+        bot_id,
+        wallet_address: bot.agent_wallet,
+        required,
+        status: status.to_string(),
+        last_checked_at: last_check.as_ref().map(|(_, _, at)| *at),
+        last_check: last_check.and_then(|(_, metadata, _)| metadata),
+        instructions,
+    }))
+}
+
+/// POST /bots/:id/credentials - Issue runner credentials for a manual bot
+///
+/// Rotates the bootstrap token, so earlier credentials stop working. The
+/// runner exchanges the token for its secrets on first start.
+pub async fn issue_runner_credentials(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Path(bot_id): Path<Uuid>,
+) -> Result<Json<RunnerCredentialsResponse>, (StatusCode, String)> {
+    let bot = get_authorized_bot(&state.db, &auth, bot_id).await?;
+    if bot.provisioning != ProvisioningMode::Manual {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "Credentials are only issued for manually provisioned bots".to_string(),
+        ));
+    }
+
+    let bootstrap_token = generate_bootstrap_token();
+    sqlx::query(
+        "UPDATE bots SET bootstrap_token = $1, bootstrap_token_used_at = NULL, updated_at = NOW()
+         WHERE id = $2",
+    )
+    .bind(&bootstrap_token)
+    .bind(bot_id)
+    .execute(&state.db)
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    let control_plane_url = state.settings.current().services.control_plane_url.clone();
+
+    info!("Issued runner credentials for manual bot {}", bot_id);
+
+    Ok(Json(RunnerCredentialsResponse {
+        bot_id,
+        env: format!(
+            "RUNNER_MODE=external\nBOT_ID={}\nCONTROL_PLANE_URL={}\nBOOTSTRAP_TOKEN={}\n",
+            bot_id, control_plane_url, bootstrap_token
+        ),
+        bootstrap_token,
+        control_plane_url,
+    }))
+}
+
 /// PATCH /bots/:id/config - Update bot config
 pub async fn update_bot_config(
     State(state): State<Arc<AppState>>,
@@ -508,6 +1033,31 @@ pub async fn update_bot_config(
         .risk_caps
         .validate()
         .map_err(|e| (StatusCode::BAD_REQUEST, format!("Invalid risk caps: {}", e)))?;
+    validate_trailing_stop(req.config.trailing_stop_percent)
+        .map_err(|e| (StatusCode::BAD_REQUEST, e))?;
+    if let Some(limits) = &req.config.asset_limits {
+        limits.validate().map_err(|e| {
+            (
+                StatusCode::BAD_REQUEST,
+                format!("Invalid asset limits: {}", e),
+            )
+        })?;
+    }
+    if let Some(window) = &req.config.trading_window {
+        window.validate().map_err(|e| {
+            (
+                StatusCode::BAD_REQUEST,
+                format!("Invalid trading window: {}", e),
+            )
+        })?;
+    }
+    let registry = universe::registry(&state.db).await;
+    universe::validate_focus(
+        &registry,
+        req.config.asset_focus,
+        req.config.custom_assets.as_deref(),
+    )
+    .map_err(|e| (StatusCode::BAD_REQUEST, e))?;
 
     let custom_assets_json = req
         .config
@@ -520,8 +1070,8 @@ pub async fn update_bot_config(
             id, bot_id, version, name, persona, asset_focus, custom_assets,
             algorithm_mode, strictness, max_position_size_percent, max_daily_loss_usd,
             max_drawdown_percent, max_trades_per_day, trading_mode, llm_provider,
-            encrypted_llm_api_key
-        ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16)
+            encrypted_llm_api_key, trailing_stop_percent, asset_limits, trading_window
+        ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, $18, $19)
         "#,
     )
     .bind(config_id)
@@ -546,6 +1096,19 @@ pub async fn update_bot_config(
             .map(|k| state.secrets.encrypt(k).unwrap_or_default())
             .unwrap_or_default(),
     )
+    .bind(req.config.trailing_stop_percent)
+    .bind(
+        req.config
+            .asset_limits
+            .as_ref()
+            .map(|l| serde_json::to_value(l).unwrap()),
+    )
+    .bind(
+        req.config
+            .trading_window
+            .as_ref()
+            .map(|w| serde_json::to_value(w).unwrap()),
+    )
     .execute(&state.db)
     .await
     .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
@@ -570,19 +1133,95 @@ pub async fn update_bot_config(
     Ok(Json(config))
 }
 
+/// GET /bots/:id/config/export - Current config as a portable YAML document
+///
+/// Secrets are left out; see `config_document`.
+pub async fn export_bot_config(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Path(bot_id): Path<Uuid>,
+) -> Result<Response, (StatusCode, String)> {
+    let bot = get_authorized_bot(&state.db, &auth, bot_id).await?;
+
+    let config = sqlx::query_as::<_, ConfigVersion>("SELECT * FROM config_versions WHERE id = $1")
+        .bind(bot.desired_version_id)
+        .fetch_one(&state.db)
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::RowNotFound => (StatusCode::NOT_FOUND, "Config not found".to_string()),
+            _ => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+        })?;
+    let yaml = ConfigDocument::from_version(&config)
+        .and_then(|doc| doc.to_yaml())
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?;
+
+    let filename: String = bot
+        .name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+        .collect();
+    Ok((
+        [
+            (header::CONTENT_TYPE, "application/yaml".to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"{}.yaml\"", filename),
+            ),
+        ],
+        yaml,
+    )
+        .into_response())
+}
+
+/// POST /bots/import - Create a bot from a YAML config document
+///
+/// The body is a document from `GET /bots/:id/config/export`. It goes through
+/// the same checks as `POST /bots` (including `?dry_run=true`); the new bot
+/// has no LLM API key or channel tokens until they are set.
+pub async fn import_bot(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Extension(sub): Extension<SubscriptionContext>,
+    Extension(kill_switch): Extension<KillSwitchStatus>,
+    Query(params): Query<CreateBotParams>,
+    body: String,
+) -> Result<Response, (StatusCode, String)> {
+    let doc = ConfigDocument::from_yaml(&body).map_err(|e| (StatusCode::BAD_REQUEST, e))?;
+    info!(
+        "Importing config document '{}' for user {}",
+        doc.name, auth.user_id
+    );
+
+    create_bot(
+        State(state),
+        Extension(auth),
+        Extension(sub),
+        Extension(kill_switch),
+        Query(params),
+        Json(doc.into_create_request()),
+    )
+    .await
+}
+
 /// POST /bots/:id/actions - Perform action on bot
 pub async fn bot_action(
     State(state): State<Arc<AppState>>,
     Extension(auth): Extension<AuthContext>,
+    Extension(sub): Extension<SubscriptionContext>,
+    Extension(kill_switch): Extension<KillSwitchStatus>,
     Path(bot_id): Path<Uuid>,
     Json(req): Json<BotActionRequest>,
 ) -> Result<StatusCode, (StatusCode, String)> {
     let bot = get_authorized_bot(&state.db, &auth, bot_id).await?;
+    if matches!(req.action, BotAction::Resume | BotAction::Redeploy) {
+        kill_switch.ensure_released()?;
+    }
 
     let pool = state.db.clone();
 
     match req.action {
         BotAction::Pause => {
+            // The runner sees `desired_status: paused` on its next sync and stops deciding
             sqlx::query("UPDATE bots SET status = $1, updated_at = NOW() WHERE id = $2")
                 .bind(BotStatus::Paused)
                 .bind(bot_id)
@@ -592,15 +1231,37 @@ pub async fn bot_action(
             info!("Bot {} paused", bot_id);
         }
         BotAction::Resume => {
-            sqlx::query("UPDATE bots SET status = $1, updated_at = NOW() WHERE id = $2")
-                .bind(BotStatus::Online)
-                .bind(bot_id)
-                .execute(&state.db)
-                .await
-                .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+            // Bots paused for an unpaid subscription come back when it's paid
+            let billing_paused: bool =
+                sqlx::query_scalar("SELECT billing_paused_at IS NOT NULL FROM bots WHERE id = $1")
+                    .bind(bot_id)
+                    .fetch_one(&state.db)
+                    .await
+                    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+            if billing_paused && !sub.tier.has_feature("live_trading") {
+                return Err((
+                    StatusCode::PAYMENT_REQUIRED,
+                    "Live trading is paused until the subscription is paid".to_string(),
+                ));
+            }
+            sqlx::query(
+                "UPDATE bots SET status = $1, billing_paused_at = NULL, updated_at = NOW() \
+                 WHERE id = $2",
+            )
+            .bind(BotStatus::Online)
+            .bind(bot_id)
+            .execute(&state.db)
+            .await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
             info!("Bot {} resumed", bot_id);
         }
         BotAction::Redeploy => {
+            if bot.provisioning == ProvisioningMode::Manual {
+                return Err((
+                    StatusCode::BAD_REQUEST,
+                    "Manual bots are redeployed by restarting the runner".to_string(),
+                ));
+            }
             sqlx::query("UPDATE bots SET status = $1, updated_at = NOW() WHERE id = $2")
                 .bind(BotStatus::Provisioning)
                 .bind(bot_id)
@@ -608,22 +1269,24 @@ pub async fn bot_action(
                 .await
                 .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
 
+            // A running droplet hands its state to the next one before it goes
+            let export_requested_at = match bot.droplet_id {
+                Some(_) => Some(
+                    handover::request_state_export(&state.db, bot_id)
+                        .await
+                        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?,
+                ),
+                None => None,
+            };
+
             let bot_name = bot.name.clone();
             let old_droplet_id = bot.droplet_id;
-            let secrets = state.secrets.clone();
-            let semaphore = state.droplet_semaphore.clone();
-            let metrics = state.metrics.clone();
+            let state = Arc::clone(&state);
             tokio::spawn(async move {
-                redeploy_bot_droplet(
-                    bot_id,
-                    bot_name,
-                    old_droplet_id,
-                    pool,
-                    secrets,
-                    metrics,
-                    semaphore,
-                )
-                .await;
+                if let Some(since) = export_requested_at {
+                    handover::await_state_export(&pool, bot_id, since).await;
+                }
+                redeploy_bot_droplet(bot_id, bot_name, old_droplet_id, state).await;
             });
             info!("Bot {} redeploy triggered", bot_id);
         }
@@ -649,56 +1312,749 @@ pub async fn bot_action(
 }
 
 /// GET /bots/:id/metrics - Get bot metrics
+///
+/// `range` is 24h, 7d (default), 30d or all; `resolution` is raw or a bucket
+/// width (5m, 15m, 1h, 4h, 1d), picked to fit the range when left out.
 pub async fn get_metrics(
     State(state): State<Arc<AppState>>,
     Extension(auth): Extension<AuthContext>,
     Path(bot_id): Path<Uuid>,
+    Query(query): Query<MetricsQuery>,
 ) -> Result<Json<MetricsResponse>, (StatusCode, String)> {
     // Verify bot exists and user is authorized
     let _bot = get_authorized_bot(&state.db, &auth, bot_id).await?;
 
-    let metrics_db = sqlx::query_as::<_, MetricDb>(
-        r#"
-        SELECT * FROM metrics 
-        WHERE bot_id = $1 
-        AND timestamp > NOW() - INTERVAL '7 days'
-        ORDER BY timestamp DESC
-        LIMIT 1000
-        "#,
+    let range = match query.range.as_deref() {
+        None => MetricsRange::Week,
+        Some(r) => MetricsRange::parse(r).ok_or((
+            StatusCode::BAD_REQUEST,
+            "range must be one of 24h, 7d, 30d, all".to_string(),
+        ))?,
+    };
+    let since = metric_series::range_start(&state.db, bot_id, range)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    let span = Utc::now() - since;
+    let resolution = match query.resolution.as_deref() {
+        None => Resolution::auto(span),
+        Some(r) => {
+            let resolution = Resolution::parse(r).ok_or((
+                StatusCode::BAD_REQUEST,
+                "resolution must be raw, 5m, 15m, 1h, 4h or 1d".to_string(),
+            ))?;
+            if resolution
+                .points(span)
+                .is_some_and(|points| points > metric_series::MAX_POINTS)
+            {
+                return Err((
+                    StatusCode::BAD_REQUEST,
+                    format!(
+                        "{} over {} is more than {} points; use a coarser resolution",
+                        r,
+                        range.as_str(),
+                        metric_series::MAX_POINTS
+                    ),
+                ));
+            }
+            resolution
+        }
+    };
+
+    let metrics_db = metric_series::load(&state.db, bot_id, since, resolution)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    let metrics: Vec<Metric> = metrics_db.into_iter().map(Metric::from).collect();
+    let drawdowns = crate::drawdowns::episodes_since(&state.db, bot_id, since)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    let performance: Option<serde_json::Value> = sqlx::query_scalar(
+        "SELECT performance FROM metrics WHERE bot_id = $1 AND performance IS NOT NULL \
+         ORDER BY timestamp DESC LIMIT 1",
     )
     .bind(bot_id)
-    .fetch_all(&state.db)
+    .fetch_optional(&state.db)
     .await
     .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
-
-    let metrics: Vec<Metric> = metrics_db.into_iter().map(Metric::from).collect();
+    let performance = match performance.map(serde_json::from_value::<PerformanceStats>) {
+        Some(Ok(stats)) => Some(stats),
+        Some(Err(e)) => {
+            warn!("Unreadable performance stats for bot {}: {}", bot_id, e);
+            None
+        }
+        None => None,
+    };
 
     Ok(Json(MetricsResponse {
         metrics,
-        range: "7d".to_string(),
+        range: range.as_str().to_string(),
+        resolution: resolution.as_str().to_string(),
+        drawdowns,
+        performance,
     }))
 }
 
-/// GET /bots/:id/events - Get bot events
+/// GET /bots/:id/events - Get bot events, newest first
+///
+/// Pages with `cursor` (the previous page's `next_cursor`) and filters by
+/// `event_type` (comma-separated), `severity` and `since`/`until`.
 pub async fn get_events(
     State(state): State<Arc<AppState>>,
     Extension(auth): Extension<AuthContext>,
     Path(bot_id): Path<Uuid>,
+    Query(query): Query<EventsQuery>,
 ) -> Result<Json<EventsResponse>, (StatusCode, String)> {
     // Verify bot exists and user is authorized
     let _bot = get_authorized_bot(&state.db, &auth, bot_id).await?;
 
-    let events = sqlx::query_as::<_, Event>(
-        "SELECT * FROM events WHERE bot_id = $1 ORDER BY created_at DESC LIMIT 100",
+    let after = query
+        .cursor
+        .as_deref()
+        .map(|c| {
+            events::EventCursor::decode(c)
+                .ok_or((StatusCode::BAD_REQUEST, "Invalid cursor".to_string()))
+        })
+        .transpose()?;
+    if let Some(s) = &query.severity {
+        if !events::SEVERITIES.contains(&s.as_str()) {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                format!("severity must be one of {}", events::SEVERITIES.join(", ")),
+            ));
+        }
+    }
+    if let (Some(since), Some(until)) = (query.since, query.until) {
+        if since >= until {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                "since must be before until".to_string(),
+            ));
+        }
+    }
+
+    let filter = events::EventFilter {
+        event_types: query
+            .event_type
+            .as_deref()
+            .map(events::parse_event_types)
+            .unwrap_or_default(),
+        severity: query.severity,
+        since: query.since,
+        until: query.until,
+        after,
+        limit: query.limit.unwrap_or(events::DEFAULT_PAGE_SIZE),
+    };
+    let (events, next_cursor) = events::list_events(&state.db, bot_id, &filter)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    Ok(Json(EventsResponse {
+        events,
+        next_cursor,
+    }))
+}
+
+/// GET /bots/:id/journal/verify - Re-check the bot's decision journal hash chain
+pub async fn verify_journal(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Path(bot_id): Path<Uuid>,
+) -> Result<Json<crate::journal::JournalVerification>, (StatusCode, String)> {
+    let _bot = get_authorized_bot(&state.db, &auth, bot_id).await?;
+
+    let verification = crate::journal::verify_bot_journal(&state.db, bot_id)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    Ok(Json(verification))
+}
+
+/// Query params for GET /bots/:id/performance
+#[derive(Debug, serde::Deserialize)]
+pub struct PerformanceParams {
+    /// day (default) or week
+    pub period: Option<String>,
+    /// Days of history (default 30, max 366)
+    pub days: Option<i64>,
+}
+
+/// Response for GET /bots/:id/performance
+#[derive(Debug, serde::Serialize)]
+pub struct PerformanceResponse {
+    pub bot_id: Uuid,
+    pub period: String,
+    /// Oldest first; the current period is partial
+    pub rollups: Vec<crate::rollups::Rollup>,
+}
+
+/// GET /bots/:id/performance - Daily or weekly PnL, trades, win rate, fees and drawdown
+///
+/// Served from the rollup task's table, refreshed hourly.
+pub async fn get_performance(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Path(bot_id): Path<Uuid>,
+    Query(params): Query<PerformanceParams>,
+) -> Result<Json<PerformanceResponse>, (StatusCode, String)> {
+    get_authorized_bot(&state.db, &auth, bot_id).await?;
+
+    let period = match params.period.as_deref() {
+        None => crate::rollups::Period::Day,
+        Some(p) => crate::rollups::Period::parse(p).ok_or((
+            StatusCode::BAD_REQUEST,
+            "period must be day or week".to_string(),
+        ))?,
+    };
+    let days = params.days.unwrap_or(30).clamp(1, 366);
+    let since = period.start_of((Utc::now() - chrono::Duration::days(days)).date_naive());
+    let rollups = crate::rollups::rollups_since(&state.db, bot_id, period, since)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    Ok(Json(PerformanceResponse {
+        bot_id,
+        period: period.as_str().to_string(),
+        rollups,
+    }))
+}
+
+/// Query params for GET /bots/:id/trades
+#[derive(Debug, serde::Deserialize)]
+pub struct TradesParams {
+    /// `next_cursor` of the previous page
+    pub cursor: Option<String>,
+    /// Only trades of this token
+    pub mint: Option<String>,
+    /// Max rows (default 100, capped at 500)
+    pub limit: Option<i64>,
+}
+
+/// Response for GET /bots/:id/trades
+#[derive(Debug, serde::Serialize)]
+pub struct TradesResponse {
+    /// Newest first
+    pub trades: Vec<crate::trades::Trade>,
+    /// Pass as `cursor` for the next (older) page; None on the last page
+    pub next_cursor: Option<String>,
+}
+
+/// GET /bots/:id/trades - Trade history with fees, slippage and realized PnL
+pub async fn get_trades(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Path(bot_id): Path<Uuid>,
+    Query(params): Query<TradesParams>,
+) -> Result<Json<TradesResponse>, (StatusCode, String)> {
+    get_authorized_bot(&state.db, &auth, bot_id).await?;
+
+    let after = params
+        .cursor
+        .as_deref()
+        .map(|c| {
+            events::EventCursor::decode(c)
+                .ok_or((StatusCode::BAD_REQUEST, "Invalid cursor".to_string()))
+        })
+        .transpose()?;
+    let (trades, next_cursor) = crate::trades::list_trades(
+        &state.db,
+        bot_id,
+        params.mint.as_deref(),
+        after,
+        params.limit.unwrap_or(events::DEFAULT_PAGE_SIZE),
+    )
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    Ok(Json(TradesResponse {
+        trades,
+        next_cursor,
+    }))
+}
+
+/// GET /bots/:id/performance/by-asset - PnL, win rate, holding time and fees per mint
+///
+/// Built from the `trade_confirmed` ledger (optionally the last `days`),
+/// with open quantity marked at current prices where available.
+pub async fn get_performance_by_asset(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Path(bot_id): Path<Uuid>,
+    Query(query): Query<AssetPerformanceQuery>,
+) -> Result<Json<AssetPerformanceResponse>, (StatusCode, String)> {
+    let _bot = get_authorized_bot(&state.db, &auth, bot_id).await?;
+    let since = match query.days {
+        Some(days) if !(1..=365).contains(&days) => {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                format!("days must be 1-365, got {}", days),
+            ))
+        }
+        Some(days) => Some(Utc::now() - chrono::Duration::days(days)),
+        None => None,
+    };
+
+    let rows: Vec<(chrono::DateTime<Utc>, Option<serde_json::Value>)> = sqlx::query_as(
+        "SELECT created_at, metadata FROM events \
+         WHERE bot_id = $1 AND event_type::text = 'trade_confirmed' \
+         AND ($2::timestamptz IS NULL OR created_at > $2) \
+         ORDER BY created_at",
     )
     .bind(bot_id)
+    .bind(since)
     .fetch_all(&state.db)
     .await
     .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    let trades: Vec<crate::performance::FeeTrade> = rows
+        .iter()
+        .filter_map(|(at, metadata)| {
+            crate::performance::FeeTrade::from_event(*at, metadata.as_ref()?)
+        })
+        .collect();
+
+    let without_marks = crate::performance::asset_breakdown(&trades, &HashMap::new(), |_| None);
+    let open: Vec<String> = without_marks
+        .iter()
+        .filter(|a| !a.open_quantity.is_zero())
+        .map(|a| a.mint.clone())
+        .collect();
+    let marks = if open.is_empty() {
+        HashMap::new()
+    } else {
+        crate::performance::live_marks(&open).await
+    };
+    let assets = crate::performance::asset_breakdown(&trades, &marks, |mint| {
+        crate::backfill::known_mint(mint).map(|(symbol, _)| symbol.to_string())
+    });
 
-    Ok(Json(EventsResponse {
-        events,
-        next_cursor: None,
+    Ok(Json(AssetPerformanceResponse {
+        bot_id,
+        since,
+        assets,
+    }))
+}
+
+/// GET /bots/:id/what-if - Replay the trade ledger under hypothetical risk caps
+///
+/// Caps left out of the query keep the current config's value. Reports what
+/// the tighter caps would have blocked and how PnL and max drawdown differ.
+pub async fn get_what_if(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Path(bot_id): Path<Uuid>,
+    Query(query): Query<WhatIfQuery>,
+) -> Result<Json<WhatIfResponse>, (StatusCode, String)> {
+    let bot = get_authorized_bot(&state.db, &auth, bot_id).await?;
+    let days = query.days.unwrap_or(30);
+    if !(1..=365).contains(&days) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!("days must be 1-365, got {}", days),
+        ));
+    }
+    let since = Utc::now() - chrono::Duration::days(days);
+    let db_err = |e: sqlx::Error| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string());
+
+    let config = sqlx::query_as::<_, ConfigVersion>("SELECT * FROM config_versions WHERE id = $1")
+        .bind(bot.desired_version_id)
+        .fetch_optional(&state.db)
+        .await
+        .map_err(db_err)?
+        .ok_or((StatusCode::NOT_FOUND, "Bot has no config".to_string()))?;
+    let actual_caps = RiskCaps {
+        max_position_size_percent: config.max_position_size_percent,
+        max_daily_loss_usd: config.max_daily_loss_usd,
+        max_drawdown_percent: config.max_drawdown_percent,
+        max_trades_per_day: config.max_trades_per_day,
+    };
+    let hypothetical_caps = RiskCaps {
+        max_position_size_percent: query
+            .max_position_size_percent
+            .unwrap_or(actual_caps.max_position_size_percent),
+        max_daily_loss_usd: query
+            .max_daily_loss_usd
+            .unwrap_or(actual_caps.max_daily_loss_usd),
+        max_trades_per_day: query
+            .max_trades_per_day
+            .unwrap_or(actual_caps.max_trades_per_day),
+        ..actual_caps
+    };
+    hypothetical_caps
+        .validate()
+        .map_err(|e| (StatusCode::BAD_REQUEST, e))?;
+
+    let rows: Vec<(chrono::DateTime<Utc>, Option<serde_json::Value>)> = sqlx::query_as(
+        "SELECT created_at, metadata FROM events \
+         WHERE bot_id = $1 AND event_type::text = 'trade_confirmed' AND created_at > $2 \
+         ORDER BY created_at",
+    )
+    .bind(bot_id)
+    .bind(since)
+    .fetch_all(&state.db)
+    .await
+    .map_err(db_err)?;
+    let trades: Vec<crate::performance::FeeTrade> = rows
+        .iter()
+        .filter_map(|(at, metadata)| {
+            crate::performance::FeeTrade::from_event(*at, metadata.as_ref()?)
+        })
+        .collect();
+
+    // From a day earlier so the first trades have an equity to size against
+    let equity: Vec<(chrono::DateTime<Utc>, rust_decimal::Decimal)> =
+        sqlx::query_as::<_, MetricDb>(
+            "SELECT * FROM metrics WHERE bot_id = $1 AND timestamp > $2 ORDER BY timestamp",
+        )
+        .bind(bot_id)
+        .bind(since - chrono::Duration::days(1))
+        .fetch_all(&state.db)
+        .await
+        .map_err(db_err)?
+        .into_iter()
+        .map(Metric::from)
+        .map(|m| (m.timestamp, m.equity))
+        .collect();
+
+    let loosened = crate::whatif::loosened_caps(&actual_caps, &hypothetical_caps);
+    let unreplayed_intents: i64 = if loosened.is_empty() {
+        0
+    } else {
+        sqlx::query_scalar(
+            "SELECT COUNT(*) FROM events \
+             WHERE bot_id = $1 AND event_type::text = 'trade_blocked' AND created_at > $2 \
+             AND metadata->>'blocked_by' = ANY($3)",
+        )
+        .bind(bot_id)
+        .bind(since)
+        .bind(&loosened)
+        .fetch_one(&state.db)
+        .await
+        .map_err(db_err)?
+    };
+
+    let (actual, hypothetical, trades_blocked) =
+        crate::whatif::replay(&trades, &equity, &actual_caps, &hypothetical_caps);
+    Ok(Json(WhatIfResponse {
+        bot_id,
+        since,
+        actual_caps,
+        hypothetical_caps,
+        pnl_delta_usd: hypothetical.pnl_usd - actual.pnl_usd,
+        max_drawdown_delta_usd: hypothetical.max_drawdown_usd - actual.max_drawdown_usd,
+        actual,
+        hypothetical,
+        trades_blocked,
+        unreplayed_intents,
+    }))
+}
+
+/// Default starting equity when the bot reported none before the window
+const SHADOW_DEFAULT_EQUITY_USD: i64 = 10_000;
+const SHADOW_DEFAULT_SLIPPAGE_BPS: u32 = 50;
+const SHADOW_DEFAULT_FEE_BPS: u32 = 10;
+const SHADOW_MAX_COST_BPS: u32 = 1000;
+
+/// POST /bots/:id/shadow-replay - Replay the bot's journaled decisions in
+/// paper under another config
+///
+/// Fills at recorded hourly prices (CoinGecko when too few are recorded),
+/// starting from the bot's equity at the first decision, and puts the shadow
+/// equity curve next to the bot's reported one. See [`crate::shadow`].
+pub async fn shadow_replay(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Path(bot_id): Path<Uuid>,
+    Json(req): Json<ShadowReplayRequest>,
+) -> Result<Json<ShadowReplayResponse>, (StatusCode, String)> {
+    use crate::shadow::{self, Decision, ShadowConfig};
+    use rust_decimal::Decimal;
+
+    let bot = get_authorized_bot(&state.db, &auth, bot_id).await?;
+    let days = req.days.unwrap_or(30);
+    if !(1..=60).contains(&days) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!("days must be 1-60, got {}", days),
+        ));
+    }
+    let slippage_bps = req.slippage_bps.unwrap_or(SHADOW_DEFAULT_SLIPPAGE_BPS);
+    let fee_bps = req.fee_bps.unwrap_or(SHADOW_DEFAULT_FEE_BPS);
+    if slippage_bps > SHADOW_MAX_COST_BPS || fee_bps > SHADOW_MAX_COST_BPS {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!(
+                "slippage_bps and fee_bps must be at most {}",
+                SHADOW_MAX_COST_BPS
+            ),
+        ));
+    }
+    let since = Utc::now() - chrono::Duration::days(days);
+    let db_err = |e: sqlx::Error| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string());
+
+    let config = sqlx::query_as::<_, ConfigVersion>("SELECT * FROM config_versions WHERE id = $1")
+        .bind(bot.desired_version_id)
+        .fetch_optional(&state.db)
+        .await
+        .map_err(db_err)?
+        .ok_or((StatusCode::NOT_FOUND, "Bot has no config".to_string()))?;
+    let actual_caps = RiskCaps {
+        max_position_size_percent: config.max_position_size_percent,
+        max_daily_loss_usd: config.max_daily_loss_usd,
+        max_drawdown_percent: config.max_drawdown_percent,
+        max_trades_per_day: config.max_trades_per_day,
+    };
+    let shadow_caps = RiskCaps {
+        max_position_size_percent: req
+            .max_position_size_percent
+            .unwrap_or(actual_caps.max_position_size_percent),
+        max_daily_loss_usd: req
+            .max_daily_loss_usd
+            .unwrap_or(actual_caps.max_daily_loss_usd),
+        max_drawdown_percent: req
+            .max_drawdown_percent
+            .unwrap_or(actual_caps.max_drawdown_percent),
+        max_trades_per_day: req
+            .max_trades_per_day
+            .unwrap_or(actual_caps.max_trades_per_day),
+    };
+    shadow_caps
+        .validate()
+        .map_err(|e| (StatusCode::BAD_REQUEST, e))?;
+    let algorithm = req.algorithm_mode.map(|mode| {
+        crate::algorithms::AlgorithmFactory::create(
+            mode,
+            config.persona,
+            req.strictness.unwrap_or(config.strictness),
+            shadow_caps,
+        )
+    });
+
+    let entries: Vec<serde_json::Value> = sqlx::query_scalar(
+        "SELECT entry FROM decision_journal \
+         WHERE bot_id = $1 AND received_at > $2 ORDER BY seq",
+    )
+    .bind(bot_id)
+    .bind(since - chrono::Duration::days(1))
+    .fetch_all(&state.db)
+    .await
+    .map_err(db_err)?;
+    let mut decisions: Vec<Decision> = entries
+        .iter()
+        .filter_map(Decision::from_entry)
+        .filter(|d| d.timestamp > since)
+        .collect();
+    decisions.sort_by_key(|d| d.timestamp);
+    if decisions.is_empty() {
+        return Err((
+            StatusCode::NOT_FOUND,
+            format!("No journaled decisions in the last {} days", days),
+        ));
+    }
+
+    // Hourly candles for every asset the bot decided on
+    let registry = universe::registry(&state.db).await;
+    let mut prices = shadow::PriceHistory::new();
+    let mut mints: Vec<&str> = decisions.iter().map(|d| d.mint.as_str()).collect();
+    mints.sort();
+    mints.dedup();
+    for mint in mints {
+        let symbol = registry
+            .iter()
+            .find(|a| a.mint == mint)
+            .map(|a| a.symbol.clone())
+            .or_else(|| crate::backfill::known_mint(mint).map(|(s, _)| s.to_string()))
+            .unwrap_or_else(|| mint.to_string());
+        let timeframe = TimeFrame::Hour1;
+        let candles =
+            match crate::handlers::backtest::recorded_candles(&state, &symbol, timeframe).await {
+                Some(candles) => Ok(candles),
+                None => crate::handlers::backtest::fetch_candles(&symbol, timeframe).await,
+            };
+        match candles {
+            Ok(mut candles) => {
+                candles.sort_by_key(|c| c.timestamp);
+                prices.insert(mint.to_string(), candles);
+            }
+            Err((_, e)) => warn!(
+                "Shadow replay of bot {} can't price {}: {}",
+                bot_id, mint, e
+            ),
+        }
+    }
+
+    let equity: Vec<(chrono::DateTime<Utc>, Decimal)> = sqlx::query_as::<_, MetricDb>(
+        "SELECT * FROM metrics WHERE bot_id = $1 AND timestamp > $2 ORDER BY timestamp",
+    )
+    .bind(bot_id)
+    .bind(since - chrono::Duration::days(1))
+    .fetch_all(&state.db)
+    .await
+    .map_err(db_err)?
+    .into_iter()
+    .map(Metric::from)
+    .map(|m| (m.timestamp, m.equity))
+    .collect();
+    let equity_at = |at: chrono::DateTime<Utc>| {
+        let i = equity.partition_point(|(t, _)| *t <= at);
+        i.checked_sub(1).map(|i| equity[i].1)
+    };
+    let start = decisions[0].timestamp;
+    let initial_equity = equity_at(start)
+        .or_else(|| equity.first().map(|(_, e)| *e))
+        .filter(|e| *e > Decimal::ZERO)
+        .unwrap_or_else(|| Decimal::from(SHADOW_DEFAULT_EQUITY_USD));
+
+    let algorithm_name = algorithm.as_ref().map(|a| a.name().to_string());
+    let shadow_config = ShadowConfig {
+        risk_caps: shadow_caps,
+        algorithm,
+        initial_cash: initial_equity,
+        slippage_bps,
+        fee_bps,
+    };
+    let decision_count = decisions.len() as i64;
+    // With an algorithm each tick re-runs it over the history so far
+    let run =
+        tokio::task::spawn_blocking(move || shadow::replay(&decisions, &prices, &shadow_config))
+            .await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    let actual_curve: Vec<crate::algorithms::backtest::EquityPoint> = run
+        .equity_curve
+        .iter()
+        .filter_map(|p| {
+            Some(crate::algorithms::backtest::EquityPoint {
+                timestamp: p.timestamp,
+                equity: equity_at(p.timestamp)?,
+            })
+        })
+        .collect();
+    let window: Vec<Decimal> = equity
+        .iter()
+        .filter(|(t, _)| *t >= start)
+        .map(|(_, e)| *e)
+        .collect();
+    let final_equity = window.last().copied().unwrap_or(initial_equity);
+    let actual = ShadowActual {
+        final_equity_usd: final_equity,
+        total_return_pct: shadow::return_pct(initial_equity, final_equity),
+        max_drawdown_pct: shadow::max_drawdown_pct(
+            std::iter::once(initial_equity).chain(window.iter().copied()),
+        ),
+        equity_curve: actual_curve,
+    };
+
+    Ok(Json(ShadowReplayResponse {
+        bot_id,
+        since,
+        decisions: decision_count,
+        actual_caps,
+        shadow_caps,
+        algorithm: algorithm_name,
+        initial_equity_usd: initial_equity,
+        return_delta_pct: run.total_return_pct - actual.total_return_pct,
+        actual,
+        shadow: run,
+    }))
+}
+
+/// Events included per bot in the dashboard
+const DASHBOARD_EVENTS_PER_BOT: i64 = 10;
+
+/// GET /dashboard - Bots, latest metric and recent events per bot, and
+/// entitlements in one response
+///
+/// All reads run in one REPEATABLE READ transaction so the bots, metrics and
+/// events come from the same snapshot (no event for a bot the list doesn't
+/// include, no metric newer than the bot's status).
+pub async fn get_dashboard(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Extension(sub): Extension<SubscriptionContext>,
+) -> Result<Json<DashboardResponse>, (StatusCode, String)> {
+    let user_id = Uuid::parse_str(&auth.user_id)
+        .map_err(|_| (StatusCode::BAD_REQUEST, "Invalid user ID".to_string()))?;
+    let db_err = |e: sqlx::Error| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string());
+
+    let mut tx = state.db.begin().await.map_err(db_err)?;
+    sqlx::query("SET TRANSACTION ISOLATION LEVEL REPEATABLE READ READ ONLY")
+        .execute(&mut *tx)
+        .await
+        .map_err(db_err)?;
+
+    let bots =
+        sqlx::query_as::<_, Bot>("SELECT * FROM bots WHERE user_id = $1 ORDER BY created_at DESC")
+            .bind(user_id)
+            .fetch_all(&mut *tx)
+            .await
+            .map_err(db_err)?;
+    let bot_ids: Vec<Uuid> = bots.iter().map(|b| b.id).collect();
+
+    let latest_metrics = sqlx::query_as::<_, MetricDb>(
+        r#"
+        SELECT DISTINCT ON (bot_id) * FROM metrics
+        WHERE bot_id = ANY($1)
+        ORDER BY bot_id, timestamp DESC
+        "#,
+    )
+    .bind(&bot_ids)
+    .fetch_all(&mut *tx)
+    .await
+    .map_err(db_err)?;
+
+    let recent_events = sqlx::query_as::<_, Event>(&format!(
+        r#"
+        SELECT e.* FROM unnest($1::uuid[]) AS b(id)
+        CROSS JOIN LATERAL (
+            SELECT *, {} AS severity FROM events
+            WHERE bot_id = b.id
+            ORDER BY created_at DESC
+            LIMIT $2
+        ) e
+        "#,
+        events::SEVERITY_SQL
+    ))
+    .bind(&bot_ids)
+    .bind(DASHBOARD_EVENTS_PER_BOT)
+    .fetch_all(&mut *tx)
+    .await
+    .map_err(db_err)?;
+
+    tx.commit().await.map_err(db_err)?;
+
+    let mut metrics_by_bot: HashMap<Uuid, Metric> = latest_metrics
+        .into_iter()
+        .map(|m| (m.bot_id, Metric::from(m)))
+        .collect();
+    let mut events_by_bot: HashMap<Uuid, Vec<Event>> = HashMap::new();
+    for event in recent_events {
+        events_by_bot.entry(event.bot_id).or_default().push(event);
+    }
+
+    let bot_count = bots
+        .iter()
+        .filter(|b| b.status != BotStatus::Destroying)
+        .count() as i64;
+    let bots = bots
+        .into_iter()
+        .map(|bot| DashboardBot {
+            latest_metric: metrics_by_bot.remove(&bot.id),
+            recent_events: events_by_bot.remove(&bot.id).unwrap_or_default(),
+            bot,
+        })
+        .collect();
+
+    Ok(Json(DashboardResponse {
+        bots,
+        entitlements: DashboardEntitlements {
+            tier: sub.tier,
+            is_active: sub.is_active,
+            expires_at: sub.expires_at,
+            max_bots: sub.tier.max_bots(),
+            max_trades_per_day: sub.tier.max_trades_per_day(),
+            features: sub.tier.features(),
+            bot_count,
+        },
+        generated_at: Utc::now(),
     }))
 }
 
@@ -721,6 +2077,101 @@ pub async fn get_current_user(
     Ok(Json(user))
 }
 
+/// GET /me/alert-settings - Quiet hours and hourly alert cap
+pub async fn get_alert_settings(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+) -> Json<crate::alerting::UserAlertSettings> {
+    Json(state.alerts.user_settings(&auth.user_id).await)
+}
+
+/// PUT /me/alert-settings - Replace quiet hours and hourly alert cap
+pub async fn update_alert_settings(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Json(settings): Json<crate::alerting::UserAlertSettings>,
+) -> Result<Json<crate::alerting::UserAlertSettings>, (StatusCode, String)> {
+    let user_id = Uuid::parse_str(&auth.user_id)
+        .map_err(|_| (StatusCode::BAD_REQUEST, "Invalid user ID".to_string()))?;
+
+    if settings.quiet_start_hour.is_some() != settings.quiet_end_hour.is_some() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "quiet_start_hour and quiet_end_hour must be set together".to_string(),
+        ));
+    }
+    if [settings.quiet_start_hour, settings.quiet_end_hour]
+        .iter()
+        .flatten()
+        .any(|h| *h > 23)
+    {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "Quiet hours must be between 0 and 23 (UTC)".to_string(),
+        ));
+    }
+    if settings.max_alerts_per_hour == Some(0) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "max_alerts_per_hour must be at least 1".to_string(),
+        ));
+    }
+
+    sqlx::query(
+        "INSERT INTO alert_settings (user_id, quiet_start_hour, quiet_end_hour, max_alerts_per_hour) \
+         VALUES ($1, $2, $3, $4) \
+         ON CONFLICT (user_id) DO UPDATE SET quiet_start_hour = $2, quiet_end_hour = $3, \
+         max_alerts_per_hour = $4, updated_at = NOW()",
+    )
+    .bind(user_id)
+    .bind(settings.quiet_start_hour.map(i16::from))
+    .bind(settings.quiet_end_hour.map(i16::from))
+    .bind(settings.max_alerts_per_hour.map(|m| m as i32))
+    .execute(&state.db)
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    state
+        .alerts
+        .set_user_settings(&auth.user_id, settings)
+        .await;
+    Ok(Json(settings))
+}
+
+/// GET /me/preferences - Defaults for new bots and alert rendering
+pub async fn get_preferences(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+) -> Result<Json<UserPreferences>, (StatusCode, String)> {
+    let user_id = Uuid::parse_str(&auth.user_id)
+        .map_err(|_| (StatusCode::BAD_REQUEST, "Invalid user ID".to_string()))?;
+
+    preferences::load(&state.db, user_id)
+        .await
+        .map(Json)
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))
+}
+
+/// PATCH /me/preferences - Update some preferences, keeping the rest
+pub async fn update_preferences(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Json(update): Json<UpdatePreferencesRequest>,
+) -> Result<Json<UserPreferences>, (StatusCode, String)> {
+    let user_id = Uuid::parse_str(&auth.user_id)
+        .map_err(|_| (StatusCode::BAD_REQUEST, "Invalid user ID".to_string()))?;
+
+    let prefs = preferences::load(&state.db, user_id)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+        .merge(update)
+        .map_err(|e| (StatusCode::BAD_REQUEST, e))?;
+    preferences::save(&state.db, user_id, &prefs)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    Ok(Json(prefs))
+}
+
 /// Generate a cryptographically secure bootstrap token
 fn generate_bootstrap_token() -> String {
     use rand::Rng;
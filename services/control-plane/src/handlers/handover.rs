@@ -0,0 +1,241 @@
+//! Runner state handover for redeploys
+//!
+//! A redeploy queues `export_state` for the running bot and holds off
+//! destroying its droplet until the runner has uploaded its state (or
+//! [`STATE_EXPORT_TIMEOUT`] passes). The replacement runner downloads the
+//! snapshot on first boot, so portfolio, journal chain and queued events
+//! carry over instead of starting cold. Bundles are opaque here apart from
+//! a size cap. With object storage configured the bundle goes to the bucket
+//! as a `state_snapshot` artifact and the row only keeps its key.
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use chrono::{DateTime, Utc};
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use crate::{
+    artifacts::{self, ArtifactKind},
+    db::Db,
+    storage::ObjectStore,
+    AppState,
+};
+
+/// How long a redeploy waits for the old runner's export (runners sync every 30s)
+pub const STATE_EXPORT_TIMEOUT: Duration = Duration::from_secs(90);
+
+const EXPORT_POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Largest accepted bundle, as serialized JSON
+const MAX_BUNDLE_BYTES: usize = 1_500_000;
+
+async fn record_event(pool: &Db, bot_id: Uuid, event_type: &str, message: &str) {
+    if let Err(e) =
+        sqlx::query("INSERT INTO events (bot_id, event_type, message) VALUES ($1, $2, $3)")
+            .bind(bot_id)
+            .bind(event_type)
+            .bind(message)
+            .execute(pool)
+            .await
+    {
+        warn!(
+            "Failed to record {} event for bot {}: {}",
+            event_type, bot_id, e
+        );
+    }
+}
+
+/// PUT /bot/:id/state - Runner uploads its state ahead of a redeploy
+pub async fn upload_state(
+    State(state): State<Arc<AppState>>,
+    Path(bot_id): Path<Uuid>,
+    Json(bundle): Json<serde_json::Value>,
+) -> Result<StatusCode, (StatusCode, String)> {
+    let size = serde_json::to_vec(&bundle)
+        .map_err(|e| (StatusCode::BAD_REQUEST, e.to_string()))?
+        .len();
+    if size > MAX_BUNDLE_BYTES {
+        return Err((
+            StatusCode::PAYLOAD_TOO_LARGE,
+            format!("State bundle is limited to {} bytes", MAX_BUNDLE_BYTES),
+        ));
+    }
+
+    // Offload to the bucket when there is one; the row then has no body
+    let (inline, object_key) = match ObjectStore::from_config(&state.db, &state.secrets).await {
+        Some(store) => {
+            let body = serde_json::to_vec(&bundle)
+                .map_err(|e| (StatusCode::BAD_REQUEST, e.to_string()))?;
+            let artifact = artifacts::store(
+                &state.db,
+                &store,
+                bot_id,
+                ArtifactKind::StateSnapshot,
+                "json",
+                "application/json",
+                body,
+            )
+            .await
+            .map_err(|e| (StatusCode::BAD_GATEWAY, e.to_string()))?;
+            (None, Some(artifact.object_key))
+        }
+        None => (Some(bundle), None),
+    };
+
+    let stored = sqlx::query(
+        "INSERT INTO bot_state_snapshots (bot_id, bundle, object_key, size_bytes) \
+         SELECT id, $2, $3, $4 FROM bots WHERE id = $1 \
+         ON CONFLICT (bot_id) DO UPDATE SET bundle = EXCLUDED.bundle, \
+             object_key = EXCLUDED.object_key, size_bytes = EXCLUDED.size_bytes, \
+             exported_at = NOW(), restored_at = NULL",
+    )
+    .bind(bot_id)
+    .bind(&inline)
+    .bind(&object_key)
+    .bind(size as i32)
+    .execute(&state.db)
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    if stored.rows_affected() == 0 {
+        return Err((StatusCode::NOT_FOUND, "Bot not found".to_string()));
+    }
+
+    info!("Bot {} exported {} bytes of state", bot_id, size);
+    record_event(
+        &state.db,
+        bot_id,
+        "state_exported",
+        "Runner state saved for redeploy",
+    )
+    .await;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// GET /bot/:id/state - New runner fetches the state its predecessor exported
+///
+/// Each snapshot is handed out once; after that the droplet that restored it
+/// owns the state, and a later cold start shouldn't roll it back. A snapshot
+/// only counts as restored once its bytes are in hand, and only if no newer
+/// export replaced it meanwhile.
+pub async fn download_state(
+    State(state): State<Arc<AppState>>,
+    Path(bot_id): Path<Uuid>,
+) -> Result<Json<serde_json::Value>, (StatusCode, String)> {
+    let not_found = || {
+        (
+            StatusCode::NOT_FOUND,
+            "No state waiting to be restored".to_string(),
+        )
+    };
+    type Pending = (Option<serde_json::Value>, Option<String>, DateTime<Utc>);
+    let pending: Option<Pending> = sqlx::query_as(
+        "SELECT bundle, object_key, exported_at FROM bot_state_snapshots \
+         WHERE bot_id = $1 AND restored_at IS NULL",
+    )
+    .bind(bot_id)
+    .fetch_optional(&state.db)
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    let (bundle, object_key, exported_at) = pending.ok_or_else(not_found)?;
+    let bundle = match (bundle, object_key) {
+        (Some(bundle), _) => bundle,
+        (None, Some(object_key)) => {
+            // Fetch before marking it restored, so a storage hiccup can be retried
+            let store = crate::handlers::artifacts::require_store(&state).await?;
+            let body = store
+                .get(&object_key)
+                .await
+                .map_err(|e| (StatusCode::BAD_GATEWAY, e.to_string()))?
+                .ok_or_else(not_found)?;
+            serde_json::from_slice(&body)
+                .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+        }
+        (None, None) => return Err(not_found()),
+    };
+
+    let claimed = sqlx::query(
+        "UPDATE bot_state_snapshots SET restored_at = NOW() \
+         WHERE bot_id = $1 AND exported_at = $2 AND restored_at IS NULL",
+    )
+    .bind(bot_id)
+    .bind(exported_at)
+    .execute(&state.db)
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    if claimed.rows_affected() == 0 {
+        return Err(not_found());
+    }
+
+    info!("Bot {} restoring exported state", bot_id);
+    record_event(
+        &state.db,
+        bot_id,
+        "state_restored",
+        "Runner state restored after redeploy",
+    )
+    .await;
+    Ok(Json(bundle))
+}
+
+/// Queue `export_state` for the bot; returns when it was queued
+pub async fn request_state_export(pool: &Db, bot_id: Uuid) -> Result<DateTime<Utc>, sqlx::Error> {
+    sqlx::query_scalar(
+        "INSERT INTO bot_commands (bot_id, command) VALUES ($1, 'export_state') RETURNING created_at",
+    )
+    .bind(bot_id)
+    .fetch_one(pool)
+    .await
+}
+
+/// Wait for an export newer than `since`; true once it has landed
+///
+/// On timeout any older snapshot is dropped, so the new droplet starts cold
+/// rather than from stale state.
+pub async fn await_state_export(pool: &Db, bot_id: Uuid, since: DateTime<Utc>) -> bool {
+    let deadline = tokio::time::Instant::now() + STATE_EXPORT_TIMEOUT;
+    loop {
+        let exported: Result<bool, _> = sqlx::query_scalar(
+            "SELECT EXISTS (SELECT 1 FROM bot_state_snapshots WHERE bot_id = $1 AND exported_at >= $2)",
+        )
+        .bind(bot_id)
+        .bind(since)
+        .fetch_one(pool)
+        .await;
+        match exported {
+            Ok(true) => return true,
+            Ok(false) => {}
+            Err(e) => warn!("State export lookup failed for bot {}: {}", bot_id, e),
+        }
+        if tokio::time::Instant::now() >= deadline {
+            break;
+        }
+        tokio::time::sleep(EXPORT_POLL_INTERVAL).await;
+    }
+
+    warn!(
+        "Bot {} didn't export its state within {:?}; redeploying cold",
+        bot_id, STATE_EXPORT_TIMEOUT
+    );
+    if let Err(e) =
+        sqlx::query("DELETE FROM bot_state_snapshots WHERE bot_id = $1 AND exported_at < $2")
+            .bind(bot_id)
+            .bind(since)
+            .execute(pool)
+            .await
+    {
+        warn!("Failed to drop stale state for bot {}: {}", bot_id, e);
+    }
+    record_event(
+        pool,
+        bot_id,
+        "state_export_missed",
+        "Runner didn't export its state in time; the new droplet starts fresh",
+    )
+    .await;
+    false
+}
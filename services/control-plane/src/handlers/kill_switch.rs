@@ -0,0 +1,217 @@
+//! Account-level kill switch
+//!
+//! Engaging pauses every one of the user's bots, queues `cancel_intents`
+//! (and optionally `flatten_positions`) on each bot's command channel, and
+//! keeps them from being resumed, redeployed or created until the switch is
+//! released. Both directions need a session that re-authenticated within
+//! the last few minutes, so a leaked long-lived token can't flip it.
+
+use axum::{
+    extract::{Extension, State},
+    http::StatusCode,
+    Json,
+};
+use chrono::{DateTime, Duration, Utc};
+use std::sync::Arc;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use crate::{
+    middleware::{kill_switch::KillSwitchStatus, AuthContext},
+    models::*,
+    AppState,
+};
+
+/// Longest accepted `reason`
+const MAX_REASON_LEN: usize = 500;
+
+fn user_uuid(auth: &AuthContext) -> Result<Uuid, (StatusCode, String)> {
+    Uuid::parse_str(&auth.user_id)
+        .map_err(|_| (StatusCode::BAD_REQUEST, "Invalid user ID".to_string()))
+}
+
+/// Reject unless the caller's session signed in (or stepped up) recently
+///
+/// API keys have no session and can never pass.
+async fn require_recent_auth(
+    state: &AppState,
+    auth: &AuthContext,
+    user_id: Uuid,
+) -> Result<Uuid, (StatusCode, String)> {
+    let reauth = || {
+        (
+            StatusCode::FORBIDDEN,
+            "Re-authenticate to use the kill switch".to_string(),
+        )
+    };
+    let session_id = auth.session_id.ok_or_else(reauth)?;
+
+    let last_strong_auth: Option<Option<DateTime<Utc>>> = sqlx::query_scalar(
+        "SELECT last_strong_auth_at FROM sessions \
+         WHERE id = $1 AND user_id = $2 AND revoked_at IS NULL AND expires_at > NOW()",
+    )
+    .bind(session_id)
+    .bind(user_id)
+    .fetch_optional(&state.db)
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    let max_age = Duration::seconds(cedros_login::services::DEFAULT_STEP_UP_MAX_AGE_SECS);
+    match last_strong_auth.flatten() {
+        Some(at) if Utc::now() - at <= max_age => Ok(session_id),
+        _ => Err(reauth()),
+    }
+}
+
+/// Queue a command for each bot; returns how many bots got it
+async fn queue_command(
+    tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    user_id: Uuid,
+    command: &str,
+    args: &serde_json::Value,
+) -> Result<usize, (StatusCode, String)> {
+    let queued = sqlx::query(
+        "INSERT INTO bot_commands (bot_id, command, args) \
+         SELECT id, $2, $3 FROM bots WHERE user_id = $1 AND status != 'destroying'",
+    )
+    .bind(user_id)
+    .bind(command)
+    .bind(args)
+    .execute(&mut **tx)
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    Ok(queued.rows_affected() as usize)
+}
+
+/// POST /me/kill-switch - Stop all of the user's trading now
+///
+/// Engaging again while already engaged re-sends the commands and can add
+/// flattening, but keeps the original `engaged_at`.
+pub async fn engage_kill_switch(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    body: Option<Json<KillSwitchRequest>>,
+) -> Result<(Extension<KillSwitchStatus>, Json<KillSwitchResponse>), (StatusCode, String)> {
+    let user_id = user_uuid(&auth)?;
+    let session_id = require_recent_auth(&state, &auth, user_id).await?;
+    let req = body.map(|Json(req)| req).unwrap_or_default();
+    let reason = req
+        .reason
+        .map(|r| r.trim().to_string())
+        .filter(|r| !r.is_empty());
+    if reason.as_ref().is_some_and(|r| r.len() > MAX_REASON_LEN) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!("reason is limited to {} characters", MAX_REASON_LEN),
+        ));
+    }
+
+    let mut tx = state
+        .db
+        .begin()
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    let kill_switch = sqlx::query_as::<_, KillSwitch>(
+        "INSERT INTO kill_switches (user_id, reason, flatten, session_id) VALUES ($1, $2, $3, $4) \
+         ON CONFLICT (user_id) DO UPDATE SET \
+             reason = COALESCE(EXCLUDED.reason, kill_switches.reason), \
+             flatten = kill_switches.flatten OR EXCLUDED.flatten \
+         RETURNING engaged_at, reason, flatten",
+    )
+    .bind(user_id)
+    .bind(&reason)
+    .bind(req.flatten)
+    .bind(session_id)
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    // Provisioning bots are left alone; the sync response keeps their runner
+    // paused once it comes up
+    let paused: Vec<(Uuid,)> = sqlx::query_as(
+        "UPDATE bots SET status = 'paused', updated_at = NOW() \
+         WHERE user_id = $1 AND status IN ('online', 'offline', 'error') RETURNING id",
+    )
+    .bind(user_id)
+    .fetch_all(&mut *tx)
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    for (bot_id,) in &paused {
+        sqlx::query(
+            "INSERT INTO events (bot_id, event_type, message, metadata) VALUES ($1, $2, $3, $4)",
+        )
+        .bind(bot_id)
+        .bind("status_change")
+        .bind("Paused by the account kill switch")
+        .bind(serde_json::json!({"reason": "kill_switch", "flatten": req.flatten}))
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    }
+
+    let args = serde_json::json!({"reason": "kill_switch"});
+    let bots_notified = queue_command(&mut tx, user_id, "cancel_intents", &args).await?;
+    if req.flatten {
+        queue_command(&mut tx, user_id, "flatten_positions", &args).await?;
+    }
+
+    tx.commit()
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    warn!(
+        "Kill switch engaged by {}: {} bots paused, {} notified (flatten: {})",
+        user_id,
+        paused.len(),
+        bots_notified,
+        req.flatten
+    );
+    let status = KillSwitchStatus {
+        engaged_at: Some(kill_switch.engaged_at),
+    };
+    Ok((
+        Extension(status),
+        Json(KillSwitchResponse {
+            engaged: true,
+            kill_switch: Some(kill_switch),
+            bots_paused: paused.len(),
+            bots_notified,
+        }),
+    ))
+}
+
+/// DELETE /me/kill-switch - Allow the user's bots to trade again
+///
+/// Bots stay paused; each one is resumed with the usual bot action.
+pub async fn release_kill_switch(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+) -> Result<(Extension<KillSwitchStatus>, Json<KillSwitchResponse>), (StatusCode, String)> {
+    let user_id = user_uuid(&auth)?;
+    require_recent_auth(&state, &auth, user_id).await?;
+
+    let released = sqlx::query("DELETE FROM kill_switches WHERE user_id = $1")
+        .bind(user_id)
+        .execute(&state.db)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    if released.rows_affected() == 0 {
+        return Err((
+            StatusCode::NOT_FOUND,
+            "Kill switch is not engaged".to_string(),
+        ));
+    }
+
+    info!("Kill switch released by {}", user_id);
+    Ok((
+        Extension(KillSwitchStatus::default()),
+        Json(KillSwitchResponse {
+            engaged: false,
+            kill_switch: None,
+            bots_paused: 0,
+            bots_notified: 0,
+        }),
+    ))
+}
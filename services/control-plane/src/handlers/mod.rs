@@ -1,5 +1,13 @@
 pub mod admin;
+pub mod alerts;
+pub mod artifacts;
+pub mod backtest;
+pub mod billing;
 pub mod bots;
+pub mod handover;
+pub mod kill_switch;
 pub mod openclaw_config;
+pub mod public;
 pub mod simulate;
 pub mod sync;
+pub mod webhooks;
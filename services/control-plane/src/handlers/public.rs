@@ -0,0 +1,309 @@
+//! Opt-in public performance pages
+//!
+//! Owners mint a share token for a bot; anyone holding it can read a
+//! sanitized view of the bot's performance (returns in percent, trade count,
+//! win rate) without signing in. Tokens are 256-bit random values and only
+//! their SHA-256 is stored, so a database leak doesn't expose live links.
+
+use axum::{
+    extract::{Extension, Path, State},
+    http::StatusCode,
+    Json,
+};
+use chrono::{DateTime, Duration, Utc};
+use rust_decimal::Decimal;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tracing::info;
+use uuid::Uuid;
+
+use crate::{
+    backfill::{is_cash, LedgerTrade},
+    handlers::bots::get_authorized_bot,
+    middleware::AuthContext,
+    models::*,
+    AppState,
+};
+
+/// Days of history on the public page
+const PUBLIC_WINDOW_DAYS: i64 = 30;
+
+fn hash_token(token: &str) -> String {
+    hex::encode(Sha256::digest(token.as_bytes()))
+}
+
+/// Whether each trade of a ledger closed at a gain, using average cost per
+/// token; None for trades that didn't close anything
+///
+/// Buys (cash -> token) add to the token's cost basis; sells (token -> cash)
+/// close at the cash received versus the average cost of what was sold.
+/// Sells of tokens bought before the window have no basis and are skipped.
+pub fn close_outcomes<'a>(trades: impl IntoIterator<Item = &'a LedgerTrade>) -> Vec<Option<bool>> {
+    // mint -> (quantity held, total cost)
+    let mut basis: HashMap<&str, (Decimal, Decimal)> = HashMap::new();
+    let mut outcomes = Vec::new();
+
+    for trade in trades {
+        let mut outcome = None;
+        if is_cash(&trade.input_mint) && !is_cash(&trade.output_mint) {
+            let entry = basis
+                .entry(trade.output_mint.as_str())
+                .or_insert((Decimal::ZERO, Decimal::ZERO));
+            entry.0 += trade.out_amount;
+            entry.1 += trade.in_amount;
+        } else if !is_cash(&trade.input_mint) && is_cash(&trade.output_mint) {
+            if let Some((qty, cost)) = basis
+                .get_mut(trade.input_mint.as_str())
+                .filter(|(qty, _)| !qty.is_zero())
+            {
+                let sold = trade.in_amount.min(*qty);
+                let sold_cost = *cost * sold / *qty;
+                *qty -= sold;
+                *cost -= sold_cost;
+                outcome = Some(trade.out_amount > sold_cost);
+            }
+        }
+        outcomes.push(outcome);
+    }
+    outcomes
+}
+
+/// Closed trades and winners from a ledger (see [`close_outcomes`])
+pub fn closed_trade_stats(trades: &[LedgerTrade]) -> (i64, i64) {
+    let outcomes = close_outcomes(trades);
+    let closed = outcomes.iter().flatten().count() as i64;
+    let wins = outcomes.iter().flatten().filter(|win| **win).count() as i64;
+    (closed, wins)
+}
+
+/// GET /bots/:id/share - Whether the bot has a live share link
+pub async fn get_share_link(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Path(bot_id): Path<Uuid>,
+) -> Result<Json<ShareLinkStatus>, (StatusCode, String)> {
+    let _bot = get_authorized_bot(&state.db, &auth, bot_id).await?;
+
+    let created_at: Option<DateTime<Utc>> = sqlx::query_scalar(
+        "SELECT created_at FROM bot_share_links WHERE bot_id = $1 AND revoked_at IS NULL",
+    )
+    .bind(bot_id)
+    .fetch_optional(&state.db)
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    Ok(Json(ShareLinkStatus {
+        bot_id,
+        active: created_at.is_some(),
+        created_at,
+    }))
+}
+
+/// POST /bots/:id/share - Create a public link, replacing any existing one
+pub async fn create_share_link(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Path(bot_id): Path<Uuid>,
+) -> Result<Json<ShareLinkResponse>, (StatusCode, String)> {
+    let _bot = get_authorized_bot(&state.db, &auth, bot_id).await?;
+
+    let token = hex::encode(rand::random::<[u8; 32]>());
+    let mut tx = state
+        .db
+        .begin()
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    sqlx::query(
+        "UPDATE bot_share_links SET revoked_at = NOW() WHERE bot_id = $1 AND revoked_at IS NULL",
+    )
+    .bind(bot_id)
+    .execute(&mut *tx)
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    let created_at: DateTime<Utc> = sqlx::query_scalar(
+        "INSERT INTO bot_share_links (bot_id, token_hash) VALUES ($1, $2) RETURNING created_at",
+    )
+    .bind(bot_id)
+    .bind(hash_token(&token))
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    tx.commit()
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    info!(
+        "User {} created a public share link for bot {}",
+        auth.user_id, bot_id
+    );
+
+    Ok(Json(ShareLinkResponse {
+        bot_id,
+        path: format!("/v1/public/perf/{}", token),
+        token,
+        created_at,
+    }))
+}
+
+/// DELETE /bots/:id/share - Revoke the bot's public link
+pub async fn revoke_share_link(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Path(bot_id): Path<Uuid>,
+) -> Result<StatusCode, (StatusCode, String)> {
+    let _bot = get_authorized_bot(&state.db, &auth, bot_id).await?;
+
+    let revoked = sqlx::query(
+        "UPDATE bot_share_links SET revoked_at = NOW() WHERE bot_id = $1 AND revoked_at IS NULL",
+    )
+    .bind(bot_id)
+    .execute(&state.db)
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+    .rows_affected();
+
+    if revoked == 0 {
+        return Err((StatusCode::NOT_FOUND, "No active share link".to_string()));
+    }
+    info!(
+        "User {} revoked the share link for bot {}",
+        auth.user_id, bot_id
+    );
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// GET /public/perf/:token - Sanitized performance view (no auth)
+///
+/// Unknown and revoked tokens both answer 404.
+pub async fn get_public_performance(
+    State(state): State<Arc<AppState>>,
+    Path(token): Path<String>,
+) -> Result<Json<PublicPerformance>, (StatusCode, String)> {
+    let not_found = || (StatusCode::NOT_FOUND, "Not found".to_string());
+    if token.len() != 64 || !token.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return Err(not_found());
+    }
+    let token_hash = hash_token(&token);
+    if !state
+        .rate_limiter
+        .check(&format!("public:{}", &token_hash[..16]))
+        .await
+    {
+        return Err((
+            StatusCode::TOO_MANY_REQUESTS,
+            "Too many requests".to_string(),
+        ));
+    }
+
+    let bot = sqlx::query_as::<_, Bot>(
+        r#"
+        SELECT b.* FROM bot_share_links l
+        JOIN bots b ON b.id = l.bot_id
+        WHERE l.token_hash = $1 AND l.revoked_at IS NULL
+        "#,
+    )
+    .bind(&token_hash)
+    .fetch_optional(&state.db)
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+    .ok_or_else(not_found)?;
+
+    let since = Utc::now() - Duration::days(PUBLIC_WINDOW_DAYS);
+
+    // Hourly samples keep the curve small without losing its shape
+    let metrics = sqlx::query_as::<_, MetricDb>(
+        r#"
+        SELECT DISTINCT ON (date_trunc('hour', timestamp)) * FROM metrics
+        WHERE bot_id = $1 AND timestamp > $2
+        ORDER BY date_trunc('hour', timestamp), timestamp DESC
+        "#,
+    )
+    .bind(bot.id)
+    .bind(since)
+    .fetch_all(&state.db)
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    let metrics: Vec<Metric> = metrics.into_iter().map(Metric::from).collect();
+
+    let equity_curve: Vec<PublicEquityPoint> = match metrics.first() {
+        Some(first) if !first.equity.is_zero() => metrics
+            .iter()
+            .map(|m| PublicEquityPoint {
+                timestamp: m.timestamp,
+                return_pct: ((m.equity / first.equity - Decimal::ONE) * Decimal::from(100))
+                    .round_dp(2),
+            })
+            .collect(),
+        _ => Vec::new(),
+    };
+
+    let trade_rows: Vec<(DateTime<Utc>, Option<serde_json::Value>)> = sqlx::query_as(
+        "SELECT created_at, metadata FROM events \
+         WHERE bot_id = $1 AND event_type::text = 'trade_confirmed' AND created_at > $2 \
+         ORDER BY created_at",
+    )
+    .bind(bot.id)
+    .bind(since)
+    .fetch_all(&state.db)
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    let trades: Vec<LedgerTrade> = trade_rows
+        .iter()
+        .filter_map(|(at, metadata)| LedgerTrade::from_event(*at, metadata.as_ref()?))
+        .collect();
+    let (closed_trades, wins) = closed_trade_stats(&trades);
+
+    let trading_mode: Option<TradingMode> =
+        sqlx::query_scalar("SELECT trading_mode FROM config_versions WHERE id = $1")
+            .bind(bot.desired_version_id)
+            .fetch_optional(&state.db)
+            .await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    Ok(Json(PublicPerformance {
+        name: bot.name,
+        persona: bot.persona,
+        trading_mode,
+        since: bot.created_at.max(since),
+        window_days: PUBLIC_WINDOW_DAYS,
+        return_pct: equity_curve.last().map(|p| p.return_pct),
+        equity_curve,
+        trade_count: trade_rows.len() as i64,
+        closed_trades,
+        win_rate: (closed_trades > 0).then(|| wins as f64 / closed_trades as f64),
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const USDC: &str = "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v";
+    const SOL: &str = "So11111111111111111111111111111111111111112";
+
+    fn trade(input: &str, in_amount: i64, output: &str, out_amount: i64) -> LedgerTrade {
+        LedgerTrade {
+            timestamp: Utc::now(),
+            input_mint: input.to_string(),
+            in_amount: Decimal::from(in_amount),
+            output_mint: output.to_string(),
+            out_amount: Decimal::from(out_amount),
+        }
+    }
+
+    #[test]
+    fn test_closed_trade_stats() {
+        let trades = [
+            // Sell with no basis in the window is ignored
+            trade(SOL, 1, USDC, 150),
+            trade(USDC, 100, SOL, 1),
+            trade(USDC, 300, SOL, 1),
+            // Avg cost 200/SOL: first half sells at a gain, second at a loss
+            trade(SOL, 1, USDC, 250),
+            trade(SOL, 1, USDC, 180),
+        ];
+        assert_eq!(closed_trade_stats(&trades), (2, 1));
+        assert_eq!(hash_token("abc").len(), 64);
+    }
+}
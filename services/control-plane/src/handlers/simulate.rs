@@ -4,7 +4,7 @@ use axum::{extract::State, http::StatusCode, Json};
 use std::sync::Arc;
 
 use crate::{
-    algorithms::{signal::Signal, AlgorithmFactory, Candle, MarketContext, Position},
+    algorithms::{signal::Signal, AlgorithmFactory, Candle, Funding, MarketContext, Position},
     models::*,
     AppState,
 };
@@ -22,6 +22,12 @@ pub struct SimulateRequest {
     pub risk_caps: RiskCaps,
     pub portfolio_value: Option<Decimal>,
     pub position: Option<PositionInput>,
+    /// Perpetual funding rate per 8h, as a fraction
+    #[serde(default)]
+    pub funding_rate: Option<f64>,
+    /// Sentiment from -1 (bearish) to 1 (bullish)
+    #[serde(default)]
+    pub sentiment_score: Option<f64>,
 }
 
 #[derive(Debug, serde::Deserialize)]
@@ -97,6 +103,8 @@ pub async fn simulate_signal(
         position,
         portfolio_value: req.portfolio_value.unwrap_or_else(|| Decimal::from(10000)),
         risk_caps: req.risk_caps,
+        funding: req.funding_rate.map(|rate| Funding { rate }),
+        sentiment: req.sentiment_score,
     };
 
     // Create algorithm with persona-based defaults
@@ -1,23 +1,36 @@
 use axum::{
     extract::{Path, State},
-    http::StatusCode,
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
     Json,
 };
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use sha2::{Digest, Sha256};
 use std::sync::Arc;
-use tracing::{info, warn};
+use tracing::{error, info, warn};
 use uuid::Uuid;
 
 use crate::{
+    alerting::{AlertSeverity, AlertType},
+    algorithms::AlgorithmFactory,
+    backfill,
     models::*,
     observability::{metrics, Logger},
+    universe::{self, RegistryAsset},
     AppState,
 };
 
 /// GET /bot/:id/config - Bot polls for config updates
+///
+/// Responses carry an ETag derived from the config id/version and the OpenClaw
+/// config timestamp. A matching If-None-Match returns 304 before any secrets
+/// are decrypted.
 pub async fn get_bot_config(
     State(state): State<Arc<AppState>>,
     Path(bot_id): Path<Uuid>,
-) -> Result<Json<BotConfigPayload>, (StatusCode, String)> {
+    headers: HeaderMap,
+) -> Result<Response, (StatusCode, String)> {
     let start = std::time::Instant::now();
 
     let bot = sqlx::query_as::<_, Bot>("SELECT * FROM bots WHERE id = $1")
@@ -29,6 +42,37 @@ pub async fn get_bot_config(
             _ => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
         })?;
 
+    // Cheap lookup of everything the ETag depends on (no secrets involved)
+    let (config_version, openclaw_updated_at): (i32, Option<DateTime<Utc>>) = sqlx::query_as(
+        r#"
+        SELECT cv.version, oc.updated_at
+        FROM config_versions cv
+        LEFT JOIN bot_openclaw_config oc ON oc.bot_id = $2
+        WHERE cv.id = $1
+        "#,
+    )
+    .bind(bot.desired_version_id)
+    .bind(bot_id)
+    .fetch_one(&state.db)
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    let registry = universe::registry(&state.db).await;
+    let etag = config_etag(
+        bot.desired_version_id,
+        config_version,
+        openclaw_updated_at,
+        &registry,
+    );
+
+    if if_none_match_matches(&headers, &etag) {
+        state
+            .metrics
+            .increment(metrics::CONFIG_NOT_MODIFIED_COUNT, 1)
+            .await;
+        return Ok((StatusCode::NOT_MODIFIED, [(header::ETAG, etag)]).into_response());
+    }
+
     let config = sqlx::query_as::<_, ConfigVersion>("SELECT * FROM config_versions WHERE id = $1")
         .bind(bot.desired_version_id)
         .fetch_one(&state.db)
@@ -76,6 +120,23 @@ pub async fn get_bot_config(
         }
     };
 
+    let custom_assets: Vec<String> = config
+        .custom_assets
+        .clone()
+        .and_then(|v| serde_json::from_value(v).ok())
+        .unwrap_or_default();
+
+    let exit_params = AlgorithmFactory::params_for_persona(
+        config.persona,
+        config.strictness,
+        &RiskCaps {
+            max_position_size_percent: config.max_position_size_percent,
+            max_daily_loss_usd: config.max_daily_loss_usd,
+            max_drawdown_percent: config.max_drawdown_percent,
+            max_trades_per_day: config.max_trades_per_day,
+        },
+    );
+
     let payload = BotConfigPayload {
         version: format!("v{}", config.version),
         hash: config_hash,
@@ -101,6 +162,27 @@ pub async fn get_bot_config(
             api_key: decrypted_key,
             telegram_bot_token,
         },
+        funding: state.settings.current().trading.funding_requirements(),
+        exits: ExitRules {
+            stop_loss_pct: exit_params.stop_loss_pct,
+            take_profit_pct: exit_params.take_profit_pct,
+            trailing_stop_pct: config
+                .trailing_stop_percent
+                .map(|p| Decimal::from(p) / Decimal::from(100))
+                .unwrap_or(Decimal::ZERO),
+        },
+        asset_limits: config
+            .asset_limits
+            .clone()
+            .and_then(|v| serde_json::from_value(v).ok())
+            .unwrap_or_default(),
+        trading_window: config
+            .trading_window
+            .clone()
+            .and_then(|v| serde_json::from_value(v).ok()),
+        openclaw: OpenClawPayload {
+            asset_universe: universe::resolve(&registry, config.asset_focus, &custom_assets),
+        },
     };
 
     // Record metrics
@@ -114,7 +196,41 @@ pub async fn get_bot_config(
         .increment(metrics::CONFIG_FETCH_COUNT, 1)
         .await;
 
-    Ok(Json(payload))
+    Ok(([(header::ETAG, etag)], Json(payload)).into_response())
+}
+
+/// Strong ETag for a bot's desired config
+///
+/// Covers the asset registry too, so registry edits reach every runner.
+fn config_etag(
+    config_id: Uuid,
+    version: i32,
+    openclaw_updated_at: Option<DateTime<Utc>>,
+    registry: &[RegistryAsset],
+) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(format!(
+        "{}:{}:{}",
+        config_id,
+        version,
+        openclaw_updated_at
+            .map(|t| t.timestamp_micros())
+            .unwrap_or_default()
+    ));
+    hasher.update(serde_json::to_string(registry).unwrap_or_default());
+    let digest = hasher.finalize();
+    format!("\"{}\"", hex::encode(&digest[..16]))
+}
+
+/// Check an If-None-Match header (comma-separated list or `*`) against an ETag
+fn if_none_match_matches(headers: &HeaderMap, etag: &str) -> bool {
+    headers
+        .get_all(header::IF_NONE_MATCH)
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .flat_map(|v| v.split(','))
+        .map(|tag| tag.trim().trim_start_matches("W/"))
+        .any(|tag| tag == "*" || tag == etag)
 }
 
 /// POST /bot/:id/config_ack - Bot confirms config applied
@@ -162,11 +278,73 @@ pub async fn ack_config(
         "Bot {} acknowledged config version {} at {:?}",
         bot_id, ack.version, ack.applied_at
     );
+    state
+        .alerts
+        .resolve_for_bot(&bot_id.to_string(), &["config_mismatch"])
+        .await;
     state.metrics.increment(metrics::CONFIG_ACK_COUNT, 1).await;
 
     Ok(StatusCode::OK)
 }
 
+/// POST /bot/:id/bootstrap - Bot reports the outcome of a bootstrap phase
+///
+/// Failed phases are logged loudly: the droplet can re-run
+/// `bot-runner bootstrap` instead of being rebuilt.
+pub async fn report_bootstrap_phase(
+    State(state): State<Arc<AppState>>,
+    Path(bot_id): Path<Uuid>,
+    Json(req): Json<BootstrapPhaseReport>,
+) -> Result<StatusCode, (StatusCode, String)> {
+    if !BOOTSTRAP_PHASES.contains(&req.phase.as_str()) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!("Unknown bootstrap phase: {}", req.phase),
+        ));
+    }
+    if !BOOTSTRAP_STATUSES.contains(&req.status.as_str()) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!("Unknown bootstrap status: {}", req.status),
+        ));
+    }
+
+    let result = sqlx::query(
+        r#"
+        UPDATE bots SET bootstrap_phases = bootstrap_phases || jsonb_build_object(
+            $1::text, jsonb_build_object('status', $2::text, 'detail', $3::text, 'at', NOW())
+        ), updated_at = NOW()
+        WHERE id = $4
+        "#,
+    )
+    .bind(&req.phase)
+    .bind(&req.status)
+    .bind(&req.detail)
+    .bind(bot_id)
+    .execute(&state.db)
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    if result.rows_affected() == 0 {
+        return Err((StatusCode::NOT_FOUND, "Bot not found".to_string()));
+    }
+
+    if req.status == "failed" {
+        warn!(
+            "Bot {} bootstrap phase '{}' failed: {}",
+            bot_id,
+            req.phase,
+            req.detail.as_deref().unwrap_or("no detail")
+        );
+    } else {
+        info!(
+            "Bot {} bootstrap phase '{}': {}",
+            bot_id, req.phase, req.status
+        );
+    }
+    Ok(StatusCode::OK)
+}
+
 /// POST /bot/:id/wallet - Bot reports its Solana wallet address
 pub async fn report_wallet(
     State(state): State<Arc<AppState>>,
@@ -192,6 +370,29 @@ pub async fn report_wallet(
         ));
     }
 
+    // Once a wallet is registered it sticks: a runner reporting a different
+    // key is using the wrong keypair file and must not trade as this bot
+    let registered: Option<Option<String>> =
+        sqlx::query_scalar("SELECT agent_wallet FROM bots WHERE id = $1")
+            .bind(bot_id)
+            .fetch_optional(&state.db)
+            .await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    match registered {
+        None => return Err((StatusCode::NOT_FOUND, "Bot not found".to_string())),
+        Some(Some(existing)) if !existing.is_empty() && existing != req.wallet_address => {
+            warn!(
+                "Bot {} reported wallet {} but is registered to {}",
+                bot_id, req.wallet_address, existing
+            );
+            return Err((
+                StatusCode::CONFLICT,
+                format!("Bot is registered to wallet {}", existing),
+            ));
+        }
+        _ => {}
+    }
+
     // Update bot with wallet address
     let result = sqlx::query("UPDATE bots SET agent_wallet = $1, updated_at = NOW() WHERE id = $2")
         .bind(&req.wallet_address)
@@ -233,42 +434,10 @@ pub async fn heartbeat(
 ) -> Result<Json<HeartbeatResponse>, (StatusCode, String)> {
     let start = std::time::Instant::now();
 
-    // Use server timestamp for heartbeat to prevent clock skew issues
-    sqlx::query("UPDATE bots SET last_heartbeat_at = NOW(), updated_at = NOW() WHERE id = $1")
-        .bind(bot_id)
-        .execute(&state.db)
-        .await
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    touch_heartbeat(&state, bot_id).await?;
 
     if let Some(metrics_batch) = req.metrics {
-        let batch_len = metrics_batch.len();
-        for metric in metrics_batch {
-            // Convert Decimal to BigDecimal for database storage with proper error handling
-            let equity_bd = bigdecimal_from_decimal(&metric.equity).map_err(|e| {
-                (
-                    StatusCode::BAD_REQUEST,
-                    format!("Invalid equity value: {}", e),
-                )
-            })?;
-            let pnl_bd = bigdecimal_from_decimal(&metric.pnl)
-                .map_err(|e| (StatusCode::BAD_REQUEST, format!("Invalid pnl value: {}", e)))?;
-
-            sqlx::query(
-                "INSERT INTO metrics (bot_id, timestamp, equity, pnl) VALUES ($1, $2, $3, $4)",
-            )
-            .bind(bot_id)
-            .bind(metric.timestamp)
-            .bind(equity_bd)
-            .bind(pnl_bd)
-            .execute(&state.db)
-            .await
-            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
-        }
-
-        state
-            .metrics
-            .increment(metrics::METRICS_BATCH_RECEIVED, batch_len as u64)
-            .await;
+        store_metrics(&state, bot_id, &metrics_batch).await?;
     }
 
     let bot = sqlx::query_as::<_, Bot>("SELECT * FROM bots WHERE id = $1")
@@ -277,7 +446,7 @@ pub async fn heartbeat(
         .await
         .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
 
-    let needs_update = bot.desired_version_id != bot.applied_version_id.unwrap_or_default();
+    let needs_update = check_config_pending(&state, &bot).await;
 
     // Record heartbeat metrics
     let duration = start.elapsed().as_millis() as f64;
@@ -287,30 +456,6 @@ pub async fn heartbeat(
         .await;
     state.metrics.increment(metrics::HEARTBEAT_COUNT, 1).await;
 
-    // Check for config mismatch alert
-    if needs_update {
-        state
-            .metrics
-            .increment(metrics::CONFIG_MISMATCH_COUNT, 1)
-            .await;
-        if let Some(alert) = state
-            .alerts
-            .check_config_mismatch(
-                &bot_id.to_string(),
-                &bot.desired_version_id.to_string(),
-                &bot.applied_version_id
-                    .map(|id| id.to_string())
-                    .unwrap_or_default(),
-            )
-            .await
-        {
-            state
-                .alerts
-                .fire_alert(&alert, crate::alerting::AlertSeverity::Warning)
-                .await;
-        }
-    }
-
     Ok(Json(HeartbeatResponse {
         needs_config_update: needs_update,
         message: if needs_update {
@@ -318,6 +463,7 @@ pub async fn heartbeat(
         } else {
             "OK".to_string()
         },
+        desired_status: desired_runner_status(&state, &bot).await,
     }))
 }
 
@@ -327,11 +473,406 @@ pub async fn ingest_events(
     Path(bot_id): Path<Uuid>,
     Json(req): Json<EventsBatchRequest>,
 ) -> Result<StatusCode, (StatusCode, String)> {
-    let event_count = req.events.len() as u64;
+    store_events(&state, bot_id, &req.events).await?;
+    Ok(StatusCode::OK)
+}
+
+/// Event types the runner may deliver on the priority lane
+pub const PRIORITY_EVENT_TYPES: &[&str] = &[
+    "bot_shutdown",
+    "trading_halted",
+    "daily_loss_breached",
+    "drawdown_breached",
+];
+
+/// Most events one priority request may carry
+pub const MAX_PRIORITY_EVENTS: usize = 10;
+
+/// POST /bot/:id/events/priority - Critical events, outside the sync batch
+///
+/// Routed without the compression and bot rate limit layers so a shutdown or
+/// halt lands even while the runner is throttled. Only
+/// [`PRIORITY_EVENT_TYPES`] are accepted, in small batches, so the lane can't
+/// carry routine traffic.
+pub async fn ingest_priority_events(
+    State(state): State<Arc<AppState>>,
+    Path(bot_id): Path<Uuid>,
+    Json(req): Json<EventsBatchRequest>,
+) -> Result<StatusCode, (StatusCode, String)> {
+    if req.events.is_empty() || req.events.len() > MAX_PRIORITY_EVENTS {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!("Send 1 to {} priority events", MAX_PRIORITY_EVENTS),
+        ));
+    }
+    if let Some(event) = req
+        .events
+        .iter()
+        .find(|e| !PRIORITY_EVENT_TYPES.contains(&e.event_type.as_str()))
+    {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!("{} is not a priority event type", event.event_type),
+        ));
+    }
+
+    for event in &req.events {
+        warn!(
+            "Priority event from bot {}: {} - {}",
+            bot_id, event.event_type, event.message
+        );
+    }
+    store_events(&state, bot_id, &req.events).await?;
+    Ok(StatusCode::OK)
+}
+
+/// POST /bot/:id/sync - Consolidated heartbeat, metrics, events and state summary
+///
+/// Replaces the separate heartbeat/events calls the runner used to make each
+/// cycle. The response tells the bot whether a new config is waiting and
+/// carries any queued commands (each delivered exactly once). A payload whose
+/// `sync_id` was already stored, by this region or another, is acknowledged
+/// without being stored twice.
+pub async fn sync_bot(
+    State(state): State<Arc<AppState>>,
+    Path(bot_id): Path<Uuid>,
+    Json(req): Json<BotSyncRequest>,
+) -> Result<Json<BotSyncResponse>, (StatusCode, String)> {
+    let start = std::time::Instant::now();
+
+    let state_json = req
+        .state
+        .as_ref()
+        .map(|s| serde_json::to_value(s).unwrap_or_default());
+
+    let result = sqlx::query(
+        "UPDATE bots SET last_heartbeat_at = NOW(), last_sync_at = NOW(), \
+         last_sync_state = COALESCE($1, last_sync_state), updated_at = NOW() WHERE id = $2",
+    )
+    .bind(state_json)
+    .bind(bot_id)
+    .execute(&state.db)
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    if result.rows_affected() == 0 {
+        return Err((StatusCode::NOT_FOUND, "Bot not found".to_string()));
+    }
+
+    let region = crate::sync_receipts::region();
+    let duplicate = match req.sync_id {
+        Some(sync_id) => {
+            !crate::sync_receipts::claim(&state.db, bot_id, sync_id, region.as_deref())
+                .await
+                .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+        }
+        None => false,
+    };
+
+    let events_accepted = req.events.len();
+    let journal_accepted = if duplicate {
+        info!(
+            "Bot {} resent sync {} after a failover, not storing it again",
+            bot_id,
+            req.sync_id.unwrap_or_default()
+        );
+        req.journal.len()
+    } else {
+        match store_sync_payload(&state, bot_id, &req).await {
+            Ok(journal_accepted) => journal_accepted,
+            Err(e) => {
+                // Let the runner's retry store it instead
+                if let Some(sync_id) = req.sync_id {
+                    if let Err(release_err) =
+                        crate::sync_receipts::release(&state.db, bot_id, sync_id).await
+                    {
+                        error!(
+                            "Failed to release sync {} for bot {}: {}",
+                            sync_id, bot_id, release_err
+                        );
+                    }
+                }
+                return Err(e);
+            }
+        }
+    };
+
+    let bot = sqlx::query_as::<_, Bot>("SELECT * FROM bots WHERE id = $1")
+        .bind(bot_id)
+        .fetch_one(&state.db)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    if let Some(usage) = req
+        .llm_usage
+        .as_ref()
+        .filter(|u| u.requests > 0 && !duplicate)
+    {
+        meter_llm_usage(&state, &bot, usage).await;
+    }
+
+    let config_pending = check_config_pending(&state, &bot).await;
+
+    // Claim pending commands atomically so each is delivered once
+    let commands = sqlx::query_as::<_, BotCommand>(
+        r#"
+        UPDATE bot_commands SET delivered_at = NOW()
+        WHERE id IN (
+            SELECT id FROM bot_commands
+            WHERE bot_id = $1 AND delivered_at IS NULL
+            ORDER BY created_at
+            FOR UPDATE SKIP LOCKED
+        )
+        RETURNING id, command, args
+        "#,
+    )
+    .bind(bot_id)
+    .fetch_all(&state.db)
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    if !commands.is_empty() {
+        info!("Delivering {} command(s) to bot {}", commands.len(), bot_id);
+    }
+
+    let duration = start.elapsed().as_millis() as f64;
+    state
+        .metrics
+        .histogram(metrics::SYNC_DURATION_MS, duration)
+        .await;
+    state.metrics.increment(metrics::SYNC_COUNT, 1).await;
+
+    let halt = state.settings.current().trading.halt();
+
+    Ok(Json(BotSyncResponse {
+        config_pending,
+        new_version_id: config_pending.then_some(bot.desired_version_id),
+        commands,
+        events_accepted,
+        trading_halted: halt.is_some(),
+        halt_reason: halt.filter(|r| !r.is_empty()),
+        journal_accepted,
+        desired_status: desired_runner_status(&state, &bot).await,
+        region,
+        duplicate,
+    }))
+}
+
+/// Store a sync's metrics, events and journal; returns the journal entries
+/// accepted
+async fn store_sync_payload(
+    state: &AppState,
+    bot_id: Uuid,
+    req: &BotSyncRequest,
+) -> Result<usize, (StatusCode, String)> {
+    // Detect a reporting gap before the new points land
+    let gap_start = match req.metrics.iter().map(|m| m.timestamp).min() {
+        Some(first) => last_metric_before_gap(state, bot_id, first).await,
+        None => None,
+    };
+
+    if !req.metrics.is_empty() {
+        store_metrics(state, bot_id, &req.metrics).await?;
+    }
+
+    if !req.events.is_empty() {
+        store_events(state, bot_id, &req.events).await?;
+    }
+
+    let journal_accepted = if req.journal.is_empty() {
+        0
+    } else {
+        crate::journal::store_entries(&state.db, bot_id, &req.journal)
+            .await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+    };
+
+    // Trades made while offline arrive in this same sync, so rebuild after both
+    if let Some(since) = gap_start {
+        info!("Bot {} resumed after a metrics gap, backfilling", bot_id);
+        backfill::spawn_gap_backfill(state.db.clone(), bot_id, since);
+    }
+
+    Ok(journal_accepted)
+}
+
+/// Record reported LLM usage and alert the owner as it eats into their budget
+///
+/// Metering never fails the sync; errors are logged and the usage is lost.
+async fn meter_llm_usage(state: &AppState, bot: &Bot, usage: &crate::llm_usage::LlmUsageReport) {
+    let now = Utc::now();
+    let before = match crate::llm_usage::month_to_date_cost(&state.db, bot.user_id, now).await {
+        Ok(before) => before,
+        Err(e) => {
+            warn!("LLM usage lookup failed for user {}: {}", bot.user_id, e);
+            return;
+        }
+    };
+    let cost = match crate::llm_usage::record(&state.db, bot.id, usage).await {
+        Ok(cost) => cost,
+        Err(e) => {
+            warn!("Failed to record LLM usage for bot {}: {}", bot.id, e);
+            return;
+        }
+    };
+
+    let tier = match state.entitlements.get(bot.user_id) {
+        Some(entitlement) => entitlement.tier,
+        None => match crate::entitlements::load(
+            &state.db,
+            bot.user_id,
+            state.settings.current().billing.grace_period(),
+        )
+        .await
+        {
+            Ok(entitlement) => entitlement.tier,
+            Err(e) => {
+                warn!("Entitlement lookup failed for user {}: {}", bot.user_id, e);
+                return;
+            }
+        },
+    };
+    let budget = tier.included_llm_budget_usd();
+    let after = before + cost;
+    if let Some(threshold_pct) = crate::llm_usage::budget_crossing(before, after, budget) {
+        let severity = if threshold_pct >= 100 {
+            AlertSeverity::Critical
+        } else {
+            AlertSeverity::Warning
+        };
+        let user_id = bot.user_id.to_string();
+        crate::webhook::fire_alert_with_webhook(
+            &state.alerts,
+            &state.webhooks,
+            &AlertType::LlmBudget {
+                user_id: user_id.clone(),
+                spent_usd: after,
+                budget_usd: budget,
+                threshold_pct,
+            },
+            severity,
+            Some(&user_id),
+        )
+        .await;
+    }
+}
+
+/// What the runner should be doing; the owner's kill switch overrides the
+/// bot's own status
+async fn desired_runner_status(state: &AppState, bot: &Bot) -> &'static str {
+    let kill_switch: Result<bool, _> =
+        sqlx::query_scalar("SELECT EXISTS (SELECT 1 FROM kill_switches WHERE user_id = $1)")
+            .bind(bot.user_id)
+            .fetch_one(&state.db)
+            .await;
+    match kill_switch {
+        Ok(true) => "paused",
+        Ok(false) => bot.status.desired_runner_status(),
+        Err(e) => {
+            // Fail safe: a bot that can't be checked doesn't trade
+            warn!("Kill switch lookup failed for bot {}: {}", bot.id, e);
+            "paused"
+        }
+    }
+}
+
+/// Record a heartbeat using the server clock
+async fn touch_heartbeat(state: &AppState, bot_id: Uuid) -> Result<(), (StatusCode, String)> {
+    // Use server timestamp for heartbeat to prevent clock skew issues
+    sqlx::query("UPDATE bots SET last_heartbeat_at = NOW(), updated_at = NOW() WHERE id = $1")
+        .bind(bot_id)
+        .execute(&state.db)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    Ok(())
+}
+
+/// Timestamp of the bot's latest real metric if it is more than a gap before `next`
+async fn last_metric_before_gap(
+    state: &AppState,
+    bot_id: Uuid,
+    next: DateTime<Utc>,
+) -> Option<DateTime<Utc>> {
+    let last: Option<DateTime<Utc>> = sqlx::query_scalar(
+        "SELECT MAX(timestamp) FROM metrics WHERE bot_id = $1 AND NOT synthetic AND timestamp < $2",
+    )
+    .bind(bot_id)
+    .bind(next)
+    .fetch_one(&state.db)
+    .await
+    .ok()
+    .flatten();
+
+    last.filter(|last| (next - *last).num_seconds() > backfill::GAP_THRESHOLD_SECS)
+}
+
+/// Persist a batch of equity/PnL metrics for a bot
+async fn store_metrics(
+    state: &AppState,
+    bot_id: Uuid,
+    metrics_batch: &[MetricInput],
+) -> Result<(), (StatusCode, String)> {
+    let batch_len = metrics_batch.len();
+    for metric in metrics_batch {
+        // Convert Decimal to BigDecimal for database storage with proper error handling
+        let equity_bd = bigdecimal_from_decimal(&metric.equity).map_err(|e| {
+            (
+                StatusCode::BAD_REQUEST,
+                format!("Invalid equity value: {}", e),
+            )
+        })?;
+        let pnl_bd = bigdecimal_from_decimal(&metric.pnl)
+            .map_err(|e| (StatusCode::BAD_REQUEST, format!("Invalid pnl value: {}", e)))?;
+
+        let fees_bd = metric
+            .fees_usd
+            .map(|fees| bigdecimal_from_decimal(&fees))
+            .transpose()
+            .map_err(|e| {
+                (
+                    StatusCode::BAD_REQUEST,
+                    format!("Invalid fees_usd value: {}", e),
+                )
+            })?;
+
+        let performance = metric
+            .performance
+            .as_ref()
+            .map(|p| serde_json::to_value(p).unwrap_or_default());
+
+        sqlx::query(
+            "INSERT INTO metrics (bot_id, timestamp, equity, pnl, fees_usd, performance) \
+             VALUES ($1, $2, $3, $4, $5, $6)",
+        )
+        .bind(bot_id)
+        .bind(metric.timestamp)
+        .bind(equity_bd)
+        .bind(pnl_bd)
+        .bind(fees_bd)
+        .bind(performance)
+        .execute(&state.db)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    }
+
+    state
+        .metrics
+        .increment(metrics::METRICS_BATCH_RECEIVED, batch_len as u64)
+        .await;
+    Ok(())
+}
+
+/// Persist a batch of bot events and update event counters
+async fn store_events(
+    state: &AppState,
+    bot_id: Uuid,
+    events: &[EventInput],
+) -> Result<(), (StatusCode, String)> {
+    let event_count = events.len() as u64;
     let mut trade_count = 0u64;
     let mut error_count = 0u64;
 
-    for event in &req.events {
+    for event in events {
         sqlx::query(
             "INSERT INTO events (bot_id, event_type, message, metadata, created_at) VALUES ($1, $2, $3, $4, $5)"
         )
@@ -344,6 +885,29 @@ pub async fn ingest_events(
         .await
         .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
 
+        // Fills and closes also go to the trade history
+        if event.event_type == "trade_confirmed" || event.event_type == "trade_closed" {
+            crate::trades::record_from_event(
+                &state.db,
+                bot_id,
+                &event.event_type,
+                event.metadata.as_ref(),
+                event.timestamp,
+            )
+            .await;
+        }
+
+        // The day's official close rides along with the rollover
+        if event.event_type == "day_rollover" {
+            crate::daily_marks::record_from_event(
+                &state.db,
+                bot_id,
+                event.metadata.as_ref(),
+                event.timestamp,
+            )
+            .await;
+        }
+
         // Count trade events
         if event.event_type.starts_with("trade_") {
             trade_count += 1;
@@ -388,7 +952,54 @@ pub async fn ingest_events(
         ),
     );
 
-    Ok(StatusCode::OK)
+    Ok(())
+}
+
+/// Check whether the bot has a config it hasn't applied yet, firing a mismatch alert if so
+///
+/// Called on every check-in, so it also resolves the bot's offline alert and,
+/// once the config is applied, its mismatch alert.
+async fn check_config_pending(state: &AppState, bot: &Bot) -> bool {
+    let needs_update = bot.desired_version_id != bot.applied_version_id.unwrap_or_default();
+
+    let cleared: &[&str] = if needs_update {
+        &["bot_offline"]
+    } else {
+        &["bot_offline", "config_mismatch", "orphaned_bot"]
+    };
+    state
+        .alerts
+        .resolve_for_bot(&bot.id.to_string(), cleared)
+        .await;
+
+    if needs_update {
+        state
+            .metrics
+            .increment(metrics::CONFIG_MISMATCH_COUNT, 1)
+            .await;
+        if let Some(alert) = state
+            .alerts
+            .check_config_mismatch(
+                &bot.id.to_string(),
+                &bot.desired_version_id.to_string(),
+                &bot.applied_version_id
+                    .map(|id| id.to_string())
+                    .unwrap_or_default(),
+            )
+            .await
+        {
+            state
+                .alerts
+                .notify(
+                    &alert,
+                    crate::alerting::AlertSeverity::Warning,
+                    Some(&bot.user_id.to_string()),
+                )
+                .await;
+        }
+    }
+
+    needs_update
 }
 
 /// POST /bot/register - Bot registration on first boot
@@ -499,12 +1110,17 @@ pub struct SecretsResponse {
     pub jupiter_api_key: String,
     pub data_retrieval_url: String,
     pub solana_rpc_url: String,
+    /// Comma-separated, empty when the control plane runs in one region
+    pub control_plane_failover_urls: String,
     // OpenClaw secrets
     pub llm_provider: String,
     pub llm_model: String,
     pub llm_api_key: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub telegram_bot_token: Option<String>,
+    /// Hex AES-256 key for the state directory, for bots that opted in
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub state_encryption_key: Option<String>,
 }
 
 /// POST /bot/:id/secrets - Bot retrieves secrets using bootstrap token (one-time)
@@ -576,18 +1192,10 @@ pub async fn get_bot_secrets(
 
     let jupiter_api_key =
         config::get_config_decrypted_or(&state.db, &state.secrets, keys::JUPITER_API_KEY, "").await;
-    let data_retrieval_url = config::get_config_or(
-        &state.db,
-        keys::DATA_RETRIEVAL_URL,
-        "https://data.trawling-traders.com",
-    )
-    .await;
-    let solana_rpc_url = config::get_config_or(
-        &state.db,
-        keys::SOLANA_RPC_URL,
-        "https://api.devnet.solana.com",
-    )
-    .await;
+    let settings = state.settings.current();
+    let data_retrieval_url = settings.services.data_retrieval_url.clone();
+    let solana_rpc_url = settings.trading.solana_rpc_url.clone();
+    let control_plane_failover_urls = settings.services.control_plane_failover_urls.clone();
 
     // Retrieve OpenClaw secrets from bot_openclaw_config table
     let openclaw_config = sqlx::query_as::<_, BotOpenClawConfig>(
@@ -646,6 +1254,21 @@ pub async fn get_bot_secrets(
         }
     };
 
+    // Derived rather than stored, so a redeployed droplet (or a restored
+    // state bundle) gets the same key
+    let state_encryption_key = if bot.encrypt_state {
+        let key = state.secrets.derive_key(&format!("state:{}", bot_id));
+        if key.is_none() {
+            warn!(
+                "Bot {} asked for state encryption but SECRETS_ENCRYPTION_KEY is not set",
+                bot_id
+            );
+        }
+        key
+    } else {
+        None
+    };
+
     info!("Bot {} retrieved secrets successfully", bot_id);
     Logger::bot_event(
         &bot_id.to_string(),
@@ -657,9 +1280,44 @@ pub async fn get_bot_secrets(
         jupiter_api_key,
         data_retrieval_url,
         solana_rpc_url,
+        control_plane_failover_urls,
         llm_provider,
         llm_model,
         llm_api_key,
         telegram_bot_token,
+        state_encryption_key,
     }))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_config_etag_changes_with_version() {
+        let id = Uuid::new_v4();
+        let a = config_etag(id, 1, None, &[]);
+        assert_eq!(a, config_etag(id, 1, None, &[]));
+        assert_ne!(a, config_etag(id, 2, None, &[]));
+        assert_ne!(a, config_etag(id, 1, Some(Utc::now()), &[]));
+        let registry = universe::default_registry();
+        assert_ne!(a, config_etag(id, 1, None, &registry));
+        assert!(a.starts_with('"') && a.ends_with('"'));
+    }
+
+    #[test]
+    fn test_if_none_match() {
+        let etag = "\"abc\"";
+        let mut headers = HeaderMap::new();
+        assert!(!if_none_match_matches(&headers, etag));
+
+        headers.insert(header::IF_NONE_MATCH, "\"xyz\", W/\"abc\"".parse().unwrap());
+        assert!(if_none_match_matches(&headers, etag));
+
+        headers.insert(header::IF_NONE_MATCH, "\"xyz\"".parse().unwrap());
+        assert!(!if_none_match_matches(&headers, etag));
+
+        headers.insert(header::IF_NONE_MATCH, "*".parse().unwrap());
+        assert!(if_none_match_matches(&headers, etag));
+    }
+}
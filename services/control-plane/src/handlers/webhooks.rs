@@ -0,0 +1,156 @@
+//! User-registered webhook endpoints and their delivery log
+//!
+//! See `crate::webhook` for the signature scheme receivers should verify.
+
+use axum::{
+    extract::{Extension, Path, Query, State},
+    http::StatusCode,
+    Json,
+};
+use std::sync::Arc;
+use tracing::info;
+use uuid::Uuid;
+
+use crate::{middleware::AuthContext, models::*, AppState};
+
+/// Endpoints per user
+const MAX_ENDPOINTS: i64 = 10;
+
+fn user_uuid(auth: &AuthContext) -> Result<Uuid, (StatusCode, String)> {
+    Uuid::parse_str(&auth.user_id)
+        .map_err(|_| (StatusCode::BAD_REQUEST, "Invalid user ID".to_string()))
+}
+
+/// The endpoint, if it belongs to the user
+async fn get_owned_endpoint(
+    state: &AppState,
+    user_id: Uuid,
+    endpoint_id: Uuid,
+) -> Result<WebhookEndpoint, (StatusCode, String)> {
+    sqlx::query_as::<_, WebhookEndpoint>(
+        "SELECT id, url, enabled, created_at FROM webhook_endpoints WHERE id = $1 AND user_id = $2",
+    )
+    .bind(endpoint_id)
+    .bind(user_id)
+    .fetch_optional(&state.db)
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+    .ok_or((StatusCode::NOT_FOUND, "Webhook not found".to_string()))
+}
+
+/// GET /webhooks - The user's endpoints
+pub async fn list_webhooks(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+) -> Result<Json<WebhooksResponse>, (StatusCode, String)> {
+    let user_id = user_uuid(&auth)?;
+
+    let webhooks = sqlx::query_as::<_, WebhookEndpoint>(
+        "SELECT id, url, enabled, created_at FROM webhook_endpoints WHERE user_id = $1 \
+         ORDER BY created_at",
+    )
+    .bind(user_id)
+    .fetch_all(&state.db)
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    Ok(Json(WebhooksResponse { webhooks }))
+}
+
+/// POST /webhooks - Register an endpoint for the user's alerts
+///
+/// Returns the signing secret; it is stored encrypted and never shown again.
+pub async fn create_webhook(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Json(req): Json<CreateWebhookRequest>,
+) -> Result<(StatusCode, Json<CreateWebhookResponse>), (StatusCode, String)> {
+    let user_id = user_uuid(&auth)?;
+    let url = reqwest::Url::parse(req.url.trim())
+        .map_err(|_| (StatusCode::BAD_REQUEST, "Invalid webhook URL".to_string()))?;
+    if url.scheme() != "https" || url.host_str().is_none() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "Webhook URL must be https".to_string(),
+        ));
+    }
+
+    let (count,): (i64,) =
+        sqlx::query_as("SELECT COUNT(*) FROM webhook_endpoints WHERE user_id = $1")
+            .bind(user_id)
+            .fetch_one(&state.db)
+            .await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    if count >= MAX_ENDPOINTS {
+        return Err((
+            StatusCode::CONFLICT,
+            format!("At most {} webhooks per account", MAX_ENDPOINTS),
+        ));
+    }
+
+    let secret = format!("whsec_{}", hex::encode(rand::random::<[u8; 32]>()));
+    let encrypted = state
+        .secrets
+        .encrypt(&secret)
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    let endpoint = sqlx::query_as::<_, WebhookEndpoint>(
+        "INSERT INTO webhook_endpoints (user_id, url, encrypted_secret) VALUES ($1, $2, $3) \
+         RETURNING id, url, enabled, created_at",
+    )
+    .bind(user_id)
+    .bind(url.as_str())
+    .bind(&encrypted)
+    .fetch_one(&state.db)
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    info!("User {} registered webhook {}", user_id, endpoint.id);
+    Ok((
+        StatusCode::CREATED,
+        Json(CreateWebhookResponse { endpoint, secret }),
+    ))
+}
+
+/// DELETE /webhooks/:id - Remove an endpoint and its delivery log
+pub async fn delete_webhook(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Path(endpoint_id): Path<Uuid>,
+) -> Result<StatusCode, (StatusCode, String)> {
+    let user_id = user_uuid(&auth)?;
+    get_owned_endpoint(&state, user_id, endpoint_id).await?;
+
+    sqlx::query("DELETE FROM webhook_endpoints WHERE id = $1")
+        .bind(endpoint_id)
+        .execute(&state.db)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    info!("User {} deleted webhook {}", user_id, endpoint_id);
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// GET /webhooks/:id/deliveries - Recent delivery attempts, newest first
+pub async fn list_deliveries(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Path(endpoint_id): Path<Uuid>,
+    Query(query): Query<WebhookDeliveriesQuery>,
+) -> Result<Json<WebhookDeliveriesResponse>, (StatusCode, String)> {
+    let user_id = user_uuid(&auth)?;
+    get_owned_endpoint(&state, user_id, endpoint_id).await?;
+    let limit = query.limit.unwrap_or(50).clamp(1, 200);
+
+    let deliveries = sqlx::query_as::<_, WebhookDelivery>(
+        "SELECT id, delivery_id, event_type, attempt, status_code, error, duration_ms, created_at \
+         FROM webhook_deliveries WHERE endpoint_id = $1 ORDER BY created_at DESC LIMIT $2",
+    )
+    .bind(endpoint_id)
+    .bind(limit)
+    .fetch_all(&state.db)
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    Ok(Json(WebhookDeliveriesResponse { deliveries }))
+}
@@ -0,0 +1,326 @@
+//! Tamper-evident decision journals
+//!
+//! Runners upload their decision journal with each sync as a hash chain: every
+//! entry carries `seq`, the previous entry's hash and its own hash over
+//! `prev_hash`, the plan hash and the canonical JSON of the entry. We store
+//! each entry with whether it linked up to the stored head, and
+//! `GET /bots/:id/journal/verify` re-derives the whole chain from the stored
+//! entries. The hashing must match `bot_runner::journal`.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tracing::warn;
+use uuid::Uuid;
+
+/// `prev_hash` of the first entry in a bot's chain
+pub const GENESIS_HASH: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
+/// Journal entry as uploaded by the runner
+#[derive(Debug, Clone, Deserialize)]
+pub struct JournalEntryInput {
+    pub seq: i64,
+    pub prev_hash: String,
+    pub hash: String,
+    pub entry: serde_json::Value,
+}
+
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct JournalRow {
+    pub seq: i64,
+    pub prev_hash: String,
+    pub entry_hash: String,
+    pub entry: serde_json::Value,
+}
+
+/// Where and why a chain stops verifying
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ChainBreak {
+    pub seq: i64,
+    pub reason: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct JournalVerification {
+    pub bot_id: Uuid,
+    pub entries: usize,
+    pub valid: bool,
+    pub head_seq: Option<i64>,
+    pub head_hash: Option<String>,
+    pub first_break: Option<ChainBreak>,
+    pub verified_at: DateTime<Utc>,
+}
+
+/// JSON with object keys sorted at every level, so both ends hash the same bytes
+pub fn canonical_json(value: &serde_json::Value) -> String {
+    match value {
+        serde_json::Value::Object(map) => {
+            let mut keys: Vec<&String> = map.keys().collect();
+            keys.sort();
+            let fields: Vec<String> = keys
+                .into_iter()
+                .map(|k| {
+                    format!(
+                        "{}:{}",
+                        serde_json::Value::String(k.clone()),
+                        canonical_json(&map[k])
+                    )
+                })
+                .collect();
+            format!("{{{}}}", fields.join(","))
+        }
+        serde_json::Value::Array(items) => {
+            let items: Vec<String> = items.iter().map(canonical_json).collect();
+            format!("[{}]", items.join(","))
+        }
+        other => other.to_string(),
+    }
+}
+
+/// Hash linking an entry to its predecessor
+pub fn entry_hash(prev_hash: &str, plan_hash: &str, entry: &serde_json::Value) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(prev_hash.as_bytes());
+    hasher.update(b"\n");
+    hasher.update(plan_hash.as_bytes());
+    hasher.update(b"\n");
+    hasher.update(canonical_json(entry).as_bytes());
+    hex::encode(hasher.finalize())
+}
+
+/// Check one entry against its claimed hash and the expected predecessor
+///
+/// `head` is the (seq, hash) the entry should follow, or None for the first.
+pub fn check_link(
+    seq: i64,
+    prev_hash: &str,
+    hash: &str,
+    entry: &serde_json::Value,
+    head: Option<(i64, &str)>,
+) -> Result<(), ChainBreak> {
+    let broken = |reason: String| Err(ChainBreak { seq, reason });
+    let (expected_seq, expected_prev) = match head {
+        Some((head_seq, head_hash)) => (head_seq + 1, head_hash),
+        None => (1, GENESIS_HASH),
+    };
+    if seq != expected_seq {
+        return broken(format!("expected seq {}, got {}", expected_seq, seq));
+    }
+    if prev_hash != expected_prev {
+        return broken("prev_hash does not match the preceding entry".to_string());
+    }
+    let plan_hash = entry
+        .get("plan_hash")
+        .and_then(|v| v.as_str())
+        .unwrap_or_default();
+    if entry_hash(prev_hash, plan_hash, entry) != hash {
+        return broken("entry contents do not match its hash".to_string());
+    }
+    Ok(())
+}
+
+/// Re-verify a stored chain (rows ordered by seq)
+pub fn verify_rows(rows: &[JournalRow]) -> Option<ChainBreak> {
+    let mut head: Option<(i64, &str)> = None;
+    for row in rows {
+        if let Err(chain_break) =
+            check_link(row.seq, &row.prev_hash, &row.entry_hash, &row.entry, head)
+        {
+            return Some(chain_break);
+        }
+        head = Some((row.seq, &row.entry_hash));
+    }
+    None
+}
+
+/// Store uploaded entries, flagging the ones that don't extend the chain
+///
+/// Returns the number of new entries stored. Re-sent entries (same seq and
+/// hash) are skipped; a different hash for a stored seq means history was
+/// rewritten and is recorded as a `journal_chain_broken` event. Those
+/// entries are not stored, so the stored chain keeps the first version.
+pub async fn store_entries(
+    pool: &sqlx::PgPool,
+    bot_id: Uuid,
+    entries: &[JournalEntryInput],
+) -> Result<usize, sqlx::Error> {
+    let mut head: Option<(i64, String)> = sqlx::query_as(
+        "SELECT seq, entry_hash FROM decision_journal WHERE bot_id = $1 ORDER BY seq DESC LIMIT 1",
+    )
+    .bind(bot_id)
+    .fetch_optional(pool)
+    .await?;
+
+    let mut stored = 0;
+    let mut entries: Vec<&JournalEntryInput> = entries.iter().collect();
+    entries.sort_by_key(|e| e.seq);
+
+    for input in entries {
+        if head.as_ref().is_some_and(|(seq, _)| input.seq <= *seq) {
+            let existing: Option<String> = sqlx::query_scalar(
+                "SELECT entry_hash FROM decision_journal WHERE bot_id = $1 AND seq = $2",
+            )
+            .bind(bot_id)
+            .bind(input.seq)
+            .fetch_optional(pool)
+            .await?;
+            if existing.as_deref() != Some(input.hash.as_str()) {
+                record_break(
+                    pool,
+                    bot_id,
+                    &ChainBreak {
+                        seq: input.seq,
+                        reason: "entry differs from the one already uploaded".to_string(),
+                    },
+                )
+                .await?;
+            }
+            continue;
+        }
+
+        let link = check_link(
+            input.seq,
+            &input.prev_hash,
+            &input.hash,
+            &input.entry,
+            head.as_ref().map(|(seq, hash)| (*seq, hash.as_str())),
+        );
+        if let Err(chain_break) = &link {
+            record_break(pool, bot_id, chain_break).await?;
+        }
+
+        sqlx::query(
+            r#"
+            INSERT INTO decision_journal
+                (bot_id, seq, intent_id, plan_hash, prev_hash, entry_hash, entry, chain_valid)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            ON CONFLICT (bot_id, seq) DO NOTHING
+            "#,
+        )
+        .bind(bot_id)
+        .bind(input.seq)
+        .bind(
+            input
+                .entry
+                .get("intent_id")
+                .and_then(|v| v.as_str())
+                .and_then(|s| Uuid::parse_str(s).ok()),
+        )
+        .bind(
+            input
+                .entry
+                .get("plan_hash")
+                .and_then(|v| v.as_str())
+                .unwrap_or_default(),
+        )
+        .bind(&input.prev_hash)
+        .bind(&input.hash)
+        .bind(&input.entry)
+        .bind(link.is_ok())
+        .execute(pool)
+        .await?;
+
+        stored += 1;
+        head = Some((input.seq, input.hash.clone()));
+    }
+
+    Ok(stored)
+}
+
+async fn record_break(
+    pool: &sqlx::PgPool,
+    bot_id: Uuid,
+    chain_break: &ChainBreak,
+) -> Result<(), sqlx::Error> {
+    warn!(
+        "Bot {} journal chain broken at seq {}: {}",
+        bot_id, chain_break.seq, chain_break.reason
+    );
+    sqlx::query(
+        "INSERT INTO events (bot_id, event_type, message, metadata) VALUES ($1, $2, $3, $4)",
+    )
+    .bind(bot_id)
+    .bind("journal_chain_broken")
+    .bind(format!(
+        "Decision journal chain broken at entry {}: {}",
+        chain_break.seq, chain_break.reason
+    ))
+    .bind(serde_json::json!({"seq": chain_break.seq, "reason": chain_break.reason}))
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Verify a bot's full stored chain
+pub async fn verify_bot_journal(
+    pool: &sqlx::PgPool,
+    bot_id: Uuid,
+) -> Result<JournalVerification, sqlx::Error> {
+    let rows: Vec<JournalRow> = sqlx::query_as(
+        "SELECT seq, prev_hash, entry_hash, entry FROM decision_journal WHERE bot_id = $1 ORDER BY seq",
+    )
+    .bind(bot_id)
+    .fetch_all(pool)
+    .await?;
+
+    let first_break = verify_rows(&rows);
+    let head = rows.last();
+    Ok(JournalVerification {
+        bot_id,
+        entries: rows.len(),
+        valid: first_break.is_none(),
+        head_seq: head.map(|r| r.seq),
+        head_hash: head.map(|r| r.entry_hash.clone()),
+        first_break,
+        verified_at: Utc::now(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chain_verification() {
+        // Same vector as bot_runner::journal's test
+        let value = serde_json::json!({"b": [1, {"d": "x", "c": null}], "a": "1.50"});
+        assert_eq!(
+            entry_hash(GENESIS_HASH, "plan", &value),
+            "f7c34c0f66867cd1d98fe9120c371574168fe6f3e74f16560d116a0bc4d6404c"
+        );
+
+        let row = |seq: i64, prev: &str, entry: serde_json::Value| {
+            let plan = entry["plan_hash"].as_str().unwrap().to_string();
+            JournalRow {
+                seq,
+                prev_hash: prev.to_string(),
+                entry_hash: entry_hash(prev, &plan, &entry),
+                entry,
+            }
+        };
+        let first = row(
+            1,
+            GENESIS_HASH,
+            serde_json::json!({"plan_hash": "p1", "amount": "10"}),
+        );
+        let second = row(
+            2,
+            &first.entry_hash.clone(),
+            serde_json::json!({"plan_hash": "p2", "amount": "20"}),
+        );
+        assert_eq!(verify_rows(&[first.clone(), second.clone()]), None);
+
+        // Editing an entry in place is caught at that entry
+        let mut edited = first.clone();
+        edited.entry["amount"] = "1000".into();
+        assert_eq!(verify_rows(&[edited, second.clone()]).unwrap().seq, 1);
+
+        // Dropping an entry is caught at the next one
+        let third = row(
+            3,
+            &second.entry_hash.clone(),
+            serde_json::json!({"plan_hash": "p3"}),
+        );
+        assert_eq!(verify_rows(&[first, third]).unwrap().seq, 3);
+    }
+}
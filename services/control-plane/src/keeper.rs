@@ -0,0 +1,351 @@
+//! Keeper for stuck state transitions
+//!
+//! Bots can sit in a transitional state with nobody noticing: a config that
+//! stays `pending` because the runner never applies it, or a manually hosted
+//! bot left in `provisioning` because its runner was never started. The
+//! keeper wakes every few minutes and looks for both:
+//!
+//! - past the deadline (`stuck_config_deadline_minutes`,
+//!   `stuck_provisioning_deadline_minutes`) the owner gets an `orphaned_bot`
+//!   alert, and the runner keeps being offered the config on every sync
+//! - past `stuck_fail_after_minutes` the config is marked `failed`, or the
+//!   bot `error`, with an event on the bot's timeline
+//!
+//! A failed config isn't a dead end: the runner is still offered it and
+//! `config_status` becomes `applied` if it ever acks. Droplet bots stuck
+//! provisioning are destroyed by the orphan cleanup long before these
+//! deadlines (see `provisioning::spawn_cleanup_task`) and are only counted.
+//! Counts of everything past its deadline are in `GET /admin/kpis`.
+
+use chrono::{DateTime, Duration, Utc};
+use serde::Serialize;
+use std::sync::Arc;
+use tracing::{error, info, warn};
+use uuid::Uuid;
+
+use crate::alerting::{AlertSeverity, AlertType};
+use crate::AppState;
+
+pub const DEFAULT_CONFIG_DEADLINE_MINUTES: i64 = 60;
+pub const DEFAULT_PROVISIONING_DEADLINE_MINUTES: i64 = 120;
+pub const DEFAULT_FAIL_AFTER_MINUTES: i64 = 720;
+
+/// How often the keeper looks for stuck bots
+const KEEPER_INTERVAL_SECS: u64 = 300;
+
+/// Deadlines, from platform config
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct KeeperConfig {
+    /// A config pending this long is stuck
+    pub config_deadline: Duration,
+    /// A bot provisioning this long is stuck
+    pub provisioning_deadline: Duration,
+    /// Anything stuck this long is failed
+    pub fail_after: Duration,
+}
+
+impl Default for KeeperConfig {
+    fn default() -> Self {
+        Self {
+            config_deadline: Duration::minutes(DEFAULT_CONFIG_DEADLINE_MINUTES),
+            provisioning_deadline: Duration::minutes(DEFAULT_PROVISIONING_DEADLINE_MINUTES),
+            fail_after: Duration::minutes(DEFAULT_FAIL_AFTER_MINUTES),
+        }
+    }
+}
+
+impl From<&crate::settings::ProvisioningSettings> for KeeperConfig {
+    fn from(settings: &crate::settings::ProvisioningSettings) -> Self {
+        Self {
+            config_deadline: Duration::minutes(settings.stuck_config_deadline_minutes),
+            provisioning_deadline: Duration::minutes(settings.stuck_provisioning_deadline_minutes),
+            fail_after: Duration::minutes(settings.stuck_fail_after_minutes),
+        }
+    }
+}
+
+/// What to do about something stuck since `since`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeeperAction {
+    /// Past the deadline: tell the owner
+    Alert,
+    /// Past `fail_after` (and the deadline): give up on it
+    Fail,
+}
+
+pub fn action(
+    since: DateTime<Utc>,
+    deadline: Duration,
+    fail_after: Duration,
+    now: DateTime<Utc>,
+) -> Option<KeeperAction> {
+    let stuck_for = now - since;
+    if stuck_for < deadline {
+        None
+    } else if stuck_for >= fail_after {
+        Some(KeeperAction::Fail)
+    } else {
+        Some(KeeperAction::Alert)
+    }
+}
+
+/// Bots past their deadline right now
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+pub struct StuckCounts {
+    /// Configs pending past `stuck_config_deadline_minutes`
+    pub config_pending: i64,
+    /// Bots provisioning past `stuck_provisioning_deadline_minutes`
+    pub provisioning: i64,
+}
+
+pub async fn stuck_counts(
+    pool: &sqlx::PgPool,
+    config: &KeeperConfig,
+) -> Result<StuckCounts, sqlx::Error> {
+    let (config_pending, provisioning): (i64, i64) = sqlx::query_as(
+        r#"
+        SELECT
+            (SELECT COUNT(*) FROM bots b
+             JOIN config_versions cv ON cv.id = b.desired_version_id
+             WHERE b.config_status = 'pending' AND b.status <> 'destroying'
+               AND cv.created_at < NOW() - INTERVAL '1 second' * $1),
+            (SELECT COUNT(*) FROM bots
+             WHERE status = 'provisioning'
+               AND updated_at < NOW() - INTERVAL '1 second' * $2)
+        "#,
+    )
+    .bind(config.config_deadline.num_seconds())
+    .bind(config.provisioning_deadline.num_seconds())
+    .fetch_one(pool)
+    .await?;
+    Ok(StuckCounts {
+        config_pending,
+        provisioning,
+    })
+}
+
+/// Spawn the keeper loop
+pub fn spawn_keeper(state: Arc<AppState>) {
+    tokio::spawn(async move {
+        let mut interval =
+            tokio::time::interval(std::time::Duration::from_secs(KEEPER_INTERVAL_SECS));
+        loop {
+            interval.tick().await;
+            let config = KeeperConfig::from(&state.settings.current().provisioning);
+            if let Err(e) = keep_configs(&state, &config).await {
+                error!("Keeper failed checking pending configs: {}", e);
+            }
+            if let Err(e) = keep_provisioning(&state, &config).await {
+                error!("Keeper failed checking provisioning bots: {}", e);
+            }
+        }
+    });
+}
+
+/// Alert on, then fail, configs the runner hasn't applied
+async fn keep_configs(state: &AppState, config: &KeeperConfig) -> Result<(), sqlx::Error> {
+    let stuck: Vec<(Uuid, Uuid, Uuid, DateTime<Utc>)> = sqlx::query_as(
+        r#"
+        SELECT b.id, b.user_id, b.desired_version_id, cv.created_at
+        FROM bots b
+        JOIN config_versions cv ON cv.id = b.desired_version_id
+        WHERE b.config_status = 'pending' AND b.status <> 'destroying'
+          AND cv.created_at < NOW() - INTERVAL '1 second' * $1
+        "#,
+    )
+    .bind(config.config_deadline.num_seconds())
+    .fetch_all(&state.db)
+    .await?;
+
+    let now = Utc::now();
+    for (bot_id, user_id, version_id, since) in stuck {
+        let Some(action) = action(since, config.config_deadline, config.fail_after, now) else {
+            continue;
+        };
+        let mut severity = AlertSeverity::Warning;
+        let mut status = "config_pending";
+        if action == KeeperAction::Fail {
+            // Only if nothing changed since the scan
+            let failed = sqlx::query(
+                "UPDATE bots SET config_status = 'failed', updated_at = NOW() \
+                 WHERE id = $1 AND desired_version_id = $2 AND config_status = 'pending'",
+            )
+            .bind(bot_id)
+            .bind(version_id)
+            .execute(&state.db)
+            .await?
+            .rows_affected();
+            if failed == 0 {
+                continue;
+            }
+            warn!(
+                "Config {} for bot {} pending since {}; marked failed",
+                version_id, bot_id, since
+            );
+            record_event(
+                state,
+                bot_id,
+                "config_failed",
+                "Config was never applied by the runner; marked failed",
+                serde_json::json!({
+                    "version_id": version_id,
+                    "pending_since": since,
+                    "reason": "keeper",
+                }),
+            )
+            .await;
+            severity = AlertSeverity::Critical;
+            status = "config_failed";
+        }
+        alert(state, bot_id, user_id, status, now - since, severity).await;
+    }
+    Ok(())
+}
+
+/// Alert on bots stuck provisioning, and fail manual ones
+///
+/// Droplet bots are left to the orphan cleanup, which also destroys their
+/// droplet.
+async fn keep_provisioning(state: &AppState, config: &KeeperConfig) -> Result<(), sqlx::Error> {
+    let stuck: Vec<(Uuid, Uuid, String, DateTime<Utc>)> = sqlx::query_as(
+        r#"
+        SELECT id, user_id, provisioning::text, updated_at
+        FROM bots
+        WHERE status = 'provisioning'
+          AND updated_at < NOW() - INTERVAL '1 second' * $1
+        "#,
+    )
+    .bind(config.provisioning_deadline.num_seconds())
+    .fetch_all(&state.db)
+    .await?;
+
+    let now = Utc::now();
+    for (bot_id, user_id, mode, since) in stuck {
+        let Some(action) = action(since, config.provisioning_deadline, config.fail_after, now)
+        else {
+            continue;
+        };
+        if action == KeeperAction::Fail && mode == "manual" {
+            let failed = sqlx::query(
+                "UPDATE bots SET status = 'error', updated_at = NOW() \
+                 WHERE id = $1 AND status = 'provisioning'",
+            )
+            .bind(bot_id)
+            .execute(&state.db)
+            .await?
+            .rows_affected();
+            if failed == 0 {
+                continue;
+            }
+            info!(
+                "Manual bot {} provisioning since {} without a runner; marked error",
+                bot_id, since
+            );
+            record_event(
+                state,
+                bot_id,
+                "status_change",
+                "Runner never registered; bot marked error",
+                serde_json::json!({"provisioning_since": since, "reason": "keeper"}),
+            )
+            .await;
+            alert(
+                state,
+                bot_id,
+                user_id,
+                "provisioning_failed",
+                now - since,
+                AlertSeverity::Critical,
+            )
+            .await;
+            continue;
+        }
+        alert(
+            state,
+            bot_id,
+            user_id,
+            "provisioning",
+            now - since,
+            AlertSeverity::Warning,
+        )
+        .await;
+    }
+    Ok(())
+}
+
+async fn alert(
+    state: &AppState,
+    bot_id: Uuid,
+    user_id: Uuid,
+    status: &str,
+    stuck_for: Duration,
+    severity: AlertSeverity,
+) {
+    crate::webhook::fire_alert_with_webhook(
+        &state.alerts,
+        &state.webhooks,
+        &AlertType::OrphanedBot {
+            bot_id: bot_id.to_string(),
+            status: status.to_string(),
+            duration_secs: stuck_for.num_seconds().max(0) as u64,
+        },
+        severity,
+        Some(&user_id.to_string()),
+    )
+    .await;
+}
+
+async fn record_event(
+    state: &AppState,
+    bot_id: Uuid,
+    event_type: &str,
+    message: &str,
+    metadata: serde_json::Value,
+) {
+    if let Err(e) = sqlx::query(
+        "INSERT INTO events (bot_id, event_type, message, metadata) VALUES ($1, $2, $3, $4)",
+    )
+    .bind(bot_id)
+    .bind(event_type)
+    .bind(message)
+    .bind(metadata)
+    .execute(&state.db)
+    .await
+    {
+        warn!("Failed to record keeper event for bot {}: {}", bot_id, e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_action_by_age() {
+        let now = Utc.with_ymd_and_hms(2026, 3, 1, 12, 0, 0).unwrap();
+        let deadline = Duration::minutes(60);
+        let fail_after = Duration::minutes(720);
+        let at = |minutes: i64| now - Duration::minutes(minutes);
+
+        assert_eq!(action(at(59), deadline, fail_after, now), None);
+        assert_eq!(
+            action(at(60), deadline, fail_after, now),
+            Some(KeeperAction::Alert)
+        );
+        assert_eq!(
+            action(at(719), deadline, fail_after, now),
+            Some(KeeperAction::Alert)
+        );
+        assert_eq!(
+            action(at(720), deadline, fail_after, now),
+            Some(KeeperAction::Fail)
+        );
+        // A fail_after shorter than the deadline still waits for the deadline
+        assert_eq!(action(at(30), deadline, Duration::minutes(10), now), None);
+        assert_eq!(
+            action(at(90), deadline, Duration::minutes(10), now),
+            Some(KeeperAction::Fail)
+        );
+    }
+}
@@ -5,28 +5,62 @@ pub mod models;
 pub mod user_data;
 pub mod handlers {
     pub mod admin;
+    pub mod alerts;
+    pub mod artifacts;
+    pub mod backtest;
+    pub mod billing;
     pub mod bots;
+    pub mod handover;
+    pub mod kill_switch;
     pub mod openclaw_config;
+    pub mod public;
     pub mod simulate;
     pub mod sync;
+    pub mod webhooks;
 }
 pub mod alerting;
+pub mod artifacts;
+pub mod backfill;
 pub mod cedros;
+pub mod compaction;
+pub mod config_document;
+pub mod daily_marks;
 pub mod db;
+pub mod drawdowns;
+pub mod droplets;
+pub mod entitlements;
+pub mod events;
 pub mod health;
+pub mod journal;
+pub mod keeper;
+pub mod llm_usage;
+pub mod log_level;
+pub mod metric_series;
 pub mod middleware;
 pub mod observability;
+pub mod performance;
+pub mod preferences;
 pub mod provisioning;
+pub mod rollups;
 pub mod secrets;
+pub mod settings;
+pub mod shadow;
+pub mod storage;
+pub mod sync_receipts;
+pub mod trades;
+pub mod universe;
 pub mod webhook;
+pub mod whatif;
 
 use axum::{
-    routing::{get, patch, post},
+    routing::{delete, get, patch, post, put},
     Router,
 };
 use std::sync::Arc;
 use tokio::sync::Semaphore;
+use tower_http::compression::CompressionLayer;
 use tower_http::cors::CorsLayer;
+use tower_http::decompression::RequestDecompressionLayer;
 use tower_http::trace::TraceLayer;
 
 pub use alerting::{AlertConfig, AlertManager};
@@ -44,28 +78,46 @@ pub struct AppState {
     pub metrics: MetricsCollector,
     pub rate_limiter: middleware::rate_limit::RateLimiter,
     pub bot_rate_limiter: middleware::rate_limit::RateLimiter,
-    /// Concurrency limit for droplet provisioning (max 3 concurrent)
+    /// Per-route budgets, auth failure backoff and temporary blocks
+    pub abuse: middleware::AbuseGuard,
+    /// Typed platform_config, refreshed on edit
+    pub settings: settings::SettingsHandle,
+    /// Concurrency limit for droplet provisioning (`max_concurrent_provisions`)
     pub droplet_semaphore: Arc<Semaphore>,
     /// Alert manager for threshold-based notifications
     pub alerts: AlertManager,
     /// Webhook notifier for external alerts
     pub webhooks: WebhookNotifier,
+    /// Cached subscription plans, invalidated by payment webhooks
+    pub entitlements: entitlements::EntitlementCache,
     /// JWT service for RS256 token validation (from cedros-login)
     pub jwt_service: Option<cedros_login::services::JwtService>,
+    /// Reload handle for the tracing filter (None when logging was set up elsewhere)
+    pub log_level: Option<log_level::LogLevelControl>,
 }
 
 impl AppState {
     pub fn new(db: Db) -> Self {
+        let alerts = AlertManager::new(AlertConfig::default()).with_store(db.clone());
+        let secrets = SecretsManager::new();
+        let webhooks =
+            WebhookNotifier::new(WebhookConfig::default()).with_store(db.clone(), secrets.clone());
+        let settings = settings::SettingsHandle::new();
+        let provisions = settings.current().limits.max_concurrent_provisions as usize;
         Self {
             db,
-            secrets: SecretsManager::new(),
+            secrets,
             metrics: MetricsCollector::new(),
             rate_limiter: middleware::rate_limit::RateLimiter::new(60, 100),
             bot_rate_limiter: middleware::rate_limit::RateLimiter::new(60, 120),
-            droplet_semaphore: Arc::new(Semaphore::new(3)),
-            alerts: AlertManager::new(AlertConfig::default()),
-            webhooks: WebhookNotifier::new(WebhookConfig::default()),
+            abuse: middleware::AbuseGuard::new(),
+            settings,
+            droplet_semaphore: Arc::new(Semaphore::new(provisions)),
+            alerts,
+            webhooks,
+            entitlements: entitlements::EntitlementCache::new(),
             jwt_service: None,
+            log_level: None,
         }
     }
 
@@ -74,6 +126,12 @@ impl AppState {
         self.jwt_service = Some(jwt_service);
         self
     }
+
+    /// Enable `PUT /admin/log-level`
+    pub fn with_log_level_control(mut self, control: log_level::LogLevelControl) -> Self {
+        self.log_level = Some(control);
+        self
+    }
 }
 
 /// Build the API router
@@ -108,11 +166,41 @@ pub async fn app(state: Arc<AppState>) -> Router {
             header::COOKIE,
             header::HeaderName::from_static("x-csrf-token"),
         ])
+        .expose_headers([
+            middleware::request_id::X_REQUEST_ID.clone(),
+            middleware::kill_switch::X_KILL_SWITCH.clone(),
+            middleware::kill_switch::X_KILL_SWITCH_SINCE.clone(),
+        ])
         .allow_credentials(true);
 
     // App-facing routes (require auth + subscription + rate limit)
     let app_routes = Router::new()
         .route("/me", get(handlers::bots::get_current_user))
+        .route(
+            "/me/alert-settings",
+            get(handlers::bots::get_alert_settings).put(handlers::bots::update_alert_settings),
+        )
+        .route(
+            "/me/preferences",
+            get(handlers::bots::get_preferences).patch(handlers::bots::update_preferences),
+        )
+        .route(
+            "/me/kill-switch",
+            post(handlers::kill_switch::engage_kill_switch)
+                .delete(handlers::kill_switch::release_kill_switch),
+        )
+        .route("/alerts", get(handlers::alerts::list_alerts))
+        .route("/alerts/:id/ack", post(handlers::alerts::acknowledge_alert))
+        .route(
+            "/webhooks",
+            get(handlers::webhooks::list_webhooks).post(handlers::webhooks::create_webhook),
+        )
+        .route("/webhooks/:id", delete(handlers::webhooks::delete_webhook))
+        .route(
+            "/webhooks/:id/deliveries",
+            get(handlers::webhooks::list_deliveries),
+        )
+        .route("/dashboard", get(handlers::bots::get_dashboard))
         .route("/bots", get(handlers::bots::list_bots))
         .route(
             "/bots",
@@ -121,18 +209,88 @@ pub async fn app(state: Arc<AppState>) -> Router {
                 middleware::subscription::bot_create_limit_middleware,
             )),
         )
+        .route(
+            "/bots/import",
+            post(handlers::bots::import_bot).layer(axum::middleware::from_fn_with_state(
+                state.clone(),
+                middleware::subscription::bot_create_limit_middleware,
+            )),
+        )
         .route("/bots/:id", get(handlers::bots::get_bot))
         .route("/bots/:id/config", patch(handlers::bots::update_bot_config))
+        .route(
+            "/bots/:id/config/export",
+            get(handlers::bots::export_bot_config),
+        )
         .route("/bots/:id/actions", post(handlers::bots::bot_action))
         .route("/bots/:id/metrics", get(handlers::bots::get_metrics))
         .route("/bots/:id/events", get(handlers::bots::get_events))
+        .route(
+            "/bots/:id/journal/verify",
+            get(handlers::bots::verify_journal),
+        )
+        .route(
+            "/bots/:id/performance",
+            get(handlers::bots::get_performance),
+        )
+        .route("/bots/:id/trades", get(handlers::bots::get_trades))
+        .route(
+            "/bots/:id/performance/by-asset",
+            get(handlers::bots::get_performance_by_asset),
+        )
+        .route("/bots/:id/what-if", get(handlers::bots::get_what_if))
+        .route(
+            "/bots/:id/shadow-replay",
+            post(handlers::bots::shadow_replay),
+        )
+        .route(
+            "/bots/:id/journal/export",
+            post(handlers::artifacts::export_journal),
+        )
+        .route(
+            "/bots/:id/artifacts",
+            get(handlers::artifacts::list_artifacts),
+        )
+        .route(
+            "/bots/:id/artifacts/:artifact_id/download",
+            get(handlers::artifacts::download_artifact),
+        )
+        .route(
+            "/bots/:id/diagnostics",
+            post(handlers::artifacts::request_diagnostics),
+        )
+        .route(
+            "/bots/:id/share",
+            get(handlers::public::get_share_link)
+                .post(handlers::public::create_share_link)
+                .delete(handlers::public::revoke_share_link),
+        )
+        .route("/bots/:id/infra-cost", get(handlers::bots::get_infra_cost))
+        .route("/bots/:id/llm-usage", get(handlers::bots::get_llm_usage))
+        .route(
+            "/bots/:id/daily-marks",
+            get(handlers::bots::get_daily_marks),
+        )
+        .route(
+            "/bots/:id/funding",
+            get(handlers::bots::get_funding_instructions),
+        )
+        .route(
+            "/bots/:id/credentials",
+            post(handlers::bots::issue_runner_credentials),
+        )
         .route(
             "/simulate-signal",
             post(handlers::simulate::simulate_signal),
         )
+        .route("/backtest", post(handlers::backtest::run_backtest))
         // Health endpoints
         .route("/healthz", get(health::healthz))
         .route("/readyz", get(health::readyz))
+        .layer(axum::middleware::from_fn_with_state(
+            state.clone(),
+            middleware::kill_switch::kill_switch_middleware,
+        ))
         .layer(axum::middleware::from_fn_with_state(
             state.clone(),
             middleware::subscription::subscription_middleware,
@@ -141,10 +299,18 @@ pub async fn app(state: Arc<AppState>) -> Router {
             state.clone(),
             middleware::rate_limit::rate_limit_middleware,
         ))
+        .layer(axum::middleware::from_fn_with_state(
+            state.clone(),
+            middleware::abuse::user_budget_middleware,
+        ))
         .layer(axum::middleware::from_fn_with_state(
             state.clone(),
             middleware::auth_middleware,
         ))
+        .layer(axum::middleware::from_fn_with_state(
+            state.clone(),
+            middleware::abuse::app_abuse_middleware,
+        ))
         .with_state(state.clone());
 
     // Bot-facing routes (internal, from VPS)
@@ -153,14 +319,65 @@ pub async fn app(state: Arc<AppState>) -> Router {
         .route("/bot/:id/config", get(handlers::sync::get_bot_config))
         .route("/bot/:id/config_ack", post(handlers::sync::ack_config))
         .route("/bot/:id/wallet", post(handlers::sync::report_wallet))
+        .route(
+            "/bot/:id/bootstrap",
+            post(handlers::sync::report_bootstrap_phase),
+        )
         .route("/bot/:id/heartbeat", post(handlers::sync::heartbeat))
         .route("/bot/:id/events", post(handlers::sync::ingest_events))
+        .route("/bot/:id/sync", post(handlers::sync::sync_bot))
+        .route(
+            "/bot/:id/state",
+            get(handlers::handover::download_state).put(handlers::handover::upload_state),
+        )
+        .route(
+            "/bot/:id/artifacts/diagnostics",
+            put(handlers::artifacts::upload_diagnostics),
+        )
+        .layer(axum::middleware::from_fn_with_state(
+            state.clone(),
+            middleware::compression::decoded_size_middleware,
+        ))
+        .layer(RequestDecompressionLayer::new())
+        .layer(axum::middleware::from_fn_with_state(
+            state.clone(),
+            middleware::compression::wire_size_middleware,
+        ))
+        .layer(CompressionLayer::new())
         .layer(axum::middleware::from_fn_with_state(
             state.clone(),
             middleware::rate_limit::bot_rate_limit_middleware,
         ))
         .with_state(state.clone());
 
+    // Critical bot events: no compression or bot rate limit in the way
+    let priority_routes = Router::new()
+        .route(
+            "/bot/:id/events/priority",
+            post(handlers::sync::ingest_priority_events),
+        )
+        .with_state(state.clone());
+
+    // Public share pages (no auth; the token is the credential)
+    let public_routes = Router::new()
+        .route(
+            "/public/perf/:token",
+            get(handlers::public::get_public_performance),
+        )
+        .route(
+            "/billing/webhooks/stripe",
+            post(handlers::billing::stripe_webhook),
+        )
+        .route(
+            "/billing/webhooks/cedros-pay",
+            post(handlers::billing::cedros_pay_webhook),
+        )
+        .layer(axum::middleware::from_fn_with_state(
+            state.clone(),
+            middleware::abuse::public_abuse_middleware,
+        ))
+        .with_state(state.clone());
+
     // Cedros Pay routes - try full integration, fallback to placeholder
     let pay_routes = match cedros::pay::full_router(state.db.clone()).await {
         Ok(router) => {
@@ -180,7 +397,11 @@ pub async fn app(state: Arc<AppState>) -> Router {
     Router::new()
         .nest("/v1", app_routes)
         .nest("/v1", bot_routes)
+        .nest("/v1", priority_routes)
+        .nest("/v1", public_routes)
         .merge(pay_routes) // cedros-pay applies its own /paywall/v1 prefix
         .layer(cors)
         .layer(TraceLayer::new_for_http())
+        // Outermost, so the trace span and every log line below carry the ID
+        .layer(axum::middleware::from_fn(middleware::request_id_middleware))
 }
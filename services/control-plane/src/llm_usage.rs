@@ -0,0 +1,357 @@
+//! LLM usage metering
+//!
+//! Runners count the decision requests they make and the tokens the gateway
+//! reports for each, and send the totals with every sync. They land in
+//! `llm_usage`, one row per bot, day, provider and model, with a cost
+//! estimated from the list prices below at the time of ingest.
+//!
+//! Costs are estimates: a provider or model missing from the table is
+//! counted but not costed. Each plan includes a monthly LLM budget
+//! (`SubscriptionTier::included_llm_budget_usd`); the owner gets an
+//! `llm_budget` alert as their month-to-date estimate crosses 80% and 100%
+//! of it.
+
+use bigdecimal::BigDecimal;
+use chrono::{DateTime, Datelike, NaiveDate, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::models::{try_bigdecimal_from_decimal, try_decimal_from_bigdecimal};
+
+/// Percentages of the included budget that alert the owner
+pub const BUDGET_THRESHOLDS_PCT: [u32; 2] = [80, 100];
+
+/// Usage totals a runner reports on sync
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+pub struct LlmUsageReport {
+    pub requests: u64,
+    #[serde(default)]
+    pub prompt_tokens: u64,
+    #[serde(default)]
+    pub completion_tokens: u64,
+    pub model: Option<String>,
+}
+
+/// List price in USD per million (prompt, completion) tokens
+///
+/// Models are matched by prefix, so dated snapshots (`gpt-4o-2024-08-06`)
+/// price like their family; the first match wins. An empty model prices at
+/// the provider's default.
+pub fn price_per_million(provider: &str, model: &str) -> Option<(Decimal, Decimal)> {
+    // (model prefix, prompt cents, completion cents)
+    let table: &[(&str, i64, i64)] = match provider {
+        "openai" => &[
+            ("gpt-4o-mini", 15, 60),
+            ("gpt-4o", 250, 1000),
+            ("gpt-4.1-mini", 40, 160),
+            ("gpt-4.1", 200, 800),
+            ("", 250, 1000),
+        ],
+        "anthropic" => &[
+            ("claude-3-5-haiku", 80, 400),
+            ("claude-3-opus", 1500, 7500),
+            ("claude-3-5-sonnet", 300, 1500),
+            ("", 300, 1500),
+        ],
+        "venice" => &[("", 70, 280)],
+        _ => return None,
+    };
+    let model = model.to_ascii_lowercase();
+    table
+        .iter()
+        .find(|(prefix, _, _)| model.starts_with(prefix))
+        .map(|(_, prompt, completion)| (Decimal::new(*prompt, 2), Decimal::new(*completion, 2)))
+}
+
+/// Estimated cost of a report, if its provider and model are priced
+pub fn estimate_cost_usd(provider: &str, model: &str, report: &LlmUsageReport) -> Option<Decimal> {
+    let (prompt, completion) = price_per_million(provider, model)?;
+    let million = Decimal::from(1_000_000);
+    Some(
+        (prompt * Decimal::from(report.prompt_tokens)
+            + completion * Decimal::from(report.completion_tokens))
+            / million,
+    )
+}
+
+/// Highest budget threshold crossed going from `before` to `after`
+pub fn budget_crossing(before: Decimal, after: Decimal, budget: Decimal) -> Option<u32> {
+    if budget <= Decimal::ZERO {
+        return None;
+    }
+    BUDGET_THRESHOLDS_PCT
+        .iter()
+        .rev()
+        .find(|pct| {
+            let line = budget * Decimal::from(**pct) / Decimal::from(100);
+            before < line && after >= line
+        })
+        .copied()
+}
+
+/// Add a sync's usage to the bot's row for today
+///
+/// Returns the cost estimated for it (zero if unpriced).
+pub async fn record(
+    pool: &sqlx::PgPool,
+    bot_id: Uuid,
+    report: &LlmUsageReport,
+) -> Result<Decimal, sqlx::Error> {
+    let (provider, configured_model): (String, String) = sqlx::query_as(
+        r#"
+        SELECT COALESCE(oc.llm_provider, cv.llm_provider), COALESCE(oc.llm_model, '')
+        FROM bots b
+        JOIN config_versions cv ON cv.id = b.desired_version_id
+        LEFT JOIN bot_openclaw_config oc ON oc.bot_id = b.id
+        WHERE b.id = $1
+        "#,
+    )
+    .bind(bot_id)
+    .fetch_one(pool)
+    .await?;
+    let model = report
+        .model
+        .clone()
+        .filter(|m| !m.is_empty())
+        .unwrap_or(configured_model);
+    let cost = estimate_cost_usd(&provider, &model, report);
+
+    sqlx::query(
+        r#"
+        INSERT INTO llm_usage
+            (bot_id, day, provider, model, requests, prompt_tokens, completion_tokens,
+             estimated_cost_usd, unpriced_requests)
+        VALUES ($1, CURRENT_DATE, $2, $3, $4, $5, $6, $7, $8)
+        ON CONFLICT (bot_id, day, provider, model) DO UPDATE SET
+            requests = llm_usage.requests + EXCLUDED.requests,
+            prompt_tokens = llm_usage.prompt_tokens + EXCLUDED.prompt_tokens,
+            completion_tokens = llm_usage.completion_tokens + EXCLUDED.completion_tokens,
+            estimated_cost_usd = llm_usage.estimated_cost_usd + EXCLUDED.estimated_cost_usd,
+            unpriced_requests = llm_usage.unpriced_requests + EXCLUDED.unpriced_requests,
+            updated_at = NOW()
+        "#,
+    )
+    .bind(bot_id)
+    .bind(&provider)
+    .bind(&model)
+    .bind(report.requests as i64)
+    .bind(report.prompt_tokens as i64)
+    .bind(report.completion_tokens as i64)
+    .bind(try_bigdecimal_from_decimal(&cost.unwrap_or_default()))
+    .bind(if cost.is_some() {
+        0
+    } else {
+        report.requests as i64
+    })
+    .execute(pool)
+    .await?;
+
+    Ok(cost.unwrap_or_default())
+}
+
+/// Usage for one day, provider and model (or a rollup of them)
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct LlmUsageRow {
+    pub day: Option<NaiveDate>,
+    pub provider: String,
+    pub model: String,
+    pub requests: i64,
+    pub prompt_tokens: i64,
+    pub completion_tokens: i64,
+    pub estimated_cost_usd: Decimal,
+    /// Requests whose provider or model has no price
+    pub unpriced_requests: i64,
+}
+
+#[derive(sqlx::FromRow)]
+struct UsageRecord {
+    day: Option<NaiveDate>,
+    provider: String,
+    model: String,
+    requests: i64,
+    prompt_tokens: i64,
+    completion_tokens: i64,
+    estimated_cost_usd: Option<BigDecimal>,
+    unpriced_requests: i64,
+}
+
+impl From<UsageRecord> for LlmUsageRow {
+    fn from(r: UsageRecord) -> Self {
+        Self {
+            day: r.day,
+            provider: r.provider,
+            model: r.model,
+            requests: r.requests,
+            prompt_tokens: r.prompt_tokens,
+            completion_tokens: r.completion_tokens,
+            estimated_cost_usd: r
+                .estimated_cost_usd
+                .as_ref()
+                .and_then(try_decimal_from_bigdecimal)
+                .unwrap_or_default(),
+            unpriced_requests: r.unpriced_requests,
+        }
+    }
+}
+
+/// A bot's usage per day since `since`, newest first
+pub async fn bot_usage(
+    pool: &sqlx::PgPool,
+    bot_id: Uuid,
+    since: NaiveDate,
+) -> Result<Vec<LlmUsageRow>, sqlx::Error> {
+    let rows: Vec<UsageRecord> = sqlx::query_as(
+        "SELECT day, provider, model, requests, prompt_tokens, completion_tokens, \
+         estimated_cost_usd, unpriced_requests FROM llm_usage \
+         WHERE bot_id = $1 AND day >= $2 ORDER BY day DESC, provider, model",
+    )
+    .bind(bot_id)
+    .bind(since)
+    .fetch_all(pool)
+    .await?;
+    Ok(rows.into_iter().map(Into::into).collect())
+}
+
+/// Estimated cost of all of a user's bots since the start of `now`'s month
+pub async fn month_to_date_cost(
+    pool: &sqlx::PgPool,
+    user_id: Uuid,
+    now: DateTime<Utc>,
+) -> Result<Decimal, sqlx::Error> {
+    let month_start = now.date_naive().with_day(1).unwrap_or(now.date_naive());
+    let total: Option<BigDecimal> = sqlx::query_scalar(
+        "SELECT SUM(u.estimated_cost_usd) FROM llm_usage u JOIN bots b ON b.id = u.bot_id \
+         WHERE b.user_id = $1 AND u.day >= $2",
+    )
+    .bind(user_id)
+    .bind(month_start)
+    .fetch_one(pool)
+    .await?;
+    Ok(total
+        .as_ref()
+        .and_then(try_decimal_from_bigdecimal)
+        .unwrap_or_default())
+}
+
+/// Platform usage since `since` per provider and model, costliest first
+pub async fn usage_by_provider(
+    pool: &sqlx::PgPool,
+    since: NaiveDate,
+) -> Result<Vec<LlmUsageRow>, sqlx::Error> {
+    let rows: Vec<UsageRecord> = sqlx::query_as(
+        "SELECT NULL::date AS day, provider, model, SUM(requests)::bigint AS requests, \
+         SUM(prompt_tokens)::bigint AS prompt_tokens, \
+         SUM(completion_tokens)::bigint AS completion_tokens, \
+         SUM(estimated_cost_usd) AS estimated_cost_usd, \
+         SUM(unpriced_requests)::bigint AS unpriced_requests \
+         FROM llm_usage WHERE day >= $1 GROUP BY provider, model \
+         ORDER BY SUM(estimated_cost_usd) DESC",
+    )
+    .bind(since)
+    .fetch_all(pool)
+    .await?;
+    Ok(rows.into_iter().map(Into::into).collect())
+}
+
+/// One bot's spend in an admin rollup
+#[derive(Debug, Clone, Serialize)]
+pub struct BotLlmSpend {
+    pub bot_id: Uuid,
+    pub user_id: Uuid,
+    pub requests: i64,
+    pub estimated_cost_usd: Decimal,
+}
+
+/// Bots with the highest estimated spend since `since`
+pub async fn top_bots(
+    pool: &sqlx::PgPool,
+    since: NaiveDate,
+    limit: i64,
+) -> Result<Vec<BotLlmSpend>, sqlx::Error> {
+    let rows: Vec<(Uuid, Uuid, i64, Option<BigDecimal>)> = sqlx::query_as(
+        "SELECT u.bot_id, b.user_id, SUM(u.requests)::bigint AS requests, \
+         SUM(u.estimated_cost_usd) AS estimated_cost_usd \
+         FROM llm_usage u JOIN bots b ON b.id = u.bot_id \
+         WHERE u.day >= $1 GROUP BY u.bot_id, b.user_id \
+         ORDER BY SUM(u.estimated_cost_usd) DESC LIMIT $2",
+    )
+    .bind(since)
+    .bind(limit)
+    .fetch_all(pool)
+    .await?;
+    Ok(rows
+        .into_iter()
+        .map(|(bot_id, user_id, requests, cost)| BotLlmSpend {
+            bot_id,
+            user_id,
+            requests,
+            estimated_cost_usd: cost
+                .as_ref()
+                .and_then(try_decimal_from_bigdecimal)
+                .unwrap_or_default(),
+        })
+        .collect())
+}
+
+/// Sum rows into one total (day, provider and model left empty)
+pub fn total(rows: &[LlmUsageRow]) -> LlmUsageRow {
+    rows.iter().fold(LlmUsageRow::default(), |mut sum, row| {
+        sum.requests += row.requests;
+        sum.prompt_tokens += row.prompt_tokens;
+        sum.completion_tokens += row.completion_tokens;
+        sum.estimated_cost_usd += row.estimated_cost_usd;
+        sum.unpriced_requests += row.unpriced_requests;
+        sum
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_price_table_and_estimate() {
+        // Snapshots price like their family; mini isn't mistaken for 4o
+        assert_eq!(
+            price_per_million("openai", "gpt-4o-2024-08-06"),
+            Some((Decimal::new(250, 2), Decimal::new(1000, 2)))
+        );
+        assert_eq!(
+            price_per_million("openai", "GPT-4o-mini"),
+            Some((Decimal::new(15, 2), Decimal::new(60, 2)))
+        );
+        assert_eq!(
+            price_per_million("anthropic", ""),
+            Some((Decimal::new(300, 2), Decimal::new(1500, 2)))
+        );
+        assert_eq!(price_per_million("openrouter", "gpt-4o"), None);
+
+        let report = LlmUsageReport {
+            requests: 10,
+            prompt_tokens: 2_000_000,
+            completion_tokens: 100_000,
+            model: None,
+        };
+        // 2M prompt at $2.50 + 0.1M completion at $10
+        assert_eq!(
+            estimate_cost_usd("openai", "gpt-4o", &report),
+            Some(Decimal::from(6))
+        );
+        assert_eq!(estimate_cost_usd("openrouter", "", &report), None);
+    }
+
+    #[test]
+    fn test_budget_crossing() {
+        let budget = Decimal::from(10);
+        let d = |v: i64| Decimal::new(v, 1);
+        assert_eq!(budget_crossing(d(70), d(79), budget), None);
+        assert_eq!(budget_crossing(d(79), d(80), budget), Some(80));
+        // Already past 80%: no repeat until 100%
+        assert_eq!(budget_crossing(d(80), d(95), budget), None);
+        assert_eq!(budget_crossing(d(95), d(101), budget), Some(100));
+        // A big jump reports the highest line crossed
+        assert_eq!(budget_crossing(d(0), d(120), budget), Some(100));
+        assert_eq!(budget_crossing(d(0), d(120), Decimal::ZERO), None);
+    }
+}
@@ -0,0 +1,123 @@
+//! Runtime-adjustable tracing filter for the control plane
+//!
+//! `PUT /v1/admin/log-level` swaps the filter through a reload handle; the
+//! change expires after its TTL and the startup filter (RUST_LOG or `info`)
+//! comes back. Bots get the same via the `set_log_level` command.
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tracing::{info, warn};
+use tracing_subscriber::{layer::SubscriberExt, reload, util::SubscriberInitExt, EnvFilter};
+
+pub const DEFAULT_TTL_SECS: u64 = 900;
+pub const MAX_TTL_SECS: u64 = 4 * 3600;
+
+type FilterHandle = reload::Handle<EnvFilter, tracing_subscriber::Registry>;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LogLevelStatus {
+    pub current: String,
+    pub default: String,
+    /// When the current override lapses (None when running the default)
+    pub reverts_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Clone)]
+pub struct LogLevelControl {
+    handle: FilterHandle,
+    default_directives: String,
+    /// Expiry of the active override; a revert timer only fires if it still matches
+    override_until: Arc<Mutex<Option<DateTime<Utc>>>>,
+}
+
+impl LogLevelControl {
+    /// Install the global subscriber and return the handle for changing it
+    pub fn init() -> Self {
+        let default_directives = std::env::var("RUST_LOG")
+            .ok()
+            .filter(|d| EnvFilter::try_new(d).is_ok())
+            .unwrap_or_else(|| "info".to_string());
+        let (filter, handle) = reload::Layer::new(EnvFilter::new(&default_directives));
+        tracing_subscriber::registry()
+            .with(filter)
+            .with(tracing_subscriber::fmt::layer())
+            .init();
+        Self::new(handle, default_directives)
+    }
+
+    pub fn new(handle: FilterHandle, default_directives: String) -> Self {
+        Self {
+            handle,
+            default_directives,
+            override_until: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    /// Switch to `directives` for `ttl` (capped at `MAX_TTL_SECS`)
+    pub fn set(&self, directives: &str, ttl: Duration) -> Result<LogLevelStatus, String> {
+        let filter = EnvFilter::try_new(directives)
+            .map_err(|e| format!("Invalid log filter '{}': {}", directives, e))?;
+        let ttl = ttl.min(Duration::from_secs(MAX_TTL_SECS));
+        let until = Utc::now() + chrono::Duration::from_std(ttl).unwrap_or_default();
+
+        self.handle.reload(filter).map_err(|e| e.to_string())?;
+        *self.override_until.lock().unwrap() = Some(until);
+        info!("Log filter set to '{}' until {}", directives, until);
+
+        let control = self.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(ttl).await;
+            let expired = *control.override_until.lock().unwrap() == Some(until);
+            if expired {
+                control.reset();
+            }
+        });
+        Ok(self.status())
+    }
+
+    /// Drop any override and restore the startup filter
+    pub fn reset(&self) {
+        *self.override_until.lock().unwrap() = None;
+        match self.handle.reload(EnvFilter::new(&self.default_directives)) {
+            Ok(()) => info!("Log filter reverted to '{}'", self.default_directives),
+            Err(e) => warn!("Failed to revert log filter: {}", e),
+        }
+    }
+
+    pub fn status(&self) -> LogLevelStatus {
+        LogLevelStatus {
+            current: self
+                .handle
+                .with_current(|f| f.to_string())
+                .unwrap_or_else(|_| self.default_directives.clone()),
+            default: self.default_directives.clone(),
+            reverts_at: *self.override_until.lock().unwrap(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_override_expires() {
+        let (_layer, handle) =
+            reload::Layer::<_, tracing_subscriber::Registry>::new(EnvFilter::new("info"));
+        let control = LogLevelControl::new(handle, "info".to_string());
+
+        assert!(control.set("=[bad", Duration::from_secs(5)).is_err());
+        let status = control
+            .set("control_plane=debug", Duration::from_millis(100))
+            .unwrap();
+        assert_eq!(status.current, "control_plane=debug");
+        assert!(status.reverts_at.is_some());
+
+        tokio::time::sleep(Duration::from_millis(250)).await;
+        let status = control.status();
+        assert_eq!(status.current, "info");
+        assert!(status.reverts_at.is_none());
+    }
+}
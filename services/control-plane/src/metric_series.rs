@@ -0,0 +1,213 @@
+//! Equity series for `GET /bots/:id/metrics`
+//!
+//! Runners report a metric every sync, so a month of them is tens of
+//! thousands of rows. Unless a client asks for `raw` points the series is
+//! downsampled in the database into fixed time buckets: equity and PnL are the bucket's averages, fees
+//! (cumulative) its maximum, and a bucket is `synthetic` only when every
+//! point in it was backfilled. Without an explicit `resolution` the
+//! smallest bucket that keeps the range under [`MAX_POINTS`] is used.
+
+use chrono::{DateTime, Duration, Utc};
+use uuid::Uuid;
+
+use crate::db::Db;
+use crate::models::MetricDb;
+
+/// Most points one response carries
+pub const MAX_POINTS: i64 = 1000;
+
+/// Bucket widths a client can ask for, finest first
+pub const BUCKETS: [(&str, i64); 5] = [
+    ("5m", 300),
+    ("15m", 900),
+    ("1h", 3600),
+    ("4h", 14_400),
+    ("1d", 86_400),
+];
+
+/// How far back the series goes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MetricsRange {
+    Day,
+    Week,
+    Month,
+    All,
+}
+
+impl MetricsRange {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "24h" => Some(Self::Day),
+            "7d" => Some(Self::Week),
+            "30d" => Some(Self::Month),
+            "all" => Some(Self::All),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Day => "24h",
+            Self::Week => "7d",
+            Self::Month => "30d",
+            Self::All => "all",
+        }
+    }
+
+    /// None for all history
+    pub fn duration(&self) -> Option<Duration> {
+        match self {
+            Self::Day => Some(Duration::hours(24)),
+            Self::Week => Some(Duration::days(7)),
+            Self::Month => Some(Duration::days(30)),
+            Self::All => None,
+        }
+    }
+}
+
+/// Every point (the newest [`MAX_POINTS`]), or averages over buckets of
+/// this many seconds
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Resolution {
+    Raw,
+    Bucket(i64),
+}
+
+impl Resolution {
+    /// `raw` or one of [`BUCKETS`]
+    pub fn parse(s: &str) -> Option<Self> {
+        if s == "raw" {
+            return Some(Self::Raw);
+        }
+        BUCKETS
+            .iter()
+            .find(|(name, _)| *name == s)
+            .map(|(_, secs)| Self::Bucket(*secs))
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Raw => "raw",
+            Self::Bucket(secs) => BUCKETS
+                .iter()
+                .find(|(_, s)| s == secs)
+                .map_or("raw", |(name, _)| name),
+        }
+    }
+
+    /// The finest bucket that fits `span` in [`MAX_POINTS`] (days past that)
+    pub fn auto(span: Duration) -> Self {
+        let secs = span.num_seconds().max(1);
+        let (_, bucket) = BUCKETS
+            .iter()
+            .find(|(_, b)| secs / b <= MAX_POINTS)
+            .unwrap_or(&BUCKETS[BUCKETS.len() - 1]);
+        Self::Bucket(*bucket)
+    }
+
+    /// Points it gives over `span`, at most
+    pub fn points(&self, span: Duration) -> Option<i64> {
+        match self {
+            Self::Raw => None,
+            Self::Bucket(secs) => Some(span.num_seconds().max(0) / secs + 1),
+        }
+    }
+}
+
+/// Start of `range` for the bot: the window's start, or its first metric
+pub async fn range_start(
+    db: &Db,
+    bot_id: Uuid,
+    range: MetricsRange,
+) -> Result<DateTime<Utc>, sqlx::Error> {
+    if let Some(duration) = range.duration() {
+        return Ok(Utc::now() - duration);
+    }
+    let first: Option<DateTime<Utc>> =
+        sqlx::query_scalar("SELECT MIN(timestamp) FROM metrics WHERE bot_id = $1")
+            .bind(bot_id)
+            .fetch_one(db)
+            .await?;
+    Ok(first.unwrap_or_else(Utc::now))
+}
+
+/// The bot's metrics since `since`, newest first
+///
+/// Either way only the newest [`MAX_POINTS`] are kept. A bucketed point's
+/// id is that of the last metric in it and its timestamp the bucket's start.
+pub async fn load(
+    db: &Db,
+    bot_id: Uuid,
+    since: DateTime<Utc>,
+    resolution: Resolution,
+) -> Result<Vec<MetricDb>, sqlx::Error> {
+    match resolution {
+        Resolution::Raw => {
+            sqlx::query_as::<_, MetricDb>(
+                "SELECT * FROM metrics WHERE bot_id = $1 AND timestamp >= $2 \
+                 ORDER BY timestamp DESC LIMIT $3",
+            )
+            .bind(bot_id)
+            .bind(since)
+            .bind(MAX_POINTS)
+            .fetch_all(db)
+            .await
+        }
+        Resolution::Bucket(secs) => {
+            sqlx::query_as::<_, MetricDb>(
+                r#"
+                SELECT (array_agg(id ORDER BY timestamp DESC))[1] AS id,
+                       bot_id,
+                       to_timestamp(floor(extract(epoch FROM timestamp) / $3)::float8 * $3)
+                           AS timestamp,
+                       ROUND(AVG(equity), 8) AS equity,
+                       ROUND(AVG(pnl), 8) AS pnl,
+                       bool_and(synthetic) AS synthetic,
+                       MAX(fees_usd) AS fees_usd
+                FROM metrics
+                WHERE bot_id = $1 AND timestamp >= $2
+                GROUP BY bot_id, 3
+                ORDER BY 3 DESC
+                LIMIT $4
+                "#,
+            )
+            .bind(bot_id)
+            .bind(since)
+            .bind(secs)
+            .bind(MAX_POINTS)
+            .fetch_all(db)
+            .await
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_range_and_resolution() {
+        assert_eq!(MetricsRange::parse("30d"), Some(MetricsRange::Month));
+        assert_eq!(MetricsRange::parse("1y"), None);
+        assert_eq!(MetricsRange::All.duration(), None);
+
+        assert_eq!(Resolution::parse("raw"), Some(Resolution::Raw));
+        assert_eq!(Resolution::parse("4h"), Some(Resolution::Bucket(14_400)));
+        assert_eq!(Resolution::parse("2h"), None);
+        assert_eq!(Resolution::Bucket(900).as_str(), "15m");
+    }
+
+    #[test]
+    fn test_auto_resolution_stays_under_max_points() {
+        assert_eq!(Resolution::auto(Duration::hours(24)).as_str(), "5m");
+        assert_eq!(Resolution::auto(Duration::days(7)).as_str(), "15m");
+        assert_eq!(Resolution::auto(Duration::days(30)).as_str(), "1h");
+        assert_eq!(Resolution::auto(Duration::days(400)).as_str(), "1d");
+        // Years of history still come back, just capped
+        assert_eq!(Resolution::auto(Duration::days(4000)).as_str(), "1d");
+        for range in [MetricsRange::Day, MetricsRange::Week, MetricsRange::Month] {
+            let span = range.duration().unwrap();
+            assert!(Resolution::auto(span).points(span).unwrap() <= MAX_POINTS + 1);
+        }
+    }
+}
@@ -0,0 +1,567 @@
+//! Anti-abuse protections for public routes
+//!
+//! Runs in front of the general rate limiter with budgets of its own, per
+//! route class and per caller:
+//! - per IP: `auth` 20/min, `public` 60/min, `app` 300/min
+//! - per user on app routes: 3000/hour
+//!
+//! Failed authentication (401/403 from `/v1/auth`, 401 elsewhere) is counted
+//! per IP. After [`FREE_AUTH_FAILURES`] each further failure locks the IP out
+//! for twice as long as the last, from 2 seconds up to 5 minutes; the count is
+//! forgotten after [`FAILURE_MEMORY_SECS`] without one, or on an
+//! authenticated app request. An IP reaching [`BLOCK_AFTER_AUTH_FAILURES`],
+//! or a caller that keeps going after its budget is spent, is blocked for
+//! [`BLOCK_SECS`].
+//!
+//! The caller's IP is the last `X-Forwarded-For` hop (the one our load
+//! balancer added), or the peer address without one. Counters and blocks are
+//! in memory per instance, like the rate limiter; backoffs and blocks are
+//! recorded in `abuse_events` for `GET /admin/abuse`.
+
+use axum::{
+    body::Body,
+    extract::{ConnectInfo, Request, State},
+    http::{header, HeaderValue, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::RwLock;
+use tracing::{error, warn};
+
+use crate::{
+    middleware::{rate_limit::RateLimiter, AuthContext},
+    AppState,
+};
+
+/// Auth failures an IP gets before it's slowed down
+pub const FREE_AUTH_FAILURES: u32 = 5;
+
+/// Auth failures that get an IP blocked outright
+pub const BLOCK_AFTER_AUTH_FAILURES: u32 = 20;
+
+/// An IP's failure count resets after this long without a failure
+pub const FAILURE_MEMORY_SECS: u64 = 900;
+
+/// How long a temporary block lasts
+pub const BLOCK_SECS: u64 = 900;
+
+/// First lockout after the free failures; each one after doubles it
+const BACKOFF_BASE_SECS: u64 = 2;
+
+/// Longest lockout short of a block
+const MAX_BACKOFF_SECS: u64 = 300;
+
+/// Over-budget requests per 10 minutes before the caller is blocked
+const STRIKES_BEFORE_BLOCK: u32 = 50;
+
+/// Callers with failures on record before stale ones are swept out
+const MAX_TRACKED_FAILURES: usize = 10_000;
+
+/// Per-user budget on app routes, per hour
+const USER_BUDGET_PER_HOUR: u32 = 3000;
+
+/// Routes with their own per-IP budget
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RouteClass {
+    /// `/v1/auth`: login, registration, token refresh
+    Auth,
+    /// Share pages and payment webhooks
+    Public,
+    /// Authenticated app routes
+    App,
+}
+
+impl RouteClass {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RouteClass::Auth => "auth",
+            RouteClass::Public => "public",
+            RouteClass::App => "app",
+        }
+    }
+
+    /// Requests per minute one IP may make
+    fn ip_budget(&self) -> u32 {
+        match self {
+            RouteClass::Auth => 20,
+            RouteClass::Public => 60,
+            RouteClass::App => 300,
+        }
+    }
+
+    /// Whether a response status counts as failed authentication
+    pub fn is_auth_failure(&self, status: StatusCode) -> bool {
+        match self {
+            RouteClass::Auth => {
+                status == StatusCode::UNAUTHORIZED || status == StatusCode::FORBIDDEN
+            }
+            RouteClass::Public | RouteClass::App => status == StatusCode::UNAUTHORIZED,
+        }
+    }
+}
+
+/// Lockout after `failures` consecutive auth failures, if any
+pub fn auth_backoff(failures: u32) -> Option<Duration> {
+    let over = failures.checked_sub(FREE_AUTH_FAILURES + 1)?;
+    let secs = BACKOFF_BASE_SECS.saturating_mul(1 << over.min(16));
+    Some(Duration::from_secs(secs.min(MAX_BACKOFF_SECS)))
+}
+
+/// A temporary block in force
+#[derive(Debug, Clone, Serialize)]
+pub struct ActiveBlock {
+    /// `ip:<addr>` or `user:<uuid>`
+    pub subject: String,
+    pub route_class: RouteClass,
+    pub reason: String,
+    pub expires_at: DateTime<Utc>,
+    #[serde(skip)]
+    until: Instant,
+}
+
+#[derive(Debug, Clone)]
+struct FailureRecord {
+    count: u32,
+    last_at: Instant,
+    locked_until: Option<Instant>,
+}
+
+/// What an auth failure led to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Escalation {
+    /// Locked out for this long; `first` on the failure that started it
+    Backoff { lockout: Duration, first: bool },
+    /// Enough failures for a block
+    Block,
+}
+
+/// Anti-abuse state shared by every route class
+#[derive(Clone)]
+pub struct AbuseGuard {
+    auth_ip: RateLimiter,
+    public_ip: RateLimiter,
+    app_ip: RateLimiter,
+    user: RateLimiter,
+    /// Requests turned away for being over budget
+    strikes: RateLimiter,
+    failures: Arc<RwLock<HashMap<String, FailureRecord>>>,
+    blocks: Arc<RwLock<HashMap<String, ActiveBlock>>>,
+}
+
+impl Default for AbuseGuard {
+    fn default() -> Self {
+        Self {
+            auth_ip: RateLimiter::new(60, RouteClass::Auth.ip_budget()),
+            public_ip: RateLimiter::new(60, RouteClass::Public.ip_budget()),
+            app_ip: RateLimiter::new(60, RouteClass::App.ip_budget()),
+            user: RateLimiter::new(3600, USER_BUDGET_PER_HOUR),
+            strikes: RateLimiter::new(600, STRIKES_BEFORE_BLOCK),
+            failures: Arc::new(RwLock::new(HashMap::new())),
+            blocks: Arc::new(RwLock::new(HashMap::new())),
+        }
+    }
+}
+
+impl AbuseGuard {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn ip_limiter(&self, class: RouteClass) -> &RateLimiter {
+        match class {
+            RouteClass::Auth => &self.auth_ip,
+            RouteClass::Public => &self.public_ip,
+            RouteClass::App => &self.app_ip,
+        }
+    }
+
+    /// Time left on `subject`'s block or auth lockout
+    pub async fn held_off(&self, subject: &str) -> Option<Duration> {
+        let now = Instant::now();
+        {
+            let mut blocks = self.blocks.write().await;
+            match blocks.get(subject) {
+                Some(block) if block.until > now => return Some(block.until - now),
+                Some(_) => {
+                    blocks.remove(subject);
+                }
+                None => {}
+            }
+        }
+        self.failures
+            .read()
+            .await
+            .get(subject)
+            .and_then(|r| r.locked_until)
+            .filter(|until| *until > now)
+            .map(|until| until - now)
+    }
+
+    /// Block `subject` for [`BLOCK_SECS`]
+    pub async fn block(&self, subject: &str, route_class: RouteClass, reason: &str) -> ActiveBlock {
+        let now = Instant::now();
+        let block = ActiveBlock {
+            subject: subject.to_string(),
+            route_class,
+            reason: reason.to_string(),
+            expires_at: Utc::now() + chrono::Duration::seconds(BLOCK_SECS as i64),
+            until: now + Duration::from_secs(BLOCK_SECS),
+        };
+        let mut blocks = self.blocks.write().await;
+        blocks.retain(|_, b| b.until > now);
+        blocks.insert(subject.to_string(), block.clone());
+        block
+    }
+
+    /// Lift a block early; false if there wasn't one
+    pub async fn unblock(&self, subject: &str) -> bool {
+        self.failures.write().await.remove(subject);
+        self.blocks.write().await.remove(subject).is_some()
+    }
+
+    /// Blocks still in force, soonest to expire first
+    pub async fn active_blocks(&self) -> Vec<ActiveBlock> {
+        let now = Instant::now();
+        let mut blocks: Vec<ActiveBlock> = self
+            .blocks
+            .read()
+            .await
+            .values()
+            .filter(|b| b.until > now)
+            .cloned()
+            .collect();
+        blocks.sort_by_key(|b| b.until);
+        blocks
+    }
+
+    /// Count an over-budget request; true once `subject` has earned a block
+    async fn strike(&self, subject: &str) -> bool {
+        !self.strikes.check(subject).await
+    }
+
+    /// Count a failed authentication from `subject`
+    pub async fn record_auth_failure(&self, subject: &str) -> Option<Escalation> {
+        let now = Instant::now();
+        let mut failures = self.failures.write().await;
+        if failures.len() >= MAX_TRACKED_FAILURES {
+            let memory = Duration::from_secs(FAILURE_MEMORY_SECS);
+            failures.retain(|_, r| now.duration_since(r.last_at) < memory);
+        }
+        let record = failures
+            .entry(subject.to_string())
+            .or_insert(FailureRecord {
+                count: 0,
+                last_at: now,
+                locked_until: None,
+            });
+        if now.duration_since(record.last_at) >= Duration::from_secs(FAILURE_MEMORY_SECS) {
+            record.count = 0;
+        }
+        record.count += 1;
+        record.last_at = now;
+
+        if record.count >= BLOCK_AFTER_AUTH_FAILURES {
+            failures.remove(subject);
+            return Some(Escalation::Block);
+        }
+        let lockout = auth_backoff(record.count)?;
+        record.locked_until = Some(now + lockout);
+        Some(Escalation::Backoff {
+            lockout,
+            first: record.count == FREE_AUTH_FAILURES + 1,
+        })
+    }
+
+    /// Forget `subject`'s auth failures
+    pub async fn record_auth_success(&self, subject: &str) {
+        self.failures.write().await.remove(subject);
+    }
+}
+
+/// Record an anti-abuse action in `abuse_events`
+pub async fn record_event(
+    pool: &sqlx::PgPool,
+    subject: &str,
+    route_class: RouteClass,
+    kind: &str,
+    reason: &str,
+    blocked_until: Option<DateTime<Utc>>,
+    actor: Option<&str>,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "INSERT INTO abuse_events (subject, route_class, kind, reason, blocked_until, actor) \
+         VALUES ($1, $2, $3, $4, $5, $6)",
+    )
+    .bind(subject)
+    .bind(route_class.as_str())
+    .bind(kind)
+    .bind(reason)
+    .bind(blocked_until)
+    .bind(actor)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Block `subject` and record it; recording never fails the request
+async fn place_block(state: &AppState, subject: &str, route_class: RouteClass, reason: &str) {
+    let block = state.abuse.block(subject, route_class, reason).await;
+    warn!(
+        "Blocked {} on {} routes until {}: {}",
+        subject,
+        route_class.as_str(),
+        block.expires_at.to_rfc3339(),
+        reason
+    );
+    if let Err(e) = record_event(
+        &state.db,
+        subject,
+        route_class,
+        "blocked",
+        reason,
+        Some(block.expires_at),
+        None,
+    )
+    .await
+    {
+        error!("Failed to record block of {}: {}", subject, e);
+    }
+}
+
+/// 429 telling the caller how long it's held off
+fn held_off_response(wait: Duration) -> Response {
+    let secs = wait.as_secs() + u64::from(wait.subsec_nanos() > 0);
+    let mut response = (
+        StatusCode::TOO_MANY_REQUESTS,
+        "Too many requests; try again later",
+    )
+        .into_response();
+    response
+        .headers_mut()
+        .insert(header::RETRY_AFTER, HeaderValue::from(secs.max(1)));
+    response
+}
+
+/// The caller's IP: the last `X-Forwarded-For` hop, else the peer address
+fn client_ip(request: &Request<Body>) -> String {
+    request
+        .headers()
+        .get("x-forwarded-for")
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.rsplit(',').next())
+        .map(str::trim)
+        .filter(|ip| !ip.is_empty())
+        .map(str::to_string)
+        .or_else(|| {
+            request
+                .extensions()
+                .get::<ConnectInfo<SocketAddr>>()
+                .map(|ConnectInfo(addr)| addr.ip().to_string())
+        })
+        .unwrap_or_else(|| "unknown".to_string())
+}
+
+/// Per-IP budget, auth failure backoff and blocks for one route class
+async fn guard(
+    state: &AppState,
+    class: RouteClass,
+    request: Request<Body>,
+    next: Next,
+) -> Response {
+    let subject = format!("ip:{}", client_ip(&request));
+    if let Some(wait) = state.abuse.held_off(&subject).await {
+        return held_off_response(wait);
+    }
+
+    let budget = state
+        .abuse
+        .ip_limiter(class)
+        .acquire(&format!("{}:{}", class.as_str(), subject))
+        .await;
+    if !budget.allowed {
+        if state.abuse.strike(&subject).await {
+            place_block(state, &subject, class, "kept going over the per-IP budget").await;
+        }
+        let mut response = StatusCode::TOO_MANY_REQUESTS.into_response();
+        budget.apply_headers(response.headers_mut());
+        return response;
+    }
+
+    let response = next.run(request).await;
+    let status = response.status();
+
+    if class.is_auth_failure(status) {
+        match state.abuse.record_auth_failure(&subject).await {
+            Some(Escalation::Block) => {
+                place_block(state, &subject, class, "repeated authentication failures").await;
+            }
+            Some(Escalation::Backoff {
+                lockout,
+                first: true,
+            }) => {
+                warn!(
+                    "{} failed authentication {} times, backing off {:?}",
+                    subject,
+                    FREE_AUTH_FAILURES + 1,
+                    lockout
+                );
+                let reason = format!("{} authentication failures", FREE_AUTH_FAILURES + 1);
+                if let Err(e) = record_event(
+                    &state.db,
+                    &subject,
+                    class,
+                    "auth_backoff",
+                    &reason,
+                    None,
+                    None,
+                )
+                .await
+                {
+                    error!("Failed to record auth backoff for {}: {}", subject, e);
+                }
+            }
+            _ => {}
+        }
+    } else if class == RouteClass::App && status.is_success() {
+        state.abuse.record_auth_success(&subject).await;
+    }
+
+    response
+}
+
+/// Anti-abuse layer for `/v1/auth`
+pub async fn auth_abuse_middleware(
+    State(state): State<Arc<AppState>>,
+    request: Request<Body>,
+    next: Next,
+) -> Response {
+    guard(&state, RouteClass::Auth, request, next).await
+}
+
+/// Anti-abuse layer for share pages and payment webhooks
+pub async fn public_abuse_middleware(
+    State(state): State<Arc<AppState>>,
+    request: Request<Body>,
+    next: Next,
+) -> Response {
+    guard(&state, RouteClass::Public, request, next).await
+}
+
+/// Anti-abuse layer for app routes (outside `auth_middleware`, so it sees
+/// rejected tokens)
+pub async fn app_abuse_middleware(
+    State(state): State<Arc<AppState>>,
+    request: Request<Body>,
+    next: Next,
+) -> Response {
+    guard(&state, RouteClass::App, request, next).await
+}
+
+/// Hourly per-user budget on app routes (must run after `auth_middleware`)
+pub async fn user_budget_middleware(
+    State(state): State<Arc<AppState>>,
+    request: Request<Body>,
+    next: Next,
+) -> Response {
+    let Some(auth) = request.extensions().get::<AuthContext>() else {
+        return next.run(request).await;
+    };
+    let subject = format!("user:{}", auth.user_id);
+    if let Some(wait) = state.abuse.held_off(&subject).await {
+        return held_off_response(wait);
+    }
+
+    let budget = state.abuse.user.acquire(&subject).await;
+    if !budget.allowed {
+        if state.abuse.strike(&subject).await {
+            place_block(
+                &state,
+                &subject,
+                RouteClass::App,
+                "kept going over the hourly per-user budget",
+            )
+            .await;
+        }
+        let mut response = StatusCode::TOO_MANY_REQUESTS.into_response();
+        budget.apply_headers(response.headers_mut());
+        return response;
+    }
+
+    next.run(request).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_auth_backoff_doubles_and_caps() {
+        assert_eq!(auth_backoff(0), None);
+        assert_eq!(auth_backoff(FREE_AUTH_FAILURES), None);
+        assert_eq!(
+            auth_backoff(FREE_AUTH_FAILURES + 1),
+            Some(Duration::from_secs(2))
+        );
+        assert_eq!(
+            auth_backoff(FREE_AUTH_FAILURES + 3),
+            Some(Duration::from_secs(8))
+        );
+        assert_eq!(
+            auth_backoff(FREE_AUTH_FAILURES + 40),
+            Some(Duration::from_secs(MAX_BACKOFF_SECS))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_failures_escalate_to_a_block() {
+        let guard = AbuseGuard::new();
+        let ip = "ip:203.0.113.7";
+
+        for _ in 0..FREE_AUTH_FAILURES {
+            assert_eq!(guard.record_auth_failure(ip).await, None);
+        }
+        assert!(guard.held_off(ip).await.is_none());
+
+        assert_eq!(
+            guard.record_auth_failure(ip).await,
+            Some(Escalation::Backoff {
+                lockout: Duration::from_secs(2),
+                first: true
+            })
+        );
+        assert!(guard.held_off(ip).await.is_some());
+        // Other callers aren't affected
+        assert!(guard.held_off("ip:198.51.100.1").await.is_none());
+
+        for _ in FREE_AUTH_FAILURES + 2..BLOCK_AFTER_AUTH_FAILURES {
+            assert!(matches!(
+                guard.record_auth_failure(ip).await,
+                Some(Escalation::Backoff { first: false, .. })
+            ));
+        }
+        assert_eq!(guard.record_auth_failure(ip).await, Some(Escalation::Block));
+
+        guard.block(ip, RouteClass::Auth, "test").await;
+        assert!(guard.held_off(ip).await.unwrap() > Duration::from_secs(BLOCK_SECS - 5));
+        assert_eq!(guard.active_blocks().await.len(), 1);
+        assert!(guard.unblock(ip).await);
+        assert!(guard.held_off(ip).await.is_none());
+        assert!(!guard.unblock(ip).await);
+    }
+
+    #[test]
+    fn test_auth_failure_statuses() {
+        assert!(RouteClass::Auth.is_auth_failure(StatusCode::FORBIDDEN));
+        assert!(RouteClass::App.is_auth_failure(StatusCode::UNAUTHORIZED));
+        // Entitlement and kill switch rejections aren't bad credentials
+        assert!(!RouteClass::App.is_auth_failure(StatusCode::FORBIDDEN));
+        assert!(!RouteClass::Auth.is_auth_failure(StatusCode::TOO_MANY_REQUESTS));
+    }
+}
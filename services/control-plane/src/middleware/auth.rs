@@ -23,6 +23,8 @@ pub struct AuthContext {
     pub user_id: String,
     pub email: Option<String>,
     pub is_admin: bool,
+    /// Cedros Login session behind a JWT (None for API keys)
+    pub session_id: Option<uuid::Uuid>,
 }
 
 /// Auth middleware that validates Cedros Login RS256 JWTs or API keys
@@ -73,6 +75,7 @@ fn authenticate_jwt(state: &AppState, token: &str) -> Result<AuthContext, Status
         user_id: claims.sub.to_string(),
         email: None,
         is_admin: claims.is_system_admin.unwrap_or(false),
+        session_id: Some(claims.sid),
     })
 }
 
@@ -123,6 +126,7 @@ async fn authenticate_api_key(state: &AppState, api_key: &str) -> Result<AuthCon
         user_id: user_id.to_string(),
         email,
         is_admin,
+        session_id: None,
     })
 }
 
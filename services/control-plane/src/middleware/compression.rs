@@ -0,0 +1,74 @@
+//! Payload size tracking for compressed bot sync routes
+//!
+//! Bot routes accept gzip/zstd request bodies (decoded by tower-http's
+//! `RequestDecompressionLayer`). These two middlewares sit on either side of
+//! that layer so we can see how much compression actually saves.
+
+use axum::{
+    body::Body,
+    extract::{Request, State},
+    http::{header, StatusCode},
+    middleware::Next,
+    response::Response,
+};
+use std::sync::Arc;
+
+use crate::{observability::metrics, AppState};
+
+/// Upper bound on a decoded bot request body (guards against decompression bombs)
+pub const MAX_DECODED_BODY_BYTES: usize = 10 * 1024 * 1024;
+
+/// Record the on-the-wire request size (apply OUTSIDE the decompression layer)
+pub async fn wire_size_middleware(
+    State(state): State<Arc<AppState>>,
+    request: Request<Body>,
+    next: Next,
+) -> Response {
+    let wire_bytes = request
+        .headers()
+        .get(header::CONTENT_LENGTH)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.parse::<u64>().ok());
+
+    if let Some(bytes) = wire_bytes {
+        state
+            .metrics
+            .histogram(metrics::BOT_PAYLOAD_WIRE_BYTES, bytes as f64)
+            .await;
+        if request.headers().contains_key(header::CONTENT_ENCODING) {
+            state
+                .metrics
+                .increment(metrics::BOT_PAYLOAD_COMPRESSED, 1)
+                .await;
+        }
+    }
+
+    next.run(request).await
+}
+
+/// Record the decoded request size (apply INSIDE the decompression layer)
+///
+/// Buffers the body, so it also enforces `MAX_DECODED_BODY_BYTES`. Bodies that
+/// exceed the limit or fail to decode are rejected with 400.
+pub async fn decoded_size_middleware(
+    State(state): State<Arc<AppState>>,
+    request: Request<Body>,
+    next: Next,
+) -> Result<Response, StatusCode> {
+    let (parts, body) = request.into_parts();
+
+    let bytes = axum::body::to_bytes(body, MAX_DECODED_BODY_BYTES)
+        .await
+        .map_err(|_| StatusCode::BAD_REQUEST)?;
+
+    if !bytes.is_empty() {
+        state
+            .metrics
+            .histogram(metrics::BOT_PAYLOAD_DECODED_BYTES, bytes.len() as f64)
+            .await;
+    }
+
+    Ok(next
+        .run(Request::from_parts(parts, Body::from(bytes)))
+        .await)
+}
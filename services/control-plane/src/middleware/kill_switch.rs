@@ -0,0 +1,111 @@
+//! Account kill switch flag
+//!
+//! Looks up whether the caller has engaged `POST /me/kill-switch` and makes
+//! the answer available to handlers as a [`KillSwitchStatus`] extension.
+//! While engaged, every response carries `X-Kill-Switch: engaged` and
+//! `X-Kill-Switch-Since` so clients can show it no matter which screen made
+//! the request.
+
+use axum::{
+    body::Body,
+    extract::{Request, State},
+    http::{HeaderName, HeaderValue, StatusCode},
+    middleware::Next,
+    response::Response,
+};
+use chrono::{DateTime, Utc};
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::{middleware::AuthContext, AppState};
+
+pub static X_KILL_SWITCH: HeaderName = HeaderName::from_static("x-kill-switch");
+pub static X_KILL_SWITCH_SINCE: HeaderName = HeaderName::from_static("x-kill-switch-since");
+
+/// Whether the caller's kill switch is engaged
+#[derive(Debug, Clone, Copy, Default)]
+pub struct KillSwitchStatus {
+    pub engaged_at: Option<DateTime<Utc>>,
+}
+
+impl KillSwitchStatus {
+    pub fn is_engaged(&self) -> bool {
+        self.engaged_at.is_some()
+    }
+
+    /// 409 for actions that would restart trading while engaged
+    pub fn ensure_released(&self) -> Result<(), (StatusCode, String)> {
+        match self.engaged_at {
+            Some(since) => Err((
+                StatusCode::CONFLICT,
+                format!(
+                    "Kill switch engaged since {}; release it before restarting bots",
+                    since.to_rfc3339()
+                ),
+            )),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Attach the caller's kill switch status (must run after `auth_middleware`)
+pub async fn kill_switch_middleware(
+    State(state): State<Arc<AppState>>,
+    mut request: Request<Body>,
+    next: Next,
+) -> Result<Response, StatusCode> {
+    let auth = request
+        .extensions()
+        .get::<AuthContext>()
+        .ok_or(StatusCode::UNAUTHORIZED)?;
+    let user_id = Uuid::parse_str(&auth.user_id).map_err(|_| StatusCode::BAD_REQUEST)?;
+
+    let engaged_at: Option<DateTime<Utc>> =
+        sqlx::query_scalar("SELECT engaged_at FROM kill_switches WHERE user_id = $1")
+            .bind(user_id)
+            .fetch_optional(&state.db)
+            .await
+            .map_err(|e| {
+                tracing::error!("Kill switch lookup failed: {}", e);
+                StatusCode::INTERNAL_SERVER_ERROR
+            })?;
+
+    request
+        .extensions_mut()
+        .insert(KillSwitchStatus { engaged_at });
+    let mut response = next.run(request).await;
+
+    // Re-read after the handler: engaging or releasing flips the flag on the
+    // very response that did it
+    let status = response
+        .extensions()
+        .get::<KillSwitchStatus>()
+        .copied()
+        .unwrap_or(KillSwitchStatus { engaged_at });
+    if let Some(since) = status.engaged_at {
+        let headers = response.headers_mut();
+        headers.insert(X_KILL_SWITCH.clone(), HeaderValue::from_static("engaged"));
+        if let Ok(value) = HeaderValue::from_str(&since.to_rfc3339()) {
+            headers.insert(X_KILL_SWITCH_SINCE.clone(), value);
+        }
+    }
+    Ok(response)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_engaged_switch_blocks_restarts() {
+        assert!(KillSwitchStatus::default().ensure_released().is_ok());
+
+        let engaged = KillSwitchStatus {
+            engaged_at: Some(Utc::now()),
+        };
+        assert!(engaged.is_engaged());
+        let (status, message) = engaged.ensure_released().unwrap_err();
+        assert_eq!(status, StatusCode::CONFLICT);
+        assert!(message.contains("Kill switch engaged"));
+    }
+}
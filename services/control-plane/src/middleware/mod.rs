@@ -1,14 +1,21 @@
 //! Middleware module for Trawling Traders control plane
 
+pub mod abuse;
 pub mod admin;
 pub mod auth;
+pub mod compression;
+pub mod kill_switch;
 pub mod rate_limit;
+pub mod request_id;
 pub mod subscription;
 
 // Re-export commonly used items
+pub use abuse::AbuseGuard;
 pub use admin::{admin_middleware, AdminContext};
 pub use auth::{auth_middleware, AuthContext};
+pub use kill_switch::{kill_switch_middleware, KillSwitchStatus};
 pub use rate_limit::rate_limit_middleware;
+pub use request_id::{request_id_middleware, RequestId};
 pub use subscription::{
     bot_create_limit_middleware, live_trading_guard_middleware, subscription_middleware,
     SubscriptionContext, SubscriptionTier,
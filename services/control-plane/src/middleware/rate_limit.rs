@@ -1,17 +1,21 @@
 //! Rate limiting middleware
 //!
 //! Prevents abuse by limiting request rates per user/bot.
+//!
+//! Every response that passes a limiter carries `X-RateLimit-Limit`,
+//! `X-RateLimit-Remaining` and `X-RateLimit-Reset` (unix seconds when the
+//! window resets); 429s add `Retry-After` in seconds.
 
 use axum::{
     body::Body,
     extract::{Request, State},
-    http::StatusCode,
+    http::{header, HeaderMap, HeaderName, HeaderValue, StatusCode},
     middleware::Next,
-    response::Response,
+    response::{IntoResponse, Response},
 };
 use std::collections::HashMap;
 use std::sync::Arc;
-use std::time::{Duration, Instant};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 use tokio::sync::RwLock;
 
 use crate::{middleware::AuthContext, AppState};
@@ -26,6 +30,60 @@ struct RateLimitBucket {
 /// Cleanup interval in seconds
 const CLEANUP_INTERVAL_SECS: u64 = 60;
 
+pub static X_RATELIMIT_LIMIT: HeaderName = HeaderName::from_static("x-ratelimit-limit");
+pub static X_RATELIMIT_REMAINING: HeaderName = HeaderName::from_static("x-ratelimit-remaining");
+pub static X_RATELIMIT_RESET: HeaderName = HeaderName::from_static("x-ratelimit-reset");
+
+/// Result of a limiter check, as reported to the client
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimitStatus {
+    pub allowed: bool,
+    pub limit: u32,
+    pub remaining: u32,
+    /// Time left in the current window
+    pub reset_after: Duration,
+}
+
+impl RateLimitStatus {
+    /// Whole seconds until the window resets, at least 1
+    fn reset_after_secs(&self) -> u64 {
+        let secs = self.reset_after.as_secs() + u64::from(self.reset_after.subsec_nanos() > 0);
+        secs.max(1)
+    }
+
+    /// Add the rate limit headers (and `Retry-After` when rejected)
+    pub fn apply_headers(&self, headers: &mut HeaderMap) {
+        let reset_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs()
+            + self.reset_after_secs();
+        headers.insert(X_RATELIMIT_LIMIT.clone(), HeaderValue::from(self.limit));
+        headers.insert(
+            X_RATELIMIT_REMAINING.clone(),
+            HeaderValue::from(self.remaining),
+        );
+        headers.insert(X_RATELIMIT_RESET.clone(), HeaderValue::from(reset_at));
+        if !self.allowed {
+            headers.insert(
+                header::RETRY_AFTER,
+                HeaderValue::from(self.reset_after_secs()),
+            );
+        }
+    }
+
+    /// The response for this status: `next` if allowed, otherwise a 429
+    async fn respond(self, request: Request<Body>, next: Next) -> Response {
+        let mut response = if self.allowed {
+            next.run(request).await
+        } else {
+            StatusCode::TOO_MANY_REQUESTS.into_response()
+        };
+        self.apply_headers(response.headers_mut());
+        response
+    }
+}
+
 /// In-memory rate limiter (per-process, not distributed)
 #[derive(Clone)]
 pub struct RateLimiter {
@@ -47,6 +105,11 @@ impl RateLimiter {
 
     /// Check if request is allowed
     pub async fn check(&self, key: &str) -> bool {
+        self.acquire(key).await.allowed
+    }
+
+    /// Count a request against `key` and report where its window stands
+    pub async fn acquire(&self, key: &str) -> RateLimitStatus {
         let mut buckets = self.buckets.write().await;
         let now = Instant::now();
         let window = Duration::from_secs(self.window_secs);
@@ -66,34 +129,25 @@ impl RateLimiter {
             *self.last_cleanup.write().await = now;
         }
 
-        match buckets.get_mut(key) {
-            Some(bucket) => {
-                // Check if window expired
-                if now.duration_since(bucket.window_start) >= window {
-                    // Reset window
-                    bucket.requests = 1;
-                    bucket.window_start = now;
-                    true
-                } else if bucket.requests < self.max_requests {
-                    // Increment and allow
-                    bucket.requests += 1;
-                    true
-                } else {
-                    // Rate limit exceeded
-                    false
-                }
-            }
-            None => {
-                // New bucket
-                buckets.insert(
-                    key.to_string(),
-                    RateLimitBucket {
-                        requests: 1,
-                        window_start: now,
-                    },
-                );
-                true
-            }
+        let bucket = buckets.entry(key.to_string()).or_insert(RateLimitBucket {
+            requests: 0,
+            window_start: now,
+        });
+        if now.duration_since(bucket.window_start) >= window {
+            // Window expired, start a new one
+            bucket.requests = 0;
+            bucket.window_start = now;
+        }
+        let allowed = bucket.requests < self.max_requests;
+        if allowed {
+            bucket.requests += 1;
+        }
+
+        RateLimitStatus {
+            allowed,
+            limit: self.max_requests,
+            remaining: self.max_requests - bucket.requests,
+            reset_after: window.saturating_sub(now.duration_since(bucket.window_start)),
         }
     }
 }
@@ -112,7 +166,7 @@ pub async fn rate_limit_middleware(
     State(state): State<Arc<AppState>>,
     request: Request<Body>,
     next: Next,
-) -> Result<Response, StatusCode> {
+) -> Response {
     // Get user ID from auth context or IP
     let key = if let Some(auth) = request.extensions().get::<AuthContext>() {
         format!("user:{}", auth.user_id)
@@ -122,12 +176,12 @@ pub async fn rate_limit_middleware(
         "anonymous".to_string()
     };
 
-    // Check rate limit
-    if !state.rate_limiter.check(&key).await {
-        return Err(StatusCode::TOO_MANY_REQUESTS);
-    }
-
-    Ok(next.run(request).await)
+    state
+        .rate_limiter
+        .acquire(&key)
+        .await
+        .respond(request, next)
+        .await
 }
 
 /// Bot-specific rate limiting (for bot-facing routes)
@@ -137,7 +191,7 @@ pub async fn bot_rate_limit_middleware(
     State(state): State<Arc<AppState>>,
     request: Request<Body>,
     next: Next,
-) -> Result<Response, StatusCode> {
+) -> Response {
     // Extract bot_id from path if available
     let bot_id = request
         .uri()
@@ -149,9 +203,41 @@ pub async fn bot_rate_limit_middleware(
     let key = format!("bot:{}", bot_id);
 
     // Check bot-specific rate limit (more permissive for heartbeats)
-    if !state.bot_rate_limiter.check(&key).await {
-        return Err(StatusCode::TOO_MANY_REQUESTS);
-    }
+    state
+        .bot_rate_limiter
+        .acquire(&key)
+        .await
+        .respond(request, next)
+        .await
+}
 
-    Ok(next.run(request).await)
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_status_and_headers() {
+        let limiter = RateLimiter::new(60, 2);
+        let first = limiter.acquire("bot:a").await;
+        assert!(first.allowed);
+        assert_eq!(first.remaining, 1);
+        assert!(limiter.acquire("bot:a").await.allowed);
+
+        let rejected = limiter.acquire("bot:a").await;
+        assert!(!rejected.allowed);
+        assert_eq!(rejected.remaining, 0);
+        assert!(rejected.reset_after <= Duration::from_secs(60));
+        // Other keys have their own window
+        assert!(limiter.check("bot:b").await);
+
+        let mut headers = HeaderMap::new();
+        rejected.apply_headers(&mut headers);
+        assert_eq!(headers[&X_RATELIMIT_LIMIT], "2");
+        assert_eq!(headers[&X_RATELIMIT_REMAINING], "0");
+        assert_eq!(headers[header::RETRY_AFTER], "60");
+
+        let mut headers = HeaderMap::new();
+        first.apply_headers(&mut headers);
+        assert!(!headers.contains_key(header::RETRY_AFTER));
+    }
 }
@@ -0,0 +1,157 @@
+//! Per-request IDs for matching client reports to server logs
+//!
+//! Every response carries an `X-Request-Id`. A well-formed ID sent by the
+//! client (or a proxy) is reused, otherwise one is generated. The ID is a
+//! field on the request's tracing span, so everything logged while handling
+//! it (including the `TraceLayer` span nested inside) can be grepped by it,
+//! and error bodies mention it so users can quote it in bug reports.
+
+use axum::{
+    body::Body,
+    extract::Request,
+    http::{header, HeaderName, HeaderValue},
+    middleware::Next,
+    response::Response,
+};
+use tracing::Instrument;
+
+pub static X_REQUEST_ID: HeaderName = HeaderName::from_static("x-request-id");
+
+/// Largest error body buffered to add the ID to
+const MAX_ERROR_BODY_BYTES: usize = 64 * 1024;
+
+/// The current request's ID, available to handlers as an extension
+#[derive(Debug, Clone)]
+pub struct RequestId(pub String);
+
+/// Accept client IDs that are short and header-safe; anything else is replaced
+fn incoming_id(request: &Request) -> Option<String> {
+    let id = request.headers().get(&X_REQUEST_ID)?.to_str().ok()?;
+    let valid = !id.is_empty()
+        && id.len() <= 128
+        && id
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || b"-_.:".contains(&b));
+    valid.then(|| id.to_string())
+}
+
+/// Assign a request ID, run the request inside a span carrying it, and log the outcome
+pub async fn request_id_middleware(mut request: Request, next: Next) -> Response {
+    let id = incoming_id(&request).unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+    let header_value = HeaderValue::from_str(&id).expect("request id is header-safe");
+    request
+        .headers_mut()
+        .insert(X_REQUEST_ID.clone(), header_value.clone());
+    request.extensions_mut().insert(RequestId(id.clone()));
+
+    let method = request.method().clone();
+    let path = request.uri().path().to_string();
+    let span = tracing::info_span!("request", request_id = %id);
+    let start = std::time::Instant::now();
+
+    let response = next.run(request).instrument(span.clone()).await;
+
+    let status = response.status();
+    let latency_ms = start.elapsed().as_millis() as u64;
+    span.in_scope(|| {
+        if status.is_server_error() {
+            tracing::error!(%method, %path, status = status.as_u16(), latency_ms, "request failed");
+        } else if status.is_client_error() {
+            tracing::warn!(%method, %path, status = status.as_u16(), latency_ms, "request rejected");
+        } else {
+            tracing::debug!(%method, %path, status = status.as_u16(), latency_ms, "request completed");
+        }
+    });
+
+    let mut response = if status.is_client_error() || status.is_server_error() {
+        with_request_id_in_body(response, &id).await
+    } else {
+        response
+    };
+    response
+        .headers_mut()
+        .insert(X_REQUEST_ID.clone(), header_value);
+    response
+}
+
+/// Add the request ID to an error body: a `request_id` field for JSON
+/// objects, a trailing note for plain text
+async fn with_request_id_in_body(response: Response, id: &str) -> Response {
+    if response.headers().contains_key(header::CONTENT_ENCODING) {
+        return response;
+    }
+    let is_json = response
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|ct| ct.starts_with("application/json"));
+
+    let (mut parts, body) = response.into_parts();
+    let Ok(bytes) = axum::body::to_bytes(body, MAX_ERROR_BODY_BYTES).await else {
+        // Oversized or broken body; drop it rather than send half of it
+        parts.headers.remove(header::CONTENT_LENGTH);
+        return Response::from_parts(parts, Body::empty());
+    };
+
+    let body = if is_json {
+        match serde_json::from_slice::<serde_json::Value>(&bytes) {
+            Ok(serde_json::Value::Object(mut obj)) => {
+                obj.insert("request_id".to_string(), id.into());
+                serde_json::to_vec(&obj).unwrap_or_else(|_| bytes.to_vec())
+            }
+            _ => bytes.to_vec(),
+        }
+    } else if bytes.is_empty() {
+        format!("request_id: {}", id).into_bytes()
+    } else {
+        format!("{} (request_id: {})", String::from_utf8_lossy(&bytes), id).into_bytes()
+    };
+
+    parts.headers.remove(header::CONTENT_LENGTH);
+    Response::from_parts(parts, Body::from(body))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{http::StatusCode, response::IntoResponse, Json};
+
+    async fn body_string(response: Response) -> String {
+        let bytes = axum::body::to_bytes(response.into_body(), 1024)
+            .await
+            .unwrap();
+        String::from_utf8(bytes.to_vec()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_request_id_added_to_error_bodies() {
+        let with_header = |value: &str| {
+            Request::get("/")
+                .header("x-request-id", value)
+                .body(Body::empty())
+                .unwrap()
+        };
+        assert_eq!(
+            incoming_id(&with_header("abc-123")).as_deref(),
+            Some("abc-123")
+        );
+        assert_eq!(incoming_id(&with_header("bad id\twith junk")), None);
+
+        let text = (StatusCode::NOT_FOUND, "Bot not found").into_response();
+        assert_eq!(
+            body_string(with_request_id_in_body(text, "req-1").await).await,
+            "Bot not found (request_id: req-1)"
+        );
+
+        let json = (
+            StatusCode::BAD_REQUEST,
+            Json(serde_json::json!({"error": "bad"})),
+        )
+            .into_response();
+        let body: serde_json::Value =
+            serde_json::from_str(&body_string(with_request_id_in_body(json, "req-2").await).await)
+                .unwrap();
+        assert_eq!(body["request_id"], "req-2");
+        assert_eq!(body["error"], "bad");
+    }
+}
@@ -43,6 +43,15 @@ impl SubscriptionTier {
         }
     }
 
+    /// Estimated LLM spend (USD) included each month, across all bots
+    pub fn included_llm_budget_usd(&self) -> rust_decimal::Decimal {
+        match self {
+            SubscriptionTier::Free => rust_decimal::Decimal::from(5),
+            SubscriptionTier::Pro => rust_decimal::Decimal::from(50),
+            SubscriptionTier::Enterprise => rust_decimal::Decimal::from(500),
+        }
+    }
+
     /// Features enabled per tier
     pub fn features(&self) -> Vec<&'static str> {
         match self {
@@ -92,50 +101,41 @@ pub async fn subscription_middleware(
 
     let user_id = Uuid::parse_str(&auth.user_id).map_err(|_| StatusCode::BAD_REQUEST)?;
 
-    // Fetch subscription from database
-    // Schema: subscriptions(status subscription_status, max_bots, current_period_end)
-    let subscription = sqlx::query_as::<_, (String, i32, chrono::DateTime<chrono::Utc>, i64)>(
-        r#"
-            SELECT s.status::text, s.max_bots, s.current_period_end, COUNT(b.id) as bot_count
-            FROM subscriptions s
-            LEFT JOIN bots b ON b.user_id = s.user_id AND b.status != 'destroying'
-            WHERE s.user_id = $1
-            AND s.current_period_end > NOW()
-            GROUP BY s.id
-            "#,
+    // Plan from the entitlement cache (refreshed by payment webhooks)
+    let entitlement = match state.entitlements.get(user_id) {
+        Some(entitlement) => entitlement,
+        None => {
+            let grace = state.settings.current().billing.grace_period();
+            let entitlement = crate::entitlements::load(&state.db, user_id, grace)
+                .await
+                .map_err(|e| {
+                    tracing::error!("Subscription query failed: {}", e);
+                    StatusCode::INTERNAL_SERVER_ERROR
+                })?;
+            state.entitlements.insert(user_id, entitlement.clone());
+            entitlement
+        }
+    };
+
+    let bot_count: i64 = sqlx::query_scalar(
+        "SELECT COUNT(*) FROM bots WHERE user_id = $1 AND status != 'destroying'",
     )
     .bind(user_id)
-    .fetch_optional(&state.db)
+    .fetch_one(&state.db)
     .await
     .map_err(|e| {
-        tracing::error!("Subscription query failed: {}", e);
+        tracing::error!("Bot count query failed: {}", e);
         StatusCode::INTERNAL_SERVER_ERROR
     })?;
 
-    let (tier, expires_at, bot_count) = match subscription {
-        Some((status, max_bots, period_end, count)) => {
-            let tier = if status == "active" {
-                if max_bots >= 20 {
-                    SubscriptionTier::Enterprise
-                } else {
-                    SubscriptionTier::Pro
-                }
-            } else {
-                SubscriptionTier::Free
-            };
-            (tier, Some(period_end), count as i32)
-        }
-        None => {
-            // No subscription found - treat as free tier
-            (SubscriptionTier::Free, None, 0)
-        }
-    };
-
     let sub_context = SubscriptionContext {
-        tier,
-        is_active: expires_at.map(|e| e > chrono::Utc::now()).unwrap_or(true),
-        expires_at,
-        bot_count,
+        tier: entitlement.tier,
+        is_active: entitlement
+            .expires_at
+            .map(|e| e > chrono::Utc::now())
+            .unwrap_or(true),
+        expires_at: entitlement.expires_at,
+        bot_count: bot_count as i32,
     };
 
     // Check if subscription is active
@@ -154,10 +154,19 @@ pub async fn subscription_middleware(
 ///
 /// Uses the bot_count cached in SubscriptionContext from subscription_middleware
 /// to avoid N+1 query. This middleware must run after subscription_middleware.
+/// Dry runs pass through so the handler can report the limit in its plan.
 pub async fn bot_create_limit_middleware(
     request: Request<Body>,
     next: Next,
 ) -> Result<Response, StatusCode> {
+    let dry_run =
+        axum::extract::Query::<crate::models::CreateBotParams>::try_from_uri(request.uri())
+            .map(|q| q.dry_run)
+            .unwrap_or(false);
+    if dry_run {
+        return Ok(next.run(request).await);
+    }
+
     let sub = request
         .extensions()
         .get::<SubscriptionContext>()
@@ -63,6 +63,29 @@ pub enum BotStatus {
     Destroying,
 }
 
+impl BotStatus {
+    /// Status the runner should act on: paused bots stop trading, everything
+    /// else runs
+    pub fn desired_runner_status(&self) -> &'static str {
+        match self {
+            BotStatus::Paused => "paused",
+            _ => "online",
+        }
+    }
+}
+
+/// How a bot's runner is hosted
+#[derive(Debug, Clone, Copy, Default, PartialEq, sqlx::Type, Serialize, Deserialize)]
+#[sqlx(type_name = "provisioning_mode", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum ProvisioningMode {
+    /// We create and manage a DigitalOcean droplet
+    #[default]
+    Droplet,
+    /// The owner runs bot-runner themselves (RUNNER_MODE=external)
+    Manual,
+}
+
 /// Config status for sync
 #[derive(Debug, Clone, Copy, PartialEq, sqlx::Type, Serialize, Deserialize)]
 #[sqlx(type_name = "config_status", rename_all = "snake_case")]
@@ -140,6 +163,155 @@ impl RiskCaps {
     }
 }
 
+/// Validate an optional trailing stop percentage (1-50)
+pub fn validate_trailing_stop(percent: Option<i32>) -> Result<(), String> {
+    match percent {
+        Some(p) if !(1..=50).contains(&p) => {
+            Err(format!("trailing_stop_percent must be 1-50, got {}", p))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Exposure cap for one asset
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AssetCap {
+    /// Symbol (e.g. "SOL") or mint address
+    pub asset: String,
+    /// Most USD the bot may hold in this asset
+    pub max_exposure_usd: Option<Decimal>,
+    /// Most of total equity the bot may hold in this asset
+    pub max_exposure_percent: Option<Decimal>,
+}
+
+/// Limits on top of the global risk caps
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct AssetLimits {
+    /// Most positions open at once
+    #[serde(default)]
+    pub max_open_positions: Option<i32>,
+    /// Minutes between trades on the same mint
+    #[serde(default)]
+    pub cooldown_minutes: Option<i32>,
+    /// Most of total equity in longs plus shorts
+    #[serde(default)]
+    pub max_gross_exposure_percent: Option<Decimal>,
+    /// Most of total equity that longs minus shorts may come to, either way
+    #[serde(default)]
+    pub max_net_exposure_percent: Option<Decimal>,
+    #[serde(default)]
+    pub per_asset: Vec<AssetCap>,
+}
+
+impl AssetLimits {
+    /// Validate limits are within acceptable ranges
+    pub fn validate(&self) -> Result<(), String> {
+        if let Some(max) = self.max_open_positions {
+            if !(1..=100).contains(&max) {
+                return Err(format!("max_open_positions must be 1-100, got {}", max));
+            }
+        }
+        if let Some(minutes) = self.cooldown_minutes {
+            if !(1..=1440).contains(&minutes) {
+                return Err(format!("cooldown_minutes must be 1-1440, got {}", minutes));
+            }
+        }
+        if let Some(percent) = self.max_gross_exposure_percent {
+            if percent < Decimal::ONE || percent > Decimal::from(200) {
+                return Err(format!(
+                    "max_gross_exposure_percent must be 1-200, got {}",
+                    percent
+                ));
+            }
+        }
+        if let Some(percent) = self.max_net_exposure_percent {
+            if percent < Decimal::ONE || percent > Decimal::from(100) {
+                return Err(format!(
+                    "max_net_exposure_percent must be 1-100, got {}",
+                    percent
+                ));
+            }
+        }
+        if self.per_asset.len() > 50 {
+            return Err("At most 50 per-asset caps are allowed".to_string());
+        }
+        let mut seen = std::collections::HashSet::new();
+        for cap in &self.per_asset {
+            let asset = cap.asset.trim();
+            if asset.is_empty() || asset.len() > 64 {
+                return Err("asset must be a symbol or mint address".to_string());
+            }
+            if !seen.insert(asset.to_uppercase()) {
+                return Err(format!("Duplicate cap for {}", asset));
+            }
+            if cap.max_exposure_usd.is_none() && cap.max_exposure_percent.is_none() {
+                return Err(format!("Cap for {} sets no limit", asset));
+            }
+            if let Some(usd) = cap.max_exposure_usd {
+                if usd < Decimal::ONE || usd > Decimal::from(1_000_000) {
+                    return Err(format!(
+                        "max_exposure_usd for {} must be 1-1000000, got {}",
+                        asset, usd
+                    ));
+                }
+            }
+            if let Some(percent) = cap.max_exposure_percent {
+                if percent < Decimal::ONE || percent > Decimal::from(100) {
+                    return Err(format!(
+                        "max_exposure_percent for {} must be 1-100, got {}",
+                        asset, percent
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// UTC hours and weekdays in which the bot may open new trades
+///
+/// `start` and `end` are `HH:MM`; a `start` later than `end` runs past
+/// midnight, and equal times (the default) mean the whole day. `days` are
+/// the days a window opens on; empty means every day.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TradingWindow {
+    #[serde(default)]
+    pub days: Vec<chrono::Weekday>,
+    #[serde(default)]
+    pub start: Option<String>,
+    #[serde(default)]
+    pub end: Option<String>,
+}
+
+fn parse_window_time(time: &str) -> Option<u32> {
+    let (hours, minutes) = time.split_once(':')?;
+    if hours.len() != 2 || minutes.len() != 2 {
+        return None;
+    }
+    let (hours, minutes): (u32, u32) = (hours.parse().ok()?, minutes.parse().ok()?);
+    (hours < 24 && minutes < 60).then_some(hours * 60 + minutes)
+}
+
+impl TradingWindow {
+    /// Validate times are `HH:MM` and days aren't repeated
+    pub fn validate(&self) -> Result<(), String> {
+        for (name, time) in [("start", &self.start), ("end", &self.end)] {
+            if let Some(time) = time {
+                if parse_window_time(time).is_none() {
+                    return Err(format!("{} must be HH:MM (UTC), got {:?}", name, time));
+                }
+            }
+        }
+        let mut seen = std::collections::HashSet::new();
+        for day in &self.days {
+            if !seen.insert(*day) {
+                return Err(format!("{} is listed twice", day));
+            }
+        }
+        Ok(())
+    }
+}
+
 /// User entity
 #[derive(Debug, Clone, FromRow, Serialize)]
 pub struct User {
@@ -173,6 +345,9 @@ pub struct Bot {
     /// When the bootstrap token was used (null = not yet used)
     #[serde(skip_serializing)]
     pub bootstrap_token_used_at: Option<DateTime<Utc>>,
+    pub provisioning: ProvisioningMode,
+    /// Whether the runner encrypts its state directory at rest
+    pub encrypt_state: bool,
 }
 
 /// Configuration version
@@ -195,6 +370,12 @@ pub struct ConfigVersion {
     pub llm_provider: String,
     pub encrypted_llm_api_key: String,
     pub created_at: DateTime<Utc>,
+    /// Sell once the price falls this far below its high since entry
+    pub trailing_stop_percent: Option<i32>,
+    /// `AssetLimits`, when any were set
+    pub asset_limits: Option<serde_json::Value>,
+    /// `TradingWindow`; NULL trades around the clock
+    pub trading_window: Option<serde_json::Value>,
 }
 
 /// OpenClaw configuration for a bot (LLM + channel integrations)
@@ -224,6 +405,8 @@ pub struct MetricDb {
     pub timestamp: DateTime<Utc>,
     pub equity: BigDecimal,
     pub pnl: BigDecimal,
+    pub synthetic: bool,
+    pub fees_usd: Option<BigDecimal>,
 }
 
 /// Metric API model (uses Decimal for business logic)
@@ -234,6 +417,10 @@ pub struct Metric {
     pub timestamp: DateTime<Utc>,
     pub equity: Decimal,
     pub pnl: Decimal,
+    /// Reconstructed by the backfill while the bot was offline
+    pub synthetic: bool,
+    /// Fees paid up to this point (None when the runner didn't report them)
+    pub fees_usd: Option<Decimal>,
 }
 
 impl From<MetricDb> for Metric {
@@ -257,6 +444,8 @@ impl From<MetricDb> for Metric {
                 );
                 Decimal::ZERO
             }),
+            synthetic: db.synthetic,
+            fees_usd: db.fees_usd.as_ref().and_then(try_decimal_from_bigdecimal),
         }
     }
 }
@@ -270,6 +459,33 @@ pub struct Event {
     pub message: String,
     pub metadata: Option<serde_json::Value>,
     pub created_at: DateTime<Utc>,
+    /// info, warning or error (see [`crate::events::SEVERITY_SQL`]); set by
+    /// queries that select it
+    #[sqlx(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub severity: Option<String>,
+}
+
+/// Stored alert (one row per unresolved alert key)
+#[derive(Debug, Clone, FromRow, Serialize)]
+pub struct Alert {
+    pub id: Uuid,
+    #[serde(skip)]
+    pub group_key: String,
+    pub alert_type: String,
+    pub bot_id: Option<Uuid>,
+    pub severity: String,
+    /// open, acknowledged or resolved
+    pub state: String,
+    pub title: String,
+    pub message: String,
+    /// Occurrences folded into this alert, including suppressed ones
+    pub count: i32,
+    pub suppressed: i32,
+    pub first_fired_at: DateTime<Utc>,
+    pub last_fired_at: DateTime<Utc>,
+    pub acknowledged_at: Option<DateTime<Utc>>,
+    pub resolved_at: Option<DateTime<Utc>>,
 }
 
 // Helper conversions between BigDecimal and Decimal
@@ -311,30 +527,382 @@ pub struct BotResponse {
     pub config: Option<ConfigVersion>,
 }
 
+#[derive(Debug, Default, Deserialize)]
+pub struct MetricsQuery {
+    /// 24h, 7d (default), 30d or all
+    pub range: Option<String>,
+    /// raw, 5m, 15m, 1h, 4h or 1d (default: the finest that fits the range)
+    pub resolution: Option<String>,
+}
+
 #[derive(Debug, Serialize)]
 pub struct MetricsResponse {
+    /// Newest first
     pub metrics: Vec<Metric>,
     pub range: String,
+    /// raw, or the bucket width the points average over
+    pub resolution: String,
+    /// Drawdowns open at any point in the range, for shading the curve
+    pub drawdowns: Vec<crate::drawdowns::DrawdownEpisode>,
+    /// Latest statistics the runner reported (None from older runners)
+    pub performance: Option<PerformanceStats>,
+}
+
+/// User-facing infrastructure cost estimate for a bot
+#[derive(Debug, Serialize)]
+pub struct InfraCostResponse {
+    pub bot_id: Uuid,
+    pub monthly_cost_usd: Decimal,
+    pub last_30d_cost_usd: Decimal,
+}
+
+/// Response for POST /bots/:id/credentials (manual bots only)
+#[derive(Debug, Serialize)]
+pub struct RunnerCredentialsResponse {
+    pub bot_id: Uuid,
+    /// Single-use; issuing new credentials invalidates the previous token
+    pub bootstrap_token: String,
+    pub control_plane_url: String,
+    /// Environment for `bot-runner` in external mode, one `KEY=value` per line
+    pub env: String,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct AlertsQuery {
+    /// Only alerts in this state (open, acknowledged, resolved)
+    pub state: Option<String>,
+    /// Max rows (default 100, capped at 500)
+    pub limit: Option<i64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AlertsResponse {
+    pub alerts: Vec<Alert>,
+}
+
+/// A user-registered webhook destination
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct WebhookEndpoint {
+    pub id: Uuid,
+    pub url: String,
+    pub enabled: bool,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateWebhookRequest {
+    /// Must be https
+    pub url: String,
+}
+
+/// A new endpoint with its signing secret (only shown once)
+#[derive(Debug, Serialize)]
+pub struct CreateWebhookResponse {
+    #[serde(flatten)]
+    pub endpoint: WebhookEndpoint,
+    pub secret: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct WebhooksResponse {
+    pub webhooks: Vec<WebhookEndpoint>,
+}
+
+/// One attempt to deliver a notification to an endpoint
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct WebhookDelivery {
+    pub id: Uuid,
+    /// Shared by every retry of the same notification
+    pub delivery_id: Uuid,
+    pub event_type: String,
+    pub attempt: i32,
+    /// None when no response came back (see `error`)
+    pub status_code: Option<i32>,
+    pub error: Option<String>,
+    pub duration_ms: i32,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct WebhookDeliveriesQuery {
+    /// Max rows (default 50, capped at 200)
+    pub limit: Option<i64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct WebhookDeliveriesResponse {
+    pub deliveries: Vec<WebhookDelivery>,
+}
+
+/// Body for `POST /me/kill-switch`
+#[derive(Debug, Default, Deserialize)]
+pub struct KillSwitchRequest {
+    /// Also sell every open position for USDC
+    #[serde(default)]
+    pub flatten: bool,
+    pub reason: Option<String>,
+}
+
+/// An engaged account kill switch
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct KillSwitch {
+    pub engaged_at: DateTime<Utc>,
+    pub reason: Option<String>,
+    pub flatten: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct KillSwitchResponse {
+    pub engaged: bool,
+    pub kill_switch: Option<KillSwitch>,
+    /// Bots paused by this request
+    pub bots_paused: usize,
+    /// Bots sent `cancel_intents` (and `flatten_positions` when flattening)
+    pub bots_notified: usize,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct EventsQuery {
+    /// `next_cursor` of the previous page
+    pub cursor: Option<String>,
+    /// Comma-separated event types
+    pub event_type: Option<String>,
+    /// info, warning or error
+    pub severity: Option<String>,
+    /// Events at or after this time
+    pub since: Option<DateTime<Utc>>,
+    /// Events before this time
+    pub until: Option<DateTime<Utc>>,
+    /// Max rows (default 100, capped at 500)
+    pub limit: Option<i64>,
 }
 
 #[derive(Debug, Serialize)]
 pub struct EventsResponse {
     pub events: Vec<Event>,
+    /// Pass as `cursor` for the next (older) page; None on the last page
     pub next_cursor: Option<String>,
 }
 
+/// A freshly created public share link (the token is only shown once)
+#[derive(Debug, Serialize)]
+pub struct ShareLinkResponse {
+    pub bot_id: Uuid,
+    pub token: String,
+    /// Path of the public page, relative to the API host
+    pub path: String,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Whether a bot currently has a live share link
+#[derive(Debug, Serialize)]
+pub struct ShareLinkStatus {
+    pub bot_id: Uuid,
+    pub active: bool,
+    pub created_at: Option<DateTime<Utc>>,
+}
+
+/// Equity relative to the start of the window, in percent
+#[derive(Debug, Serialize)]
+pub struct PublicEquityPoint {
+    pub timestamp: DateTime<Utc>,
+    pub return_pct: Decimal,
+}
+
+/// Sanitized performance shown on a public share page
+///
+/// Percentages and counts only: no balances, amounts, wallet or owner.
+#[derive(Debug, Serialize)]
+pub struct PublicPerformance {
+    pub name: String,
+    pub persona: Persona,
+    pub trading_mode: Option<TradingMode>,
+    pub since: DateTime<Utc>,
+    pub window_days: i64,
+    pub return_pct: Option<Decimal>,
+    pub equity_curve: Vec<PublicEquityPoint>,
+    pub trade_count: i64,
+    /// Closed (sold back to cash) positions in the window
+    pub closed_trades: i64,
+    /// Share of closed trades that made money (None when nothing closed)
+    pub win_rate: Option<f64>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AssetPerformanceQuery {
+    /// Only trades from the last N days (default: all history, max 365)
+    pub days: Option<i64>,
+}
+
+/// Performance of one traded mint, from the trade ledger
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct AssetPerformance {
+    pub mint: String,
+    pub symbol: Option<String>,
+    /// Confirmed fills (buys and sells)
+    pub trade_count: i64,
+    /// Sells back to cash that had a cost basis
+    pub closed_trades: i64,
+    pub win_rate: Option<f64>,
+    pub realized_pnl_usd: Decimal,
+    /// Open quantity marked to `mark_price_usd` (None without a price)
+    pub unrealized_pnl_usd: Option<Decimal>,
+    pub open_quantity: Decimal,
+    pub cost_basis_usd: Decimal,
+    pub mark_price_usd: Option<Decimal>,
+    /// Average time from buy to sell over closed trades
+    pub avg_holding_secs: Option<i64>,
+    /// Swap fees paid; already netted out of the amounts behind the PnL
+    pub fees_usd: Decimal,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AssetPerformanceResponse {
+    pub bot_id: Uuid,
+    /// Start of the window (None for all history)
+    pub since: Option<DateTime<Utc>>,
+    /// Best realized PnL first
+    pub assets: Vec<AssetPerformance>,
+}
+
+/// Hypothetical caps for `GET /bots/:id/what-if`; unset ones keep the
+/// bot's current value
+#[derive(Debug, Deserialize)]
+pub struct WhatIfQuery {
+    /// Replay the last N days (default 30, max 365)
+    pub days: Option<i64>,
+    pub max_position_size_percent: Option<i32>,
+    pub max_daily_loss_usd: Option<i32>,
+    pub max_trades_per_day: Option<i32>,
+}
+
+/// Results of one replay of the trade ledger
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct WhatIfOutcome {
+    /// Confirmed fills that went through
+    pub trade_count: i64,
+    /// Realized PnL net of fees, plus open quantity marked at the last fill
+    pub pnl_usd: Decimal,
+    /// Deepest fall of PnL below its running high
+    pub max_drawdown_usd: Decimal,
+    pub fees_usd: Decimal,
+}
+
+#[derive(Debug, Serialize)]
+pub struct WhatIfResponse {
+    pub bot_id: Uuid,
+    pub since: DateTime<Utc>,
+    pub actual_caps: RiskCaps,
+    pub hypothetical_caps: RiskCaps,
+    /// What happened under the bot's caps
+    pub actual: WhatIfOutcome,
+    /// The same trades replayed under the hypothetical caps
+    pub hypothetical: WhatIfOutcome,
+    /// Trades the hypothetical caps would have blocked, by cap
+    pub trades_blocked: std::collections::BTreeMap<String, i64>,
+    /// Intents blocked at the time by a cap the hypothetical loosens; they
+    /// never filled, so their outcome is unknown and they aren't replayed
+    pub unreplayed_intents: i64,
+    /// Hypothetical minus actual
+    pub pnl_delta_usd: Decimal,
+    pub max_drawdown_delta_usd: Decimal,
+}
+
+/// Body of POST /bots/:id/shadow-replay; left-out fields keep the bot's
+/// current config
+#[derive(Debug, Default, Deserialize)]
+pub struct ShadowReplayRequest {
+    /// Replay the last N days (default 30, max 60)
+    pub days: Option<i64>,
+    pub max_position_size_percent: Option<i32>,
+    pub max_daily_loss_usd: Option<i32>,
+    pub max_drawdown_percent: Option<i32>,
+    pub max_trades_per_day: Option<i32>,
+    /// Let this algorithm decide instead of replaying the journaled intents
+    pub algorithm_mode: Option<AlgorithmMode>,
+    pub strictness: Option<Strictness>,
+    pub slippage_bps: Option<u32>,
+    pub fee_bps: Option<u32>,
+}
+
+/// How the bot actually did over the replay window
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ShadowActual {
+    pub final_equity_usd: Decimal,
+    pub total_return_pct: Decimal,
+    pub max_drawdown_pct: Decimal,
+    /// Reported equity at each of the shadow curve's points
+    pub equity_curve: Vec<crate::algorithms::backtest::EquityPoint>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ShadowReplayResponse {
+    pub bot_id: Uuid,
+    pub since: DateTime<Utc>,
+    /// Journaled intents replayed (or, with an algorithm, decided around)
+    pub decisions: i64,
+    pub actual_caps: RiskCaps,
+    pub shadow_caps: RiskCaps,
+    /// Algorithm deciding in the shadow, when one was asked for
+    pub algorithm: Option<String>,
+    /// Equity the shadow portfolio started with, the bot's at the start
+    pub initial_equity_usd: Decimal,
+    pub actual: ShadowActual,
+    pub shadow: crate::shadow::ShadowRun,
+    /// Shadow minus actual
+    pub return_delta_pct: Decimal,
+}
+
+/// One bot on the dashboard, with its latest metric and recent events
+#[derive(Debug, Serialize)]
+pub struct DashboardBot {
+    #[serde(flatten)]
+    pub bot: Bot,
+    pub latest_metric: Option<Metric>,
+    /// Newest first
+    pub recent_events: Vec<Event>,
+}
+
+/// What the user's subscription allows, for rendering limits and upsells
+#[derive(Debug, Serialize)]
+pub struct DashboardEntitlements {
+    pub tier: crate::middleware::subscription::SubscriptionTier,
+    pub is_active: bool,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub max_bots: i32,
+    pub max_trades_per_day: i32,
+    pub features: Vec<&'static str>,
+    pub bot_count: i64,
+}
+
+/// Everything the dashboard needs on first load, read from one snapshot
+#[derive(Debug, Serialize)]
+pub struct DashboardResponse {
+    pub bots: Vec<DashboardBot>,
+    pub entitlements: DashboardEntitlements,
+    pub generated_at: DateTime<Utc>,
+}
+
 // Request types for API
 
 #[derive(Debug, Deserialize, validator::Validate)]
 pub struct CreateBotRequest {
     #[validate(length(min = 1, max = 100))]
     pub name: String,
-    pub persona: Persona,
+    /// Omit to use the owner's `default_persona` preference
+    #[serde(default)]
+    pub persona: Option<Persona>,
     pub algorithm_mode: AlgorithmMode,
     pub asset_focus: AssetFocus,
     pub strictness: Strictness,
     pub trading_mode: TradingMode,
     pub risk_caps: RiskCaps,
+    /// Trailing stop distance in percent; omit to disable
+    pub trailing_stop_percent: Option<i32>,
+    /// Per-asset exposure caps and max open positions
+    pub asset_limits: Option<AssetLimits>,
+    /// UTC session the bot may open trades in; omit to trade any time
+    pub trading_window: Option<TradingWindow>,
     #[validate(length(min = 1))]
     pub llm_provider: String,
     /// Optional LLM model (e.g., "gpt-4o", "claude-3-5-sonnet")
@@ -347,6 +915,44 @@ pub struct CreateBotRequest {
     pub telegram_enabled: bool,
     /// Telegram bot token from @BotFather (encrypted at rest)
     pub telegram_bot_token: Option<String>,
+    /// `manual` skips droplet creation for a user-run runner
+    #[serde(default)]
+    pub provisioning: ProvisioningMode,
+    /// Encrypt the runner's state files on disk with a per-bot key
+    #[serde(default)]
+    pub encrypt_state: bool,
+}
+
+/// Query params for POST /bots
+#[derive(Debug, Default, Deserialize)]
+pub struct CreateBotParams {
+    /// Validate and plan only; nothing is created
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+/// One pre-flight check in a dry-run provisioning plan
+#[derive(Debug, Serialize)]
+pub struct PlanCheck {
+    pub name: &'static str,
+    pub passed: bool,
+    pub detail: Option<String>,
+}
+
+/// Response for POST /bots?dry_run=true
+#[derive(Debug, Serialize)]
+pub struct ProvisioningPlan {
+    /// True when every check passed
+    pub would_succeed: bool,
+    pub checks: Vec<PlanCheck>,
+    pub region: String,
+    pub size: String,
+    pub image: String,
+    pub estimated_monthly_cost_usd: Option<Decimal>,
+    /// Bots already waiting to be provisioned
+    pub queue_position: i64,
+    pub estimated_ready_secs: i64,
+    pub estimated_ready_at: DateTime<Utc>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -358,6 +964,12 @@ pub struct BotConfigInput {
     pub strictness: Strictness,
     pub trading_mode: TradingMode,
     pub risk_caps: RiskCaps,
+    /// Trailing stop distance in percent; omit to disable
+    pub trailing_stop_percent: Option<i32>,
+    /// Per-asset exposure caps and max open positions
+    pub asset_limits: Option<AssetLimits>,
+    /// UTC session the bot may open trades in; omit to trade any time
+    pub trading_window: Option<TradingWindow>,
     pub llm_provider: String,
     /// Optional LLM model (e.g., "gpt-4o", "claude-3-5-sonnet")
     pub llm_model: Option<String>,
@@ -445,6 +1057,73 @@ pub struct BotConfigPayload {
     pub cron_jobs: Vec<CronJob>,
     pub trading_params: TradingParams,
     pub llm_config: LlmConfig,
+    pub funding: FundingRequirements,
+    pub exits: ExitRules,
+    pub asset_limits: AssetLimits,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub trading_window: Option<TradingWindow>,
+    pub openclaw: OpenClawPayload,
+}
+
+/// Agent-facing part of the config payload
+#[derive(Debug, Serialize)]
+pub struct OpenClawPayload {
+    /// Assets the bot may buy, resolved from its asset focus
+    pub asset_universe: Vec<UniverseAsset>,
+}
+
+/// One tradable asset as sent to the runner
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UniverseAsset {
+    pub symbol: String,
+    pub mint: String,
+    pub enabled: bool,
+    pub max_allocation_pct: Option<i32>,
+    pub decimals: Option<u8>,
+}
+
+/// Stop-loss and take-profit distances the runner enforces on every position
+///
+/// Fractions of the entry price (0.05 = 5%), taken from the persona's
+/// algorithm defaults. The trailing stop is a fraction of the highest price
+/// since entry, from the config's `trailing_stop_percent` (zero when off).
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ExitRules {
+    pub stop_loss_pct: Decimal,
+    pub take_profit_pct: Decimal,
+    pub trailing_stop_pct: Decimal,
+}
+
+/// Minimum wallet balances before a bot may trade live
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct FundingRequirements {
+    pub min_usdc: Decimal,
+    /// SOL for transaction fees and token account rent
+    pub min_sol: Decimal,
+}
+
+impl Default for FundingRequirements {
+    fn default() -> Self {
+        Self {
+            min_usdc: Decimal::from(10),
+            min_sol: Decimal::new(5, 2),
+        }
+    }
+}
+
+/// Response for GET /bots/:id/funding - what to send where before going live
+#[derive(Debug, Serialize)]
+pub struct FundingInstructionsResponse {
+    pub bot_id: Uuid,
+    /// Agent wallet to fund (None until the runner has reported it)
+    pub wallet_address: Option<String>,
+    pub required: FundingRequirements,
+    /// `funded`, `insufficient` or `unknown` (no check reported yet)
+    pub status: String,
+    /// Balances from the runner's last funding check
+    pub last_check: Option<serde_json::Value>,
+    pub last_checked_at: Option<DateTime<Utc>>,
+    pub instructions: String,
 }
 
 #[derive(Debug, Deserialize)]
@@ -459,6 +1138,20 @@ pub struct WalletReportRequest {
     pub wallet_address: String,
 }
 
+/// Setup phases run by `bot-runner bootstrap`, in order
+pub const BOOTSTRAP_PHASES: &[&str] = &["binaries", "wallet", "secrets", "services"];
+
+/// Phase outcomes: `skipped` means it was already satisfied on a re-run
+pub const BOOTSTRAP_STATUSES: &[&str] = &["ok", "skipped", "pending", "failed"];
+
+#[derive(Debug, Deserialize)]
+pub struct BootstrapPhaseReport {
+    pub phase: String,
+    pub status: String,
+    #[serde(default)]
+    pub detail: Option<String>,
+}
+
 #[derive(Debug, Deserialize)]
 pub struct HeartbeatRequest {
     pub status: String,
@@ -476,6 +1169,28 @@ pub struct MetricInput {
     pub timestamp: DateTime<Utc>,
     pub equity: Decimal,
     pub pnl: Decimal,
+    /// Cumulative swap and network fees (absent from older runners)
+    #[serde(default)]
+    pub fees_usd: Option<Decimal>,
+    /// Rolling statistics as of this point (absent from older runners)
+    #[serde(default)]
+    pub performance: Option<PerformanceStats>,
+}
+
+/// The runner's rolling performance statistics over a trailing window
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PerformanceStats {
+    pub window_days: i64,
+    /// Sells booked against a known cost basis
+    pub closed_trades: i64,
+    pub win_rate: Option<f64>,
+    pub avg_win_usd: Option<Decimal>,
+    /// Negative: the average losing trade's PnL
+    pub avg_loss_usd: Option<Decimal>,
+    /// Annualized, from daily equity returns
+    pub sharpe_ratio: Option<f64>,
+    /// Deepest peak-to-trough drop in the window, in percent
+    pub max_drawdown_pct: Decimal,
 }
 
 #[derive(Debug, Deserialize)]
@@ -486,16 +1201,77 @@ pub struct EventInput {
     pub timestamp: DateTime<Utc>,
 }
 
+/// Runner state summary reported on each sync
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SyncStateSummary {
+    pub runner_status: String,
+    pub config_version_id: Option<Uuid>,
+    pub equity_usd: Decimal,
+    pub cash_usd: Decimal,
+    pub positions_count: i32,
+    pub trades_today: i32,
+    pub last_plan_id: Option<Uuid>,
+    /// Runner's degradation mode (`normal`, `exit_only`, `paper_fallback`,
+    /// `hold` or `halt`); absent from older runners
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub degradation_mode: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub degraded_subsystems: Vec<String>,
+}
+
+/// Consolidated bot sync request: heartbeat + metrics + events + state
+#[derive(Debug, Deserialize)]
+pub struct BotSyncRequest {
+    pub status: String,
+    pub timestamp: DateTime<Utc>,
+    #[serde(default)]
+    pub metrics: Vec<MetricInput>,
+    #[serde(default)]
+    pub events: Vec<EventInput>,
+    /// Hash-chained decision journal entries since the last sync
+    #[serde(default)]
+    pub journal: Vec<crate::journal::JournalEntryInput>,
+    pub state: Option<SyncStateSummary>,
+    /// LLM requests and tokens since the last sync
+    #[serde(default)]
+    pub llm_usage: Option<crate::llm_usage::LlmUsageReport>,
+    /// Same on every retry of this payload, in any region (see `sync_receipts`)
+    #[serde(default)]
+    pub sync_id: Option<Uuid>,
+}
+
+/// Command queued for delivery to a bot
+#[derive(Debug, Clone, FromRow, Serialize)]
+pub struct BotCommand {
+    pub id: Uuid,
+    pub command: String,
+    pub args: serde_json::Value,
+}
+
 #[derive(Debug, Serialize)]
 pub struct BotSyncResponse {
     pub config_pending: bool,
     pub new_version_id: Option<Uuid>,
+    pub commands: Vec<BotCommand>,
+    pub events_accepted: usize,
+    /// Platform-wide halt; the runner stops making decisions while set
+    pub trading_halted: bool,
+    pub halt_reason: Option<String>,
+    pub journal_accepted: usize,
+    /// What the owner wants the runner doing: `paused` or `online`
+    pub desired_status: &'static str,
+    /// Region that answered, when the control plane runs in more than one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub region: Option<String>,
+    /// The payload was already stored by an earlier attempt
+    pub duplicate: bool,
 }
 
 #[derive(Debug, Serialize)]
 pub struct HeartbeatResponse {
     pub needs_config_update: bool,
     pub message: String,
+    pub desired_status: &'static str,
 }
 
 #[derive(Debug, Serialize)]
@@ -542,6 +1318,8 @@ pub struct ConfigEntry {
 pub struct ConfigListResponse {
     pub configs: Vec<ConfigEntry>,
     pub categories: Vec<String>,
+    /// The plain values in effect, typed, with defaults filled in
+    pub settings: crate::settings::Settings,
 }
 
 /// Request to update config values
@@ -154,11 +154,21 @@ pub mod metrics {
     pub const CONFIG_FETCH_DURATION_MS: &str = "config_fetch_duration_ms";
     pub const CONFIG_ACK_COUNT: &str = "config_ack_total";
     pub const CONFIG_MISMATCH_COUNT: &str = "config_mismatch_total";
+    pub const CONFIG_NOT_MODIFIED_COUNT: &str = "config_not_modified_total";
 
     // Heartbeat
     pub const HEARTBEAT_COUNT: &str = "heartbeat_total";
     pub const HEARTBEAT_DURATION_MS: &str = "heartbeat_duration_ms";
 
+    // Consolidated sync
+    pub const SYNC_COUNT: &str = "sync_total";
+    pub const SYNC_DURATION_MS: &str = "sync_duration_ms";
+
+    // Bot payload sizes (before/after request decompression)
+    pub const BOT_PAYLOAD_WIRE_BYTES: &str = "bot_payload_wire_bytes";
+    pub const BOT_PAYLOAD_DECODED_BYTES: &str = "bot_payload_decoded_bytes";
+    pub const BOT_PAYLOAD_COMPRESSED: &str = "bot_payload_compressed_total";
+
     // Wallet
     pub const WALLET_REPORT_COUNT: &str = "wallet_report_total";
     pub const WALLET_REPORT_ERRORS: &str = "wallet_report_errors_total";
@@ -0,0 +1,258 @@
+//! Per-asset performance breakdown from the trade ledger
+//!
+//! Replays a bot's `trade_confirmed` events per mint with average-cost
+//! accounting (the same rule as the public page's win rate): buys add to the
+//! mint's quantity, cost and quantity-weighted entry time, and each sell back
+//! to cash realizes proceeds minus the average cost of what was sold. Sells
+//! with no basis in the window (bought before it, or purged) count as trades
+//! but not as closes. Whatever is still held is marked at the given price,
+//! falling back to the last fill price.
+
+use chrono::{DateTime, Utc};
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+use std::collections::HashMap;
+use std::time::Duration;
+use tracing::warn;
+
+use crate::backfill::{is_cash, known_mint, LedgerTrade};
+use crate::models::AssetPerformance;
+
+/// Budget for fetching current prices before falling back to last fills
+const MARK_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// A ledger trade with the swap fee it paid
+#[derive(Debug, Clone, PartialEq)]
+pub struct FeeTrade {
+    pub trade: LedgerTrade,
+    pub fee_usd: Decimal,
+}
+
+impl FeeTrade {
+    /// Parse a `trade_confirmed` event; the fee is `fee_usd` when reported,
+    /// otherwise `fee_bps` of the cash side of the swap
+    pub fn from_event(timestamp: DateTime<Utc>, metadata: &serde_json::Value) -> Option<Self> {
+        let trade = LedgerTrade::from_event(timestamp, metadata)?;
+        let number = |key: &str| -> Option<Decimal> {
+            let value = metadata.get(key)?;
+            value
+                .as_str()
+                .and_then(|s| s.parse().ok())
+                .or_else(|| value.as_f64().and_then(Decimal::from_f64_retain))
+        };
+        let fee_usd = number("fee_usd").unwrap_or_else(|| {
+            let notional = if is_cash(&trade.input_mint) {
+                trade.in_amount
+            } else if is_cash(&trade.output_mint) {
+                trade.out_amount
+            } else {
+                Decimal::ZERO
+            };
+            number("fee_bps")
+                .map(|bps| notional * bps / Decimal::from(10_000))
+                .unwrap_or_default()
+        });
+        Some(Self { trade, fee_usd })
+    }
+}
+
+#[derive(Default)]
+struct AssetBook {
+    trade_count: i64,
+    closed: i64,
+    wins: i64,
+    realized: Decimal,
+    quantity: Decimal,
+    cost: Decimal,
+    /// Quantity-weighted entry time of what's held, as unix seconds
+    entry_secs: Decimal,
+    holding_secs: Vec<i64>,
+    fees: Decimal,
+    last_price: Option<Decimal>,
+}
+
+/// Per-mint results for `trades` (oldest first), best realized PnL first
+///
+/// `marks` maps mints to a current USD price for valuing open quantity.
+pub fn asset_breakdown(
+    trades: &[FeeTrade],
+    marks: &HashMap<String, Decimal>,
+    symbol_for: impl Fn(&str) -> Option<String>,
+) -> Vec<AssetPerformance> {
+    let mut books: HashMap<&str, AssetBook> = HashMap::new();
+
+    for FeeTrade { trade, fee_usd } in trades {
+        let ts = Decimal::from(trade.timestamp.timestamp());
+        if is_cash(&trade.input_mint) && !is_cash(&trade.output_mint) {
+            let book = books.entry(trade.output_mint.as_str()).or_default();
+            book.trade_count += 1;
+            book.fees += fee_usd;
+            if trade.out_amount.is_zero() {
+                continue;
+            }
+            let held = book.quantity + trade.out_amount;
+            book.entry_secs = (book.entry_secs * book.quantity + ts * trade.out_amount) / held;
+            book.quantity = held;
+            book.cost += trade.in_amount;
+            book.last_price = Some(trade.in_amount / trade.out_amount);
+        } else if !is_cash(&trade.input_mint) && is_cash(&trade.output_mint) {
+            let book = books.entry(trade.input_mint.as_str()).or_default();
+            book.trade_count += 1;
+            book.fees += fee_usd;
+            if !trade.in_amount.is_zero() {
+                book.last_price = Some(trade.out_amount / trade.in_amount);
+            }
+            if book.quantity.is_zero() {
+                continue;
+            }
+            let sold = trade.in_amount.min(book.quantity);
+            let sold_cost = book.cost * sold / book.quantity;
+            // Proceeds for the part with a basis only
+            let proceeds = trade.out_amount * sold / trade.in_amount;
+            book.quantity -= sold;
+            book.cost -= sold_cost;
+            book.realized += proceeds - sold_cost;
+            book.closed += 1;
+            if proceeds > sold_cost {
+                book.wins += 1;
+            }
+            book.holding_secs
+                .push((ts - book.entry_secs).to_i64().unwrap_or(0).max(0));
+            if book.quantity.is_zero() {
+                book.entry_secs = Decimal::ZERO;
+            }
+        }
+    }
+
+    let mut assets: Vec<AssetPerformance> = books
+        .into_iter()
+        .map(|(mint, book)| {
+            let mark = marks.get(mint).copied().or(book.last_price);
+            let unrealized = if book.quantity.is_zero() {
+                Some(Decimal::ZERO)
+            } else {
+                mark.map(|price| (book.quantity * price - book.cost).round_dp(2))
+            };
+            AssetPerformance {
+                mint: mint.to_string(),
+                symbol: symbol_for(mint),
+                trade_count: book.trade_count,
+                closed_trades: book.closed,
+                win_rate: (book.closed > 0).then(|| book.wins as f64 / book.closed as f64),
+                realized_pnl_usd: book.realized.round_dp(2),
+                unrealized_pnl_usd: unrealized,
+                open_quantity: book.quantity,
+                cost_basis_usd: book.cost.round_dp(2),
+                mark_price_usd: mark,
+                avg_holding_secs: (!book.holding_secs.is_empty()).then(|| {
+                    book.holding_secs.iter().sum::<i64>() / book.holding_secs.len() as i64
+                }),
+                fees_usd: book.fees.round_dp(4),
+            }
+        })
+        .collect();
+    assets.sort_by(|a, b| {
+        b.realized_pnl_usd
+            .cmp(&a.realized_pnl_usd)
+            .then_with(|| a.mint.cmp(&b.mint))
+    });
+    assets
+}
+
+/// Current USD prices for `mints` that have a known symbol
+///
+/// Best effort: anything not priced within [`MARK_TIMEOUT`] is left out.
+pub async fn live_marks(mints: &[String]) -> HashMap<String, Decimal> {
+    let client = data_retrieval::CoinGeckoClient::new(std::env::var("COINGECKO_API_KEY").ok());
+    let mut marks = HashMap::new();
+    let fetch = async {
+        for mint in mints {
+            let Some((symbol, _)) = known_mint(mint) else {
+                continue;
+            };
+            match client.get_price(symbol, "usd").await {
+                Ok(point) => {
+                    marks.insert(mint.clone(), point.price);
+                }
+                Err(e) => warn!("No current price for {}: {}", symbol, e),
+            }
+        }
+    };
+    if tokio::time::timeout(MARK_TIMEOUT, fetch).await.is_err() {
+        warn!("Timed out fetching current prices; using last fills for the rest");
+    }
+    marks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    const USDC: &str = "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v";
+    const SOL: &str = "So11111111111111111111111111111111111111112";
+    const BONK: &str = "DezXAZ8z7PnrnRJjz3wXBoRgixCa6xjnB7YaB1pPB263";
+
+    fn trade(hour: i64, input: &str, in_amount: i64, output: &str, out_amount: i64) -> FeeTrade {
+        FeeTrade {
+            trade: LedgerTrade {
+                timestamp: Utc.timestamp_opt(hour * 3600, 0).unwrap(),
+                input_mint: input.to_string(),
+                in_amount: Decimal::from(in_amount),
+                output_mint: output.to_string(),
+                out_amount: Decimal::from(out_amount),
+            },
+            fee_usd: Decimal::ONE,
+        }
+    }
+
+    #[test]
+    fn test_breakdown_per_asset() {
+        let trades = [
+            trade(0, USDC, 100, SOL, 1),
+            trade(2, USDC, 300, SOL, 1),
+            // Avg cost 200: a win then a loss, one hour and three hours held
+            trade(2, SOL, 1, USDC, 250),
+            trade(4, SOL, 1, USDC, 180),
+            // Memecoin: bought, sold at a loss, bought again and still held
+            trade(0, USDC, 100, BONK, 1_000_000),
+            trade(6, BONK, 1_000_000, USDC, 60),
+            trade(7, USDC, 50, BONK, 1_000_000),
+        ];
+        let marks = HashMap::from([(BONK.to_string(), Decimal::new(4, 5))]);
+        let assets = asset_breakdown(&trades, &marks, |m| (m == SOL).then(|| "SOL".into()));
+
+        let sol = &assets[0];
+        assert_eq!(sol.mint, SOL);
+        assert_eq!(sol.symbol.as_deref(), Some("SOL"));
+        assert_eq!((sol.trade_count, sol.closed_trades), (4, 2));
+        assert_eq!(sol.win_rate, Some(0.5));
+        assert_eq!(sol.realized_pnl_usd, Decimal::from(30));
+        assert_eq!(sol.unrealized_pnl_usd, Some(Decimal::ZERO));
+        assert_eq!(sol.avg_holding_secs, Some(2 * 3600));
+        assert_eq!(sol.fees_usd, Decimal::from(4));
+
+        let bonk = &assets[1];
+        assert_eq!(bonk.realized_pnl_usd, Decimal::from(-40));
+        assert_eq!(bonk.win_rate, Some(0.0));
+        assert_eq!(bonk.open_quantity, Decimal::from(1_000_000));
+        assert_eq!(bonk.unrealized_pnl_usd, Some(Decimal::from(-10)));
+        assert_eq!(bonk.avg_holding_secs, Some(6 * 3600));
+    }
+
+    #[test]
+    fn test_fee_from_event() {
+        let meta = serde_json::json!({
+            "input_mint": USDC, "output_mint": SOL,
+            "in_amount": 200_000_000u64, "out_amount": 1_000_000_000u64,
+            "fee_bps": 50,
+        });
+        let trade = FeeTrade::from_event(Utc::now(), &meta).unwrap();
+        assert_eq!(trade.fee_usd, Decimal::ONE);
+
+        let mut reported = meta.clone();
+        reported["fee_usd"] = serde_json::json!("0.42");
+        let trade = FeeTrade::from_event(Utc::now(), &reported).unwrap();
+        assert_eq!(trade.fee_usd, Decimal::new(42, 2));
+    }
+}
@@ -0,0 +1,258 @@
+//! Per-user preferences
+//!
+//! Choices a user would otherwise repeat for every bot: the region and
+//! persona new bots start with, where alerts are delivered, and the currency
+//! and timezone alerts should be rendered in. Stored in `user_preferences`;
+//! a user without a row gets the defaults.
+//!
+//! - `default_region` / `default_persona` fill in `POST /bots` when the
+//!   request leaves them out (see `handlers::bots::create_bot`)
+//! - `notification_channels` picks where the user's alerts go: their webhook
+//!   endpoints, and/or their account email through the email webhook
+//! - `display_currency` and `timezone` travel with every alert sent to the
+//!   user's endpoints, along with the local time the alert fired
+
+use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::models::Persona;
+
+/// Where a user's alerts are delivered
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NotificationChannel {
+    /// The user's registered webhook endpoints
+    Webhook,
+    /// The account's email address, through the platform email webhook
+    Email,
+}
+
+impl NotificationChannel {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            NotificationChannel::Webhook => "webhook",
+            NotificationChannel::Email => "email",
+        }
+    }
+
+    fn parse(s: &str) -> Option<Self> {
+        match s {
+            "webhook" => Some(NotificationChannel::Webhook),
+            "email" => Some(NotificationChannel::Email),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UserPreferences {
+    /// Region for new droplet bots; the platform's `droplet_region` when unset
+    pub default_region: Option<String>,
+    /// Persona for new bots that don't name one
+    pub default_persona: Option<Persona>,
+    pub notification_channels: Vec<NotificationChannel>,
+    /// ISO 4217 code alerts are rendered in
+    pub display_currency: String,
+    /// IANA timezone alerts are rendered in
+    pub timezone: String,
+}
+
+impl Default for UserPreferences {
+    fn default() -> Self {
+        Self {
+            default_region: None,
+            default_persona: None,
+            notification_channels: vec![NotificationChannel::Webhook],
+            display_currency: data_retrieval::fx::USD.to_string(),
+            timezone: "UTC".to_string(),
+        }
+    }
+}
+
+/// Body of PATCH /me/preferences; fields left out are unchanged
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct UpdatePreferencesRequest {
+    /// An empty string clears the default
+    pub default_region: Option<String>,
+    pub default_persona: Option<Persona>,
+    /// Clears the default persona
+    #[serde(default)]
+    pub clear_default_persona: bool,
+    pub notification_channels: Option<Vec<NotificationChannel>>,
+    pub display_currency: Option<String>,
+    pub timezone: Option<String>,
+}
+
+impl UserPreferences {
+    /// Apply a PATCH, normalizing what it sets
+    pub fn merge(mut self, update: UpdatePreferencesRequest) -> Result<Self, String> {
+        if let Some(region) = update.default_region {
+            let region = region.trim().to_ascii_lowercase();
+            if region.len() > 32
+                || !region
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '-')
+            {
+                return Err(format!("Invalid region '{}'", region));
+            }
+            self.default_region = Some(region).filter(|r| !r.is_empty());
+        }
+        if update.clear_default_persona {
+            self.default_persona = None;
+        } else if let Some(persona) = update.default_persona {
+            self.default_persona = Some(persona);
+        }
+        if let Some(channels) = update.notification_channels {
+            self.notification_channels.clear();
+            for channel in channels {
+                if !self.wants(channel) {
+                    self.notification_channels.push(channel);
+                }
+            }
+        }
+        if let Some(currency) = update.display_currency {
+            let currency = currency.trim().to_ascii_uppercase();
+            if currency != data_retrieval::fx::USD
+                && !data_retrieval::fx::supported_currencies().contains(&currency.as_str())
+            {
+                return Err(format!("Unsupported display currency '{}'", currency));
+            }
+            self.display_currency = currency;
+        }
+        if let Some(timezone) = update.timezone {
+            let tz: Tz = timezone
+                .trim()
+                .parse()
+                .map_err(|_| format!("Unknown timezone '{}'", timezone))?;
+            self.timezone = tz.name().to_string();
+        }
+        Ok(self)
+    }
+
+    pub fn wants(&self, channel: NotificationChannel) -> bool {
+        self.notification_channels.contains(&channel)
+    }
+
+    /// `at` in the user's timezone, RFC 3339
+    pub fn local_time(&self, at: DateTime<Utc>) -> String {
+        let tz: Tz = self.timezone.parse().unwrap_or(chrono_tz::UTC);
+        at.with_timezone(&tz).to_rfc3339()
+    }
+}
+
+/// A user's preferences, or the defaults if they've never set any
+pub async fn load(pool: &sqlx::PgPool, user_id: Uuid) -> Result<UserPreferences, sqlx::Error> {
+    type Row = (Option<String>, Option<Persona>, Vec<String>, String, String);
+    let row: Option<Row> = sqlx::query_as(
+        "SELECT default_region, default_persona, notification_channels, display_currency, timezone \
+         FROM user_preferences WHERE user_id = $1",
+    )
+    .bind(user_id)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(match row {
+        Some((default_region, default_persona, channels, display_currency, timezone)) => {
+            UserPreferences {
+                default_region,
+                default_persona,
+                notification_channels: channels
+                    .iter()
+                    .filter_map(|c| NotificationChannel::parse(c))
+                    .collect(),
+                display_currency,
+                timezone,
+            }
+        }
+        None => UserPreferences::default(),
+    })
+}
+
+pub async fn save(
+    pool: &sqlx::PgPool,
+    user_id: Uuid,
+    prefs: &UserPreferences,
+) -> Result<(), sqlx::Error> {
+    let channels: Vec<&str> = prefs
+        .notification_channels
+        .iter()
+        .map(|c| c.as_str())
+        .collect();
+    sqlx::query(
+        "INSERT INTO user_preferences \
+         (user_id, default_region, default_persona, notification_channels, display_currency, timezone) \
+         VALUES ($1, $2, $3, $4, $5, $6) \
+         ON CONFLICT (user_id) DO UPDATE SET default_region = $2, default_persona = $3, \
+         notification_channels = $4, display_currency = $5, timezone = $6, updated_at = NOW()",
+    )
+    .bind(user_id)
+    .bind(&prefs.default_region)
+    .bind(prefs.default_persona)
+    .bind(&channels)
+    .bind(&prefs.display_currency)
+    .bind(&prefs.timezone)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_merge_validates_and_normalizes() {
+        let prefs = UserPreferences::default()
+            .merge(UpdatePreferencesRequest {
+                default_region: Some(" SFO3 ".to_string()),
+                default_persona: Some(Persona::Tweaker),
+                display_currency: Some("eur".to_string()),
+                timezone: Some("Europe/Berlin".to_string()),
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(prefs.default_region.as_deref(), Some("sfo3"));
+        assert_eq!(prefs.default_persona, Some(Persona::Tweaker));
+        assert_eq!(prefs.display_currency, "EUR");
+        assert!(prefs.wants(NotificationChannel::Webhook));
+
+        // Summer in Berlin is UTC+2
+        let at = Utc.with_ymd_and_hms(2026, 7, 1, 12, 0, 0).unwrap();
+        assert_eq!(prefs.local_time(at), "2026-07-01T14:00:00+02:00");
+
+        // Left-out fields are kept; clearing is explicit
+        let cleared = prefs
+            .clone()
+            .merge(UpdatePreferencesRequest {
+                default_region: Some(String::new()),
+                clear_default_persona: true,
+                notification_channels: Some(vec![]),
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(cleared.default_region, None);
+        assert_eq!(cleared.default_persona, None);
+        assert_eq!(cleared.timezone, "Europe/Berlin");
+        assert!(!cleared.wants(NotificationChannel::Webhook));
+
+        for bad in [
+            UpdatePreferencesRequest {
+                timezone: Some("Mars/Olympus".to_string()),
+                ..Default::default()
+            },
+            UpdatePreferencesRequest {
+                display_currency: Some("XYZ".to_string()),
+                ..Default::default()
+            },
+            UpdatePreferencesRequest {
+                default_region: Some("nyc3; drop".to_string()),
+                ..Default::default()
+            },
+        ] {
+            assert!(prefs.clone().merge(bad).is_err());
+        }
+    }
+}
@@ -1,6 +1,7 @@
 //! Provisioning utilities for DigitalOcean droplet management
 //!
-//! Provides retry logic with exponential backoff, circuit breaker, and orphan cleanup.
+//! Provides retry logic with exponential backoff, circuit breaker, orphan cleanup,
+//! and reconciliation of tagged droplets against the bots table.
 
 use std::sync::Arc;
 use std::time::{Duration, Instant};
@@ -250,6 +251,7 @@ pub async fn find_orphaned_bots(
         FROM bots
         WHERE (
             status = 'provisioning'
+            AND provisioning = 'droplet'
             AND updated_at < NOW() - INTERVAL '1 second' * $1
         )
         OR (
@@ -336,7 +338,9 @@ pub async fn cleanup_orphaned_bot(
                         if let Ok(client) =
                             claw_spawn::infrastructure::DigitalOceanClient::new(do_token)
                         {
-                            let _ = client.destroy_droplet(did).await;
+                            if client.destroy_droplet(did).await.is_ok() {
+                                crate::droplets::record_droplet_destroyed(pool, did).await?;
+                            }
                         }
                     }
                 }
@@ -363,7 +367,9 @@ pub async fn cleanup_orphaned_bot(
                         if let Ok(client) =
                             claw_spawn::infrastructure::DigitalOceanClient::new(do_token)
                         {
-                            let _ = client.destroy_droplet(did).await;
+                            if client.destroy_droplet(did).await.is_ok() {
+                                crate::droplets::record_droplet_destroyed(pool, did).await?;
+                            }
                         }
                     }
                 }
@@ -387,13 +393,32 @@ pub async fn cleanup_orphaned_bot(
 }
 
 /// Spawn a background task to periodically clean up orphaned bots
-pub fn spawn_cleanup_task(pool: sqlx::PgPool, secrets: crate::SecretsManager) {
+///
+/// Every `RECONCILE_EVERY_TICKS` runs it also reconciles droplets against the
+/// provider (see `reconcile_droplets`).
+pub fn spawn_cleanup_task(
+    pool: sqlx::PgPool,
+    secrets: crate::SecretsManager,
+    alerts: crate::AlertManager,
+    webhooks: crate::WebhookNotifier,
+) {
     tokio::spawn(async move {
         let config = CleanupConfig::default();
+        let reconcile_config = ReconcileConfig::default();
         let mut interval = tokio::time::interval(Duration::from_secs(60)); // Run every minute
+        let mut ticks = 0u64;
 
         loop {
             interval.tick().await;
+            ticks += 1;
+
+            if ticks.is_multiple_of(RECONCILE_EVERY_TICKS) {
+                if let Err(e) =
+                    reconcile_droplets(&pool, &secrets, &alerts, &webhooks, &reconcile_config).await
+                {
+                    error!("Droplet reconciliation failed: {}", e);
+                }
+            }
 
             match find_orphaned_bots(&pool, &config).await {
                 Ok(orphans) => {
@@ -413,6 +438,378 @@ pub fn spawn_cleanup_task(pool: sqlx::PgPool, secrets: crate::SecretsManager) {
     });
 }
 
+// ==================== DIGITALOCEAN API ====================
+//
+// claw-spawn covers single-droplet operations; account, region and list
+// queries go straight to the REST API.
+
+const DO_API_BASE: &str = "https://api.digitalocean.com/v2";
+
+fn do_http_client() -> reqwest::Result<reqwest::Client> {
+    reqwest::Client::builder()
+        .timeout(Duration::from_secs(30))
+        .build()
+}
+
+/// GET a DigitalOcean API path and decode the JSON body
+async fn do_get<T: serde::de::DeserializeOwned>(token: &str, path: &str) -> anyhow::Result<T> {
+    let response = do_http_client()?
+        .get(format!("{}{}", DO_API_BASE, path))
+        .bearer_auth(token)
+        .send()
+        .await?;
+    if !response.status().is_success() {
+        anyhow::bail!("DigitalOcean GET {} failed: {}", path, response.status());
+    }
+    Ok(response.json().await?)
+}
+
+/// Droplet from the configured provisioning settings
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct DropletSpec {
+    pub region: String,
+    pub size: String,
+    pub image: String,
+}
+
+/// The droplet region/size/image from platform settings
+pub fn droplet_spec(settings: &crate::settings::Settings) -> DropletSpec {
+    DropletSpec {
+        region: settings.provisioning.droplet_region.clone(),
+        size: settings.provisioning.droplet_size.clone(),
+        image: settings.provisioning.droplet_image.clone(),
+    }
+}
+
+/// Droplet quota of the account behind a token
+#[derive(Debug, Clone)]
+pub struct AccountQuota {
+    pub status: String,
+    pub droplet_limit: i64,
+    pub droplets_in_use: i64,
+}
+
+/// Fetch account status and droplet usage (also proves the token works)
+pub async fn fetch_account_quota(token: &str) -> anyhow::Result<AccountQuota> {
+    #[derive(serde::Deserialize)]
+    struct Account {
+        status: String,
+        droplet_limit: i64,
+    }
+    #[derive(serde::Deserialize)]
+    struct AccountResponse {
+        account: Account,
+    }
+    #[derive(serde::Deserialize)]
+    struct Meta {
+        total: i64,
+    }
+    #[derive(serde::Deserialize)]
+    struct CountResponse {
+        meta: Meta,
+    }
+
+    let account: AccountResponse = do_get(token, "/account").await?;
+    let droplets: CountResponse = do_get(token, "/droplets?per_page=1").await?;
+
+    Ok(AccountQuota {
+        status: account.account.status,
+        droplet_limit: account.account.droplet_limit,
+        droplets_in_use: droplets.meta.total,
+    })
+}
+
+/// Whether `region` is accepting new droplets of `size`
+pub async fn region_offers_size(token: &str, region: &str, size: &str) -> anyhow::Result<bool> {
+    #[derive(serde::Deserialize)]
+    struct Region {
+        slug: String,
+        available: bool,
+        sizes: Vec<String>,
+    }
+    #[derive(serde::Deserialize)]
+    struct RegionsResponse {
+        regions: Vec<Region>,
+    }
+
+    let response: RegionsResponse = do_get(token, "/regions?per_page=200").await?;
+    Ok(response
+        .regions
+        .iter()
+        .any(|r| r.slug == region && r.available && r.sizes.iter().any(|s| s == size)))
+}
+
+// ==================== DROPLET RECONCILIATION ====================
+
+/// Tag applied to every droplet we provision
+pub const DROPLET_TAG: &str = "trawling-traders";
+/// Reconcile every 10 cleanup ticks (10 minutes)
+const RECONCILE_EVERY_TICKS: u64 = 10;
+
+/// Reconciliation configuration
+#[derive(Debug, Clone)]
+pub struct ReconcileConfig {
+    /// Unreferenced droplets younger than this are left alone (bot row may not be updated yet)
+    pub orphan_grace_secs: i64,
+    /// Upper bound on droplets destroyed per run, in case the bots query is wrong
+    pub max_destroys_per_run: usize,
+}
+
+impl Default for ReconcileConfig {
+    fn default() -> Self {
+        Self {
+            orphan_grace_secs: 900, // 15 minutes
+            max_destroys_per_run: 5,
+        }
+    }
+}
+
+/// A droplet as listed by the DigitalOcean API
+#[derive(Debug, Clone, serde::Deserialize)]
+pub struct TaggedDroplet {
+    pub id: i64,
+    pub name: String,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+#[derive(Debug, serde::Deserialize)]
+struct DropletListResponse {
+    droplets: Vec<TaggedDroplet>,
+    #[serde(default)]
+    links: DropletListLinks,
+}
+
+#[derive(Debug, Default, serde::Deserialize)]
+struct DropletListLinks {
+    #[serde(default)]
+    pages: Option<DropletListPages>,
+}
+
+#[derive(Debug, serde::Deserialize)]
+struct DropletListPages {
+    next: Option<String>,
+}
+
+/// List every droplet carrying `tag`, following pagination
+pub async fn list_tagged_droplets(token: &str, tag: &str) -> anyhow::Result<Vec<TaggedDroplet>> {
+    let client = do_http_client()?;
+
+    let mut droplets = Vec::new();
+    let mut url = Some(format!(
+        "{}/droplets?tag_name={}&per_page=200",
+        DO_API_BASE, tag
+    ));
+
+    while let Some(page_url) = url.take() {
+        let response = client.get(&page_url).bearer_auth(token).send().await?;
+        if !response.status().is_success() {
+            anyhow::bail!("DigitalOcean list droplets failed: {}", response.status());
+        }
+        let page: DropletListResponse = response.json().await?;
+        droplets.extend(page.droplets);
+        url = page.links.pages.and_then(|p| p.next);
+    }
+
+    Ok(droplets)
+}
+
+/// What a reconciliation run should do
+#[derive(Debug, Default, PartialEq)]
+pub struct ReconcilePlan {
+    /// Droplets no bot references: destroy
+    pub orphan_droplets: Vec<(i64, String)>,
+    /// Bots whose droplet is missing from the listing: verify, then flag
+    pub missing_droplets: Vec<(uuid::Uuid, i64)>,
+}
+
+/// Compare provider droplets with bot rows `(bot_id, status, droplet_id)`
+///
+/// Bots in 'destroying' are skipped on the bot side; the orphan cleanup above
+/// already owns them.
+pub fn plan_reconciliation(
+    droplets: &[TaggedDroplet],
+    bots: &[(uuid::Uuid, String, Option<i64>)],
+    now: chrono::DateTime<chrono::Utc>,
+    config: &ReconcileConfig,
+) -> ReconcilePlan {
+    use std::collections::HashSet;
+
+    let claimed: HashSet<i64> = bots.iter().filter_map(|(_, _, d)| *d).collect();
+    let listed: HashSet<i64> = droplets.iter().map(|d| d.id).collect();
+    let grace = chrono::Duration::seconds(config.orphan_grace_secs);
+
+    let orphan_droplets = droplets
+        .iter()
+        .filter(|d| !claimed.contains(&d.id) && now - d.created_at >= grace)
+        .map(|d| (d.id, d.name.clone()))
+        .collect();
+
+    let missing_droplets = bots
+        .iter()
+        .filter(|(_, status, _)| status != "destroying")
+        .filter_map(|(bot_id, _, droplet_id)| droplet_id.map(|d| (*bot_id, d)))
+        .filter(|(_, droplet_id)| !listed.contains(droplet_id))
+        .collect();
+
+    ReconcilePlan {
+        orphan_droplets,
+        missing_droplets,
+    }
+}
+
+/// Record a destructive provisioning action
+async fn record_provisioning_audit(
+    pool: &sqlx::PgPool,
+    action: &str,
+    bot_id: Option<uuid::Uuid>,
+    droplet_id: Option<i64>,
+    reason: &str,
+) {
+    if let Err(e) = sqlx::query(
+        "INSERT INTO provisioning_audit_log (action, bot_id, droplet_id, reason) VALUES ($1, $2, $3, $4)",
+    )
+    .bind(action)
+    .bind(bot_id)
+    .bind(droplet_id)
+    .bind(reason)
+    .execute(pool)
+    .await
+    {
+        error!("Failed to write provisioning audit ({}): {}", action, e);
+    }
+}
+
+/// Cross-check tagged droplets against the bots table in both directions
+///
+/// Destroys droplets no bot references and marks bots whose droplet is gone as
+/// 'error'. Every action is alerted and written to `provisioning_audit_log`.
+pub async fn reconcile_droplets(
+    pool: &sqlx::PgPool,
+    secrets: &crate::SecretsManager,
+    alerts: &crate::AlertManager,
+    webhooks: &crate::WebhookNotifier,
+    config: &ReconcileConfig,
+) -> anyhow::Result<()> {
+    use crate::alerting::{AlertSeverity, AlertType};
+    use crate::config::{self, keys};
+    use claw_spawn::infrastructure::{DigitalOceanClient, DigitalOceanError};
+
+    let Some(token) = config::get_config_decrypted(pool, secrets, keys::DIGITALOCEAN_TOKEN)
+        .await
+        .filter(|t| !t.is_empty())
+    else {
+        debug!("Skipping droplet reconciliation - no DigitalOcean token");
+        return Ok(());
+    };
+
+    // List first: if the provider call fails we must not treat every bot as missing
+    let droplets = list_tagged_droplets(&token, DROPLET_TAG).await?;
+    let bots = sqlx::query_as::<_, (uuid::Uuid, String, Option<i64>)>(
+        "SELECT id, status::text, droplet_id FROM bots WHERE droplet_id IS NOT NULL",
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let plan = plan_reconciliation(&droplets, &bots, chrono::Utc::now(), config);
+    if plan == ReconcilePlan::default() {
+        return Ok(());
+    }
+
+    let client = DigitalOceanClient::new(token)?;
+
+    if plan.orphan_droplets.len() > config.max_destroys_per_run {
+        warn!(
+            "Reconciliation found {} orphaned droplets; destroying only {} this run",
+            plan.orphan_droplets.len(),
+            config.max_destroys_per_run
+        );
+    }
+
+    for (droplet_id, name) in plan
+        .orphan_droplets
+        .into_iter()
+        .take(config.max_destroys_per_run)
+    {
+        match client.destroy_droplet(droplet_id).await {
+            Ok(()) | Err(DigitalOceanError::NotFound(_)) => {
+                info!("Destroyed orphaned droplet {} ({})", droplet_id, name);
+                crate::droplets::record_droplet_destroyed(pool, droplet_id).await?;
+                record_provisioning_audit(
+                    pool,
+                    "destroy_orphan_droplet",
+                    None,
+                    Some(droplet_id),
+                    &format!("Droplet '{}' tagged {} with no bot", name, DROPLET_TAG),
+                )
+                .await;
+                crate::webhook::fire_alert_with_webhook(
+                    alerts,
+                    webhooks,
+                    &AlertType::OrphanedDroplet { droplet_id, name },
+                    AlertSeverity::Warning,
+                    None,
+                )
+                .await;
+            }
+            Err(e) => error!("Failed to destroy orphaned droplet {}: {}", droplet_id, e),
+        }
+    }
+
+    for (bot_id, droplet_id) in plan.missing_droplets {
+        // The listing is only filtered by tag; confirm before flagging the bot
+        match client.get_droplet(droplet_id).await {
+            Err(DigitalOceanError::NotFound(_)) => {}
+            Ok(_) => continue,
+            Err(e) => {
+                warn!(
+                    "Could not verify droplet {} for bot {}: {}",
+                    droplet_id, bot_id, e
+                );
+                continue;
+            }
+        }
+
+        let owner: Option<uuid::Uuid> = sqlx::query_scalar(
+            "UPDATE bots SET status = 'error', droplet_id = NULL, updated_at = NOW() \
+             WHERE id = $1 AND droplet_id = $2 RETURNING user_id",
+        )
+        .bind(bot_id)
+        .bind(droplet_id)
+        .fetch_optional(pool)
+        .await?;
+        let Some(owner) = owner else {
+            continue;
+        };
+
+        warn!(
+            "Bot {} claimed missing droplet {}; marked error",
+            bot_id, droplet_id
+        );
+        crate::droplets::record_droplet_destroyed(pool, droplet_id).await?;
+        record_provisioning_audit(
+            pool,
+            "flag_missing_droplet",
+            Some(bot_id),
+            Some(droplet_id),
+            "Droplet not found at provider; bot set to error",
+        )
+        .await;
+        crate::webhook::fire_alert_with_webhook(
+            alerts,
+            webhooks,
+            &AlertType::MissingDroplet {
+                bot_id: bot_id.to_string(),
+                droplet_id,
+            },
+            AlertSeverity::Warning,
+            Some(&owner.to_string()),
+        )
+        .await;
+    }
+
+    Ok(())
+}
+
 // ==================== EVENT/METRICS CLEANUP ====================
 
 /// Event/metrics retention configuration
@@ -456,6 +853,14 @@ async fn cleanup_old_data(
 
     let metrics_deleted = metrics_result.rows_affected();
 
+    let receipts_deleted = crate::sync_receipts::prune(pool).await?;
+    if receipts_deleted > 0 {
+        debug!(
+            "Data retention cleanup: deleted {} sync receipts",
+            receipts_deleted
+        );
+    }
+
     if events_deleted > 0 || metrics_deleted > 0 {
         info!(
             "Data retention cleanup: deleted {} events (>{}d), {} metrics (>{}d)",
@@ -507,3 +912,39 @@ impl IdempotencyKey {
         &self.key
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn droplet(id: i64, age_secs: i64) -> TaggedDroplet {
+        TaggedDroplet {
+            id,
+            name: format!("trawler-{}", id),
+            created_at: Utc::now() - chrono::Duration::seconds(age_secs),
+        }
+    }
+
+    #[test]
+    fn test_plan_reconciliation_both_directions() {
+        let live_bot = uuid::Uuid::new_v4();
+        let ghost_bot = uuid::Uuid::new_v4();
+        let dying_bot = uuid::Uuid::new_v4();
+        let droplets = vec![
+            droplet(1, 3600), // claimed by live_bot
+            droplet(2, 3600), // orphan
+            droplet(3, 60),   // unclaimed but within grace
+        ];
+        let bots = vec![
+            (live_bot, "online".to_string(), Some(1)),
+            (ghost_bot, "online".to_string(), Some(99)),
+            (dying_bot, "destroying".to_string(), Some(98)),
+        ];
+
+        let plan = plan_reconciliation(&droplets, &bots, Utc::now(), &ReconcileConfig::default());
+
+        assert_eq!(plan.orphan_droplets, vec![(2, "trawler-2".to_string())]);
+        assert_eq!(plan.missing_droplets, vec![(ghost_bot, 99)]);
+    }
+}
@@ -0,0 +1,476 @@
+//! Daily and weekly performance rollups
+//!
+//! Every hour the rollup task folds each active bot's metrics and
+//! `trade_confirmed` events into one row per UTC day and ISO week (Monday
+//! start) in `performance_rollups`: equity at the start and end, PnL, trade
+//! count, closes and winners, fees and the max drawdown within the period.
+//! `GET /bots/:id/performance` serves these instead of replaying raw events,
+//! and they outlive the raw data's retention.
+//!
+//! A period starts from the last equity before it (its first point when
+//! there is none) and the drawdown is measured from there. Closes use the
+//! public page's average-cost rule over every trade event still retained, so
+//! a sell counts in the period it happened even when the buy was earlier.
+//! Each pass recomputes from the bot's last stored day (at least yesterday),
+//! so trades confirmed late are picked up; older rows are never rewritten.
+
+use bigdecimal::BigDecimal;
+use chrono::{DateTime, Datelike, Duration, NaiveDate, Utc};
+use rust_decimal::Decimal;
+use serde::Serialize;
+use tracing::{error, info};
+use uuid::Uuid;
+
+use crate::handlers::public::close_outcomes;
+use crate::models::{try_bigdecimal_from_decimal, try_decimal_from_bigdecimal};
+use crate::performance::FeeTrade;
+
+/// How often the rollup task runs
+const ROLLUP_INTERVAL_SECS: u64 = 3600;
+
+/// Bots with a metric this recent get their rollups refreshed
+const ACTIVE_WITHIN_HOURS: i64 = 48;
+
+/// Length of a rollup period
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Period {
+    Day,
+    /// ISO week, Monday to Sunday
+    Week,
+}
+
+impl Period {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "day" => Some(Self::Day),
+            "week" => Some(Self::Week),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Day => "day",
+            Self::Week => "week",
+        }
+    }
+
+    /// First day of the period `day` falls in
+    pub fn start_of(&self, day: NaiveDate) -> NaiveDate {
+        match self {
+            Self::Day => day,
+            Self::Week => day - Duration::days(day.weekday().num_days_from_monday().into()),
+        }
+    }
+
+    fn days(&self) -> i64 {
+        match self {
+            Self::Day => 1,
+            Self::Week => 7,
+        }
+    }
+}
+
+/// One bot's results over one period
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Rollup {
+    pub period_start: NaiveDate,
+    pub start_equity: Decimal,
+    pub end_equity: Decimal,
+    pub pnl_usd: Decimal,
+    /// Confirmed fills (buys and sells)
+    pub trade_count: i64,
+    /// Sells back to cash that had a cost basis
+    pub closed_trades: i64,
+    pub wins: i64,
+    /// None when nothing closed
+    pub win_rate: Option<f64>,
+    pub fees_usd: Decimal,
+    /// Deepest fall from a peak within the period, in percent of the peak
+    pub max_drawdown_pct: Decimal,
+}
+
+fn midnight(day: NaiveDate) -> DateTime<Utc> {
+    day.and_hms_opt(0, 0, 0).unwrap_or_default().and_utc()
+}
+
+/// Rollups of `period` from the one containing `from` through the last with
+/// data
+///
+/// `equity` and `trades` are oldest first. Periods before the first equity
+/// point, or with neither points nor trades, are left out.
+pub fn compute(
+    period: Period,
+    equity: &[(DateTime<Utc>, Decimal)],
+    trades: &[FeeTrade],
+    from: NaiveDate,
+) -> Vec<Rollup> {
+    let outcomes = close_outcomes(trades.iter().map(|t| &t.trade));
+    let last_at = equity
+        .last()
+        .map(|(at, _)| *at)
+        .max(trades.last().map(|t| t.trade.timestamp));
+    let Some(last_at) = last_at else {
+        return Vec::new();
+    };
+
+    let mut rollups = Vec::new();
+    let mut start = period.start_of(from);
+    while midnight(start) <= last_at {
+        let end = start + Duration::days(period.days());
+        let (lo, hi) = (midnight(start), midnight(end));
+        let before = equity
+            .iter()
+            .rev()
+            .find(|(at, _)| *at < lo)
+            .map(|(_, e)| *e);
+        let points: Vec<Decimal> = equity
+            .iter()
+            .filter(|(at, _)| *at >= lo && *at < hi)
+            .map(|(_, e)| *e)
+            .collect();
+        let in_period: Vec<usize> = (0..trades.len())
+            .filter(|&i| trades[i].trade.timestamp >= lo && trades[i].trade.timestamp < hi)
+            .collect();
+
+        let Some(start_equity) = before.or(points.first().copied()) else {
+            start = end;
+            continue;
+        };
+        if points.is_empty() && in_period.is_empty() {
+            start = end;
+            continue;
+        }
+        let end_equity = points.last().copied().unwrap_or(start_equity);
+
+        let mut peak = start_equity;
+        let mut max_drawdown = Decimal::ZERO;
+        for equity in &points {
+            peak = peak.max(*equity);
+            if peak > Decimal::ZERO {
+                max_drawdown = max_drawdown.max((peak - equity) / peak * Decimal::from(100));
+            }
+        }
+
+        let closed = in_period.iter().filter(|&&i| outcomes[i].is_some()).count() as i64;
+        let wins = in_period
+            .iter()
+            .filter(|&&i| outcomes[i] == Some(true))
+            .count() as i64;
+        rollups.push(Rollup {
+            period_start: start,
+            start_equity,
+            end_equity,
+            pnl_usd: (end_equity - start_equity).round_dp(2),
+            trade_count: in_period.len() as i64,
+            closed_trades: closed,
+            wins,
+            win_rate: (closed > 0).then(|| wins as f64 / closed as f64),
+            fees_usd: in_period
+                .iter()
+                .map(|&i| trades[i].fee_usd)
+                .sum::<Decimal>()
+                .round_dp(4),
+            max_drawdown_pct: max_drawdown.round_dp(2),
+        });
+        start = end;
+    }
+    rollups
+}
+
+#[derive(sqlx::FromRow)]
+struct RollupRow {
+    period_start: NaiveDate,
+    start_equity: BigDecimal,
+    end_equity: BigDecimal,
+    pnl_usd: BigDecimal,
+    trade_count: i32,
+    closed_trades: i32,
+    wins: i32,
+    fees_usd: BigDecimal,
+    max_drawdown_pct: BigDecimal,
+}
+
+impl RollupRow {
+    fn rollup(self) -> Option<Rollup> {
+        let closed = i64::from(self.closed_trades);
+        let wins = i64::from(self.wins);
+        Some(Rollup {
+            period_start: self.period_start,
+            start_equity: try_decimal_from_bigdecimal(&self.start_equity)?,
+            end_equity: try_decimal_from_bigdecimal(&self.end_equity)?,
+            pnl_usd: try_decimal_from_bigdecimal(&self.pnl_usd)?,
+            trade_count: self.trade_count.into(),
+            closed_trades: closed,
+            wins,
+            win_rate: (closed > 0).then(|| wins as f64 / closed as f64),
+            fees_usd: try_decimal_from_bigdecimal(&self.fees_usd)?,
+            max_drawdown_pct: try_decimal_from_bigdecimal(&self.max_drawdown_pct)?,
+        })
+    }
+}
+
+/// Stored rollups of `period` starting on or after `since`, oldest first
+pub async fn rollups_since(
+    pool: &sqlx::PgPool,
+    bot_id: Uuid,
+    period: Period,
+    since: NaiveDate,
+) -> Result<Vec<Rollup>, sqlx::Error> {
+    let rows: Vec<RollupRow> = sqlx::query_as(
+        "SELECT period_start, start_equity, end_equity, pnl_usd, trade_count, closed_trades, \
+         wins, fees_usd, max_drawdown_pct \
+         FROM performance_rollups \
+         WHERE bot_id = $1 AND period = $2 AND period_start >= $3 \
+         ORDER BY period_start",
+    )
+    .bind(bot_id)
+    .bind(period.as_str())
+    .bind(since)
+    .fetch_all(pool)
+    .await?;
+    Ok(rows.into_iter().filter_map(RollupRow::rollup).collect())
+}
+
+async fn store(
+    pool: &sqlx::PgPool,
+    bot_id: Uuid,
+    period: Period,
+    rollup: &Rollup,
+) -> anyhow::Result<()> {
+    let decimal = |d: &Decimal| -> anyhow::Result<BigDecimal> {
+        try_bigdecimal_from_decimal(d).ok_or_else(|| anyhow::anyhow!("unconvertible {}", d))
+    };
+    sqlx::query(
+        r#"
+        INSERT INTO performance_rollups
+            (bot_id, period, period_start, start_equity, end_equity, pnl_usd, trade_count,
+             closed_trades, wins, fees_usd, max_drawdown_pct)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
+        ON CONFLICT (bot_id, period, period_start) DO UPDATE SET
+            start_equity = EXCLUDED.start_equity,
+            end_equity = EXCLUDED.end_equity,
+            pnl_usd = EXCLUDED.pnl_usd,
+            trade_count = EXCLUDED.trade_count,
+            closed_trades = EXCLUDED.closed_trades,
+            wins = EXCLUDED.wins,
+            fees_usd = EXCLUDED.fees_usd,
+            max_drawdown_pct = EXCLUDED.max_drawdown_pct,
+            computed_at = NOW()
+        "#,
+    )
+    .bind(bot_id)
+    .bind(period.as_str())
+    .bind(rollup.period_start)
+    .bind(decimal(&rollup.start_equity)?)
+    .bind(decimal(&rollup.end_equity)?)
+    .bind(decimal(&rollup.pnl_usd)?)
+    .bind(rollup.trade_count as i32)
+    .bind(rollup.closed_trades as i32)
+    .bind(rollup.wins as i32)
+    .bind(decimal(&rollup.fees_usd)?)
+    .bind(decimal(&rollup.max_drawdown_pct)?)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Recompute a bot's recent rollups; returns how many were written
+pub async fn refresh_bot(pool: &sqlx::PgPool, bot_id: Uuid) -> anyhow::Result<usize> {
+    let yesterday = Utc::now().date_naive() - Duration::days(1);
+    let last_stored: Option<NaiveDate> = sqlx::query_scalar(
+        "SELECT MAX(period_start) FROM performance_rollups WHERE bot_id = $1 AND period = 'day'",
+    )
+    .bind(bot_id)
+    .fetch_one(pool)
+    .await?;
+    let from = match last_stored {
+        Some(day) => day.min(yesterday),
+        None => {
+            let first: Option<DateTime<Utc>> =
+                sqlx::query_scalar("SELECT MIN(timestamp) FROM metrics WHERE bot_id = $1")
+                    .bind(bot_id)
+                    .fetch_one(pool)
+                    .await?;
+            match first {
+                Some(first) => first.date_naive(),
+                None => return Ok(0),
+            }
+        }
+    };
+    let week_from = Period::Week.start_of(from);
+
+    // A day of slack gives the first period the equity it starts from
+    let rows: Vec<(DateTime<Utc>, BigDecimal)> = sqlx::query_as(
+        "SELECT timestamp, equity FROM metrics WHERE bot_id = $1 AND timestamp >= $2 \
+         ORDER BY timestamp",
+    )
+    .bind(bot_id)
+    .bind(midnight(week_from) - Duration::days(1))
+    .fetch_all(pool)
+    .await?;
+    let equity: Vec<(DateTime<Utc>, Decimal)> = rows
+        .iter()
+        .filter_map(|(at, e)| Some((*at, try_decimal_from_bigdecimal(e)?)))
+        .collect();
+
+    // The whole ledger, so sells find the buys they close
+    let trade_rows: Vec<(DateTime<Utc>, Option<serde_json::Value>)> = sqlx::query_as(
+        "SELECT created_at, metadata FROM events \
+         WHERE bot_id = $1 AND event_type::text = 'trade_confirmed' \
+         ORDER BY created_at",
+    )
+    .bind(bot_id)
+    .fetch_all(pool)
+    .await?;
+    let trades: Vec<FeeTrade> = trade_rows
+        .iter()
+        .filter_map(|(at, metadata)| FeeTrade::from_event(*at, metadata.as_ref()?))
+        .collect();
+
+    let mut written = 0;
+    for (period, from) in [(Period::Day, from), (Period::Week, week_from)] {
+        for rollup in compute(period, &equity, &trades, from) {
+            store(pool, bot_id, period, &rollup).await?;
+            written += 1;
+        }
+    }
+    Ok(written)
+}
+
+/// Spawn the hourly rollup of active bots' metrics and trades
+pub fn spawn_rollup_task(pool: sqlx::PgPool) {
+    tokio::spawn(async move {
+        let mut interval =
+            tokio::time::interval(std::time::Duration::from_secs(ROLLUP_INTERVAL_SECS));
+
+        loop {
+            interval.tick().await;
+
+            let since = Utc::now() - Duration::hours(ACTIVE_WITHIN_HOURS);
+            let bots: Vec<Uuid> = match sqlx::query_scalar(
+                "SELECT DISTINCT bot_id FROM metrics WHERE timestamp > $1",
+            )
+            .bind(since)
+            .fetch_all(&pool)
+            .await
+            {
+                Ok(bots) => bots,
+                Err(e) => {
+                    error!("Performance rollups: failed to list bots: {}", e);
+                    continue;
+                }
+            };
+
+            let mut written = 0;
+            for bot_id in bots {
+                match refresh_bot(&pool, bot_id).await {
+                    Ok(n) => written += n,
+                    Err(e) => error!("Performance rollup failed for bot {}: {}", bot_id, e),
+                }
+            }
+            if written > 0 {
+                info!("Performance rollups: wrote {} periods", written);
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backfill::LedgerTrade;
+    use chrono::TimeZone;
+
+    const USDC: &str = "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v";
+    const SOL: &str = "So11111111111111111111111111111111111111112";
+
+    fn at(day: u32, hour: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2026, 3, day, hour, 0, 0).unwrap()
+    }
+
+    fn trade(
+        ts: DateTime<Utc>,
+        input: &str,
+        in_amount: i64,
+        output: &str,
+        out_amount: i64,
+    ) -> FeeTrade {
+        FeeTrade {
+            trade: LedgerTrade {
+                timestamp: ts,
+                input_mint: input.to_string(),
+                in_amount: Decimal::from(in_amount),
+                output_mint: output.to_string(),
+                out_amount: Decimal::from(out_amount),
+            },
+            fee_usd: Decimal::new(5, 1),
+        }
+    }
+
+    #[test]
+    fn test_week_start() {
+        // 2026-03-11 is a Wednesday
+        let wednesday = NaiveDate::from_ymd_opt(2026, 3, 11).unwrap();
+        assert_eq!(
+            Period::Week.start_of(wednesday),
+            NaiveDate::from_ymd_opt(2026, 3, 9).unwrap()
+        );
+        assert_eq!(Period::Day.start_of(wednesday), wednesday);
+        assert_eq!(Period::parse("month"), None);
+    }
+
+    #[test]
+    fn test_daily_and_weekly_rollups() {
+        let equity = [
+            (at(9, 12), Decimal::from(1_000)),
+            (at(9, 18), Decimal::from(1_100)),
+            (at(10, 6), Decimal::from(990)),
+            (at(10, 20), Decimal::from(1_050)),
+            // Nothing on the 11th; the 12th starts from the 10th's close
+            (at(12, 9), Decimal::from(1_060)),
+        ];
+        let trades = [
+            trade(at(9, 13), USDC, 100, SOL, 1),
+            trade(at(10, 7), SOL, 1, USDC, 90),
+            trade(at(12, 10), USDC, 200, SOL, 2),
+        ];
+        let from = NaiveDate::from_ymd_opt(2026, 3, 8).unwrap();
+
+        let days = compute(Period::Day, &equity, &trades, from);
+        assert_eq!(
+            days.iter()
+                .map(|d| d.period_start.day())
+                .collect::<Vec<_>>(),
+            vec![9, 10, 12]
+        );
+        let (first, second, third) = (&days[0], &days[1], &days[2]);
+        assert_eq!(first.pnl_usd, Decimal::from(100));
+        assert_eq!(first.trade_count, 1);
+        assert_eq!(first.closed_trades, 0);
+        assert_eq!(first.max_drawdown_pct, Decimal::ZERO);
+        // 1100 -> 990 overnight, and the sell closed the 9th's buy at a loss
+        assert_eq!(second.start_equity, Decimal::from(1_100));
+        assert_eq!(second.pnl_usd, Decimal::from(-50));
+        assert_eq!(second.max_drawdown_pct, Decimal::from(10));
+        assert_eq!((second.closed_trades, second.wins), (1, 0));
+        assert_eq!(second.win_rate, Some(0.0));
+        assert_eq!(third.start_equity, Decimal::from(1_050));
+        assert_eq!(third.fees_usd, Decimal::new(5, 1));
+
+        let weeks = compute(Period::Week, &equity, &trades, from);
+        // The 8th is a Sunday, in the week before the data
+        assert_eq!(weeks.len(), 1);
+        let week = &weeks[0];
+        assert_eq!(
+            week.period_start,
+            NaiveDate::from_ymd_opt(2026, 3, 9).unwrap()
+        );
+        assert_eq!(week.pnl_usd, Decimal::from(60));
+        assert_eq!(week.trade_count, 3);
+        assert_eq!(week.fees_usd, Decimal::new(15, 1));
+        assert_eq!(week.max_drawdown_pct, Decimal::from(10));
+
+        assert!(compute(Period::Day, &[], &[], from).is_empty());
+    }
+}
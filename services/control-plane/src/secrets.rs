@@ -147,6 +147,20 @@ impl SecretsManager {
         Ok(BASE64.encode(&result))
     }
 
+    /// Derive a 32-byte key for `context` from the encryption key, as hex
+    ///
+    /// HMAC-SHA256 of the context under the encryption key, so the same
+    /// context always gets the same key and nothing needs storing. `None`
+    /// without an encryption key.
+    pub fn derive_key(&self, context: &str) -> Option<String> {
+        use hmac::{Hmac, Mac};
+
+        let key = self.encryption_key.as_ref()?;
+        let mut mac = <Hmac<sha2::Sha256> as Mac>::new_from_slice(key).ok()?;
+        mac.update(context.as_bytes());
+        Some(hex::encode(mac.finalize().into_bytes()))
+    }
+
     /// Check if encryption is active
     pub fn is_encryption_active(&self) -> bool {
         self.encryption_key.is_some()
@@ -246,5 +260,15 @@ mod tests {
         // Without key, decrypt returns input
         let decrypted = manager.decrypt(plaintext).unwrap();
         assert_eq!(decrypted, plaintext);
+        assert_eq!(manager.derive_key("state:bot"), None);
+    }
+
+    #[test]
+    fn test_derived_keys_are_stable_per_context() {
+        let manager = SecretsManager::with_key(TEST_KEY);
+        let key = manager.derive_key("state:a").unwrap();
+        assert_eq!(hex::decode(&key).unwrap().len(), 32);
+        assert_eq!(manager.derive_key("state:a"), Some(key.clone()));
+        assert_ne!(manager.derive_key("state:b"), Some(key));
     }
 }
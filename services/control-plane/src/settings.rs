@@ -0,0 +1,573 @@
+//! Typed platform settings
+//!
+//! `platform_config` stores every value as text. [`Settings`] is the typed
+//! view of its plain (non-encrypted) keys, grouped by category. Each key has
+//! an entry in [`SCHEMA`]: how it parses, what it accepts, and the default
+//! used while it's unset. Admin edits go through [`validate`] before they're
+//! written, so a bad value is refused with the reason rather than quietly
+//! replaced by the default when it's read.
+//!
+//! [`SettingsHandle`] holds the settings in effect. It is reloaded after each
+//! admin edit and every [`REFRESH_SECS`] (to pick up edits made through
+//! another instance); [`SettingsHandle::subscribe`] tells of changes.
+//! Encrypted keys are left out and read with `config::get_config_decrypted`.
+
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::Arc;
+
+use rust_decimal::Decimal;
+use serde::Serialize;
+use tokio::sync::watch;
+use tracing::{error, warn};
+
+use crate::config::{self, keys};
+use crate::AppState;
+
+/// How often settings are re-read from the database
+pub const REFRESH_SECS: u64 = 30;
+
+/// What a key accepts; an empty value always means "use the default"
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Kind {
+    Text,
+    /// An http(s) URL
+    Url,
+    /// Comma-separated http(s) URLs
+    UrlList,
+    /// `true` or `false`
+    Bool,
+    /// A whole number in `min..=max`
+    Int {
+        min: i64,
+        max: i64,
+    },
+    /// A decimal of zero or more
+    Amount,
+    /// JSON asset list (see `universe::parse_registry`)
+    AssetRegistry,
+    /// Encrypted at rest, not part of [`Settings`]
+    Secret,
+}
+
+/// One `platform_config` key
+#[derive(Debug, Clone, Copy)]
+pub struct Field {
+    pub key: &'static str,
+    pub kind: Kind,
+    pub default: &'static str,
+}
+
+const fn field(key: &'static str, kind: Kind, default: &'static str) -> Field {
+    Field { key, kind, default }
+}
+
+const MINUTES_PER_WEEK: i64 = 7 * 24 * 60;
+
+/// Every key the control plane reads
+pub const SCHEMA: &[Field] = &[
+    // Provisioning
+    field(keys::DIGITALOCEAN_TOKEN, Kind::Secret, ""),
+    field(keys::DROPLET_REGION, Kind::Text, "nyc3"),
+    field(keys::DROPLET_SIZE, Kind::Text, "s-1vcpu-2gb"),
+    field(keys::DROPLET_IMAGE, Kind::Text, "ubuntu-22-04-x64"),
+    field(keys::SHOW_INFRA_COST_TO_USERS, Kind::Bool, "false"),
+    field(
+        keys::STUCK_CONFIG_DEADLINE_MINUTES,
+        Kind::Int {
+            min: 1,
+            max: MINUTES_PER_WEEK,
+        },
+        "60",
+    ),
+    field(
+        keys::STUCK_PROVISIONING_DEADLINE_MINUTES,
+        Kind::Int {
+            min: 1,
+            max: MINUTES_PER_WEEK,
+        },
+        "120",
+    ),
+    field(
+        keys::STUCK_FAIL_AFTER_MINUTES,
+        Kind::Int {
+            min: 1,
+            max: MINUTES_PER_WEEK,
+        },
+        "720",
+    ),
+    // Trading
+    field(keys::JUPITER_API_KEY, Kind::Secret, ""),
+    field(
+        keys::SOLANA_RPC_URL,
+        Kind::Url,
+        "https://api.devnet.solana.com",
+    ),
+    field(
+        keys::DEFAULT_SLIPPAGE_BPS,
+        Kind::Int { min: 1, max: 5_000 },
+        "50",
+    ),
+    field(keys::PAPER_TRADING_DEFAULT, Kind::Bool, "true"),
+    field(keys::LIVE_MIN_USDC, Kind::Amount, "10"),
+    field(keys::LIVE_MIN_SOL, Kind::Amount, "0.05"),
+    field(keys::TRADING_HALTED, Kind::Bool, "false"),
+    field(keys::TRADING_HALT_REASON, Kind::Text, ""),
+    field(keys::ASSET_REGISTRY, Kind::AssetRegistry, ""),
+    // Services
+    field(
+        keys::CONTROL_PLANE_URL,
+        Kind::Url,
+        "https://api.trawlingtraders.com",
+    ),
+    field(keys::CONTROL_PLANE_FAILOVER_URLS, Kind::UrlList, ""),
+    field(
+        keys::DATA_RETRIEVAL_URL,
+        Kind::Url,
+        "https://data.trawling-traders.com",
+    ),
+    // Alerting
+    field(keys::DISCORD_WEBHOOK_URL, Kind::Secret, ""),
+    field(keys::EMAIL_WEBHOOK_URL, Kind::Secret, ""),
+    field(
+        keys::ALERT_EMAIL_TO,
+        Kind::Text,
+        "alerts@trawlingtraders.com",
+    ),
+    field(keys::ALERTS_ENABLED, Kind::Bool, "true"),
+    // Billing
+    field(keys::STRIPE_WEBHOOK_SECRET, Kind::Secret, ""),
+    field(keys::CEDROS_PAY_WEBHOOK_SECRET, Kind::Secret, ""),
+    field(
+        keys::PAYMENT_GRACE_PERIOD_HOURS,
+        Kind::Int { min: 0, max: 720 },
+        "72",
+    ),
+    // Object storage
+    field(keys::OBJECT_STORAGE_ENDPOINT, Kind::Url, ""),
+    field(keys::OBJECT_STORAGE_BUCKET, Kind::Text, ""),
+    field(keys::OBJECT_STORAGE_REGION, Kind::Text, "us-east-1"),
+    field(keys::OBJECT_STORAGE_ACCESS_KEY, Kind::Text, ""),
+    field(keys::OBJECT_STORAGE_SECRET_KEY, Kind::Secret, ""),
+    // Limits
+    field(keys::MAX_BOTS_PER_USER, Kind::Int { min: 1, max: 100 }, "5"),
+    field(
+        keys::MAX_CONCURRENT_PROVISIONS,
+        Kind::Int { min: 1, max: 50 },
+        "3",
+    ),
+    field(
+        keys::RATE_LIMIT_REQUESTS_PER_MINUTE,
+        Kind::Int {
+            min: 1,
+            max: 100_000,
+        },
+        "60",
+    ),
+];
+
+/// Schema entry for `key`
+pub fn schema_field(key: &str) -> Option<&'static Field> {
+    SCHEMA.iter().find(|f| f.key == key)
+}
+
+impl Kind {
+    /// Check a non-empty value
+    fn check(self, value: &str) -> Result<(), String> {
+        match self {
+            Kind::Text | Kind::Secret => Ok(()),
+            Kind::Url => check_url(value),
+            Kind::UrlList => value
+                .split(',')
+                .map(str::trim)
+                .filter(|u| !u.is_empty())
+                .try_for_each(check_url),
+            Kind::Bool => parse_bool(value)
+                .map(|_| ())
+                .ok_or_else(|| format!("expected true or false, got '{}'", value)),
+            Kind::Int { min, max } => match value.parse::<i64>() {
+                Ok(n) if (min..=max).contains(&n) => Ok(()),
+                Ok(n) => Err(format!("must be from {} to {}, got {}", min, max, n)),
+                Err(_) => Err(format!("expected a whole number, got '{}'", value)),
+            },
+            Kind::Amount => match Decimal::from_str(value) {
+                Ok(d) if !d.is_sign_negative() => Ok(()),
+                Ok(_) => Err(format!("must not be negative, got {}", value)),
+                Err(_) => Err(format!("expected a decimal number, got '{}'", value)),
+            },
+            Kind::AssetRegistry => crate::universe::parse_registry(value).map(|_| ()),
+        }
+    }
+}
+
+fn check_url(value: &str) -> Result<(), String> {
+    match reqwest::Url::parse(value) {
+        Ok(url) if matches!(url.scheme(), "http" | "https") => Ok(()),
+        Ok(url) => Err(format!(
+            "expected an http(s) URL, got scheme '{}'",
+            url.scheme()
+        )),
+        Err(e) => Err(format!("'{}' is not a URL: {}", value, e)),
+    }
+}
+
+fn parse_bool(value: &str) -> Option<bool> {
+    if value.eq_ignore_ascii_case("true") {
+        Some(true)
+    } else if value.eq_ignore_ascii_case("false") {
+        Some(false)
+    } else {
+        None
+    }
+}
+
+/// Check a value an admin wants to store under `key`
+///
+/// Keys outside the schema are accepted as they are.
+pub fn validate(key: &str, value: &str) -> Result<(), String> {
+    let value = value.trim();
+    match schema_field(key) {
+        Some(field) if !value.is_empty() => field.kind.check(value),
+        _ => Ok(()),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ProvisioningSettings {
+    pub droplet_region: String,
+    pub droplet_size: String,
+    pub droplet_image: String,
+    pub show_infra_cost_to_users: bool,
+    pub stuck_config_deadline_minutes: i64,
+    pub stuck_provisioning_deadline_minutes: i64,
+    pub stuck_fail_after_minutes: i64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TradingSettings {
+    pub solana_rpc_url: String,
+    pub default_slippage_bps: u32,
+    pub paper_trading_default: bool,
+    pub live_min_usdc: Decimal,
+    pub live_min_sol: Decimal,
+    pub trading_halted: bool,
+    pub trading_halt_reason: String,
+    /// Raw JSON; empty uses the built-in list
+    pub asset_registry: String,
+}
+
+impl TradingSettings {
+    /// Balances a bot's wallet must hold before live trading starts
+    pub fn funding_requirements(&self) -> crate::models::FundingRequirements {
+        crate::models::FundingRequirements {
+            min_usdc: self.live_min_usdc,
+            min_sol: self.live_min_sol,
+        }
+    }
+
+    /// The halt reason (possibly empty) while trading is halted
+    pub fn halt(&self) -> Option<String> {
+        self.trading_halted
+            .then(|| self.trading_halt_reason.clone())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ServiceSettings {
+    pub control_plane_url: String,
+    /// Comma-separated, as sent to runners
+    pub control_plane_failover_urls: String,
+    pub data_retrieval_url: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct AlertingSettings {
+    pub alert_email_to: String,
+    pub alerts_enabled: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct BillingSettings {
+    pub payment_grace_period_hours: i64,
+}
+
+impl BillingSettings {
+    /// How long a past-due subscription keeps its plan
+    pub fn grace_period(&self) -> chrono::Duration {
+        chrono::Duration::hours(self.payment_grace_period_hours)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct StorageSettings {
+    pub object_storage_endpoint: String,
+    pub object_storage_bucket: String,
+    pub object_storage_region: String,
+    pub object_storage_access_key: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct LimitSettings {
+    pub max_bots_per_user: u32,
+    pub max_concurrent_provisions: u32,
+    pub rate_limit_requests_per_minute: u32,
+}
+
+/// Plain platform settings, one group per `platform_config` category
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Settings {
+    pub provisioning: ProvisioningSettings,
+    pub trading: TradingSettings,
+    pub services: ServiceSettings,
+    pub alerting: AlertingSettings,
+    pub billing: BillingSettings,
+    pub storage: StorageSettings,
+    pub limits: LimitSettings,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self::from_values(&HashMap::new()).0
+    }
+}
+
+/// Stored values, resolved against the schema
+struct Values<'a> {
+    stored: &'a HashMap<String, String>,
+    /// Keys whose stored value was refused, and why
+    rejected: Vec<(&'static str, String)>,
+}
+
+impl Values<'_> {
+    /// Stored value if set and valid, else its environment fallback, else
+    /// the default
+    fn raw(&mut self, key: &'static str) -> String {
+        let field = schema_field(key).expect("key is in SCHEMA");
+        if let Some(value) = self.stored.get(key).map(|v| v.trim()) {
+            if !value.is_empty() {
+                match field.kind.check(value) {
+                    Ok(()) => return value.to_string(),
+                    Err(e) => self.rejected.push((key, e)),
+                }
+            }
+        }
+        config::env_fallback(key).unwrap_or_else(|| field.default.to_string())
+    }
+
+    fn text(&mut self, key: &'static str) -> String {
+        self.raw(key)
+    }
+
+    fn flag(&mut self, key: &'static str) -> bool {
+        parse_bool(&self.raw(key)).unwrap_or_default()
+    }
+
+    fn int(&mut self, key: &'static str) -> i64 {
+        self.raw(key).parse().unwrap_or_default()
+    }
+
+    fn amount(&mut self, key: &'static str) -> Decimal {
+        Decimal::from_str(&self.raw(key)).unwrap_or_default()
+    }
+}
+
+impl Settings {
+    /// Settings from stored `key -> value` pairs, with the keys whose value
+    /// was refused (and fell back to its default)
+    pub fn from_values(stored: &HashMap<String, String>) -> (Self, Vec<(&'static str, String)>) {
+        let mut v = Values {
+            stored,
+            rejected: Vec::new(),
+        };
+        let settings = Self {
+            provisioning: ProvisioningSettings {
+                droplet_region: v.text(keys::DROPLET_REGION),
+                droplet_size: v.text(keys::DROPLET_SIZE),
+                droplet_image: v.text(keys::DROPLET_IMAGE),
+                show_infra_cost_to_users: v.flag(keys::SHOW_INFRA_COST_TO_USERS),
+                stuck_config_deadline_minutes: v.int(keys::STUCK_CONFIG_DEADLINE_MINUTES),
+                stuck_provisioning_deadline_minutes: v
+                    .int(keys::STUCK_PROVISIONING_DEADLINE_MINUTES),
+                stuck_fail_after_minutes: v.int(keys::STUCK_FAIL_AFTER_MINUTES),
+            },
+            trading: TradingSettings {
+                solana_rpc_url: v.text(keys::SOLANA_RPC_URL),
+                default_slippage_bps: v.int(keys::DEFAULT_SLIPPAGE_BPS) as u32,
+                paper_trading_default: v.flag(keys::PAPER_TRADING_DEFAULT),
+                live_min_usdc: v.amount(keys::LIVE_MIN_USDC),
+                live_min_sol: v.amount(keys::LIVE_MIN_SOL),
+                trading_halted: v.flag(keys::TRADING_HALTED),
+                trading_halt_reason: v.text(keys::TRADING_HALT_REASON),
+                asset_registry: v.text(keys::ASSET_REGISTRY),
+            },
+            services: ServiceSettings {
+                control_plane_url: v.text(keys::CONTROL_PLANE_URL),
+                control_plane_failover_urls: v.text(keys::CONTROL_PLANE_FAILOVER_URLS),
+                data_retrieval_url: v.text(keys::DATA_RETRIEVAL_URL),
+            },
+            alerting: AlertingSettings {
+                alert_email_to: v.text(keys::ALERT_EMAIL_TO),
+                alerts_enabled: v.flag(keys::ALERTS_ENABLED),
+            },
+            billing: BillingSettings {
+                payment_grace_period_hours: v.int(keys::PAYMENT_GRACE_PERIOD_HOURS),
+            },
+            storage: StorageSettings {
+                object_storage_endpoint: v.text(keys::OBJECT_STORAGE_ENDPOINT),
+                object_storage_bucket: v.text(keys::OBJECT_STORAGE_BUCKET),
+                object_storage_region: v.text(keys::OBJECT_STORAGE_REGION),
+                object_storage_access_key: v.text(keys::OBJECT_STORAGE_ACCESS_KEY),
+            },
+            limits: LimitSettings {
+                max_bots_per_user: v.int(keys::MAX_BOTS_PER_USER) as u32,
+                max_concurrent_provisions: v.int(keys::MAX_CONCURRENT_PROVISIONS) as u32,
+                rate_limit_requests_per_minute: v.int(keys::RATE_LIMIT_REQUESTS_PER_MINUTE) as u32,
+            },
+        };
+        (settings, v.rejected)
+    }
+}
+
+/// The settings in effect, shared by the whole process
+#[derive(Clone)]
+pub struct SettingsHandle {
+    tx: Arc<watch::Sender<Arc<Settings>>>,
+}
+
+impl Default for SettingsHandle {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SettingsHandle {
+    /// Defaults (and environment fallbacks) until the first [`reload`](Self::reload)
+    pub fn new() -> Self {
+        let (tx, _) = watch::channel(Arc::new(Settings::default()));
+        Self { tx: Arc::new(tx) }
+    }
+
+    pub fn current(&self) -> Arc<Settings> {
+        self.tx.borrow().clone()
+    }
+
+    /// Receiver that wakes whenever the settings change
+    pub fn subscribe(&self) -> watch::Receiver<Arc<Settings>> {
+        self.tx.subscribe()
+    }
+
+    /// Re-read `platform_config`; returns whether anything changed
+    pub async fn reload(&self, pool: &sqlx::PgPool) -> Result<bool, sqlx::Error> {
+        let rows: Vec<(String, String)> =
+            sqlx::query_as("SELECT key, value FROM platform_config WHERE NOT encrypted")
+                .fetch_all(pool)
+                .await?;
+        let (settings, rejected) = Settings::from_values(&rows.into_iter().collect());
+        let changed = self.tx.send_if_modified(|current| {
+            if **current == settings {
+                return false;
+            }
+            *current = Arc::new(settings);
+            true
+        });
+        // Only on change, so a bad row isn't reported every refresh
+        if changed {
+            for (key, e) in rejected {
+                warn!("Ignoring platform config {}: {}", key, e);
+            }
+        }
+        Ok(changed)
+    }
+}
+
+/// Keep settings fresh and apply the ones that resize running state
+///
+/// `max_concurrent_provisions` resizes the droplet semaphore. Permits held
+/// by running provisions can't be taken back, so a decrease completes as
+/// they finish.
+pub fn spawn_refresh_task(state: Arc<AppState>) {
+    tokio::spawn(async move {
+        let mut changes = state.settings.subscribe();
+        let mut permits = state.settings.current().limits.max_concurrent_provisions as usize;
+        let mut interval = tokio::time::interval(std::time::Duration::from_secs(REFRESH_SECS));
+        loop {
+            tokio::select! {
+                _ = interval.tick() => {
+                    if let Err(e) = state.settings.reload(&state.db).await {
+                        error!("Failed to reload platform settings: {}", e);
+                    }
+                }
+                changed = changes.changed() => {
+                    if changed.is_err() {
+                        return;
+                    }
+                }
+            }
+
+            let wanted = state.settings.current().limits.max_concurrent_provisions as usize;
+            if wanted > permits {
+                state.droplet_semaphore.add_permits(wanted - permits);
+                permits = wanted;
+            } else if wanted < permits {
+                // Whatever is still held is retried on the next wake
+                permits -= state.droplet_semaphore.forget_permits(permits - wanted);
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_schema_defaults_are_valid() {
+        for field in SCHEMA {
+            assert_eq!(validate(field.key, field.default), Ok(()), "{}", field.key);
+        }
+        let (settings, rejected) = Settings::from_values(&HashMap::new());
+        assert!(rejected.is_empty());
+        assert_eq!(settings.limits.max_concurrent_provisions, 3);
+        assert_eq!(settings.trading.live_min_sol, Decimal::new(5, 2));
+        assert_eq!(settings.billing.grace_period(), chrono::Duration::hours(72));
+    }
+
+    #[test]
+    fn test_validate_explains_bad_values() {
+        assert_eq!(
+            validate(keys::MAX_CONCURRENT_PROVISIONS, "lots"),
+            Err("expected a whole number, got 'lots'".to_string())
+        );
+        assert_eq!(
+            validate(keys::MAX_CONCURRENT_PROVISIONS, "0"),
+            Err("must be from 1 to 50, got 0".to_string())
+        );
+        assert_eq!(
+            validate(keys::TRADING_HALTED, "yes"),
+            Err("expected true or false, got 'yes'".to_string())
+        );
+        assert!(validate(keys::LIVE_MIN_USDC, "-1").is_err());
+        assert!(validate(
+            keys::CONTROL_PLANE_FAILOVER_URLS,
+            "https://a.example, ftp://b"
+        )
+        .is_err());
+        assert_eq!(validate(keys::MAX_CONCURRENT_PROVISIONS, ""), Ok(()));
+        assert_eq!(validate("not_in_schema", "anything"), Ok(()));
+    }
+
+    #[test]
+    fn test_bad_stored_values_fall_back_to_defaults() {
+        let stored = HashMap::from([
+            (keys::MAX_BOTS_PER_USER.to_string(), "8".to_string()),
+            (keys::TRADING_HALTED.to_string(), "TRUE".to_string()),
+            (keys::LIVE_MIN_USDC.to_string(), "plenty".to_string()),
+        ]);
+        let (settings, rejected) = Settings::from_values(&stored);
+        assert_eq!(settings.limits.max_bots_per_user, 8);
+        assert_eq!(settings.trading.halt(), Some(String::new()));
+        assert_eq!(settings.trading.live_min_usdc, Decimal::from(10));
+        assert_eq!(rejected.len(), 1);
+        assert_eq!(rejected[0].0, keys::LIVE_MIN_USDC);
+    }
+}
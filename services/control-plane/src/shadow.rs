@@ -0,0 +1,610 @@
+//! Shadow replay of a live bot's decisions under another config
+//!
+//! Trades the decisions a bot journaled again in a paper portfolio with a
+//! different config, so a change can be judged on the bot's own history
+//! rather than a generic backtest. Every journaled intent is replayed,
+//! including the ones the bot's caps blocked at the time, so looser caps can
+//! be tried as well as tighter ones (the [`crate::whatif`] ledger replay only
+//! has fills to go on). With an algorithm set, the journaled intents are
+//! ignored and the algorithm decides instead at each plan's tick, for every
+//! asset priced.
+//!
+//! Fills are paper fills at the close of the last candle at or before the
+//! decision, moved against the trade by the slippage and charged the fee.
+//! Intents go through the runner's rails in its order (trades per UTC day,
+//! drawdown for buys, position size for buys, the day's realized loss). A
+//! sell spends its USD amount of the held asset at the fill price, capped at
+//! what's held. Ticks where the agent chose to do nothing aren't journaled,
+//! so an algorithm only gets to decide at ticks where the bot acted.
+
+use chrono::{DateTime, NaiveDate, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use uuid::Uuid;
+
+use crate::algorithms::backtest::EquityPoint;
+use crate::algorithms::{Algorithm, Candle, MarketContext, Position, SignalType};
+use crate::backfill::is_cash;
+use crate::models::RiskCaps;
+
+/// Candles handed to an algorithm per decision (the newest ones)
+const ALGORITHM_CANDLES: usize = 500;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Action {
+    Buy,
+    Sell,
+    Hold,
+}
+
+/// One journaled intent
+#[derive(Debug, Clone, PartialEq)]
+pub struct Decision {
+    pub timestamp: DateTime<Utc>,
+    /// Nil for exits the runner decided on its own
+    pub plan_id: Uuid,
+    pub action: Action,
+    /// Asset bought or sold
+    pub mint: String,
+    pub amount_usd: Decimal,
+    /// Whether the bot's own checks let it through
+    pub approved: bool,
+}
+
+#[derive(Deserialize)]
+struct JournalEntry {
+    plan_id: Uuid,
+    intent: JournalIntent,
+    validation: JournalValidation,
+    timestamp: DateTime<Utc>,
+}
+
+#[derive(Deserialize)]
+struct JournalIntent {
+    action: Action,
+    input_mint: String,
+    output_mint: String,
+    amount_usd: Decimal,
+}
+
+#[derive(Deserialize)]
+struct JournalValidation {
+    approved: bool,
+}
+
+impl Decision {
+    /// Read a stored `decision_journal` entry; None for holds and entries
+    /// that don't parse
+    pub fn from_entry(entry: &serde_json::Value) -> Option<Self> {
+        let entry: JournalEntry = serde_json::from_value(entry.clone()).ok()?;
+        let mint = match entry.intent.action {
+            Action::Buy => entry.intent.output_mint,
+            Action::Sell => entry.intent.input_mint,
+            Action::Hold => return None,
+        };
+        (!is_cash(&mint)).then_some(Self {
+            timestamp: entry.timestamp,
+            plan_id: entry.plan_id,
+            action: entry.intent.action,
+            mint,
+            amount_usd: entry.intent.amount_usd,
+            approved: entry.validation.approved,
+        })
+    }
+}
+
+/// The config decisions are replayed under
+pub struct ShadowConfig {
+    pub risk_caps: RiskCaps,
+    /// Decides in place of the journaled intents when set
+    pub algorithm: Option<Box<dyn Algorithm>>,
+    pub initial_cash: Decimal,
+    pub slippage_bps: u32,
+    pub fee_bps: u32,
+}
+
+/// One paper fill
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ShadowTrade {
+    pub timestamp: DateTime<Utc>,
+    pub mint: String,
+    pub action: Action,
+    /// Fill price after slippage
+    pub price: Decimal,
+    pub quantity: Decimal,
+    pub notional_usd: Decimal,
+    pub fee_usd: Decimal,
+    /// Sells only, net of both fills' fees
+    pub realized_pnl_usd: Option<Decimal>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct ShadowRun {
+    pub final_equity_usd: Decimal,
+    pub total_return_pct: Decimal,
+    pub max_drawdown_pct: Decimal,
+    pub fees_usd: Decimal,
+    pub trades: Vec<ShadowTrade>,
+    /// Intents the shadow config stopped, by `blocked_by` code
+    pub trades_blocked: BTreeMap<String, i64>,
+    /// Decisions with no candle at or before them to fill at
+    pub unpriced: i64,
+    /// Equity after each decision tick
+    pub equity_curve: Vec<EquityPoint>,
+}
+
+/// Hourly (or any) candles per mint, oldest first
+pub type PriceHistory = HashMap<String, Vec<Candle>>;
+
+/// Candles of `mint` up to and including `at`
+fn candles_until<'a>(prices: &'a PriceHistory, mint: &str, at: DateTime<Utc>) -> &'a [Candle] {
+    let Some(candles) = prices.get(mint) else {
+        return &[];
+    };
+    &candles[..candles.partition_point(|c| c.timestamp <= at)]
+}
+
+fn price_at(prices: &PriceHistory, mint: &str, at: DateTime<Utc>) -> Option<Decimal> {
+    candles_until(prices, mint, at)
+        .last()
+        .map(|c| c.close)
+        .filter(|p| *p > Decimal::ZERO)
+}
+
+#[derive(Default)]
+struct Book {
+    quantity: Decimal,
+    /// What was paid, fees included
+    cost: Decimal,
+    entry_price: Decimal,
+}
+
+struct Sim<'a> {
+    config: &'a ShadowConfig,
+    prices: &'a PriceHistory,
+    cash: Decimal,
+    books: HashMap<String, Book>,
+    run: ShadowRun,
+    high_water: Decimal,
+    day: Option<NaiveDate>,
+    day_trades: i32,
+    day_realized: Decimal,
+}
+
+impl Sim<'_> {
+    fn bps(bps: u32) -> Decimal {
+        Decimal::from(bps) / Decimal::from(10_000)
+    }
+
+    /// Cash plus holdings at their last price (at cost while unpriced)
+    fn equity(&self, at: DateTime<Utc>) -> Decimal {
+        self.cash
+            + self
+                .books
+                .iter()
+                .map(|(mint, book)| match price_at(self.prices, mint, at) {
+                    Some(price) => book.quantity * price,
+                    None => book.cost,
+                })
+                .sum::<Decimal>()
+    }
+
+    fn roll(&mut self, at: DateTime<Utc>) {
+        let day = at.date_naive();
+        if self.day != Some(day) {
+            self.day = Some(day);
+            self.day_trades = 0;
+            self.day_realized = Decimal::ZERO;
+        }
+    }
+
+    fn block(&mut self, code: &str) {
+        *self.run.trades_blocked.entry(code.to_string()).or_default() += 1;
+    }
+
+    /// The cap an intent would break, if any
+    fn check_caps(
+        &self,
+        action: Action,
+        notional: Decimal,
+        equity: Decimal,
+    ) -> Option<&'static str> {
+        let caps = &self.config.risk_caps;
+        if self.day_trades >= caps.max_trades_per_day {
+            return Some("max_trades_per_day");
+        }
+        if action == Action::Buy {
+            let drawdown = if self.high_water > Decimal::ZERO {
+                (self.high_water - equity) / self.high_water * Decimal::from(100)
+            } else {
+                Decimal::ZERO
+            };
+            if drawdown > Decimal::from(caps.max_drawdown_percent) {
+                return Some("max_drawdown_percent");
+            }
+            let max_position =
+                equity * Decimal::from(caps.max_position_size_percent) / Decimal::from(100);
+            if notional > max_position {
+                return Some("max_position_size_percent");
+            }
+        }
+        if self.day_realized < -Decimal::from(caps.max_daily_loss_usd) {
+            return Some("max_daily_loss_usd");
+        }
+        None
+    }
+
+    fn trade(&mut self, at: DateTime<Utc>, action: Action, mint: &str, notional: Decimal) {
+        if action == Action::Hold || notional <= Decimal::ZERO {
+            return;
+        }
+        let Some(price) = price_at(self.prices, mint, at) else {
+            self.run.unpriced += 1;
+            return;
+        };
+        self.roll(at);
+        let equity = self.equity(at);
+        if let Some(code) = self.check_caps(action, notional, equity) {
+            self.block(code);
+            return;
+        }
+        let slippage = Self::bps(self.config.slippage_bps);
+        let fee_rate = Self::bps(self.config.fee_bps);
+        match action {
+            Action::Buy => {
+                let notional = notional.min(self.cash / (Decimal::ONE + fee_rate));
+                if notional <= Decimal::ZERO {
+                    self.block("insufficient_cash");
+                    return;
+                }
+                let fill = price * (Decimal::ONE + slippage);
+                let fee = notional * fee_rate;
+                let quantity = notional / fill;
+                self.cash -= notional + fee;
+                let book = self.books.entry(mint.to_string()).or_default();
+                book.quantity += quantity;
+                book.cost += notional + fee;
+                book.entry_price = book.cost / book.quantity;
+                self.record(ShadowTrade {
+                    timestamp: at,
+                    mint: mint.to_string(),
+                    action,
+                    price: fill,
+                    quantity,
+                    notional_usd: notional,
+                    fee_usd: fee,
+                    realized_pnl_usd: None,
+                });
+            }
+            Action::Sell => {
+                let held = self.books.get(mint).map(|b| b.quantity).unwrap_or_default();
+                if held <= Decimal::ZERO {
+                    self.block("nothing_held");
+                    return;
+                }
+                let fill = price * (Decimal::ONE - slippage);
+                let quantity = (notional / price).min(held);
+                let proceeds = quantity * fill;
+                let fee = proceeds * fee_rate;
+                let book = self.books.get_mut(mint).expect("held above");
+                let cost = book.cost * quantity / book.quantity;
+                book.quantity -= quantity;
+                book.cost -= cost;
+                if book.quantity <= Decimal::ZERO {
+                    self.books.remove(mint);
+                }
+                let realized = proceeds - fee - cost;
+                self.cash += proceeds - fee;
+                self.day_realized += realized;
+                self.record(ShadowTrade {
+                    timestamp: at,
+                    mint: mint.to_string(),
+                    action,
+                    price: fill,
+                    quantity,
+                    notional_usd: proceeds,
+                    fee_usd: fee,
+                    realized_pnl_usd: Some(realized.round_dp(6)),
+                });
+            }
+            Action::Hold => {}
+        }
+    }
+
+    fn record(&mut self, trade: ShadowTrade) {
+        self.day_trades += 1;
+        self.run.fees_usd += trade.fee_usd;
+        self.run.trades.push(trade);
+    }
+
+    /// Let the algorithm decide for every priced asset at `at`
+    fn decide(&mut self, algorithm: &dyn Algorithm, at: DateTime<Utc>) {
+        let params = algorithm.parameters();
+        let mut mints: Vec<&String> = self.prices.keys().collect();
+        mints.sort();
+        for mint in mints {
+            let candles = candles_until(self.prices, mint, at);
+            let Some(last) = candles.last() else {
+                continue;
+            };
+            if candles.len() < 2 {
+                continue;
+            }
+            let equity = self.equity(at);
+            let position = self.books.get(mint.as_str()).map(|book| Position {
+                symbol: mint.clone(),
+                quantity: book.quantity,
+                entry_price: book.entry_price,
+                unrealized_pnl: book.quantity * last.close - book.cost,
+            });
+            let held = position.as_ref().map(|p| p.quantity * last.close);
+            let ctx = MarketContext {
+                symbol: mint.clone(),
+                current_price: last.close,
+                candles: candles[candles.len().saturating_sub(ALGORITHM_CANDLES)..].to_vec(),
+                position,
+                portfolio_value: equity,
+                risk_caps: self.config.risk_caps,
+                // Funding and sentiment aren't recorded with candle history
+                funding: None,
+                sentiment: None,
+            };
+            let signal = algorithm.generate_signal(&ctx);
+            if !signal.is_actionable(params.min_confidence) {
+                continue;
+            }
+            match (signal.signal_type, held) {
+                (SignalType::Buy, None) => self.trade(
+                    at,
+                    Action::Buy,
+                    mint,
+                    equity * signal.suggested_position_pct,
+                ),
+                (SignalType::Sell, Some(value)) => self.trade(at, Action::Sell, mint, value),
+                _ => {}
+            }
+        }
+    }
+}
+
+/// Replay `decisions` (oldest first) under `config`, filling at `prices`
+pub fn replay(decisions: &[Decision], prices: &PriceHistory, config: &ShadowConfig) -> ShadowRun {
+    let mut sim = Sim {
+        config,
+        prices,
+        cash: config.initial_cash,
+        books: HashMap::new(),
+        run: ShadowRun::default(),
+        high_water: config.initial_cash,
+        day: None,
+        day_trades: 0,
+        day_realized: Decimal::ZERO,
+    };
+    let mut max_drawdown = Decimal::ZERO;
+    let mut last_plan = None;
+
+    for (i, decision) in decisions.iter().enumerate() {
+        match &config.algorithm {
+            None => sim.trade(
+                decision.timestamp,
+                decision.action,
+                &decision.mint,
+                decision.amount_usd,
+            ),
+            // One decision per plan tick; the runner's own exits aren't ticks
+            Some(algorithm) => {
+                if !decision.plan_id.is_nil() && last_plan != Some(decision.plan_id) {
+                    sim.roll(decision.timestamp);
+                    sim.decide(algorithm.as_ref(), decision.timestamp);
+                }
+                last_plan = Some(decision.plan_id);
+            }
+        }
+
+        // A tick's intents share its timestamp; mark equity once they're all in
+        let tick_done = decisions
+            .get(i + 1)
+            .is_none_or(|next| next.timestamp != decision.timestamp);
+        if tick_done {
+            let equity = sim.equity(decision.timestamp);
+            sim.high_water = sim.high_water.max(equity);
+            if sim.high_water > Decimal::ZERO {
+                max_drawdown = max_drawdown
+                    .max((sim.high_water - equity) / sim.high_water * Decimal::from(100));
+            }
+            sim.run.equity_curve.push(EquityPoint {
+                timestamp: decision.timestamp,
+                equity: equity.round_dp(2),
+            });
+        }
+    }
+
+    let final_equity = sim
+        .run
+        .equity_curve
+        .last()
+        .map_or(config.initial_cash, |p| p.equity);
+    let mut run = sim.run;
+    run.final_equity_usd = final_equity;
+    run.total_return_pct = return_pct(config.initial_cash, final_equity);
+    run.max_drawdown_pct = max_drawdown.round_dp(4);
+    run.fees_usd = run.fees_usd.round_dp(6);
+    run
+}
+
+/// Percent change from `start` to `end`
+pub fn return_pct(start: Decimal, end: Decimal) -> Decimal {
+    if start > Decimal::ZERO {
+        ((end - start) / start * Decimal::from(100)).round_dp(4)
+    } else {
+        Decimal::ZERO
+    }
+}
+
+/// Deepest fall below the running high of `equity`, in percent
+pub fn max_drawdown_pct(equity: impl IntoIterator<Item = Decimal>) -> Decimal {
+    let mut high = Decimal::ZERO;
+    let mut deepest = Decimal::ZERO;
+    for value in equity {
+        high = high.max(value);
+        if high > Decimal::ZERO {
+            deepest = deepest.max((high - value) / high * Decimal::from(100));
+        }
+    }
+    deepest.round_dp(4)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithms::{AlgorithmParams, Signal};
+    use crate::models::AlgorithmMode;
+    use chrono::{Duration, TimeZone};
+
+    const SOL: &str = "So11111111111111111111111111111111111111112";
+
+    fn hour(h: i64) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2026, 3, 10, 0, 0, 0).unwrap() + Duration::hours(h)
+    }
+
+    fn prices(closes: &[i64]) -> PriceHistory {
+        let candles = closes
+            .iter()
+            .enumerate()
+            .map(|(i, close)| Candle {
+                timestamp: hour(i as i64),
+                open: Decimal::from(*close),
+                high: Decimal::from(*close),
+                low: Decimal::from(*close),
+                close: Decimal::from(*close),
+                volume: Decimal::ZERO,
+            })
+            .collect();
+        HashMap::from([(SOL.to_string(), candles)])
+    }
+
+    fn decision(h: i64, action: Action, amount: i64, approved: bool) -> Decision {
+        Decision {
+            timestamp: hour(h),
+            plan_id: Uuid::from_u128(h as u128 + 1),
+            action,
+            mint: SOL.to_string(),
+            amount_usd: Decimal::from(amount),
+            approved,
+        }
+    }
+
+    fn config(max_position_size_percent: i32) -> ShadowConfig {
+        ShadowConfig {
+            risk_caps: RiskCaps {
+                max_position_size_percent,
+                max_daily_loss_usd: 1_000,
+                max_drawdown_percent: 50,
+                max_trades_per_day: 10,
+            },
+            algorithm: None,
+            initial_cash: Decimal::from(1_000),
+            slippage_bps: 0,
+            fee_bps: 0,
+        }
+    }
+
+    #[test]
+    fn test_decision_from_journal_entry() {
+        let entry = serde_json::json!({
+            "intent_id": "00000000-0000-0000-0000-000000000001",
+            "plan_id": "00000000-0000-0000-0000-000000000002",
+            "plan_hash": "abc",
+            "intent": {
+                "intent_id": "00000000-0000-0000-0000-000000000001",
+                "action": "buy",
+                "input_mint": "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v",
+                "output_mint": SOL,
+                "amount_usd": "250.5",
+                "rationale": "trend",
+                "confidence": 0.8
+            },
+            "validation": {"approved": false, "blocked_by": "max_position_size_percent"},
+            "execution": null,
+            "timestamp": "2026-03-10T01:00:00Z"
+        });
+        let decision = Decision::from_entry(&entry).unwrap();
+        assert_eq!(decision.mint, SOL);
+        assert_eq!(decision.amount_usd, Decimal::new(2505, 1));
+        assert!(!decision.approved);
+        assert_eq!(decision.timestamp, hour(1));
+    }
+
+    #[test]
+    fn test_replays_blocked_intents_under_looser_caps() {
+        let prices = prices(&[100, 100, 120, 120]);
+        // The bot's 10% cap blocked the $200 buy; the second went through
+        let decisions = [
+            decision(0, Action::Buy, 200, false),
+            decision(2, Action::Sell, 1_000, true),
+        ];
+
+        let strict = replay(&decisions, &prices, &config(10));
+        assert_eq!(strict.trades_blocked["max_position_size_percent"], 1);
+        assert_eq!(strict.trades_blocked["nothing_held"], 1);
+        assert_eq!(strict.final_equity_usd, Decimal::from(1_000));
+
+        let loose = replay(&decisions, &prices, &config(25));
+        assert_eq!(loose.trades.len(), 2);
+        // 2 SOL bought at 100 and sold at 120
+        assert_eq!(loose.final_equity_usd, Decimal::from(1_040));
+        assert_eq!(loose.total_return_pct, Decimal::from(4));
+        assert_eq!(loose.trades[1].realized_pnl_usd, Some(Decimal::from(40)));
+        assert_eq!(loose.equity_curve.len(), 2);
+    }
+
+    struct AlwaysBuy;
+
+    impl Algorithm for AlwaysBuy {
+        fn name(&self) -> &str {
+            "always_buy"
+        }
+        fn mode(&self) -> AlgorithmMode {
+            AlgorithmMode::Trend
+        }
+        fn generate_signal(&self, ctx: &MarketContext) -> Signal {
+            Signal::buy(
+                ctx.symbol.clone(),
+                ctx.current_price,
+                Decimal::ONE,
+                "always_buy".into(),
+                "test".into(),
+            )
+            .with_position_size(Decimal::new(2, 1))
+        }
+        fn parameters(&self) -> AlgorithmParams {
+            AlgorithmParams::default()
+        }
+        fn update_parameters(&mut self, _params: AlgorithmParams) {}
+    }
+
+    #[test]
+    fn test_algorithm_decides_at_plan_ticks() {
+        let prices = prices(&[100, 100, 110, 110]);
+        let decisions = [
+            decision(1, Action::Sell, 50, true),
+            decision(3, Action::Sell, 50, true),
+        ];
+        let config = ShadowConfig {
+            algorithm: Some(Box::new(AlwaysBuy)),
+            ..config(25)
+        };
+        let run = replay(&decisions, &prices, &config);
+        // Buys $200 at the first tick, already holds at the second
+        assert_eq!(run.trades.len(), 1);
+        assert_eq!(run.trades[0].notional_usd, Decimal::from(200));
+        assert_eq!(run.final_equity_usd, Decimal::from(1_020));
+
+        assert_eq!(
+            max_drawdown_pct([100, 120, 90, 130].map(Decimal::from)),
+            Decimal::from(25)
+        );
+    }
+}
@@ -0,0 +1,62 @@
+//! Sync receipts
+//!
+//! Bots can be pointed at more than one control-plane region, all writing to
+//! the same database. A runner that loses its region mid-sync resends the
+//! same payload to the next one, and it can't tell whether the first attempt
+//! was stored before the connection dropped. Each sync carries a `sync_id`
+//! that stays the same across those retries: the first request to claim it
+//! stores the payload, and a repeat only picks up commands and config state.
+//!
+//! Receipts are pruned after [`RETENTION_HOURS`] by the data retention task.
+
+use uuid::Uuid;
+
+/// How long a receipt is kept; far longer than any runner retries a sync
+pub const RETENTION_HOURS: i64 = 24;
+
+/// This instance's region, from `CONTROL_PLANE_REGION`
+pub fn region() -> Option<String> {
+    std::env::var("CONTROL_PLANE_REGION")
+        .ok()
+        .map(|r| r.trim().to_string())
+        .filter(|r| !r.is_empty())
+}
+
+/// Claim `sync_id` for `bot_id`; false if an earlier attempt already did
+pub async fn claim(
+    pool: &sqlx::PgPool,
+    bot_id: Uuid,
+    sync_id: Uuid,
+    region: Option<&str>,
+) -> Result<bool, sqlx::Error> {
+    let inserted = sqlx::query(
+        "INSERT INTO sync_receipts (bot_id, sync_id, region) VALUES ($1, $2, $3) \
+         ON CONFLICT (bot_id, sync_id) DO NOTHING",
+    )
+    .bind(bot_id)
+    .bind(sync_id)
+    .bind(region)
+    .execute(pool)
+    .await?;
+    Ok(inserted.rows_affected() > 0)
+}
+
+/// Give back a claim whose payload failed to store, so the retry stores it
+pub async fn release(pool: &sqlx::PgPool, bot_id: Uuid, sync_id: Uuid) -> Result<(), sqlx::Error> {
+    sqlx::query("DELETE FROM sync_receipts WHERE bot_id = $1 AND sync_id = $2")
+        .bind(bot_id)
+        .bind(sync_id)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+/// Drop receipts past retention; returns how many went
+pub async fn prune(pool: &sqlx::PgPool) -> Result<u64, sqlx::Error> {
+    let result =
+        sqlx::query("DELETE FROM sync_receipts WHERE received_at < NOW() - INTERVAL '1 hour' * $1")
+            .bind(RETENTION_HOURS)
+            .execute(pool)
+            .await?;
+    Ok(result.rows_affected())
+}
@@ -0,0 +1,444 @@
+//! Trade history from the runner's trade events
+//!
+//! As events are ingested, each `trade_confirmed` becomes a row in `trades`,
+//! keyed by the bot and the intent that produced it, and the intent's
+//! `trade_closed` (a sell booked against its cost basis) adds the realized
+//! PnL. The two are upserted independently, so a redelivered or reordered
+//! event fills in the same row. `GET /bots/:id/trades` pages these newest
+//! first with the same keyset cursor as the events API, so the dashboard
+//! doesn't have to stitch trades together from raw events.
+//!
+//! Amounts are converted with the mint's decimals when the mint is known to
+//! data-retrieval; otherwise `quantity` and `notional_usd` are left empty.
+
+use bigdecimal::BigDecimal;
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use serde::Serialize;
+use tracing::{error, warn};
+use uuid::Uuid;
+
+use crate::backfill::{is_cash, known_mint};
+use crate::events::EventCursor;
+use crate::models::{try_bigdecimal_from_decimal, try_decimal_from_bigdecimal};
+
+/// A number sent either as a string or as a JSON number
+fn number(metadata: &serde_json::Value, key: &str) -> Option<Decimal> {
+    let value = metadata.get(key)?;
+    value
+        .as_str()
+        .and_then(|s| s.parse().ok())
+        .or_else(|| value.as_f64().and_then(Decimal::from_f64_retain))
+}
+
+fn intent_id(metadata: &serde_json::Value) -> Option<Uuid> {
+    Uuid::parse_str(metadata.get("intent_id")?.as_str()?).ok()
+}
+
+/// A confirmed fill, from its `trade_confirmed` event
+#[derive(Debug, Clone, PartialEq)]
+pub struct Fill {
+    pub intent_id: Uuid,
+    /// buy (cash to token), sell (token to cash) or swap
+    pub side: &'static str,
+    /// The non-cash side of the swap
+    pub mint: String,
+    pub symbol: Option<String>,
+    pub input_mint: String,
+    pub output_mint: String,
+    /// Tokens bought or sold
+    pub quantity: Option<Decimal>,
+    /// Cash paid or received
+    pub notional_usd: Option<Decimal>,
+    pub executed_price: Option<Decimal>,
+    pub signature: Option<String>,
+    pub mode: Option<String>,
+    pub price_impact_pct: Option<f64>,
+    pub slippage_bps: Option<f64>,
+    pub fees_usd: Option<Decimal>,
+    pub executed_at: DateTime<Utc>,
+}
+
+impl Fill {
+    pub fn from_event(executed_at: DateTime<Utc>, metadata: &serde_json::Value) -> Option<Self> {
+        let input_mint = metadata.get("input_mint")?.as_str()?.to_string();
+        let output_mint = metadata.get("output_mint")?.as_str()?.to_string();
+        let amount = |key: &str, mint: &str| {
+            let (_, decimals) = known_mint(mint)?;
+            Some(number(metadata, key)? / Decimal::from(10u64.checked_pow(decimals)?))
+        };
+        let in_amount = amount("in_amount", &input_mint);
+        let out_amount = amount("out_amount", &output_mint);
+        let (side, mint, quantity, notional) = match (is_cash(&input_mint), is_cash(&output_mint)) {
+            (true, false) => ("buy", &output_mint, out_amount, in_amount),
+            (false, true) => ("sell", &input_mint, in_amount, out_amount),
+            _ => ("swap", &output_mint, out_amount, None),
+        };
+        let fees_usd = number(metadata, "fee_usd")
+            .or_else(|| Some(notional? * number(metadata, "fee_bps")? / Decimal::from(10_000)));
+        let float = |key: &str| metadata.get(key).and_then(|v| v.as_f64());
+        let text = |key: &str| metadata.get(key).and_then(|v| v.as_str()).map(String::from);
+
+        Some(Self {
+            intent_id: intent_id(metadata)?,
+            side,
+            mint: mint.clone(),
+            symbol: known_mint(mint).map(|(symbol, _)| symbol.to_string()),
+            quantity,
+            notional_usd: notional,
+            executed_price: number(metadata, "executed_price"),
+            signature: text("signature"),
+            mode: text("mode"),
+            price_impact_pct: float("price_impact_pct"),
+            slippage_bps: float("slippage_bps"),
+            fees_usd: fees_usd.map(|f| f.round_dp(6)),
+            executed_at,
+            input_mint,
+            output_mint,
+        })
+    }
+}
+
+/// The realized side of a sell, from its `trade_closed` event
+#[derive(Debug, Clone, PartialEq)]
+pub struct Close {
+    pub intent_id: Uuid,
+    pub mint: Option<String>,
+    pub symbol: Option<String>,
+    pub entry_price: Option<Decimal>,
+    pub cost_basis_usd: Option<Decimal>,
+    pub realized_pnl_usd: Decimal,
+    pub fully_closed: Option<bool>,
+    pub closed_at: DateTime<Utc>,
+}
+
+impl Close {
+    pub fn from_event(closed_at: DateTime<Utc>, metadata: &serde_json::Value) -> Option<Self> {
+        // A made-up basis would make a made-up PnL
+        if metadata.get("unknown_cost_basis").and_then(|v| v.as_bool()) == Some(true) {
+            return None;
+        }
+        let text = |key: &str| metadata.get(key).and_then(|v| v.as_str()).map(String::from);
+        Some(Self {
+            intent_id: intent_id(metadata)?,
+            mint: text("mint"),
+            symbol: text("symbol"),
+            entry_price: number(metadata, "entry_price"),
+            cost_basis_usd: number(metadata, "cost_basis_usd"),
+            realized_pnl_usd: number(metadata, "realized_pnl_usd")?,
+            fully_closed: metadata.get("fully_closed").and_then(|v| v.as_bool()),
+            closed_at,
+        })
+    }
+}
+
+/// A row of `GET /bots/:id/trades`
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Trade {
+    pub id: Uuid,
+    /// The intent (and decision journal entry) the trade came from
+    pub intent_id: Uuid,
+    pub side: Option<String>,
+    pub mint: Option<String>,
+    pub symbol: Option<String>,
+    pub input_mint: Option<String>,
+    pub output_mint: Option<String>,
+    pub quantity: Option<Decimal>,
+    pub notional_usd: Option<Decimal>,
+    pub executed_price: Option<Decimal>,
+    pub signature: Option<String>,
+    pub mode: Option<String>,
+    pub price_impact_pct: Option<f64>,
+    pub slippage_bps: Option<f64>,
+    /// Swap and network fees
+    pub fees_usd: Option<Decimal>,
+    /// Sells with a known cost basis only
+    pub realized_pnl_usd: Option<Decimal>,
+    pub cost_basis_usd: Option<Decimal>,
+    pub entry_price: Option<Decimal>,
+    pub fully_closed: Option<bool>,
+    /// None until the `trade_confirmed` event arrives
+    pub executed_at: Option<DateTime<Utc>>,
+    pub closed_at: Option<DateTime<Utc>>,
+    /// When the first event of the trade arrived; the page order
+    pub recorded_at: DateTime<Utc>,
+}
+
+#[derive(sqlx::FromRow)]
+struct TradeRow {
+    id: Uuid,
+    intent_id: Uuid,
+    side: Option<String>,
+    mint: Option<String>,
+    symbol: Option<String>,
+    input_mint: Option<String>,
+    output_mint: Option<String>,
+    quantity: Option<BigDecimal>,
+    notional_usd: Option<BigDecimal>,
+    executed_price: Option<BigDecimal>,
+    signature: Option<String>,
+    mode: Option<String>,
+    price_impact_pct: Option<f64>,
+    slippage_bps: Option<f64>,
+    fees_usd: Option<BigDecimal>,
+    realized_pnl_usd: Option<BigDecimal>,
+    cost_basis_usd: Option<BigDecimal>,
+    entry_price: Option<BigDecimal>,
+    fully_closed: Option<bool>,
+    executed_at: Option<DateTime<Utc>>,
+    closed_at: Option<DateTime<Utc>>,
+    recorded_at: DateTime<Utc>,
+}
+
+impl From<TradeRow> for Trade {
+    fn from(row: TradeRow) -> Self {
+        let decimal = |d: &Option<BigDecimal>| d.as_ref().and_then(try_decimal_from_bigdecimal);
+        Self {
+            id: row.id,
+            intent_id: row.intent_id,
+            quantity: decimal(&row.quantity),
+            notional_usd: decimal(&row.notional_usd),
+            executed_price: decimal(&row.executed_price),
+            fees_usd: decimal(&row.fees_usd),
+            realized_pnl_usd: decimal(&row.realized_pnl_usd),
+            cost_basis_usd: decimal(&row.cost_basis_usd),
+            entry_price: decimal(&row.entry_price),
+            side: row.side,
+            mint: row.mint,
+            symbol: row.symbol,
+            input_mint: row.input_mint,
+            output_mint: row.output_mint,
+            signature: row.signature,
+            mode: row.mode,
+            price_impact_pct: row.price_impact_pct,
+            slippage_bps: row.slippage_bps,
+            fully_closed: row.fully_closed,
+            executed_at: row.executed_at,
+            closed_at: row.closed_at,
+            recorded_at: row.recorded_at,
+        }
+    }
+}
+
+fn big(d: Option<Decimal>) -> Option<BigDecimal> {
+    d.as_ref().and_then(try_bigdecimal_from_decimal)
+}
+
+async fn store_fill(pool: &sqlx::PgPool, bot_id: Uuid, fill: &Fill) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        INSERT INTO trades
+            (bot_id, intent_id, side, mint, symbol, input_mint, output_mint, quantity,
+             notional_usd, executed_price, signature, mode, price_impact_pct, slippage_bps,
+             fees_usd, executed_at, recorded_at)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $16)
+        ON CONFLICT (bot_id, intent_id) DO UPDATE SET
+            side = EXCLUDED.side,
+            mint = EXCLUDED.mint,
+            symbol = COALESCE(trades.symbol, EXCLUDED.symbol),
+            input_mint = EXCLUDED.input_mint,
+            output_mint = EXCLUDED.output_mint,
+            quantity = EXCLUDED.quantity,
+            notional_usd = EXCLUDED.notional_usd,
+            executed_price = EXCLUDED.executed_price,
+            signature = EXCLUDED.signature,
+            mode = EXCLUDED.mode,
+            price_impact_pct = EXCLUDED.price_impact_pct,
+            slippage_bps = EXCLUDED.slippage_bps,
+            fees_usd = EXCLUDED.fees_usd,
+            executed_at = EXCLUDED.executed_at
+        "#,
+    )
+    .bind(bot_id)
+    .bind(fill.intent_id)
+    .bind(fill.side)
+    .bind(&fill.mint)
+    .bind(&fill.symbol)
+    .bind(&fill.input_mint)
+    .bind(&fill.output_mint)
+    .bind(big(fill.quantity))
+    .bind(big(fill.notional_usd))
+    .bind(big(fill.executed_price))
+    .bind(&fill.signature)
+    .bind(&fill.mode)
+    .bind(fill.price_impact_pct)
+    .bind(fill.slippage_bps)
+    .bind(big(fill.fees_usd))
+    .bind(fill.executed_at)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+async fn store_close(pool: &sqlx::PgPool, bot_id: Uuid, close: &Close) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        INSERT INTO trades
+            (bot_id, intent_id, mint, symbol, entry_price, cost_basis_usd, realized_pnl_usd,
+             fully_closed, closed_at, recorded_at)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $9)
+        ON CONFLICT (bot_id, intent_id) DO UPDATE SET
+            mint = COALESCE(trades.mint, EXCLUDED.mint),
+            symbol = COALESCE(EXCLUDED.symbol, trades.symbol),
+            entry_price = EXCLUDED.entry_price,
+            cost_basis_usd = EXCLUDED.cost_basis_usd,
+            realized_pnl_usd = EXCLUDED.realized_pnl_usd,
+            fully_closed = EXCLUDED.fully_closed,
+            closed_at = EXCLUDED.closed_at
+        "#,
+    )
+    .bind(bot_id)
+    .bind(close.intent_id)
+    .bind(&close.mint)
+    .bind(&close.symbol)
+    .bind(big(close.entry_price))
+    .bind(big(close.cost_basis_usd))
+    .bind(big(Some(close.realized_pnl_usd)))
+    .bind(close.fully_closed)
+    .bind(close.closed_at)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Record a trade event in `trades`; anything but `trade_confirmed` and
+/// `trade_closed` is ignored
+///
+/// Best effort: the event itself is already stored, so a failure is logged.
+pub async fn record_from_event(
+    pool: &sqlx::PgPool,
+    bot_id: Uuid,
+    event_type: &str,
+    metadata: Option<&serde_json::Value>,
+    at: DateTime<Utc>,
+) {
+    let Some(metadata) = metadata else {
+        return;
+    };
+    let stored = match event_type {
+        "trade_confirmed" => match Fill::from_event(at, metadata) {
+            Some(fill) => store_fill(pool, bot_id, &fill).await,
+            None => {
+                warn!("Bot {} sent a trade_confirmed without its fill", bot_id);
+                return;
+            }
+        },
+        "trade_closed" => match Close::from_event(at, metadata) {
+            Some(close) => store_close(pool, bot_id, &close).await,
+            None => return,
+        },
+        _ => return,
+    };
+    if let Err(e) = stored {
+        error!("Failed to record {} for bot {}: {}", event_type, bot_id, e);
+    }
+}
+
+/// One page of a bot's trades, newest first, and the cursor of the next
+pub async fn list_trades(
+    pool: &sqlx::PgPool,
+    bot_id: Uuid,
+    mint: Option<&str>,
+    after: Option<EventCursor>,
+    limit: i64,
+) -> Result<(Vec<Trade>, Option<String>), sqlx::Error> {
+    let limit = limit.clamp(1, crate::events::MAX_PAGE_SIZE);
+    let mut rows: Vec<TradeRow> = sqlx::query_as(
+        "SELECT * FROM trades \
+         WHERE bot_id = $1 \
+         AND ($2::text IS NULL OR mint = $2) \
+         AND ($3::timestamptz IS NULL OR (recorded_at, id) < ($3, $4)) \
+         ORDER BY recorded_at DESC, id DESC \
+         LIMIT $5",
+    )
+    .bind(bot_id)
+    .bind(mint)
+    .bind(after.map(|c| c.created_at))
+    .bind(after.map(|c| c.id))
+    .bind(limit + 1)
+    .fetch_all(pool)
+    .await?;
+
+    let next_cursor = if rows.len() as i64 > limit {
+        rows.truncate(limit as usize);
+        rows.last().map(|r| {
+            EventCursor {
+                created_at: r.recorded_at,
+                id: r.id,
+            }
+            .encode()
+        })
+    } else {
+        None
+    };
+    Ok((rows.into_iter().map(Trade::from).collect(), next_cursor))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const USDC: &str = "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v";
+    const SOL: &str = "So11111111111111111111111111111111111111112";
+
+    #[test]
+    fn test_fill_from_confirmed_event() {
+        let intent = Uuid::new_v4();
+        let meta = serde_json::json!({
+            "intent_id": intent.to_string(),
+            "signature": "5sig",
+            "input_mint": SOL,
+            "output_mint": USDC,
+            "in_amount": 2_000_000_000u64,
+            "out_amount": 301_500_000u64,
+            "executed_price": "150.75",
+            "price_impact_pct": 0.12,
+            "slippage_bps": 50,
+            "fee_bps": 20,
+            "mode": "Paper",
+        });
+        let fill = Fill::from_event(Utc::now(), &meta).unwrap();
+        assert_eq!(fill.intent_id, intent);
+        assert_eq!(fill.side, "sell");
+        assert_eq!(fill.mint, SOL);
+        assert_eq!(fill.symbol.as_deref(), Some("SOL"));
+        assert_eq!(fill.quantity, Some(Decimal::from(2)));
+        assert_eq!(fill.notional_usd, Some(Decimal::new(3015, 1)));
+        // No fee_usd: 20 bps of the cash side
+        assert_eq!(fill.fees_usd, Some(Decimal::new(603, 3)));
+        assert_eq!(fill.slippage_bps, Some(50.0));
+        assert_eq!(fill.signature.as_deref(), Some("5sig"));
+
+        let mut unknown = meta.clone();
+        unknown["input_mint"] = serde_json::json!(USDC);
+        unknown["output_mint"] = serde_json::json!("Meme1111111111111111111111111111111111111111");
+        unknown["fee_usd"] = serde_json::json!("0.4");
+        let fill = Fill::from_event(Utc::now(), &unknown).unwrap();
+        assert_eq!(fill.side, "buy");
+        assert_eq!(fill.quantity, None);
+        assert_eq!(fill.fees_usd, Some(Decimal::new(4, 1)));
+
+        assert!(Fill::from_event(Utc::now(), &serde_json::json!({"input_mint": SOL})).is_none());
+    }
+
+    #[test]
+    fn test_close_from_event() {
+        let meta = serde_json::json!({
+            "intent_id": Uuid::new_v4().to_string(),
+            "mint": SOL,
+            "symbol": "SOL",
+            "entry_price": "140",
+            "cost_basis_usd": "280",
+            "realized_pnl_usd": "21.5",
+            "fully_closed": true,
+            "unknown_cost_basis": false,
+        });
+        let close = Close::from_event(Utc::now(), &meta).unwrap();
+        assert_eq!(close.realized_pnl_usd, Decimal::new(215, 1));
+        assert_eq!(close.fully_closed, Some(true));
+
+        let mut guessed = meta.clone();
+        guessed["unknown_cost_basis"] = serde_json::json!(true);
+        assert!(Close::from_event(Utc::now(), &guessed).is_none());
+    }
+}
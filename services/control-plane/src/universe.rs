@@ -0,0 +1,272 @@
+//! Asset universe resolution
+//!
+//! A bot's config only names an asset focus (and, for `Custom`, a list of
+//! symbols or mints). The control plane turns that into the concrete list of
+//! mints the runner may buy, from the asset registry: the `asset_registry`
+//! platform config entry (a JSON list of [`RegistryAsset`]) or, while that is
+//! empty, the built-in list below. The resolved universe is sent with every
+//! config, so the runner, the agent and the control plane agree on exactly
+//! which assets are tradable.
+
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use std::collections::HashSet;
+use tracing::warn;
+
+use crate::config::{self, keys};
+use crate::models::{AssetFocus, UniverseAsset};
+
+/// Most decimals a registry asset may declare
+const MAX_DECIMALS: u8 = 18;
+
+/// Most assets the registry may hold
+pub const MAX_REGISTRY_ASSETS: usize = 200;
+
+/// One tradable asset in the registry
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RegistryAsset {
+    pub symbol: String,
+    pub mint: String,
+    pub decimals: u8,
+    /// Focus lists the asset is in; any registry asset can be picked as custom
+    #[serde(default)]
+    pub focus: Vec<AssetFocus>,
+}
+
+/// Built-in registry, used while `asset_registry` is empty
+///
+/// Tokenized equities and metals have no vetted mints yet; until the
+/// registry lists some, bots with those focuses resolve to no assets.
+const DEFAULT_REGISTRY: &[(&str, &str, u8, AssetFocus)] = &[
+    (
+        "SOL",
+        "So11111111111111111111111111111111111111112",
+        9,
+        AssetFocus::Majors,
+    ),
+    (
+        "BTC",
+        "qfnqNLS3x2K5R3oCmS1NjwiKOK8Tq77pCH6zTX8mR2F",
+        8,
+        AssetFocus::Majors,
+    ),
+    (
+        "ETH",
+        "7vfCXTUXx5WJV5JADk17DUJ4ksgau7utNKj4b963voxs",
+        8,
+        AssetFocus::Majors,
+    ),
+    (
+        "BONK",
+        "DezXAZ8z7PnrnRJjz3wXBoRgixCa6xjnB7YaB1pPB263",
+        5,
+        AssetFocus::Memes,
+    ),
+    (
+        "WIF",
+        "EKpQGSJtjMFqKZ9KQbSqL2zPQCpA5xZKN2CjeJRdQpump",
+        6,
+        AssetFocus::Memes,
+    ),
+];
+
+pub fn default_registry() -> Vec<RegistryAsset> {
+    DEFAULT_REGISTRY
+        .iter()
+        .map(|(symbol, mint, decimals, focus)| RegistryAsset {
+            symbol: symbol.to_string(),
+            mint: mint.to_string(),
+            decimals: *decimals,
+            focus: vec![*focus],
+        })
+        .collect()
+}
+
+/// Parse and check an `asset_registry` value
+///
+/// Every mint has to look like an address (32-64 alphanumeric characters),
+/// and neither symbols (case-insensitively) nor mints may repeat.
+pub fn parse_registry(raw: &str) -> Result<Vec<RegistryAsset>, String> {
+    let assets: Vec<RegistryAsset> =
+        serde_json::from_str(raw).map_err(|e| format!("Invalid asset registry JSON: {}", e))?;
+    if assets.len() > MAX_REGISTRY_ASSETS {
+        return Err(format!(
+            "At most {} registry assets are allowed",
+            MAX_REGISTRY_ASSETS
+        ));
+    }
+
+    let mut symbols = HashSet::new();
+    let mut mints = HashSet::new();
+    for asset in &assets {
+        if asset.symbol.trim().is_empty() || asset.symbol.len() > 20 {
+            return Err(format!("Invalid symbol '{}'", asset.symbol));
+        }
+        let looks_like_mint = (32..=64).contains(&asset.mint.len())
+            && asset.mint.chars().all(|c| c.is_ascii_alphanumeric());
+        if !looks_like_mint {
+            return Err(format!("{}: invalid mint '{}'", asset.symbol, asset.mint));
+        }
+        if asset.decimals > MAX_DECIMALS {
+            return Err(format!(
+                "{}: decimals must be at most {}",
+                asset.symbol, MAX_DECIMALS
+            ));
+        }
+        if asset.focus.contains(&AssetFocus::Custom) {
+            return Err(format!(
+                "{}: custom is not a focus list; any asset can be picked as custom",
+                asset.symbol
+            ));
+        }
+        if !symbols.insert(asset.symbol.to_ascii_uppercase()) || !mints.insert(&asset.mint) {
+            return Err(format!("{} is listed twice", asset.symbol));
+        }
+    }
+    Ok(assets)
+}
+
+/// The registry in effect: platform config, else the built-in list
+///
+/// An entry that fails to parse (it is checked when set, so only a direct
+/// database edit gets here) falls back to the built-in list.
+pub async fn registry(pool: &PgPool) -> Vec<RegistryAsset> {
+    match config::get_config(pool, keys::ASSET_REGISTRY).await {
+        Some(raw) => parse_registry(&raw).unwrap_or_else(|e| {
+            warn!("Ignoring asset_registry platform config: {}", e);
+            default_registry()
+        }),
+        None => default_registry(),
+    }
+}
+
+/// Registry asset named by a symbol (case-insensitively) or mint
+fn find<'a>(registry: &'a [RegistryAsset], name: &str) -> Option<&'a RegistryAsset> {
+    registry
+        .iter()
+        .find(|a| a.mint == name || a.symbol.eq_ignore_ascii_case(name.trim()))
+}
+
+/// Custom asset names that aren't in the registry
+pub fn unknown_assets(registry: &[RegistryAsset], names: &[String]) -> Vec<String> {
+    names
+        .iter()
+        .filter(|name| find(registry, name).is_none())
+        .cloned()
+        .collect()
+}
+
+/// Check that a config's focus resolves to at least one registry asset
+///
+/// A `Custom` focus must name only registry assets.
+pub fn validate_focus(
+    registry: &[RegistryAsset],
+    focus: AssetFocus,
+    custom_assets: Option<&[String]>,
+) -> Result<(), String> {
+    let custom_assets = custom_assets.unwrap_or_default();
+    if focus == AssetFocus::Custom {
+        let unknown = unknown_assets(registry, custom_assets);
+        if !unknown.is_empty() {
+            return Err(format!("Not in the asset registry: {}", unknown.join(", ")));
+        }
+    }
+    if resolve(registry, focus, custom_assets).is_empty() {
+        return Err(format!("No tradable assets for asset focus {:?}", focus));
+    }
+    Ok(())
+}
+
+/// The assets a bot with `focus` may trade, in registry order
+///
+/// `Custom` takes the registry assets named in `custom_assets`, in the order
+/// given; names not in the registry are left out.
+pub fn resolve(
+    registry: &[RegistryAsset],
+    focus: AssetFocus,
+    custom_assets: &[String],
+) -> Vec<UniverseAsset> {
+    let picked: Vec<&RegistryAsset> = match focus {
+        AssetFocus::Custom => {
+            let mut seen = HashSet::new();
+            custom_assets
+                .iter()
+                .filter_map(|name| find(registry, name))
+                .filter(|asset| seen.insert(&asset.mint))
+                .collect()
+        }
+        focus => registry
+            .iter()
+            .filter(|a| a.focus.contains(&focus))
+            .collect(),
+    };
+    picked
+        .into_iter()
+        .map(|asset| UniverseAsset {
+            symbol: asset.symbol.clone(),
+            mint: asset.mint.clone(),
+            enabled: true,
+            max_allocation_pct: None,
+            decimals: Some(asset.decimals),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_focus_and_custom() {
+        let registry = default_registry();
+        let symbols = |universe: Vec<UniverseAsset>| {
+            universe.into_iter().map(|a| a.symbol).collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            symbols(resolve(&registry, AssetFocus::Majors, &[])),
+            ["SOL", "BTC", "ETH"]
+        );
+        assert!(resolve(&registry, AssetFocus::TokenizedEquities, &[]).is_empty());
+
+        // By symbol or mint, deduplicated, unknown names dropped
+        let custom = [
+            "wif".to_string(),
+            "So11111111111111111111111111111111111111112".to_string(),
+            "SOL".to_string(),
+            "NOPE".to_string(),
+        ];
+        let universe = resolve(&registry, AssetFocus::Custom, &custom);
+        assert_eq!(universe[1].decimals, Some(9));
+        assert_eq!(symbols(universe), ["WIF", "SOL"]);
+        assert_eq!(unknown_assets(&registry, &custom), ["NOPE"]);
+
+        assert!(validate_focus(&registry, AssetFocus::Memes, None).is_ok());
+        assert!(validate_focus(&registry, AssetFocus::TokenizedMetals, None).is_err());
+        assert!(validate_focus(&registry, AssetFocus::Custom, None).is_err());
+        assert!(validate_focus(&registry, AssetFocus::Custom, Some(&custom)).is_err());
+        assert!(validate_focus(&registry, AssetFocus::Custom, Some(&custom[..3])).is_ok());
+    }
+
+    #[test]
+    fn test_parse_registry() {
+        let raw = serde_json::to_string(&default_registry()).unwrap();
+        assert_eq!(parse_registry(&raw).unwrap(), default_registry());
+
+        let entry = |symbol: &str, mint: &str, decimals: u8| {
+            format!(
+                r#"{{"symbol":"{}","mint":"{}","decimals":{},"focus":["TokenizedMetals"]}}"#,
+                symbol, mint, decimals
+            )
+        };
+        let sol = "So11111111111111111111111111111111111111112";
+        let parsed = parse_registry(&format!("[{}]", entry("GOLD", sol, 6))).unwrap();
+        assert_eq!(parsed[0].focus, [AssetFocus::TokenizedMetals]);
+
+        assert!(parse_registry("{}").is_err());
+        assert!(parse_registry(&format!("[{}]", entry("GOLD", "not-a-mint", 6))).is_err());
+        assert!(parse_registry(&format!("[{}]", entry("GOLD", sol, 19))).is_err());
+        let twice = format!("[{},{}]", entry("GOLD", sol, 6), entry("gold", sol, 6));
+        assert!(parse_registry(&twice).is_err());
+    }
+}
@@ -1,15 +1,106 @@
 //! Webhook notifications for critical alerts
+//!
+//! Besides the platform's Discord and email hooks, users can register their
+//! own endpoints (`/v1/webhooks`), which receive their alerts as signed JSON.
+//! Each request carries `X-Trawling-Signature: t=<unix secs>,v1=<hex>`, an
+//! HMAC-SHA256 with the endpoint's secret over `"<t>.<body>"`. Receivers
+//! should recompute it and reject timestamps more than
+//! `SIGNATURE_TOLERANCE_SECS` old; `X-Trawling-Delivery` is the same for
+//! every retry of one notification, for de-duplication. Every attempt is
+//! logged in `webhook_deliveries`.
 
 use crate::alerting::{AlertSeverity, AlertType};
+use crate::preferences::{NotificationChannel, UserPreferences};
+use crate::secrets::SecretsManager;
+use hmac::{Hmac, Mac};
 use reqwest::Client;
-use std::time::Duration;
+use sha2::Sha256;
+use std::time::{Duration, Instant};
 use tracing::{debug, error, info, warn};
+use uuid::Uuid;
+
+pub const SIGNATURE_HEADER: &str = "X-Trawling-Signature";
+pub const DELIVERY_HEADER: &str = "X-Trawling-Delivery";
+pub const EVENT_HEADER: &str = "X-Trawling-Event";
+
+/// Oldest signature timestamp a receiver should accept
+pub const SIGNATURE_TOLERANCE_SECS: i64 = 300;
+
+/// Attempts per notification to a user endpoint
+const DELIVERY_ATTEMPTS: u32 = 3;
+
+/// Signature header value for `body` sent at `timestamp`
+pub fn sign_payload(secret: &str, timestamp: i64, body: &[u8]) -> String {
+    format!(
+        "t={},v1={}",
+        timestamp,
+        signature_hex(secret, timestamp, body)
+    )
+}
+
+fn signature_hex(secret: &str, timestamp: i64, body: &[u8]) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts any key length");
+    mac.update(timestamp.to_string().as_bytes());
+    mac.update(b".");
+    mac.update(body);
+    hex::encode(mac.finalize().into_bytes())
+}
+
+/// Check a signature header the way a receiver should
+///
+/// Fails if the header is malformed, the timestamp is outside the replay
+/// window around `now`, or no `v1` signature matches.
+pub fn verify_signature(
+    secret: &str,
+    header: &str,
+    body: &[u8],
+    now: i64,
+) -> Result<(), &'static str> {
+    let mut timestamp = None;
+    let mut signatures = Vec::new();
+    for part in header.split(',') {
+        match part.trim().split_once('=') {
+            Some(("t", t)) => timestamp = t.parse::<i64>().ok(),
+            Some(("v1", sig)) => signatures.push(sig),
+            _ => {}
+        }
+    }
+    let timestamp = timestamp.ok_or("missing timestamp")?;
+    if (now - timestamp).abs() > SIGNATURE_TOLERANCE_SECS {
+        return Err("timestamp outside tolerance");
+    }
+    let Ok(key) = Hmac::<Sha256>::new_from_slice(secret.as_bytes()) else {
+        return Err("invalid secret");
+    };
+    for sig in signatures {
+        let Ok(sig) = hex::decode(sig) else { continue };
+        let mut mac = key.clone();
+        mac.update(timestamp.to_string().as_bytes());
+        mac.update(b".");
+        mac.update(body);
+        if mac.verify_slice(&sig).is_ok() {
+            return Ok(());
+        }
+    }
+    Err("signature mismatch")
+}
+
+/// A user's registered endpoint, with its secret decrypted
+#[derive(Debug, Clone)]
+struct Destination {
+    id: Uuid,
+    url: String,
+    secret: String,
+}
 
 /// Webhook configuration
 #[derive(Debug, Clone)]
 pub struct WebhookConfig {
     pub discord_webhook_url: Option<String>,
     pub email_webhook_url: Option<String>,
+    /// Signs requests to the email webhook when set
+    pub email_webhook_secret: Option<String>,
     pub timeout_secs: u64,
 }
 
@@ -18,6 +109,9 @@ impl Default for WebhookConfig {
         Self {
             discord_webhook_url: std::env::var("DISCORD_ALERT_WEBHOOK").ok(),
             email_webhook_url: std::env::var("EMAIL_ALERT_WEBHOOK").ok(),
+            email_webhook_secret: std::env::var("EMAIL_ALERT_WEBHOOK_SECRET")
+                .ok()
+                .filter(|s| !s.is_empty()),
             timeout_secs: 10,
         }
     }
@@ -28,6 +122,8 @@ impl Default for WebhookConfig {
 pub struct WebhookNotifier {
     config: WebhookConfig,
     client: Client,
+    /// Where user endpoints and the delivery log live (None: platform hooks only)
+    store: Option<(sqlx::PgPool, SecretsManager)>,
 }
 
 impl WebhookNotifier {
@@ -37,11 +133,30 @@ impl WebhookNotifier {
             .build()
             .expect("Failed to build HTTP client");
 
-        Self { config, client }
+        Self {
+            config,
+            client,
+            store: None,
+        }
+    }
+
+    /// Also deliver alerts to users' registered endpoints
+    pub fn with_store(mut self, pool: sqlx::PgPool, secrets: SecretsManager) -> Self {
+        self.store = Some((pool, secrets));
+        self
     }
 
-    /// Send alert to all configured webhooks
-    pub async fn send_alert(&self, alert: &AlertType, severity: AlertSeverity) {
+    /// Send alert to all configured webhooks, and to `user_id`'s endpoints
+    pub async fn send_alert(
+        &self,
+        alert: &AlertType,
+        severity: AlertSeverity,
+        user_id: Option<&str>,
+    ) {
+        if let Some(user_id) = user_id {
+            self.send_to_user_endpoints(user_id, alert, severity).await;
+        }
+
         // Discord webhook
         if let Some(ref discord_url) = self.config.discord_webhook_url {
             if let Err(e) = self.send_discord_alert(discord_url, alert, severity).await {
@@ -51,7 +166,14 @@ impl WebhookNotifier {
 
         // Email webhook (generic HTTP POST)
         if let Some(ref email_url) = self.config.email_webhook_url {
-            if let Err(e) = self.send_email_webhook(email_url, alert, severity).await {
+            // Note: alert_email_to is read from env var as fallback
+            // For dynamic config, use the admin dashboard at /v1/admin/config
+            let email_to = std::env::var("ALERT_EMAIL_TO")
+                .unwrap_or_else(|_| "alerts@trawlingtraders.com".to_string());
+            if let Err(e) = self
+                .send_email_webhook(email_url, &email_to, alert, severity)
+                .await
+            {
                 error!("Failed to send email webhook: {}", e);
             }
         }
@@ -98,16 +220,12 @@ impl WebhookNotifier {
     async fn send_email_webhook(
         &self,
         webhook_url: &str,
+        email_to: &str,
         alert: &AlertType,
         severity: AlertSeverity,
     ) -> anyhow::Result<()> {
         let (subject, body) = self.format_email_content(alert, severity);
 
-        // Note: alert_email_to is read from env var as fallback
-        // For dynamic config, use the admin dashboard at /v1/admin/config
-        let email_to = std::env::var("ALERT_EMAIL_TO")
-            .unwrap_or_else(|_| "alerts@trawlingtraders.com".to_string());
-
         let payload = serde_json::json!({
             "to": email_to,
             "subject": subject,
@@ -115,7 +233,18 @@ impl WebhookNotifier {
             "severity": severity.as_str(),
         });
 
-        let response = self.client.post(webhook_url).json(&payload).send().await?;
+        let body = serde_json::to_vec(&payload)?;
+        let mut request = self
+            .client
+            .post(webhook_url)
+            .header(reqwest::header::CONTENT_TYPE, "application/json");
+        if let Some(secret) = &self.config.email_webhook_secret {
+            request = request.header(
+                SIGNATURE_HEADER,
+                sign_payload(secret, chrono::Utc::now().timestamp(), &body),
+            );
+        }
+        let response = request.body(body).send().await?;
 
         if !response.status().is_success() {
             let status = response.status();
@@ -126,6 +255,207 @@ impl WebhookNotifier {
         Ok(())
     }
 
+    /// Deliver an alert to each of the user's enabled endpoints
+    ///
+    /// Deliveries run in the background so a slow receiver doesn't hold up
+    /// the caller.
+    async fn send_to_user_endpoints(
+        &self,
+        user_id: &str,
+        alert: &AlertType,
+        severity: AlertSeverity,
+    ) {
+        let Some((pool, secrets)) = &self.store else {
+            return;
+        };
+        let Ok(user_id) = Uuid::parse_str(user_id) else {
+            return;
+        };
+        let prefs = match crate::preferences::load(pool, user_id).await {
+            Ok(prefs) => prefs,
+            Err(e) => {
+                error!("Failed to load preferences for {}: {}", user_id, e);
+                UserPreferences::default()
+            }
+        };
+
+        if prefs.wants(NotificationChannel::Email) {
+            self.send_to_user_email(pool, user_id, alert, severity)
+                .await;
+        }
+        if !prefs.wants(NotificationChannel::Webhook) {
+            return;
+        }
+
+        let rows: Vec<(Uuid, String, String)> = match sqlx::query_as(
+            "SELECT id, url, encrypted_secret FROM webhook_endpoints WHERE user_id = $1 AND enabled",
+        )
+        .bind(user_id)
+        .fetch_all(pool)
+        .await
+        {
+            Ok(rows) => rows,
+            Err(e) => {
+                error!("Failed to load webhook endpoints for {}: {}", user_id, e);
+                return;
+            }
+        };
+
+        let (title, message) = alert.describe();
+        let event_type = format!("alert.{}", alert.kind());
+        let data = serde_json::json!({
+            "kind": alert.kind(),
+            "severity": severity.as_str(),
+            "bot_id": alert.bot_id(),
+            "title": title,
+            "message": message,
+            "local_time": prefs.local_time(chrono::Utc::now()),
+            "timezone": prefs.timezone,
+            "display_currency": prefs.display_currency,
+        });
+
+        for (id, url, encrypted_secret) in rows {
+            let secret = match secrets.decrypt(&encrypted_secret) {
+                Ok(secret) => secret,
+                Err(e) => {
+                    error!("Cannot decrypt secret for webhook {}: {}", id, e);
+                    continue;
+                }
+            };
+            let notifier = self.clone();
+            let pool = pool.clone();
+            let event_type = event_type.clone();
+            let data = data.clone();
+            tokio::spawn(async move {
+                notifier
+                    .deliver(&pool, &Destination { id, url, secret }, &event_type, data)
+                    .await;
+            });
+        }
+    }
+
+    /// Email an alert to the user's address through the email webhook
+    async fn send_to_user_email(
+        &self,
+        pool: &sqlx::PgPool,
+        user_id: Uuid,
+        alert: &AlertType,
+        severity: AlertSeverity,
+    ) {
+        let Some(email_url) = &self.config.email_webhook_url else {
+            return;
+        };
+        let email: Option<String> =
+            match sqlx::query_scalar("SELECT email FROM users WHERE id = $1")
+                .bind(user_id)
+                .fetch_optional(pool)
+                .await
+            {
+                Ok(email) => email.flatten(),
+                Err(e) => {
+                    error!("Failed to load email for {}: {}", user_id, e);
+                    return;
+                }
+            };
+        let Some(email) = email else {
+            return;
+        };
+        if let Err(e) = self
+            .send_email_webhook(email_url, &email, alert, severity)
+            .await
+        {
+            error!("Failed to email alert to user {}: {}", user_id, e);
+        }
+    }
+
+    /// POST a signed event, retrying transient failures; every attempt is logged
+    ///
+    /// Returns whether the receiver accepted it.
+    async fn deliver(
+        &self,
+        pool: &sqlx::PgPool,
+        destination: &Destination,
+        event_type: &str,
+        data: serde_json::Value,
+    ) -> bool {
+        let delivery_id = Uuid::new_v4();
+        let body = serde_json::json!({
+            "id": delivery_id,
+            "event_type": event_type,
+            "created_at": chrono::Utc::now(),
+            "data": data,
+        })
+        .to_string()
+        .into_bytes();
+
+        for attempt in 1..=DELIVERY_ATTEMPTS {
+            if attempt > 1 {
+                tokio::time::sleep(Duration::from_secs(2u64.pow(attempt - 1))).await;
+            }
+            // Re-signed per attempt so retries stay inside the replay window
+            let signature =
+                sign_payload(&destination.secret, chrono::Utc::now().timestamp(), &body);
+            let started = Instant::now();
+            let result = self
+                .client
+                .post(&destination.url)
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .header(SIGNATURE_HEADER, signature)
+                .header(DELIVERY_HEADER, delivery_id.to_string())
+                .header(EVENT_HEADER, event_type)
+                .body(body.clone())
+                .send()
+                .await;
+            let duration_ms = started.elapsed().as_millis().min(i32::MAX as u128) as i32;
+
+            let (status_code, error) = match &result {
+                Ok(response) => (Some(response.status().as_u16() as i32), None),
+                Err(e) => (None, Some(e.to_string())),
+            };
+            if let Err(e) = sqlx::query(
+                r#"
+                INSERT INTO webhook_deliveries
+                    (endpoint_id, delivery_id, event_type, attempt, status_code, error, duration_ms)
+                VALUES ($1, $2, $3, $4, $5, $6, $7)
+                "#,
+            )
+            .bind(destination.id)
+            .bind(delivery_id)
+            .bind(event_type)
+            .bind(attempt as i32)
+            .bind(status_code)
+            .bind(&error)
+            .bind(duration_ms)
+            .execute(pool)
+            .await
+            {
+                warn!("Failed to log webhook delivery {}: {}", delivery_id, e);
+            }
+
+            match result {
+                Ok(response) if response.status().is_success() => {
+                    debug!("Webhook {} delivered to {}", delivery_id, destination.id);
+                    return true;
+                }
+                // The receiver rejected it; retrying won't change that
+                Ok(response)
+                    if response.status().is_client_error()
+                        && response.status() != reqwest::StatusCode::REQUEST_TIMEOUT
+                        && response.status() != reqwest::StatusCode::TOO_MANY_REQUESTS =>
+                {
+                    break;
+                }
+                _ => {}
+            }
+        }
+
+        warn!(
+            "Webhook {} to endpoint {} failed after retries",
+            delivery_id, destination.id
+        );
+        false
+    }
+
     /// Format Discord embed from alert
     fn format_discord_embed(
         &self,
@@ -189,6 +519,17 @@ impl WebhookNotifier {
                 format!("⚡ High Error Rate [{}]", component),
                 format!("**{}%** errors (threshold: {}%)", error_rate, threshold),
             ),
+            AlertType::OrphanedDroplet { droplet_id, name } => (
+                format!("🧹 Orphaned Droplet [{}]", droplet_id),
+                format!("Destroyed untracked droplet `{}`", name),
+            ),
+            AlertType::MissingDroplet { bot_id, droplet_id } => (
+                format!("👻 Missing Droplet [{}]", bot_id),
+                format!(
+                    "Droplet **{}** no longer exists. Bot marked `error`.",
+                    droplet_id
+                ),
+            ),
             AlertType::BotOffline {
                 bot_id,
                 last_heartbeat,
@@ -224,6 +565,27 @@ impl WebhookNotifier {
                 format!("🔥 Drawdown Breach [{}]", bot_id),
                 format!("Current: **{}%** (limit: {}%)", current_dd, limit),
             ),
+            AlertType::SubscriptionChanged {
+                user_id,
+                status,
+                detail,
+            } => (
+                format!("💳 Subscription {} [{}]", status, user_id),
+                detail.clone(),
+            ),
+            AlertType::LlmBudget {
+                user_id,
+                spent_usd,
+                budget_usd,
+                threshold_pct,
+            } => (
+                format!("🧠 LLM Budget {}% [{}]", threshold_pct, user_id),
+                format!(
+                    "Estimated **${}** of ${} included this month",
+                    spent_usd.round_dp(2),
+                    budget_usd
+                ),
+            ),
         };
 
         (title, description, color)
@@ -250,6 +612,12 @@ impl WebhookNotifier {
             AlertType::HighErrorRate { component, .. } => {
                 format!("[TRAWLERS] High Error Rate - {}", component)
             }
+            AlertType::OrphanedDroplet { droplet_id, .. } => {
+                format!("[TRAWLERS] ORPHANED DROPLET - {}", droplet_id)
+            }
+            AlertType::MissingDroplet { bot_id, .. } => {
+                format!("[TRAWLERS] MISSING DROPLET - {}", bot_id)
+            }
             AlertType::BotOffline { bot_id, .. } => format!("[TRAWLERS] BOT OFFLINE - {}", bot_id),
             AlertType::ConfigMismatch { bot_id, .. } => {
                 format!("[TRAWLERS] Config Mismatch - {}", bot_id)
@@ -260,6 +628,12 @@ impl WebhookNotifier {
             AlertType::DrawdownBreach { bot_id, .. } => {
                 format!("[TRAWLERS] DRAWDOWN BREACH - {}", bot_id)
             }
+            AlertType::SubscriptionChanged { status, .. } => {
+                format!("[TRAWLERS] Subscription {}", status)
+            }
+            AlertType::LlmBudget { threshold_pct, .. } => {
+                format!("[TRAWLERS] LLM Budget {}% Used", threshold_pct)
+            }
         };
 
         let body = format!(
@@ -316,10 +690,48 @@ pub async fn fire_alert_with_webhook(
     webhook_notifier: &WebhookNotifier,
     alert: &AlertType,
     severity: AlertSeverity,
+    user_id: Option<&str>,
 ) {
-    // Log the alert
-    alert_manager.fire_alert(alert, severity).await;
+    // Record and log the alert; grouped or capped alerts skip the webhook
+    let decision = alert_manager.notify(alert, severity, user_id).await;
+    if decision == crate::alerting::AlertDecision::Deliver {
+        webhook_notifier.send_alert(alert, severity, user_id).await;
+    }
+}
 
-    // Send webhook
-    webhook_notifier.send_alert(alert, severity).await;
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_signature_roundtrip_and_replay_window() {
+        let body = br#"{"id":"d1","event_type":"alert.bot_offline"}"#;
+        let now = 1_760_000_000;
+        let header = sign_payload("whsec_test", now, body);
+        assert!(header.starts_with(&format!("t={},v1=", now)));
+
+        assert_eq!(
+            verify_signature("whsec_test", &header, body, now + 10),
+            Ok(())
+        );
+        assert_eq!(
+            verify_signature("other", &header, body, now),
+            Err("signature mismatch")
+        );
+        assert_eq!(
+            verify_signature("whsec_test", &header, b"{}", now),
+            Err("signature mismatch")
+        );
+        // A captured request can't be replayed after the window
+        assert_eq!(
+            verify_signature(
+                "whsec_test",
+                &header,
+                body,
+                now + SIGNATURE_TOLERANCE_SECS + 1
+            ),
+            Err("timestamp outside tolerance")
+        );
+        assert!(verify_signature("whsec_test", "v1=abc", body, now).is_err());
+    }
 }
@@ -0,0 +1,306 @@
+//! What-if replay of the trade ledger under hypothetical risk caps
+//!
+//! Replays a bot's `trade_confirmed` events twice with average-cost
+//! accounting: once as they happened, and once applying the runner's checks
+//! with the hypothetical caps (trades per UTC day, then position size against
+//! the equity reported at the time, then the day's realized loss). A blocked
+//! buy leaves nothing for the later sells of that asset to close, and a sell
+//! sells the same share of the hypothetical position as it did of the real
+//! one. Only caps tighter than the bot's are applied; a looser cap can't be
+//! replayed because the intents it would have let through never filled. The
+//! ledger doesn't tell stop and target exits from OpenClaw's sells, so the
+//! replay holds them to the daily caps too.
+
+use chrono::{DateTime, NaiveDate, Utc};
+use rust_decimal::Decimal;
+use std::collections::{BTreeMap, HashMap};
+
+use crate::backfill::is_cash;
+use crate::models::{RiskCaps, WhatIfOutcome};
+use crate::performance::FeeTrade;
+
+#[derive(Default)]
+struct Book {
+    quantity: Decimal,
+    cost: Decimal,
+    last_price: Decimal,
+}
+
+#[derive(Default)]
+struct Run {
+    books: HashMap<String, Book>,
+    /// Realized PnL, net of every fee paid
+    realized: Decimal,
+    fees: Decimal,
+    trade_count: i64,
+    peak: Decimal,
+    max_drawdown: Decimal,
+    day: Option<NaiveDate>,
+    day_trades: i32,
+    /// Matches the runner's `realized_pnl_today`: sells only
+    day_realized: Decimal,
+}
+
+impl Run {
+    fn pnl(&self) -> Decimal {
+        self.realized
+            + self
+                .books
+                .values()
+                .map(|b| b.quantity * b.last_price - b.cost)
+                .sum::<Decimal>()
+    }
+
+    fn held(&self, mint: &str) -> Decimal {
+        self.books.get(mint).map(|b| b.quantity).unwrap_or_default()
+    }
+
+    fn roll(&mut self, at: DateTime<Utc>) {
+        let day = at.date_naive();
+        if self.day != Some(day) {
+            self.day = Some(day);
+            self.day_trades = 0;
+            self.day_realized = Decimal::ZERO;
+        }
+    }
+
+    fn buy(&mut self, mint: &str, cash: Decimal, quantity: Decimal, fee: Decimal) {
+        let book = self.books.entry(mint.to_string()).or_default();
+        book.quantity += quantity;
+        book.cost += cash;
+        if !quantity.is_zero() {
+            book.last_price = cash / quantity;
+        }
+        self.realized -= fee;
+        self.filled(fee);
+    }
+
+    fn sell(&mut self, mint: &str, quantity: Decimal, proceeds: Decimal, fee: Decimal) {
+        let book = self.books.entry(mint.to_string()).or_default();
+        if !quantity.is_zero() {
+            book.last_price = proceeds / quantity;
+        }
+        let net = if book.quantity.is_zero() {
+            // No basis in the window: the fee is all that's known
+            -fee
+        } else {
+            let sold = quantity.min(book.quantity);
+            let sold_cost = book.cost * sold / book.quantity;
+            book.quantity -= sold;
+            book.cost -= sold_cost;
+            proceeds * sold / quantity - sold_cost - fee
+        };
+        self.realized += net;
+        self.day_realized += net;
+        self.filled(fee);
+    }
+
+    fn filled(&mut self, fee: Decimal) {
+        self.fees += fee;
+        self.trade_count += 1;
+        self.day_trades += 1;
+        let pnl = self.pnl();
+        self.peak = self.peak.max(pnl);
+        self.max_drawdown = self.max_drawdown.max(self.peak - pnl);
+    }
+
+    fn outcome(&self) -> WhatIfOutcome {
+        WhatIfOutcome {
+            trade_count: self.trade_count,
+            pnl_usd: self.pnl().round_dp(2),
+            max_drawdown_usd: self.max_drawdown.round_dp(2),
+            fees_usd: self.fees.round_dp(4),
+        }
+    }
+}
+
+/// Caps in `hypothetical` that are looser than `actual`, by `blocked_by` code
+pub fn loosened_caps(actual: &RiskCaps, hypothetical: &RiskCaps) -> Vec<String> {
+    [
+        (
+            "max_position_size_percent",
+            hypothetical.max_position_size_percent > actual.max_position_size_percent,
+        ),
+        (
+            "max_daily_loss_usd",
+            hypothetical.max_daily_loss_usd > actual.max_daily_loss_usd,
+        ),
+        (
+            "max_trades_per_day",
+            hypothetical.max_trades_per_day > actual.max_trades_per_day,
+        ),
+    ]
+    .into_iter()
+    .filter(|(_, looser)| *looser)
+    .map(|(code, _)| code.to_string())
+    .collect()
+}
+
+/// Replay `trades` (oldest first) as they happened and under `hypothetical`
+///
+/// `equity` is the bot's reported equity over time, oldest first; buys
+/// before the first point skip the position size check. Returns the actual
+/// and hypothetical outcomes and the hypothetical blocks per cap.
+pub fn replay(
+    trades: &[FeeTrade],
+    equity: &[(DateTime<Utc>, Decimal)],
+    actual_caps: &RiskCaps,
+    hypothetical: &RiskCaps,
+) -> (WhatIfOutcome, WhatIfOutcome, BTreeMap<String, i64>) {
+    let mut actual = Run::default();
+    let mut whatif = Run::default();
+    let mut blocked: BTreeMap<String, i64> = BTreeMap::new();
+    let equity_at = |at: DateTime<Utc>| {
+        let i = equity.partition_point(|(ts, _)| *ts <= at);
+        (i > 0).then(|| equity[i - 1].1)
+    };
+
+    for FeeTrade { trade, fee_usd } in trades {
+        let buy = is_cash(&trade.input_mint) && !is_cash(&trade.output_mint);
+        let sell = !is_cash(&trade.input_mint) && is_cash(&trade.output_mint);
+        if !buy && !sell {
+            continue;
+        }
+        actual.roll(trade.timestamp);
+        whatif.roll(trade.timestamp);
+
+        // What the hypothetical run trades: a buy as it was, a sell scaled to
+        // the share of the real position it closed
+        let (mint, quantity, cash, fee) = if buy {
+            let mint = trade.output_mint.as_str();
+            (mint, trade.out_amount, trade.in_amount, *fee_usd)
+        } else {
+            let mint = trade.input_mint.as_str();
+            let real_held = actual.held(mint);
+            let share = if real_held.is_zero() {
+                Decimal::ONE
+            } else {
+                (whatif.held(mint) / real_held).min(Decimal::ONE)
+            };
+            (
+                mint,
+                trade.in_amount * share,
+                trade.out_amount * share,
+                *fee_usd * share,
+            )
+        };
+        // Nothing left to sell after a blocked buy
+        let skipped = sell && quantity.is_zero();
+
+        let block = if skipped {
+            None
+        } else if hypothetical.max_trades_per_day < actual_caps.max_trades_per_day
+            && whatif.day_trades >= hypothetical.max_trades_per_day
+        {
+            Some("max_trades_per_day")
+        } else if buy
+            && hypothetical.max_position_size_percent < actual_caps.max_position_size_percent
+            && equity_at(trade.timestamp).is_some_and(|equity| {
+                // Equity as it would have been with the hypothetical PnL
+                let equity = equity + whatif.pnl() - actual.pnl();
+                cash > equity * Decimal::from(hypothetical.max_position_size_percent)
+                    / Decimal::from(100)
+            })
+        {
+            Some("max_position_size_percent")
+        } else if hypothetical.max_daily_loss_usd < actual_caps.max_daily_loss_usd
+            && whatif.day_realized < -Decimal::from(hypothetical.max_daily_loss_usd)
+        {
+            Some("max_daily_loss_usd")
+        } else {
+            None
+        };
+
+        if buy {
+            actual.buy(mint, trade.in_amount, trade.out_amount, *fee_usd);
+        } else {
+            actual.sell(mint, trade.in_amount, trade.out_amount, *fee_usd);
+        }
+        if skipped {
+            continue;
+        }
+        match block {
+            Some(code) => *blocked.entry(code.to_string()).or_default() += 1,
+            None if buy => whatif.buy(mint, cash, quantity, fee),
+            None => whatif.sell(mint, quantity, cash, fee),
+        }
+    }
+
+    (actual.outcome(), whatif.outcome(), blocked)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    const USDC: &str = "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v";
+    const SOL: &str = "So11111111111111111111111111111111111111112";
+
+    fn trade(hour: i64, input: &str, in_amount: i64, output: &str, out_amount: i64) -> FeeTrade {
+        FeeTrade {
+            trade: crate::backfill::LedgerTrade {
+                timestamp: Utc.timestamp_opt(hour * 3600, 0).unwrap(),
+                input_mint: input.to_string(),
+                in_amount: Decimal::from(in_amount),
+                output_mint: output.to_string(),
+                out_amount: Decimal::from(out_amount),
+            },
+            fee_usd: Decimal::ZERO,
+        }
+    }
+
+    #[test]
+    fn test_replay_under_tighter_caps() {
+        let trades = [
+            // A $100 buy then a $300 one (3% of $10k equity), then a loss
+            // on all of it and a rebuy the same day
+            trade(1, USDC, 100, SOL, 1),
+            trade(2, USDC, 300, SOL, 1),
+            trade(3, SOL, 2, USDC, 200),
+            trade(4, USDC, 100, SOL, 1),
+            trade(5, SOL, 1, USDC, 120),
+        ];
+        let equity = [(Utc.timestamp_opt(0, 0).unwrap(), Decimal::from(10_000))];
+        let caps = RiskCaps::default();
+
+        let (actual, same, blocked) = replay(&trades, &equity, &caps, &caps);
+        assert_eq!(actual.pnl_usd, Decimal::from(-180));
+        assert_eq!(actual.max_drawdown_usd, Decimal::from(400));
+        assert_eq!(same, actual);
+        assert!(blocked.is_empty());
+
+        // 2% of equity blocks the $300 buy; the sell then closes only the
+        // one SOL held, at half the proceeds
+        let tight = RiskCaps {
+            max_position_size_percent: 2,
+            ..caps
+        };
+        let (_, whatif, blocked) = replay(&trades, &equity, &caps, &tight);
+        assert_eq!(blocked.get("max_position_size_percent"), Some(&1));
+        assert_eq!(whatif.trade_count, 4);
+        assert_eq!(whatif.pnl_usd, Decimal::from(20));
+        assert_eq!(whatif.max_drawdown_usd, Decimal::ZERO);
+
+        // A $50 daily loss cap stops the rebuy, so its sell has nothing to close
+        let tight = RiskCaps {
+            max_daily_loss_usd: 50,
+            ..caps
+        };
+        let (_, whatif, blocked) = replay(&trades, &equity, &caps, &tight);
+        assert_eq!(blocked.get("max_daily_loss_usd"), Some(&1));
+        assert_eq!(whatif.trade_count, 3);
+        assert_eq!(whatif.pnl_usd, Decimal::from(-200));
+
+        assert_eq!(
+            loosened_caps(
+                &caps,
+                &RiskCaps {
+                    max_trades_per_day: 20,
+                    ..tight
+                }
+            ),
+            vec!["max_trades_per_day".to_string()]
+        );
+    }
+}
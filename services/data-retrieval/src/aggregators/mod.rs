@@ -1,10 +1,76 @@
 // Multi-source aggregation logic
 use crate::types::*;
+use chrono::{DateTime, Duration, Utc};
 use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
 use tracing::warn;
 
-/// Aggregate prices using weighted median
-pub fn aggregate_prices(prices: &[PricePoint]) -> Result<AggregatedPrice> {
+/// Weight for a source that doesn't report a confidence
+const DEFAULT_CONFIDENCE: f64 = 0.5;
+
+/// Age at which a quote's confidence has halved
+pub const STALENESS_HALF_LIFE_SECS: f64 = 30.0;
+
+/// Floor on the decay, so a lone old quote still counts for something
+const MIN_STALENESS_FACTOR: f64 = 1e-3;
+
+/// Spread between sources past which a price is flagged unreliable
+pub const DEFAULT_MAX_SPREAD_PERCENT: f64 = 2.0;
+
+/// A source's confidence, halved for every [`STALENESS_HALF_LIFE_SECS`] its
+/// quote is old at `now` (down to a thousandth)
+pub fn decayed_confidence(point: &PricePoint, now: DateTime<Utc>) -> f64 {
+    let confidence = point
+        .confidence
+        .unwrap_or(DEFAULT_CONFIDENCE)
+        .clamp(0.0, 1.0);
+    let age_secs = (now - point.timestamp).num_milliseconds().max(0) as f64 / 1000.0;
+    let decay = 0.5_f64.powf(age_secs / STALENESS_HALF_LIFE_SECS);
+    confidence * decay.max(MIN_STALENESS_FACTOR)
+}
+
+/// Price at which half the total weight lies on either side
+///
+/// When the cumulative weight lands exactly on half at a price, the median is
+/// halfway between that price and the next. None when there's no weight.
+pub fn weighted_median(weighted: &[(Decimal, f64)]) -> Option<Decimal> {
+    let mut sorted: Vec<(Decimal, f64)> = weighted
+        .iter()
+        .copied()
+        .filter(|(_, weight)| *weight > 0.0)
+        .collect();
+    sorted.sort_by_key(|(price, _)| *price);
+    let half = sorted.iter().map(|(_, weight)| weight).sum::<f64>() / 2.0;
+    if half <= 0.0 {
+        return None;
+    }
+
+    let mut cumulative = 0.0;
+    for (i, (price, weight)) in sorted.iter().enumerate() {
+        cumulative += weight;
+        if (cumulative - half).abs() <= half * 1e-9 {
+            return Some(match sorted.get(i + 1) {
+                Some((next, _)) => (*price + *next) / Decimal::from(2),
+                None => *price,
+            });
+        }
+        if cumulative > half {
+            return Some(*price);
+        }
+    }
+    sorted.last().map(|(price, _)| *price)
+}
+
+/// Aggregate source prices into one, as of `now`
+///
+/// The price is the median weighted by each source's confidence decayed for
+/// the age of its quote, so a fresh feed outvotes a stale one. Sources more
+/// than `max_spread_percent` apart mark the result `unreliable`.
+pub fn aggregate_prices(
+    prices: &[PricePoint],
+    now: DateTime<Utc>,
+    max_spread_percent: f64,
+) -> Result<AggregatedPrice> {
     if prices.is_empty() {
         return Err(DataRetrievalError::SourceUnhealthy(
             "No price data available".to_string(),
@@ -15,42 +81,52 @@ pub fn aggregate_prices(prices: &[PricePoint]) -> Result<AggregatedPrice> {
     let asset = prices[0].asset();
     let quote = prices[0].quote().unwrap_or_else(|| "USD".to_string());
 
-    // Calculate weighted median
-    let mut weighted_prices: Vec<(&PricePoint, f64)> = prices
+    let weights: Vec<f64> = prices.iter().map(|p| decayed_confidence(p, now)).collect();
+    let total_weight: f64 = weights.iter().sum();
+    // Guard against division by zero if all sources have zero confidence
+    if total_weight < f64::EPSILON {
+        return Err(DataRetrievalError::SourceUnhealthy(
+            "All sources have zero confidence".to_string(),
+        ));
+    }
+    let weighted: Vec<(Decimal, f64)> = prices
         .iter()
-        .map(|p| (p, p.confidence.unwrap_or(0.5)))
+        .zip(&weights)
+        .map(|(p, weight)| (p.price, *weight))
         .collect();
-
-    weighted_prices.sort_by(|a, b| a.0.price.cmp(&b.0.price));
-
-    // Simple median for now (could do weighted)
-    let mid = weighted_prices.len() / 2;
-    let median_price = weighted_prices[mid].0.price;
+    let median_price = weighted_median(&weighted).ok_or_else(|| {
+        DataRetrievalError::SourceUnhealthy("All sources have zero confidence".to_string())
+    })?;
 
     // Calculate spread
     let min_price = prices.iter().map(|p| p.price).min().unwrap();
     let max_price = prices.iter().map(|p| p.price).max().unwrap();
     let avg_price = (min_price + max_price) / rust_decimal::Decimal::from(2);
-    let spread = (max_price - min_price) / avg_price * rust_decimal::Decimal::from(100);
+    let spread = if avg_price > Decimal::ZERO {
+        (max_price - min_price) / avg_price * rust_decimal::Decimal::from(100)
+    } else {
+        Decimal::ZERO
+    };
 
     let sources = prices
         .iter()
-        .map(|p| PriceSource {
+        .zip(&weights)
+        .map(|(p, weight)| PriceSource {
             source: p.source.clone(),
             price: p.price,
-            weight: p.confidence.unwrap_or(0.5),
+            weight: weight / total_weight,
             timestamp: p.timestamp,
         })
         .collect();
 
-    let total_confidence: f64 = prices.iter().map(|p| p.confidence.unwrap_or(0.5)).sum();
-    let avg_confidence = total_confidence / prices.len() as f64;
+    let avg_confidence = total_weight / prices.len() as f64;
 
-    // Warn if spread is high (>1%)
-    if spread > rust_decimal::Decimal::from(1) {
+    let spread_percent = spread.to_f64().unwrap_or(0.0);
+    let unreliable = spread_percent > max_spread_percent;
+    if unreliable {
         warn!(
-            "High price spread detected: {:.2}%",
-            spread.to_f64().unwrap_or(0.0)
+            "Sources for {} disagree by {:.2}% (limit {:.2}%), price unreliable",
+            asset, spread_percent, max_spread_percent
         );
     }
 
@@ -59,8 +135,179 @@ pub fn aggregate_prices(prices: &[PricePoint]) -> Result<AggregatedPrice> {
         quote,
         price: median_price,
         sources,
-        timestamp: chrono::Utc::now(),
+        timestamp: now,
         confidence: avg_confidence,
-        spread_percent: spread.to_f64().unwrap_or(0.0),
+        spread_percent,
+        stale: false,
+        unreliable,
+        converted: false,
+        fx_rate: None,
     })
 }
+
+/// Percent change to `spot` from the price at `since`
+///
+/// That price is the open of the latest candle starting at or before `since`.
+fn change_since(candles: &[Candle], spot: Decimal, since: DateTime<Utc>) -> Option<f64> {
+    let base = candles
+        .iter()
+        .filter(|c| c.timestamp <= since)
+        .max_by_key(|c| c.timestamp)?
+        .open;
+    if base.is_zero() {
+        return None;
+    }
+    ((spot - base) / base * Decimal::from(100)).to_f64()
+}
+
+/// Build percent changes and today's OHLC from candles plus the current spot
+///
+/// Candle timestamps are the candle open. Today's range includes the spot so
+/// it stays current between candles.
+pub fn summarize_market(candles: &[Candle], spot: Decimal, now: DateTime<Utc>) -> MarketSummary {
+    let midnight = now
+        .date_naive()
+        .and_hms_opt(0, 0, 0)
+        .expect("midnight is valid")
+        .and_utc();
+
+    let mut today: Vec<&Candle> = candles
+        .iter()
+        .filter(|c| c.timestamp >= midnight && c.timestamp <= now)
+        .collect();
+    today.sort_by_key(|c| c.timestamp);
+
+    let ohlc_today = today.first().map(|first| DailyOhlc {
+        open: first.open,
+        high: today.iter().map(|c| c.high).fold(spot, Decimal::max),
+        low: today.iter().map(|c| c.low).fold(spot, Decimal::min),
+        close: spot,
+    });
+
+    MarketSummary {
+        change_1h_pct: change_since(candles, spot, now - Duration::hours(1)),
+        change_24h_pct: change_since(candles, spot, now - Duration::hours(24)),
+        change_7d_pct: change_since(candles, spot, now - Duration::days(7)),
+        ohlc_today,
+        regime: crate::regime::classify(candles),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn candle(timestamp: DateTime<Utc>, open: i64, high: i64, low: i64, close: i64) -> Candle {
+        Candle {
+            asset: "BTC".to_string(),
+            quote: "USD".to_string(),
+            timeframe: TimeFrame::Hour1,
+            open: Decimal::from(open),
+            high: Decimal::from(high),
+            low: Decimal::from(low),
+            close: Decimal::from(close),
+            volume: Decimal::ZERO,
+            timestamp,
+        }
+    }
+
+    fn point(source: &str, price: i64, age_secs: i64, confidence: f64) -> PricePoint {
+        let now = Utc.with_ymd_and_hms(2026, 3, 10, 12, 0, 0).unwrap();
+        PricePoint::new(
+            "SOL/USD",
+            Decimal::from(price),
+            source,
+            now - Duration::seconds(age_secs),
+            Some(confidence),
+        )
+    }
+
+    #[test]
+    fn test_weighted_median_with_staleness() {
+        let now = Utc.with_ymd_and_hms(2026, 3, 10, 12, 0, 0).unwrap();
+        let d = Decimal::from;
+
+        // An outlier moves a mean but not the median
+        assert_eq!(
+            weighted_median(&[(d(100), 1.0), (d(101), 1.0), (d(500), 1.0)]),
+            Some(d(101))
+        );
+        // One heavy source carries it; an exact split lands halfway
+        assert_eq!(
+            weighted_median(&[(d(100), 0.2), (d(110), 0.9), (d(120), 0.2)]),
+            Some(d(110))
+        );
+        assert_eq!(
+            weighted_median(&[(d(100), 0.5), (d(110), 0.5)]),
+            Some(d(105))
+        );
+        assert_eq!(weighted_median(&[(d(100), 0.0)]), None);
+
+        // Confidence halves every half-life of age
+        let old = point("pyth", 100, 60, 0.8);
+        assert!((decayed_confidence(&old, now) - 0.2).abs() < 1e-9);
+        let ancient = point("pyth", 100, 3600, 0.8);
+        assert!((decayed_confidence(&ancient, now) - 0.0008).abs() < 1e-9);
+
+        // Two stale sources agree, one fresh one doesn't: the fresh one wins
+        let prices = [
+            point("coingecko", 100, 120, 0.9),
+            point("pyth", 100, 120, 0.9),
+            point("binance", 102, 0, 0.9),
+        ];
+        let agg = aggregate_prices(&prices, now, DEFAULT_MAX_SPREAD_PERCENT).unwrap();
+        assert_eq!(agg.price, d(102));
+        assert!(agg.sources[2].weight > 0.8);
+        let total: f64 = agg.sources.iter().map(|s| s.weight).sum();
+        assert!((total - 1.0).abs() < 1e-9);
+
+        // Fresh, they outvote it
+        let prices = [
+            point("coingecko", 100, 0, 0.9),
+            point("pyth", 100, 0, 0.9),
+            point("binance", 102, 0, 0.9),
+        ];
+        let agg = aggregate_prices(&prices, now, DEFAULT_MAX_SPREAD_PERCENT).unwrap();
+        assert_eq!(agg.price, d(100));
+        assert!(!agg.unreliable);
+
+        // A ~2% spread passes the default limit but not a tighter one
+        assert!(aggregate_prices(&prices, now, 1.0).unwrap().unreliable);
+        let prices = [point("coingecko", 100, 0, 0.9), point("pyth", 110, 0, 0.9)];
+        let agg = aggregate_prices(&prices, now, DEFAULT_MAX_SPREAD_PERCENT).unwrap();
+        assert!(agg.unreliable);
+    }
+
+    #[test]
+    fn test_summarize_market() {
+        let now = Utc.with_ymd_and_hms(2026, 3, 10, 2, 30, 0).unwrap();
+        let hours_ago = |h: i64| now - Duration::hours(h);
+        let candles = vec![
+            candle(hours_ago(30), 80, 95, 75, 90),
+            candle(hours_ago(3), 100, 104, 98, 100), // yesterday 23:30
+            candle(hours_ago(2), 100, 110, 99, 105), // 00:30 today
+            candle(hours_ago(1), 100, 106, 95, 96),
+        ];
+
+        let summary = summarize_market(&candles, Decimal::from(120), now);
+        assert_eq!(summary.change_1h_pct, Some(20.0));
+        assert_eq!(summary.change_24h_pct, Some(50.0));
+        // Nothing reaches back a week
+        assert_eq!(summary.change_7d_pct, None);
+        assert_eq!(
+            summary.ohlc_today,
+            Some(DailyOhlc {
+                open: Decimal::from(100),
+                high: Decimal::from(120),
+                low: Decimal::from(95),
+                close: Decimal::from(120),
+            })
+        );
+
+        assert_eq!(
+            summarize_market(&[], Decimal::ONE, now),
+            MarketSummary::default()
+        );
+    }
+}
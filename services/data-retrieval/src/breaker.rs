@@ -0,0 +1,141 @@
+//! Per-source circuit breaker
+//!
+//! A source that keeps failing (down, rate limiting us, timing out) would
+//! otherwise be called on every request and add its timeout to each one.
+//! After `threshold` consecutive failures the breaker opens and the source is
+//! skipped. Once `cooldown` has passed it goes half-open: one request is let
+//! through as a probe, and its outcome closes the breaker or opens it for
+//! another cooldown.
+//!
+//! "Not found" answers aren't failures; the source is up, it just doesn't
+//! list the pair.
+
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Consecutive failures that open a breaker
+pub const DEFAULT_FAILURE_THRESHOLD: u32 = 5;
+/// How long an open breaker skips its source before probing
+pub const DEFAULT_COOLDOWN: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BreakerState {
+    /// Requests go through
+    Closed,
+    /// Source skipped until the cooldown passes
+    Open,
+    /// Cooldown over; the next request (or the one in flight) is a probe
+    HalfOpen,
+}
+
+#[derive(Debug, Default)]
+struct Inner {
+    consecutive_failures: u32,
+    opened_at: Option<Instant>,
+    /// When the half-open probe in flight was let through; a probe that
+    /// never reports back (its request was dropped) expires after a cooldown
+    probe_started: Option<Instant>,
+}
+
+#[derive(Debug)]
+pub struct CircuitBreaker {
+    threshold: u32,
+    cooldown: Duration,
+    inner: Mutex<Inner>,
+}
+
+impl Default for CircuitBreaker {
+    fn default() -> Self {
+        Self::new(DEFAULT_FAILURE_THRESHOLD, DEFAULT_COOLDOWN)
+    }
+}
+
+impl CircuitBreaker {
+    pub fn new(threshold: u32, cooldown: Duration) -> Self {
+        Self {
+            threshold: threshold.max(1),
+            cooldown,
+            inner: Mutex::new(Inner::default()),
+        }
+    }
+
+    /// Whether to call the source now; claims the probe when half-open
+    pub fn allow(&self) -> bool {
+        let mut inner = self.inner.lock().unwrap();
+        let Some(opened_at) = inner.opened_at else {
+            return true;
+        };
+        let probe_pending = inner
+            .probe_started
+            .is_some_and(|started| started.elapsed() < self.cooldown);
+        if opened_at.elapsed() >= self.cooldown && !probe_pending {
+            inner.probe_started = Some(Instant::now());
+            true
+        } else {
+            false
+        }
+    }
+
+    pub fn record_success(&self) {
+        *self.inner.lock().unwrap() = Inner::default();
+    }
+
+    pub fn record_failure(&self) {
+        let mut inner = self.inner.lock().unwrap();
+        inner.consecutive_failures = inner.consecutive_failures.saturating_add(1);
+        if inner.probe_started.is_some() || inner.consecutive_failures >= self.threshold {
+            inner.opened_at = Some(Instant::now());
+            inner.probe_started = None;
+        }
+    }
+
+    pub fn state(&self) -> BreakerState {
+        let inner = self.inner.lock().unwrap();
+        match inner.opened_at {
+            None => BreakerState::Closed,
+            Some(_) if inner.probe_started.is_some() => BreakerState::HalfOpen,
+            Some(opened_at) if opened_at.elapsed() >= self.cooldown => BreakerState::HalfOpen,
+            Some(_) => BreakerState::Open,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_breaker_opens_and_probes() {
+        let breaker = CircuitBreaker::new(3, Duration::from_millis(50));
+        breaker.record_failure();
+        breaker.record_failure();
+        assert!(breaker.allow());
+        // A success resets the count
+        breaker.record_success();
+        breaker.record_failure();
+        breaker.record_failure();
+        assert_eq!(breaker.state(), BreakerState::Closed);
+
+        breaker.record_failure();
+        assert_eq!(breaker.state(), BreakerState::Open);
+        assert!(!breaker.allow());
+
+        // After the cooldown exactly one probe goes through; its failure
+        // reopens the breaker straight away
+        std::thread::sleep(Duration::from_millis(60));
+        assert_eq!(breaker.state(), BreakerState::HalfOpen);
+        assert!(breaker.allow());
+        assert!(!breaker.allow());
+        breaker.record_failure();
+        assert_eq!(breaker.state(), BreakerState::Open);
+
+        // A successful probe closes it
+        std::thread::sleep(Duration::from_millis(60));
+        assert!(breaker.allow());
+        breaker.record_success();
+        assert_eq!(breaker.state(), BreakerState::Closed);
+        assert!(breaker.allow());
+    }
+}
@@ -0,0 +1,154 @@
+//! In-process LRU cache with per-entry TTL
+//!
+//! The first cache tier: no network hop, bounded by entry count. Expired
+//! entries are dropped when read; when the cache is full the least recently
+//! used entry makes room, expired or not.
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use super::Cache;
+
+struct Entry {
+    value: String,
+    expires_at: Instant,
+    /// Position in `Inner::order`
+    last_used: u64,
+}
+
+#[derive(Default)]
+struct Inner {
+    entries: HashMap<String, Entry>,
+    /// Keys by last use, oldest first
+    order: BTreeMap<u64, String>,
+    tick: u64,
+}
+
+impl Inner {
+    fn touch(&mut self, key: &str) {
+        self.tick += 1;
+        let tick = self.tick;
+        if let Some(entry) = self.entries.get_mut(key) {
+            self.order.remove(&entry.last_used);
+            entry.last_used = tick;
+            self.order.insert(tick, key.to_string());
+        }
+    }
+
+    fn remove(&mut self, key: &str) {
+        if let Some(entry) = self.entries.remove(key) {
+            self.order.remove(&entry.last_used);
+        }
+    }
+}
+
+pub struct MemoryCache {
+    capacity: usize,
+    inner: Mutex<Inner>,
+}
+
+impl MemoryCache {
+    /// Cache holding at most `capacity` entries
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            inner: Mutex::new(Inner::default()),
+        }
+    }
+
+    pub fn get(&self, key: &str) -> Option<String> {
+        let mut inner = self.inner.lock().unwrap();
+        let entry = inner.entries.get(key)?;
+        if entry.expires_at <= Instant::now() {
+            inner.remove(key);
+            return None;
+        }
+        let value = entry.value.clone();
+        inner.touch(key);
+        Some(value)
+    }
+
+    pub fn insert(&self, key: &str, value: String, ttl: Duration) {
+        let mut inner = self.inner.lock().unwrap();
+        inner.remove(key);
+        while inner.entries.len() >= self.capacity {
+            let Some((_, oldest)) = inner.order.pop_first() else {
+                break;
+            };
+            inner.entries.remove(&oldest);
+        }
+        inner.entries.insert(
+            key.to_string(),
+            Entry {
+                value,
+                expires_at: Instant::now() + ttl,
+                last_used: 0,
+            },
+        );
+        inner.touch(key);
+    }
+
+    pub fn remove(&self, key: &str) {
+        self.inner.lock().unwrap().remove(key);
+    }
+
+    /// Entries held, including expired ones not yet read
+    pub fn len(&self) -> usize {
+        self.inner.lock().unwrap().entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+#[async_trait::async_trait]
+impl Cache for MemoryCache {
+    fn tier(&self) -> &'static str {
+        "memory"
+    }
+
+    async fn get(&self, key: &str) -> anyhow::Result<Option<String>> {
+        Ok(MemoryCache::get(self, key))
+    }
+
+    async fn set(&self, key: &str, value: &str, ttl: Duration) -> anyhow::Result<()> {
+        self.insert(key, value.to_string(), ttl);
+        Ok(())
+    }
+
+    async fn remove(&self, key: &str) -> anyhow::Result<()> {
+        MemoryCache::remove(self, key);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lru_eviction_and_ttl() {
+        let cache = MemoryCache::new(2);
+        let minute = Duration::from_secs(60);
+        cache.insert("a", "1".to_string(), minute);
+        cache.insert("b", "2".to_string(), minute);
+        // Reading `a` makes `b` the least recently used
+        assert_eq!(cache.get("a").as_deref(), Some("1"));
+        cache.insert("c", "3".to_string(), minute);
+        assert_eq!(cache.get("b"), None);
+        assert_eq!(cache.get("a").as_deref(), Some("1"));
+        assert_eq!(cache.get("c").as_deref(), Some("3"));
+        assert_eq!(cache.len(), 2);
+
+        // Overwriting doesn't grow the cache
+        cache.insert("c", "4".to_string(), minute);
+        assert_eq!(cache.get("c").as_deref(), Some("4"));
+        assert_eq!(cache.len(), 2);
+
+        cache.insert("a", "5".to_string(), Duration::ZERO);
+        assert_eq!(cache.get("a"), None);
+        assert_eq!(cache.len(), 1);
+    }
+}
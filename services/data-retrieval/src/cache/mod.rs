@@ -1,7 +1,31 @@
-// Redis cache implementation
-use crate::types::*;
+//! Caching for prices, candles and token data
+//!
+//! [`TieredCache`] is what callers use: an in-process LRU ([`MemoryCache`])
+//! in front of Redis when `REDIS_URL` is set. Both tiers implement [`Cache`],
+//! a string store with per-entry TTLs. Without Redis, or while it's down,
+//! the memory tier caches on its own.
+
+mod memory;
+mod tiered;
+
+pub use memory::MemoryCache;
+pub use tiered::{Namespace, SharedTierStatus, TierStats, TieredCache};
+
 use redis::AsyncCommands;
-use serde_json;
+use std::time::Duration;
+
+/// One cache tier holding JSON strings
+#[async_trait::async_trait]
+pub trait Cache: Send + Sync {
+    /// Tier name in stats (`memory`, `redis`)
+    fn tier(&self) -> &'static str;
+
+    async fn get(&self, key: &str) -> anyhow::Result<Option<String>>;
+
+    async fn set(&self, key: &str, value: &str, ttl: Duration) -> anyhow::Result<()>;
+
+    async fn remove(&self, key: &str) -> anyhow::Result<()>;
+}
 
 pub struct RedisCache {
     client: redis::aio::MultiplexedConnection,
@@ -15,54 +39,49 @@ impl RedisCache {
         Ok(Self { client: conn })
     }
 
-    /// Get cached price
-    pub async fn get_price(
-        &self,
-        asset: &str,
-        quote: &str,
-    ) -> anyhow::Result<Option<AggregatedPrice>> {
-        let key = format!("price:{}:{}", asset.to_uppercase(), quote.to_uppercase());
-        let value: Option<String> = self.client.clone().get(key).await?;
-
-        match value {
-            Some(json) => {
-                let price: AggregatedPrice = serde_json::from_str(&json)?;
-                Ok(Some(price))
+    /// Connect, retrying until `wait` has elapsed
+    ///
+    /// Lets the service start alongside Redis in compose/CI without racing it.
+    pub async fn connect_with_retry(
+        redis_url: &str,
+        wait: std::time::Duration,
+    ) -> anyhow::Result<Self> {
+        let deadline = tokio::time::Instant::now() + wait;
+        let mut delay = std::time::Duration::from_millis(500);
+        loop {
+            match Self::new(redis_url).await {
+                Ok(cache) => return Ok(cache),
+                Err(e) if tokio::time::Instant::now() + delay < deadline => {
+                    tracing::warn!("Redis not ready ({}), retrying in {:?}", e, delay);
+                    tokio::time::sleep(delay).await;
+                    delay = (delay * 2).min(std::time::Duration::from_secs(5));
+                }
+                Err(e) => return Err(e),
             }
-            None => Ok(None),
         }
     }
+}
 
-    /// Cache price with TTL
-    pub async fn set_price(
-        &self,
-        asset: &str,
-        quote: &str,
-        price: &AggregatedPrice,
-    ) -> anyhow::Result<()> {
-        let key = format!("price:{}:{}", asset.to_uppercase(), quote.to_uppercase());
-        let json = serde_json::to_string(price)?;
+#[async_trait::async_trait]
+impl Cache for RedisCache {
+    fn tier(&self) -> &'static str {
+        "redis"
+    }
 
-        // Cache for 60 seconds - explicit type annotation to avoid never type fallback
-        let _: () = self.client.clone().set_ex(key, json, 60).await?;
+    async fn get(&self, key: &str) -> anyhow::Result<Option<String>> {
+        Ok(self.client.clone().get(key).await?)
+    }
 
+    /// The TTL is rounded up to whole seconds
+    async fn set(&self, key: &str, value: &str, ttl: Duration) -> anyhow::Result<()> {
+        let secs = ttl.as_secs() + u64::from(ttl.subsec_nanos() > 0);
+        // Explicit type annotation to avoid never type fallback
+        let _: () = self.client.clone().set_ex(key, value, secs.max(1)).await?;
         Ok(())
     }
 
-    /// Invalidate cached price
-    pub async fn invalidate_price(&self, asset: &str, quote: &str) -> anyhow::Result<()> {
-        let key = format!("price:{}:{}", asset.to_uppercase(), quote.to_uppercase());
+    async fn remove(&self, key: &str) -> anyhow::Result<()> {
         let _: () = self.client.clone().del(key).await?;
         Ok(())
     }
 }
-
-// Stub for when Redis is not available
-#[derive(Default)]
-pub struct NoOpCache;
-
-impl NoOpCache {
-    pub fn new() -> Self {
-        Self
-    }
-}
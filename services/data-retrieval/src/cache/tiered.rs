@@ -0,0 +1,423 @@
+//! Two-tier cache: in-process LRU in front of an optional shared tier
+//!
+//! Reads try memory, then the shared tier (Redis), and copy a shared hit
+//! into memory. Writes go to both. Values are JSON, so any serde type can be
+//! cached, and keys are prefixed by [`Namespace`] so prices, candles and
+//! token data never collide. A failing shared tier is treated as a miss;
+//! caching is an optimisation, never a reason to fail a request.
+//!
+//! A shared tier that keeps failing is taken out of the path by a
+//! [`CircuitBreaker`]: reads and writes go to memory alone until a probe
+//! after the cooldown succeeds. The shared tier can also be attached after
+//! startup ([`TieredCache::attach_l2`]), so a service that came up before
+//! Redis starts using it once it's reachable.
+//!
+//! Hits and misses are counted per tier and namespace (see
+//! [`TieredCache::stats`]).
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::Duration;
+use tracing::{debug, info, warn};
+
+use super::memory::MemoryCache;
+use super::Cache;
+use crate::breaker::{BreakerState, CircuitBreaker};
+
+/// What a cache entry holds
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Namespace {
+    /// Aggregated prices, keyed `ASSET:QUOTE`
+    Prices,
+    /// Source candles, keyed `ASSET:QUOTE:timeframe`
+    Candles,
+    /// Token names, symbols and decimals, keyed by mint
+    TokenMetadata,
+    /// Token safety checks, keyed by mint
+    TokenSafety,
+}
+
+impl Namespace {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Namespace::Prices => "prices",
+            Namespace::Candles => "candles",
+            Namespace::TokenMetadata => "token_metadata",
+            Namespace::TokenSafety => "token_safety",
+        }
+    }
+
+    /// Key prefix in the shared tier (`price` predates the other namespaces)
+    fn prefix(&self) -> &'static str {
+        match self {
+            Namespace::Prices => "price",
+            Namespace::Candles => "candles",
+            Namespace::TokenMetadata => "token_meta",
+            Namespace::TokenSafety => "token_safety",
+        }
+    }
+
+    /// How long a shared-tier hit is kept in memory (the shared tier doesn't
+    /// say how long the entry has left)
+    fn promote_ttl(&self) -> Duration {
+        match self {
+            Namespace::Prices => Duration::from_secs(30),
+            Namespace::Candles => Duration::from_secs(60),
+            Namespace::TokenMetadata => Duration::from_secs(3600),
+            Namespace::TokenSafety => Duration::from_secs(300),
+        }
+    }
+}
+
+/// (hits, misses) per (tier, namespace)
+type Counts = BTreeMap<(&'static str, Namespace), (u64, u64)>;
+
+/// Hit and miss counts for one tier and namespace
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TierStats {
+    pub tier: &'static str,
+    pub namespace: Namespace,
+    pub hits: u64,
+    pub misses: u64,
+    /// Hits over lookups, 0 before the first lookup
+    pub hit_rate: f64,
+}
+
+/// Whether the shared tier is in use
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SharedTierStatus {
+    pub tier: &'static str,
+    /// `open` while it's failing and bypassed
+    pub state: BreakerState,
+}
+
+pub struct TieredCache {
+    l1: MemoryCache,
+    l2: RwLock<Option<Arc<dyn Cache>>>,
+    l2_breaker: CircuitBreaker,
+    counts: Mutex<Counts>,
+}
+
+impl TieredCache {
+    /// Memory-only cache holding at most `capacity` entries
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            l1: MemoryCache::new(capacity),
+            l2: RwLock::new(None),
+            l2_breaker: CircuitBreaker::default(),
+            counts: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    /// Back the memory tier with a shared one
+    pub fn with_l2(self, l2: Arc<dyn Cache>) -> Self {
+        self.attach_l2(l2);
+        self
+    }
+
+    /// Back the memory tier with a shared one from now on
+    pub fn attach_l2(&self, l2: Arc<dyn Cache>) {
+        self.l2_breaker.record_success();
+        *self.l2.write().unwrap() = Some(l2);
+    }
+
+    /// Breaker taking a failing shared tier out of the path
+    pub fn with_l2_breaker(mut self, breaker: CircuitBreaker) -> Self {
+        self.l2_breaker = breaker;
+        self
+    }
+
+    pub fn has_l2(&self) -> bool {
+        self.l2.read().unwrap().is_some()
+    }
+
+    pub fn l2_status(&self) -> Option<SharedTierStatus> {
+        let l2 = self.l2.read().unwrap();
+        l2.as_ref().map(|l2| SharedTierStatus {
+            tier: l2.tier(),
+            state: self.l2_breaker.state(),
+        })
+    }
+
+    /// The shared tier, unless its breaker has it bypassed
+    fn l2(&self) -> Option<Arc<dyn Cache>> {
+        let l2 = self.l2.read().unwrap().clone()?;
+        self.l2_breaker.allow().then_some(l2)
+    }
+
+    fn l2_result<T>(
+        &self,
+        l2: &dyn Cache,
+        op: &str,
+        key: &str,
+        result: anyhow::Result<T>,
+    ) -> Option<T> {
+        match result {
+            Ok(value) => {
+                if self.l2_breaker.state() != BreakerState::Closed {
+                    info!("{} cache is back, using it again", l2.tier());
+                }
+                self.l2_breaker.record_success();
+                Some(value)
+            }
+            Err(e) => {
+                debug!("{} cache {} failed for {}: {}", l2.tier(), op, key, e);
+                let was_closed = self.l2_breaker.state() == BreakerState::Closed;
+                self.l2_breaker.record_failure();
+                if was_closed && self.l2_breaker.state() == BreakerState::Open {
+                    warn!("{} cache keeps failing, caching in memory only", l2.tier());
+                }
+                None
+            }
+        }
+    }
+
+    pub async fn get<T: DeserializeOwned>(&self, namespace: Namespace, key: &str) -> Option<T> {
+        let key = format!("{}:{}", namespace.prefix(), key);
+
+        if let Some(value) = self.l1.get(&key).and_then(|json| decode(&key, &json)) {
+            self.count(self.l1.tier(), namespace, true);
+            return Some(value);
+        }
+        self.count(self.l1.tier(), namespace, false);
+
+        let l2 = self.l2()?;
+        let result = l2.get(&key).await;
+        let json = self.l2_result(l2.as_ref(), "read", &key, result).flatten();
+        let value = json.and_then(|json| {
+            let value = decode(&key, &json)?;
+            self.l1.insert(&key, json, namespace.promote_ttl());
+            Some(value)
+        });
+        self.count(l2.tier(), namespace, value.is_some());
+        value
+    }
+
+    pub async fn set<T: Serialize>(
+        &self,
+        namespace: Namespace,
+        key: &str,
+        value: &T,
+        ttl: Duration,
+    ) {
+        let key = format!("{}:{}", namespace.prefix(), key);
+        let json = match serde_json::to_string(value) {
+            Ok(json) => json,
+            Err(e) => {
+                debug!("Not caching {}: {}", key, e);
+                return;
+            }
+        };
+        if let Some(l2) = self.l2() {
+            let result = l2.set(&key, &json, ttl).await;
+            self.l2_result(l2.as_ref(), "write", &key, result);
+        }
+        self.l1.insert(&key, json, ttl);
+    }
+
+    pub async fn remove(&self, namespace: Namespace, key: &str) {
+        let key = format!("{}:{}", namespace.prefix(), key);
+        self.l1.remove(&key);
+        if let Some(l2) = self.l2() {
+            let result = l2.remove(&key).await;
+            self.l2_result(l2.as_ref(), "delete", &key, result);
+        }
+    }
+
+    /// Counts for every tier and namespace looked up so far
+    pub fn stats(&self) -> Vec<TierStats> {
+        self.counts
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(&(tier, namespace), &(hits, misses))| TierStats {
+                tier,
+                namespace,
+                hits,
+                misses,
+                hit_rate: if hits + misses > 0 {
+                    hits as f64 / (hits + misses) as f64
+                } else {
+                    0.0
+                },
+            })
+            .collect()
+    }
+
+    fn count(&self, tier: &'static str, namespace: Namespace, hit: bool) {
+        let mut counts = self.counts.lock().unwrap();
+        let (hits, misses) = counts.entry((tier, namespace)).or_default();
+        if hit {
+            *hits += 1;
+        } else {
+            *misses += 1;
+        }
+    }
+}
+
+/// Decode a cached value; an undecodable entry (older format) is a miss
+fn decode<T: DeserializeOwned>(key: &str, json: &str) -> Option<T> {
+    serde_json::from_str(json)
+        .map_err(|e| debug!("Ignoring unreadable cache entry {}: {}", key, e))
+        .ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Memory cache standing in for Redis
+    struct SharedTier(MemoryCache);
+
+    #[async_trait::async_trait]
+    impl Cache for SharedTier {
+        fn tier(&self) -> &'static str {
+            "redis"
+        }
+
+        async fn get(&self, key: &str) -> anyhow::Result<Option<String>> {
+            Ok(self.0.get(key))
+        }
+
+        async fn set(&self, key: &str, value: &str, ttl: Duration) -> anyhow::Result<()> {
+            self.0.insert(key, value.to_string(), ttl);
+            Ok(())
+        }
+
+        async fn remove(&self, key: &str) -> anyhow::Result<()> {
+            self.0.remove(key);
+            Ok(())
+        }
+    }
+
+    /// Shared tier that can be taken down, counting the calls it gets
+    #[derive(Default)]
+    struct FlakyTier {
+        down: std::sync::atomic::AtomicBool,
+        calls: std::sync::atomic::AtomicUsize,
+    }
+
+    impl FlakyTier {
+        fn call(&self) -> anyhow::Result<()> {
+            use std::sync::atomic::Ordering;
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            if self.down.load(Ordering::SeqCst) {
+                anyhow::bail!("connection refused");
+            }
+            Ok(())
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl Cache for FlakyTier {
+        fn tier(&self) -> &'static str {
+            "redis"
+        }
+
+        async fn get(&self, _key: &str) -> anyhow::Result<Option<String>> {
+            self.call().map(|_| None)
+        }
+
+        async fn set(&self, _key: &str, _value: &str, _ttl: Duration) -> anyhow::Result<()> {
+            self.call()
+        }
+
+        async fn remove(&self, _key: &str) -> anyhow::Result<()> {
+            self.call()
+        }
+    }
+
+    fn stat(cache: &TieredCache, tier: &str, namespace: Namespace) -> (u64, u64) {
+        cache
+            .stats()
+            .iter()
+            .find(|s| s.tier == tier && s.namespace == namespace)
+            .map_or((0, 0), |s| (s.hits, s.misses))
+    }
+
+    #[tokio::test]
+    async fn test_tiers_and_stats() {
+        let shared = Arc::new(SharedTier(MemoryCache::new(10)));
+        let minute = Duration::from_secs(60);
+        let writer = TieredCache::new(10).with_l2(shared.clone());
+        writer
+            .set(Namespace::Prices, "SOL:USD", &150u32, minute)
+            .await;
+        assert_eq!(shared.0.get("price:SOL:USD").as_deref(), Some("150"));
+
+        // Another instance finds it in the shared tier and keeps a copy
+        let reader = TieredCache::new(10).with_l2(shared.clone());
+        assert_eq!(
+            reader.get::<u32>(Namespace::Prices, "SOL:USD").await,
+            Some(150)
+        );
+        assert_eq!(stat(&reader, "memory", Namespace::Prices), (0, 1));
+        assert_eq!(stat(&reader, "redis", Namespace::Prices), (1, 0));
+        assert_eq!(
+            reader.get::<u32>(Namespace::Prices, "SOL:USD").await,
+            Some(150)
+        );
+        assert_eq!(stat(&reader, "memory", Namespace::Prices), (1, 1));
+
+        // Namespaces don't collide
+        assert_eq!(reader.get::<u32>(Namespace::Candles, "SOL:USD").await, None);
+        assert_eq!(stat(&reader, "redis", Namespace::Candles), (0, 1));
+
+        reader.remove(Namespace::Prices, "SOL:USD").await;
+        assert_eq!(
+            writer.get::<u32>(Namespace::Prices, "SOL:USD").await,
+            Some(150)
+        );
+        assert_eq!(shared.0.get("price:SOL:USD"), None);
+
+        // Memory only: misses stop at the first tier
+        let local = TieredCache::new(10);
+        assert_eq!(local.get::<u32>(Namespace::TokenSafety, "mint").await, None);
+        assert!(local.stats().iter().all(|s| s.tier == "memory"));
+    }
+
+    #[tokio::test]
+    async fn test_failing_shared_tier_is_bypassed() {
+        use std::sync::atomic::Ordering;
+
+        let flaky = Arc::new(FlakyTier::default());
+        let cache =
+            TieredCache::new(10).with_l2_breaker(CircuitBreaker::new(2, Duration::from_millis(50)));
+        assert!(cache.l2_status().is_none());
+
+        // Attached after startup
+        cache.attach_l2(flaky.clone());
+        assert_eq!(cache.l2_status().unwrap().state, BreakerState::Closed);
+
+        flaky.down.store(true, Ordering::SeqCst);
+        let minute = Duration::from_secs(60);
+        cache
+            .set(Namespace::Prices, "SOL:USD", &150u32, minute)
+            .await;
+        cache
+            .set(Namespace::Prices, "BTC:USD", &65_000u32, minute)
+            .await;
+        assert_eq!(cache.l2_status().unwrap().state, BreakerState::Open);
+
+        // Memory keeps serving while the shared tier is skipped
+        cache
+            .set(Namespace::Prices, "ETH:USD", &3_000u32, minute)
+            .await;
+        assert_eq!(
+            cache.get::<u32>(Namespace::Prices, "ETH:USD").await,
+            Some(3_000)
+        );
+        assert_eq!(cache.get::<u32>(Namespace::Prices, "JUP:USD").await, None);
+        assert_eq!(flaky.calls.load(Ordering::SeqCst), 2);
+
+        // After the cooldown a successful probe puts it back in the path
+        flaky.down.store(false, Ordering::SeqCst);
+        tokio::time::sleep(Duration::from_millis(60)).await;
+        assert_eq!(cache.get::<u32>(Namespace::Prices, "JUP:USD").await, None);
+        assert_eq!(cache.l2_status().unwrap().state, BreakerState::Closed);
+        assert_eq!(flaky.calls.load(Ordering::SeqCst), 3);
+    }
+}
@@ -0,0 +1,190 @@
+//! OHLCV bars built from a trade stream
+//!
+//! Streaming sources like Binance only report individual trades. The builder
+//! rolls them into 1m, 5m and 1h bars per pair, keeping the newest
+//! `capacity` bars of each series in memory. The last bar of a series is the
+//! one still forming; every earlier bar is closed. Trades for a bar that has
+//! already closed (a late or replayed trade) are dropped.
+
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+
+use crate::{Candle, CandleKey, TimeFrame};
+
+/// Timeframes every trade is rolled into
+pub const BAR_TIMEFRAMES: [TimeFrame; 3] =
+    [TimeFrame::Minute1, TimeFrame::Minute5, TimeFrame::Hour1];
+/// Bars kept per pair and timeframe by default (over 8 hours of 1m bars)
+pub const DEFAULT_SERIES_CAPACITY: usize = 500;
+/// Upper bound on series (new pairs are ignored once reached)
+const MAX_SERIES: usize = 3000;
+
+/// One executed trade from a stream
+#[derive(Debug, Clone, PartialEq)]
+pub struct Trade {
+    pub asset: String,
+    pub quote: String,
+    pub price: Decimal,
+    pub quantity: Decimal,
+    pub timestamp: DateTime<Utc>,
+}
+
+/// Bounded bar series per (pair, timeframe)
+pub struct CandleBuilder {
+    capacity: usize,
+    series: Mutex<HashMap<CandleKey, VecDeque<Candle>>>,
+}
+
+impl Default for CandleBuilder {
+    fn default() -> Self {
+        Self::new(DEFAULT_SERIES_CAPACITY)
+    }
+}
+
+impl CandleBuilder {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            series: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Fold a trade into the bars of every [`BAR_TIMEFRAMES`] series
+    pub fn ingest(&self, trade: &Trade) {
+        let asset = trade.asset.to_uppercase();
+        let quote = trade.quote.to_uppercase();
+        let mut series = self.series.lock().unwrap();
+
+        for timeframe in BAR_TIMEFRAMES {
+            let key = (asset.clone(), quote.clone(), timeframe.as_str());
+            if !series.contains_key(&key) && series.len() >= MAX_SERIES {
+                continue;
+            }
+            let bars = series.entry(key).or_default();
+            let start = bucket_start(trade.timestamp, timeframe);
+
+            match bars.back_mut() {
+                Some(bar) if bar.timestamp == start => {
+                    bar.high = bar.high.max(trade.price);
+                    bar.low = bar.low.min(trade.price);
+                    bar.close = trade.price;
+                    bar.volume += trade.quantity;
+                }
+                Some(bar) if bar.timestamp > start => {}
+                _ => {
+                    bars.push_back(Candle {
+                        asset: asset.clone(),
+                        quote: quote.clone(),
+                        timeframe,
+                        open: trade.price,
+                        high: trade.price,
+                        low: trade.price,
+                        close: trade.price,
+                        volume: trade.quantity,
+                        timestamp: start,
+                    });
+                    if bars.len() > self.capacity {
+                        bars.pop_front();
+                    }
+                }
+            }
+        }
+    }
+
+    /// Newest `limit` bars for a pair, oldest first (the last may be forming)
+    pub fn candles(
+        &self,
+        asset: &str,
+        quote: &str,
+        timeframe: TimeFrame,
+        limit: usize,
+    ) -> Vec<Candle> {
+        let key = (
+            asset.to_uppercase(),
+            quote.to_uppercase(),
+            timeframe.as_str(),
+        );
+        let series = self.series.lock().unwrap();
+        let Some(bars) = series.get(&key) else {
+            return Vec::new();
+        };
+        bars.iter()
+            .skip(bars.len().saturating_sub(limit))
+            .cloned()
+            .collect()
+    }
+}
+
+/// Start of the `timeframe` bar containing `at`
+fn bucket_start(at: DateTime<Utc>, timeframe: TimeFrame) -> DateTime<Utc> {
+    let secs = timeframe.to_seconds();
+    let start = at.timestamp().div_euclid(secs) * secs;
+    DateTime::from_timestamp(start, 0).unwrap_or(at)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn trade(secs: i64, price: i64, quantity: i64) -> Trade {
+        Trade {
+            asset: "btc".to_string(),
+            quote: "USDT".to_string(),
+            price: Decimal::from(price),
+            quantity: Decimal::from(quantity),
+            timestamp: DateTime::from_timestamp(1_700_000_020 + secs, 0).unwrap(),
+        }
+    }
+
+    #[test]
+    fn test_trades_roll_into_bars() {
+        let builder = CandleBuilder::new(2);
+        // 1_700_000_020 is 40s into a minute
+        for (secs, price, quantity) in [(0, 100, 1), (5, 104, 2), (10, 98, 1), (19, 101, 3)] {
+            builder.ingest(&trade(secs, price, quantity));
+        }
+        builder.ingest(&trade(20, 102, 1)); // next minute
+
+        let minute = builder.candles("BTC", "usdt", TimeFrame::Minute1, 10);
+        assert_eq!(minute.len(), 2);
+        let first = &minute[0];
+        assert_eq!(first.timestamp.timestamp() % 60, 0);
+        assert_eq!(
+            (first.open, first.high, first.low, first.close, first.volume),
+            (
+                Decimal::from(100),
+                Decimal::from(104),
+                Decimal::from(98),
+                Decimal::from(101),
+                Decimal::from(7)
+            )
+        );
+        assert_eq!(minute[1].open, Decimal::from(102));
+
+        // Same trades, one bar at 5m and 1h
+        let hour = builder.candles("BTC", "USDT", TimeFrame::Hour1, 10);
+        assert_eq!(hour.len(), 1);
+        assert_eq!(hour[0].volume, Decimal::from(8));
+        assert_eq!(hour[0].close, Decimal::from(102));
+
+        // A late trade for the closed minute is dropped
+        builder.ingest(&trade(1, 500, 1));
+        let minute = builder.candles("BTC", "USDT", TimeFrame::Minute1, 10);
+        assert_eq!(minute[0].high, Decimal::from(104));
+
+        // Capacity bounds the series; limit takes the newest
+        builder.ingest(&trade(80, 103, 1));
+        let minute = builder.candles("BTC", "USDT", TimeFrame::Minute1, 10);
+        assert_eq!(minute.len(), 2);
+        assert_eq!(minute[0].open, Decimal::from(102));
+        assert_eq!(
+            builder.candles("BTC", "USDT", TimeFrame::Minute1, 1)[0].open,
+            Decimal::from(103)
+        );
+        assert!(builder
+            .candles("ETH", "USDT", TimeFrame::Minute1, 10)
+            .is_empty());
+    }
+}
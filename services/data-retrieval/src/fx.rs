@@ -0,0 +1,161 @@
+//! FX conversion for non-USD quotes
+//!
+//! Sources quote in USD (or USD stablecoins). A price asked for in another
+//! fiat currency is fetched in USD and converted with the rate from that
+//! currency's FX pair (`EURUSD`, `USDJPY`, ...), which the FX sources price
+//! like any other asset. Rates are aggregated prices, so they're cached,
+//! coalesced and served stale the same way. A converted price carries
+//! `converted: true` and the rate it used.
+
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use serde::Serialize;
+
+use crate::types::{AggregatedPrice, DataRetrievalError, Result};
+use crate::units;
+
+pub const USD: &str = "USD";
+
+/// How many units of a currency one USD buys
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FxRate {
+    pub currency: String,
+    pub per_usd: Decimal,
+    /// FX pair the rate was derived from
+    pub pair: &'static str,
+    pub timestamp: DateTime<Utc>,
+    pub stale: bool,
+    pub unreliable: bool,
+}
+
+/// FX pair pricing `currency` against USD, and whether it's quoted as USD
+/// per unit of `currency` (`EURUSD`) rather than `currency` per USD (`USDJPY`)
+pub fn fx_pair(currency: &str) -> Option<(&'static str, bool)> {
+    let currency = currency.to_ascii_uppercase();
+    if currency == USD {
+        return None;
+    }
+    units::FX_PAIRS
+        .iter()
+        .find_map(|pair| match units::fx_legs(pair)? {
+            (base, USD) if base == currency => Some((*pair, true)),
+            (USD, quote) if quote == currency => Some((*pair, false)),
+            _ => None,
+        })
+}
+
+/// Fiat currencies a USD price can be converted into
+pub fn supported_currencies() -> Vec<&'static str> {
+    units::FX_PAIRS
+        .iter()
+        .filter_map(|pair| {
+            let (base, quote) = units::fx_legs(pair)?;
+            Some(if base == USD { quote } else { base })
+        })
+        .collect()
+}
+
+/// Rate from the FX pair's price
+pub fn rate_from_pair(currency: &str, pair_price: &AggregatedPrice) -> Result<FxRate> {
+    let (pair, usd_per_unit) = fx_pair(currency)
+        .ok_or_else(|| DataRetrievalError::AssetNotFound(format!("{}/{}", USD, currency)))?;
+    if pair_price.price <= Decimal::ZERO {
+        return Err(DataRetrievalError::InvalidResponse(format!(
+            "{} rate is {}",
+            pair, pair_price.price
+        )));
+    }
+    Ok(FxRate {
+        currency: currency.to_ascii_uppercase(),
+        per_usd: if usd_per_unit {
+            Decimal::ONE / pair_price.price
+        } else {
+            pair_price.price
+        },
+        pair,
+        timestamp: pair_price.timestamp,
+        stale: pair_price.stale,
+        unreliable: pair_price.unreliable,
+    })
+}
+
+/// A USD price in `rate`'s currency
+///
+/// The result is as old, as stale and as unreliable as the worse of the two
+/// inputs.
+pub fn convert(usd: &AggregatedPrice, rate: &FxRate) -> AggregatedPrice {
+    let mut converted = usd.clone();
+    converted.quote = rate.currency.clone();
+    converted.price = usd.price * rate.per_usd;
+    for source in &mut converted.sources {
+        source.price *= rate.per_usd;
+    }
+    converted.timestamp = usd.timestamp.min(rate.timestamp);
+    converted.stale = usd.stale || rate.stale;
+    converted.unreliable = usd.unreliable || rate.unreliable;
+    converted.converted = true;
+    converted.fx_rate = Some(rate.per_usd);
+    converted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::PriceSource;
+
+    fn price(asset: &str, quote: &str, price: Decimal) -> AggregatedPrice {
+        AggregatedPrice {
+            asset: asset.to_string(),
+            quote: quote.to_string(),
+            price,
+            sources: vec![PriceSource {
+                source: "coingecko".to_string(),
+                price,
+                weight: 1.0,
+                timestamp: Utc::now(),
+            }],
+            timestamp: Utc::now(),
+            confidence: 0.9,
+            spread_percent: 0.0,
+            stale: false,
+            unreliable: false,
+            converted: false,
+            fx_rate: None,
+        }
+    }
+
+    #[test]
+    fn test_fx_pairs_both_ways() {
+        assert_eq!(fx_pair("eur"), Some(("EURUSD", true)));
+        assert_eq!(fx_pair("JPY"), Some(("USDJPY", false)));
+        assert_eq!(fx_pair("USD"), None);
+        assert_eq!(fx_pair("XYZ"), None);
+        assert!(supported_currencies().contains(&"BRL"));
+
+        // EURUSD 1.25: a dollar buys 0.8 EUR
+        let eur = rate_from_pair("EUR", &price("EURUSD", USD, Decimal::new(125, 2))).unwrap();
+        assert_eq!(eur.per_usd, Decimal::new(8, 1));
+        let jpy = rate_from_pair("JPY", &price("USDJPY", USD, Decimal::from(150))).unwrap();
+        assert_eq!(jpy.per_usd, Decimal::from(150));
+        assert!(rate_from_pair("EUR", &price("EURUSD", USD, Decimal::ZERO)).is_err());
+    }
+
+    #[test]
+    fn test_convert_marks_price() {
+        let usd = price("BTC", USD, Decimal::from(60_000));
+        let mut pair = price("EURUSD", USD, Decimal::new(125, 2));
+        pair.stale = true;
+        pair.unreliable = true;
+        let rate = rate_from_pair("EUR", &pair).unwrap();
+
+        let eur = convert(&usd, &rate);
+        assert_eq!(eur.quote, "EUR");
+        assert_eq!(eur.price, Decimal::from(48_000));
+        assert_eq!(eur.sources[0].price, Decimal::from(48_000));
+        assert_eq!(eur.fx_rate, Some(Decimal::new(8, 1)));
+        assert!(eur.converted);
+        assert!(eur.stale);
+        assert!(eur.unreliable);
+        assert!(!usd.converted);
+    }
+}
@@ -0,0 +1,273 @@
+//! gRPC price service for internal traffic
+//!
+//! Serves `proto/prices.proto` on GRPC_PORT alongside the HTTP API, backed by
+//! the same lookups, so runners can price a decision tick over one
+//! long-lived HTTP/2 connection instead of a JSON request per symbol.
+
+use std::collections::HashMap;
+use std::pin::Pin;
+use std::sync::Arc;
+
+use futures::{Stream, StreamExt};
+use tokio::sync::broadcast;
+use tonic::{Request, Response, Status};
+use tracing::{info, warn};
+
+use crate::handlers::{canonical_pair, fetch_price, stream_pairs, STREAM_MAX_SYMBOLS};
+use crate::AppState;
+use data_retrieval::regime::Regime;
+use data_retrieval::types::{MarketSummary, PricePoint, StreamGap};
+
+pub mod pb {
+    tonic::include_proto!("trawling.prices.v1");
+}
+
+use pb::price_service_server::{PriceService, PriceServiceServer};
+
+/// Most symbols one GetBatch may ask for
+const BATCH_MAX_SYMBOLS: usize = 100;
+
+pub struct PriceGrpc {
+    state: Arc<AppState>,
+}
+
+impl PriceGrpc {
+    pub fn server(state: Arc<AppState>) -> PriceServiceServer<Self> {
+        PriceServiceServer::new(Self { state })
+    }
+}
+
+fn quote_or_usd(quote: &str) -> &str {
+    if quote.is_empty() {
+        "USD"
+    } else {
+        quote
+    }
+}
+
+impl From<PricePoint> for pb::Price {
+    fn from(p: PricePoint) -> Self {
+        Self {
+            symbol: p.symbol,
+            price: p.price.to_string(),
+            source: p.source,
+            timestamp_ms: p.timestamp.timestamp_millis(),
+            confidence: p.confidence,
+            stale: p.stale,
+            unreliable: p.unreliable,
+            market: None,
+        }
+    }
+}
+
+impl From<MarketSummary> for pb::Market {
+    fn from(m: MarketSummary) -> Self {
+        Self {
+            change_1h_pct: m.change_1h_pct,
+            change_24h_pct: m.change_24h_pct,
+            change_7d_pct: m.change_7d_pct,
+            regime: m.regime.map(|r| pb::MarketRegime {
+                regime: match r.regime {
+                    Regime::Trending => pb::Regime::Trending,
+                    Regime::Ranging => pb::Regime::Ranging,
+                    Regime::HighVolatility => pb::Regime::HighVolatility,
+                } as i32,
+                efficiency_ratio: r.efficiency_ratio,
+                trend_pct: r.trend_pct,
+                volatility_pct: r.volatility_pct,
+            }),
+        }
+    }
+}
+
+impl From<StreamGap> for pb::Gap {
+    fn from(gap: StreamGap) -> Self {
+        Self {
+            source: gap.source,
+            started_at_ms: gap.started_at.timestamp_millis(),
+            ended_at_ms: gap.ended_at.timestamp_millis(),
+            attempts: gap.attempts,
+        }
+    }
+}
+
+fn price_event(price: PricePoint) -> pb::StreamEvent {
+    pb::StreamEvent {
+        event: Some(pb::stream_event::Event::Price(price.into())),
+    }
+}
+
+type EventStream = Pin<Box<dyn Stream<Item = Result<pb::StreamEvent, Status>> + Send>>;
+
+#[tonic::async_trait]
+impl PriceService for PriceGrpc {
+    async fn get_price(
+        &self,
+        request: Request<pb::PriceRequest>,
+    ) -> Result<Response<pb::Price>, Status> {
+        let req = request.into_inner();
+        if req.symbol.trim().is_empty() {
+            return Err(Status::invalid_argument("No symbol given"));
+        }
+        let (asset_class, symbol, quote) =
+            canonical_pair(&self.state, req.symbol.trim(), quote_or_usd(&req.quote));
+        let price = match fetch_price(&self.state, asset_class, &symbol, &quote).await {
+            Ok(price) => price,
+            Err(e) => {
+                warn!("gRPC price error for {}: {}", symbol, e);
+                return Err(Status::unavailable(e.to_string()));
+            }
+        };
+        let market = self
+            .state
+            .price_aggregator
+            .market_summary(&symbol, &quote, price.price)
+            .await;
+        Ok(Response::new(pb::Price {
+            market: market.map(Into::into),
+            ..price.into()
+        }))
+    }
+
+    async fn get_batch(
+        &self,
+        request: Request<pb::BatchRequest>,
+    ) -> Result<Response<pb::BatchResponse>, Status> {
+        let req = request.into_inner();
+        if req.symbols.len() > BATCH_MAX_SYMBOLS {
+            return Err(Status::invalid_argument(format!(
+                "At most {} symbols per batch",
+                BATCH_MAX_SYMBOLS
+            )));
+        }
+
+        let started = std::time::Instant::now();
+        let fetched: Vec<_> = futures::stream::iter(req.symbols)
+            .map(|symbol| {
+                let state = Arc::clone(&self.state);
+                async move {
+                    let (asset_class, sym, quote) = canonical_pair(&state, &symbol, "USD");
+                    let price = fetch_price(&state, asset_class, &sym, &quote).await;
+                    (sym, price)
+                }
+            })
+            .buffer_unordered(data_retrieval::BATCH_CONCURRENCY)
+            .collect()
+            .await;
+
+        let mut prices = HashMap::new();
+        let mut errors = Vec::new();
+        for (symbol, price) in fetched {
+            match price {
+                Ok(p) => {
+                    prices.insert(symbol, p.into());
+                }
+                Err(e) => errors.push(pb::BatchError {
+                    symbol,
+                    error: e.to_string(),
+                }),
+            }
+        }
+        errors.sort_by(|a, b| a.symbol.cmp(&b.symbol));
+
+        Ok(Response::new(pb::BatchResponse {
+            prices,
+            errors,
+            latency_ms: started.elapsed().as_millis() as u64,
+        }))
+    }
+
+    type StreamPricesStream = EventStream;
+
+    /// Same as `GET /prices/stream`: the latest streamed price of each pair
+    /// that has one, then every update, gap and lag notice
+    async fn stream_prices(
+        &self,
+        request: Request<pb::StreamRequest>,
+    ) -> Result<Response<Self::StreamPricesStream>, Status> {
+        let req = request.into_inner();
+        let wanted = stream_pairs(
+            &self.state,
+            req.symbols
+                .iter()
+                .map(|s| s.trim())
+                .filter(|s| !s.is_empty()),
+            quote_or_usd(&req.quote),
+        );
+        if wanted.is_empty() {
+            return Err(Status::invalid_argument("No symbols given"));
+        }
+        if wanted.len() > STREAM_MAX_SYMBOLS * 2 {
+            return Err(Status::invalid_argument(format!(
+                "At most {} symbols per stream",
+                STREAM_MAX_SYMBOLS
+            )));
+        }
+
+        // Subscribe before reading the snapshot so nothing falls in between
+        let aggregator = &self.state.price_aggregator;
+        let prices = aggregator.price_feed();
+        let gaps = aggregator.gap_feed();
+        let mut snapshot = Vec::new();
+        for symbol in &wanted {
+            if let Some(price) = aggregator.latest_streamed(symbol).await {
+                snapshot.push(Ok(price_event(price)));
+            }
+        }
+        info!("gRPC price stream opened for {} pairs", wanted.len());
+
+        let updates = futures::stream::unfold(
+            (prices, gaps, wanted),
+            |(mut prices, mut gaps, wanted)| async move {
+                let event = loop {
+                    tokio::select! {
+                        price = prices.recv() => match price {
+                            Ok(price) if wanted.contains(&price.symbol) => break price_event(price),
+                            Ok(_) => {}
+                            Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                                break pb::StreamEvent {
+                                    event: Some(pb::stream_event::Event::Lagged(skipped)),
+                                };
+                            }
+                            Err(broadcast::error::RecvError::Closed) => return None,
+                        },
+                        gap = gaps.recv() => match gap {
+                            Ok(gap) => break pb::StreamEvent {
+                                event: Some(pb::stream_event::Event::Gap(gap.into())),
+                            },
+                            Err(broadcast::error::RecvError::Lagged(_)) => {}
+                            Err(broadcast::error::RecvError::Closed) => return None,
+                        },
+                    }
+                };
+                Some((Ok(event), (prices, gaps, wanted)))
+            },
+        );
+
+        Ok(Response::new(Box::pin(
+            futures::stream::iter(snapshot).chain(updates),
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{TimeZone, Utc};
+
+    #[test]
+    fn test_price_keeps_decimal_precision() {
+        let point = PricePoint::new(
+            "SOL/USD",
+            rust_decimal::Decimal::new(14_212_345_678, 8),
+            "binance",
+            Utc.with_ymd_and_hms(2026, 3, 10, 12, 0, 0).unwrap(),
+            Some(0.9),
+        );
+        let price = pb::Price::from(point);
+        assert_eq!(price.price, "142.12345678");
+        assert_eq!(price.timestamp_ms, 1_773_144_000_000);
+        assert_eq!(price.confidence, Some(0.9));
+        assert!(!price.stale && !price.unreliable);
+    }
+}
@@ -1,14 +1,33 @@
 use axum::{
-    extract::{Query, State},
-    http::StatusCode,
+    extract::{Path, Query, State},
+    http::{HeaderMap, StatusCode},
+    response::sse::{Event, KeepAlive, Sse},
     Json,
 };
-use std::collections::HashMap;
+use futures::Stream;
+use std::collections::{HashMap, HashSet};
+use std::convert::Infallible;
 use std::sync::Arc;
-use tracing::{info, warn};
+use tokio::sync::broadcast;
+use tracing::{debug, info, warn};
 
 use crate::AppState;
-use data_retrieval::{types::SourceHealth, AssetClass};
+use data_retrieval::{
+    history::MAX_QUERY_CANDLES,
+    regime::MarketRegime,
+    registry::SymbolEntry,
+    sources::depth::{self, LiquidityReport, Side},
+    sources::derivatives::DerivativesSnapshot,
+    sources::jupiter::{SwapSimulation, SwapToken, DEFAULT_SLIPPAGE_BPS},
+    sources::onchain::TokenAnalytics,
+    sources::sentiment::SentimentSummary,
+    symbol::looks_like_mint,
+    types::{
+        Candle, DataRetrievalError, MarketSummary, PricePoint, PriceUnit, SourceHealth, StreamGap,
+        TimeFrame,
+    },
+    units, AssetClass,
+};
 
 /// Query params for price endpoint
 #[derive(Debug, serde::Deserialize)]
@@ -28,95 +47,340 @@ pub async fn get_price(
     State(state): State<Arc<AppState>>,
     Query(query): Query<PriceQuery>,
 ) -> Result<Json<PriceResponse>, (StatusCode, String)> {
-    let symbol = query.symbol.to_uppercase();
-    let quote = query.quote.to_uppercase();
+    // Route to appropriate source based on the symbol's registry entry
+    let (asset_class, symbol, quote) = canonical_pair(&state, &query.symbol, &query.quote);
 
     info!("Fetching price for {}/{}", symbol, quote);
 
-    // Route to appropriate source based on asset class (using consistent AssetClass enum)
-    let asset_class = AssetClass::from_symbol(&symbol);
-    let price = match asset_class {
-        AssetClass::Stock | AssetClass::Etf | AssetClass::Metal => {
-            // Use Pyth for stocks, ETFs, and metals
-            match state.pyth_client.get_price(&symbol).await {
-                Ok(p) => p,
-                Err(e) => {
-                    warn!("Pyth error for {}: {}", symbol, e);
-                    return Err((StatusCode::SERVICE_UNAVAILABLE, e.to_string()));
-                }
-            }
-        }
-        AssetClass::Crypto => {
-            // Use aggregator for crypto
-            match state
-                .price_aggregator
-                .get_price_realtime(&symbol, &quote)
-                .await
-            {
-                Ok(p) => p,
-                Err(e) => {
-                    warn!("Aggregator error for {}: {}", symbol, e);
-                    return Err((StatusCode::SERVICE_UNAVAILABLE, e.to_string()));
-                }
-            }
+    let price = match fetch_price(&state, asset_class, &symbol, &quote).await {
+        Ok(p) => p,
+        Err(e) => {
+            warn!("Price error for {}: {}", symbol, e);
+            return Err((StatusCode::SERVICE_UNAVAILABLE, e.to_string()));
         }
     };
 
+    let market = state
+        .price_aggregator
+        .market_summary(&symbol, &quote, price.price)
+        .await;
+
     Ok(Json(PriceResponse {
         symbol: price.symbol,
         price: price.price,
         source: price.source,
         timestamp: price.timestamp,
         confidence: price.confidence,
+        stale: price.stale,
+        unreliable: price.unreliable,
+        unit: price.unit,
+        market,
     }))
 }
 
+/// Current price of a canonical pair: Pyth for stocks, ETFs, metals and FX,
+/// the aggregator for crypto
+pub(crate) async fn fetch_price(
+    state: &AppState,
+    asset_class: AssetClass,
+    symbol: &str,
+    quote: &str,
+) -> anyhow::Result<PricePoint> {
+    match asset_class {
+        AssetClass::Stock | AssetClass::Etf | AssetClass::Metal | AssetClass::Fx => {
+            state.pyth_client.get_price(symbol).await
+        }
+        AssetClass::Crypto => Ok(state
+            .price_aggregator
+            .get_price_realtime(symbol, quote)
+            .await?),
+    }
+}
+
+/// Symbol and quote to look up
+///
+/// Crypto symbols arrive in many forms (`SOL-USD`, `SOLUSDT`, a mint) and are
+/// normalized through [`data_retrieval::SymbolId`] and the registry; Pyth symbols (stocks,
+/// metals, FX pairs) are its own names and only uppercased.
+pub(crate) fn canonical_pair(
+    state: &AppState,
+    symbol: &str,
+    quote: &str,
+) -> (AssetClass, String, String) {
+    let asset_class = state.price_aggregator.registry().asset_class(symbol);
+    match asset_class {
+        AssetClass::Crypto => {
+            let pair = state.price_aggregator.canonical_pair(symbol, quote);
+            (
+                asset_class,
+                pair.base().to_string(),
+                pair.quote().to_string(),
+            )
+        }
+        _ => (asset_class, symbol.to_uppercase(), quote.to_uppercase()),
+    }
+}
+
+/// Most symbols one price stream may follow
+pub(crate) const STREAM_MAX_SYMBOLS: usize = 50;
+
+#[derive(Debug, serde::Deserialize)]
+pub struct StreamQuery {
+    /// Comma separated, in any form `/prices` accepts
+    symbols: String,
+    #[serde(default = "default_quote")]
+    quote: String,
+}
+
+/// GET /prices/stream?symbols=SOL,BTC - Server-sent events of streamed prices
+///
+/// Opens with the latest streamed price of each symbol that has one, then
+/// pushes every update from the realtime sources as a `price` event
+/// (a `PricePoint`). USD symbols also get USDT-quoted updates, since that's
+/// what Binance trades. A `gap` event (`StreamGap`) says a source was down
+/// and updates were missed; `lagged` says this client fell behind and
+/// `skipped` updates were dropped.
+pub async fn stream_prices(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<StreamQuery>,
+) -> Result<Sse<impl Stream<Item = Result<Event, Infallible>>>, (StatusCode, String)> {
+    let wanted = stream_pairs(
+        &state,
+        query
+            .symbols
+            .split(',')
+            .map(str::trim)
+            .filter(|s| !s.is_empty()),
+        &query.quote,
+    );
+    if wanted.is_empty() {
+        return Err((StatusCode::BAD_REQUEST, "No symbols given".to_string()));
+    }
+    if wanted.len() > STREAM_MAX_SYMBOLS * 2 {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!("At most {} symbols per stream", STREAM_MAX_SYMBOLS),
+        ));
+    }
+
+    // Subscribe before reading the snapshot so nothing falls in between
+    let prices = state.price_aggregator.price_feed();
+    let gaps = state.price_aggregator.gap_feed();
+    let mut snapshot = Vec::new();
+    for symbol in &wanted {
+        if let Some(price) = state.price_aggregator.latest_streamed(symbol).await {
+            snapshot.push(price_event(&price));
+        }
+    }
+    info!("Price stream opened for {} pairs", wanted.len());
+
+    let updates = futures::stream::unfold(
+        (prices, gaps, wanted),
+        |(mut prices, mut gaps, wanted)| async move {
+            let event = loop {
+                tokio::select! {
+                    price = prices.recv() => match price {
+                        Ok(price) if wanted.contains(&price.symbol) => break price_event(&price),
+                        Ok(_) => {}
+                        Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                            break Event::default()
+                                .event("lagged")
+                                .data(serde_json::json!({"skipped": skipped}).to_string());
+                        }
+                        Err(broadcast::error::RecvError::Closed) => return None,
+                    },
+                    gap = gaps.recv() => match gap {
+                        Ok(gap) => break gap_event(&gap),
+                        // Missed gaps only matter as much as the next one
+                        Err(broadcast::error::RecvError::Lagged(_)) => {}
+                        Err(broadcast::error::RecvError::Closed) => return None,
+                    },
+                }
+            };
+            Some((Ok(event), (prices, gaps, wanted)))
+        },
+    );
+    let stream =
+        futures::StreamExt::chain(futures::stream::iter(snapshot.into_iter().map(Ok)), updates);
+
+    Ok(Sse::new(stream).keep_alive(KeepAlive::default()))
+}
+
+/// `BASE/QUOTE` pairs a stream of `symbols` follows; USD symbols also follow
+/// their USDT pair
+pub(crate) fn stream_pairs<'a>(
+    state: &AppState,
+    symbols: impl IntoIterator<Item = &'a str>,
+    quote: &str,
+) -> HashSet<String> {
+    let mut wanted = HashSet::new();
+    for symbol in symbols {
+        let (_, base, quote) = canonical_pair(state, symbol, quote);
+        if quote == "USD" {
+            wanted.insert(format!("{}/USDT", base));
+        }
+        wanted.insert(format!("{}/{}", base, quote));
+    }
+    wanted
+}
+
+fn price_event(price: &PricePoint) -> Event {
+    Event::default()
+        .event("price")
+        .data(serde_json::to_string(price).unwrap_or_default())
+}
+
+fn gap_event(gap: &StreamGap) -> Event {
+    Event::default()
+        .event("gap")
+        .data(serde_json::to_string(gap).unwrap_or_default())
+}
+
 /// POST /prices/batch - Get multiple prices at once
+///
+/// Symbols are fetched concurrently (at most
+/// [`data_retrieval::BATCH_CONCURRENCY`] at a time). The response holds
+/// whatever could be priced, with an error entry for each symbol that
+/// couldn't, and the batch's total latency.
 pub async fn get_prices_batch(
     State(state): State<Arc<AppState>>,
     Json(req): Json<BatchPriceRequest>,
 ) -> Result<Json<BatchPriceResponse>, (StatusCode, String)> {
-    let mut results = HashMap::new();
-    let mut errors = Vec::new();
+    use futures::StreamExt;
 
-    for symbol in &req.symbols {
-        let sym = symbol.to_uppercase();
-
-        // Use consistent asset class detection
-        let asset_class = AssetClass::from_symbol(&sym);
-        let price = match asset_class {
-            AssetClass::Stock | AssetClass::Etf | AssetClass::Metal => {
-                state.pyth_client.get_price(&sym).await.ok()
+    let started = std::time::Instant::now();
+    let fetched: Vec<_> = futures::stream::iter(req.symbols)
+        .map(|symbol| {
+            let state = Arc::clone(&state);
+            async move {
+                let (asset_class, sym, quote) = canonical_pair(&state, &symbol, "USD");
+                let price = fetch_price(&state, asset_class, &sym, &quote)
+                    .await
+                    .map_err(|e| e.to_string());
+                (sym, price)
             }
-            AssetClass::Crypto => state
-                .price_aggregator
-                .get_price_realtime(&sym, "USD")
-                .await
-                .ok(),
-        };
+        })
+        .buffer_unordered(data_retrieval::BATCH_CONCURRENCY)
+        .collect()
+        .await;
 
+    let mut results = HashMap::new();
+    let mut errors = Vec::new();
+    for (symbol, price) in fetched {
         match price {
-            Some(p) => {
+            Ok(p) => {
                 results.insert(
-                    sym.clone(),
+                    symbol,
                     PriceResponse {
                         symbol: p.symbol,
                         price: p.price,
                         source: p.source,
                         timestamp: p.timestamp,
                         confidence: p.confidence,
+                        stale: p.stale,
+                        unreliable: p.unreliable,
+                        unit: p.unit,
+                        market: None,
                     },
                 );
             }
-            None => {
-                errors.push(sym);
+            Err(e) => {
+                warn!("Batch price failed for {}: {}", symbol, e);
+                errors.push(BatchPriceError { symbol, error: e });
             }
         }
     }
+    errors.sort_by(|a, b| a.symbol.cmp(&b.symbol));
 
     Ok(Json(BatchPriceResponse {
         prices: results,
         errors,
+        latency_ms: started.elapsed().as_millis() as u64,
+    }))
+}
+
+/// Query params for the price history endpoint
+#[derive(Debug, serde::Deserialize)]
+pub struct HistoryQuery {
+    symbol: String,
+    #[serde(default = "default_quote")]
+    quote: String,
+    /// `1h`, `1d`, ... (default `1h`)
+    timeframe: Option<String>,
+    from: Option<chrono::DateTime<chrono::Utc>>,
+    to: Option<chrono::DateTime<chrono::Utc>>,
+    limit: Option<usize>,
+}
+
+/// GET /prices/history - Recorded candles for a pair, oldest first
+pub async fn get_price_history(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<HistoryQuery>,
+) -> Result<Json<HistoryResponse>, (StatusCode, String)> {
+    let Some(history) = &state.history else {
+        return Err((
+            StatusCode::SERVICE_UNAVAILABLE,
+            "Price history is not configured (set DATABASE_URL)".to_string(),
+        ));
+    };
+    let timeframe = match query.timeframe.as_deref() {
+        None => TimeFrame::Hour1,
+        Some(tf) => TimeFrame::parse(tf).ok_or_else(|| {
+            (
+                StatusCode::BAD_REQUEST,
+                format!("Unknown timeframe '{}'", tf),
+            )
+        })?,
+    };
+    let limit = query.limit.unwrap_or(500).clamp(1, MAX_QUERY_CANDLES);
+
+    let pair = state
+        .price_aggregator
+        .canonical_pair(&query.symbol, &query.quote);
+    let candles = history
+        .candles(
+            pair.base(),
+            pair.quote(),
+            timeframe,
+            query.from,
+            query.to,
+            limit,
+        )
+        .await
+        .map_err(|e| {
+            warn!("Price history query failed: {}", e);
+            (StatusCode::SERVICE_UNAVAILABLE, e.to_string())
+        })?;
+
+    Ok(Json(HistoryResponse {
+        symbol: pair.base().to_string(),
+        quote: pair.quote().to_string(),
+        timeframe: timeframe.as_str(),
+        candles,
+    }))
+}
+
+/// GET /prices/regime - Trending, ranging or high volatility, from hourly candles
+pub async fn get_market_regime(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<PriceQuery>,
+) -> Result<Json<RegimeResponse>, (StatusCode, String)> {
+    let (_, symbol, quote) = canonical_pair(&state, &query.symbol, &query.quote);
+    let regime = state
+        .price_aggregator
+        .market_regime(&symbol, &quote)
+        .await
+        .ok_or_else(|| {
+            (
+                StatusCode::NOT_FOUND,
+                format!("Not enough candles to classify {}/{}", symbol, quote),
+            )
+        })?;
+
+    Ok(Json(RegimeResponse {
+        symbol,
+        quote,
+        regime,
     }))
 }
 
@@ -131,9 +395,383 @@ pub async fn get_supported_symbols(
         stocks: supported.stocks.iter().map(|s| s.to_string()).collect(),
         etfs: supported.etfs.iter().map(|s| s.to_string()).collect(),
         metals: supported.metals.iter().map(|s| s.to_string()).collect(),
+        fx: supported.fx.iter().map(|s| s.to_string()).collect(),
+        units: supported
+            .metals
+            .iter()
+            .filter_map(|s| units::metal_spec(s).map(|m| (s.to_string(), m.unit)))
+            .collect(),
     })
 }
 
+/// Query params for swap simulation
+#[derive(Debug, serde::Deserialize)]
+pub struct SimulateSwapQuery {
+    /// Symbol (`SOL`) or mint address
+    input: String,
+    output: String,
+    /// Input amount in token units (not raw)
+    amount: rust_decimal::Decimal,
+    slippage_bps: Option<u32>,
+    /// Needed for mints we don't know the decimals of
+    input_decimals: Option<u8>,
+    output_decimals: Option<u8>,
+}
+
+/// Mint and decimals for a symbol or mint, from the registry first
+fn swap_token(state: &AppState, symbol_or_mint: &str) -> SwapToken {
+    let symbol_or_mint = symbol_or_mint.trim();
+    match state.price_aggregator.registry().lookup(symbol_or_mint) {
+        Some(entry) if entry.mint.is_some() => SwapToken {
+            mint: entry.mint.clone().unwrap_or_default(),
+            decimals: entry.decimals,
+        },
+        _ => SwapToken::resolve(symbol_or_mint),
+    }
+}
+
+/// GET /simulate-swap - Quote a swap on Jupiter without executing it
+pub async fn simulate_swap(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<SimulateSwapQuery>,
+) -> Result<Json<SwapSimulation>, (StatusCode, String)> {
+    let mut input = swap_token(&state, &query.input);
+    let mut output = swap_token(&state, &query.output);
+    input.decimals = input.decimals.or(query.input_decimals);
+    output.decimals = output.decimals.or(query.output_decimals);
+
+    let decimals = input.decimals.ok_or_else(|| {
+        (
+            StatusCode::BAD_REQUEST,
+            format!("Unknown decimals for {}; pass input_decimals", input.mint),
+        )
+    })?;
+    let slippage_bps = query.slippage_bps.unwrap_or(DEFAULT_SLIPPAGE_BPS);
+    if slippage_bps > 10_000 {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "slippage_bps must be at most 10000".to_string(),
+        ));
+    }
+
+    use rust_decimal::prelude::ToPrimitive;
+    let in_amount_raw = (query.amount * rust_decimal::Decimal::from(10u64.pow(decimals as u32)))
+        .trunc()
+        .to_u64()
+        .filter(|raw| *raw > 0)
+        .ok_or_else(|| {
+            (
+                StatusCode::BAD_REQUEST,
+                format!("Invalid amount: {}", query.amount),
+            )
+        })?;
+
+    info!(
+        "Simulating swap of {} {} -> {}",
+        query.amount, input.mint, output.mint
+    );
+
+    state
+        .jupiter
+        .simulate_swap(&input, &output, in_amount_raw, slippage_bps)
+        .await
+        .map(Json)
+        .map_err(|e| {
+            warn!("Swap simulation failed: {}", e);
+            let status = match e {
+                DataRetrievalError::AssetNotFound(_) => StatusCode::NOT_FOUND,
+                DataRetrievalError::RateLimit { .. } => StatusCode::TOO_MANY_REQUESTS,
+                _ => StatusCode::SERVICE_UNAVAILABLE,
+            };
+            (status, e.to_string())
+        })
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub struct LiquidityQuery {
+    /// Symbol (`SOL`) or mint address
+    symbol: String,
+    side: Side,
+    notional_usd: rust_decimal::Decimal,
+    /// Also report the largest notional within this slippage
+    max_slippage_bps: Option<u32>,
+}
+
+/// GET /liquidity - Depth and estimated slippage for trading a notional
+///
+/// Walks the exchange order book when the venue lists the asset, and falls
+/// back to a Jupiter route quote for tokens it doesn't.
+pub async fn get_liquidity(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<LiquidityQuery>,
+) -> Result<Json<LiquidityReport>, (StatusCode, String)> {
+    if query.notional_usd <= rust_decimal::Decimal::ZERO {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!("Invalid notional_usd: {}", query.notional_usd),
+        ));
+    }
+    let symbol = query.symbol.trim();
+    if let Some(client) = state.depth.as_ref().filter(|_| !looks_like_mint(symbol)) {
+        let (_, base, _) = canonical_pair(&state, symbol, "USD");
+        match client.get_book(&base).await {
+            Ok(book) => {
+                if let Some(report) = book.report(
+                    &base,
+                    query.side,
+                    query.notional_usd,
+                    query.max_slippage_bps,
+                ) {
+                    return Ok(Json(report));
+                }
+                debug!("{} book for {} is empty", client.venue().as_str(), base);
+            }
+            Err(e) => debug!(
+                "No {} book for {} ({}), trying Jupiter",
+                client.venue().as_str(),
+                base,
+                e
+            ),
+        }
+    }
+
+    let token = swap_token(&state, symbol);
+    depth::route_report(
+        &state.jupiter,
+        symbol,
+        &token,
+        query.side,
+        query.notional_usd,
+        query.max_slippage_bps,
+    )
+    .await
+    .map(Json)
+    .map_err(|e| {
+        warn!("Liquidity estimate for {} failed: {}", symbol, e);
+        let status = match e {
+            DataRetrievalError::AssetNotFound(_) => StatusCode::NOT_FOUND,
+            DataRetrievalError::RateLimit { .. } => StatusCode::TOO_MANY_REQUESTS,
+            _ => StatusCode::SERVICE_UNAVAILABLE,
+        };
+        (status, e.to_string())
+    })
+}
+
+/// GET /derivatives/{symbol} - Perpetual funding rate and open interest
+pub async fn get_derivatives(
+    State(state): State<Arc<AppState>>,
+    Path(symbol): Path<String>,
+) -> Result<Json<DerivativesSnapshot>, (StatusCode, String)> {
+    let Some(client) = &state.derivatives else {
+        return Err((
+            StatusCode::SERVICE_UNAVAILABLE,
+            "Derivatives data is disabled".to_string(),
+        ));
+    };
+    let (_, base, _) = canonical_pair(&state, symbol.trim(), "USD");
+    client.get_snapshot(&base).await.map(Json).map_err(|e| {
+        debug!("Derivatives lookup for {} failed: {}", base, e);
+        let status = match e {
+            DataRetrievalError::AssetNotFound(_) => StatusCode::NOT_FOUND,
+            DataRetrievalError::RateLimit { .. } => StatusCode::TOO_MANY_REQUESTS,
+            _ => StatusCode::SERVICE_UNAVAILABLE,
+        };
+        (status, e.to_string())
+    })
+}
+
+/// GET /sentiment/{symbol} - Fear & Greed and news sentiment for a crypto asset
+///
+/// Either reading may be missing; the endpoint only fails when both are.
+pub async fn get_sentiment(
+    State(state): State<Arc<AppState>>,
+    Path(symbol): Path<String>,
+) -> Result<Json<SentimentSummary>, (StatusCode, String)> {
+    let (asset_class, base, _) = canonical_pair(&state, symbol.trim(), "USD");
+    if asset_class != AssetClass::Crypto {
+        return Err((
+            StatusCode::NOT_FOUND,
+            format!("No sentiment for {} assets", asset_class.as_str()),
+        ));
+    }
+
+    let (fear_greed, news) = tokio::join!(
+        state.sentiment.get_fear_greed(),
+        state.sentiment.get_news(&base)
+    );
+    let fear_greed = fear_greed
+        .map_err(|e| debug!("Fear & Greed lookup failed: {}", e))
+        .ok();
+    let news = news
+        .map_err(|e| debug!("News sentiment for {} failed: {}", base, e))
+        .ok()
+        .flatten();
+    SentimentSummary::combine(&base, fear_greed, news)
+        .map(Json)
+        .ok_or_else(|| {
+            (
+                StatusCode::SERVICE_UNAVAILABLE,
+                format!("No sentiment source answered for {}", base),
+            )
+        })
+}
+
+/// GET /tokens/{mint}/analytics - Pool liquidity, holder concentration and
+/// mint authorities of a Solana token
+pub async fn get_token_analytics(
+    State(state): State<Arc<AppState>>,
+    Path(mint): Path<String>,
+) -> Result<Json<TokenAnalytics>, (StatusCode, String)> {
+    let mint = mint.trim();
+    if !looks_like_mint(mint) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!("Not a mint address: {}", mint),
+        ));
+    }
+    state
+        .onchain
+        .get_analytics(mint)
+        .await
+        .map(Json)
+        .map_err(|e| {
+            warn!("Token analytics for {} failed: {}", mint, e);
+            let status = match e {
+                DataRetrievalError::AssetNotFound(_) => StatusCode::NOT_FOUND,
+                DataRetrievalError::RateLimit { .. } => StatusCode::TOO_MANY_REQUESTS,
+                _ => StatusCode::SERVICE_UNAVAILABLE,
+            };
+            (status, e.to_string())
+        })
+}
+
+/// Check the request's bearer token against ADMIN_TOKEN
+fn require_admin(state: &AppState, headers: &HeaderMap) -> Result<(), (StatusCode, String)> {
+    let Some(expected) = &state.admin_token else {
+        return Err((
+            StatusCode::NOT_FOUND,
+            "Admin API is disabled (set ADMIN_TOKEN)".to_string(),
+        ));
+    };
+    let given = headers
+        .get(axum::http::header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+        .unwrap_or_default();
+    // Compare every byte so the time taken doesn't leak a matching prefix
+    let matches = given.len() == expected.len()
+        && given
+            .bytes()
+            .zip(expected.bytes())
+            .fold(0u8, |diff, (a, b)| diff | (a ^ b))
+            == 0;
+    if !matches {
+        return Err((StatusCode::UNAUTHORIZED, "Invalid admin token".to_string()));
+    }
+    Ok(())
+}
+
+/// GET /admin/symbols - Every registry entry
+pub async fn list_registry(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+) -> Result<Json<Vec<SymbolEntry>>, (StatusCode, String)> {
+    require_admin(&state, &headers)?;
+    Ok(Json(state.price_aggregator.registry().entries()))
+}
+
+/// Body of PUT /admin/symbols/:symbol
+#[derive(Debug, serde::Deserialize)]
+pub struct RegistryEntryRequest {
+    asset_class: AssetClass,
+    #[serde(default)]
+    sources: Vec<String>,
+    decimals: Option<u8>,
+    mint: Option<String>,
+}
+
+/// PUT /admin/symbols/:symbol - Add or replace a symbol in the database layer
+pub async fn upsert_registry_entry(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Path(symbol): Path<String>,
+    Json(req): Json<RegistryEntryRequest>,
+) -> Result<Json<SymbolEntry>, (StatusCode, String)> {
+    require_admin(&state, &headers)?;
+    let registry = state.price_aggregator.registry();
+    require_registry_database(registry)?;
+    let entry = SymbolEntry {
+        symbol,
+        asset_class: req.asset_class,
+        sources: req.sources,
+        decimals: req.decimals,
+        mint: req.mint,
+    }
+    .normalized()
+    .map_err(|e| (StatusCode::BAD_REQUEST, e.to_string()))?;
+
+    let entry = registry.upsert(entry).await.map_err(|e| {
+        warn!("Symbol registry update failed: {}", e);
+        (StatusCode::SERVICE_UNAVAILABLE, e.to_string())
+    })?;
+    info!("Registry entry for {} updated", entry.symbol);
+    Ok(Json(entry))
+}
+
+/// DELETE /admin/symbols/:symbol - Drop a symbol's database entry
+pub async fn delete_registry_entry(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Path(symbol): Path<String>,
+) -> Result<StatusCode, (StatusCode, String)> {
+    require_admin(&state, &headers)?;
+    let registry = state.price_aggregator.registry();
+    require_registry_database(registry)?;
+    match registry.remove(&symbol).await {
+        Ok(true) => Ok(StatusCode::NO_CONTENT),
+        Ok(false) => Err((
+            StatusCode::NOT_FOUND,
+            format!("{} has no database entry", symbol.to_uppercase()),
+        )),
+        Err(e) => {
+            warn!("Symbol registry delete failed: {}", e);
+            Err((StatusCode::SERVICE_UNAVAILABLE, e.to_string()))
+        }
+    }
+}
+
+/// POST /admin/symbols/reload - Re-read the registry file and table
+pub async fn reload_registry(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+) -> Result<Json<RegistryReloadResponse>, (StatusCode, String)> {
+    require_admin(&state, &headers)?;
+    let symbols = state
+        .price_aggregator
+        .registry()
+        .reload()
+        .await
+        .map_err(|e| {
+            warn!("Symbol registry reload failed: {}", e);
+            (StatusCode::UNPROCESSABLE_ENTITY, e.to_string())
+        })?;
+    info!("Symbol registry reloaded ({} symbols)", symbols);
+    Ok(Json(RegistryReloadResponse { symbols }))
+}
+
+fn require_registry_database(
+    registry: &data_retrieval::SymbolRegistry,
+) -> Result<(), (StatusCode, String)> {
+    if registry.has_database() {
+        return Ok(());
+    }
+    Err((
+        StatusCode::CONFLICT,
+        "Registry edits need a database (set DATABASE_URL); edit SYMBOL_REGISTRY_PATH and reload instead"
+            .to_string(),
+    ))
+}
+
 /// GET /health - Service health check
 pub async fn health_check(State(state): State<Arc<AppState>>) -> Json<HealthResponse> {
     let source_health = state.price_aggregator.health_check().await;
@@ -150,6 +788,14 @@ pub async fn health_check(State(state): State<Arc<AppState>>) -> Json<HealthResp
     })
 }
 
+/// GET /cache/stats - hit and miss counts per cache tier and namespace
+pub async fn get_cache_stats(State(state): State<Arc<AppState>>) -> Json<CacheStatsResponse> {
+    Json(CacheStatsResponse {
+        tiers: state.price_aggregator.cache_stats(),
+        shared: state.price_aggregator.shared_cache_status(),
+    })
+}
+
 // Response types
 #[derive(Debug, serde::Serialize)]
 pub struct PriceResponse {
@@ -158,6 +804,39 @@ pub struct PriceResponse {
     pub source: String,
     pub timestamp: chrono::DateTime<chrono::Utc>,
     pub confidence: Option<f64>,
+    /// True when served from cache past its freshness window
+    pub stale: bool,
+    /// True when the sources disagree by more than `PRICE_MAX_SPREAD_PERCENT`
+    pub unreliable: bool,
+    /// Quoting unit for metals (per troy ounce, per gram)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub unit: Option<PriceUnit>,
+    /// 1h/24h/7d change and today's OHLC (single-symbol requests only)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub market: Option<MarketSummary>,
+}
+
+#[derive(Debug, serde::Serialize)]
+pub struct CacheStatsResponse {
+    pub tiers: Vec<data_retrieval::cache::TierStats>,
+    /// Redis and its breaker state; None when caching in memory only
+    pub shared: Option<data_retrieval::cache::SharedTierStatus>,
+}
+
+#[derive(Debug, serde::Serialize)]
+pub struct HistoryResponse {
+    pub symbol: String,
+    pub quote: String,
+    pub timeframe: &'static str,
+    pub candles: Vec<Candle>,
+}
+
+#[derive(Debug, serde::Serialize)]
+pub struct RegimeResponse {
+    pub symbol: String,
+    pub quote: String,
+    #[serde(flatten)]
+    pub regime: MarketRegime,
 }
 
 #[derive(Debug, serde::Deserialize)]
@@ -168,7 +847,16 @@ pub struct BatchPriceRequest {
 #[derive(Debug, serde::Serialize)]
 pub struct BatchPriceResponse {
     pub prices: HashMap<String, PriceResponse>,
-    pub errors: Vec<String>,
+    /// Symbols that couldn't be priced, and why
+    pub errors: Vec<BatchPriceError>,
+    /// Time taken for the whole batch
+    pub latency_ms: u64,
+}
+
+#[derive(Debug, serde::Serialize)]
+pub struct BatchPriceError {
+    pub symbol: String,
+    pub error: String,
 }
 
 #[derive(Debug, serde::Serialize)]
@@ -177,6 +865,14 @@ pub struct SupportedSymbolsResponse {
     pub stocks: Vec<String>,
     pub etfs: Vec<String>,
     pub metals: Vec<String>,
+    pub fx: Vec<String>,
+    /// Quoting unit of each metal symbol
+    pub units: HashMap<String, PriceUnit>,
+}
+
+#[derive(Debug, serde::Serialize)]
+pub struct RegistryReloadResponse {
+    pub symbols: usize,
 }
 
 #[derive(Debug, serde::Serialize)]
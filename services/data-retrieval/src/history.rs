@@ -0,0 +1,301 @@
+//! Price history store
+//!
+//! Candles fetched from the price sources are written to Postgres
+//! (`price_candles`, created by the control plane's migrations) on a schedule,
+//! so backtests and charts read one canonical history instead of asking a
+//! third-party API at query time. A candle is keyed by pair, timeframe and
+//! start time; re-recording it (the newest candle is still forming) replaces
+//! the stored values.
+//!
+//! `data-retrieval backfill` fills the table with as much history as the
+//! sources return, for a fresh database.
+
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use sqlx::postgres::{PgPool, PgPoolOptions};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::task::JoinHandle;
+use tracing::{debug, info, warn};
+
+use crate::{Candle, PriceAggregator, TimeFrame};
+
+/// Candles asked for on each scheduled recording (overlaps the last run)
+const RECORD_CANDLES: usize = 48;
+/// Candles asked for per pair and timeframe by a backfill
+pub const BACKFILL_CANDLES: usize = 1000;
+/// Most candles one query returns
+pub const MAX_QUERY_CANDLES: usize = 2000;
+
+/// Settings for the scheduled recorder
+#[derive(Debug, Clone)]
+pub struct HistoryConfig {
+    /// Assets to record, quoted in `quote`
+    pub symbols: Vec<String>,
+    pub quote: String,
+    pub timeframes: Vec<TimeFrame>,
+    /// How often candles are fetched and written
+    pub interval: Duration,
+}
+
+impl Default for HistoryConfig {
+    fn default() -> Self {
+        Self {
+            symbols: ["BTC", "ETH", "SOL"].map(String::from).to_vec(),
+            quote: "USD".to_string(),
+            timeframes: vec![TimeFrame::Hour1, TimeFrame::Day1],
+            interval: Duration::from_secs(900),
+        }
+    }
+}
+
+impl HistoryConfig {
+    /// Read overrides from HISTORY_SYMBOLS, HISTORY_TIMEFRAMES (e.g. `1h,1d`)
+    /// and HISTORY_INTERVAL_SECS
+    pub fn from_env() -> Self {
+        let mut config = Self::default();
+        if let Ok(symbols) = std::env::var("HISTORY_SYMBOLS") {
+            config.symbols = parse_symbols(&symbols);
+        }
+        if let Ok(timeframes) = std::env::var("HISTORY_TIMEFRAMES") {
+            config.timeframes = parse_timeframes(&timeframes);
+        }
+        if let Some(secs) = std::env::var("HISTORY_INTERVAL_SECS")
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .filter(|secs| *secs > 0)
+        {
+            config.interval = Duration::from_secs(secs);
+        }
+        config
+    }
+}
+
+/// Comma-separated symbols, uppercased and deduplicated
+pub fn parse_symbols(list: &str) -> Vec<String> {
+    let mut symbols: Vec<String> = Vec::new();
+    for symbol in list.split(',').map(|s| s.trim().to_uppercase()) {
+        if !symbol.is_empty() && !symbols.contains(&symbol) {
+            symbols.push(symbol);
+        }
+    }
+    symbols
+}
+
+/// Comma-separated timeframes; unknown ones are skipped with a warning
+pub fn parse_timeframes(list: &str) -> Vec<TimeFrame> {
+    list.split(',')
+        .filter(|s| !s.trim().is_empty())
+        .filter_map(|s| {
+            let parsed = TimeFrame::parse(s);
+            if parsed.is_none() {
+                warn!("Ignoring unknown history timeframe '{}'", s.trim());
+            }
+            parsed
+        })
+        .collect()
+}
+
+/// Reads and writes `price_candles`
+#[derive(Clone)]
+pub struct HistoryStore {
+    pool: PgPool,
+}
+
+impl HistoryStore {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    pub fn pool(&self) -> &PgPool {
+        &self.pool
+    }
+
+    /// Pool for `database_url`, connecting on first use
+    ///
+    /// The service can start before Postgres (or its migrations); queries
+    /// fail until it's there and the recorder retries on its next run.
+    pub fn connect_lazy(database_url: &str) -> anyhow::Result<Self> {
+        let pool = PgPoolOptions::new()
+            .max_connections(5)
+            .acquire_timeout(Duration::from_secs(10))
+            .connect_lazy(database_url)?;
+        Ok(Self::new(pool))
+    }
+
+    /// Insert or replace candles; returns how many were written
+    pub async fn upsert_candles(&self, candles: &[Candle]) -> anyhow::Result<u64> {
+        let mut written = 0;
+        let mut tx = self.pool.begin().await?;
+        for candle in candles {
+            written += sqlx::query(
+                r#"
+                INSERT INTO price_candles
+                    (asset, quote, timeframe, bucket_start, open, high, low, close, volume)
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+                ON CONFLICT (asset, quote, timeframe, bucket_start) DO UPDATE SET
+                    open = EXCLUDED.open,
+                    high = EXCLUDED.high,
+                    low = EXCLUDED.low,
+                    close = EXCLUDED.close,
+                    volume = EXCLUDED.volume,
+                    recorded_at = NOW()
+                "#,
+            )
+            .bind(candle.asset.to_uppercase())
+            .bind(candle.quote.to_uppercase())
+            .bind(candle.timeframe.as_str())
+            .bind(candle.timestamp)
+            .bind(candle.open)
+            .bind(candle.high)
+            .bind(candle.low)
+            .bind(candle.close)
+            .bind(candle.volume)
+            .execute(&mut *tx)
+            .await?
+            .rows_affected();
+        }
+        tx.commit().await?;
+        Ok(written)
+    }
+
+    /// Stored candles for a pair, oldest first
+    ///
+    /// With no `to`, the newest `limit` candles (from `from` on, if given);
+    /// otherwise the newest `limit` up to `to`.
+    pub async fn candles(
+        &self,
+        asset: &str,
+        quote: &str,
+        timeframe: TimeFrame,
+        from: Option<DateTime<Utc>>,
+        to: Option<DateTime<Utc>>,
+        limit: usize,
+    ) -> anyhow::Result<Vec<Candle>> {
+        let asset = asset.to_uppercase();
+        let quote = quote.to_uppercase();
+        let rows: Vec<(DateTime<Utc>, Decimal, Decimal, Decimal, Decimal, Decimal)> =
+            sqlx::query_as(
+                r#"
+                SELECT bucket_start, open, high, low, close, volume FROM price_candles
+                WHERE asset = $1 AND quote = $2 AND timeframe = $3
+                  AND ($4::timestamptz IS NULL OR bucket_start >= $4)
+                  AND ($5::timestamptz IS NULL OR bucket_start <= $5)
+                ORDER BY bucket_start DESC
+                LIMIT $6
+                "#,
+            )
+            .bind(&asset)
+            .bind(&quote)
+            .bind(timeframe.as_str())
+            .bind(from)
+            .bind(to)
+            .bind(limit.min(MAX_QUERY_CANDLES) as i64)
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(rows
+            .into_iter()
+            .rev()
+            .map(|(timestamp, open, high, low, close, volume)| Candle {
+                asset: asset.clone(),
+                quote: quote.clone(),
+                timeframe,
+                open,
+                high,
+                low,
+                close,
+                volume,
+                timestamp,
+            })
+            .collect())
+    }
+}
+
+/// Fetch candles for one pair and timeframe and store them
+pub async fn record(
+    aggregator: &PriceAggregator,
+    store: &HistoryStore,
+    asset: &str,
+    quote: &str,
+    timeframe: TimeFrame,
+    limit: usize,
+) -> anyhow::Result<u64> {
+    let candles = aggregator
+        .get_candles(asset, quote, timeframe, limit)
+        .await?;
+    store.upsert_candles(&candles).await
+}
+
+/// Spawn the loop that records every configured pair and timeframe
+///
+/// The first run happens immediately; failures (a source outage, the table
+/// not migrated yet) are logged and retried on the next run.
+pub fn spawn_recorder(
+    aggregator: Arc<PriceAggregator>,
+    store: HistoryStore,
+    config: HistoryConfig,
+) -> JoinHandle<()> {
+    info!(
+        "Price history recorder: {} symbols x {} timeframes every {}s",
+        config.symbols.len(),
+        config.timeframes.len(),
+        config.interval.as_secs()
+    );
+
+    tokio::spawn(async move {
+        let mut tick = tokio::time::interval(config.interval);
+        tick.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+
+        loop {
+            tick.tick().await;
+            for symbol in &config.symbols {
+                for &timeframe in &config.timeframes {
+                    match record(
+                        &aggregator,
+                        &store,
+                        symbol,
+                        &config.quote,
+                        timeframe,
+                        RECORD_CANDLES,
+                    )
+                    .await
+                    {
+                        Ok(written) => debug!(
+                            "Recorded {} {} candles for {}/{}",
+                            written,
+                            timeframe.as_str(),
+                            symbol,
+                            config.quote
+                        ),
+                        Err(e) => warn!(
+                            "Failed to record {} candles for {}/{}: {}",
+                            timeframe.as_str(),
+                            symbol,
+                            config.quote,
+                            e
+                        ),
+                    }
+                }
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_history_lists() {
+        assert_eq!(parse_symbols(" sol,BTC,,sol "), ["SOL", "BTC"]);
+
+        let timeframes = parse_timeframes("1h, 1d,2h");
+        assert_eq!(
+            timeframes.iter().map(TimeFrame::as_str).collect::<Vec<_>>(),
+            ["1h", "1d"]
+        );
+        assert!(TimeFrame::parse("1w").is_some());
+        assert!(TimeFrame::parse("Hour1").is_none());
+    }
+}
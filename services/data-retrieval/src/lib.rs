@@ -2,47 +2,131 @@ pub mod types;
 pub mod sources {
     pub mod binance_ws;
     pub mod coingecko;
+    pub mod depth;
+    pub mod derivatives;
+    pub mod exchange_ws;
+    pub mod jupiter;
+    pub mod onchain;
     pub mod pyth;
+    pub mod pyth_stream;
+    pub mod sentiment;
 }
 pub mod aggregators;
+pub mod breaker;
 pub mod cache;
+pub mod candle_builder;
+pub mod fx;
+pub mod history;
 pub mod normalizers;
+pub mod refresher;
+pub mod regime;
+pub mod registry;
+pub mod request_id;
+pub mod singleflight;
+pub mod symbol;
+pub mod units;
 
+pub use registry::SymbolRegistry;
 pub use sources::binance_ws::BinanceWebSocketClient;
 pub use sources::coingecko::CoinGeckoClient;
+pub use sources::exchange_ws::{Exchange, ExchangeStreamClient};
+pub use sources::jupiter::JupiterClient;
 pub use sources::pyth::PythClient;
+pub use sources::pyth_stream::PythStreamClient;
+pub use symbol::SymbolId;
 pub use types::*;
 
-use chrono::{Duration, Utc};
-use std::collections::HashMap;
-use std::sync::Arc;
-use tokio::sync::RwLock;
-use tracing::{info, warn};
-
-/// Helper to reconnect WebSocket client
-///
-/// Delegates to client.reconnect() which uses interior mutability
-/// to replace the connection and resubscribe to streams.
-async fn reconnect_ws(client: &Arc<BinanceWebSocketClient>) -> Result<()> {
-    client.reconnect().await
-}
+use breaker::{BreakerState, CircuitBreaker};
+use candle_builder::{CandleBuilder, Trade};
+use chrono::{DateTime, Duration, Utc};
+use refresher::RequestStats;
+use singleflight::SingleFlight;
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, OnceLock};
+use std::time::Instant;
+use tokio::sync::{broadcast, mpsc, RwLock};
+use tracing::{debug, info, warn};
 
 /// Maximum number of symbols in the price cache (prevent unbounded growth)
 const MAX_CACHE_SIZE: usize = 10000;
+/// How long the realtime consumer waits on a quiet stream before re-checking it
+const REALTIME_POLL_SECS: u64 = 5;
 /// Price TTL in seconds (prices older than this are evicted)
 const PRICE_TTL_SECONDS: i64 = 300; // 5 minutes
+/// How long to remember that no source knows a symbol
+const NEGATIVE_CACHE_TTL_SECS: u64 = 60;
+/// Cached aggregated prices younger than this are served without refetching
+const CACHE_FRESH_SECS: i64 = 30;
+/// Default limit on how old a cached price may be when served stale
+const DEFAULT_MAX_STALENESS_SECS: u64 = 120;
+/// How long fetched candles are reused before asking the source again
+const CANDLE_CACHE_SECS: u64 = 300;
+/// Hourly candles needed for a market summary (a week plus slack)
+const SUMMARY_CANDLES: usize = 24 * 7 + 2;
+/// Most symbols of one batch request fetched at the same time
+pub const BATCH_CONCURRENCY: usize = 8;
+
+/// Coalescing / negative-cache key: (ASSET, QUOTE)
+type PriceKey = (String, String);
+/// Candle cache key: (ASSET, QUOTE, timeframe)
+type CandleKey = (String, String, &'static str);
+
+/// Where a cached price sits relative to the freshness window
+#[derive(Debug, PartialEq)]
+enum CacheAge {
+    Fresh,
+    /// Past the freshness window but still within max staleness
+    Stale,
+    Expired,
+}
+
+fn classify_cache_age(age_secs: i64, max_staleness: std::time::Duration) -> CacheAge {
+    if age_secs < CACHE_FRESH_SECS {
+        CacheAge::Fresh
+    } else if age_secs < max_staleness.as_secs() as i64 {
+        CacheAge::Stale
+    } else {
+        CacheAge::Expired
+    }
+}
 
 /// Asset class for routing to appropriate data sources
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum AssetClass {
     Crypto,
     Stock,
     Etf,
     Metal,
+    Fx,
 }
 
 impl AssetClass {
-    /// Detect asset class from symbol
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AssetClass::Crypto => "crypto",
+            AssetClass::Stock => "stock",
+            AssetClass::Etf => "etf",
+            AssetClass::Metal => "metal",
+            AssetClass::Fx => "fx",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "crypto" => Some(AssetClass::Crypto),
+            "stock" => Some(AssetClass::Stock),
+            "etf" => Some(AssetClass::Etf),
+            "metal" => Some(AssetClass::Metal),
+            "fx" => Some(AssetClass::Fx),
+            _ => None,
+        }
+    }
+
+    /// Detect asset class from symbol alone
+    ///
+    /// The fallback for symbols the [`SymbolRegistry`] doesn't list; route
+    /// requests through [`SymbolRegistry::asset_class`].
     pub fn from_symbol(symbol: &str) -> Self {
         let sym = symbol.to_uppercase();
 
@@ -67,23 +151,60 @@ impl AssetClass {
         }
 
         // Metals
-        if ["ORO", "XAU", "XAG"].contains(&sym.as_str()) {
+        if units::metal_spec(&sym).is_some() {
             return AssetClass::Metal;
         }
 
+        // FX pairs (EURUSD, USDJPY, ...)
+        if units::is_fx_pair(&sym) {
+            return AssetClass::Fx;
+        }
+
         // Default to crypto
         AssetClass::Crypto
     }
 }
 
 /// Multi-source price aggregator with real-time and cached data
+///
+/// Pair arguments take any form [`SymbolId`] parses (`SOL`, `SOL-USD`,
+/// `SOLUSDT`, a mint) and are normalized before any cache or source lookup.
 pub struct PriceAggregator {
     crypto_sources: Vec<Arc<dyn PriceDataSource>>,
     stock_sources: Vec<Arc<dyn PriceDataSource>>,
     metal_sources: Vec<Arc<dyn PriceDataSource>>,
-    realtime_sources: Vec<Arc<BinanceWebSocketClient>>,
-    cache: Option<cache::RedisCache>,
+    fx_sources: Vec<Arc<dyn PriceDataSource>>,
+    realtime_sources: Vec<Arc<dyn RealtimePriceSource>>,
+    /// Outages of streaming sources, published when each one ends
+    gap_tx: broadcast::Sender<StreamGap>,
+    /// Every update from the streaming sources, as it arrives
+    price_tx: broadcast::Sender<PricePoint>,
+    /// Aggregated prices and source candles, in memory and (optionally) Redis
+    cache: cache::TieredCache,
     latest_prices: Arc<RwLock<HashMap<String, PricePoint>>>, // symbol -> price
+    /// Shares one upstream fetch between concurrent requests for the same pair
+    inflight: SingleFlight<PriceKey, Result<AggregatedPrice>>,
+    /// Pairs that every source reported as unknown, with when we learned it
+    negative_cache: RwLock<HashMap<PriceKey, Instant>>,
+    /// Request frequency per pair, drives the hot symbol refresher
+    request_stats: RequestStats,
+    /// Longest a cached price may be served (flagged stale) past freshness
+    max_staleness: std::time::Duration,
+    /// Spread between sources past which an aggregated price is unreliable
+    max_spread_percent: f64,
+    /// Queue feeding the background revalidator, if one was started
+    revalidate_tx: OnceLock<mpsc::UnboundedSender<PriceKey>>,
+    /// Pairs already queued for revalidation
+    revalidating: std::sync::Mutex<HashSet<PriceKey>>,
+    /// Bars built from streamed trades
+    live_candles: Arc<CandleBuilder>,
+    /// Asset class, preferred sources and mints per symbol
+    registry: Arc<SymbolRegistry>,
+    /// Circuit breaker per source name, shared across asset classes
+    breakers: HashMap<String, Arc<CircuitBreaker>>,
+    /// Settings for breakers of sources added from now on
+    breaker_threshold: u32,
+    breaker_cooldown: std::time::Duration,
 }
 
 impl Default for PriceAggregator {
@@ -98,69 +219,197 @@ impl PriceAggregator {
             crypto_sources: Vec::new(),
             stock_sources: Vec::new(),
             metal_sources: Vec::new(),
+            fx_sources: Vec::new(),
             realtime_sources: Vec::new(),
-            cache: None,
+            gap_tx: broadcast::channel(64).0,
+            price_tx: broadcast::channel(10000).0,
+            cache: cache::TieredCache::new(MAX_CACHE_SIZE),
             latest_prices: Arc::new(RwLock::new(HashMap::new())),
+            inflight: SingleFlight::new(),
+            negative_cache: RwLock::new(HashMap::new()),
+            request_stats: RequestStats::default(),
+            max_staleness: std::time::Duration::from_secs(DEFAULT_MAX_STALENESS_SECS),
+            max_spread_percent: aggregators::DEFAULT_MAX_SPREAD_PERCENT,
+            revalidate_tx: OnceLock::new(),
+            revalidating: std::sync::Mutex::new(HashSet::new()),
+            live_candles: Arc::new(CandleBuilder::default()),
+            registry: Arc::new(SymbolRegistry::builtin()),
+            breakers: HashMap::new(),
+            breaker_threshold: breaker::DEFAULT_FAILURE_THRESHOLD,
+            breaker_cooldown: breaker::DEFAULT_COOLDOWN,
+        }
+    }
+
+    /// Route requests by `registry` instead of the built-in one
+    pub fn with_registry(mut self, registry: Arc<SymbolRegistry>) -> Self {
+        self.registry = registry;
+        self
+    }
+
+    pub fn registry(&self) -> &Arc<SymbolRegistry> {
+        &self.registry
+    }
+
+    /// Canonical pair, with mints the registry lists resolved to their symbol
+    pub fn canonical_pair(&self, asset: &str, quote: &str) -> SymbolId {
+        let pair = SymbolId::with_default_quote(asset, quote);
+        if symbol::looks_like_mint(pair.base()) {
+            return SymbolId::new(&self.registry.canonical_symbol(pair.base()), pair.quote());
         }
+        pair
+    }
+
+    /// Set when source breakers open and how long they stay open; applies
+    /// to sources added afterwards
+    pub fn with_circuit_breaker(mut self, threshold: u32, cooldown: std::time::Duration) -> Self {
+        self.breaker_threshold = threshold;
+        self.breaker_cooldown = cooldown;
+        self
+    }
+
+    fn register_breaker(&mut self, source: &Arc<dyn PriceDataSource>) {
+        let (threshold, cooldown) = (self.breaker_threshold, self.breaker_cooldown);
+        self.breakers
+            .entry(source.name().to_string())
+            .or_insert_with(|| Arc::new(CircuitBreaker::new(threshold, cooldown)));
+    }
+
+    /// Breaker for a source (every added source has one)
+    fn breaker(&self, source: &dyn PriceDataSource) -> Option<&CircuitBreaker> {
+        self.breakers.get(source.name()).map(Arc::as_ref)
     }
 
     pub fn add_crypto_source(&mut self, source: Arc<dyn PriceDataSource>) {
+        self.register_breaker(&source);
         self.crypto_sources.push(source);
     }
 
     pub fn add_stock_source(&mut self, source: Arc<dyn PriceDataSource>) {
+        self.register_breaker(&source);
         self.stock_sources.push(source);
     }
 
     pub fn add_metal_source(&mut self, source: Arc<dyn PriceDataSource>) {
+        self.register_breaker(&source);
         self.metal_sources.push(source);
     }
 
-    pub fn add_realtime_source(&mut self, source: Arc<BinanceWebSocketClient>) {
+    pub fn add_fx_source(&mut self, source: Arc<dyn PriceDataSource>) {
+        self.register_breaker(&source);
+        self.fx_sources.push(source);
+    }
+
+    pub fn add_realtime_source(&mut self, source: Arc<dyn RealtimePriceSource>) {
         self.realtime_sources.push(source);
     }
 
+    /// Receive every streamed price update from now on
+    ///
+    /// A receiver that falls too far behind skips ahead (`Lagged`).
+    pub fn price_feed(&self) -> broadcast::Receiver<PricePoint> {
+        self.price_tx.subscribe()
+    }
+
+    /// Latest streamed price for a `BASE/QUOTE` symbol, however old
+    pub async fn latest_streamed(&self, symbol: &str) -> Option<PricePoint> {
+        self.latest_prices.read().await.get(symbol).cloned()
+    }
+
+    /// Receive a [`StreamGap`] whenever a streaming source recovers
+    pub fn gap_feed(&self) -> broadcast::Receiver<StreamGap> {
+        self.gap_tx.subscribe()
+    }
+
+    /// Share cached prices and candles through Redis, behind the memory tier
     pub fn with_cache(mut self, cache: cache::RedisCache) -> Self {
-        self.cache = Some(cache);
+        self.cache = self.cache.with_l2(Arc::new(cache));
+        self
+    }
+
+    /// Put Redis behind the memory cache of a running aggregator
+    pub fn attach_cache(&self, cache: cache::RedisCache) {
+        self.cache.attach_l2(Arc::new(cache));
+    }
+
+    /// Hit and miss counts per cache tier and namespace
+    pub fn cache_stats(&self) -> Vec<cache::TierStats> {
+        self.cache.stats()
+    }
+
+    /// Whether Redis is attached and in use
+    pub fn shared_cache_status(&self) -> Option<cache::SharedTierStatus> {
+        self.cache.l2_status()
+    }
+
+    /// Set how long past freshness a cached price may still be served
+    pub fn with_max_staleness(mut self, max_staleness: std::time::Duration) -> Self {
+        self.max_staleness = max_staleness;
+        self
+    }
+
+    /// Set the spread between sources, in percent, past which aggregated
+    /// prices are flagged `unreliable`
+    pub fn with_max_spread_percent(mut self, max_spread_percent: f64) -> Self {
+        self.max_spread_percent = max_spread_percent;
         self
     }
 
     /// Start background task to consume real-time price updates
     ///
     /// Includes automatic reconnection with exponential backoff when disconnected.
+    /// A source that goes quiet is re-checked every few seconds, so one that
+    /// reports itself stale gets reconnected rather than waited on forever.
     pub async fn start_realtime_consumer(&self) {
         let latest_prices = Arc::clone(&self.latest_prices);
 
         for source in &self.realtime_sources {
             let source = Arc::clone(source);
             let prices = Arc::clone(&latest_prices);
+            let gap_tx = self.gap_tx.clone();
+            let price_tx = self.price_tx.clone();
 
             tokio::spawn(async move {
                 let mut eviction_counter = 0u32;
                 let mut reconnect_delay_secs = 1u64;
                 const MAX_RECONNECT_DELAY: u64 = 60;
+                // Start of the current outage and reconnects tried in it
+                let mut down_since: Option<DateTime<Utc>> = None;
+                let mut attempts = 0u32;
 
                 loop {
                     // Check connection status and attempt reconnect if needed
                     if !source.is_connected().await {
+                        let started_at = *down_since.get_or_insert_with(Utc::now);
                         warn!(
-                            "WebSocket disconnected, attempting reconnect in {}s...",
+                            "{} disconnected, attempting reconnect in {}s...",
+                            source.name(),
                             reconnect_delay_secs
                         );
                         tokio::time::sleep(tokio::time::Duration::from_secs(reconnect_delay_secs))
                             .await;
 
-                        // Clone the source for reconnection (need mutable access)
-                        // Note: reconnect() requires &mut self, so we work around via interior mutability
-                        // The reconnect method already handles this internally via Arc<Mutex>
-                        let source_clone = Arc::clone(&source);
-                        match reconnect_ws(&source_clone).await {
+                        attempts += 1;
+                        match source.reconnect().await {
                             Ok(()) => {
-                                info!("WebSocket reconnected successfully");
+                                let gap = StreamGap {
+                                    source: source.name().to_string(),
+                                    started_at,
+                                    ended_at: Utc::now(),
+                                    attempts,
+                                };
+                                info!(
+                                    "{} reconnected after {}s without data",
+                                    gap.source,
+                                    (gap.ended_at - gap.started_at).num_seconds()
+                                );
+                                // Nobody listening is fine
+                                let _ = gap_tx.send(gap);
+                                down_since = None;
+                                attempts = 0;
                                 reconnect_delay_secs = 1; // Reset backoff on success
                             }
                             Err(e) => {
-                                warn!("WebSocket reconnection failed: {}", e);
+                                warn!("{} reconnection failed: {}", source.name(), e);
                                 // Exponential backoff, capped at MAX_RECONNECT_DELAY
                                 reconnect_delay_secs =
                                     (reconnect_delay_secs * 2).min(MAX_RECONNECT_DELAY);
@@ -169,7 +418,19 @@ impl PriceAggregator {
                         }
                     }
 
-                    if let Some(price) = source.next_price().await {
+                    let next = tokio::time::timeout(
+                        tokio::time::Duration::from_secs(REALTIME_POLL_SECS),
+                        source.next_price(),
+                    )
+                    .await;
+                    let Ok(next) = next else {
+                        // Quiet stream: loop back to the connection check
+                        continue;
+                    };
+
+                    if let Some(price) = next {
+                        // No stream subscribers is fine
+                        let _ = price_tx.send(price.clone());
                         // Use the symbol field directly
                         let key = price.symbol.clone();
                         let mut p = prices.write().await;
@@ -191,7 +452,7 @@ impl PriceAggregator {
                             // If still over max size, evict oldest entries
                             if p.len() > MAX_CACHE_SIZE {
                                 let mut entries: Vec<_> = p.drain().collect();
-                                entries.sort_by(|a, b| b.1.timestamp.cmp(&a.1.timestamp));
+                                entries.sort_by_key(|e| std::cmp::Reverse(e.1.timestamp));
                                 entries.truncate(MAX_CACHE_SIZE);
                                 for (k, v) in entries {
                                     p.insert(k, v);
@@ -212,20 +473,57 @@ impl PriceAggregator {
         }
     }
 
+    /// Roll a trade stream into bars (see [`candle_builder`])
+    pub fn start_candle_builder(&self, mut trades: broadcast::Receiver<Trade>) {
+        let builder = Arc::clone(&self.live_candles);
+        tokio::spawn(async move {
+            loop {
+                match trades.recv().await {
+                    Ok(trade) => builder.ingest(&trade),
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        warn!("Candle builder fell behind, skipped {} trades", skipped);
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                }
+            }
+        });
+    }
+
+    /// Newest `limit` bars built from streamed trades, oldest first
+    ///
+    /// The last bar is still forming. Only 1m, 5m and 1h bars are built, and
+    /// only for streamed pairs; USD falls back to USDT-quoted bars, since
+    /// that's what Binance trades.
+    pub fn live_candles(
+        &self,
+        asset: &str,
+        quote: &str,
+        timeframe: TimeFrame,
+        limit: usize,
+    ) -> Vec<Candle> {
+        let pair = self.canonical_pair(asset, quote);
+        let (asset, quote) = (pair.base(), pair.quote());
+        let candles = self.live_candles.candles(asset, quote, timeframe, limit);
+        if candles.is_empty() && quote.eq_ignore_ascii_case("USD") {
+            return self.live_candles.candles(asset, "USDT", timeframe, limit);
+        }
+        candles
+    }
+
     /// Get real-time price (from WebSocket if available, else cached/REST)
     pub async fn get_price_realtime(&self, asset: &str, quote: &str) -> Result<PricePoint> {
+        let pair = self.canonical_pair(asset, quote);
+        let (asset, quote) = (pair.base(), pair.quote());
         let key = format!("{}/{}", asset.to_uppercase(), quote.to_uppercase());
 
-        // Check real-time cache first (WebSocket) - only for crypto
-        let asset_class = AssetClass::from_symbol(asset);
-        if asset_class == AssetClass::Crypto {
-            {
-                let prices = self.latest_prices.read().await;
-                if let Some(price) = prices.get(&key) {
-                    // Check if fresh (< 5 seconds for real-time)
-                    if (Utc::now() - price.timestamp).num_seconds() < 5 {
-                        return Ok(price.clone());
-                    }
+        // Check real-time cache first (Binance WS for crypto, Pyth stream for
+        // equities/metals)
+        {
+            let prices = self.latest_prices.read().await;
+            if let Some(price) = prices.get(&key) {
+                // Check if fresh (< 5 seconds for real-time)
+                if (Utc::now() - price.timestamp).num_seconds() < 5 {
+                    return Ok(price.clone());
                 }
             }
         }
@@ -239,27 +537,312 @@ impl PriceAggregator {
                 source: "aggregated".to_string(),
                 timestamp: agg.timestamp,
                 confidence: Some(agg.confidence),
+                stale: agg.stale,
+                unreliable: agg.unreliable,
+                unit: units::metal_spec(asset).map(|m| m.unit),
             })
     }
 
     /// Get aggregated price from appropriate sources for asset class
+    ///
+    /// Concurrent calls for the same pair are coalesced into one upstream fetch,
+    /// and pairs no source recognises are remembered for a short while.
+    ///
+    /// A cached price past its freshness window but within `max_staleness` is
+    /// returned with `stale: true` while it is refreshed in the background.
+    /// Without a background revalidator the refresh runs inline and the stale
+    /// value is only used if every source fails.
+    ///
+    /// Quotes in another fiat currency (EUR, GBP, BRL, ...) are fetched in
+    /// USD and converted once FX sources are configured (see [`fx`]).
     pub async fn get_aggregated_price(&self, asset: &str, quote: &str) -> Result<AggregatedPrice> {
+        let pair = self.canonical_pair(asset, quote);
+        let (asset, quote) = (pair.base(), pair.quote());
+        if !self.fx_sources.is_empty() && fx::fx_pair(quote).is_some() && !units::is_fx_pair(asset)
+        {
+            let (usd, rate) = tokio::join!(
+                self.get_aggregated_pair(asset, fx::USD),
+                self.fx_rate(quote)
+            );
+            return Ok(fx::convert(&usd?, &rate?));
+        }
+        self.get_aggregated_pair(asset, quote).await
+    }
+
+    /// Units of `currency` per USD, from its FX pair's aggregated price
+    pub async fn fx_rate(&self, currency: &str) -> Result<fx::FxRate> {
+        let (pair, _) = fx::fx_pair(currency).ok_or_else(|| {
+            DataRetrievalError::AssetNotFound(format!("{}/{}", fx::USD, currency))
+        })?;
+        let price = self.get_aggregated_pair(pair, fx::USD).await?;
+        fx::rate_from_pair(currency, &price)
+    }
+
+    /// [`Self::get_aggregated_price`] for a canonical pair, without conversion
+    async fn get_aggregated_pair(&self, asset: &str, quote: &str) -> Result<AggregatedPrice> {
+        // Already canonical; unknown mints have to keep their case
+        let key = (asset.to_string(), quote.to_string());
+        self.request_stats.record_request(&key);
+
         // Try cache first
-        if let Some(ref cache) = self.cache {
-            if let Ok(Some(cached)) = cache.get_price(asset, quote).await {
-                if (Utc::now() - cached.timestamp).num_seconds() < 30 {
-                    return Ok(cached);
+        let mut stale = None;
+        let cached: Option<AggregatedPrice> = self
+            .cache
+            .get(cache::Namespace::Prices, &format!("{}:{}", asset, quote))
+            .await;
+        if let Some(cached) = cached {
+            let age_secs = (Utc::now() - cached.timestamp).num_seconds();
+            match classify_cache_age(age_secs, self.max_staleness) {
+                CacheAge::Fresh => return Ok(cached),
+                CacheAge::Stale => stale = Some(cached),
+                CacheAge::Expired => {}
+            }
+        }
+
+        if self.is_known_unsupported(&key).await {
+            return Err(DataRetrievalError::AssetNotFound(format!(
+                "{}/{}",
+                key.0, key.1
+            )));
+        }
+
+        let Some(mut stale) = stale else {
+            return self.fetch_coalesced(&key).await;
+        };
+        stale.stale = true;
+
+        if self.request_revalidation(&key) {
+            return Ok(stale);
+        }
+
+        match self.fetch_coalesced(&key).await {
+            Err(e) if !matches!(e, DataRetrievalError::AssetNotFound(_)) => {
+                warn!("Serving stale price for {}/{}: {}", key.0, key.1, e);
+                Ok(stale)
+            }
+            result => result,
+        }
+    }
+
+    /// Install the sender used to queue background revalidations
+    ///
+    /// Returns false if a revalidator is already attached.
+    pub(crate) fn attach_revalidator(&self, tx: mpsc::UnboundedSender<PriceKey>) -> bool {
+        self.revalidate_tx.set(tx).is_ok()
+    }
+
+    /// Queue a pair for background refresh; false if no revalidator is running
+    fn request_revalidation(&self, key: &PriceKey) -> bool {
+        let Some(tx) = self.revalidate_tx.get() else {
+            return false;
+        };
+        if !self.revalidating.lock().unwrap().insert(key.clone()) {
+            // Already queued
+            return true;
+        }
+        if tx.send(key.clone()).is_err() {
+            self.revalidating.lock().unwrap().remove(key);
+            return false;
+        }
+        true
+    }
+
+    /// Mark a queued revalidation as done
+    pub(crate) fn finish_revalidation(&self, key: &PriceKey) {
+        self.revalidating.lock().unwrap().remove(key);
+    }
+
+    /// Re-fetch a pair from upstream and refresh its cache entry
+    ///
+    /// Used by the hot symbol refresher; does not count as a client request.
+    pub(crate) async fn refresh_price(&self, key: &PriceKey) -> Result<AggregatedPrice> {
+        if self.is_known_unsupported(key).await {
+            return Err(DataRetrievalError::AssetNotFound(format!(
+                "{}/{}",
+                key.0, key.1
+            )));
+        }
+        self.fetch_coalesced(key).await
+    }
+
+    /// Fetch through the single-flight group, recording the outcome
+    async fn fetch_coalesced(&self, key: &PriceKey) -> Result<AggregatedPrice> {
+        self.inflight
+            .run(key.clone(), || async {
+                let result = self.fetch_aggregated_price(&key.0, &key.1).await;
+                match result {
+                    Ok(_) => self.request_stats.record_fetch(key),
+                    Err(DataRetrievalError::AssetNotFound(_)) => {
+                        self.negative_cache
+                            .write()
+                            .await
+                            .insert(key.clone(), Instant::now());
+                    }
+                    Err(_) => {}
                 }
+                result
+            })
+            .await
+    }
+
+    /// Check (and expire) the negative cache for a pair
+    async fn is_known_unsupported(&self, key: &PriceKey) -> bool {
+        let ttl = std::time::Duration::from_secs(NEGATIVE_CACHE_TTL_SECS);
+        {
+            let negative = self.negative_cache.read().await;
+            match negative.get(key) {
+                None => return false,
+                Some(at) if at.elapsed() < ttl => return true,
+                Some(_) => {}
             }
         }
+        self.negative_cache.write().await.remove(key);
+        false
+    }
 
-        // Route to appropriate sources based on asset class
-        let asset_class = AssetClass::from_symbol(asset);
-        let sources: &[Arc<dyn PriceDataSource>] = match asset_class {
+    /// Feed a source call's outcome to its breaker ("not found" counts as up)
+    fn record_outcome<T>(&self, source: &dyn PriceDataSource, result: &Result<T>) {
+        let Some(breaker) = self.breaker(source) else {
+            return;
+        };
+        match result {
+            Ok(_) | Err(DataRetrievalError::AssetNotFound(_)) => breaker.record_success(),
+            Err(_) => {
+                breaker.record_failure();
+                if breaker.state() == BreakerState::Open {
+                    debug!("Circuit breaker open for {}", source.name());
+                }
+            }
+        }
+    }
+
+    /// Sources for an asset's class, narrowed to the ones its registry entry
+    /// prefers (all of them if none of those are configured)
+    fn sources_for_asset(&self, asset: &str) -> (AssetClass, Vec<&Arc<dyn PriceDataSource>>) {
+        let entry = self.registry.lookup(asset);
+        let asset_class = entry
+            .as_ref()
+            .map(|e| e.asset_class)
+            .unwrap_or_else(|| AssetClass::from_symbol(asset));
+        let sources = self.sources_for(asset_class);
+        let preferred: Vec<_> = sources
+            .iter()
+            .filter(|source| entry.as_ref().is_none_or(|e| e.prefers(source.name())))
+            .collect();
+        if preferred.is_empty() {
+            (asset_class, sources.iter().collect())
+        } else {
+            (asset_class, preferred)
+        }
+    }
+
+    /// Sources configured for an asset class
+    fn sources_for(&self, asset_class: AssetClass) -> &[Arc<dyn PriceDataSource>] {
+        match asset_class {
             AssetClass::Crypto => &self.crypto_sources,
             AssetClass::Stock | AssetClass::Etf => &self.stock_sources,
             AssetClass::Metal => &self.metal_sources,
-        };
+            AssetClass::Fx => &self.fx_sources,
+        }
+    }
+
+    /// Get candles from the first source for the asset class that has them
+    ///
+    /// Bars built from streamed trades are used when they cover `limit`;
+    /// otherwise results are cached for `CANDLE_CACHE_SECS`.
+    pub async fn get_candles(
+        &self,
+        asset: &str,
+        quote: &str,
+        timeframe: TimeFrame,
+        limit: usize,
+    ) -> Result<Vec<Candle>> {
+        let pair = self.canonical_pair(asset, quote);
+        let (asset, quote) = (pair.base(), pair.quote());
+        let live = self.live_candles(asset, quote, timeframe, limit);
+        if limit > 0 && live.len() >= limit {
+            return Ok(live);
+        }
+
+        let key = format!("{}:{}:{}", asset, quote, timeframe.as_str());
+        let cached: Option<Vec<Candle>> = self.cache.get(cache::Namespace::Candles, &key).await;
+        if let Some(candles) = cached {
+            return Ok(candles);
+        }
+
+        let mut last_error = None;
+        for source in self.sources_for_asset(asset).1 {
+            if self
+                .breaker(source.as_ref())
+                .is_some_and(|breaker| !breaker.allow())
+            {
+                continue;
+            }
+            let result = source.get_candles(asset, quote, timeframe, limit).await;
+            self.record_outcome(source.as_ref(), &result);
+            match result {
+                Ok(candles) if !candles.is_empty() => {
+                    let ttl = std::time::Duration::from_secs(CANDLE_CACHE_SECS);
+                    self.cache
+                        .set(cache::Namespace::Candles, &key, &candles, ttl)
+                        .await;
+                    return Ok(candles);
+                }
+                Ok(_) => {}
+                Err(e) => last_error = Some(e),
+            }
+        }
+
+        Err(last_error.unwrap_or_else(|| {
+            DataRetrievalError::AssetNotFound(format!("no candles for {}/{}", asset, quote))
+        }))
+    }
+
+    /// Percent changes and today's OHLC around `spot`, from cached hourly candles
+    ///
+    /// Returns `None` when no source has candles for the pair.
+    pub async fn market_summary(
+        &self,
+        asset: &str,
+        quote: &str,
+        spot: rust_decimal::Decimal,
+    ) -> Option<MarketSummary> {
+        let pair = self.canonical_pair(asset, quote);
+        let (asset, quote) = (pair.base(), pair.quote());
+        match self
+            .get_candles(asset, quote, TimeFrame::Hour1, SUMMARY_CANDLES)
+            .await
+        {
+            Ok(candles) => Some(aggregators::summarize_market(&candles, spot, Utc::now())),
+            Err(e) => {
+                debug!("No market summary for {}/{}: {}", asset, quote, e);
+                None
+            }
+        }
+    }
+
+    /// Regime from the same cached hourly candles as [`Self::market_summary`]
+    ///
+    /// Returns `None` when no source has enough candles for the pair.
+    pub async fn market_regime(&self, asset: &str, quote: &str) -> Option<regime::MarketRegime> {
+        let pair = self.canonical_pair(asset, quote);
+        let (asset, quote) = (pair.base(), pair.quote());
+        match self
+            .get_candles(asset, quote, TimeFrame::Hour1, SUMMARY_CANDLES)
+            .await
+        {
+            Ok(candles) => regime::classify(&candles),
+            Err(e) => {
+                debug!("No regime for {}/{}: {}", asset, quote, e);
+                None
+            }
+        }
+    }
+
+    /// Fan out to every source for the pair's asset class and aggregate
+    async fn fetch_aggregated_price(&self, asset: &str, quote: &str) -> Result<AggregatedPrice> {
+        // Route to appropriate sources based on asset class
+        let (asset_class, sources) = self.sources_for_asset(asset);
 
         if sources.is_empty() {
             return Err(DataRetrievalError::SourceUnhealthy(format!(
@@ -268,9 +851,21 @@ impl PriceAggregator {
             )));
         }
 
+        // Skip sources whose breaker is open
+        let sources: Vec<&Arc<dyn PriceDataSource>> = sources
+            .into_iter()
+            .filter(|source| self.breaker(source.as_ref()).is_none_or(|b| b.allow()))
+            .collect();
+        if sources.is_empty() {
+            return Err(DataRetrievalError::SourceUnhealthy(format!(
+                "Every {:?} source's circuit breaker is open",
+                asset_class
+            )));
+        }
+
         // Fetch from all sources concurrently
         let mut futures = Vec::new();
-        for source in sources {
+        for source in &sources {
             let fut = source.get_price(asset, quote);
             futures.push(fut);
         }
@@ -279,89 +874,44 @@ impl PriceAggregator {
 
         // Collect successful results
         let mut prices: Vec<PricePoint> = Vec::new();
-        for result in results {
+        let mut not_found = 0usize;
+        for (source, result) in sources.iter().zip(results) {
+            self.record_outcome(source.as_ref(), &result);
             match result {
                 Ok(price) => prices.push(price),
+                Err(DataRetrievalError::AssetNotFound(_)) => not_found += 1,
                 Err(e) => warn!("Source error: {}", e),
             }
         }
 
         if prices.is_empty() {
+            // Every source says it doesn't know the symbol: report that, not an outage
+            if not_found == sources.len() {
+                return Err(DataRetrievalError::AssetNotFound(format!(
+                    "{}/{}",
+                    asset, quote
+                )));
+            }
             return Err(DataRetrievalError::SourceUnhealthy(
                 "All sources failed".to_string(),
             ));
         }
 
-        // Calculate weighted median
-        let total_weight: f64 = prices.iter().map(|p| p.confidence.unwrap_or(0.5)).sum();
-
-        // Guard against division by zero if all sources have zero confidence
-        if total_weight < f64::EPSILON {
-            return Err(DataRetrievalError::SourceUnhealthy(
-                "All sources have zero confidence".to_string(),
-            ));
-        }
-
-        let weighted_sum: rust_decimal::Decimal = prices
-            .iter()
-            .map(|p| {
-                let weight = p.confidence.unwrap_or(0.5);
-                p.price * rust_decimal::Decimal::try_from(weight).unwrap_or_default()
-            })
-            .sum();
+        let mut result =
+            aggregators::aggregate_prices(&prices, Utc::now(), self.max_spread_percent)?;
+        result.asset = asset.to_string();
+        result.quote = quote.to_string();
 
-        let total_weight_decimal = rust_decimal::Decimal::try_from(total_weight)
-            .map_err(|_| DataRetrievalError::SourceUnhealthy("Invalid weight value".to_string()))?;
-        let aggregated_price = weighted_sum / total_weight_decimal;
-
-        // Calculate spread
-        let prices_f64: Vec<f64> = prices
-            .iter()
-            .filter_map(|p| {
-                // Convert Decimal to f64 via string parsing
-                let s = p.price.to_string();
-                s.parse::<f64>().ok()
-            })
-            .collect();
-
-        let min_price = prices_f64.iter().copied().fold(f64::INFINITY, f64::min);
-        let max_price = prices_f64.iter().copied().fold(f64::NEG_INFINITY, f64::max);
-        let avg_price = (min_price + max_price) / 2.0;
-        let spread_percent = if avg_price > 0.0 {
-            (max_price - min_price) / avg_price * 100.0
-        } else {
-            0.0
-        };
-
-        // Build source contributions
-        let sources: Vec<PriceSource> = prices
-            .iter()
-            .map(|p| PriceSource {
-                source: p.source.clone(),
-                price: p.price,
-                weight: p.confidence.unwrap_or(0.5) / total_weight,
-                timestamp: p.timestamp,
-            })
-            .collect();
-
-        let result = AggregatedPrice {
-            asset: asset.to_uppercase(),
-            quote: quote.to_uppercase(),
-            price: aggregated_price,
-            sources,
-            timestamp: Utc::now(),
-            confidence: prices
-                .iter()
-                .map(|p| p.confidence.unwrap_or(0.5))
-                .sum::<f64>()
-                / prices.len() as f64,
-            spread_percent,
-        };
-
-        // Cache result
-        if let Some(ref cache) = self.cache {
-            let _ = cache.set_price(asset, quote, &result).await;
-        }
+        // Cache result (kept around long enough to be served stale)
+        let ttl_secs = self.max_staleness.as_secs().max(CACHE_FRESH_SECS as u64);
+        self.cache
+            .set(
+                cache::Namespace::Prices,
+                &format!("{}:{}", asset, quote),
+                &result,
+                std::time::Duration::from_secs(ttl_secs),
+            )
+            .await;
 
         Ok(result)
     }
@@ -372,49 +922,62 @@ impl PriceAggregator {
     }
 
     /// Get batch prices for multiple stocks
+    ///
+    /// Symbols are fetched concurrently, at most [`BATCH_CONCURRENCY`] at a
+    /// time, and each gets its own result so one failure doesn't sink the
+    /// batch.
     pub async fn get_stock_prices_batch(
         &self,
         symbols: &[&str],
-    ) -> Result<HashMap<String, PricePoint>> {
-        let mut results = HashMap::new();
+    ) -> HashMap<String, Result<PricePoint>> {
+        use futures::StreamExt;
 
-        for symbol in symbols {
-            match self.get_stock_price(symbol).await {
-                Ok(price) => {
-                    results.insert(symbol.to_string(), price);
+        futures::stream::iter(symbols)
+            .map(|symbol| async move {
+                let price = self.get_stock_price(symbol).await;
+                if let Err(e) = &price {
+                    warn!("Failed to get price for {}: {}", symbol, e);
                 }
-                Err(e) => warn!("Failed to get price for {}: {}", symbol, e),
-            }
-        }
-
-        Ok(results)
+                (symbol.to_string(), price)
+            })
+            .buffer_unordered(BATCH_CONCURRENCY)
+            .collect()
+            .await
     }
 
     /// Get health status of all sources
     pub async fn health_check(&self) -> Vec<SourceHealth> {
         let mut healths = Vec::new();
 
-        for source in &self.crypto_sources {
-            healths.push(source.health().await);
-        }
-
-        for source in &self.stock_sources {
-            healths.push(source.health().await);
-        }
-
-        for source in &self.metal_sources {
-            healths.push(source.health().await);
+        let sources = self
+            .crypto_sources
+            .iter()
+            .chain(&self.stock_sources)
+            .chain(&self.metal_sources)
+            .chain(&self.fx_sources);
+        for source in sources {
+            let mut health = source.health().await;
+            if let Some(breaker) = self.breaker(source.as_ref()) {
+                let state = breaker.state();
+                if state != BreakerState::Closed {
+                    health.is_healthy = false;
+                }
+                health.breaker = Some(state);
+            }
+            healths.push(health);
         }
 
-        // Add WebSocket sources
+        // Add streaming sources
         for ws in &self.realtime_sources {
+            let connected = ws.is_connected().await;
             healths.push(SourceHealth {
-                source: "binance_ws".to_string(),
-                is_healthy: ws.is_connected().await,
+                source: ws.name().to_string(),
+                is_healthy: connected,
                 last_success: Some(Utc::now()),
                 last_error: None,
-                success_rate_24h: if ws.is_connected().await { 1.0 } else { 0.0 },
-                avg_latency_ms: 50, // WebSocket is fast
+                success_rate_24h: if connected { 1.0 } else { 0.0 },
+                avg_latency_ms: 50, // Streams are fast
+                breaker: None,
             });
         }
 
@@ -424,10 +987,11 @@ impl PriceAggregator {
     /// Get supported symbols for each asset class
     pub fn get_supported_symbols(&self) -> SupportedSymbols {
         SupportedSymbols {
-            crypto: vec!["BTC", "ETH", "SOL", "BNB", "XRP", "ADA"],
-            stocks: PythClient::supported_stocks(),
-            etfs: PythClient::supported_etfs(),
-            metals: PythClient::supported_metals(),
+            crypto: self.registry.symbols(AssetClass::Crypto),
+            stocks: self.registry.symbols(AssetClass::Stock),
+            etfs: self.registry.symbols(AssetClass::Etf),
+            metals: self.registry.symbols(AssetClass::Metal),
+            fx: self.registry.symbols(AssetClass::Fx),
         }
     }
 }
@@ -435,8 +999,325 @@ impl PriceAggregator {
 /// List of supported symbols by category
 #[derive(Debug, Clone)]
 pub struct SupportedSymbols {
-    pub crypto: Vec<&'static str>,
-    pub stocks: Vec<&'static str>,
-    pub etfs: Vec<&'static str>,
-    pub metals: Vec<&'static str>,
+    pub crypto: Vec<String>,
+    pub stocks: Vec<String>,
+    pub etfs: Vec<String>,
+    pub metals: Vec<String>,
+    pub fx: Vec<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// Source that counts calls and knows only one symbol (and errors on DOWN)
+    struct CountingSource {
+        calls: AtomicUsize,
+        known: &'static str,
+    }
+
+    #[async_trait::async_trait]
+    impl PriceDataSource for CountingSource {
+        async fn get_price(&self, asset: &str, quote: &str) -> Result<PricePoint> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            tokio::time::sleep(tokio::time::Duration::from_millis(20)).await;
+            if asset == "DOWN" {
+                return Err(DataRetrievalError::ApiError("503".to_string()));
+            }
+            if asset != self.known {
+                return Err(DataRetrievalError::AssetNotFound(asset.to_string()));
+            }
+            Ok(PricePoint::new(
+                format!("{}/{}", asset, quote),
+                rust_decimal::Decimal::from(100),
+                "counting",
+                Utc::now(),
+                Some(0.9),
+            ))
+        }
+
+        async fn get_candles(
+            &self,
+            _asset: &str,
+            _quote: &str,
+            _timeframe: TimeFrame,
+            _limit: usize,
+        ) -> Result<Vec<Candle>> {
+            Ok(Vec::new())
+        }
+
+        async fn health(&self) -> SourceHealth {
+            SourceHealth {
+                source: "counting".to_string(),
+                is_healthy: true,
+                last_success: None,
+                last_error: None,
+                success_rate_24h: 1.0,
+                avg_latency_ms: 0,
+                breaker: None,
+            }
+        }
+
+        fn name(&self) -> &str {
+            "counting"
+        }
+    }
+
+    fn aggregator_with(source: Arc<CountingSource>) -> PriceAggregator {
+        let mut aggregator = PriceAggregator::new();
+        aggregator.add_crypto_source(source);
+        aggregator
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_requests_are_coalesced() {
+        let source = Arc::new(CountingSource {
+            calls: AtomicUsize::new(0),
+            known: "BTC",
+        });
+        let aggregator = aggregator_with(Arc::clone(&source));
+
+        let results = futures::future::join_all(
+            (0..8).map(|_| aggregator.get_aggregated_price("BTC", "USD")),
+        )
+        .await;
+
+        assert!(results.iter().all(|r| r.is_ok()));
+        assert_eq!(source.calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_fiat_quotes_are_converted() {
+        let crypto = Arc::new(CountingSource {
+            calls: AtomicUsize::new(0),
+            known: "BTC",
+        });
+        let fx_source = Arc::new(CountingSource {
+            calls: AtomicUsize::new(0),
+            known: "USDJPY",
+        });
+
+        // Without FX sources the quote goes to the sources as asked
+        let plain = aggregator_with(Arc::clone(&crypto));
+        let price = plain.get_aggregated_price("BTC", "JPY").await.unwrap();
+        assert!(!price.converted);
+
+        let mut aggregator = aggregator_with(Arc::clone(&crypto));
+        aggregator.add_fx_source(fx_source.clone());
+        for _ in 0..2 {
+            let price = aggregator.get_aggregated_price("BTC", "JPY").await.unwrap();
+            assert_eq!(price.quote, "JPY");
+            assert_eq!(price.price, rust_decimal::Decimal::from(10_000));
+            assert_eq!(price.fx_rate, Some(rust_decimal::Decimal::from(100)));
+            assert!(price.converted);
+        }
+        // Both legs came from cache the second time
+        assert_eq!(crypto.calls.load(Ordering::SeqCst), 2);
+        assert_eq!(fx_source.calls.load(Ordering::SeqCst), 1);
+
+        let usd = aggregator.get_aggregated_price("BTC", "USD").await.unwrap();
+        assert!(!usd.converted);
+    }
+
+    #[tokio::test]
+    async fn test_unsupported_symbol_is_negatively_cached() {
+        let source = Arc::new(CountingSource {
+            calls: AtomicUsize::new(0),
+            known: "BTC",
+        });
+        let aggregator = aggregator_with(Arc::clone(&source));
+
+        for _ in 0..3 {
+            let err = aggregator.get_aggregated_price("NOPE", "USD").await;
+            assert!(matches!(err, Err(DataRetrievalError::AssetNotFound(_))));
+        }
+        assert_eq!(source.calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_batch_is_concurrent_with_partial_results() {
+        let source = Arc::new(CountingSource {
+            calls: AtomicUsize::new(0),
+            known: "BTC",
+        });
+        let aggregator = aggregator_with(Arc::clone(&source));
+        let mut symbols = vec!["BTC", "DOWN"];
+        let unknown: Vec<String> = (0..14).map(|i| format!("NOPE{}", i)).collect();
+        symbols.extend(unknown.iter().map(String::as_str));
+
+        let started = Instant::now();
+        let results = aggregator.get_stock_prices_batch(&symbols).await;
+        // 16 sequential fetches would take 320ms
+        assert!(started.elapsed() < std::time::Duration::from_millis(200));
+        assert_eq!(results.len(), 16);
+        assert!(results["BTC"].is_ok());
+        assert!(matches!(
+            results["DOWN"],
+            Err(DataRetrievalError::SourceUnhealthy(_))
+        ));
+        assert!(matches!(
+            results["NOPE0"],
+            Err(DataRetrievalError::AssetNotFound(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_failing_source_trips_breaker() {
+        let source = Arc::new(CountingSource {
+            calls: AtomicUsize::new(0),
+            known: "BTC",
+        });
+        let mut aggregator =
+            PriceAggregator::new().with_circuit_breaker(2, std::time::Duration::from_secs(60));
+        aggregator.add_crypto_source(Arc::clone(&source) as Arc<dyn PriceDataSource>);
+
+        // Not found doesn't count against the source
+        for _ in 0..3 {
+            let _ = aggregator.fetch_aggregated_price("NOPE", "USD").await;
+        }
+        assert_eq!(
+            aggregator.health_check().await[0].breaker,
+            Some(BreakerState::Closed)
+        );
+
+        for _ in 0..2 {
+            assert!(aggregator
+                .get_aggregated_price("DOWN", "USD")
+                .await
+                .is_err());
+        }
+        // Open: skipped without a call
+        let err = aggregator.get_aggregated_price("BTC", "USD").await;
+        assert!(matches!(err, Err(DataRetrievalError::SourceUnhealthy(_))));
+        assert_eq!(source.calls.load(Ordering::SeqCst), 5);
+
+        let health = &aggregator.health_check().await[0];
+        assert_eq!(health.breaker, Some(BreakerState::Open));
+        assert!(!health.is_healthy);
+    }
+
+    /// Stream that starts out down and comes back when reconnected
+    #[derive(Default)]
+    struct FlakyStream {
+        connected: std::sync::atomic::AtomicBool,
+    }
+
+    #[async_trait::async_trait]
+    impl RealtimePriceSource for FlakyStream {
+        fn name(&self) -> &str {
+            "flaky_ws"
+        }
+
+        async fn next_price(&self) -> Option<PricePoint> {
+            futures::future::pending().await
+        }
+
+        async fn is_connected(&self) -> bool {
+            self.connected.load(Ordering::SeqCst)
+        }
+
+        async fn reconnect(&self) -> Result<()> {
+            self.connected.store(true, Ordering::SeqCst);
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_reconnect_publishes_gap() {
+        let stream = Arc::new(FlakyStream::default());
+        let mut aggregator = PriceAggregator::new();
+        aggregator.add_realtime_source(Arc::clone(&stream) as Arc<dyn RealtimePriceSource>);
+        let mut gaps = aggregator.gap_feed();
+        aggregator.start_realtime_consumer().await;
+
+        let gap = gaps.recv().await.unwrap();
+        assert_eq!(gap.source, "flaky_ws");
+        assert_eq!(gap.attempts, 1);
+        assert!(gap.ended_at >= gap.started_at);
+        assert!(stream.is_connected().await);
+    }
+
+    /// Connected stream with one tick to give
+    struct OneTick(tokio::sync::Mutex<Option<PricePoint>>);
+
+    #[async_trait::async_trait]
+    impl RealtimePriceSource for OneTick {
+        fn name(&self) -> &str {
+            "tick_ws"
+        }
+
+        async fn next_price(&self) -> Option<PricePoint> {
+            match self.0.lock().await.take() {
+                Some(price) => Some(price),
+                None => futures::future::pending().await,
+            }
+        }
+
+        async fn is_connected(&self) -> bool {
+            true
+        }
+
+        async fn reconnect(&self) -> Result<()> {
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_streamed_prices_are_published() {
+        let tick = PricePoint::new(
+            "SOL/USDT".to_string(),
+            rust_decimal::Decimal::from(150),
+            "binance",
+            Utc::now(),
+            Some(0.95),
+        );
+        let mut aggregator = PriceAggregator::new();
+        aggregator.add_realtime_source(Arc::new(OneTick(tokio::sync::Mutex::new(Some(
+            tick.clone(),
+        )))));
+        let mut feed = aggregator.price_feed();
+        aggregator.start_realtime_consumer().await;
+
+        let published = feed.recv().await.unwrap();
+        assert_eq!(published.symbol, "SOL/USDT");
+        assert_eq!(published.price, tick.price);
+        tokio::time::sleep(tokio::time::Duration::from_millis(10)).await;
+        let latest = aggregator.latest_streamed("SOL/USDT").await.unwrap();
+        assert_eq!(latest.price, tick.price);
+        assert!(aggregator.latest_streamed("BTC/USDT").await.is_none());
+    }
+
+    #[test]
+    fn test_classify_cache_age() {
+        let max = std::time::Duration::from_secs(120);
+        assert_eq!(classify_cache_age(5, max), CacheAge::Fresh);
+        assert_eq!(classify_cache_age(90, max), CacheAge::Stale);
+        assert_eq!(classify_cache_age(120, max), CacheAge::Expired);
+    }
+
+    #[tokio::test]
+    async fn test_refresh_marks_hot_pair_fetched() {
+        let source = Arc::new(CountingSource {
+            calls: AtomicUsize::new(0),
+            known: "BTC",
+        });
+        let aggregator = aggregator_with(Arc::clone(&source));
+        let key = ("BTC".to_string(), "USD".to_string());
+        let max_age = std::time::Duration::from_secs(25);
+
+        aggregator.request_stats.record_request(&key);
+        aggregator.request_stats.record_request(&key);
+        assert_eq!(
+            aggregator.request_stats.due_for_refresh(10, 2.0, max_age),
+            vec![key.clone()]
+        );
+
+        aggregator.refresh_price(&key).await.unwrap();
+        assert_eq!(source.calls.load(Ordering::SeqCst), 1);
+        assert!(aggregator
+            .request_stats
+            .due_for_refresh(10, 2.0, max_age)
+            .is_empty());
+    }
 }
@@ -2,12 +2,28 @@ use axum::{routing::get, Router};
 use std::sync::Arc;
 use tower_http::cors::{Any, CorsLayer};
 use tower_http::trace::TraceLayer;
-use tracing::{info, warn, Level};
+use tracing::{debug, info, warn, Level};
+
+/// How often to retry Redis when it wasn't up at startup
+const REDIS_RECONNECT_INTERVAL: std::time::Duration = std::time::Duration::from_secs(30);
 
 /// Application state shared across handlers
 pub struct AppState {
-    pub price_aggregator: data_retrieval::PriceAggregator,
+    pub price_aggregator: Arc<data_retrieval::PriceAggregator>,
     pub pyth_client: data_retrieval::PythClient,
+    pub jupiter: data_retrieval::JupiterClient,
+    /// Order books for /liquidity; unset leaves Jupiter routes only
+    pub depth: Option<data_retrieval::sources::depth::DepthClient>,
+    /// Perpetual funding and open interest; unset where Binance is geo-blocked
+    pub derivatives: Option<data_retrieval::sources::derivatives::DerivativesClient>,
+    /// Fear & Greed, plus CryptoPanic news with CRYPTOPANIC_API_KEY
+    pub sentiment: data_retrieval::sources::sentiment::SentimentClient,
+    /// Pool liquidity, holders and authorities of Solana mints
+    pub onchain: data_retrieval::sources::onchain::OnChainClient,
+    /// Recorded candles, when DATABASE_URL is set
+    pub history: Option<data_retrieval::history::HistoryStore>,
+    /// Bearer token for /admin routes; unset disables them
+    pub admin_token: Option<String>,
 }
 
 #[tokio::main]
@@ -15,57 +31,208 @@ async fn main() -> anyhow::Result<()> {
     // Initialize logging
     tracing_subscriber::fmt().with_max_level(Level::INFO).init();
 
+    // `data-retrieval backfill [SYMBOL...]` fills the price history and exits
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.first().map(String::as_str) == Some("backfill") {
+        return backfill(&args[1..]).await;
+    }
+
     info!("Starting Data Retrieval Service...");
 
     // Initialize CoinGecko client (REST) for crypto
     let coingecko = Arc::new(data_retrieval::CoinGeckoClient::new(None));
     info!("✓ CoinGecko client initialized");
 
-    // Initialize Binance WebSocket (real-time) for crypto - optional, may be geo-blocked
-    let binance_ws = match data_retrieval::BinanceWebSocketClient::new().await {
-        Ok(client) => {
-            let ws = Arc::new(client);
-            // Subscribe to BTC, ETH, SOL real-time trades
-            if let Err(e) = ws.subscribe_trades("BTCUSDT").await {
-                warn!("Failed to subscribe to BTCUSDT: {}", e);
-            }
-            if let Err(e) = ws.subscribe_trades("ETHUSDT").await {
-                warn!("Failed to subscribe to ETHUSDT: {}", e);
-            }
-            if let Err(e) = ws.subscribe_trades("SOLUSDT").await {
-                warn!("Failed to subscribe to SOLUSDT: {}", e);
-            }
-            info!("✓ Binance WebSocket connected");
-            Some(ws)
-        }
-        Err(e) => {
-            warn!(
-                "⚠ Binance WebSocket unavailable ({}), continuing without real-time data",
-                e
-            );
-            None
-        }
+    // Geo-blocked sources are off by default in container builds (`docker` feature)
+    let geo_blocked_disabled =
+        env_flag("DISABLE_GEO_BLOCKED_SOURCES").unwrap_or(cfg!(feature = "docker"));
+
+    // Real-time crypto trade streams from REALTIME_EXCHANGES (default Binance,
+    // or Coinbase where geo-blocked sources are disabled)
+    let exchanges = realtime_exchanges(geo_blocked_disabled);
+    let binance_ws = if !exchanges.iter().any(|e| e == "binance") {
+        None
+    } else if geo_blocked_disabled {
+        info!("Geo-blocked sources disabled, skipping Binance WebSocket");
+        None
+    } else {
+        connect_binance().await
     };
+    let exchange_streams = connect_exchange_streams(&exchanges, geo_blocked_disabled).await;
 
     // Initialize Pyth client for stocks/metals
     let pyth_client = data_retrieval::PythClient::new();
     info!("✓ Pyth client initialized for xStocks/metals");
 
+    // Optional Postgres price history, recorded on a schedule
+    let history = connect_history();
+
+    // Symbol registry: built-ins, then SYMBOL_REGISTRY_PATH, then the database
+    let registry = symbol_registry(history.as_ref()).await;
+
+    // Quotes for /simulate-swap, and prices for any Solana mint; the API key
+    // just lifts the keyless rate limit
+    let jupiter = data_retrieval::JupiterClient::new(
+        std::env::var("JUPITER_API_KEY")
+            .ok()
+            .filter(|k| !k.is_empty()),
+    )
+    .with_registry(Arc::clone(&registry));
+
     // Create aggregator with crypto sources
-    let mut aggregator = data_retrieval::PriceAggregator::new();
+    let mut aggregator = data_retrieval::PriceAggregator::new().with_registry(registry);
     aggregator.add_crypto_source(coingecko);
+    // Jupiter prices for long-tail SPL tokens (JUPITER_PRICES=false to disable)
+    if env_flag("JUPITER_PRICES").unwrap_or(true) {
+        aggregator.add_crypto_source(Arc::new(jupiter.clone()));
+        info!("✓ Jupiter price source enabled");
+    }
     aggregator.add_stock_source(Arc::new(pyth_client.clone()));
     aggregator.add_metal_source(Arc::new(pyth_client.clone()));
+    aggregator.add_fx_source(Arc::new(pyth_client.clone()));
+    let mut has_realtime = false;
+    // Bars come from one trade feed (Binance first) so volume isn't counted twice
     if let Some(ws) = binance_ws {
+        aggregator.start_candle_builder(ws.trade_feed());
         aggregator.add_realtime_source(ws);
+        has_realtime = true;
+    }
+    for stream in exchange_streams {
+        if !has_realtime {
+            aggregator.start_candle_builder(stream.trade_feed());
+        }
+        aggregator.add_realtime_source(stream);
+        has_realtime = true;
+    }
+
+    // Pyth Hermes stream for equities/ETFs/metals (PYTH_STREAM=false to disable)
+    if env_flag("PYTH_STREAM").unwrap_or(true) {
+        if let Some(stream) = connect_pyth_stream().await {
+            aggregator.add_realtime_source(stream);
+            has_realtime = true;
+        }
+    }
+
+    if has_realtime {
         aggregator.start_realtime_consumer().await;
         info!("✓ Real-time price consumer started");
     }
 
+    // Optional Redis tier behind the in-memory cache (waits up to REDIS_WAIT_SECS for it to come up)
+    let mut redis_pending = None;
+    if let Ok(redis_url) = std::env::var("REDIS_URL") {
+        let wait = std::time::Duration::from_secs(env_parse("REDIS_WAIT_SECS").unwrap_or(30));
+        match data_retrieval::cache::RedisCache::connect_with_retry(&redis_url, wait).await {
+            Ok(cache) => {
+                aggregator = aggregator.with_cache(cache);
+                info!("✓ Redis price cache connected");
+            }
+            Err(e) => {
+                warn!("⚠ Redis unavailable ({}), caching in memory only", e);
+                redis_pending = Some(redis_url);
+            }
+        }
+    }
+
+    let max_staleness_secs = env_parse("PRICE_MAX_STALENESS_SECS").unwrap_or(120);
+    let max_spread_percent = env_parse("PRICE_MAX_SPREAD_PERCENT")
+        .unwrap_or(data_retrieval::aggregators::DEFAULT_MAX_SPREAD_PERCENT);
+    let aggregator = Arc::new(
+        aggregator
+            .with_max_staleness(std::time::Duration::from_secs(max_staleness_secs))
+            .with_max_spread_percent(max_spread_percent),
+    );
+
+    // Keep trying in the background and attach Redis once it's up
+    if let Some(redis_url) = redis_pending {
+        let aggregator = Arc::clone(&aggregator);
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(REDIS_RECONNECT_INTERVAL);
+            interval.tick().await;
+            loop {
+                interval.tick().await;
+                match data_retrieval::cache::RedisCache::new(&redis_url).await {
+                    Ok(cache) => {
+                        aggregator.attach_cache(cache);
+                        info!("✓ Redis price cache connected");
+                        break;
+                    }
+                    Err(e) => debug!("Redis still unavailable: {}", e),
+                }
+            }
+        });
+    }
+
+    // Serve stale cache hits immediately and refresh them in the background
+    data_retrieval::refresher::spawn_revalidator(Arc::clone(&aggregator));
+    info!(
+        "✓ Stale-while-revalidate enabled (max staleness {}s)",
+        max_staleness_secs
+    );
+    info!(
+        "✓ Prices flagged unreliable past a {}% source spread",
+        max_spread_percent
+    );
+
+    // Keep the most requested pairs warm in cache
+    let refresher_config = data_retrieval::refresher::RefresherConfig::from_env();
+    if refresher_config.top_n > 0 {
+        data_retrieval::refresher::spawn_hot_refresher(Arc::clone(&aggregator), refresher_config);
+        info!("✓ Hot symbol refresher started");
+    }
+
+    if let Some(store) = &history {
+        let config = data_retrieval::history::HistoryConfig::from_env();
+        if !config.symbols.is_empty() && !config.timeframes.is_empty() {
+            data_retrieval::history::spawn_recorder(Arc::clone(&aggregator), store.clone(), config);
+        }
+    }
+
+    // Order books for liquidity estimates from DEPTH_EXCHANGE (default
+    // Binance, or Coinbase where geo-blocked sources are disabled; "none" for
+    // Jupiter routes only)
+    let depth = depth_client(geo_blocked_disabled);
+
+    // Funding rates and open interest from Binance futures
+    let derivatives = if geo_blocked_disabled {
+        info!("Geo-blocked sources disabled, skipping funding rates");
+        None
+    } else {
+        info!("✓ Binance futures funding rates enabled");
+        Some(data_retrieval::sources::derivatives::DerivativesClient::new())
+    };
+
+    // Fear & Greed needs no key; CryptoPanic news does
+    let sentiment = data_retrieval::sources::sentiment::SentimentClient::new(
+        std::env::var("CRYPTOPANIC_API_KEY")
+            .ok()
+            .filter(|k| !k.is_empty()),
+    );
+    if sentiment.has_news() {
+        info!("✓ CryptoPanic news sentiment enabled");
+    }
+
+    // Token analytics read mint accounts from SOLANA_RPC_URL
+    let onchain = data_retrieval::sources::onchain::OnChainClient::new(
+        &std::env::var("SOLANA_RPC_URL")
+            .ok()
+            .filter(|u| !u.is_empty())
+            .unwrap_or_else(|| {
+                data_retrieval::sources::onchain::DEFAULT_SOLANA_RPC_URL.to_string()
+            }),
+    );
+
     // Create app state
     let state = Arc::new(AppState {
         price_aggregator: aggregator,
         pyth_client,
+        jupiter,
+        depth,
+        derivatives,
+        sentiment,
+        onchain,
+        history,
+        admin_token: std::env::var("ADMIN_TOKEN").ok().filter(|t| !t.is_empty()),
     });
 
     // Build router
@@ -77,23 +244,348 @@ async fn main() -> anyhow::Result<()> {
             axum::routing::post(handlers::get_prices_batch),
         )
         .route("/prices/supported", get(handlers::get_supported_symbols))
+        .route("/prices/stream", get(handlers::stream_prices))
+        .route("/prices/history", get(handlers::get_price_history))
+        .route("/prices/regime", get(handlers::get_market_regime))
+        .route("/simulate-swap", get(handlers::simulate_swap))
+        .route("/liquidity", get(handlers::get_liquidity))
+        .route("/derivatives/{symbol}", get(handlers::get_derivatives))
+        .route("/sentiment/{symbol}", get(handlers::get_sentiment))
+        .route(
+            "/tokens/{mint}/analytics",
+            get(handlers::get_token_analytics),
+        )
         .route("/health", get(handlers::health_check))
-        .layer(CorsLayer::new().allow_origin(Any))
+        .route("/cache/stats", get(handlers::get_cache_stats))
+        .route("/admin/symbols", get(handlers::list_registry))
+        .route(
+            "/admin/symbols/reload",
+            axum::routing::post(handlers::reload_registry),
+        )
+        .route(
+            "/admin/symbols/:symbol",
+            axum::routing::put(handlers::upsert_registry_entry)
+                .delete(handlers::delete_registry_entry),
+        )
+        .layer(
+            CorsLayer::new()
+                .allow_origin(Any)
+                .expose_headers([data_retrieval::request_id::X_REQUEST_ID.clone()]),
+        )
         .layer(TraceLayer::new_for_http())
-        .with_state(state);
+        .layer(axum::middleware::from_fn(
+            data_retrieval::request_id::request_id_middleware,
+        ))
+        .with_state(Arc::clone(&state));
 
     // Start server
-    let port = std::env::var("PORT")
+    let bind_addr = std::env::var("BIND_ADDR").unwrap_or_else(|_| "0.0.0.0".to_string());
+    let port: u16 = env_parse("PORT").unwrap_or(8080);
+
+    let listener = tokio::net::TcpListener::bind((bind_addr.as_str(), port)).await?;
+    info!(
+        "🚀 Data Retrieval Service listening on {}:{}",
+        bind_addr, port
+    );
+
+    // gRPC for internal price lookups; GRPC_PORT=0 turns it off
+    let grpc_port: u16 = env_parse("GRPC_PORT").unwrap_or(50051);
+    let grpc = async {
+        if grpc_port == 0 {
+            return std::future::pending().await;
+        }
+        let addr = tokio::net::lookup_host((bind_addr.as_str(), grpc_port))
+            .await?
+            .next()
+            .ok_or_else(|| anyhow::anyhow!("BIND_ADDR {} did not resolve", bind_addr))?;
+        info!("🚀 gRPC price service listening on {}", addr);
+        tonic::transport::Server::builder()
+            .add_service(grpc::PriceGrpc::server(state))
+            .serve(addr)
+            .await?;
+        anyhow::Ok(())
+    };
+
+    tokio::try_join!(
+        async { anyhow::Ok(axum::serve(listener, app).await?) },
+        grpc
+    )?;
+
+    Ok(())
+}
+
+/// Registry layered from SYMBOL_REGISTRY_PATH and the history database
+///
+/// A file or table that fails to load is logged and the built-ins are used
+/// until a reload succeeds.
+async fn symbol_registry(
+    history: Option<&data_retrieval::history::HistoryStore>,
+) -> Arc<data_retrieval::SymbolRegistry> {
+    let mut registry = data_retrieval::SymbolRegistry::builtin();
+    if let Some(path) = std::env::var("SYMBOL_REGISTRY_PATH")
         .ok()
-        .and_then(|p| p.parse().ok())
-        .unwrap_or(8080);
+        .filter(|p| !p.is_empty())
+    {
+        registry = registry.with_file(path);
+    }
+    if let Some(store) = history {
+        registry = registry.with_database(store.pool().clone());
+    }
+    match registry.reload().await {
+        Ok(count) => info!("✓ Symbol registry loaded ({} symbols)", count),
+        Err(e) => warn!("⚠ Symbol registry failed to load ({}), using built-ins", e),
+    }
+    Arc::new(registry)
+}
 
-    let listener = tokio::net::TcpListener::bind(format!("0.0.0.0:{}", port)).await?;
-    info!("🚀 Data Retrieval Service listening on port {}", port);
+/// Price history store from DATABASE_URL, if set and valid
+fn connect_history() -> Option<data_retrieval::history::HistoryStore> {
+    let url = std::env::var("DATABASE_URL")
+        .ok()
+        .filter(|u| !u.is_empty())?;
+    match data_retrieval::history::HistoryStore::connect_lazy(&url) {
+        Ok(store) => {
+            info!("✓ Price history store configured");
+            Some(store)
+        }
+        Err(e) => {
+            warn!(
+                "⚠ Invalid DATABASE_URL ({}), not recording price history",
+                e
+            );
+            None
+        }
+    }
+}
 
-    axum::serve(listener, app).await?;
+/// Fetch as much history as the sources return for each symbol (default
+/// HISTORY_SYMBOLS) and timeframe (HISTORY_TIMEFRAMES), and store it
+async fn backfill(symbols: &[String]) -> anyhow::Result<()> {
+    use data_retrieval::history::{self, HistoryConfig, HistoryStore, BACKFILL_CANDLES};
 
+    tracing_subscriber::fmt().with_max_level(Level::INFO).init();
+    let url = std::env::var("DATABASE_URL")
+        .map_err(|_| anyhow::anyhow!("DATABASE_URL must be set to backfill price history"))?;
+    let store = HistoryStore::connect_lazy(&url)?;
+
+    let mut config = HistoryConfig::from_env();
+    if !symbols.is_empty() {
+        config.symbols = history::parse_symbols(&symbols.join(","));
+    }
+
+    let mut aggregator = data_retrieval::PriceAggregator::new();
+    aggregator.add_crypto_source(Arc::new(data_retrieval::CoinGeckoClient::new(
+        std::env::var("COINGECKO_API_KEY").ok(),
+    )));
+    let pyth = Arc::new(data_retrieval::PythClient::new());
+    aggregator.add_stock_source(pyth.clone());
+    aggregator.add_metal_source(pyth.clone());
+    aggregator.add_fx_source(pyth);
+
+    let mut failed = 0;
+    for symbol in &config.symbols {
+        for &timeframe in &config.timeframes {
+            match history::record(
+                &aggregator,
+                &store,
+                symbol,
+                &config.quote,
+                timeframe,
+                BACKFILL_CANDLES,
+            )
+            .await
+            {
+                Ok(written) => info!(
+                    "Backfilled {} {} candles for {}/{}",
+                    written,
+                    timeframe.as_str(),
+                    symbol,
+                    config.quote
+                ),
+                Err(e) => {
+                    warn!(
+                        "Backfill of {} {} failed: {}",
+                        symbol,
+                        timeframe.as_str(),
+                        e
+                    );
+                    failed += 1;
+                }
+            }
+        }
+    }
+    anyhow::ensure!(failed == 0, "{} backfills failed", failed);
     Ok(())
 }
 
+/// Connect to Binance and subscribe to the majors' trade streams
+async fn connect_binance() -> Option<Arc<data_retrieval::BinanceWebSocketClient>> {
+    match data_retrieval::BinanceWebSocketClient::new().await {
+        Ok(client) => {
+            let ws = Arc::new(client.with_stale_after(realtime_stale_after()));
+            // Subscribe to BTC, ETH, SOL real-time trades
+            for symbol in ["BTCUSDT", "ETHUSDT", "SOLUSDT"] {
+                if let Err(e) = ws.subscribe_trades(symbol).await {
+                    warn!("Failed to subscribe to {}: {}", symbol, e);
+                }
+            }
+            info!("✓ Binance WebSocket connected");
+            Some(ws)
+        }
+        Err(e) => {
+            warn!(
+                "⚠ Binance WebSocket unavailable ({}), continuing without real-time data",
+                e
+            );
+            None
+        }
+    }
+}
+
+/// How long a trade stream may go quiet before it is reconnected
+fn realtime_stale_after() -> std::time::Duration {
+    std::time::Duration::from_secs(env_parse("REALTIME_STALE_SECS").unwrap_or(60))
+}
+
+/// Exchanges named in REALTIME_EXCHANGES, lowercase
+fn realtime_exchanges(geo_blocked_disabled: bool) -> Vec<String> {
+    let default = if geo_blocked_disabled {
+        "coinbase"
+    } else {
+        "binance"
+    };
+    std::env::var("REALTIME_EXCHANGES")
+        .unwrap_or_else(|_| default.to_string())
+        .split(',')
+        .map(|e| e.trim().to_ascii_lowercase())
+        .filter(|e| !e.is_empty())
+        .collect()
+}
+
+/// Order book client for DEPTH_EXCHANGE, if it names a usable venue
+fn depth_client(geo_blocked_disabled: bool) -> Option<data_retrieval::sources::depth::DepthClient> {
+    use data_retrieval::sources::depth::{BookVenue, DepthClient};
+
+    let default = if geo_blocked_disabled {
+        "coinbase"
+    } else {
+        "binance"
+    };
+    let name = std::env::var("DEPTH_EXCHANGE").unwrap_or_else(|_| default.to_string());
+    if name.trim().eq_ignore_ascii_case("none") {
+        return None;
+    }
+    let Some(venue) = BookVenue::parse(&name) else {
+        warn!(
+            "Unknown DEPTH_EXCHANGE {:?}, using Jupiter routes only",
+            name
+        );
+        return None;
+    };
+    if geo_blocked_disabled && venue.is_geo_blocked() {
+        info!(
+            "Geo-blocked sources disabled, skipping {} order books",
+            venue.as_str()
+        );
+        return None;
+    }
+    info!(
+        "✓ {} order books enabled for liquidity estimates",
+        venue.as_str()
+    );
+    Some(DepthClient::new(venue))
+}
+
+/// Connect the non-Binance exchanges in `exchanges`, streaming
+/// REALTIME_SYMBOLS (default BTC,ETH,SOL)
+async fn connect_exchange_streams(
+    exchanges: &[String],
+    geo_blocked_disabled: bool,
+) -> Vec<Arc<data_retrieval::ExchangeStreamClient>> {
+    let symbols = std::env::var("REALTIME_SYMBOLS").unwrap_or_else(|_| "BTC,ETH,SOL".to_string());
+    let symbols: Vec<&str> = symbols
+        .split(',')
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .collect();
+    let stale_after = realtime_stale_after();
+
+    let mut streams = Vec::new();
+    for name in exchanges.iter().filter(|e| *e != "binance") {
+        let Some(exchange) = data_retrieval::Exchange::parse(name) else {
+            warn!("⚠ Unknown exchange '{}' in REALTIME_EXCHANGES", name);
+            continue;
+        };
+        if geo_blocked_disabled && exchange.is_geo_blocked() {
+            info!("Geo-blocked sources disabled, skipping {}", name);
+            continue;
+        }
+        match data_retrieval::ExchangeStreamClient::connect(exchange, &symbols).await {
+            Ok(client) => {
+                info!("✓ {} trade stream connected", name);
+                streams.push(Arc::new(client.with_stale_after(stale_after)));
+            }
+            Err(e) => warn!("⚠ {} trade stream unavailable ({})", name, e),
+        }
+    }
+    streams
+}
+
+/// Stream PYTH_STREAM_SYMBOLS (default: every supported stock, ETF and metal)
+async fn connect_pyth_stream() -> Option<Arc<data_retrieval::PythStreamClient>> {
+    let configured = std::env::var("PYTH_STREAM_SYMBOLS").ok();
+    let symbols: Vec<String> = match configured {
+        Some(list) => list
+            .split(',')
+            .map(|s| s.trim().to_uppercase())
+            .filter(|s| !s.is_empty())
+            .collect(),
+        None => data_retrieval::PythClient::supported_stocks()
+            .into_iter()
+            .chain(data_retrieval::PythClient::supported_etfs())
+            .chain(data_retrieval::PythClient::supported_metals())
+            .map(str::to_string)
+            .collect(),
+    };
+    let symbols: Vec<&str> = symbols.iter().map(String::as_str).collect();
+
+    match data_retrieval::PythStreamClient::connect(&symbols).await {
+        Ok(client) => {
+            let stale_after: u64 = env_parse("PYTH_STREAM_STALE_SECS").unwrap_or(30);
+            let client = client.with_stale_after(std::time::Duration::from_secs(stale_after));
+            info!(
+                "✓ Pyth price stream connected ({} feeds)",
+                client.symbols().len()
+            );
+            Some(Arc::new(client))
+        }
+        Err(e) => {
+            warn!(
+                "⚠ Pyth price stream unavailable ({}), equities use REST polling",
+                e
+            );
+            None
+        }
+    }
+}
+
+fn env_parse<T: std::str::FromStr>(name: &str) -> Option<T> {
+    std::env::var(name).ok().and_then(|v| v.parse().ok())
+}
+
+/// Parse a boolean env var (1/true/yes/on, 0/false/no/off)
+fn env_flag(name: &str) -> Option<bool> {
+    match std::env::var(name)
+        .ok()?
+        .trim()
+        .to_ascii_lowercase()
+        .as_str()
+    {
+        "1" | "true" | "yes" | "on" => Some(true),
+        "0" | "false" | "no" | "off" => Some(false),
+        _ => None,
+    }
+}
+
+mod grpc;
 mod handlers;
@@ -17,6 +17,9 @@ pub fn normalize_price(source: &str, raw_price: f64, symbol: &str) -> PricePoint
         source: source.to_string(),
         timestamp: chrono::Utc::now(),
         confidence,
+        stale: false,
+        unreliable: false,
+        unit: None,
     }
 }
 
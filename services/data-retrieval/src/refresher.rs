@@ -0,0 +1,261 @@
+//! Background refresh for hot symbols
+//!
+//! Tracks how often each pair is requested and keeps the most requested ones
+//! warm in cache by re-fetching them shortly before their cached price goes
+//! stale. Request counts decay over time so the hot set follows current demand.
+//!
+//! Also hosts the revalidator that refreshes pairs served stale from cache.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use tracing::{debug, info, warn};
+
+use crate::{PriceAggregator, PriceKey, CACHE_FRESH_SECS};
+
+/// Upper bound on tracked pairs (new pairs are ignored until decay frees room)
+const MAX_TRACKED_SYMBOLS: usize = 10_000;
+/// Entries whose decayed hit count falls below this are forgotten
+const MIN_TRACKED_HITS: f64 = 0.5;
+
+/// Settings for the hot symbol refresher
+#[derive(Debug, Clone)]
+pub struct RefresherConfig {
+    /// How many of the most requested pairs to keep warm (0 disables)
+    pub top_n: usize,
+    /// Refresh this long before a cached price stops being served
+    pub refresh_lead: Duration,
+    /// Pairs need at least this many (decayed) requests to count as hot
+    pub min_hits: f64,
+    /// How often the loop checks for pairs that are due
+    pub check_interval: Duration,
+    /// How often request counts are halved
+    pub decay_interval: Duration,
+}
+
+impl Default for RefresherConfig {
+    fn default() -> Self {
+        Self {
+            top_n: 20,
+            refresh_lead: Duration::from_secs(5),
+            min_hits: 2.0,
+            check_interval: Duration::from_secs(1),
+            decay_interval: Duration::from_secs(60),
+        }
+    }
+}
+
+impl RefresherConfig {
+    /// Read overrides from HOT_SYMBOLS_TOP_N and HOT_SYMBOLS_REFRESH_LEAD_SECS
+    pub fn from_env() -> Self {
+        let mut config = Self::default();
+        if let Some(top_n) = env_parse::<usize>("HOT_SYMBOLS_TOP_N") {
+            config.top_n = top_n;
+        }
+        if let Some(lead) = env_parse::<u64>("HOT_SYMBOLS_REFRESH_LEAD_SECS") {
+            config.refresh_lead = Duration::from_secs(lead);
+        }
+        config
+    }
+
+    /// Age at which a hot pair gets re-fetched
+    fn refresh_after(&self) -> Duration {
+        Duration::from_secs(CACHE_FRESH_SECS as u64).saturating_sub(self.refresh_lead)
+    }
+}
+
+fn env_parse<T: std::str::FromStr>(name: &str) -> Option<T> {
+    std::env::var(name).ok().and_then(|v| v.parse().ok())
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+struct HotEntry {
+    hits: f64,
+    last_fetched: Option<Instant>,
+}
+
+/// Per-pair request frequency and last upstream fetch time
+#[derive(Default)]
+pub(crate) struct RequestStats {
+    entries: Mutex<HashMap<PriceKey, HotEntry>>,
+}
+
+impl RequestStats {
+    /// Count a client request for a pair
+    pub(crate) fn record_request(&self, key: &PriceKey) {
+        let mut entries = self.entries.lock().unwrap();
+        if let Some(entry) = entries.get_mut(key) {
+            entry.hits += 1.0;
+        } else if entries.len() < MAX_TRACKED_SYMBOLS {
+            entries.insert(
+                key.clone(),
+                HotEntry {
+                    hits: 1.0,
+                    last_fetched: None,
+                },
+            );
+        }
+    }
+
+    /// Note that a pair was just fetched from upstream (and cached)
+    pub(crate) fn record_fetch(&self, key: &PriceKey) {
+        if let Some(entry) = self.entries.lock().unwrap().get_mut(key) {
+            entry.last_fetched = Some(Instant::now());
+        }
+    }
+
+    /// Scale every hit count by `factor`, forgetting pairs that have gone cold
+    pub(crate) fn decay(&self, factor: f64) {
+        let mut entries = self.entries.lock().unwrap();
+        entries.retain(|_, entry| {
+            entry.hits *= factor;
+            entry.hits >= MIN_TRACKED_HITS
+        });
+    }
+
+    /// The `top_n` hottest pairs whose last fetch is older than `max_age`
+    pub(crate) fn due_for_refresh(
+        &self,
+        top_n: usize,
+        min_hits: f64,
+        max_age: Duration,
+    ) -> Vec<PriceKey> {
+        let entries = self.entries.lock().unwrap();
+        let mut hot: Vec<(&PriceKey, &HotEntry)> = entries
+            .iter()
+            .filter(|(_, entry)| entry.hits >= min_hits)
+            .collect();
+        hot.sort_by(|a, b| b.1.hits.total_cmp(&a.1.hits));
+        hot.truncate(top_n);
+
+        hot.into_iter()
+            .filter(|(_, entry)| entry.last_fetched.is_none_or(|at| at.elapsed() >= max_age))
+            .map(|(key, _)| key.clone())
+            .collect()
+    }
+}
+
+/// Spawn the refresh loop for `aggregator`
+///
+/// Only useful when the aggregator has a cache configured; without one there
+/// is nothing to keep warm.
+pub fn spawn_hot_refresher(
+    aggregator: Arc<PriceAggregator>,
+    config: RefresherConfig,
+) -> JoinHandle<()> {
+    info!(
+        "Hot symbol refresher: top {} pairs, refreshing {}s before expiry",
+        config.top_n,
+        config.refresh_lead.as_secs()
+    );
+
+    tokio::spawn(async move {
+        let mut check = tokio::time::interval(config.check_interval);
+        check.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+        let mut last_decay = Instant::now();
+        let refresh_after = config.refresh_after();
+
+        loop {
+            check.tick().await;
+
+            if last_decay.elapsed() >= config.decay_interval {
+                aggregator.request_stats.decay(0.5);
+                last_decay = Instant::now();
+            }
+
+            let due = aggregator.request_stats.due_for_refresh(
+                config.top_n,
+                config.min_hits,
+                refresh_after,
+            );
+            if due.is_empty() {
+                continue;
+            }
+
+            let refreshes = due.iter().map(|key| aggregator.refresh_price(key));
+            let results = futures::future::join_all(refreshes).await;
+            for (key, result) in due.iter().zip(results) {
+                if let Err(e) = result {
+                    debug!("Hot refresh failed for {}/{}: {}", key.0, key.1, e);
+                }
+            }
+        }
+    })
+}
+
+/// Spawn the background revalidator for stale cache hits
+///
+/// Once attached, `get_aggregated_price` answers stale hits immediately and
+/// queues the pair here instead of refreshing inline.
+pub fn spawn_revalidator(aggregator: Arc<PriceAggregator>) -> JoinHandle<()> {
+    let (tx, mut rx) = mpsc::unbounded_channel::<PriceKey>();
+    if !aggregator.attach_revalidator(tx) {
+        warn!("Price revalidator already running; not starting another");
+    }
+
+    tokio::spawn(async move {
+        while let Some(key) = rx.recv().await {
+            let aggregator = Arc::clone(&aggregator);
+            tokio::spawn(async move {
+                if let Err(e) = aggregator.refresh_price(&key).await {
+                    debug!("Revalidation failed for {}/{}: {}", key.0, key.1, e);
+                }
+                aggregator.finish_revalidation(&key);
+            });
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(asset: &str) -> PriceKey {
+        (asset.to_string(), "USD".to_string())
+    }
+
+    #[test]
+    fn test_due_for_refresh_picks_hottest_stale_pairs() {
+        let stats = RequestStats::default();
+        for _ in 0..5 {
+            stats.record_request(&key("BTC"));
+        }
+        for _ in 0..3 {
+            stats.record_request(&key("ETH"));
+        }
+        stats.record_request(&key("DOGE"));
+
+        // DOGE is below min_hits; only the top 1 is considered
+        let due = stats.due_for_refresh(1, 2.0, Duration::from_secs(25));
+        assert_eq!(due, vec![key("BTC")]);
+
+        // A fresh fetch takes BTC out of the due list
+        stats.record_fetch(&key("BTC"));
+        assert!(stats
+            .due_for_refresh(1, 2.0, Duration::from_secs(25))
+            .is_empty());
+        assert_eq!(
+            stats.due_for_refresh(2, 2.0, Duration::from_secs(25)),
+            vec![key("ETH")]
+        );
+    }
+
+    #[test]
+    fn test_decay_forgets_cold_pairs() {
+        let stats = RequestStats::default();
+        for _ in 0..4 {
+            stats.record_request(&key("BTC"));
+        }
+        stats.record_request(&key("ETH"));
+
+        stats.decay(0.5);
+        assert_eq!(stats.entries.lock().unwrap().len(), 2);
+        stats.decay(0.5);
+
+        let entries = stats.entries.lock().unwrap();
+        assert!(entries.contains_key(&key("BTC")));
+        assert!(!entries.contains_key(&key("ETH")));
+    }
+}
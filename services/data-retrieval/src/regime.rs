@@ -0,0 +1,188 @@
+//! Market regime: trending, ranging or unusually volatile
+//!
+//! Classified from a pair's recent candles so strategies (and the OpenClaw
+//! brain) can trade harder into a trend and back off when the market turns
+//! wild. Two rolling statistics drive it:
+//!
+//! - the efficiency ratio of the last [`RECENT_BARS`] closes: net move over
+//!   the path travelled, near 1 for a straight line and near 0 for chop
+//! - the volatility of those bars' returns against the whole series', so a
+//!   quiet asset waking up counts as much as an always-wild one
+//!
+//! Bars are counted, not hours, so the classifier works on whatever
+//! granularity the source hands back; volatility is scaled to a day from the
+//! bars' spacing.
+
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+use crate::types::Candle;
+
+/// Bars the trend and recent volatility are measured over
+pub const RECENT_BARS: usize = 24;
+/// Efficiency ratio from which the market counts as trending
+const TRENDING_EFFICIENCY: f64 = 0.3;
+/// Recent volatility this many times the series' is high volatility
+const HIGH_VOLATILITY_RATIO: f64 = 1.5;
+/// Daily volatility (percent) that is high whatever the asset's history
+const HIGH_VOLATILITY_DAILY_PCT: f64 = 8.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Regime {
+    /// Moving steadily one way (see `trend_pct` for which)
+    Trending,
+    /// Chopping sideways
+    Ranging,
+    /// Swinging far more than usual; takes precedence over the other two
+    HighVolatility,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MarketRegime {
+    pub regime: Regime,
+    /// Net move over the path travelled by the recent closes (0-1)
+    pub efficiency_ratio: f64,
+    /// Percent change over the recent bars
+    pub trend_pct: f64,
+    /// Standard deviation of the recent returns, scaled to a day, in percent
+    pub volatility_pct: f64,
+    /// Recent volatility over the whole series'
+    pub volatility_ratio: f64,
+    /// Candles the classification used
+    pub bars: usize,
+}
+
+/// Classify the regime from candles in any order
+///
+/// None with fewer than [`RECENT_BARS`] usable candles.
+pub fn classify(candles: &[Candle]) -> Option<MarketRegime> {
+    let mut bars: Vec<&Candle> = candles.iter().filter(|c| c.close > Decimal::ZERO).collect();
+    if bars.len() < RECENT_BARS {
+        return None;
+    }
+    bars.sort_by_key(|c| c.timestamp);
+    let closes: Vec<f64> = bars.iter().filter_map(|c| c.close.to_f64()).collect();
+    let returns: Vec<f64> = closes.windows(2).map(|w| (w[1] / w[0]).ln()).collect();
+
+    let recent_closes = &closes[closes.len() - RECENT_BARS..];
+    let recent_returns = &returns[returns.len() - (RECENT_BARS - 1)..];
+    let (first, last) = (recent_closes[0], recent_closes[RECENT_BARS - 1]);
+    let path: f64 = recent_closes.windows(2).map(|w| (w[1] - w[0]).abs()).sum();
+    let efficiency_ratio = if path > 0.0 {
+        (last - first).abs() / path
+    } else {
+        0.0
+    };
+
+    let recent_volatility = std_dev(recent_returns);
+    let series_volatility = std_dev(&returns);
+    let volatility_ratio = if series_volatility > 0.0 {
+        recent_volatility / series_volatility
+    } else {
+        1.0
+    };
+    let span_secs = (bars[bars.len() - 1].timestamp - bars[0].timestamp).num_seconds() as f64;
+    let bars_per_day = if span_secs > 0.0 {
+        86_400.0 * (bars.len() - 1) as f64 / span_secs
+    } else {
+        1.0
+    };
+    let volatility_pct = recent_volatility * bars_per_day.sqrt() * 100.0;
+
+    let regime = if volatility_ratio >= HIGH_VOLATILITY_RATIO
+        || volatility_pct >= HIGH_VOLATILITY_DAILY_PCT
+    {
+        Regime::HighVolatility
+    } else if efficiency_ratio >= TRENDING_EFFICIENCY {
+        Regime::Trending
+    } else {
+        Regime::Ranging
+    };
+
+    Some(MarketRegime {
+        regime,
+        efficiency_ratio,
+        trend_pct: (last - first) / first * 100.0,
+        volatility_pct,
+        volatility_ratio,
+        bars: bars.len(),
+    })
+}
+
+/// Sample standard deviation
+fn std_dev(values: &[f64]) -> f64 {
+    if values.len() < 2 {
+        return 0.0;
+    }
+    let mean = values.iter().sum::<f64>() / values.len() as f64;
+    let variance =
+        values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / (values.len() - 1) as f64;
+    variance.sqrt()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::TimeFrame;
+    use chrono::{Duration, TimeZone, Utc};
+
+    fn candles(closes: &[f64]) -> Vec<Candle> {
+        let start = Utc.with_ymd_and_hms(2026, 3, 1, 0, 0, 0).unwrap();
+        closes
+            .iter()
+            .enumerate()
+            .map(|(i, close)| {
+                let close = Decimal::try_from(*close).unwrap();
+                Candle {
+                    asset: "SOL".to_string(),
+                    quote: "USD".to_string(),
+                    timeframe: TimeFrame::Hour1,
+                    open: close,
+                    high: close,
+                    low: close,
+                    close,
+                    volume: Decimal::ZERO,
+                    timestamp: start + Duration::hours(i as i64),
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_classify_regimes() {
+        assert_eq!(classify(&candles(&[100.0; 10])), None);
+
+        // Steady climb with small wobbles
+        let trend: Vec<f64> = (0..48)
+            .map(|i| 100.0 + i as f64 * 0.2 + if i % 2 == 0 { 0.05 } else { 0.0 })
+            .collect();
+        let regime = classify(&candles(&trend)).unwrap();
+        assert_eq!(regime.regime, Regime::Trending);
+        assert!(regime.trend_pct > 0.0);
+        assert_eq!(regime.bars, 48);
+
+        // Back and forth around the same level
+        let range: Vec<f64> = (0..48)
+            .map(|i| if i % 2 == 0 { 100.0 } else { 100.3 })
+            .collect();
+        let regime = classify(&candles(&range)).unwrap();
+        assert_eq!(regime.regime, Regime::Ranging);
+        assert!(regime.efficiency_ratio < TRENDING_EFFICIENCY);
+
+        // Four days of that chop, then a day swinging five times as far
+        let mut wild: Vec<f64> = (0..96)
+            .map(|i| if i % 2 == 0 { 100.0 } else { 100.3 })
+            .collect();
+        wild.extend((0..24).map(|i| if i % 2 == 0 { 100.0 } else { 101.5 }));
+        let regime = classify(&candles(&wild)).unwrap();
+        assert_eq!(regime.regime, Regime::HighVolatility);
+        assert!(regime.volatility_ratio >= HIGH_VOLATILITY_RATIO);
+
+        // Order doesn't matter
+        let mut shuffled = candles(&trend);
+        shuffled.reverse();
+        assert_eq!(classify(&shuffled).unwrap().regime, Regime::Trending);
+    }
+}
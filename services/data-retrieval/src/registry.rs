@@ -0,0 +1,419 @@
+//! Symbol registry
+//!
+//! Maps each symbol the service prices to its asset class, the sources
+//! preferred for it, and (for Solana tokens) its mint and decimals. Requests
+//! are routed by what the registry says instead of hard-coded allowlists, so
+//! listing a new token is a config change.
+//!
+//! Entries are layered, later layers replacing earlier ones symbol by symbol:
+//!
+//! 1. the built-in list (the majors, known mints, Pyth's stocks, ETFs, metals
+//!    and FX pairs)
+//! 2. a TOML file (`SYMBOL_REGISTRY_PATH`), a list of `[[symbols]]` tables
+//! 3. the `symbol_registry` table, when a database is configured
+//!
+//! [`SymbolRegistry::reload`] rebuilds the layers without a restart. A symbol
+//! no layer lists falls back to [`AssetClass::from_symbol`].
+
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::{Arc, RwLock};
+use tracing::debug;
+
+use crate::symbol::{looks_like_mint, KNOWN_MINTS};
+use crate::{units, AssetClass, PythClient};
+
+/// Crypto assets listed without a mint
+const BUILTIN_CRYPTO: &[&str] = &["BTC", "ETH", "SOL", "BNB", "XRP", "ADA", "DOT", "AVAX"];
+/// Most decimals an entry may declare
+const MAX_DECIMALS: u8 = 18;
+
+/// One symbol and how to price it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SymbolEntry {
+    pub symbol: String,
+    pub asset_class: AssetClass,
+    /// Source names (`coingecko`, `pyth`) to ask, in no particular order;
+    /// empty asks every source for the asset class
+    #[serde(default)]
+    pub sources: Vec<String>,
+    #[serde(default)]
+    pub decimals: Option<u8>,
+    #[serde(default)]
+    pub mint: Option<String>,
+}
+
+impl SymbolEntry {
+    fn new(symbol: &str, asset_class: AssetClass) -> Self {
+        Self {
+            symbol: symbol.to_string(),
+            asset_class,
+            sources: Vec::new(),
+            decimals: None,
+            mint: None,
+        }
+    }
+
+    /// Uppercase the symbol and check the fields
+    pub fn normalized(mut self) -> anyhow::Result<Self> {
+        self.symbol = self.symbol.trim().to_uppercase();
+        anyhow::ensure!(
+            !self.symbol.is_empty()
+                && self.symbol.len() <= 20
+                && self
+                    .symbol
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '.'),
+            "invalid symbol '{}'",
+            self.symbol
+        );
+        if let Some(decimals) = self.decimals {
+            anyhow::ensure!(
+                decimals <= MAX_DECIMALS,
+                "{}: decimals must be at most {}",
+                self.symbol,
+                MAX_DECIMALS
+            );
+        }
+        if let Some(mint) = &self.mint {
+            anyhow::ensure!(
+                looks_like_mint(mint),
+                "{}: '{}' is not a mint address",
+                self.symbol,
+                mint
+            );
+        }
+        for source in &mut self.sources {
+            *source = source.trim().to_lowercase();
+        }
+        self.sources.retain(|s| !s.is_empty());
+        Ok(self)
+    }
+
+    /// Whether the entry lets `source` price it
+    pub fn prefers(&self, source: &str) -> bool {
+        self.sources.is_empty() || self.sources.iter().any(|s| s == source)
+    }
+}
+
+/// Layout of the TOML file
+#[derive(Debug, Deserialize)]
+struct RegistryFile {
+    #[serde(default)]
+    symbols: Vec<SymbolEntry>,
+}
+
+/// Parse a registry TOML document
+pub fn parse_toml(document: &str) -> anyhow::Result<Vec<SymbolEntry>> {
+    let file: RegistryFile = toml::from_str(document)?;
+    file.symbols
+        .into_iter()
+        .map(SymbolEntry::normalized)
+        .collect()
+}
+
+/// The built-in entries
+pub fn builtin_entries() -> Vec<SymbolEntry> {
+    let mut entries: Vec<SymbolEntry> = BUILTIN_CRYPTO
+        .iter()
+        .map(|symbol| SymbolEntry::new(symbol, AssetClass::Crypto))
+        .collect();
+    for (symbol, mint, decimals) in KNOWN_MINTS {
+        let entry = match entries.iter_mut().find(|e| e.symbol == *symbol) {
+            Some(entry) => entry,
+            None => {
+                entries.push(SymbolEntry::new(symbol, AssetClass::Crypto));
+                entries.last_mut().unwrap()
+            }
+        };
+        entry.mint = Some(mint.to_string());
+        entry.decimals = Some(*decimals);
+    }
+    let equities = [
+        (PythClient::supported_stocks(), AssetClass::Stock),
+        (PythClient::supported_etfs(), AssetClass::Etf),
+        (
+            units::METALS.iter().map(|m| m.symbol).collect(),
+            AssetClass::Metal,
+        ),
+        (units::FX_PAIRS.to_vec(), AssetClass::Fx),
+    ];
+    for (symbols, asset_class) in equities {
+        entries.extend(
+            symbols
+                .into_iter()
+                .map(|s| SymbolEntry::new(s, asset_class)),
+        );
+    }
+    entries
+}
+
+/// Symbol entries, swapped as a whole on reload
+#[derive(Default)]
+struct Entries {
+    by_symbol: HashMap<String, Arc<SymbolEntry>>,
+    by_mint: HashMap<String, Arc<SymbolEntry>>,
+}
+
+impl Entries {
+    fn build(layers: impl IntoIterator<Item = SymbolEntry>) -> Self {
+        let mut by_symbol = HashMap::new();
+        for entry in layers {
+            by_symbol.insert(entry.symbol.clone(), Arc::new(entry));
+        }
+        let by_mint = by_symbol
+            .values()
+            .filter_map(|e| Some((e.mint.clone()?, Arc::clone(e))))
+            .collect();
+        Self { by_symbol, by_mint }
+    }
+}
+
+/// Registry lookups, shared by the aggregator and the handlers
+pub struct SymbolRegistry {
+    path: Option<PathBuf>,
+    pool: Option<PgPool>,
+    entries: RwLock<Entries>,
+}
+
+impl Default for SymbolRegistry {
+    fn default() -> Self {
+        Self::builtin()
+    }
+}
+
+impl SymbolRegistry {
+    /// Registry with only the built-in entries
+    pub fn builtin() -> Self {
+        Self {
+            path: None,
+            pool: None,
+            entries: RwLock::new(Entries::build(builtin_entries())),
+        }
+    }
+
+    /// Layer a TOML file over the built-ins (read on [`reload`](Self::reload))
+    pub fn with_file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.path = Some(path.into());
+        self
+    }
+
+    /// Layer the `symbol_registry` table on top (read on [`reload`](Self::reload))
+    pub fn with_database(mut self, pool: PgPool) -> Self {
+        self.pool = Some(pool);
+        self
+    }
+
+    pub fn has_database(&self) -> bool {
+        self.pool.is_some()
+    }
+
+    /// Re-read the file and table; returns how many symbols are listed
+    ///
+    /// On any error the entries already loaded stay in place.
+    pub async fn reload(&self) -> anyhow::Result<usize> {
+        let mut layers = builtin_entries();
+        if let Some(path) = &self.path {
+            let document = tokio::fs::read_to_string(path)
+                .await
+                .map_err(|e| anyhow::anyhow!("reading {}: {}", path.display(), e))?;
+            layers.extend(parse_toml(&document)?);
+        }
+        if let Some(pool) = &self.pool {
+            layers.extend(load_rows(pool).await?);
+        }
+        let entries = Entries::build(layers);
+        let count = entries.by_symbol.len();
+        *self.entries.write().unwrap() = entries;
+        debug!("Symbol registry loaded ({} symbols)", count);
+        Ok(count)
+    }
+
+    /// Entry for a symbol or a mint
+    pub fn lookup(&self, symbol_or_mint: &str) -> Option<Arc<SymbolEntry>> {
+        let symbol_or_mint = symbol_or_mint.trim();
+        let entries = self.entries.read().unwrap();
+        if looks_like_mint(symbol_or_mint) {
+            if let Some(entry) = entries.by_mint.get(symbol_or_mint) {
+                return Some(Arc::clone(entry));
+            }
+        }
+        entries
+            .by_symbol
+            .get(&symbol_or_mint.to_uppercase())
+            .cloned()
+    }
+
+    /// Asset class to route a symbol by
+    pub fn asset_class(&self, symbol: &str) -> AssetClass {
+        self.lookup(symbol)
+            .map(|e| e.asset_class)
+            .unwrap_or_else(|| AssetClass::from_symbol(symbol))
+    }
+
+    /// Registry symbol for a symbol or mint; otherwise the symbol uppercased
+    /// (an unlisted mint as written)
+    pub fn canonical_symbol(&self, symbol_or_mint: &str) -> String {
+        let symbol_or_mint = symbol_or_mint.trim();
+        match self.lookup(symbol_or_mint) {
+            Some(entry) => entry.symbol.clone(),
+            None if looks_like_mint(symbol_or_mint) => symbol_or_mint.to_string(),
+            None => symbol_or_mint.to_uppercase(),
+        }
+    }
+
+    /// Every entry, sorted by symbol
+    pub fn entries(&self) -> Vec<SymbolEntry> {
+        let mut entries: Vec<SymbolEntry> = self
+            .entries
+            .read()
+            .unwrap()
+            .by_symbol
+            .values()
+            .map(|e| (**e).clone())
+            .collect();
+        entries.sort_by(|a, b| a.symbol.cmp(&b.symbol));
+        entries
+    }
+
+    /// Symbols of one asset class, sorted
+    pub fn symbols(&self, asset_class: AssetClass) -> Vec<String> {
+        self.entries()
+            .into_iter()
+            .filter(|e| e.asset_class == asset_class)
+            .map(|e| e.symbol)
+            .collect()
+    }
+
+    /// Write an entry to the table, then reload
+    pub async fn upsert(&self, entry: SymbolEntry) -> anyhow::Result<SymbolEntry> {
+        let entry = entry.normalized()?;
+        let pool = self.require_database()?;
+        sqlx::query(
+            r#"
+            INSERT INTO symbol_registry (symbol, asset_class, sources, decimals, mint)
+            VALUES ($1, $2, $3, $4, $5)
+            ON CONFLICT (symbol) DO UPDATE SET
+                asset_class = EXCLUDED.asset_class,
+                sources = EXCLUDED.sources,
+                decimals = EXCLUDED.decimals,
+                mint = EXCLUDED.mint,
+                updated_at = NOW()
+            "#,
+        )
+        .bind(&entry.symbol)
+        .bind(entry.asset_class.as_str())
+        .bind(&entry.sources)
+        .bind(entry.decimals.map(i16::from))
+        .bind(&entry.mint)
+        .execute(pool)
+        .await?;
+        self.reload().await?;
+        Ok(entry)
+    }
+
+    /// Delete a symbol's row, then reload; false if it had none
+    ///
+    /// A built-in or file entry for the symbol applies again afterwards.
+    pub async fn remove(&self, symbol: &str) -> anyhow::Result<bool> {
+        let pool = self.require_database()?;
+        let removed = sqlx::query("DELETE FROM symbol_registry WHERE symbol = $1")
+            .bind(symbol.trim().to_uppercase())
+            .execute(pool)
+            .await?
+            .rows_affected();
+        self.reload().await?;
+        Ok(removed > 0)
+    }
+
+    fn require_database(&self) -> anyhow::Result<&PgPool> {
+        self.pool
+            .as_ref()
+            .ok_or_else(|| anyhow::anyhow!("symbol registry has no database (set DATABASE_URL)"))
+    }
+}
+
+/// symbol, asset_class, sources, decimals, mint
+type RegistryRow = (String, String, Vec<String>, Option<i16>, Option<String>);
+
+/// Rows of `symbol_registry`
+async fn load_rows(pool: &PgPool) -> anyhow::Result<Vec<SymbolEntry>> {
+    let rows: Vec<RegistryRow> =
+        sqlx::query_as("SELECT symbol, asset_class, sources, decimals, mint FROM symbol_registry")
+            .fetch_all(pool)
+            .await?;
+    rows.into_iter()
+        .map(|(symbol, asset_class, sources, decimals, mint)| {
+            let asset_class = AssetClass::parse(&asset_class).ok_or_else(|| {
+                anyhow::anyhow!("{}: unknown asset class '{}'", symbol, asset_class)
+            })?;
+            SymbolEntry {
+                symbol,
+                asset_class,
+                sources,
+                decimals: decimals.and_then(|d| u8::try_from(d).ok()),
+                mint,
+            }
+            .normalized()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const JTO: &str = "jtojtomepa8beP8AuQc6eXt5FriJwfFMwQx2v2f9mCL";
+
+    #[tokio::test]
+    async fn test_file_layers_over_builtins() {
+        let path = std::env::temp_dir().join(format!("registry-{}.toml", uuid::Uuid::new_v4()));
+        let document = format!(
+            r#"
+            [[symbols]]
+            symbol = "jto"
+            asset_class = "crypto"
+            sources = ["CoinGecko"]
+            decimals = 9
+            mint = "{JTO}"
+
+            [[symbols]]
+            symbol = "COIN"
+            asset_class = "stock"
+            "#
+        );
+        std::fs::write(&path, document).unwrap();
+        let registry = SymbolRegistry::builtin().with_file(&path);
+
+        // Built-ins until the file is read
+        assert_eq!(registry.asset_class("SPY"), AssetClass::Etf);
+        assert_eq!(registry.asset_class("COIN"), AssetClass::Crypto);
+        assert!(registry.lookup(JTO).is_none());
+
+        registry.reload().await.unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(registry.asset_class("coin"), AssetClass::Stock);
+        let jto = registry.lookup(JTO).unwrap();
+        assert_eq!((jto.symbol.as_str(), jto.decimals), ("JTO", Some(9)));
+        assert!(jto.prefers("coingecko") && !jto.prefers("pyth"));
+        assert_eq!(registry.canonical_symbol(JTO), "JTO");
+        assert!(registry
+            .symbols(AssetClass::Stock)
+            .contains(&"COIN".to_string()));
+
+        // Unlisted symbols use the built-in detection
+        assert_eq!(registry.asset_class("EURUSD"), AssetClass::Fx);
+
+        // A bad file leaves the loaded entries alone
+        assert!(registry.reload().await.is_err());
+        assert_eq!(registry.asset_class("COIN"), AssetClass::Stock);
+
+        assert!(
+            parse_toml("[[symbols]]\nsymbol = \"X\"\nasset_class = \"crypto\"\ndecimals = 30")
+                .is_err()
+        );
+        assert!(parse_toml("[[symbols]]\nsymbol = \"X\"\nasset_class = \"bond\"").is_err());
+    }
+}
@@ -0,0 +1,103 @@
+//! `X-Request-Id` on every response
+//!
+//! Reuses a well-formed ID from the caller or generates one, runs the handler
+//! inside a span tagged with it, and appends it to error bodies so a failed
+//! price lookup can be traced back to the log lines that explain it.
+
+use axum::{
+    body::Body,
+    extract::Request,
+    http::{header, HeaderName, HeaderValue},
+    middleware::Next,
+    response::Response,
+};
+use tracing::Instrument;
+
+pub static X_REQUEST_ID: HeaderName = HeaderName::from_static("x-request-id");
+
+/// Largest error body buffered to append the ID to
+const MAX_ERROR_BODY_BYTES: usize = 64 * 1024;
+
+fn valid_request_id(id: &str) -> bool {
+    !id.is_empty()
+        && id.len() <= 128
+        && id
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || b"-_.:".contains(&b))
+}
+
+pub async fn request_id_middleware(mut request: Request, next: Next) -> Response {
+    let id = request
+        .headers()
+        .get(&X_REQUEST_ID)
+        .and_then(|v| v.to_str().ok())
+        .filter(|id| valid_request_id(id))
+        .map(str::to_string)
+        .unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+    let header_value = HeaderValue::from_str(&id).expect("request id is header-safe");
+    request
+        .headers_mut()
+        .insert(X_REQUEST_ID.clone(), header_value.clone());
+
+    let method = request.method().clone();
+    let path = request.uri().path().to_string();
+    let span = tracing::info_span!("request", request_id = %id);
+    let start = std::time::Instant::now();
+
+    let response = next.run(request).instrument(span.clone()).await;
+
+    let status = response.status();
+    let latency_ms = start.elapsed().as_millis() as u64;
+    let failed = status.is_client_error() || status.is_server_error();
+    span.in_scope(|| {
+        if status.is_server_error() {
+            tracing::warn!(%method, %path, status = status.as_u16(), latency_ms, "request failed");
+        } else if failed {
+            tracing::info!(%method, %path, status = status.as_u16(), latency_ms, "request rejected");
+        } else {
+            tracing::debug!(%method, %path, status = status.as_u16(), latency_ms, "request completed");
+        }
+    });
+
+    let mut response = if failed {
+        append_request_id(response, &id).await
+    } else {
+        response
+    };
+    response
+        .headers_mut()
+        .insert(X_REQUEST_ID.clone(), header_value);
+    response
+}
+
+/// Error bodies here are plain text; append the ID to them
+async fn append_request_id(response: Response, id: &str) -> Response {
+    let (mut parts, body) = response.into_parts();
+    parts.headers.remove(header::CONTENT_LENGTH);
+    let body = match axum::body::to_bytes(body, MAX_ERROR_BODY_BYTES).await {
+        Ok(bytes) if bytes.is_empty() => format!("request_id: {}", id),
+        Ok(bytes) => format!("{} (request_id: {})", String::from_utf8_lossy(&bytes), id),
+        Err(_) => format!("request_id: {}", id),
+    };
+    Response::from_parts(parts, Body::from(body))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{http::StatusCode, response::IntoResponse};
+
+    #[tokio::test]
+    async fn test_appends_request_id_to_errors() {
+        assert!(valid_request_id("3f2a-77:b"));
+        assert!(!valid_request_id("has space"));
+        assert!(!valid_request_id(&"x".repeat(129)));
+
+        let response = (StatusCode::NOT_FOUND, "Unknown symbol: FOO").into_response();
+        let response = append_request_id(response, "req-9").await;
+        let body = axum::body::to_bytes(response.into_body(), 1024)
+            .await
+            .unwrap();
+        assert_eq!(&body[..], b"Unknown symbol: FOO (request_id: req-9)");
+    }
+}
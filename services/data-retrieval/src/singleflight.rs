@@ -0,0 +1,168 @@
+//! Single-flight request coalescing
+//!
+//! Concurrent callers asking for the same key share one in-flight future
+//! instead of each hitting upstream sources. The first caller (the leader)
+//! runs the fetch; everyone else waits for its result.
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::hash::Hash;
+use std::sync::Mutex;
+use tokio::sync::broadcast;
+
+/// Coalesces concurrent calls keyed by `K` into a single execution
+pub struct SingleFlight<K, V> {
+    inflight: Mutex<HashMap<K, broadcast::Sender<V>>>,
+}
+
+impl<K, V> Default for SingleFlight<K, V>
+where
+    K: Eq + Hash + Clone,
+    V: Clone,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K, V> SingleFlight<K, V>
+where
+    K: Eq + Hash + Clone,
+    V: Clone,
+{
+    pub fn new() -> Self {
+        Self {
+            inflight: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Run `fetch` for `key`, or wait for an identical call already in flight
+    ///
+    /// If the leader is cancelled before producing a value, waiting callers
+    /// fall back to running `fetch` themselves.
+    pub async fn run<F, Fut>(&self, key: K, fetch: F) -> V
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = V>,
+    {
+        let follower = {
+            let mut inflight = self.inflight.lock().unwrap();
+            match inflight.get(&key) {
+                Some(tx) => Some(tx.subscribe()),
+                None => {
+                    let (tx, _) = broadcast::channel(1);
+                    inflight.insert(key.clone(), tx);
+                    None
+                }
+            }
+        };
+
+        if let Some(mut rx) = follower {
+            return match rx.recv().await {
+                Ok(value) => value,
+                Err(_) => fetch().await,
+            };
+        }
+
+        let guard = LeaderGuard {
+            inflight: &self.inflight,
+            key: Some(key),
+        };
+        let value = fetch().await;
+        if let Some(tx) = guard.finish() {
+            let _ = tx.send(value.clone());
+        }
+        value
+    }
+
+    /// Number of keys currently being fetched
+    pub fn in_flight(&self) -> usize {
+        self.inflight.lock().unwrap().len()
+    }
+}
+
+/// Removes the in-flight entry even if the leader future is dropped mid-fetch
+struct LeaderGuard<'a, K: Eq + Hash, V> {
+    inflight: &'a Mutex<HashMap<K, broadcast::Sender<V>>>,
+    key: Option<K>,
+}
+
+impl<K: Eq + Hash, V> LeaderGuard<'_, K, V> {
+    fn finish(mut self) -> Option<broadcast::Sender<V>> {
+        let key = self.key.take()?;
+        self.inflight.lock().unwrap().remove(&key)
+    }
+}
+
+impl<K: Eq + Hash, V> Drop for LeaderGuard<'_, K, V> {
+    fn drop(&mut self) {
+        if let Some(key) = self.key.take() {
+            if let Ok(mut inflight) = self.inflight.lock() {
+                inflight.remove(&key);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_concurrent_calls_share_one_fetch() {
+        let flight = Arc::new(SingleFlight::<String, u32>::new());
+        let calls = Arc::new(AtomicUsize::new(0));
+
+        let mut handles = Vec::new();
+        for _ in 0..10 {
+            let flight = Arc::clone(&flight);
+            let calls = Arc::clone(&calls);
+            handles.push(tokio::spawn(async move {
+                flight
+                    .run("BTC/USD".to_string(), || async {
+                        calls.fetch_add(1, Ordering::SeqCst);
+                        tokio::time::sleep(Duration::from_millis(50)).await;
+                        42
+                    })
+                    .await
+            }));
+        }
+
+        for handle in handles {
+            assert_eq!(handle.await.unwrap(), 42);
+        }
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+        assert_eq!(flight.in_flight(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_cancelled_leader_does_not_block_followers() {
+        let flight = Arc::new(SingleFlight::<&'static str, u32>::new());
+
+        let leader = {
+            let flight = Arc::clone(&flight);
+            tokio::spawn(async move {
+                flight
+                    .run("SOL/USD", || async {
+                        tokio::time::sleep(Duration::from_secs(60)).await;
+                        1
+                    })
+                    .await
+            })
+        };
+        tokio::time::sleep(Duration::from_millis(10)).await;
+
+        let follower = {
+            let flight = Arc::clone(&flight);
+            tokio::spawn(async move { flight.run("SOL/USD", || async { 2 }).await })
+        };
+        tokio::time::sleep(Duration::from_millis(10)).await;
+
+        leader.abort();
+        assert_eq!(follower.await.unwrap(), 2);
+        assert_eq!(flight.in_flight(), 0);
+    }
+}
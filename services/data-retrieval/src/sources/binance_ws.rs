@@ -1,3 +1,5 @@
+use crate::candle_builder::Trade;
+use crate::symbol::SymbolId;
 use crate::types::*;
 use futures::stream::{SplitSink, SplitStream};
 use futures::{SinkExt, StreamExt};
@@ -6,8 +8,10 @@ use serde_json::Value;
 use std::collections::HashMap;
 use std::str::FromStr;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 use tokio::net::TcpStream;
-use tokio::sync::{mpsc, Mutex, RwLock};
+use tokio::sync::{broadcast, mpsc, Mutex, RwLock};
+use tokio::task::JoinHandle;
 use tokio_tungstenite::{connect_async, tungstenite::Message, MaybeTlsStream, WebSocketStream};
 use tracing::{debug, error, info, warn};
 
@@ -15,10 +19,18 @@ type WsStream = WebSocketStream<MaybeTlsStream<TcpStream>>;
 type WsSink = SplitSink<WsStream, Message>;
 type WsReader = SplitStream<WsStream>;
 
+const BINANCE_WS_URL: &str = "wss://stream.binance.com:9443/ws";
+/// Default for how long a subscribed stream may go quiet before it counts as stalled
+const DEFAULT_STALE_AFTER: Duration = Duration::from_secs(60);
+
 /// Binance WebSocket client for real-time price feeds
 ///
 /// Uses split read/write channels to prevent deadlock between
 /// sending subscriptions and receiving messages.
+///
+/// A dropped socket, or one that has gone quiet for `stale_after` while
+/// streams are subscribed, reports itself disconnected; `reconnect` then
+/// opens a new socket and re-subscribes every stream subscribed so far.
 pub struct BinanceWebSocketClient {
     /// WebSocket write half (for sending subscriptions)
     ws_sink: Arc<Mutex<WsSink>>,
@@ -27,19 +39,23 @@ pub struct BinanceWebSocketClient {
     /// Channel for receiving price updates
     price_tx: mpsc::Sender<PricePoint>,
     price_rx: Arc<Mutex<mpsc::Receiver<PricePoint>>>,
+    /// Every trade with its quantity, for building bars
+    trade_tx: broadcast::Sender<Trade>,
     /// Subscribed streams
     subscriptions: Arc<RwLock<HashMap<String, String>>>, // symbol -> stream_name
     /// Connection status
     connected: Arc<RwLock<bool>>,
+    /// When the socket last delivered anything
+    last_event: Arc<std::sync::Mutex<Instant>>,
+    stale_after: Duration,
+    /// Task reading the current socket
+    handler: Arc<Mutex<Option<JoinHandle<()>>>>,
 }
 
 impl BinanceWebSocketClient {
     /// Connect to Binance combined stream WebSocket
     pub async fn new() -> Result<Self> {
-        // Binance WebSocket URL for combined streams
-        let url = "wss://stream.binance.com:9443/ws";
-
-        let (ws_stream, _) = connect_async(url).await.map_err(|e| {
+        let (ws_stream, _) = connect_async(BINANCE_WS_URL).await.map_err(|e| {
             DataRetrievalError::ApiError(format!("WebSocket connection failed: {}", e))
         })?;
 
@@ -51,25 +67,44 @@ impl BinanceWebSocketClient {
         // Larger buffer to prevent data loss during price spikes
         // 10k entries = ~10 seconds of high-volume crypto trading
         let (price_tx, price_rx) = mpsc::channel(10000);
+        let (trade_tx, _) = broadcast::channel(10000);
 
         let client = Self {
             ws_sink: Arc::new(Mutex::new(ws_sink)),
             ws_reader: Arc::new(Mutex::new(ws_reader)),
             price_tx,
             price_rx: Arc::new(Mutex::new(price_rx)),
+            trade_tx,
             subscriptions: Arc::new(RwLock::new(HashMap::new())),
             connected: Arc::new(RwLock::new(true)),
+            last_event: Arc::new(std::sync::Mutex::new(Instant::now())),
+            stale_after: DEFAULT_STALE_AFTER,
+            handler: Arc::new(Mutex::new(None)),
         };
 
-        // Spawn message handler
-        let client_clone = client.clone();
-        tokio::spawn(async move {
-            client_clone.message_handler().await;
-        });
+        client.spawn_handler().await;
 
         Ok(client)
     }
 
+    /// Set how long subscribed streams may go without messages before the
+    /// socket is treated as down
+    pub fn with_stale_after(mut self, stale_after: Duration) -> Self {
+        self.stale_after = stale_after;
+        self
+    }
+
+    /// Start reading the current socket, replacing any previous reader
+    async fn spawn_handler(&self) {
+        let client_clone = self.clone();
+        let handle = tokio::spawn(async move {
+            client_clone.message_handler().await;
+        });
+        if let Some(old) = self.handler.lock().await.replace(handle) {
+            old.abort();
+        }
+    }
+
     /// Clone for spawning tasks
     fn clone(&self) -> Self {
         Self {
@@ -77,11 +112,22 @@ impl BinanceWebSocketClient {
             ws_reader: Arc::clone(&self.ws_reader),
             price_tx: self.price_tx.clone(),
             price_rx: Arc::clone(&self.price_rx),
+            trade_tx: self.trade_tx.clone(),
             subscriptions: Arc::clone(&self.subscriptions),
             connected: Arc::clone(&self.connected),
+            last_event: Arc::clone(&self.last_event),
+            stale_after: self.stale_after,
+            handler: Arc::clone(&self.handler),
         }
     }
 
+    /// Receive every trade (price and quantity) from now on
+    ///
+    /// Survives reconnects; a receiver that falls too far behind skips ahead.
+    pub fn trade_feed(&self) -> broadcast::Receiver<Trade> {
+        self.trade_tx.subscribe()
+    }
+
     /// Subscribe to real-time trades for a symbol
     pub async fn subscribe_trades(&self, symbol: &str) -> Result<()> {
         let stream_name = format!("{}@trade", symbol.to_lowercase());
@@ -169,6 +215,7 @@ impl BinanceWebSocketClient {
                 let mut reader = self.ws_reader.lock().await;
                 reader.next().await
             };
+            *self.last_event.lock().unwrap() = Instant::now();
 
             match msg {
                 Some(Ok(Message::Text(text))) => {
@@ -258,20 +305,38 @@ impl BinanceWebSocketClient {
             .map_err(|e| DataRetrievalError::InvalidResponse(format!("Invalid price: {}", e)))?;
 
         // Format symbol as BTC/USDT from BTCUSDT
-        let formatted_symbol = if let Some(base) = symbol.strip_suffix("USDT") {
-            format!("{}/USDT", base)
-        } else if let Some(base) = symbol.strip_suffix("USD") {
-            format!("{}/USD", base)
-        } else {
-            symbol.to_string()
+        let pair = SymbolId::from_binance(symbol);
+        let formatted_symbol = match &pair {
+            Some(pair) => pair.to_string(),
+            None => symbol.to_string(),
         };
 
+        // No receivers (no candle builder running) is fine
+        if let (Some(pair), Some(quantity)) = (
+            pair,
+            value
+                .get("q")
+                .and_then(|v| v.as_str())
+                .and_then(|q| Decimal::from_str(q).ok()),
+        ) {
+            let _ = self.trade_tx.send(Trade {
+                asset: pair.base().to_string(),
+                quote: pair.quote().to_string(),
+                price,
+                quantity,
+                timestamp,
+            });
+        }
+
         let price_point = PricePoint {
             symbol: formatted_symbol,
             price,
             source: "binance".to_string(),
             timestamp,
             confidence: Some(0.95), // Binance is real-time exchange data
+            stale: false,
+            unreliable: false,
+            unit: None,
         };
 
         // Send to channel
@@ -317,19 +382,45 @@ impl BinanceWebSocketClient {
         rx.try_recv().ok()
     }
 
-    /// Check if connected
+    /// Whether the socket is open and, with streams subscribed, still delivering
     pub async fn is_connected(&self) -> bool {
-        *self.connected.read().await
+        if !*self.connected.read().await {
+            return false;
+        }
+        // Nothing subscribed means nothing to expect; otherwise a quiet
+        // socket is likely half-open
+        if self.subscriptions.read().await.is_empty() {
+            return true;
+        }
+        let quiet_for = self.last_event.lock().unwrap().elapsed();
+        if quiet_for > self.stale_after {
+            warn!(
+                "Binance WebSocket quiet for {}s, marking stale",
+                quiet_for.as_secs()
+            );
+            *self.connected.write().await = false;
+            return false;
+        }
+        true
     }
 
     /// Reconnect to WebSocket
     ///
     /// Uses interior mutability (Arc<Mutex>) so this can be called from shared references.
+    /// The old reader is stopped first (it may be blocked on a stalled socket
+    /// while holding the read half), then every stream subscribed so far is
+    /// re-subscribed in one request.
     pub async fn reconnect(&self) -> Result<()> {
         info!("Reconnecting to Binance WebSocket...");
 
+        if let Some(old) = self.handler.lock().await.take() {
+            old.abort();
+            let _ = old.await;
+        }
+        *self.connected.write().await = false;
+
         // Connect new WebSocket
-        let (ws_stream, _) = connect_async("wss://stream.binance.com:9443/ws")
+        let (ws_stream, _) = connect_async(BINANCE_WS_URL)
             .await
             .map_err(|e| DataRetrievalError::ApiError(format!("Reconnection failed: {}", e)))?;
 
@@ -347,41 +438,38 @@ impl BinanceWebSocketClient {
         }
 
         // Resubscribe to previous streams
-        let subs: Vec<(String, String)> = {
-            let s = self.subscriptions.read().await;
-            s.iter().map(|(k, v)| (k.clone(), v.clone())).collect()
+        let streams = {
+            let subs = self.subscriptions.read().await;
+            resubscribe_streams(&subs)
         };
-
-        for (_symbol, stream) in subs {
-            // Re-subscribe using write half
+        if !streams.is_empty() {
             let subscribe_msg = serde_json::json!({
                 "method": "SUBSCRIBE",
-                "params": [&stream],
-                "id": 1,
+                "params": &streams,
+                "id": 3,
             });
 
             let msg = Message::Text(subscribe_msg.to_string());
-            {
-                let mut sink = self.ws_sink.lock().await;
-                sink.send(msg).await.map_err(|e| {
-                    DataRetrievalError::ApiError(format!("Resubscription failed: {}", e))
-                })?;
-            }
+            let mut sink = self.ws_sink.lock().await;
+            sink.send(msg).await.map_err(|e| {
+                DataRetrievalError::ApiError(format!("Resubscription failed: {}", e))
+            })?;
         }
 
         // Mark as connected
+        *self.last_event.lock().unwrap() = Instant::now();
         {
             let mut connected = self.connected.write().await;
             *connected = true;
         }
 
         // Restart message handler
-        let client_clone = self.clone();
-        tokio::spawn(async move {
-            client_clone.message_handler().await;
-        });
+        self.spawn_handler().await;
 
-        info!("Reconnected to Binance WebSocket");
+        info!(
+            "Reconnected to Binance WebSocket ({} streams resubscribed)",
+            streams.len()
+        );
         Ok(())
     }
 
@@ -406,10 +494,54 @@ impl BinanceWebSocketClient {
     }
 }
 
+/// Distinct stream names to subscribe again after a reconnect, sorted
+fn resubscribe_streams(subscriptions: &HashMap<String, String>) -> Vec<String> {
+    let mut streams: Vec<String> = subscriptions.values().cloned().collect();
+    streams.sort();
+    streams.dedup();
+    streams
+}
+
+#[async_trait::async_trait]
+impl RealtimePriceSource for BinanceWebSocketClient {
+    fn name(&self) -> &str {
+        "binance_ws"
+    }
+
+    async fn next_price(&self) -> Option<PricePoint> {
+        BinanceWebSocketClient::next_price(self).await
+    }
+
+    async fn is_connected(&self) -> bool {
+        BinanceWebSocketClient::is_connected(self).await
+    }
+
+    async fn reconnect(&self) -> Result<()> {
+        BinanceWebSocketClient::reconnect(self).await
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_resubscribe_streams() {
+        let subs = HashMap::from([
+            ("SOLUSDT".to_string(), "solusdt@trade".to_string()),
+            ("BTCUSDT".to_string(), "btcusdt@trade".to_string()),
+            (
+                "BTCUSDT_kline_1m".to_string(),
+                "btcusdt@kline_1m".to_string(),
+            ),
+        ]);
+        assert_eq!(
+            resubscribe_streams(&subs),
+            vec!["btcusdt@kline_1m", "btcusdt@trade", "solusdt@trade"]
+        );
+        assert!(resubscribe_streams(&HashMap::new()).is_empty());
+    }
+
     #[tokio::test]
     #[ignore] // Integration test - requires real Binance WebSocket (may be geo-blocked)
     async fn test_connect() {
@@ -2,7 +2,6 @@ use crate::types::*;
 use chrono::{DateTime, Utc};
 use reqwest::Client;
 use rust_decimal::Decimal;
-use std::collections::HashMap;
 use std::sync::atomic::{AtomicU64, Ordering};
 use std::time::{Duration, Instant};
 
@@ -215,25 +214,13 @@ impl CoinGeckoClient {
     /// Get CoinGecko ID for asset symbol
     async fn get_coin_id(&self, symbol: &str) -> Result<String> {
         // Common mappings for speed (avoid API call)
-        let static_mappings: HashMap<&str, &str> = [
-            ("BTC", "bitcoin"),
-            ("ETH", "ethereum"),
-            ("SOL", "solana"),
-            ("USDC", "usd-coin"),
-            ("USDT", "tether"),
-            ("BNB", "binancecoin"),
-            ("XRP", "ripple"),
-            ("ADA", "cardano"),
-            ("DOGE", "dogecoin"),
-            ("MATIC", "matic-network"),
-        ]
-        .iter()
-        .cloned()
-        .collect();
-
-        if let Some(&id) = static_mappings.get(symbol.to_uppercase().as_str()) {
+        if let Some(id) = crate::symbol::coingecko_id(symbol) {
             return Ok(id.to_string());
         }
+        // Search matches symbols and names, never a Solana mint (Jupiter prices those)
+        if crate::symbol::looks_like_mint(symbol) {
+            return Err(DataRetrievalError::AssetNotFound(symbol.to_string()));
+        }
 
         // Fallback: search API (expensive, cache this)
         let endpoint = format!("/search?query={}", symbol);
@@ -281,6 +268,9 @@ impl CoinGeckoClient {
             source: "coingecko".to_string(),
             timestamp: Utc::now(),
             confidence: Some(0.85), // CoinGecko is reliable but not real-time
+            stale: false,
+            unreliable: false,
+            unit: None,
         })
     }
 
@@ -377,6 +367,7 @@ impl CoinGeckoClient {
             },
             success_rate_24h: success_rate,
             avg_latency_ms: latency,
+            breaker: None,
         }
     }
 
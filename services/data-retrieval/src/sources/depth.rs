@@ -0,0 +1,587 @@
+//! Order book depth and liquidity estimates
+//!
+//! A price says nothing about how much can be traded at it. This pulls the
+//! top of book and depth from an exchange's REST order book (Binance or
+//! Coinbase) and walks it to estimate the slippage of a given notional, and
+//! the largest notional that stays within a slippage budget. The runner asks
+//! before sending an intent so it can shrink one the market can't absorb.
+//!
+//! Tokens without an exchange book (long-tail SPL tokens, xStocks) are
+//! estimated from a Jupiter route quote instead. Jupiter only reports the
+//! price impact of the quoted size, so the largest notional within budget is
+//! extrapolated from it assuming impact grows linearly with size.
+//!
+//! Binance books are quoted in USDT and Coinbase books in USD; both are
+//! treated as USD.
+
+use chrono::{DateTime, Utc};
+use reqwest::Client;
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use crate::sources::jupiter::{JupiterClient, SwapToken, DEFAULT_SLIPPAGE_BPS};
+use crate::types::*;
+
+const BINANCE_REST_BASE: &str = "https://api.binance.com";
+const COINBASE_REST_BASE: &str = "https://api.exchange.coinbase.com";
+/// Levels asked for per side
+const BOOK_LIMIT: u32 = 100;
+/// Books younger than this are served from memory
+const BOOK_CACHE_SECS: i64 = 2;
+/// Most books kept in memory
+const MAX_CACHED_BOOKS: usize = 200;
+/// Distances from mid that depth is summed within
+pub const DEPTH_BANDS_BPS: [u32; 3] = [10, 50, 100];
+/// Bisection steps when searching for the largest notional within budget
+const SEARCH_STEPS: usize = 32;
+
+/// Exchange an order book is fetched from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BookVenue {
+    Binance,
+    Coinbase,
+}
+
+impl BookVenue {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "binance" => Some(BookVenue::Binance),
+            "coinbase" => Some(BookVenue::Coinbase),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BookVenue::Binance => "binance",
+            BookVenue::Coinbase => "coinbase",
+        }
+    }
+
+    /// Whether the venue refuses some regions (see `DISABLE_GEO_BLOCKED_SOURCES`)
+    pub fn is_geo_blocked(&self) -> bool {
+        matches!(self, BookVenue::Binance)
+    }
+
+    /// The venue's name for `base` in its USD(T) market
+    fn instrument(&self, base: &str) -> String {
+        let base = base.trim().to_uppercase();
+        match self {
+            BookVenue::Binance => format!("{}USDT", base),
+            BookVenue::Coinbase => format!("{}-USD", base),
+        }
+    }
+}
+
+/// Side of the trade being sized; a buy walks the asks, a sell the bids
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Side {
+    Buy,
+    Sell,
+}
+
+/// One price level, size in base units
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BookLevel {
+    pub price: Decimal,
+    pub size: Decimal,
+}
+
+/// A snapshot of one market's book, best levels first
+#[derive(Debug, Clone, PartialEq)]
+pub struct OrderBook {
+    pub venue: BookVenue,
+    pub instrument: String,
+    pub bids: Vec<BookLevel>,
+    pub asks: Vec<BookLevel>,
+    pub fetched_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TopOfBook {
+    pub best_bid: Decimal,
+    pub best_ask: Decimal,
+    pub mid: Decimal,
+    pub spread_bps: f64,
+}
+
+/// USD resting on each side within `within_bps` of mid
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DepthBand {
+    pub within_bps: u32,
+    pub bid_usd: Decimal,
+    pub ask_usd: Decimal,
+}
+
+/// What trading `notional_usd` at once would cost
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SlippageEstimate {
+    pub side: Side,
+    pub notional_usd: Decimal,
+    /// Average fill price (None when the source doesn't give one)
+    pub avg_price: Option<Decimal>,
+    /// Distance of the average fill from mid, against the trader
+    pub slippage_bps: f64,
+    /// Notional the book could absorb (all of it for route quotes)
+    pub filled_usd: Decimal,
+    pub fully_filled: bool,
+}
+
+/// Response of `GET /liquidity`
+#[derive(Debug, Clone, Serialize)]
+pub struct LiquidityReport {
+    pub symbol: String,
+    /// `binance`, `coinbase` or `jupiter`
+    pub source: String,
+    /// Absent for route quotes
+    pub top: Option<TopOfBook>,
+    pub depth: Vec<DepthBand>,
+    pub estimate: SlippageEstimate,
+    /// Largest notional up to the requested one whose estimated slippage is
+    /// within `max_slippage_bps`, when a budget was given
+    pub max_notional_usd: Option<Decimal>,
+    /// Jupiter's route, for route quotes
+    pub route_summary: Option<String>,
+    pub timestamp: DateTime<Utc>,
+}
+
+fn bps(fraction: Decimal) -> f64 {
+    (fraction * Decimal::from(10_000))
+        .to_f64()
+        .unwrap_or(f64::MAX)
+}
+
+impl OrderBook {
+    pub fn top(&self) -> Option<TopOfBook> {
+        let best_bid = self.bids.first()?.price;
+        let best_ask = self.asks.first()?.price;
+        let mid = (best_bid + best_ask) / Decimal::TWO;
+        if mid <= Decimal::ZERO {
+            return None;
+        }
+        Some(TopOfBook {
+            best_bid,
+            best_ask,
+            mid,
+            spread_bps: bps((best_ask - best_bid) / mid),
+        })
+    }
+
+    /// Depth within each of [`DEPTH_BANDS_BPS`] of mid
+    pub fn depth(&self) -> Vec<DepthBand> {
+        let Some(top) = self.top() else {
+            return Vec::new();
+        };
+        let within = |levels: &[BookLevel], band: u32, side: Side| -> Decimal {
+            let offset = top.mid * Decimal::from(band) / Decimal::from(10_000);
+            levels
+                .iter()
+                .take_while(|l| match side {
+                    Side::Buy => l.price <= top.mid + offset,
+                    Side::Sell => l.price >= top.mid - offset,
+                })
+                .map(|l| l.price * l.size)
+                .sum()
+        };
+        DEPTH_BANDS_BPS
+            .iter()
+            .map(|&band| DepthBand {
+                within_bps: band,
+                bid_usd: within(&self.bids, band, Side::Sell).round_dp(2),
+                ask_usd: within(&self.asks, band, Side::Buy).round_dp(2),
+            })
+            .collect()
+    }
+
+    /// Walk the book for `notional_usd`; None for an empty or one-sided book
+    pub fn estimate(&self, side: Side, notional_usd: Decimal) -> Option<SlippageEstimate> {
+        let mid = self.top()?.mid;
+        let levels = match side {
+            Side::Buy => &self.asks,
+            Side::Sell => &self.bids,
+        };
+        let mut remaining = notional_usd;
+        let mut filled_usd = Decimal::ZERO;
+        let mut quantity = Decimal::ZERO;
+        for level in levels {
+            if remaining <= Decimal::ZERO {
+                break;
+            }
+            if level.price <= Decimal::ZERO {
+                continue;
+            }
+            let take = (level.price * level.size).min(remaining);
+            quantity += take / level.price;
+            filled_usd += take;
+            remaining -= take;
+        }
+        if quantity <= Decimal::ZERO {
+            return None;
+        }
+        let avg_price = filled_usd / quantity;
+        let slippage = match side {
+            Side::Buy => (avg_price - mid) / mid,
+            Side::Sell => (mid - avg_price) / mid,
+        };
+        Some(SlippageEstimate {
+            side,
+            notional_usd,
+            avg_price: Some(avg_price.round_dp(8)),
+            slippage_bps: bps(slippage),
+            filled_usd: filled_usd.round_dp(2),
+            fully_filled: remaining <= Decimal::ZERO,
+        })
+    }
+
+    /// Largest notional up to `notional_usd` the book fills within `max_bps`
+    pub fn max_notional_within(&self, side: Side, notional_usd: Decimal, max_bps: u32) -> Decimal {
+        let fits = |notional: Decimal| {
+            self.estimate(side, notional)
+                .is_some_and(|e| e.fully_filled && e.slippage_bps <= max_bps as f64)
+        };
+        if fits(notional_usd) {
+            return notional_usd;
+        }
+        // Slippage only grows with size, so bisect for the edge
+        let (mut lo, mut hi) = (Decimal::ZERO, notional_usd);
+        for _ in 0..SEARCH_STEPS {
+            let mid = (lo + hi) / Decimal::TWO;
+            if fits(mid) {
+                lo = mid;
+            } else {
+                hi = mid;
+            }
+        }
+        lo.round_dp_with_strategy(2, rust_decimal::RoundingStrategy::ToZero)
+    }
+
+    pub fn report(
+        &self,
+        symbol: &str,
+        side: Side,
+        notional_usd: Decimal,
+        max_slippage_bps: Option<u32>,
+    ) -> Option<LiquidityReport> {
+        Some(LiquidityReport {
+            symbol: symbol.to_string(),
+            source: self.venue.as_str().to_string(),
+            top: self.top(),
+            depth: self.depth(),
+            estimate: self.estimate(side, notional_usd)?,
+            max_notional_usd: max_slippage_bps
+                .map(|max| self.max_notional_within(side, notional_usd, max)),
+            route_summary: None,
+            timestamp: self.fetched_at,
+        })
+    }
+}
+
+/// Largest notional up to `notional_usd` within `max_bps`, from the impact
+/// of quoting `notional_usd` and assuming impact is linear in size
+pub fn extrapolate_max_notional(notional_usd: Decimal, impact_bps: f64, max_bps: u32) -> Decimal {
+    if impact_bps <= max_bps as f64 {
+        return notional_usd;
+    }
+    let share = Decimal::try_from(max_bps as f64 / impact_bps).unwrap_or(Decimal::ZERO);
+    (notional_usd * share).round_dp_with_strategy(2, rust_decimal::RoundingStrategy::ToZero)
+}
+
+#[derive(Debug, Deserialize)]
+struct RestBook {
+    bids: Vec<Vec<serde_json::Value>>,
+    asks: Vec<Vec<serde_json::Value>>,
+}
+
+/// Levels arrive as `[price, size, ...]` string arrays on both venues
+fn parse_levels(raw: &[Vec<serde_json::Value>]) -> Result<Vec<BookLevel>> {
+    let field = |level: &[serde_json::Value], i: usize| {
+        level
+            .get(i)
+            .and_then(|v| v.as_str())
+            .and_then(|s| Decimal::from_str(s).ok())
+            .ok_or_else(|| {
+                DataRetrievalError::InvalidResponse(format!("bad book level: {:?}", level))
+            })
+    };
+    raw.iter()
+        .map(|level| {
+            Ok(BookLevel {
+                price: field(level, 0)?,
+                size: field(level, 1)?,
+            })
+        })
+        .collect()
+}
+
+/// Exchange order books (briefly cached) and Jupiter route estimates
+pub struct DepthClient {
+    client: Client,
+    venue: BookVenue,
+    base_url: String,
+    books: Mutex<HashMap<String, OrderBook>>,
+}
+
+impl DepthClient {
+    pub fn new(venue: BookVenue) -> Self {
+        let base_url = match venue {
+            BookVenue::Binance => BINANCE_REST_BASE,
+            BookVenue::Coinbase => COINBASE_REST_BASE,
+        };
+        Self::with_base_url(venue, base_url)
+    }
+
+    pub fn with_base_url(venue: BookVenue, base_url: &str) -> Self {
+        let client = Client::builder()
+            .timeout(Duration::from_secs(5))
+            // Coinbase rejects requests without one
+            .user_agent("trawling-traders-data-retrieval")
+            .build()
+            .expect("Failed to create HTTP client");
+        Self {
+            client,
+            venue,
+            base_url: base_url.trim_end_matches('/').to_string(),
+            books: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn venue(&self) -> BookVenue {
+        self.venue
+    }
+
+    /// Book for `base` in the venue's USD(T) market
+    pub async fn get_book(&self, base: &str) -> Result<OrderBook> {
+        let instrument = self.venue.instrument(base);
+        if let Some(book) = self.books.lock().unwrap().get(&instrument) {
+            if (Utc::now() - book.fetched_at).num_seconds() < BOOK_CACHE_SECS {
+                return Ok(book.clone());
+            }
+        }
+
+        let request = match self.venue {
+            BookVenue::Binance => self
+                .client
+                .get(format!("{}/api/v3/depth", self.base_url))
+                .query(&[
+                    ("symbol", instrument.clone()),
+                    ("limit", BOOK_LIMIT.to_string()),
+                ]),
+            BookVenue::Coinbase => self
+                .client
+                .get(format!("{}/products/{}/book", self.base_url, instrument))
+                .query(&[("level", "2")]),
+        };
+        let response = request.send().await.map_err(|e| {
+            DataRetrievalError::ApiError(format!("{} book failed: {}", self.venue.as_str(), e))
+        })?;
+
+        match response.status() {
+            status if status.is_success() => {}
+            reqwest::StatusCode::TOO_MANY_REQUESTS => {
+                return Err(DataRetrievalError::RateLimit {
+                    source_name: self.venue.as_str().to_string(),
+                    retry_after: None,
+                })
+            }
+            // Both answer 400/404 for a market they don't list
+            reqwest::StatusCode::BAD_REQUEST | reqwest::StatusCode::NOT_FOUND => {
+                return Err(DataRetrievalError::AssetNotFound(format!(
+                    "{} has no {} market",
+                    self.venue.as_str(),
+                    instrument
+                )))
+            }
+            status => {
+                return Err(DataRetrievalError::ApiError(format!(
+                    "{} book error: {}",
+                    self.venue.as_str(),
+                    status
+                )))
+            }
+        }
+
+        let raw: RestBook = response
+            .json()
+            .await
+            .map_err(|e| DataRetrievalError::InvalidResponse(e.to_string()))?;
+        let book = OrderBook {
+            venue: self.venue,
+            instrument: instrument.clone(),
+            bids: parse_levels(&raw.bids)?,
+            asks: parse_levels(&raw.asks)?,
+            fetched_at: Utc::now(),
+        };
+
+        let mut books = self.books.lock().unwrap();
+        if books.len() >= MAX_CACHED_BOOKS {
+            books.retain(|_, b| (Utc::now() - b.fetched_at).num_seconds() < BOOK_CACHE_SECS);
+        }
+        books.insert(instrument, book.clone());
+        Ok(book)
+    }
+}
+
+/// Estimate from the Jupiter route for swapping `notional_usd` of USDC into
+/// `token` (buy) or that much of `token` into USDC (sell)
+pub async fn route_report(
+    jupiter: &JupiterClient,
+    symbol: &str,
+    token: &SwapToken,
+    side: Side,
+    notional_usd: Decimal,
+    max_slippage_bps: Option<u32>,
+) -> Result<LiquidityReport> {
+    let usdc = SwapToken::resolve("USDC");
+    let scale = |decimals: u8| Decimal::from(10u64.pow(decimals as u32));
+    let (input, output, in_amount) = match side {
+        Side::Buy => (&usdc, token, notional_usd * scale(6)),
+        Side::Sell => {
+            let decimals = token.decimals.ok_or_else(|| {
+                DataRetrievalError::AssetNotFound(format!("unknown decimals for {}", token.mint))
+            })?;
+            let price = jupiter
+                .get_usd_prices(&[token.mint.as_str()])
+                .await?
+                .remove(&token.mint)
+                .ok_or_else(|| DataRetrievalError::AssetNotFound(symbol.to_string()))?;
+            (token, &usdc, notional_usd / price * scale(decimals))
+        }
+    };
+    let in_amount_raw = in_amount
+        .trunc()
+        .to_u64()
+        .filter(|raw| *raw > 0)
+        .ok_or_else(|| {
+            DataRetrievalError::InvalidResponse(format!("can't quote ${} notional", notional_usd))
+        })?;
+
+    let sim = jupiter
+        .simulate_swap(input, output, in_amount_raw, DEFAULT_SLIPPAGE_BPS)
+        .await?;
+    let slippage_bps = sim.price_impact_pct * 100.0;
+    // effective_price is output per input: tokens per USDC on a buy
+    let avg_price = match side {
+        Side::Buy => sim
+            .effective_price
+            .and_then(|p| Decimal::ONE.checked_div(p))
+            .map(|p| p.round_dp(8)),
+        Side::Sell => sim.effective_price.map(|p| p.round_dp(8)),
+    };
+    Ok(LiquidityReport {
+        symbol: symbol.to_string(),
+        source: "jupiter".to_string(),
+        top: None,
+        depth: Vec::new(),
+        estimate: SlippageEstimate {
+            side,
+            notional_usd,
+            avg_price,
+            slippage_bps,
+            filled_usd: notional_usd,
+            fully_filled: true,
+        },
+        max_notional_usd: max_slippage_bps
+            .map(|max| extrapolate_max_notional(notional_usd, slippage_bps, max)),
+        route_summary: Some(sim.route_summary),
+        timestamp: Utc::now(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn level(price: i64, size: i64) -> BookLevel {
+        BookLevel {
+            price: Decimal::from(price),
+            size: Decimal::from(size),
+        }
+    }
+
+    fn book() -> OrderBook {
+        OrderBook {
+            venue: BookVenue::Binance,
+            instrument: "SOLUSDT".to_string(),
+            bids: vec![level(99, 10), level(98, 20)],
+            asks: vec![level(101, 10), level(102, 20)],
+            fetched_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_top_and_depth() {
+        let book = book();
+        let top = book.top().unwrap();
+        assert_eq!(top.mid, Decimal::from(100));
+        assert!((top.spread_bps - 200.0).abs() < 1e-9);
+
+        let depth = book.depth();
+        assert_eq!(depth.len(), DEPTH_BANDS_BPS.len());
+        // Nothing within 10 or 50 bps of a 100 mid with a 2% spread
+        assert_eq!(depth[1].ask_usd, Decimal::ZERO);
+        assert_eq!(depth[2].ask_usd, Decimal::from(1_010));
+        assert_eq!(depth[2].bid_usd, Decimal::from(990));
+    }
+
+    #[test]
+    fn test_estimate_walks_the_book() {
+        let book = book();
+        let small = book.estimate(Side::Buy, Decimal::from(505)).unwrap();
+        assert_eq!(small.avg_price, Some(Decimal::from(101)));
+        assert!((small.slippage_bps - 100.0).abs() < 1e-9);
+        assert!(small.fully_filled);
+
+        // 1010 at 101 then 1020 at 102
+        let large = book.estimate(Side::Buy, Decimal::from(2_030)).unwrap();
+        assert!(large.slippage_bps > 100.0 && large.slippage_bps < 200.0);
+        assert!(large.fully_filled);
+
+        let too_big = book.estimate(Side::Sell, Decimal::from(10_000)).unwrap();
+        assert!(!too_big.fully_filled);
+        assert_eq!(too_big.filled_usd, Decimal::from(2_950));
+    }
+
+    #[test]
+    fn test_max_notional_within_budget() {
+        let book = book();
+        // The first ask level is exactly 100 bps from mid
+        let max = book.max_notional_within(Side::Buy, Decimal::from(5_000), 100);
+        assert!(max >= Decimal::from(1_009) && max <= Decimal::from(1_010));
+        assert_eq!(
+            book.max_notional_within(Side::Buy, Decimal::from(500), 100),
+            Decimal::from(500)
+        );
+        assert_eq!(
+            book.max_notional_within(Side::Buy, Decimal::from(500), 50),
+            Decimal::ZERO
+        );
+
+        assert_eq!(
+            extrapolate_max_notional(Decimal::from(1_000), 40.0, 100),
+            Decimal::from(1_000)
+        );
+        assert_eq!(
+            extrapolate_max_notional(Decimal::from(1_000), 400.0, 100),
+            Decimal::from(250)
+        );
+    }
+
+    #[test]
+    fn test_parse_rest_book() {
+        let raw: RestBook = serde_json::from_str(
+            r#"{"sequence":1,"bids":[["142.10","3.5",2]],"asks":[["142.12","1.25",1]]}"#,
+        )
+        .unwrap();
+        let bids = parse_levels(&raw.bids).unwrap();
+        assert_eq!(bids[0].price, Decimal::new(14210, 2));
+        assert_eq!(bids[0].size, Decimal::new(35, 1));
+        assert_eq!(BookVenue::Coinbase.instrument("sol"), "SOL-USD");
+        assert_eq!(BookVenue::Binance.instrument("sol"), "SOLUSDT");
+    }
+}
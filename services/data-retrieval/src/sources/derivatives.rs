@@ -0,0 +1,284 @@
+//! Perpetual futures funding rates and open interest
+//!
+//! Spot prices don't show how the market is positioned. Funding on
+//! perpetuals does: persistently positive funding means longs are paying to
+//! stay in and the long side is crowded, negative means the same of shorts.
+//! Open interest says how much is riding on it. This pulls both from Binance
+//! USD-M futures so algorithms and OpenClaw can read funding as a regime
+//! signal next to the spot price.
+//!
+//! Binance futures refuses some regions, so the client isn't started when
+//! `DISABLE_GEO_BLOCKED_SOURCES` is set.
+
+use chrono::{DateTime, TimeZone, Utc};
+use reqwest::Client;
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use crate::types::*;
+
+const BINANCE_FUTURES_BASE: &str = "https://fapi.binance.com";
+/// Funding changes every eight hours, so a minute-old snapshot is fresh
+const SNAPSHOT_CACHE_SECS: i64 = 60;
+/// Most snapshots kept in memory
+const MAX_CACHED_SNAPSHOTS: usize = 200;
+/// Binance settles funding three times a day
+const FUNDING_PERIODS_PER_YEAR: f64 = 3.0 * 365.0;
+/// Funding per period beyond which one side counts as crowded (0.03%, about
+/// 33% a year; the neutral rate is 0.01%)
+pub const CROWDED_FUNDING_RATE: f64 = 0.0003;
+
+/// Which side is paying to hold its position
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Positioning {
+    CrowdedLong,
+    Neutral,
+    CrowdedShort,
+}
+
+impl Positioning {
+    pub fn from_funding(rate: f64) -> Self {
+        if rate >= CROWDED_FUNDING_RATE {
+            Positioning::CrowdedLong
+        } else if rate <= -CROWDED_FUNDING_RATE {
+            Positioning::CrowdedShort
+        } else {
+            Positioning::Neutral
+        }
+    }
+}
+
+/// Response of `GET /derivatives/{symbol}`
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DerivativesSnapshot {
+    pub symbol: String,
+    pub source: String,
+    /// Perpetual the numbers are from (`SOLUSDT`)
+    pub instrument: String,
+    /// Last settled funding rate per 8h period, as a fraction
+    pub funding_rate: f64,
+    /// `funding_rate` over a year without compounding, in percent
+    pub funding_annualized_pct: f64,
+    pub next_funding_time: DateTime<Utc>,
+    pub positioning: Positioning,
+    pub mark_price: Decimal,
+    pub index_price: Decimal,
+    /// Premium of the perpetual over spot
+    pub basis_bps: f64,
+    /// Open contracts, in base units
+    pub open_interest: Decimal,
+    pub open_interest_usd: Decimal,
+    pub timestamp: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PremiumIndex {
+    mark_price: String,
+    index_price: String,
+    last_funding_rate: String,
+    next_funding_time: i64,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct OpenInterest {
+    open_interest: String,
+}
+
+fn decimal(field: &str, raw: &str) -> Result<Decimal> {
+    Decimal::from_str(raw)
+        .map_err(|_| DataRetrievalError::InvalidResponse(format!("bad {}: {:?}", field, raw)))
+}
+
+impl DerivativesSnapshot {
+    fn from_parts(
+        symbol: &str,
+        instrument: &str,
+        premium: &PremiumIndex,
+        open_interest: &OpenInterest,
+    ) -> Result<Self> {
+        let mark_price = decimal("markPrice", &premium.mark_price)?;
+        let index_price = decimal("indexPrice", &premium.index_price)?;
+        let funding_rate = decimal("lastFundingRate", &premium.last_funding_rate)?
+            .to_f64()
+            .unwrap_or(0.0);
+        let open_interest = decimal("openInterest", &open_interest.open_interest)?;
+        let basis_bps = if index_price > Decimal::ZERO {
+            ((mark_price - index_price) / index_price * Decimal::from(10_000))
+                .to_f64()
+                .unwrap_or(0.0)
+        } else {
+            0.0
+        };
+        let next_funding_time = Utc
+            .timestamp_millis_opt(premium.next_funding_time)
+            .single()
+            .ok_or_else(|| {
+                DataRetrievalError::InvalidResponse(format!(
+                    "bad nextFundingTime: {}",
+                    premium.next_funding_time
+                ))
+            })?;
+
+        Ok(Self {
+            symbol: symbol.to_string(),
+            source: "binance_futures".to_string(),
+            instrument: instrument.to_string(),
+            funding_rate,
+            funding_annualized_pct: funding_rate * FUNDING_PERIODS_PER_YEAR * 100.0,
+            next_funding_time,
+            positioning: Positioning::from_funding(funding_rate),
+            mark_price,
+            index_price,
+            basis_bps,
+            open_interest,
+            open_interest_usd: (open_interest * mark_price).round_dp(2),
+            timestamp: Utc::now(),
+        })
+    }
+}
+
+/// Binance USD-M futures funding and open interest (cached for a minute)
+pub struct DerivativesClient {
+    client: Client,
+    base_url: String,
+    snapshots: Mutex<HashMap<String, DerivativesSnapshot>>,
+}
+
+impl Default for DerivativesClient {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DerivativesClient {
+    pub fn new() -> Self {
+        Self::with_base_url(BINANCE_FUTURES_BASE)
+    }
+
+    pub fn with_base_url(base_url: &str) -> Self {
+        let client = Client::builder()
+            .timeout(Duration::from_secs(5))
+            .build()
+            .expect("Failed to create HTTP client");
+        Self {
+            client,
+            base_url: base_url.trim_end_matches('/').to_string(),
+            snapshots: Mutex::new(HashMap::new()),
+        }
+    }
+
+    async fn get<T: serde::de::DeserializeOwned>(&self, path: &str, instrument: &str) -> Result<T> {
+        let response = self
+            .client
+            .get(format!("{}{}", self.base_url, path))
+            .query(&[("symbol", instrument)])
+            .send()
+            .await
+            .map_err(|e| DataRetrievalError::ApiError(format!("binance futures failed: {}", e)))?;
+
+        match response.status() {
+            status if status.is_success() => {}
+            reqwest::StatusCode::TOO_MANY_REQUESTS | reqwest::StatusCode::IM_A_TEAPOT => {
+                return Err(DataRetrievalError::RateLimit {
+                    source_name: "binance_futures".to_string(),
+                    retry_after: None,
+                })
+            }
+            // 400 with "Invalid symbol" for a pair with no perpetual
+            reqwest::StatusCode::BAD_REQUEST | reqwest::StatusCode::NOT_FOUND => {
+                return Err(DataRetrievalError::AssetNotFound(format!(
+                    "binance futures has no {} perpetual",
+                    instrument
+                )))
+            }
+            status => {
+                return Err(DataRetrievalError::ApiError(format!(
+                    "binance futures error: {}",
+                    status
+                )))
+            }
+        }
+
+        response
+            .json()
+            .await
+            .map_err(|e| DataRetrievalError::InvalidResponse(e.to_string()))
+    }
+
+    /// Funding and open interest of the `base`/USDT perpetual
+    pub async fn get_snapshot(&self, base: &str) -> Result<DerivativesSnapshot> {
+        let base = base.trim().to_uppercase();
+        let instrument = format!("{}USDT", base);
+        if let Some(snapshot) = self.snapshots.lock().unwrap().get(&instrument) {
+            if (Utc::now() - snapshot.timestamp).num_seconds() < SNAPSHOT_CACHE_SECS {
+                return Ok(snapshot.clone());
+            }
+        }
+
+        let (premium, open_interest) = tokio::try_join!(
+            self.get::<PremiumIndex>("/fapi/v1/premiumIndex", &instrument),
+            self.get::<OpenInterest>("/fapi/v1/openInterest", &instrument),
+        )?;
+        let snapshot =
+            DerivativesSnapshot::from_parts(&base, &instrument, &premium, &open_interest)?;
+
+        let mut snapshots = self.snapshots.lock().unwrap();
+        if snapshots.len() >= MAX_CACHED_SNAPSHOTS {
+            snapshots.retain(|_, s| (Utc::now() - s.timestamp).num_seconds() < SNAPSHOT_CACHE_SECS);
+        }
+        snapshots.insert(instrument, snapshot.clone());
+        Ok(snapshot)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_snapshot_from_binance_responses() {
+        let premium: PremiumIndex = serde_json::from_str(
+            r#"{"symbol":"SOLUSDT","markPrice":"150.15000000","indexPrice":"150.00000000",
+                "estimatedSettlePrice":"150.01","lastFundingRate":"0.00050000",
+                "interestRate":"0.00010000","nextFundingTime":1773158400000,"time":1773144000000}"#,
+        )
+        .unwrap();
+        let open_interest: OpenInterest = serde_json::from_str(
+            r#"{"openInterest":"1000.5","symbol":"SOLUSDT","time":1773144000000}"#,
+        )
+        .unwrap();
+
+        let snapshot =
+            DerivativesSnapshot::from_parts("SOL", "SOLUSDT", &premium, &open_interest).unwrap();
+        assert!((snapshot.funding_rate - 0.0005).abs() < 1e-12);
+        assert!((snapshot.funding_annualized_pct - 54.75).abs() < 1e-9);
+        assert_eq!(snapshot.positioning, Positioning::CrowdedLong);
+        assert!((snapshot.basis_bps - 10.0).abs() < 1e-9);
+        assert_eq!(snapshot.open_interest_usd, Decimal::new(15_022_508, 2));
+        assert_eq!(
+            snapshot.next_funding_time.timestamp_millis(),
+            1_773_158_400_000
+        );
+    }
+
+    #[test]
+    fn test_positioning_thresholds() {
+        assert_eq!(Positioning::from_funding(0.0001), Positioning::Neutral);
+        assert_eq!(
+            Positioning::from_funding(CROWDED_FUNDING_RATE),
+            Positioning::CrowdedLong
+        );
+        assert_eq!(
+            Positioning::from_funding(-0.0004),
+            Positioning::CrowdedShort
+        );
+    }
+}
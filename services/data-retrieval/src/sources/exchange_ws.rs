@@ -0,0 +1,520 @@
+//! Coinbase, Kraken and OKX trade streams
+//!
+//! Alternatives to the Binance WebSocket where Binance is geo-blocked. Each
+//! exchange's public trade channel is subscribed for a set of base assets
+//! and every trade becomes a [`PricePoint`] (and a [`Trade`] for building
+//! bars), so the realtime consumer treats them all like Binance.
+//!
+//! Coinbase and Kraken quote the pairs in USD, OKX in USDT. Heartbeats keep
+//! a quiet market from looking like a dead connection; after `stale_after`
+//! without any message the client reports itself disconnected and the
+//! realtime consumer reconnects it.
+
+use chrono::{DateTime, Utc};
+use futures::{SinkExt, StreamExt};
+use rust_decimal::Decimal;
+use serde_json::Value;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::{broadcast, mpsc, Mutex};
+use tokio::task::JoinHandle;
+use tokio_tungstenite::{connect_async, tungstenite::Message};
+use tracing::{debug, info, warn};
+
+use crate::candle_builder::Trade;
+use crate::symbol::SymbolId;
+use crate::types::*;
+
+/// Default for how long the stream may go quiet before it counts as stalled
+const DEFAULT_STALE_AFTER: Duration = Duration::from_secs(60);
+/// How often OKX is pinged (it drops connections idle for 30 seconds)
+const KEEPALIVE_INTERVAL: Duration = Duration::from_secs(20);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Exchange {
+    Coinbase,
+    Kraken,
+    Okx,
+}
+
+impl Exchange {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "coinbase" => Some(Exchange::Coinbase),
+            "kraken" => Some(Exchange::Kraken),
+            "okx" => Some(Exchange::Okx),
+            _ => None,
+        }
+    }
+
+    /// Source name on price points (`coinbase`, `kraken`, `okx`)
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Exchange::Coinbase => "coinbase",
+            Exchange::Kraken => "kraken",
+            Exchange::Okx => "okx",
+        }
+    }
+
+    /// Whether the exchange refuses some regions (see `DISABLE_GEO_BLOCKED_SOURCES`)
+    pub fn is_geo_blocked(&self) -> bool {
+        matches!(self, Exchange::Okx)
+    }
+
+    fn url(&self) -> &'static str {
+        match self {
+            Exchange::Coinbase => "wss://ws-feed.exchange.coinbase.com",
+            Exchange::Kraken => "wss://ws.kraken.com/v2",
+            Exchange::Okx => "wss://ws.okx.com:8443/ws/v5/public",
+        }
+    }
+
+    /// Quote the exchange's pairs are subscribed in
+    pub fn quote(&self) -> &'static str {
+        match self {
+            Exchange::Coinbase | Exchange::Kraken => "USD",
+            Exchange::Okx => "USDT",
+        }
+    }
+
+    /// The exchange's name for `base` over [`Self::quote`]
+    fn instrument(&self, base: &str) -> String {
+        let base = base.trim().to_uppercase();
+        match self {
+            Exchange::Coinbase | Exchange::Okx => format!("{}-{}", base, self.quote()),
+            Exchange::Kraken => format!("{}/{}", base, self.quote()),
+        }
+    }
+
+    fn subscribe_message(&self, instruments: &[String]) -> String {
+        let message = match self {
+            Exchange::Coinbase => serde_json::json!({
+                "type": "subscribe",
+                "product_ids": instruments,
+                "channels": ["matches", "heartbeat"],
+            }),
+            Exchange::Kraken => serde_json::json!({
+                "method": "subscribe",
+                "params": {"channel": "trade", "symbol": instruments},
+            }),
+            Exchange::Okx => serde_json::json!({
+                "op": "subscribe",
+                "args": instruments
+                    .iter()
+                    .map(|id| serde_json::json!({"channel": "trades", "instId": id}))
+                    .collect::<Vec<_>>(),
+            }),
+        };
+        message.to_string()
+    }
+
+    /// Text frame to send every [`KEEPALIVE_INTERVAL`], if the exchange wants one
+    fn keepalive(&self) -> Option<&'static str> {
+        match self {
+            Exchange::Okx => Some("ping"),
+            Exchange::Coinbase | Exchange::Kraken => None,
+        }
+    }
+
+    /// Trades in a text frame (none for acks, heartbeats and other channels)
+    fn parse_trades(&self, text: &str) -> Vec<Trade> {
+        let Ok(value) = serde_json::from_str::<Value>(text) else {
+            // OKX answers its keepalive with a bare "pong"
+            return Vec::new();
+        };
+        match self {
+            Exchange::Coinbase => {
+                let is_trade = matches!(
+                    value.get("type").and_then(Value::as_str),
+                    Some("match" | "last_match")
+                );
+                if !is_trade {
+                    return Vec::new();
+                }
+                trade_from(
+                    value.get("product_id"),
+                    value.get("price"),
+                    value.get("size"),
+                    value
+                        .get("time")
+                        .and_then(Value::as_str)
+                        .and_then(parse_rfc3339),
+                )
+                .into_iter()
+                .collect()
+            }
+            Exchange::Kraken => {
+                if value.get("channel").and_then(Value::as_str) != Some("trade") {
+                    return Vec::new();
+                }
+                trades_in(&value, |t| {
+                    trade_from(
+                        t.get("symbol"),
+                        t.get("price"),
+                        t.get("qty"),
+                        t.get("timestamp")
+                            .and_then(Value::as_str)
+                            .and_then(parse_rfc3339),
+                    )
+                })
+            }
+            Exchange::Okx => {
+                let channel = value.pointer("/arg/channel").and_then(Value::as_str);
+                if channel != Some("trades") {
+                    return Vec::new();
+                }
+                trades_in(&value, |t| {
+                    trade_from(
+                        t.get("instId"),
+                        t.get("px"),
+                        t.get("sz"),
+                        t.get("ts")
+                            .and_then(Value::as_str)
+                            .and_then(|ms| ms.parse().ok())
+                            .and_then(DateTime::from_timestamp_millis),
+                    )
+                })
+            }
+        }
+    }
+}
+
+fn trades_in(value: &Value, parse: impl Fn(&Value) -> Option<Trade>) -> Vec<Trade> {
+    value
+        .get("data")
+        .and_then(Value::as_array)
+        .map(|data| data.iter().filter_map(parse).collect())
+        .unwrap_or_default()
+}
+
+fn trade_from(
+    instrument: Option<&Value>,
+    price: Option<&Value>,
+    quantity: Option<&Value>,
+    timestamp: Option<DateTime<Utc>>,
+) -> Option<Trade> {
+    let pair = SymbolId::parse(instrument?.as_str()?);
+    Some(Trade {
+        asset: pair.base().to_string(),
+        quote: pair.quote().to_string(),
+        price: decimal(price?)?,
+        quantity: decimal(quantity?)?,
+        timestamp: timestamp.unwrap_or_else(Utc::now),
+    })
+}
+
+/// A decimal sent as a string (Coinbase, OKX) or a JSON number (Kraken)
+fn decimal(value: &Value) -> Option<Decimal> {
+    let text = match value {
+        Value::String(s) => s.clone(),
+        Value::Number(n) => n.to_string(),
+        _ => return None,
+    };
+    Decimal::from_str(&text)
+        .or_else(|_| Decimal::from_scientific(&text))
+        .ok()
+}
+
+fn parse_rfc3339(s: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(s)
+        .ok()
+        .map(|t| t.with_timezone(&Utc))
+}
+
+/// Trade stream from one exchange
+pub struct ExchangeStreamClient {
+    exchange: Exchange,
+    source_name: String,
+    instruments: Vec<String>,
+    price_tx: mpsc::Sender<PricePoint>,
+    price_rx: Mutex<mpsc::Receiver<PricePoint>>,
+    /// Every trade with its quantity, for building bars
+    trade_tx: broadcast::Sender<Trade>,
+    connected: Arc<AtomicBool>,
+    last_event: Arc<std::sync::Mutex<Instant>>,
+    stale_after: Duration,
+    reader: Mutex<Option<JoinHandle<()>>>,
+}
+
+impl ExchangeStreamClient {
+    /// Open a stream of `bases` (e.g. `BTC`) traded on `exchange`
+    pub async fn connect(exchange: Exchange, bases: &[&str]) -> Result<Self> {
+        let client = Self::new(exchange, bases)?;
+        client.open_stream().await?;
+        Ok(client)
+    }
+
+    fn new(exchange: Exchange, bases: &[&str]) -> Result<Self> {
+        let instruments: Vec<String> = bases
+            .iter()
+            .filter(|b| !b.trim().is_empty())
+            .map(|b| exchange.instrument(b))
+            .collect();
+        if instruments.is_empty() {
+            return Err(DataRetrievalError::AssetNotFound(format!(
+                "no symbols to stream from {}",
+                exchange.as_str()
+            )));
+        }
+        let (price_tx, price_rx) = mpsc::channel(10000);
+        let (trade_tx, _) = broadcast::channel(10000);
+
+        Ok(Self {
+            exchange,
+            source_name: format!("{}_ws", exchange.as_str()),
+            instruments,
+            price_tx,
+            price_rx: Mutex::new(price_rx),
+            trade_tx,
+            connected: Arc::new(AtomicBool::new(false)),
+            last_event: Arc::new(std::sync::Mutex::new(Instant::now())),
+            stale_after: DEFAULT_STALE_AFTER,
+            reader: Mutex::new(None),
+        })
+    }
+
+    /// Set how long the stream may go without messages before it is treated as down
+    pub fn with_stale_after(mut self, stale_after: Duration) -> Self {
+        self.stale_after = stale_after;
+        self
+    }
+
+    pub fn exchange(&self) -> Exchange {
+        self.exchange
+    }
+
+    /// Pairs this stream carries, in the exchange's naming
+    pub fn instruments(&self) -> &[String] {
+        &self.instruments
+    }
+
+    /// Receive every trade (price and quantity) from now on
+    ///
+    /// Survives reconnects; a receiver that falls too far behind skips ahead.
+    pub fn trade_feed(&self) -> broadcast::Receiver<Trade> {
+        self.trade_tx.subscribe()
+    }
+
+    /// Connect, subscribe and spawn the reader, replacing any previous one
+    async fn open_stream(&self) -> Result<()> {
+        let exchange = self.exchange;
+        let (mut ws, _) = connect_async(exchange.url()).await.map_err(|e| {
+            DataRetrievalError::ApiError(format!(
+                "{} WebSocket connection failed: {}",
+                exchange.as_str(),
+                e
+            ))
+        })?;
+        ws.send(Message::Text(exchange.subscribe_message(&self.instruments)))
+            .await
+            .map_err(|e| {
+                DataRetrievalError::ApiError(format!(
+                    "{} subscription failed: {}",
+                    exchange.as_str(),
+                    e
+                ))
+            })?;
+
+        *self.last_event.lock().unwrap() = Instant::now();
+        self.connected.store(true, Ordering::SeqCst);
+
+        let price_tx = self.price_tx.clone();
+        let trade_tx = self.trade_tx.clone();
+        let connected = Arc::clone(&self.connected);
+        let last_event = Arc::clone(&self.last_event);
+
+        let handle = tokio::spawn(async move {
+            let mut keepalive = tokio::time::interval(KEEPALIVE_INTERVAL);
+            loop {
+                tokio::select! {
+                    msg = ws.next() => {
+                        match msg {
+                            Some(Ok(Message::Text(text))) => {
+                                *last_event.lock().unwrap() = Instant::now();
+                                for trade in exchange.parse_trades(&text) {
+                                    let point = PricePoint {
+                                        symbol: format!("{}/{}", trade.asset, trade.quote),
+                                        price: trade.price,
+                                        source: exchange.as_str().to_string(),
+                                        timestamp: trade.timestamp,
+                                        // Real-time exchange data, as for Binance
+                                        confidence: Some(0.95),
+                                        stale: false,
+                                        unreliable: false,
+                                        unit: None,
+                                    };
+                                    // No receivers (no candle builder running) is fine
+                                    let _ = trade_tx.send(trade);
+                                    if price_tx.send(point).await.is_err() {
+                                        return;
+                                    }
+                                }
+                            }
+                            Some(Ok(Message::Ping(data))) => {
+                                *last_event.lock().unwrap() = Instant::now();
+                                if let Err(e) = ws.send(Message::Pong(data)).await {
+                                    warn!("{} pong failed: {}", exchange.as_str(), e);
+                                    break;
+                                }
+                            }
+                            Some(Ok(Message::Close(_))) | None => break,
+                            Some(Err(e)) => {
+                                warn!("{} WebSocket error: {}", exchange.as_str(), e);
+                                break;
+                            }
+                            Some(Ok(_)) => *last_event.lock().unwrap() = Instant::now(),
+                        }
+                    }
+                    _ = keepalive.tick() => {
+                        let Some(ping) = exchange.keepalive() else {
+                            continue;
+                        };
+                        if let Err(e) = ws.send(Message::Text(ping.to_string())).await {
+                            debug!("{} keepalive failed: {}", exchange.as_str(), e);
+                            break;
+                        }
+                    }
+                }
+            }
+
+            info!("{} trade stream ended", exchange.as_str());
+            connected.store(false, Ordering::SeqCst);
+        });
+
+        if let Some(old) = self.reader.lock().await.replace(handle) {
+            old.abort();
+        }
+
+        info!(
+            "Connected to {} trade stream ({} pairs)",
+            exchange.as_str(),
+            self.instruments.len()
+        );
+        Ok(())
+    }
+
+    /// Whether the stream is open and has produced messages recently
+    pub async fn is_connected(&self) -> bool {
+        if !self.connected.load(Ordering::SeqCst) {
+            return false;
+        }
+        let quiet_for = self.last_event.lock().unwrap().elapsed();
+        if quiet_for > self.stale_after {
+            warn!(
+                "{} stream quiet for {}s, marking stale",
+                self.exchange.as_str(),
+                quiet_for.as_secs()
+            );
+            self.connected.store(false, Ordering::SeqCst);
+            return false;
+        }
+        true
+    }
+
+    /// Receive the next price update
+    pub async fn next_price(&self) -> Option<PricePoint> {
+        self.price_rx.lock().await.recv().await
+    }
+
+    /// Re-open the stream and its subscriptions
+    pub async fn reconnect(&self) -> Result<()> {
+        info!("Reconnecting to {} trade stream...", self.exchange.as_str());
+        self.open_stream().await
+    }
+}
+
+#[async_trait::async_trait]
+impl RealtimePriceSource for ExchangeStreamClient {
+    fn name(&self) -> &str {
+        &self.source_name
+    }
+
+    async fn next_price(&self) -> Option<PricePoint> {
+        ExchangeStreamClient::next_price(self).await
+    }
+
+    async fn is_connected(&self) -> bool {
+        ExchangeStreamClient::is_connected(self).await
+    }
+
+    async fn reconnect(&self) -> Result<()> {
+        ExchangeStreamClient::reconnect(self).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_subscriptions() {
+        let coinbase = ExchangeStreamClient::new(Exchange::Coinbase, &["btc", "SOL"]).unwrap();
+        assert_eq!(coinbase.instruments(), ["BTC-USD", "SOL-USD"]);
+        assert_eq!(coinbase.source_name, "coinbase_ws");
+
+        let kraken: Value = serde_json::from_str(
+            &Exchange::Kraken.subscribe_message(&[Exchange::Kraken.instrument("eth")]),
+        )
+        .unwrap();
+        assert_eq!(kraken["params"]["symbol"][0], "ETH/USD");
+
+        let okx: Value = serde_json::from_str(
+            &Exchange::Okx.subscribe_message(&[Exchange::Okx.instrument("SOL")]),
+        )
+        .unwrap();
+        assert_eq!(okx["args"][0]["instId"], "SOL-USDT");
+
+        assert!(ExchangeStreamClient::new(Exchange::Okx, &[]).is_err());
+        assert_eq!(Exchange::parse("OKX"), Some(Exchange::Okx));
+        assert_eq!(Exchange::parse("binance"), None);
+    }
+
+    #[test]
+    fn test_parse_trades() {
+        let coinbase = Exchange::Coinbase.parse_trades(
+            r#"{"type":"match","trade_id":1,"product_id":"BTC-USD","size":"0.015",
+                "price":"64250.12","side":"buy","time":"2026-03-10T12:00:00.123456Z"}"#,
+        );
+        assert_eq!(coinbase.len(), 1);
+        assert_eq!(
+            (coinbase[0].asset.as_str(), coinbase[0].quote.as_str()),
+            ("BTC", "USD")
+        );
+        assert_eq!(coinbase[0].price, Decimal::new(6425012, 2));
+        assert_eq!(coinbase[0].quantity, Decimal::new(15, 3));
+        assert_eq!(coinbase[0].timestamp.timestamp(), 1_773_144_000);
+        assert!(Exchange::Coinbase
+            .parse_trades(r#"{"type":"heartbeat","product_id":"BTC-USD"}"#)
+            .is_empty());
+
+        // Kraken sends numbers, several trades per frame
+        let kraken = Exchange::Kraken.parse_trades(
+            r#"{"channel":"trade","type":"update","data":[
+                {"symbol":"SOL/USD","side":"sell","price":142.37,"qty":3.5,
+                 "ord_type":"market","trade_id":7,"timestamp":"2026-03-10T12:00:01.000000Z"},
+                {"symbol":"SOL/USD","side":"buy","price":142.4,"qty":1e-5,
+                 "ord_type":"limit","trade_id":8,"timestamp":"2026-03-10T12:00:01.500000Z"}]}"#,
+        );
+        assert_eq!(kraken.len(), 2);
+        assert_eq!(kraken[0].price, Decimal::new(14237, 2));
+        assert_eq!(kraken[1].quantity, Decimal::new(1, 5));
+        assert!(Exchange::Kraken
+            .parse_trades(r#"{"channel":"heartbeat"}"#)
+            .is_empty());
+
+        let okx = Exchange::Okx.parse_trades(
+            r#"{"arg":{"channel":"trades","instId":"ETH-USDT"},"data":[
+                {"instId":"ETH-USDT","tradeId":"9","px":"3120.5","sz":"0.2",
+                 "side":"buy","ts":"1773144000000"}]}"#,
+        );
+        assert_eq!(okx.len(), 1);
+        assert_eq!(
+            (okx[0].asset.as_str(), okx[0].quote.as_str()),
+            ("ETH", "USDT")
+        );
+        assert_eq!(okx[0].timestamp.timestamp(), 1_773_144_000);
+        assert!(Exchange::Okx.parse_trades("pong").is_empty());
+    }
+}
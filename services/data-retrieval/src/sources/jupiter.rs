@@ -0,0 +1,521 @@
+//! Jupiter swap quotes and token prices
+//!
+//! Asks Jupiter's quote API what a swap would return right now, without
+//! building or signing a transaction. The control plane's trade preview and
+//! backtests use this to model slippage and price impact from real routes.
+//!
+//! The client is also a [`PriceDataSource`] backed by Jupiter's price API,
+//! which prices any Solana mint with on-chain liquidity. That covers the
+//! memecoins and long-tail SPL tokens CoinGecko can't find by symbol.
+
+use reqwest::Client;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::registry::SymbolRegistry;
+use crate::symbol::{looks_like_mint, mint_info, symbol_mint};
+use crate::types::*;
+
+/// Keyless host (rate limited); keyed requests use `api.jup.ag`
+const JUPITER_LITE_BASE: &str = "https://lite-api.jup.ag";
+const JUPITER_PRO_BASE: &str = "https://api.jup.ag";
+/// Slippage assumed when the caller doesn't pass one
+pub const DEFAULT_SLIPPAGE_BPS: u32 = 50;
+/// Confidence given to Jupiter prices (derived from on-chain liquidity,
+/// without an interval of their own)
+const PRICE_CONFIDENCE: f64 = 0.8;
+
+/// A token resolved from a symbol or mint
+#[derive(Debug, Clone, PartialEq)]
+pub struct SwapToken {
+    pub mint: String,
+    /// Unknown for mints that aren't in the table
+    pub decimals: Option<u8>,
+}
+
+impl SwapToken {
+    /// Resolve a symbol (`SOL`) or a raw mint address
+    pub fn resolve(symbol_or_mint: &str) -> Self {
+        let known = symbol_mint(symbol_or_mint)
+            .or_else(|| mint_info(symbol_or_mint).map(|(_, decimals)| (symbol_or_mint, decimals)));
+        match known {
+            Some((mint, decimals)) => Self {
+                mint: mint.to_string(),
+                decimals: Some(decimals),
+            },
+            None => Self {
+                mint: symbol_or_mint.to_string(),
+                decimals: None,
+            },
+        }
+    }
+}
+
+/// One leg of the route Jupiter picked
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct RouteHop {
+    /// AMM name, e.g. "Raydium CLMM"
+    pub label: String,
+    pub input_mint: String,
+    pub output_mint: String,
+    /// Share of the input sent through this leg
+    pub percent: u8,
+}
+
+/// What a swap would do if executed now
+#[derive(Debug, Clone, Serialize)]
+pub struct SwapSimulation {
+    pub input_mint: String,
+    pub output_mint: String,
+    pub in_amount_raw: u64,
+    pub expected_out_raw: u64,
+    /// Worst case output at the requested slippage
+    pub min_out_raw: u64,
+    /// Output in token units (None when the output decimals are unknown)
+    pub expected_out: Option<Decimal>,
+    /// Output tokens per input token, in token units
+    pub effective_price: Option<Decimal>,
+    pub price_impact_pct: f64,
+    pub slippage_bps: u32,
+    pub route: Vec<RouteHop>,
+    /// Human readable route, e.g. "Whirlpool (70%) + Raydium (30%)"
+    pub route_summary: String,
+    pub context_slot: Option<u64>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct QuoteResponse {
+    input_mint: String,
+    in_amount: String,
+    output_mint: String,
+    out_amount: String,
+    other_amount_threshold: String,
+    slippage_bps: u32,
+    price_impact_pct: String,
+    #[serde(default)]
+    route_plan: Vec<RoutePlanStep>,
+    #[serde(default)]
+    context_slot: Option<u64>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RoutePlanStep {
+    swap_info: SwapInfo,
+    percent: u8,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SwapInfo {
+    #[serde(default)]
+    label: Option<String>,
+    input_mint: String,
+    output_mint: String,
+}
+
+/// Entry of a price API response, keyed by mint
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TokenPrice {
+    usd_price: f64,
+}
+
+/// Jupiter quote and price API client
+#[derive(Clone)]
+pub struct JupiterClient {
+    client: Client,
+    base_url: String,
+    api_key: Option<String>,
+    /// Mints for symbols beyond the built-in table
+    registry: Option<Arc<SymbolRegistry>>,
+}
+
+impl JupiterClient {
+    /// Keyed clients use the pro endpoint, keyless ones the lite endpoint
+    pub fn new(api_key: Option<String>) -> Self {
+        let base_url = if api_key.is_some() {
+            JUPITER_PRO_BASE
+        } else {
+            JUPITER_LITE_BASE
+        };
+        Self::with_base_url(base_url, api_key)
+    }
+
+    pub fn with_base_url(base_url: &str, api_key: Option<String>) -> Self {
+        let client = Client::builder()
+            .timeout(Duration::from_secs(10))
+            .build()
+            .expect("Failed to create HTTP client");
+        Self {
+            client,
+            base_url: base_url.trim_end_matches('/').to_string(),
+            api_key,
+            registry: None,
+        }
+    }
+
+    /// Resolve symbols to mints through `registry` as well as the built-ins
+    pub fn with_registry(mut self, registry: Arc<SymbolRegistry>) -> Self {
+        self.registry = Some(registry);
+        self
+    }
+
+    /// Mint for a symbol or mint address, if one is known
+    fn mint_for(&self, asset: &str) -> Option<String> {
+        if looks_like_mint(asset) {
+            return Some(asset.to_string());
+        }
+        self.registry
+            .as_ref()
+            .and_then(|registry| registry.lookup(asset)?.mint.clone())
+            .or_else(|| symbol_mint(asset).map(|(mint, _)| mint.to_string()))
+    }
+
+    fn get(&self, url: String) -> reqwest::RequestBuilder {
+        let request = self.client.get(url);
+        match &self.api_key {
+            Some(key) => request.header("x-api-key", key),
+            None => request,
+        }
+    }
+
+    /// Quote swapping `in_amount_raw` of `input` into `output`
+    pub async fn simulate_swap(
+        &self,
+        input: &SwapToken,
+        output: &SwapToken,
+        in_amount_raw: u64,
+        slippage_bps: u32,
+    ) -> Result<SwapSimulation> {
+        let request = self
+            .get(format!("{}/swap/v1/quote", self.base_url))
+            .query(&[
+                ("inputMint", input.mint.as_str()),
+                ("outputMint", output.mint.as_str()),
+                ("amount", &in_amount_raw.to_string()),
+                ("slippageBps", &slippage_bps.to_string()),
+            ]);
+
+        let response = request
+            .send()
+            .await
+            .map_err(|e| DataRetrievalError::ApiError(format!("Jupiter quote failed: {}", e)))?;
+
+        match response.status() {
+            status if status.is_success() => {}
+            reqwest::StatusCode::TOO_MANY_REQUESTS => {
+                return Err(DataRetrievalError::RateLimit {
+                    source_name: "jupiter".to_string(),
+                    retry_after: None,
+                })
+            }
+            // Jupiter answers 400 when there is no route between the mints
+            reqwest::StatusCode::BAD_REQUEST => {
+                let body = response.text().await.unwrap_or_default();
+                return Err(DataRetrievalError::AssetNotFound(format!(
+                    "no Jupiter route {} -> {}: {}",
+                    input.mint, output.mint, body
+                )));
+            }
+            status => {
+                return Err(DataRetrievalError::ApiError(format!(
+                    "Jupiter quote error: {}",
+                    status
+                )))
+            }
+        }
+
+        let quote: QuoteResponse = response
+            .json()
+            .await
+            .map_err(|e| DataRetrievalError::InvalidResponse(e.to_string()))?;
+        simulation_from_quote(quote, input.decimals, output.decimals)
+    }
+
+    /// USD prices for up to 50 mints; mints Jupiter can't price are left out
+    pub async fn get_usd_prices(&self, mints: &[&str]) -> Result<HashMap<String, Decimal>> {
+        let response = self
+            .get(format!("{}/price/v3", self.base_url))
+            .query(&[("ids", mints.join(","))])
+            .send()
+            .await
+            .map_err(|e| DataRetrievalError::ApiError(format!("Jupiter price failed: {}", e)))?;
+
+        match response.status() {
+            status if status.is_success() => {}
+            reqwest::StatusCode::TOO_MANY_REQUESTS => {
+                return Err(DataRetrievalError::RateLimit {
+                    source_name: "jupiter".to_string(),
+                    retry_after: None,
+                })
+            }
+            status => {
+                return Err(DataRetrievalError::ApiError(format!(
+                    "Jupiter price error: {}",
+                    status
+                )))
+            }
+        }
+
+        let prices: HashMap<String, Option<TokenPrice>> = response
+            .json()
+            .await
+            .map_err(|e| DataRetrievalError::InvalidResponse(e.to_string()))?;
+        Ok(usd_prices(prices))
+    }
+
+    /// Price of `asset` in `quote`, both symbols or mints
+    ///
+    /// USD is Jupiter's own unit; any other quote has to be a token with a
+    /// mint, and the price is the ratio of the two USD prices.
+    pub async fn get_price(&self, asset: &str, quote: &str) -> Result<PricePoint> {
+        let not_found = || DataRetrievalError::AssetNotFound(format!("{}/{}", asset, quote));
+        let mint = self.mint_for(asset).ok_or_else(not_found)?;
+        let quote_mint = if quote.eq_ignore_ascii_case("USD") {
+            None
+        } else {
+            Some(self.mint_for(quote).ok_or_else(not_found)?)
+        };
+
+        let mut ids = vec![mint.as_str()];
+        ids.extend(quote_mint.as_deref());
+        let prices = self.get_usd_prices(&ids).await?;
+
+        let price = prices.get(&mint).ok_or_else(not_found)?;
+        let price = match &quote_mint {
+            None => *price,
+            Some(quote_mint) => {
+                let quote_price = prices.get(quote_mint).ok_or_else(not_found)?;
+                price.checked_div(*quote_price).ok_or_else(|| {
+                    DataRetrievalError::InvalidResponse(format!("Jupiter priced {} at 0", quote))
+                })?
+            }
+        };
+
+        Ok(PricePoint {
+            symbol: format!("{}/{}", asset, quote),
+            price,
+            source: "jupiter".to_string(),
+            timestamp: chrono::Utc::now(),
+            confidence: Some(PRICE_CONFIDENCE),
+            stale: false,
+            unreliable: false,
+            unit: None,
+        })
+    }
+}
+
+/// Convert a price API response, dropping mints without a usable price
+///
+/// Prices arrive as JSON numbers, so they pass through f64 as CoinGecko's do.
+fn usd_prices(prices: HashMap<String, Option<TokenPrice>>) -> HashMap<String, Decimal> {
+    prices
+        .into_iter()
+        .filter_map(|(mint, price)| {
+            let price = Decimal::try_from(price?.usd_price).ok()?;
+            (price > Decimal::ZERO).then_some((mint, price))
+        })
+        .collect()
+}
+
+#[async_trait::async_trait]
+impl PriceDataSource for JupiterClient {
+    async fn get_price(&self, asset: &str, quote: &str) -> Result<PricePoint> {
+        JupiterClient::get_price(self, asset, quote).await
+    }
+
+    async fn get_candles(
+        &self,
+        asset: &str,
+        quote: &str,
+        _timeframe: TimeFrame,
+        _limit: usize,
+    ) -> Result<Vec<Candle>> {
+        // Not an outage, so the aggregator moves on without tripping a breaker
+        Err(DataRetrievalError::AssetNotFound(format!(
+            "Jupiter has no candles for {}/{}",
+            asset, quote
+        )))
+    }
+
+    async fn health(&self) -> SourceHealth {
+        let started = std::time::Instant::now();
+        let result = JupiterClient::get_price(self, "SOL", "USD").await;
+        SourceHealth {
+            source: "jupiter".to_string(),
+            is_healthy: result.is_ok(),
+            last_success: result.is_ok().then(chrono::Utc::now),
+            last_error: result.err().map(|e| e.to_string()),
+            success_rate_24h: 0.0,
+            avg_latency_ms: started.elapsed().as_millis() as u64,
+            breaker: None,
+        }
+    }
+
+    fn name(&self) -> &str {
+        "jupiter"
+    }
+}
+
+/// Convert a quote into a simulation, scaling amounts where decimals are known
+fn simulation_from_quote(
+    quote: QuoteResponse,
+    input_decimals: Option<u8>,
+    output_decimals: Option<u8>,
+) -> Result<SwapSimulation> {
+    let parse_raw = |field: &str, value: &str| {
+        value.parse::<u64>().map_err(|_| {
+            DataRetrievalError::InvalidResponse(format!(
+                "Jupiter {} not an integer: {}",
+                field, value
+            ))
+        })
+    };
+    let in_amount_raw = parse_raw("inAmount", &quote.in_amount)?;
+    let expected_out_raw = parse_raw("outAmount", &quote.out_amount)?;
+    let min_out_raw = parse_raw("otherAmountThreshold", &quote.other_amount_threshold)?;
+
+    // Jupiter reports impact as a fraction ("0.0012" = 0.12%)
+    let price_impact_pct = f64::from_str(&quote.price_impact_pct).unwrap_or(0.0) * 100.0;
+
+    let scale =
+        |raw: u64, decimals: u8| Decimal::from(raw) / Decimal::from(10u64.pow(decimals as u32));
+    let expected_out = output_decimals.map(|d| scale(expected_out_raw, d));
+    let effective_price = match (input_decimals, expected_out) {
+        (Some(d), Some(out)) if in_amount_raw > 0 => Some(out / scale(in_amount_raw, d)),
+        _ => None,
+    };
+
+    let route: Vec<RouteHop> = quote
+        .route_plan
+        .into_iter()
+        .map(|step| RouteHop {
+            label: step
+                .swap_info
+                .label
+                .unwrap_or_else(|| "unknown".to_string()),
+            input_mint: step.swap_info.input_mint,
+            output_mint: step.swap_info.output_mint,
+            percent: step.percent,
+        })
+        .collect();
+
+    Ok(SwapSimulation {
+        input_mint: quote.input_mint,
+        output_mint: quote.output_mint,
+        in_amount_raw,
+        expected_out_raw,
+        min_out_raw,
+        expected_out,
+        effective_price,
+        price_impact_pct,
+        slippage_bps: quote.slippage_bps,
+        route_summary: summarize_route(&route),
+        route,
+        context_slot: quote.context_slot,
+    })
+}
+
+/// Legs out of the input mint are split (`+`); later legs are hops (`->`)
+fn summarize_route(route: &[RouteHop]) -> String {
+    let Some(first) = route.first() else {
+        return String::new();
+    };
+    let mut summary = String::new();
+    for (i, hop) in route.iter().enumerate() {
+        if i > 0 {
+            summary.push_str(if hop.input_mint == first.input_mint {
+                " + "
+            } else {
+                " -> "
+            });
+        }
+        summary.push_str(&format!("{} ({}%)", hop.label, hop.percent));
+    }
+    summary
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_simulation_from_quote() {
+        let sol = SwapToken::resolve("sol");
+        let usdc = SwapToken::resolve("EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v");
+        assert_eq!(sol.decimals, Some(9));
+        assert_eq!(usdc.decimals, Some(6));
+
+        let quote: QuoteResponse = serde_json::from_str(&format!(
+            r#"{{"inputMint":"{sol}","inAmount":"2000000000","outputMint":"{usdc}",
+                "outAmount":"301500000","otherAmountThreshold":"300000000","swapMode":"ExactIn",
+                "slippageBps":50,"priceImpactPct":"0.0015","contextSlot":123,
+                "routePlan":[
+                  {{"swapInfo":{{"ammKey":"a","label":"Whirlpool","inputMint":"{sol}","outputMint":"{usdc}"}},"percent":70}},
+                  {{"swapInfo":{{"ammKey":"b","label":"Raydium","inputMint":"{sol}","outputMint":"{usdc}"}},"percent":30}}
+                ]}}"#,
+            sol = sol.mint,
+            usdc = usdc.mint
+        ))
+        .unwrap();
+
+        let sim = simulation_from_quote(quote, sol.decimals, usdc.decimals).unwrap();
+        assert_eq!(sim.in_amount_raw, 2_000_000_000);
+        assert_eq!(sim.min_out_raw, 300_000_000);
+        assert_eq!(sim.expected_out, Some(Decimal::new(3015, 1)));
+        assert_eq!(sim.effective_price, Some(Decimal::new(15075, 2)));
+        assert!((sim.price_impact_pct - 0.15).abs() < 1e-9);
+        assert_eq!(sim.route_summary, "Whirlpool (70%) + Raydium (30%)");
+
+        // Unknown output decimals leave the scaled amounts empty
+        let unknown = SwapToken::resolve("SomeMint111");
+        assert_eq!(unknown.decimals, None);
+    }
+
+    #[test]
+    fn test_price_response() {
+        let sol = "So11111111111111111111111111111111111111112";
+        let prices: HashMap<String, Option<TokenPrice>> = serde_json::from_str(&format!(
+            r#"{{"{sol}":{{"usdPrice":147.4821,"blockId":348004026,"decimals":9,
+                "priceChange24h":1.29}},
+                "7xKXtg2CW87d97TXJSDpbD5jBkheTqA83TZRuJosgAsU":{{"usdPrice":0.00000213}},
+                "EKpQGSJtjMFqKZ9KQbSqL2zPQCpA5xZKN2CjeJRdQpump":{{"usdPrice":0}},
+                "DezXAZ8z7PnrnRJjz3wXBoRgixCa6xjnB7YaB1pPB263":null}}"#
+        ))
+        .unwrap();
+        let prices = usd_prices(prices);
+        assert_eq!(prices.len(), 2);
+        assert_eq!(prices[sol], Decimal::new(1474821, 4));
+        assert_eq!(
+            prices["7xKXtg2CW87d97TXJSDpbD5jBkheTqA83TZRuJosgAsU"],
+            Decimal::new(213, 8)
+        );
+
+        // Symbols resolve through the built-ins, mints pass through as written
+        let client = JupiterClient::new(None);
+        assert_eq!(client.mint_for("SOL").as_deref(), Some(sol));
+        assert_eq!(
+            client.mint_for("7xKXtg2CW87d97TXJSDpbD5jBkheTqA83TZRuJosgAsU"),
+            Some("7xKXtg2CW87d97TXJSDpbD5jBkheTqA83TZRuJosgAsU".to_string())
+        );
+        assert_eq!(client.mint_for("AAPL"), None);
+    }
+
+    #[test]
+    fn test_summarize_multi_hop_route() {
+        let hop = |label: &str, input: &str, output: &str, percent| RouteHop {
+            label: label.to_string(),
+            input_mint: input.to_string(),
+            output_mint: output.to_string(),
+            percent,
+        };
+        let route = [hop("Orca", "A", "B", 100), hop("Meteora", "B", "C", 100)];
+        assert_eq!(summarize_route(&route), "Orca (100%) -> Meteora (100%)");
+    }
+}
@@ -0,0 +1,325 @@
+//! On-chain liquidity and holder analytics for Solana tokens
+//!
+//! Memecoins fail in ways a price doesn't show: a pool too thin to exit, a
+//! handful of wallets holding the supply, or a mint authority that can print
+//! more. This gathers the facts behind those risks for a mint:
+//!
+//! - pool liquidity summed over the token's DEX pairs (DexScreener)
+//! - the share of supply in the largest accounts (`getTokenLargestAccounts`)
+//! - whether the mint and freeze authorities are still set (`getAccountInfo`)
+//!
+//! The largest accounts include pool vaults and exchange wallets, so
+//! concentration overstates what insiders hold; it's a warning sign, not a
+//! verdict. The runner uses these in its shield check and skips tokens
+//! whose pools are too thin.
+
+use chrono::{DateTime, Utc};
+use reqwest::Client;
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use crate::types::*;
+
+const DEXSCREENER_BASE: &str = "https://api.dexscreener.com";
+pub const DEFAULT_SOLANA_RPC_URL: &str = "https://api.mainnet-beta.solana.com";
+/// Pools and holders move slowly next to prices
+const ANALYTICS_CACHE_SECS: i64 = 300;
+/// Most analytics kept in memory
+const MAX_CACHED_ANALYTICS: usize = 500;
+/// Accounts counted for concentration (the RPC returns up to 20)
+const TOP_HOLDERS: usize = 10;
+/// Top-holder share beyond which `concentrated_holders` is flagged
+pub const CONCENTRATED_HOLDERS_PCT: f64 = 50.0;
+/// Pool liquidity under which `low_liquidity` is flagged
+pub const LOW_LIQUIDITY_USD: i64 = 10_000;
+
+/// Response of `GET /tokens/{mint}/analytics`
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TokenAnalytics {
+    pub mint: String,
+    /// USD in the token's pools, None when DexScreener didn't answer
+    pub liquidity_usd: Option<Decimal>,
+    pub pools: usize,
+    /// DEX of the deepest pool
+    pub top_pool_dex: Option<String>,
+    /// Share of supply in the largest accounts, in percent
+    pub top10_holder_pct: Option<f64>,
+    pub largest_holder_pct: Option<f64>,
+    /// None once renounced
+    pub mint_authority: Option<String>,
+    pub freeze_authority: Option<String>,
+    /// `low_liquidity`, `concentrated_holders`, `mint_authority_active`,
+    /// `freeze_authority_active`
+    pub flags: Vec<String>,
+    pub timestamp: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct DexPair {
+    #[serde(default)]
+    dex_id: Option<String>,
+    #[serde(default)]
+    liquidity: Option<PairLiquidity>,
+}
+
+#[derive(Debug, Deserialize)]
+struct PairLiquidity {
+    #[serde(default)]
+    usd: Option<f64>,
+}
+
+/// Total pool liquidity, the number of pools and the deepest pool's DEX
+fn summarize_pools(pairs: &[DexPair]) -> (Decimal, usize, Option<String>) {
+    let mut total = Decimal::ZERO;
+    let mut deepest: Option<(f64, &DexPair)> = None;
+    for pair in pairs {
+        let usd = pair
+            .liquidity
+            .as_ref()
+            .and_then(|l| l.usd)
+            .filter(|u| u.is_finite() && *u > 0.0)
+            .unwrap_or(0.0);
+        total += Decimal::try_from(usd).unwrap_or(Decimal::ZERO);
+        if deepest.is_none_or(|(best, _)| usd > best) {
+            deepest = Some((usd, pair));
+        }
+    }
+    (
+        total.round_dp(2),
+        pairs.len(),
+        deepest.and_then(|(_, p)| p.dex_id.clone()),
+    )
+}
+
+/// Percent of `supply` in the largest and the top [`TOP_HOLDERS`] accounts
+fn concentration(largest: &[Value], supply: Decimal) -> Option<(f64, f64)> {
+    if supply <= Decimal::ZERO {
+        return None;
+    }
+    let amounts: Vec<Decimal> = largest
+        .iter()
+        .filter_map(|a| a["amount"].as_str())
+        .filter_map(|a| Decimal::from_str(a).ok())
+        .collect();
+    let pct = |raw: Decimal| (raw / supply * Decimal::from(100)).to_f64();
+    let top: Decimal = amounts.iter().take(TOP_HOLDERS).sum();
+    Some((pct(top)?, pct(amounts.first().copied()?)?))
+}
+
+fn flags(analytics: &TokenAnalytics) -> Vec<String> {
+    let mut flags = Vec::new();
+    if analytics
+        .liquidity_usd
+        .is_some_and(|l| l < Decimal::from(LOW_LIQUIDITY_USD))
+    {
+        flags.push("low_liquidity".to_string());
+    }
+    if analytics
+        .top10_holder_pct
+        .is_some_and(|p| p > CONCENTRATED_HOLDERS_PCT)
+    {
+        flags.push("concentrated_holders".to_string());
+    }
+    if analytics.mint_authority.is_some() {
+        flags.push("mint_authority_active".to_string());
+    }
+    if analytics.freeze_authority.is_some() {
+        flags.push("freeze_authority_active".to_string());
+    }
+    flags
+}
+
+/// DexScreener pools and Solana RPC mint and holder data (cached)
+pub struct OnChainClient {
+    client: Client,
+    rpc_url: String,
+    dexscreener_base: String,
+    analytics: Mutex<HashMap<String, TokenAnalytics>>,
+}
+
+impl OnChainClient {
+    pub fn new(rpc_url: &str) -> Self {
+        Self::with_base_urls(rpc_url, DEXSCREENER_BASE)
+    }
+
+    pub fn with_base_urls(rpc_url: &str, dexscreener_base: &str) -> Self {
+        let client = Client::builder()
+            .timeout(Duration::from_secs(10))
+            .build()
+            .expect("Failed to create HTTP client");
+        Self {
+            client,
+            rpc_url: rpc_url.to_string(),
+            dexscreener_base: dexscreener_base.trim_end_matches('/').to_string(),
+            analytics: Mutex::new(HashMap::new()),
+        }
+    }
+
+    async fn rpc(&self, method: &str, params: Value) -> Result<Value> {
+        let response = self
+            .client
+            .post(&self.rpc_url)
+            .json(&json!({"jsonrpc": "2.0", "id": 1, "method": method, "params": params}))
+            .send()
+            .await
+            .map_err(|e| DataRetrievalError::ApiError(format!("{} failed: {}", method, e)))?;
+        if response.status() == reqwest::StatusCode::TOO_MANY_REQUESTS {
+            return Err(DataRetrievalError::RateLimit {
+                source_name: "solana_rpc".to_string(),
+                retry_after: None,
+            });
+        }
+        let body: Value = response
+            .json()
+            .await
+            .map_err(|e| DataRetrievalError::InvalidResponse(e.to_string()))?;
+        if let Some(error) = body.get("error") {
+            return Err(DataRetrievalError::ApiError(format!(
+                "{} error: {}",
+                method, error
+            )));
+        }
+        Ok(body["result"].clone())
+    }
+
+    async fn pools(&self, mint: &str) -> Result<Vec<DexPair>> {
+        let response = self
+            .client
+            .get(format!(
+                "{}/tokens/v1/solana/{}",
+                self.dexscreener_base, mint
+            ))
+            .send()
+            .await
+            .map_err(|e| DataRetrievalError::ApiError(format!("dexscreener failed: {}", e)))?;
+        if !response.status().is_success() {
+            return Err(DataRetrievalError::ApiError(format!(
+                "dexscreener error: {}",
+                response.status()
+            )));
+        }
+        response
+            .json()
+            .await
+            .map_err(|e| DataRetrievalError::InvalidResponse(e.to_string()))
+    }
+
+    /// Pools, holders and authorities of `mint`
+    ///
+    /// The mint account must exist; pools and holders are left empty when
+    /// their source fails.
+    pub async fn get_analytics(&self, mint: &str) -> Result<TokenAnalytics> {
+        if let Some(cached) = self.analytics.lock().unwrap().get(mint) {
+            if (Utc::now() - cached.timestamp).num_seconds() < ANALYTICS_CACHE_SECS {
+                return Ok(cached.clone());
+            }
+        }
+
+        let (account, largest, pools) = tokio::join!(
+            self.rpc("getAccountInfo", json!([mint, {"encoding": "jsonParsed"}])),
+            self.rpc("getTokenLargestAccounts", json!([mint])),
+            self.pools(mint),
+        );
+        let info = account?["value"]["data"]["parsed"]["info"].clone();
+        if info.is_null() {
+            return Err(DataRetrievalError::AssetNotFound(format!(
+                "{} is not a token mint",
+                mint
+            )));
+        }
+        let supply = info["supply"]
+            .as_str()
+            .and_then(|s| Decimal::from_str(s).ok())
+            .unwrap_or(Decimal::ZERO);
+        let authority = |key: &str| info[key].as_str().map(String::from);
+
+        let holders = largest
+            .map_err(|e| tracing::debug!("Largest accounts of {} unavailable: {}", mint, e))
+            .ok()
+            .and_then(|l| concentration(l["value"].as_array()?, supply));
+        let pools = pools
+            .map_err(|e| tracing::debug!("Pools of {} unavailable: {}", mint, e))
+            .ok()
+            .map(|pairs| summarize_pools(&pairs));
+
+        let mut analytics = TokenAnalytics {
+            mint: mint.to_string(),
+            liquidity_usd: pools.as_ref().map(|(usd, _, _)| *usd),
+            pools: pools.as_ref().map_or(0, |(_, count, _)| *count),
+            top_pool_dex: pools.and_then(|(_, _, dex)| dex),
+            top10_holder_pct: holders.map(|(top, _)| top),
+            largest_holder_pct: holders.map(|(_, largest)| largest),
+            mint_authority: authority("mintAuthority"),
+            freeze_authority: authority("freezeAuthority"),
+            flags: Vec::new(),
+            timestamp: Utc::now(),
+        };
+        analytics.flags = flags(&analytics);
+
+        let mut cache = self.analytics.lock().unwrap();
+        if cache.len() >= MAX_CACHED_ANALYTICS {
+            cache.retain(|_, a| (Utc::now() - a.timestamp).num_seconds() < ANALYTICS_CACHE_SECS);
+        }
+        cache.insert(mint.to_string(), analytics.clone());
+        Ok(analytics)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pool_summary() {
+        let pairs: Vec<DexPair> = serde_json::from_str(
+            r#"[{"chainId":"solana","dexId":"raydium","liquidity":{"usd":12000.5,"base":1,"quote":2}},
+                {"chainId":"solana","dexId":"orca","liquidity":{"usd":30000.25}},
+                {"chainId":"solana","dexId":"pumpswap"}]"#,
+        )
+        .unwrap();
+        let (usd, count, dex) = summarize_pools(&pairs);
+        assert_eq!(usd, Decimal::new(4_200_075, 2));
+        assert_eq!(count, 3);
+        assert_eq!(dex.as_deref(), Some("orca"));
+    }
+
+    #[test]
+    fn test_concentration_and_flags() {
+        let largest: Vec<Value> = (0..12)
+            .map(|i| json!({"address": format!("acct{}", i), "amount": if i == 0 { "400" } else { "50" }}))
+            .collect();
+        let (top10, largest_pct) = concentration(&largest, Decimal::from(1_000)).unwrap();
+        // 400 + 9 * 50 of 1000
+        assert!((top10 - 85.0).abs() < 1e-9);
+        assert!((largest_pct - 40.0).abs() < 1e-9);
+        assert!(concentration(&largest, Decimal::ZERO).is_none());
+
+        let analytics = TokenAnalytics {
+            mint: "Mint".to_string(),
+            liquidity_usd: Some(Decimal::from(5_000)),
+            pools: 1,
+            top_pool_dex: None,
+            top10_holder_pct: Some(top10),
+            largest_holder_pct: Some(largest_pct),
+            mint_authority: None,
+            freeze_authority: Some("Freezer".to_string()),
+            flags: Vec::new(),
+            timestamp: Utc::now(),
+        };
+        assert_eq!(
+            flags(&analytics),
+            vec![
+                "low_liquidity",
+                "concentrated_holders",
+                "freeze_authority_active"
+            ]
+        );
+    }
+}
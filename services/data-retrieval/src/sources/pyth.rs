@@ -1,14 +1,17 @@
 use anyhow::{Context, Result};
 use chrono::{DateTime, Utc};
 use reqwest::Client;
+use rust_decimal::prelude::ToPrimitive;
 use rust_decimal::Decimal;
 use serde::Deserialize;
 use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
 use tracing::{debug, info};
 
 use crate::types::{
     Candle, DataRetrievalError, PriceDataSource, PricePoint, SourceHealth, TimeFrame,
 };
+use crate::units::{self, convert_unit_price};
 
 const PYTH_HERMES_BASE: &str = "https://hermes.pyth.network/v2";
 
@@ -60,6 +63,26 @@ pub struct ParsedPrice {
     pub ema_price: PriceData,
 }
 
+/// Entry from Hermes `/price_feeds` (feed discovery)
+#[derive(Debug, Deserialize)]
+struct FeedInfo {
+    id: String,
+    #[serde(default)]
+    attributes: HashMap<String, String>,
+}
+
+/// Hermes symbol for feeds we resolve at runtime (`Metal.XPT/USD`, `FX.EUR/USD`)
+fn hermes_symbol(symbol: &str) -> Option<(String, &'static str)> {
+    if let Some(metal) = units::metal_spec(symbol) {
+        return Some((format!("Metal.{}/USD", metal.underlying), "metal"));
+    }
+    let (base, quote) = units::fx_legs(symbol)?;
+    Some((
+        format!("FX.{}/{}", base.to_uppercase(), quote.to_uppercase()),
+        "fx",
+    ))
+}
+
 #[derive(Debug, Deserialize)]
 pub struct PriceData {
     pub price: String,
@@ -68,11 +91,59 @@ pub struct PriceData {
     pub publish_time: i64,
 }
 
+/// Scale a Pyth fixed-point integer (`value * 10^expo`) exactly
+fn scale_pyth_int(value: i64, expo: i32) -> Option<Decimal> {
+    if expo <= 0 {
+        let scale = expo.unsigned_abs();
+        (scale <= 28).then(|| Decimal::new(value, scale))
+    } else {
+        Decimal::from(value).checked_mul(Decimal::from(10i64.checked_pow(expo as u32)?))
+    }
+}
+
+/// Build a price point from a parsed Pyth price
+///
+/// `confidence` is the confidence interval relative to price (conf / price).
+pub(crate) fn price_point_from_pyth(symbol: &str, data: &PriceData) -> Result<PricePoint> {
+    let price_int: i64 = data.price.parse().context("Failed to parse Pyth price")?;
+    let conf_int: u64 = data
+        .conf
+        .parse()
+        .context("Failed to parse Pyth confidence")?;
+
+    let price = scale_pyth_int(price_int, data.expo).context("Pyth price out of range")?;
+    let conf = i64::try_from(conf_int)
+        .ok()
+        .and_then(|c| scale_pyth_int(c, data.expo))
+        .context("Pyth confidence out of range")?;
+    let timestamp = DateTime::from_timestamp(data.publish_time, 0).unwrap_or_else(Utc::now);
+
+    let confidence = if price > Decimal::ZERO {
+        (conf / price).to_f64().unwrap_or(0.0)
+    } else {
+        0.0
+    };
+
+    let asset = symbol.split('/').next().unwrap_or(symbol);
+    Ok(PricePoint {
+        symbol: symbol.to_string(),
+        price,
+        source: "pyth".to_string(),
+        timestamp,
+        confidence: Some(confidence),
+        stale: false,
+        unreliable: false,
+        unit: units::metal_spec(asset).map(|m| m.unit),
+    })
+}
+
 /// Pyth Network client for price feeds
 #[derive(Clone)]
 pub struct PythClient {
     client: Client,
     base_url: String,
+    /// Feed ids looked up from Hermes for symbols missing from `PYTH_FEED_IDS`
+    discovered_feeds: Arc<RwLock<HashMap<String, String>>>,
 }
 
 impl Default for PythClient {
@@ -91,14 +162,77 @@ impl PythClient {
                 .build()
                 .expect("Failed to create HTTP client"),
             base_url: PYTH_HERMES_BASE.to_string(),
+            discovered_feeds: Arc::new(RwLock::new(HashMap::new())),
         }
     }
 
-    /// Get price for a stock/metal symbol
-    pub async fn get_price(&self, symbol: &str) -> Result<PricePoint> {
-        let feed_id = PYTH_FEED_IDS
-            .get(symbol)
+    /// Feed id for a symbol: the static table first, then Hermes feed search
+    /// (metals and FX), cached for the life of the client
+    pub async fn resolve_feed_id(&self, symbol: &str) -> Result<String> {
+        if let Some(id) = PYTH_FEED_IDS.get(symbol) {
+            return Ok(id.to_string());
+        }
+        if let Some(id) = self.discovered_feeds.read().unwrap().get(symbol) {
+            return Ok(id.clone());
+        }
+
+        let (wanted, asset_type) = hermes_symbol(symbol)
             .with_context(|| format!("No Pyth feed ID for symbol: {}", symbol))?;
+        let query = wanted
+            .split(['.', '/'])
+            .nth(1)
+            .unwrap_or(symbol)
+            .to_string();
+        let url = format!(
+            "{}/price_feeds?query={}&asset_type={}",
+            self.base_url, query, asset_type
+        );
+
+        let feeds: Vec<FeedInfo> = self
+            .client
+            .get(&url)
+            .send()
+            .await
+            .context("Failed to search Pyth feeds")?
+            .json()
+            .await
+            .context("Failed to parse Pyth feed search")?;
+
+        let id = feeds
+            .into_iter()
+            .find(|f| {
+                f.attributes
+                    .get("symbol")
+                    .is_some_and(|s| s.eq_ignore_ascii_case(&wanted))
+            })
+            .map(|f| f.id.trim_start_matches("0x").to_string())
+            .with_context(|| format!("Pyth has no {} feed", wanted))?;
+
+        info!("Resolved Pyth feed {} -> {}", wanted, id);
+        self.discovered_feeds
+            .write()
+            .unwrap()
+            .insert(symbol.to_string(), id.clone());
+        Ok(id)
+    }
+
+    /// Get price for a stock/metal/FX symbol
+    ///
+    /// Metals carry their quoting unit; derived metals (ORO) are converted
+    /// from their underlying's per-ounce spot price.
+    pub async fn get_price(&self, symbol: &str) -> Result<PricePoint> {
+        if let Some(metal) = units::metal_spec(symbol).filter(|m| m.is_derived()) {
+            let spot = Box::pin(self.get_price(metal.underlying)).await?;
+            let spot_unit = spot.unit.unwrap_or(units::PriceUnit::TroyOunce);
+            return Ok(PricePoint {
+                symbol: symbol.to_string(),
+                price: convert_unit_price(spot.price, spot_unit, metal.unit),
+                ..spot
+            }
+            .with_unit(metal.unit));
+        }
+
+        let feed_id = self.resolve_feed_id(symbol).await?;
 
         let url = format!("{}/updates/price/latest?ids[]={}", self.base_url, feed_id);
 
@@ -128,41 +262,17 @@ impl PythClient {
             .next()
             .context("No price data in Pyth response")?;
 
-        let price_data = parsed.price;
-
         // Pyth returns price as integer with exponent
-        let price_int: i64 = price_data
-            .price
-            .parse()
-            .context("Failed to parse Pyth price")?;
-        let price_f64 = (price_int as f64) * 10f64.powi(price_data.expo);
-
-        let confidence: u64 = price_data
-            .conf
-            .parse()
-            .context("Failed to parse Pyth confidence")?;
-        let confidence_usd = (confidence as f64) * 10f64.powi(price_data.expo);
-
-        let timestamp =
-            DateTime::from_timestamp(price_data.publish_time, 0).unwrap_or_else(Utc::now);
+        let point = price_point_from_pyth(symbol, &parsed.price)?;
 
         info!(
-            "Pyth price for {}: ${:.4} (confidence: ${:.4})",
-            symbol, price_f64, confidence_usd
+            "Pyth price for {}: ${} (confidence: {:.4}%)",
+            symbol,
+            point.price,
+            point.confidence.unwrap_or(0.0) * 100.0
         );
 
-        Ok(PricePoint {
-            symbol: symbol.to_string(),
-            price: Decimal::try_from(price_f64)
-                .map_err(|e| anyhow::anyhow!("Failed to convert price: {}", e))?,
-            source: "pyth".to_string(),
-            timestamp,
-            confidence: Some(if price_f64 > 0.0 {
-                confidence_usd / price_f64
-            } else {
-                0.0
-            }),
-        })
+        Ok(point)
     }
 
     /// Get multiple prices in one request (more efficient)
@@ -218,6 +328,9 @@ impl PythClient {
                         source: "pyth".to_string(),
                         timestamp,
                         confidence: None,
+                        stale: false,
+                        unreliable: false,
+                        unit: units::metal_spec(symbol).map(|m| m.unit),
                     },
                 );
             }
@@ -226,9 +339,11 @@ impl PythClient {
         Ok(result)
     }
 
-    /// Check if symbol is supported
+    /// Check if symbol is supported (static feed, metal or FX pair)
     pub fn supports_symbol(symbol: &str) -> bool {
         PYTH_FEED_IDS.contains_key(symbol)
+            || units::metal_spec(symbol).is_some()
+            || units::is_fx_pair(symbol)
     }
 
     /// Get list of supported stock symbols
@@ -245,13 +360,21 @@ impl PythClient {
 
     /// Get list of supported metal symbols
     pub fn supported_metals() -> Vec<&'static str> {
-        vec!["XAU", "XAG"]
+        units::METALS.iter().map(|m| m.symbol).collect()
+    }
+
+    /// Get list of supported FX pairs
+    pub fn supported_fx() -> Vec<&'static str> {
+        units::FX_PAIRS.to_vec()
     }
 }
 
 #[async_trait::async_trait]
 impl PriceDataSource for PythClient {
     async fn get_price(&self, asset: &str, _quote: &str) -> crate::types::Result<PricePoint> {
+        if !PythClient::supports_symbol(asset) {
+            return Err(DataRetrievalError::AssetNotFound(asset.to_string()));
+        }
         PythClient::get_price(self, asset)
             .await
             .map_err(|e| DataRetrievalError::ApiError(e.to_string()))
@@ -278,6 +401,7 @@ impl PriceDataSource for PythClient {
                 last_error: None,
                 success_rate_24h: 1.0,
                 avg_latency_ms: 0,
+                breaker: None,
             },
             Err(e) => SourceHealth {
                 source: "pyth".to_string(),
@@ -286,6 +410,7 @@ impl PriceDataSource for PythClient {
                 last_error: Some(e.to_string()),
                 success_rate_24h: 0.0,
                 avg_latency_ms: 0,
+                breaker: None,
             },
         }
     }
@@ -305,6 +430,26 @@ mod tests {
         assert!(PythClient::supports_symbol("TSLA"));
         assert!(PythClient::supports_symbol("BTC"));
         assert!(!PythClient::supports_symbol("FAKE"));
+        assert!(PythClient::supports_symbol("XPT"));
+        assert!(PythClient::supports_symbol("EURUSD"));
+    }
+
+    #[test]
+    fn test_hermes_symbol_for_runtime_feeds() {
+        assert_eq!(
+            hermes_symbol("XPD"),
+            Some(("Metal.XPD/USD".to_string(), "metal"))
+        );
+        // Derived metals resolve to their underlying
+        assert_eq!(
+            hermes_symbol("ORO"),
+            Some(("Metal.XAU/USD".to_string(), "metal"))
+        );
+        assert_eq!(
+            hermes_symbol("USDJPY"),
+            Some(("FX.USD/JPY".to_string(), "fx"))
+        );
+        assert_eq!(hermes_symbol("AAPL"), None);
     }
 
     #[test]
@@ -314,5 +459,8 @@ mod tests {
         let expo = -8;
         let price = (price_int as f64) * 10f64.powi(expo);
         assert!((price - 1.225).abs() < 0.0001);
+
+        assert_eq!(scale_pyth_int(price_int, expo), Some(Decimal::new(1225, 3)));
+        assert_eq!(scale_pyth_int(12, 2), Some(Decimal::from(1200)));
     }
 }
@@ -0,0 +1,308 @@
+//! Pyth Hermes streaming (Server-Sent Events) price feed
+//!
+//! Subscribes to `/updates/price/stream` for a set of Pyth feeds and pushes
+//! each update into a channel, like the Binance WebSocket does for crypto.
+//! Hermes sends an event roughly every slot, so a quiet stream means the
+//! connection is wedged: after `stale_after` without data the client reports
+//! itself disconnected and the realtime consumer reconnects it.
+
+use futures::StreamExt;
+use reqwest::Client;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::{mpsc, Mutex};
+use tokio::task::JoinHandle;
+use tracing::{debug, info, warn};
+
+use super::pyth::{price_point_from_pyth, ParsedPrice, PYTH_FEED_IDS};
+use crate::types::*;
+
+const PYTH_HERMES_BASE: &str = "https://hermes.pyth.network/v2";
+/// Default for how long the stream may go quiet before it counts as stalled
+const DEFAULT_STALE_AFTER: Duration = Duration::from_secs(30);
+
+/// Streaming Pyth client for low-latency equity/ETF/metal prices
+pub struct PythStreamClient {
+    client: Client,
+    base_url: String,
+    /// feed id (lowercase hex, no 0x) -> symbol
+    feeds: HashMap<String, String>,
+    price_tx: mpsc::Sender<PricePoint>,
+    price_rx: Mutex<mpsc::Receiver<PricePoint>>,
+    connected: Arc<AtomicBool>,
+    last_event: Arc<std::sync::Mutex<Instant>>,
+    stale_after: Duration,
+    reader: Mutex<Option<JoinHandle<()>>>,
+}
+
+impl PythStreamClient {
+    /// Open a stream for `symbols` (those without a Pyth feed are skipped)
+    pub async fn connect(symbols: &[&str]) -> Result<Self> {
+        let client = Self::new(symbols, PYTH_HERMES_BASE)?;
+        client.open_stream().await?;
+        Ok(client)
+    }
+
+    fn new(symbols: &[&str], base_url: &str) -> Result<Self> {
+        let feeds: HashMap<String, String> = symbols
+            .iter()
+            .filter_map(|s| {
+                PYTH_FEED_IDS
+                    .get(s)
+                    .map(|id| (id.to_string(), format!("{}/USD", s)))
+            })
+            .collect();
+        if feeds.is_empty() {
+            return Err(DataRetrievalError::AssetNotFound(symbols.join(",")));
+        }
+
+        // No overall timeout: the response body is the long-lived stream
+        let client = Client::builder()
+            .connect_timeout(Duration::from_secs(10))
+            .build()
+            .map_err(|e| DataRetrievalError::ApiError(e.to_string()))?;
+        let (price_tx, price_rx) = mpsc::channel(10000);
+
+        Ok(Self {
+            client,
+            base_url: base_url.to_string(),
+            feeds,
+            price_tx,
+            price_rx: Mutex::new(price_rx),
+            connected: Arc::new(AtomicBool::new(false)),
+            last_event: Arc::new(std::sync::Mutex::new(Instant::now())),
+            stale_after: DEFAULT_STALE_AFTER,
+            reader: Mutex::new(None),
+        })
+    }
+
+    /// Set how long the stream may go without events before it is treated as down
+    pub fn with_stale_after(mut self, stale_after: Duration) -> Self {
+        self.stale_after = stale_after;
+        self
+    }
+
+    /// Symbols this stream carries (as `SYMBOL/USD`)
+    pub fn symbols(&self) -> Vec<String> {
+        self.feeds.values().cloned().collect()
+    }
+
+    fn stream_url(&self) -> String {
+        let mut url = format!("{}/updates/price/stream?parsed=true", self.base_url);
+        for id in self.feeds.keys() {
+            url.push_str(&format!("&ids[]={}", id));
+        }
+        url
+    }
+
+    /// Connect and spawn the reader, replacing any previous one
+    async fn open_stream(&self) -> Result<()> {
+        let response = self
+            .client
+            .get(self.stream_url())
+            .header(reqwest::header::ACCEPT, "text/event-stream")
+            .send()
+            .await
+            .map_err(|e| DataRetrievalError::ApiError(format!("Pyth stream failed: {}", e)))?;
+
+        if !response.status().is_success() {
+            return Err(DataRetrievalError::ApiError(format!(
+                "Pyth stream error: {}",
+                response.status()
+            )));
+        }
+
+        *self.last_event.lock().unwrap() = Instant::now();
+        self.connected.store(true, Ordering::SeqCst);
+
+        let feeds = self.feeds.clone();
+        let price_tx = self.price_tx.clone();
+        let connected = Arc::clone(&self.connected);
+        let last_event = Arc::clone(&self.last_event);
+
+        let handle = tokio::spawn(async move {
+            let mut body = response.bytes_stream();
+            let mut parser = SseParser::default();
+
+            while let Some(chunk) = body.next().await {
+                let chunk = match chunk {
+                    Ok(chunk) => chunk,
+                    Err(e) => {
+                        warn!("Pyth stream read error: {}", e);
+                        break;
+                    }
+                };
+                *last_event.lock().unwrap() = Instant::now();
+
+                for data in parser.push(&chunk) {
+                    for point in parse_stream_event(&data, &feeds) {
+                        if price_tx.send(point).await.is_err() {
+                            return;
+                        }
+                    }
+                }
+            }
+
+            info!("Pyth price stream ended");
+            connected.store(false, Ordering::SeqCst);
+        });
+
+        if let Some(old) = self.reader.lock().await.replace(handle) {
+            old.abort();
+        }
+
+        info!(
+            "Connected to Pyth price stream ({} feeds)",
+            self.feeds.len()
+        );
+        Ok(())
+    }
+
+    /// Whether the stream is open and has produced data recently
+    pub async fn is_connected(&self) -> bool {
+        if !self.connected.load(Ordering::SeqCst) {
+            return false;
+        }
+        let quiet_for = self.last_event.lock().unwrap().elapsed();
+        if quiet_for > self.stale_after {
+            warn!(
+                "Pyth stream quiet for {}s, marking stale",
+                quiet_for.as_secs()
+            );
+            self.connected.store(false, Ordering::SeqCst);
+            return false;
+        }
+        true
+    }
+
+    /// Receive the next price update
+    pub async fn next_price(&self) -> Option<PricePoint> {
+        self.price_rx.lock().await.recv().await
+    }
+
+    /// Re-open the stream
+    pub async fn reconnect(&self) -> Result<()> {
+        info!("Reconnecting to Pyth price stream...");
+        self.open_stream().await
+    }
+}
+
+#[async_trait::async_trait]
+impl RealtimePriceSource for PythStreamClient {
+    fn name(&self) -> &str {
+        "pyth_stream"
+    }
+
+    async fn next_price(&self) -> Option<PricePoint> {
+        PythStreamClient::next_price(self).await
+    }
+
+    async fn is_connected(&self) -> bool {
+        PythStreamClient::is_connected(self).await
+    }
+
+    async fn reconnect(&self) -> Result<()> {
+        PythStreamClient::reconnect(self).await
+    }
+}
+
+/// One Hermes stream event (`binary` is ignored)
+#[derive(Debug, Deserialize)]
+struct StreamEvent {
+    #[serde(default)]
+    parsed: Vec<ParsedPrice>,
+}
+
+/// Turn an event's JSON payload into price points for the feeds we track
+fn parse_stream_event(data: &str, feeds: &HashMap<String, String>) -> Vec<PricePoint> {
+    let event: StreamEvent = match serde_json::from_str(data) {
+        Ok(event) => event,
+        Err(e) => {
+            debug!("Skipping unparseable Pyth event: {}", e);
+            return Vec::new();
+        }
+    };
+
+    event
+        .parsed
+        .iter()
+        .filter_map(|parsed| {
+            let symbol = feeds.get(parsed.id.trim_start_matches("0x"))?;
+            price_point_from_pyth(symbol, &parsed.price)
+                .map_err(|e| debug!("Bad Pyth price for {}: {}", symbol, e))
+                .ok()
+        })
+        .collect()
+}
+
+/// Incremental Server-Sent Events parser
+///
+/// Feed it raw chunks; it returns the `data` payload of every event completed
+/// so far (multi-line data joined with `\n`). Comments and other fields are
+/// ignored.
+#[derive(Default)]
+struct SseParser {
+    buffer: String,
+    data: Vec<String>,
+}
+
+impl SseParser {
+    fn push(&mut self, chunk: &[u8]) -> Vec<String> {
+        self.buffer.push_str(&String::from_utf8_lossy(chunk));
+
+        let mut events = Vec::new();
+        while let Some(pos) = self.buffer.find('\n') {
+            let line: String = self.buffer.drain(..=pos).collect();
+            let line = line.trim_end_matches(['\n', '\r']);
+
+            if line.is_empty() {
+                if !self.data.is_empty() {
+                    events.push(self.data.join("\n"));
+                    self.data.clear();
+                }
+            } else if let Some(value) = line.strip_prefix("data:") {
+                self.data
+                    .push(value.strip_prefix(' ').unwrap_or(value).to_string());
+            }
+        }
+        events
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal::Decimal;
+
+    #[test]
+    fn test_sse_parser_handles_split_chunks() {
+        let mut parser = SseParser::default();
+        assert!(parser.push(b": keepalive\n\ndata: {\"a\"").is_empty());
+        let events = parser.push(b":1}\r\n\r\ndata: x\ndata: y\n\n");
+        assert_eq!(events, vec!["{\"a\":1}".to_string(), "x\ny".to_string()]);
+    }
+
+    #[test]
+    fn test_parse_stream_event_maps_tracked_feeds() {
+        let aapl = PYTH_FEED_IDS.get("AAPL").unwrap();
+        let feeds: HashMap<String, String> = [(aapl.to_string(), "AAPL/USD".to_string())].into();
+        let data = format!(
+            r#"{{"binary":{{"encoding":"hex","data":[]}},"parsed":[
+                {{"id":"{}","price":{{"price":"19012345","conf":"1000","expo":-5,"publish_time":1700000000}},
+                  "ema_price":{{"price":"19000000","conf":"1000","expo":-5,"publish_time":1700000000}}}},
+                {{"id":"ffff","price":{{"price":"1","conf":"1","expo":0,"publish_time":1700000000}},
+                  "ema_price":{{"price":"1","conf":"1","expo":0,"publish_time":1700000000}}}}
+            ]}}"#,
+            aapl
+        );
+
+        let points = parse_stream_event(&data, &feeds);
+        assert_eq!(points.len(), 1);
+        assert_eq!(points[0].symbol, "AAPL/USD");
+        assert_eq!(points[0].price, Decimal::new(19012345, 5));
+        assert_eq!(points[0].timestamp.timestamp(), 1_700_000_000);
+    }
+}
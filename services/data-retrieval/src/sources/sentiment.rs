@@ -0,0 +1,371 @@
+//! Market sentiment from the Fear & Greed index and CryptoPanic news
+//!
+//! The Crypto Fear & Greed index (alternative.me, no key) is one market-wide
+//! reading from 0 (extreme fear) to 100 (extreme greed), updated daily.
+//! When `CRYPTOPANIC_API_KEY` is set, the votes on recent CryptoPanic posts
+//! tagged with the asset add a per-symbol news score. Both are folded into a
+//! single score from -1 (bearish) to 1 (bullish) that the runner can pass to
+//! OpenClaw and that QuantLite strategies weigh into their confidence.
+//!
+//! Only crypto assets are covered; neither source tracks stocks or metals.
+
+use chrono::{DateTime, Utc};
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use crate::types::*;
+
+const FEAR_GREED_URL: &str = "https://api.alternative.me/fng/";
+const CRYPTOPANIC_BASE: &str = "https://cryptopanic.com/api/v1";
+/// The index moves once a day, so this only bounds how late a new one shows
+const FEAR_GREED_CACHE_SECS: i64 = 600;
+const NEWS_CACHE_SECS: i64 = 300;
+/// Most symbols whose news is kept in memory
+const MAX_CACHED_NEWS: usize = 200;
+/// Share of the combined score taken from news when there is some
+const NEWS_WEIGHT: f64 = 0.6;
+/// Score beyond which sentiment is labelled bullish or bearish
+const LEAN_THRESHOLD: f64 = 0.2;
+
+/// Direction the combined score leans
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SentimentLabel {
+    Bearish,
+    Neutral,
+    Bullish,
+}
+
+impl SentimentLabel {
+    pub fn from_score(score: f64) -> Self {
+        if score >= LEAN_THRESHOLD {
+            SentimentLabel::Bullish
+        } else if score <= -LEAN_THRESHOLD {
+            SentimentLabel::Bearish
+        } else {
+            SentimentLabel::Neutral
+        }
+    }
+}
+
+/// Today's Crypto Fear & Greed reading
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FearGreed {
+    /// 0 (extreme fear) to 100 (extreme greed)
+    pub value: u8,
+    /// alternative.me's label, e.g. `Extreme Fear`
+    pub classification: String,
+    pub timestamp: DateTime<Utc>,
+}
+
+impl FearGreed {
+    /// The index rescaled to -1..1
+    pub fn score(&self) -> f64 {
+        (self.value as f64 - 50.0) / 50.0
+    }
+}
+
+/// Votes on recent news posts about one asset
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct NewsSentiment {
+    pub posts: usize,
+    pub positive_votes: u64,
+    pub negative_votes: u64,
+    /// Net share of positive votes, -1..1 (0 when nobody voted)
+    pub score: f64,
+    pub fetched_at: DateTime<Utc>,
+}
+
+/// Response of `GET /sentiment/{symbol}`
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SentimentSummary {
+    pub symbol: String,
+    /// -1 (bearish) to 1 (bullish)
+    pub score: f64,
+    pub label: SentimentLabel,
+    pub fear_greed: Option<FearGreed>,
+    /// Absent without a CryptoPanic key
+    pub news: Option<NewsSentiment>,
+    pub timestamp: DateTime<Utc>,
+}
+
+impl SentimentSummary {
+    /// Combine whichever readings are available; None when neither is
+    pub fn combine(
+        symbol: &str,
+        fear_greed: Option<FearGreed>,
+        news: Option<NewsSentiment>,
+    ) -> Option<Self> {
+        let news_score = news.as_ref().filter(|n| n.posts > 0).map(|n| n.score);
+        let score = match (fear_greed.as_ref().map(FearGreed::score), news_score) {
+            (Some(index), Some(news)) => NEWS_WEIGHT * news + (1.0 - NEWS_WEIGHT) * index,
+            (Some(index), None) => index,
+            (None, Some(news)) => news,
+            (None, None) => return None,
+        };
+        Some(Self {
+            symbol: symbol.to_string(),
+            score,
+            label: SentimentLabel::from_score(score),
+            fear_greed,
+            news,
+            timestamp: Utc::now(),
+        })
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct FearGreedResponse {
+    data: Vec<FearGreedEntry>,
+}
+
+#[derive(Debug, Deserialize)]
+struct FearGreedEntry {
+    value: String,
+    value_classification: String,
+    timestamp: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct PostsResponse {
+    results: Vec<Post>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Post {
+    #[serde(default)]
+    votes: Votes,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct Votes {
+    #[serde(default)]
+    positive: u64,
+    #[serde(default)]
+    negative: u64,
+    #[serde(default)]
+    liked: u64,
+    #[serde(default)]
+    disliked: u64,
+}
+
+fn parse_fear_greed(raw: FearGreedResponse) -> Result<FearGreed> {
+    let entry = raw
+        .data
+        .into_iter()
+        .next()
+        .ok_or_else(|| DataRetrievalError::InvalidResponse("empty fear & greed".to_string()))?;
+    let value = entry.value.parse::<u8>().map_err(|_| {
+        DataRetrievalError::InvalidResponse(format!("bad fear & greed value: {:?}", entry.value))
+    })?;
+    let timestamp = entry
+        .timestamp
+        .parse::<i64>()
+        .ok()
+        .and_then(|secs| DateTime::from_timestamp(secs, 0))
+        .unwrap_or_else(Utc::now);
+    Ok(FearGreed {
+        value: value.min(100),
+        classification: entry.value_classification,
+        timestamp,
+    })
+}
+
+fn summarize_posts(raw: PostsResponse) -> NewsSentiment {
+    let (positive, negative) = raw.results.iter().fold((0, 0), |(pos, neg), post| {
+        (
+            pos + post.votes.positive + post.votes.liked,
+            neg + post.votes.negative + post.votes.disliked,
+        )
+    });
+    let total = positive + negative;
+    NewsSentiment {
+        posts: raw.results.len(),
+        positive_votes: positive,
+        negative_votes: negative,
+        score: if total == 0 {
+            0.0
+        } else {
+            (positive as f64 - negative as f64) / total as f64
+        },
+        fetched_at: Utc::now(),
+    }
+}
+
+/// Fear & Greed and (with a key) CryptoPanic news, cached
+pub struct SentimentClient {
+    client: Client,
+    fear_greed_url: String,
+    cryptopanic_base: String,
+    cryptopanic_key: Option<String>,
+    fear_greed: Mutex<Option<(DateTime<Utc>, FearGreed)>>,
+    news: Mutex<HashMap<String, NewsSentiment>>,
+}
+
+impl SentimentClient {
+    pub fn new(cryptopanic_key: Option<String>) -> Self {
+        Self::with_base_urls(FEAR_GREED_URL, CRYPTOPANIC_BASE, cryptopanic_key)
+    }
+
+    pub fn with_base_urls(
+        fear_greed_url: &str,
+        cryptopanic_base: &str,
+        cryptopanic_key: Option<String>,
+    ) -> Self {
+        let client = Client::builder()
+            .timeout(Duration::from_secs(5))
+            .build()
+            .expect("Failed to create HTTP client");
+        Self {
+            client,
+            fear_greed_url: fear_greed_url.to_string(),
+            cryptopanic_base: cryptopanic_base.trim_end_matches('/').to_string(),
+            cryptopanic_key,
+            fear_greed: Mutex::new(None),
+            news: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn has_news(&self) -> bool {
+        self.cryptopanic_key.is_some()
+    }
+
+    async fn get_json<T: serde::de::DeserializeOwned>(
+        &self,
+        source: &str,
+        request: reqwest::RequestBuilder,
+    ) -> Result<T> {
+        let response = request
+            .send()
+            .await
+            .map_err(|e| DataRetrievalError::ApiError(format!("{} failed: {}", source, e)))?;
+        match response.status() {
+            status if status.is_success() => {}
+            reqwest::StatusCode::TOO_MANY_REQUESTS => {
+                return Err(DataRetrievalError::RateLimit {
+                    source_name: source.to_string(),
+                    retry_after: None,
+                })
+            }
+            status => {
+                return Err(DataRetrievalError::ApiError(format!(
+                    "{} error: {}",
+                    source, status
+                )))
+            }
+        }
+        response
+            .json()
+            .await
+            .map_err(|e| DataRetrievalError::InvalidResponse(e.to_string()))
+    }
+
+    /// Today's index
+    pub async fn get_fear_greed(&self) -> Result<FearGreed> {
+        // The reading's own timestamp is the start of its day, so the cache
+        // goes by when it was fetched
+        if let Some((fetched_at, cached)) = self.fear_greed.lock().unwrap().as_ref() {
+            if (Utc::now() - *fetched_at).num_seconds() < FEAR_GREED_CACHE_SECS {
+                return Ok(cached.clone());
+            }
+        }
+
+        let raw = self
+            .get_json(
+                "fear_greed",
+                self.client
+                    .get(&self.fear_greed_url)
+                    .query(&[("limit", "1")]),
+            )
+            .await?;
+        let reading = parse_fear_greed(raw)?;
+        *self.fear_greed.lock().unwrap() = Some((Utc::now(), reading.clone()));
+        Ok(reading)
+    }
+
+    /// News votes for `base`; None without a CryptoPanic key
+    pub async fn get_news(&self, base: &str) -> Result<Option<NewsSentiment>> {
+        let Some(key) = &self.cryptopanic_key else {
+            return Ok(None);
+        };
+        let base = base.trim().to_uppercase();
+        if let Some(cached) = self.news.lock().unwrap().get(&base) {
+            if (Utc::now() - cached.fetched_at).num_seconds() < NEWS_CACHE_SECS {
+                return Ok(Some(cached.clone()));
+            }
+        }
+
+        let raw: PostsResponse = self
+            .get_json(
+                "cryptopanic",
+                self.client
+                    .get(format!("{}/posts/", self.cryptopanic_base))
+                    .query(&[
+                        ("auth_token", key.as_str()),
+                        ("currencies", base.as_str()),
+                        ("public", "true"),
+                    ]),
+            )
+            .await?;
+        let news = summarize_posts(raw);
+
+        let mut cache = self.news.lock().unwrap();
+        if cache.len() >= MAX_CACHED_NEWS {
+            cache.retain(|_, n| (Utc::now() - n.fetched_at).num_seconds() < NEWS_CACHE_SECS);
+        }
+        cache.insert(base, news.clone());
+        Ok(Some(news))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_fear_greed() {
+        let raw: FearGreedResponse = serde_json::from_str(
+            r#"{"name":"Fear and Greed Index","data":[{"value":"25","value_classification":"Extreme Fear",
+                "timestamp":"1773100800","time_until_update":"43200"}],"metadata":{"error":null}}"#,
+        )
+        .unwrap();
+        let reading = parse_fear_greed(raw).unwrap();
+        assert_eq!(reading.value, 25);
+        assert_eq!(reading.classification, "Extreme Fear");
+        assert!((reading.score() + 0.5).abs() < 1e-12);
+        assert_eq!(reading.timestamp.timestamp(), 1_773_100_800);
+    }
+
+    #[test]
+    fn test_news_votes_and_combined_score() {
+        let raw: PostsResponse = serde_json::from_str(
+            r#"{"count":3,"results":[
+                {"title":"a","votes":{"positive":6,"negative":1,"important":2,"liked":1,"disliked":0}},
+                {"title":"b","votes":{"positive":0,"negative":2}},
+                {"title":"c"}]}"#,
+        )
+        .unwrap();
+        let news = summarize_posts(raw);
+        assert_eq!(news.posts, 3);
+        assert_eq!((news.positive_votes, news.negative_votes), (7, 3));
+        assert!((news.score - 0.4).abs() < 1e-12);
+
+        let fear_greed = FearGreed {
+            value: 75,
+            classification: "Greed".to_string(),
+            timestamp: Utc::now(),
+        };
+        let summary =
+            SentimentSummary::combine("SOL", Some(fear_greed.clone()), Some(news)).unwrap();
+        // 0.6 * 0.4 + 0.4 * 0.5
+        assert!((summary.score - 0.44).abs() < 1e-12);
+        assert_eq!(summary.label, SentimentLabel::Bullish);
+
+        let index_only = SentimentSummary::combine("SOL", Some(fear_greed), None).unwrap();
+        assert!((index_only.score - 0.5).abs() < 1e-12);
+        assert!(SentimentSummary::combine("SOL", None, None).is_none());
+    }
+}
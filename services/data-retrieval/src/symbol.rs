@@ -0,0 +1,263 @@
+//! Canonical trading pair names
+//!
+//! The same pair turns up as `SOL`, `SOL/USD`, `SOL-USD`, `SOLUSDT`,
+//! `Crypto.SOL/USD` or a Solana mint address depending on who's asking.
+//! [`SymbolId`] parses any of those into an uppercase base and quote, and
+//! formats the pair the way each external system wants it, so lookups on
+//! both sides of a service boundary land on the same key.
+//!
+//! Mint addresses are case sensitive: a known mint becomes its symbol, an
+//! unknown one is kept as written.
+
+use std::fmt;
+
+/// Quote assumed when a symbol doesn't carry one
+pub const DEFAULT_QUOTE: &str = "USD";
+
+/// Solana mints for the tokens the platform trades: (symbol, mint, decimals)
+pub const KNOWN_MINTS: &[(&str, &str, u8)] = &[
+    ("SOL", "So11111111111111111111111111111111111111112", 9),
+    ("USDC", "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v", 6),
+    ("USDT", "Es9vMFrzaCERmJfrF4H2FYD4KCoNkY11McCe8BenwNYB", 6),
+    ("BTC", "qfnqNLS3x2K5R3oCmS1NjwiKOK8Tq77pCH6zTX8mR2F", 8),
+    ("WBTC", "3NZ9JMVBmGAqocybic2c7LQCJScmgsAZ6vQqTDzcqmJh", 8),
+    ("ETH", "7vfCXTUXx5WJV5JADk17DUJ4ksgau7utNKj4b963voxs", 8),
+    ("JUP", "JUPyiwrYJFskUPiHa7hkeR8VUtAeFoSYbKedZNsDvCN", 6),
+    ("BONK", "DezXAZ8z7PnrnRJjz3wXBoRgixCa6xjnB7YaB1pPB263", 5),
+    ("WIF", "EKpQGSJtjMFqKZ9KQbSqL2zPQCpA5xZKN2CjeJRdQpump", 6),
+];
+
+/// CoinGecko coin ids for common assets (others need a search)
+const COINGECKO_IDS: &[(&str, &str)] = &[
+    ("BTC", "bitcoin"),
+    ("ETH", "ethereum"),
+    ("SOL", "solana"),
+    ("USDC", "usd-coin"),
+    ("USDT", "tether"),
+    ("BNB", "binancecoin"),
+    ("XRP", "ripple"),
+    ("ADA", "cardano"),
+    ("DOGE", "dogecoin"),
+    ("MATIC", "matic-network"),
+    ("WBTC", "wrapped-bitcoin"),
+    ("JUP", "jupiter-exchange-solana"),
+    ("BONK", "bonk"),
+    ("WIF", "dogwifcoin"),
+];
+
+/// Quotes recognised at the end of a concatenated pair, longest first
+const CONCATENATED_QUOTES: &[&str] = &["USDT", "USDC", "BUSD", "USD", "EUR"];
+
+/// Hermes feed name prefixes
+const PYTH_PREFIXES: &[&str] = &["Crypto.", "Equity.", "FX.", "Metal."];
+
+/// Symbol and decimals for a known mint
+pub fn mint_info(mint: &str) -> Option<(&'static str, u8)> {
+    KNOWN_MINTS
+        .iter()
+        .find(|(_, m, _)| *m == mint)
+        .map(|(symbol, _, decimals)| (*symbol, *decimals))
+}
+
+/// Mint and decimals for a known symbol
+pub fn symbol_mint(symbol: &str) -> Option<(&'static str, u8)> {
+    KNOWN_MINTS
+        .iter()
+        .find(|(s, _, _)| s.eq_ignore_ascii_case(symbol))
+        .map(|(_, mint, decimals)| (*mint, *decimals))
+}
+
+/// Whether `s` has the shape of a Solana address (32-44 base58 characters)
+pub fn looks_like_mint(s: &str) -> bool {
+    (32..=44).contains(&s.len())
+        && s.chars()
+            .all(|c| c.is_ascii_alphanumeric() && !matches!(c, '0' | 'O' | 'I' | 'l'))
+}
+
+/// A base asset over a quote, e.g. SOL/USD
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SymbolId {
+    base: String,
+    quote: String,
+}
+
+impl SymbolId {
+    /// Pair from a base and quote, each in any accepted form
+    pub fn new(base: &str, quote: &str) -> Self {
+        Self {
+            base: canonical_asset(base),
+            quote: canonical_asset(quote),
+        }
+    }
+
+    /// Parse any accepted form, quoted in [`DEFAULT_QUOTE`] unless it says
+    /// otherwise
+    pub fn parse(input: &str) -> Self {
+        Self::with_default_quote(input, DEFAULT_QUOTE)
+    }
+
+    /// Parse any accepted form, quoted in `quote` unless it says otherwise
+    pub fn with_default_quote(input: &str, quote: &str) -> Self {
+        let (base, parsed_quote) = split_pair(input.trim());
+        Self::new(base, parsed_quote.unwrap_or(quote))
+    }
+
+    /// Parse a Binance pair (`SOLUSDT`); None when no quote is recognised
+    pub fn from_binance(pair: &str) -> Option<Self> {
+        let pair = pair.trim().to_uppercase();
+        CONCATENATED_QUOTES.iter().find_map(|quote| {
+            let base = pair.strip_suffix(quote)?;
+            (!base.is_empty()).then(|| Self::new(base, quote))
+        })
+    }
+
+    pub fn base(&self) -> &str {
+        &self.base
+    }
+
+    pub fn quote(&self) -> &str {
+        &self.quote
+    }
+
+    /// Binance pair name; Binance has no USD books, so USD maps to USDT
+    pub fn binance(&self) -> String {
+        let quote = if self.quote == "USD" {
+            "USDT"
+        } else {
+            &self.quote
+        };
+        format!("{}{}", self.base, quote)
+    }
+
+    /// CoinGecko coin id, for assets in the built-in table
+    pub fn coingecko_id(&self) -> Option<&'static str> {
+        coingecko_id(&self.base)
+    }
+
+    /// CoinGecko `vs_currency`
+    pub fn coingecko_vs(&self) -> String {
+        self.quote.to_lowercase()
+    }
+
+    /// Pyth Hermes crypto feed name, e.g. `Crypto.SOL/USD`
+    pub fn pyth(&self) -> String {
+        format!("Crypto.{}/{}", self.base, self.quote)
+    }
+
+    /// Solana mint of the base asset, if known
+    pub fn mint(&self) -> Option<&'static str> {
+        symbol_mint(&self.base).map(|(mint, _)| mint)
+    }
+}
+
+impl fmt::Display for SymbolId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.base, self.quote)
+    }
+}
+
+/// CoinGecko coin id for a symbol in the built-in table
+pub fn coingecko_id(symbol: &str) -> Option<&'static str> {
+    COINGECKO_IDS
+        .iter()
+        .find(|(s, _)| s.eq_ignore_ascii_case(symbol))
+        .map(|(_, id)| *id)
+}
+
+/// Uppercase symbol, or the symbol for a known mint (unknown mints as written)
+fn canonical_asset(asset: &str) -> String {
+    let asset = asset.trim();
+    if looks_like_mint(asset) {
+        return mint_info(asset)
+            .map(|(symbol, _)| symbol.to_string())
+            .unwrap_or_else(|| asset.to_string());
+    }
+    asset.to_uppercase()
+}
+
+/// Split into base and (if present) quote
+fn split_pair(input: &str) -> (&str, Option<&str>) {
+    if looks_like_mint(input) {
+        return (input, None);
+    }
+    let input = PYTH_PREFIXES
+        .iter()
+        .find_map(|prefix| input.strip_prefix(prefix))
+        .unwrap_or(input);
+    if let Some((base, quote)) = input.split_once(['/', '-', '_', ':']) {
+        return (base, Some(quote));
+    }
+    // Run together (`SOLUSDT`): only split off a quote when what's left is
+    // an asset we know, so `BUSD` or a stock ticker isn't cut up
+    if !input.is_ascii() {
+        return (input, None);
+    }
+    let upper = input.to_ascii_uppercase();
+    for quote in CONCATENATED_QUOTES {
+        if let Some(base) = upper.strip_suffix(quote) {
+            if coingecko_id(base).is_some() || symbol_mint(base).is_some() {
+                return (&input[..base.len()], Some(&input[base.len()..]));
+            }
+        }
+    }
+    (input, None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SOL: &str = "So11111111111111111111111111111111111111112";
+
+    #[test]
+    fn test_parse_any_form() {
+        let sol_usd = SymbolId::new("SOL", "USD");
+        for input in [
+            "SOL",
+            "sol",
+            "SOL/USD",
+            "sol-usd",
+            "SOL_USD",
+            "Crypto.SOL/USD",
+            SOL,
+        ] {
+            assert_eq!(SymbolId::parse(input), sol_usd, "{}", input);
+        }
+        assert_eq!(SymbolId::parse("SOLUSDT"), SymbolId::new("SOL", "USDT"));
+        assert_eq!(
+            SymbolId::with_default_quote("btc", "eur"),
+            SymbolId::new("BTC", "EUR")
+        );
+        // A quote in the symbol wins over the default
+        assert_eq!(
+            SymbolId::with_default_quote("ETH/USDC", "USD"),
+            SymbolId::new("ETH", "USDC")
+        );
+        assert_eq!(SymbolId::new(SOL, "usdc").to_string(), "SOL/USDC");
+
+        // Not cut up: unknown bases, stablecoins, unknown mints
+        assert_eq!(SymbolId::parse("BUSD").base(), "BUSD");
+        assert_eq!(SymbolId::parse("AAPL").base(), "AAPL");
+        let unknown = "7xKXtg2CW87d97TXJSDpbD5jBkheTqA83TZRuJosgAsU";
+        assert_eq!(SymbolId::parse(unknown).base(), unknown);
+    }
+
+    #[test]
+    fn test_external_formats() {
+        let sol = SymbolId::parse("sol");
+        assert_eq!(sol.binance(), "SOLUSDT");
+        assert_eq!(SymbolId::parse("BTC/USDC").binance(), "BTCUSDC");
+        assert_eq!(sol.coingecko_id(), Some("solana"));
+        assert_eq!(sol.coingecko_vs(), "usd");
+        assert_eq!(sol.pyth(), "Crypto.SOL/USD");
+        assert_eq!(sol.mint(), Some(SOL));
+        assert_eq!(mint_info(SOL), Some(("SOL", 9)));
+
+        assert_eq!(
+            SymbolId::from_binance("ETHUSDT"),
+            Some(SymbolId::new("ETH", "USDT"))
+        );
+        assert_eq!(SymbolId::from_binance("USDT"), None);
+        assert_eq!(SymbolId::from_binance("ETHBTC"), None);
+    }
+}
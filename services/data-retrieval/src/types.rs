@@ -2,6 +2,8 @@ use chrono::{DateTime, Utc};
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 
+pub use crate::units::PriceUnit;
+
 /// Universal price data point from any source
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PricePoint {
@@ -10,6 +12,15 @@ pub struct PricePoint {
     pub source: String, // "coingecko", "binance", "pyth"
     pub timestamp: DateTime<Utc>,
     pub confidence: Option<f64>, // 0.0 - 1.0 based on source quality, None if unknown
+    /// Served from cache past its freshness window while a refresh runs
+    #[serde(default)]
+    pub stale: bool,
+    /// Aggregated from sources that disagree by more than the spread limit
+    #[serde(default)]
+    pub unreliable: bool,
+    /// Quoting unit for metals (per troy ounce, per gram); None for everything else
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub unit: Option<PriceUnit>,
 }
 
 impl PricePoint {
@@ -27,9 +38,18 @@ impl PricePoint {
             source: source.into(),
             timestamp,
             confidence,
+            stale: false,
+            unreliable: false,
+            unit: None,
         }
     }
 
+    /// Tag the quoting unit
+    pub fn with_unit(mut self, unit: PriceUnit) -> Self {
+        self.unit = Some(unit);
+        self
+    }
+
     /// Get asset part of symbol (e.g., "BTC" from "BTC/USD")
     pub fn asset(&self) -> String {
         self.symbol
@@ -59,6 +79,31 @@ pub struct Candle {
     pub timestamp: DateTime<Utc>,
 }
 
+/// Open/high/low/close for the current UTC day
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DailyOhlc {
+    pub open: Decimal,
+    pub high: Decimal,
+    pub low: Decimal,
+    /// Latest price
+    pub close: Decimal,
+}
+
+/// Recent "market shape" around a spot price, built from hourly candles
+///
+/// Changes are percentages against the price at the start of each window;
+/// `None` where the candles don't reach back far enough.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct MarketSummary {
+    pub change_1h_pct: Option<f64>,
+    pub change_24h_pct: Option<f64>,
+    pub change_7d_pct: Option<f64>,
+    pub ohlc_today: Option<DailyOhlc>,
+    /// Trending, ranging or high volatility, from the same candles
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub regime: Option<crate::regime::MarketRegime>,
+}
+
 /// Supported timeframes
 #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 pub enum TimeFrame {
@@ -86,6 +131,22 @@ impl TimeFrame {
         }
     }
 
+    /// Inverse of [`TimeFrame::as_str`]
+    pub fn parse(value: &str) -> Option<Self> {
+        [
+            TimeFrame::Minute1,
+            TimeFrame::Minute5,
+            TimeFrame::Minute15,
+            TimeFrame::Minute30,
+            TimeFrame::Hour1,
+            TimeFrame::Hour4,
+            TimeFrame::Day1,
+            TimeFrame::Week1,
+        ]
+        .into_iter()
+        .find(|tf| tf.as_str() == value.trim())
+    }
+
     pub fn to_seconds(&self) -> i64 {
         match self {
             TimeFrame::Minute1 => 60,
@@ -142,6 +203,18 @@ pub struct AggregatedPrice {
     pub timestamp: DateTime<Utc>,
     pub confidence: f64,
     pub spread_percent: f64, // Max price - min price as % of avg
+    /// Served from cache past its freshness window while a refresh runs
+    #[serde(default)]
+    pub stale: bool,
+    /// Sources disagree by more than the aggregator's spread limit
+    #[serde(default)]
+    pub unreliable: bool,
+    /// Fetched in USD and converted into `quote` (see [`crate::fx`])
+    #[serde(default)]
+    pub converted: bool,
+    /// Units of `quote` per USD used for the conversion
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fx_rate: Option<Decimal>,
 }
 
 /// Individual source contribution to aggregated price
@@ -162,10 +235,13 @@ pub struct SourceHealth {
     pub last_error: Option<String>,
     pub success_rate_24h: f64,
     pub avg_latency_ms: u64,
+    /// Circuit breaker state, when the source sits behind an aggregator
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub breaker: Option<crate::breaker::BreakerState>,
 }
 
 /// Error types for data retrieval
-#[derive(Debug, thiserror::Error)]
+#[derive(Debug, Clone, thiserror::Error)]
 pub enum DataRetrievalError {
     #[error("API request failed: {0}")]
     ApiError(String),
@@ -192,6 +268,40 @@ pub enum DataRetrievalError {
 /// Result type for data retrieval operations
 pub type Result<T> = std::result::Result<T, DataRetrievalError>;
 
+/// Streaming source that pushes price updates (WebSocket, SSE)
+///
+/// The aggregator's realtime consumer drains `next_price` into its latest
+/// price map and calls `reconnect` whenever `is_connected` turns false.
+#[async_trait::async_trait]
+pub trait RealtimePriceSource: Send + Sync {
+    /// Source name (for health reporting)
+    fn name(&self) -> &str;
+
+    /// Next price update; `None` if the channel has closed
+    async fn next_price(&self) -> Option<PricePoint>;
+
+    /// Whether the stream is up and delivering data
+    async fn is_connected(&self) -> bool;
+
+    /// Re-establish the stream and its subscriptions
+    async fn reconnect(&self) -> Result<()>;
+}
+
+/// A stretch during which a streaming source delivered nothing
+///
+/// Published by the realtime consumer once the source is back, so anything
+/// built from the stream (bars, signals) knows it missed data in between.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StreamGap {
+    pub source: String,
+    /// When the stream was found down or stalled
+    pub started_at: DateTime<Utc>,
+    /// When it was re-established
+    pub ended_at: DateTime<Utc>,
+    /// Reconnects tried before one succeeded
+    pub attempts: u32,
+}
+
 /// Trait for price data sources
 #[async_trait::async_trait]
 pub trait PriceDataSource: Send + Sync {
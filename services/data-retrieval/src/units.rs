@@ -0,0 +1,151 @@
+//! Quoting units for metals and the FX pairs we route to Pyth
+//!
+//! Spot metal prices are quoted per troy ounce. Some tokenized metals are
+//! quoted per gram instead; those are listed here with the ounce-quoted
+//! underlying they are derived from, so every metal price carries an
+//! explicit unit.
+
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+/// Grams in one troy ounce (exact by definition)
+pub const GRAMS_PER_TROY_OUNCE: Decimal = Decimal::from_parts(311034768, 0, 0, false, 7);
+
+/// Mass unit a metal price is quoted in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PriceUnit {
+    TroyOunce,
+    Gram,
+    Kilogram,
+}
+
+impl PriceUnit {
+    /// Mass of one unit in grams
+    pub fn grams(&self) -> Decimal {
+        match self {
+            PriceUnit::TroyOunce => GRAMS_PER_TROY_OUNCE,
+            PriceUnit::Gram => Decimal::ONE,
+            PriceUnit::Kilogram => Decimal::from(1000),
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PriceUnit::TroyOunce => "troy_ounce",
+            PriceUnit::Gram => "gram",
+            PriceUnit::Kilogram => "kilogram",
+        }
+    }
+}
+
+/// Convert a price per `from` unit into a price per `to` unit
+pub fn convert_unit_price(price: Decimal, from: PriceUnit, to: PriceUnit) -> Decimal {
+    if from == to {
+        return price;
+    }
+    price * to.grams() / from.grams()
+}
+
+/// A metal symbol and how it is priced
+#[derive(Debug, Clone, Copy)]
+pub struct MetalSpec {
+    pub symbol: &'static str,
+    pub name: &'static str,
+    /// Spot metal the price comes from (self for the spot symbols)
+    pub underlying: &'static str,
+    pub unit: PriceUnit,
+}
+
+impl MetalSpec {
+    /// Whether this symbol is derived from another metal's spot price
+    pub fn is_derived(&self) -> bool {
+        self.symbol != self.underlying
+    }
+}
+
+/// Supported metals; spot symbols are per troy ounce
+pub const METALS: &[MetalSpec] = &[
+    MetalSpec {
+        symbol: "XAU",
+        name: "Gold",
+        underlying: "XAU",
+        unit: PriceUnit::TroyOunce,
+    },
+    MetalSpec {
+        symbol: "XAG",
+        name: "Silver",
+        underlying: "XAG",
+        unit: PriceUnit::TroyOunce,
+    },
+    MetalSpec {
+        symbol: "XPT",
+        name: "Platinum",
+        underlying: "XPT",
+        unit: PriceUnit::TroyOunce,
+    },
+    MetalSpec {
+        symbol: "XPD",
+        name: "Palladium",
+        underlying: "XPD",
+        unit: PriceUnit::TroyOunce,
+    },
+    // Tokenized gold quoted per gram
+    MetalSpec {
+        symbol: "ORO",
+        name: "Gold (per gram)",
+        underlying: "XAU",
+        unit: PriceUnit::Gram,
+    },
+];
+
+/// FX pairs, written without a separator (`EURUSD` = USD per EUR)
+pub const FX_PAIRS: &[&str] = &[
+    "EURUSD", "GBPUSD", "USDJPY", "AUDUSD", "USDCAD", "USDCHF", "USDBRL", "USDMXN",
+];
+
+pub fn metal_spec(symbol: &str) -> Option<&'static MetalSpec> {
+    METALS
+        .iter()
+        .find(|m| m.symbol.eq_ignore_ascii_case(symbol))
+}
+
+pub fn is_fx_pair(symbol: &str) -> bool {
+    FX_PAIRS.iter().any(|p| p.eq_ignore_ascii_case(symbol))
+}
+
+/// Split an FX pair into (base, quote), e.g. `USDJPY` -> (`USD`, `JPY`)
+pub fn fx_legs(symbol: &str) -> Option<(&str, &str)> {
+    (symbol.len() == 6 && is_fx_pair(symbol)).then(|| symbol.split_at(3))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ounce_gram_conversion() {
+        let per_ounce = Decimal::from(2000);
+        let per_gram = convert_unit_price(per_ounce, PriceUnit::TroyOunce, PriceUnit::Gram);
+        assert_eq!(per_gram.round_dp(4), Decimal::new(643015, 4));
+
+        let back = convert_unit_price(per_gram, PriceUnit::Gram, PriceUnit::TroyOunce);
+        assert_eq!(back.round_dp(8), per_ounce);
+        assert_eq!(
+            convert_unit_price(Decimal::ONE, PriceUnit::Gram, PriceUnit::Kilogram),
+            Decimal::from(1000)
+        );
+    }
+
+    #[test]
+    fn test_metal_and_fx_lookup() {
+        let oro = metal_spec("oro").unwrap();
+        assert!(oro.is_derived());
+        assert_eq!(oro.underlying, "XAU");
+        assert_eq!(oro.unit, PriceUnit::Gram);
+        assert!(!metal_spec("XPT").unwrap().is_derived());
+
+        assert_eq!(fx_legs("USDJPY"), Some(("USD", "JPY")));
+        assert_eq!(fx_legs("BTCUSD"), None);
+    }
+}
@@ -0,0 +1,494 @@
+//! HTTP client for the app-facing `/v1` routes
+
+use chrono::{DateTime, SecondsFormat, Utc};
+use reqwest::header::{HeaderMap, AUTHORIZATION, CONTENT_TYPE, RETRY_AFTER};
+use reqwest::{Method, Response, StatusCode};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+use uuid::Uuid;
+
+use crate::error::{ClientError, Result};
+use crate::types::{
+    ArtifactDownload, ArtifactsResponse, AssetPerformanceResponse, BacktestRequest,
+    BacktestResponse, Bot, BotAction, BotActionRequest, BotConfigInput, BotResponse, ConfigVersion,
+    CreateBotRequest, EventsQuery, EventsResponse, KillSwitchRequest, KillSwitchResponse,
+    ListBotsResponse, MetricsRange, MetricsResponse, PerformanceResponse, RollupPeriod,
+    TradesResponse, UpdateBotConfigRequest, User, WhatIfQuery, WhatIfResponse,
+};
+
+/// When and how long to retry
+///
+/// GETs are retried on network errors, 5xx and 429. Anything else is only
+/// retried on 429, which the control plane answers before running the
+/// handler, so a retried POST can't create a bot twice.
+#[derive(Debug, Clone, Copy)]
+pub struct RetryPolicy {
+    pub max_retries: u32,
+    /// First backoff; doubles on each retry
+    pub base_delay: Duration,
+    /// Cap on any single wait, including `Retry-After`
+    pub max_delay: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 3,
+            base_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(60),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Wait before retry number `attempt` (1-based)
+    fn delay(&self, attempt: u32, retry_after: Option<Duration>) -> Duration {
+        let backoff = self
+            .base_delay
+            .saturating_mul(1u32 << (attempt - 1).min(16));
+        retry_after.unwrap_or(backoff).min(self.max_delay)
+    }
+}
+
+/// Typed client for the control plane
+///
+/// Cheap to clone; clones share the bearer token, so `set_token` on one
+/// refreshes all of them.
+#[derive(Clone)]
+pub struct TrawlingClient {
+    http: reqwest::Client,
+    base_url: String,
+    token: Arc<RwLock<String>>,
+    retry: RetryPolicy,
+}
+
+impl TrawlingClient {
+    /// Client for `base_url` (e.g. `https://api.trawlingtraders.com`) using a
+    /// Cedros session token
+    pub fn new(base_url: &str, token: impl Into<String>) -> Result<Self> {
+        let http = reqwest::Client::builder()
+            .timeout(Duration::from_secs(30))
+            .build()?;
+        Ok(Self {
+            http,
+            base_url: base_url.trim_end_matches('/').to_string(),
+            token: Arc::new(RwLock::new(token.into())),
+            retry: RetryPolicy::default(),
+        })
+    }
+
+    pub fn with_retry_policy(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// Replace the bearer token (after a session refresh)
+    pub fn set_token(&self, token: impl Into<String>) {
+        *self.token.write().unwrap() = token.into();
+    }
+
+    /// GET /v1/me
+    pub async fn me(&self) -> Result<User> {
+        self.get("/me").await
+    }
+
+    /// GET /v1/bots
+    pub async fn list_bots(&self) -> Result<ListBotsResponse> {
+        self.get("/bots").await
+    }
+
+    /// GET /v1/bots/:id - bot with its current config
+    pub async fn get_bot(&self, bot_id: Uuid) -> Result<BotResponse> {
+        self.get(&format!("/bots/{}", bot_id)).await
+    }
+
+    /// POST /v1/bots
+    pub async fn create_bot(&self, req: &CreateBotRequest) -> Result<Bot> {
+        let response = self.send(Method::POST, "/bots", Some(req)).await?;
+        decode(response).await
+    }
+
+    /// PATCH /v1/bots/:id/config - saves a new config version for the runner
+    pub async fn update_config(
+        &self,
+        bot_id: Uuid,
+        config: &BotConfigInput,
+    ) -> Result<ConfigVersion> {
+        let response = self
+            .send(
+                Method::PATCH,
+                &format!("/bots/{}/config", bot_id),
+                Some(&UpdateBotConfigRequest { config }),
+            )
+            .await?;
+        decode(response).await
+    }
+
+    /// POST /v1/bots/:id/actions
+    pub async fn action(&self, bot_id: Uuid, action: BotAction) -> Result<()> {
+        self.send(
+            Method::POST,
+            &format!("/bots/{}/actions", bot_id),
+            Some(&BotActionRequest { action }),
+        )
+        .await?;
+        Ok(())
+    }
+
+    /// GET /v1/bots/:id/metrics - the last 7 days
+    pub async fn metrics(&self, bot_id: Uuid) -> Result<MetricsResponse> {
+        self.get(&format!("/bots/{}/metrics", bot_id)).await
+    }
+
+    /// GET /v1/bots/:id/metrics over `range`
+    ///
+    /// `resolution` is `raw` or a bucket width (`5m`, `15m`, `1h`, `4h`,
+    /// `1d`); `None` lets the server pick one that fits the range.
+    pub async fn metrics_range(
+        &self,
+        bot_id: Uuid,
+        range: MetricsRange,
+        resolution: Option<&str>,
+    ) -> Result<MetricsResponse> {
+        let path = match resolution {
+            Some(resolution) => format!(
+                "/bots/{}/metrics?range={}&resolution={}",
+                bot_id,
+                range.as_str(),
+                resolution
+            ),
+            None => format!("/bots/{}/metrics?range={}", bot_id, range.as_str()),
+        };
+        self.get(&path).await
+    }
+
+    /// GET /v1/bots/:id/events - the newest 100 events
+    pub async fn events(&self, bot_id: Uuid) -> Result<EventsResponse> {
+        self.events_page(bot_id, &EventsQuery::default()).await
+    }
+
+    /// GET /v1/bots/:id/events - one filtered page, newest first
+    pub async fn events_page(&self, bot_id: Uuid, query: &EventsQuery) -> Result<EventsResponse> {
+        self.get(&events_path(bot_id, query)).await
+    }
+
+    /// GET /v1/bots/:id/trades - one page of trade history, newest first
+    ///
+    /// `cursor` is the previous page's `next_cursor`.
+    pub async fn trades(&self, bot_id: Uuid, cursor: Option<&str>) -> Result<TradesResponse> {
+        let path = match cursor {
+            // Cursors are URL-safe base64
+            Some(cursor) => format!("/bots/{}/trades?cursor={}", bot_id, cursor),
+            None => format!("/bots/{}/trades", bot_id),
+        };
+        self.get(&path).await
+    }
+
+    /// GET /v1/bots/:id/performance - daily or weekly rollups over the last `days`
+    ///
+    /// `days` is 1-366; `None` uses the server's default of 30.
+    pub async fn performance(
+        &self,
+        bot_id: Uuid,
+        period: RollupPeriod,
+        days: Option<u32>,
+    ) -> Result<PerformanceResponse> {
+        let path = match days {
+            Some(days) => format!(
+                "/bots/{}/performance?period={}&days={}",
+                bot_id,
+                period.as_str(),
+                days
+            ),
+            None => format!("/bots/{}/performance?period={}", bot_id, period.as_str()),
+        };
+        self.get(&path).await
+    }
+
+    /// GET /v1/bots/:id/performance/by-asset - PnL, win rate and fees per mint
+    ///
+    /// `days` limits the ledger to the last 1-365 days; `None` uses all of it.
+    pub async fn performance_by_asset(
+        &self,
+        bot_id: Uuid,
+        days: Option<u32>,
+    ) -> Result<AssetPerformanceResponse> {
+        let path = match days {
+            Some(days) => format!("/bots/{}/performance/by-asset?days={}", bot_id, days),
+            None => format!("/bots/{}/performance/by-asset", bot_id),
+        };
+        self.get(&path).await
+    }
+
+    /// GET /v1/bots/:id/what-if - the bot's recent trades replayed under other caps
+    pub async fn what_if(&self, bot_id: Uuid, query: &WhatIfQuery) -> Result<WhatIfResponse> {
+        let params = [
+            ("days", query.days.map(|v| v as i64)),
+            (
+                "max_position_size_percent",
+                query.max_position_size_percent.map(i64::from),
+            ),
+            (
+                "max_daily_loss_usd",
+                query.max_daily_loss_usd.map(i64::from),
+            ),
+            (
+                "max_trades_per_day",
+                query.max_trades_per_day.map(i64::from),
+            ),
+        ]
+        .iter()
+        .filter_map(|(key, value)| value.map(|v| format!("{}={}", key, v)))
+        .collect::<Vec<_>>()
+        .join("&");
+        let path = if params.is_empty() {
+            format!("/bots/{}/what-if", bot_id)
+        } else {
+            format!("/bots/{}/what-if?{}", bot_id, params)
+        };
+        self.get(&path).await
+    }
+
+    /// POST /v1/backtest - run a strategy config over historical candles
+    pub async fn backtest(&self, req: &BacktestRequest) -> Result<BacktestResponse> {
+        let response = self.send(Method::POST, "/backtest", Some(req)).await?;
+        decode(response).await
+    }
+
+    /// GET /v1/bots/:id/artifacts - exports, diagnostics and snapshots not yet expired
+    pub async fn artifacts(&self, bot_id: Uuid) -> Result<ArtifactsResponse> {
+        self.get(&format!("/bots/{}/artifacts", bot_id)).await
+    }
+
+    /// GET /v1/bots/:id/artifacts/:artifact_id/download - short-lived download URL
+    pub async fn artifact_download(
+        &self,
+        bot_id: Uuid,
+        artifact_id: Uuid,
+    ) -> Result<ArtifactDownload> {
+        self.get(&format!(
+            "/bots/{}/artifacts/{}/download",
+            bot_id, artifact_id
+        ))
+        .await
+    }
+
+    /// POST /v1/bots/:id/journal/export - write the decision journal out as JSONL
+    pub async fn export_journal(&self, bot_id: Uuid) -> Result<ArtifactDownload> {
+        let response = self
+            .send::<()>(
+                Method::POST,
+                &format!("/bots/{}/journal/export", bot_id),
+                None,
+            )
+            .await?;
+        decode(response).await
+    }
+
+    /// POST /v1/me/kill-switch - pause every bot, optionally selling all positions
+    ///
+    /// The session must have re-authenticated in the last few minutes,
+    /// otherwise this fails with a 403.
+    pub async fn engage_kill_switch(
+        &self,
+        flatten: bool,
+        reason: Option<&str>,
+    ) -> Result<KillSwitchResponse> {
+        let response = self
+            .send(
+                Method::POST,
+                "/me/kill-switch",
+                Some(&KillSwitchRequest { flatten, reason }),
+            )
+            .await?;
+        decode(response).await
+    }
+
+    /// DELETE /v1/me/kill-switch - bots stay paused until resumed
+    pub async fn release_kill_switch(&self) -> Result<KillSwitchResponse> {
+        let response = self
+            .send::<()>(Method::DELETE, "/me/kill-switch", None)
+            .await?;
+        decode(response).await
+    }
+
+    async fn get<T: DeserializeOwned>(&self, path: &str) -> Result<T> {
+        let response = self.send::<()>(Method::GET, path, None).await?;
+        decode(response).await
+    }
+
+    /// Send with auth and retries; non-success statuses become errors
+    async fn send<B: Serialize>(
+        &self,
+        method: Method,
+        path: &str,
+        body: Option<&B>,
+    ) -> Result<Response> {
+        let url = format!("{}/v1{}", self.base_url, path);
+        let body = body.map(serde_json::to_vec).transpose()?;
+        let idempotent = method == Method::GET;
+
+        let mut attempt = 0;
+        loop {
+            let token = self.token.read().unwrap().clone();
+            let mut request = self
+                .http
+                .request(method.clone(), &url)
+                .header(AUTHORIZATION, format!("Bearer {}", token));
+            if let Some(body) = &body {
+                request = request
+                    .header(CONTENT_TYPE, "application/json")
+                    .body(body.clone());
+            }
+
+            let (error, retry_after) = match request.send().await {
+                Ok(response) if response.status().is_success() => return Ok(response),
+                Ok(response) => {
+                    let status = response.status();
+                    let retry_after = retry_after(response.headers());
+                    let message = response.text().await.unwrap_or_default();
+                    (status_error(status, message, retry_after), retry_after)
+                }
+                Err(e) => (ClientError::Http(e), None),
+            };
+
+            attempt += 1;
+            if attempt > self.retry.max_retries || !should_retry(&error, idempotent) {
+                return Err(error);
+            }
+            tokio::time::sleep(self.retry.delay(attempt, retry_after)).await;
+        }
+    }
+}
+
+async fn decode<T: DeserializeOwned>(response: Response) -> Result<T> {
+    let body = response.bytes().await?;
+    Ok(serde_json::from_slice(&body)?)
+}
+
+fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+    let secs: u64 = headers
+        .get(RETRY_AFTER)?
+        .to_str()
+        .ok()?
+        .trim()
+        .parse()
+        .ok()?;
+    Some(Duration::from_secs(secs))
+}
+
+fn status_error(status: StatusCode, message: String, retry_after: Option<Duration>) -> ClientError {
+    match status {
+        StatusCode::UNAUTHORIZED => ClientError::Unauthorized,
+        StatusCode::TOO_MANY_REQUESTS => ClientError::RateLimited { retry_after },
+        _ => ClientError::Api {
+            status: status.as_u16(),
+            message,
+        },
+    }
+}
+
+fn should_retry(error: &ClientError, idempotent: bool) -> bool {
+    match error {
+        ClientError::RateLimited { .. } => true,
+        ClientError::Http(_) => idempotent,
+        ClientError::Api { status, .. } => idempotent && *status >= 500,
+        ClientError::Unauthorized | ClientError::Decode(_) => false,
+    }
+}
+
+fn events_path(bot_id: Uuid, query: &EventsQuery) -> String {
+    // Cursors are URL-safe base64 and event types snake_case, and UTC
+    // timestamps end in `Z`, so nothing here needs escaping
+    let timestamp = |t: DateTime<Utc>| t.to_rfc3339_opts(SecondsFormat::AutoSi, true);
+    let params = [
+        ("cursor", query.cursor.clone()),
+        (
+            "event_type",
+            Some(query.event_types.join(",")).filter(|t| !t.is_empty()),
+        ),
+        ("severity", query.severity.clone()),
+        ("since", query.since.map(timestamp)),
+        ("until", query.until.map(timestamp)),
+        ("limit", query.limit.map(|l| l.to_string())),
+    ]
+    .into_iter()
+    .filter_map(|(key, value)| value.map(|v| format!("{}={}", key, v)))
+    .collect::<Vec<_>>()
+    .join("&");
+    if params.is_empty() {
+        format!("/bots/{}/events", bot_id)
+    } else {
+        format!("/bots/{}/events?{}", bot_id, params)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_retry_decisions() {
+        let server_error = status_error(StatusCode::BAD_GATEWAY, "upstream".into(), None);
+        assert!(should_retry(&server_error, true));
+        // A POST that reached the handler may have taken effect
+        assert!(!should_retry(&server_error, false));
+
+        let throttled = status_error(
+            StatusCode::TOO_MANY_REQUESTS,
+            String::new(),
+            Some(Duration::from_secs(5)),
+        );
+        assert!(should_retry(&throttled, false));
+        assert_eq!(throttled.status(), Some(429));
+
+        let not_found = status_error(StatusCode::NOT_FOUND, "Bot not found".into(), None);
+        assert!(!should_retry(&not_found, true));
+        assert!(matches!(
+            status_error(StatusCode::UNAUTHORIZED, String::new(), None),
+            ClientError::Unauthorized
+        ));
+    }
+
+    #[test]
+    fn test_retry_delay() {
+        let policy = RetryPolicy::default();
+        assert_eq!(policy.delay(1, None), Duration::from_millis(500));
+        assert_eq!(policy.delay(3, None), Duration::from_secs(2));
+        assert_eq!(
+            policy.delay(1, Some(Duration::from_secs(7))),
+            Duration::from_secs(7)
+        );
+        assert_eq!(
+            policy.delay(1, Some(Duration::from_secs(3600))),
+            policy.max_delay
+        );
+    }
+
+    #[test]
+    fn test_events_path() {
+        let bot_id = Uuid::nil();
+        assert_eq!(
+            events_path(bot_id, &EventsQuery::default()),
+            format!("/bots/{}/events", bot_id)
+        );
+        let query = EventsQuery {
+            cursor: Some("MTc3MzE0NDAwMDAwMDAwMHw".to_string()),
+            event_types: vec!["trade_opened".to_string(), "trade_closed".to_string()],
+            severity: None,
+            since: DateTime::from_timestamp(1_773_144_000, 0),
+            until: None,
+            limit: Some(50),
+        };
+        assert_eq!(
+            events_path(bot_id, &query),
+            format!(
+                "/bots/{}/events?cursor=MTc3MzE0NDAwMDAwMDAwMHw&event_type=trade_opened,trade_closed\
+                 &since=2026-03-10T12:00:00Z&limit=50",
+                bot_id
+            )
+        );
+    }
+}
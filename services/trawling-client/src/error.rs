@@ -0,0 +1,40 @@
+use std::time::Duration;
+
+/// Errors returned by [`crate::TrawlingClient`]
+#[derive(Debug, thiserror::Error)]
+pub enum ClientError {
+    /// Connection, timeout or body read failure
+    #[error("request failed: {0}")]
+    Http(#[from] reqwest::Error),
+
+    /// Missing, expired or rejected bearer token
+    #[error("unauthorized")]
+    Unauthorized,
+
+    /// Still throttled after the last retry
+    #[error("rate limited (retry after {retry_after:?})")]
+    RateLimited { retry_after: Option<Duration> },
+
+    /// Any other non-success status; `message` is the response body
+    #[error("API error {status}: {message}")]
+    Api { status: u16, message: String },
+
+    /// The response body didn't match the expected type
+    #[error("unexpected response body: {0}")]
+    Decode(#[from] serde_json::Error),
+}
+
+impl ClientError {
+    /// HTTP status for API errors, if there was a response
+    pub fn status(&self) -> Option<u16> {
+        match self {
+            ClientError::Unauthorized => Some(401),
+            ClientError::RateLimited { .. } => Some(429),
+            ClientError::Api { status, .. } => Some(*status),
+            ClientError::Http(e) => e.status().map(|s| s.as_u16()),
+            ClientError::Decode(_) => None,
+        }
+    }
+}
+
+pub type Result<T> = std::result::Result<T, ClientError>;
@@ -0,0 +1,26 @@
+//! Typed client for the Trawling Traders control-plane API
+//!
+//! Covers the app-facing routes for bots, configs, metrics, events and
+//! lifecycle actions, with bearer auth and retries that honour the control
+//! plane's `Retry-After`.
+//!
+//! ```no_run
+//! # async fn run() -> trawling_client::Result<()> {
+//! use trawling_client::{BotAction, TrawlingClient};
+//!
+//! let client = TrawlingClient::new("https://api.trawlingtraders.com", "session-token")?;
+//! for bot in client.list_bots().await?.bots {
+//!     println!("{} {:?}", bot.name, bot.status);
+//!     client.action(bot.id, BotAction::Pause).await?;
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+pub mod client;
+pub mod error;
+pub mod types;
+
+pub use client::{RetryPolicy, TrawlingClient};
+pub use error::{ClientError, Result};
+pub use types::*;
@@ -0,0 +1,796 @@
+//! Request and response types for the control-plane API
+//!
+//! These mirror `control_plane::models` as they appear on the wire. Enums
+//! that grow over time (`BotStatus`, `EventType`) have an `Unknown` variant
+//! so an older client keeps working against a newer control plane.
+
+use chrono::{DateTime, NaiveDate, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Persona {
+    Beginner,
+    Tweaker,
+    QuantLite,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AlgorithmMode {
+    Trend,
+    MeanReversion,
+    Breakout,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AssetFocus {
+    Majors,
+    TokenizedEquities,
+    TokenizedMetals,
+    Memes,
+    Custom,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Strictness {
+    Low,
+    #[default]
+    Medium,
+    High,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TradingMode {
+    Paper,
+    Live,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BotStatus {
+    Provisioning,
+    Online,
+    Offline,
+    Paused,
+    Error,
+    Destroying,
+    #[serde(other)]
+    Unknown,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ConfigStatus {
+    Pending,
+    Applied,
+    Failed,
+}
+
+/// How a bot's runner is hosted
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProvisioningMode {
+    /// The platform creates and manages a droplet
+    #[default]
+    Droplet,
+    /// The owner runs bot-runner themselves
+    Manual,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum EventType {
+    TradeOpened,
+    TradeClosed,
+    StopTriggered,
+    ConfigApplied,
+    ConfigFailed,
+    Error,
+    StatusChange,
+    #[serde(other)]
+    Unknown,
+}
+
+/// Lifecycle actions for `POST /bots/:id/actions`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BotAction {
+    Pause,
+    Resume,
+    Redeploy,
+    Destroy,
+}
+
+/// Constraints applied to every algorithm
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RiskCaps {
+    pub max_position_size_percent: i32,
+    pub max_daily_loss_usd: i32,
+    pub max_drawdown_percent: i32,
+    pub max_trades_per_day: i32,
+}
+
+impl Default for RiskCaps {
+    fn default() -> Self {
+        Self {
+            max_position_size_percent: 5,
+            max_daily_loss_usd: 100,
+            max_drawdown_percent: 10,
+            max_trades_per_day: 10,
+        }
+    }
+}
+
+/// Exposure cap for one asset
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AssetCap {
+    /// Symbol (e.g. "SOL") or mint address
+    pub asset: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_exposure_usd: Option<Decimal>,
+    /// Percent of total equity (20 = 20%)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_exposure_percent: Option<Decimal>,
+}
+
+/// Limits on top of [`RiskCaps`], checked on every buy
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct AssetLimits {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_open_positions: Option<i32>,
+    /// Minutes between trades on the same asset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cooldown_minutes: Option<i32>,
+    /// Most of total equity in longs plus shorts
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_gross_exposure_percent: Option<Decimal>,
+    /// Most of total equity that longs minus shorts may come to, either way
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_net_exposure_percent: Option<Decimal>,
+    #[serde(default)]
+    pub per_asset: Vec<AssetCap>,
+}
+
+/// UTC session in which the bot may open new trades
+///
+/// `start` after `end` runs past midnight; leaving both out trades whole
+/// days. An empty `days` means every day.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TradingWindow {
+    #[serde(default)]
+    pub days: Vec<chrono::Weekday>,
+    /// `HH:MM`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub start: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub end: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct User {
+    pub id: Uuid,
+    pub email: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Bot {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub name: String,
+    pub status: BotStatus,
+    pub persona: Persona,
+    pub droplet_id: Option<i64>,
+    pub region: String,
+    pub ip_address: Option<String>,
+    pub agent_wallet: Option<String>,
+    pub desired_version_id: Uuid,
+    pub applied_version_id: Option<Uuid>,
+    pub config_status: ConfigStatus,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub last_heartbeat_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub provisioning: ProvisioningMode,
+    #[serde(default)]
+    pub encrypt_state: bool,
+}
+
+/// A saved configuration version
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConfigVersion {
+    pub id: Uuid,
+    pub bot_id: Uuid,
+    pub version: i32,
+    pub name: String,
+    pub persona: Persona,
+    pub asset_focus: AssetFocus,
+    pub custom_assets: Option<serde_json::Value>,
+    pub algorithm_mode: AlgorithmMode,
+    pub strictness: Strictness,
+    pub max_position_size_percent: i32,
+    pub max_daily_loss_usd: i32,
+    pub max_drawdown_percent: i32,
+    pub max_trades_per_day: i32,
+    pub trading_mode: TradingMode,
+    pub llm_provider: String,
+    pub created_at: DateTime<Utc>,
+    #[serde(default)]
+    pub trailing_stop_percent: Option<i32>,
+    #[serde(default)]
+    pub asset_limits: Option<AssetLimits>,
+    #[serde(default)]
+    pub trading_window: Option<TradingWindow>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Metric {
+    pub id: Uuid,
+    pub bot_id: Uuid,
+    pub timestamp: DateTime<Utc>,
+    pub equity: Decimal,
+    pub pnl: Decimal,
+    /// Reconstructed by the backfill while the bot was offline
+    #[serde(default)]
+    pub synthetic: bool,
+    /// Cumulative swap and network fees, when the runner reported them
+    #[serde(default)]
+    pub fees_usd: Option<Decimal>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Event {
+    pub id: Uuid,
+    pub bot_id: Uuid,
+    pub event_type: EventType,
+    pub message: String,
+    pub metadata: Option<serde_json::Value>,
+    pub created_at: DateTime<Utc>,
+    /// `info`, `warning` or `error`
+    #[serde(default)]
+    pub severity: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ListBotsResponse {
+    pub bots: Vec<Bot>,
+    pub total: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BotResponse {
+    pub bot: Bot,
+    pub config: Option<ConfigVersion>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MetricsResponse {
+    /// Newest first
+    pub metrics: Vec<Metric>,
+    pub range: String,
+    /// `raw`, or the bucket width (`15m`, `1h`, ...) the points average over
+    #[serde(default)]
+    pub resolution: Option<String>,
+    /// Drawdowns open at any point in the range
+    #[serde(default)]
+    pub drawdowns: Vec<DrawdownEpisode>,
+    /// The runner's latest rolling statistics
+    #[serde(default)]
+    pub performance: Option<PerformanceStats>,
+}
+
+/// How far back `GET /bots/:id/metrics` goes
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum MetricsRange {
+    Day,
+    #[default]
+    Week,
+    Month,
+    All,
+}
+
+impl MetricsRange {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MetricsRange::Day => "24h",
+            MetricsRange::Week => "7d",
+            MetricsRange::Month => "30d",
+            MetricsRange::All => "all",
+        }
+    }
+}
+
+/// Win rate, Sharpe ratio and drawdown over a trailing window, as reported
+/// by the runner
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PerformanceStats {
+    pub window_days: i64,
+    pub closed_trades: i64,
+    pub win_rate: Option<f64>,
+    pub avg_win_usd: Option<Decimal>,
+    /// Negative: the average losing trade's PnL
+    pub avg_loss_usd: Option<Decimal>,
+    /// Annualized, from daily equity returns
+    pub sharpe_ratio: Option<f64>,
+    pub max_drawdown_pct: Decimal,
+}
+
+/// Peak-to-recovery drawdown on the equity curve
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DrawdownEpisode {
+    /// The peak
+    pub started_at: DateTime<Utc>,
+    pub peak_equity: Decimal,
+    pub trough_at: DateTime<Utc>,
+    pub trough_equity: Decimal,
+    /// `None` while equity is still under the peak
+    pub recovered_at: Option<DateTime<Utc>>,
+    pub depth_pct: Decimal,
+    pub duration_secs: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EventsResponse {
+    pub events: Vec<Event>,
+    /// Set as [`EventsQuery::cursor`] for the next (older) page
+    pub next_cursor: Option<String>,
+}
+
+/// Page and filters for `GET /bots/:id/events`; the default is the newest 100
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EventsQuery {
+    /// `next_cursor` of the previous page
+    pub cursor: Option<String>,
+    /// Any of these types (all when empty)
+    pub event_types: Vec<String>,
+    /// `info`, `warning` or `error`
+    pub severity: Option<String>,
+    /// Inclusive
+    pub since: Option<DateTime<Utc>>,
+    /// Exclusive
+    pub until: Option<DateTime<Utc>>,
+    /// 1-500, default 100
+    pub limit: Option<u32>,
+}
+
+/// A journal export, diagnostics bundle or state snapshot in object storage
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Artifact {
+    pub id: Uuid,
+    pub bot_id: Uuid,
+    /// `journal_export`, `diagnostics` or `state_snapshot`
+    pub kind: String,
+    pub content_type: String,
+    pub size_bytes: i64,
+    pub created_at: DateTime<Utc>,
+    /// When storage lifecycle deletes it
+    pub expires_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArtifactsResponse {
+    pub artifacts: Vec<Artifact>,
+}
+
+/// Presigned link for fetching an artifact straight from storage
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArtifactDownload {
+    pub artifact: Artifact,
+    pub url: String,
+    pub url_expires_at: DateTime<Utc>,
+}
+
+/// One mint's results from `GET /bots/:id/performance/by-asset`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AssetPerformance {
+    pub mint: String,
+    pub symbol: Option<String>,
+    pub trade_count: i64,
+    /// Sells that realized PnL against a known cost basis
+    pub closed_trades: i64,
+    pub win_rate: Option<f64>,
+    pub realized_pnl_usd: Decimal,
+    /// `None` when the open quantity couldn't be priced
+    pub unrealized_pnl_usd: Option<Decimal>,
+    pub open_quantity: Decimal,
+    pub cost_basis_usd: Decimal,
+    pub mark_price_usd: Option<Decimal>,
+    pub avg_holding_secs: Option<i64>,
+    pub fees_usd: Decimal,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AssetPerformanceResponse {
+    pub bot_id: Uuid,
+    pub since: Option<DateTime<Utc>>,
+    pub assets: Vec<AssetPerformance>,
+}
+
+/// One trade from `GET /bots/:id/trades`
+///
+/// Fill fields are `None` until the `trade_confirmed` event arrives,
+/// realized fields for buys and sells without a known cost basis.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Trade {
+    pub id: Uuid,
+    /// The intent (and decision journal entry) the trade came from
+    pub intent_id: Uuid,
+    /// `buy`, `sell` or `swap`
+    pub side: Option<String>,
+    pub mint: Option<String>,
+    pub symbol: Option<String>,
+    pub input_mint: Option<String>,
+    pub output_mint: Option<String>,
+    pub quantity: Option<Decimal>,
+    pub notional_usd: Option<Decimal>,
+    pub executed_price: Option<Decimal>,
+    pub signature: Option<String>,
+    pub mode: Option<String>,
+    pub price_impact_pct: Option<f64>,
+    pub slippage_bps: Option<f64>,
+    pub fees_usd: Option<Decimal>,
+    pub realized_pnl_usd: Option<Decimal>,
+    pub cost_basis_usd: Option<Decimal>,
+    pub entry_price: Option<Decimal>,
+    pub fully_closed: Option<bool>,
+    pub executed_at: Option<DateTime<Utc>>,
+    pub closed_at: Option<DateTime<Utc>>,
+    pub recorded_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TradesResponse {
+    /// Newest first
+    pub trades: Vec<Trade>,
+    /// Pass to [`crate::TrawlingClient::trades`] for the next (older) page
+    pub next_cursor: Option<String>,
+}
+
+/// Length of a `GET /bots/:id/performance` period
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum RollupPeriod {
+    /// UTC day
+    #[default]
+    Day,
+    /// ISO week starting Monday
+    Week,
+}
+
+impl RollupPeriod {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RollupPeriod::Day => "day",
+            RollupPeriod::Week => "week",
+        }
+    }
+}
+
+/// One bot's results over a day or week
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PerformanceRollup {
+    pub period_start: NaiveDate,
+    pub start_equity: Decimal,
+    pub end_equity: Decimal,
+    pub pnl_usd: Decimal,
+    pub trade_count: i64,
+    pub closed_trades: i64,
+    pub wins: i64,
+    pub win_rate: Option<f64>,
+    pub fees_usd: Decimal,
+    pub max_drawdown_pct: Decimal,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PerformanceResponse {
+    pub bot_id: Uuid,
+    /// `day` or `week`
+    pub period: String,
+    /// Oldest first; the current period is partial
+    pub rollups: Vec<PerformanceRollup>,
+}
+
+/// Hypothetical caps for `GET /bots/:id/what-if`; `None` keeps the bot's value
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct WhatIfQuery {
+    /// Replay this many days (1-365, default 30)
+    pub days: Option<u32>,
+    pub max_position_size_percent: Option<i32>,
+    pub max_daily_loss_usd: Option<i32>,
+    pub max_trades_per_day: Option<i32>,
+}
+
+/// One replay of the ledger in a [`WhatIfResponse`]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WhatIfOutcome {
+    pub trade_count: i64,
+    /// Net of fees, open quantity marked at the last fill
+    pub pnl_usd: Decimal,
+    pub max_drawdown_usd: Decimal,
+    pub fees_usd: Decimal,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WhatIfResponse {
+    pub bot_id: Uuid,
+    pub since: DateTime<Utc>,
+    pub actual_caps: RiskCaps,
+    pub hypothetical_caps: RiskCaps,
+    pub actual: WhatIfOutcome,
+    pub hypothetical: WhatIfOutcome,
+    /// Trades the hypothetical caps would have blocked, by cap
+    pub trades_blocked: std::collections::BTreeMap<String, i64>,
+    /// Intents blocked by a cap the hypothetical loosens (not replayed)
+    pub unreplayed_intents: i64,
+    pub pnl_delta_usd: Decimal,
+    pub max_drawdown_delta_usd: Decimal,
+}
+
+/// OHLCV candle for [`BacktestRequest::candles`]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Candle {
+    pub timestamp: DateTime<Utc>,
+    pub open: Decimal,
+    pub high: Decimal,
+    pub low: Decimal,
+    pub close: Decimal,
+    pub volume: Decimal,
+}
+
+/// Body for `POST /backtest`; unset options take the server defaults
+#[derive(Debug, Clone, Serialize)]
+pub struct BacktestRequest {
+    /// Asset to test, e.g. `SOL`
+    pub symbol: String,
+    /// History to run over; the server fetches it when `None`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub candles: Option<Vec<Candle>>,
+    /// Candle size to fetch, e.g. `Hour1` (the default) or `Day1`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timeframe: Option<String>,
+    pub persona: Persona,
+    pub algorithm_mode: AlgorithmMode,
+    pub strictness: Strictness,
+    pub risk_caps: RiskCaps,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub initial_cash_usd: Option<Decimal>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub slippage_bps: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fee_bps: Option<u32>,
+    /// Seconds from the signal candle's close to the fill
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub latency_secs: Option<u32>,
+    /// Extra slippage per $10k of notional
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub impact_bps_per_10k: Option<u32>,
+    /// Percent of the fill candle's range added as slippage
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub volatility_slippage_pct: Option<u32>,
+    /// Base plus priority fee per fill
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub network_fee_usd: Option<Decimal>,
+    /// Bootstrap the closed trades into a distribution of outcomes
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub monte_carlo: Option<MonteCarloRequest>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct MonteCarloRequest {
+    /// Sequences to draw (server default 1000)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub runs: Option<usize>,
+    /// For a repeatable result; random when `None`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub seed: Option<u64>,
+}
+
+/// Execution model echoed in a [`BacktestResponse`]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BacktestExecution {
+    pub latency_secs: u32,
+    pub impact_bps_per_10k: u32,
+    pub volatility_slippage_pct: u32,
+    pub network_fee_usd: Decimal,
+}
+
+/// 5th, 50th and 95th percentile of a distribution
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Percentiles {
+    pub p5: Decimal,
+    pub p50: Decimal,
+    pub p95: Decimal,
+}
+
+/// Bootstrapped outcomes in a [`BacktestResponse`]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MonteCarloReport {
+    pub runs: usize,
+    pub trades_per_run: usize,
+    pub seed: u64,
+    pub terminal_equity_usd: Percentiles,
+    pub max_drawdown_pct: Percentiles,
+    /// Share of sequences ending below the initial cash
+    pub probability_of_loss: f64,
+}
+
+/// One simulated fill in a [`BacktestResponse`]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BacktestTrade {
+    pub timestamp: DateTime<Utc>,
+    /// `buy` or `sell`
+    pub side: String,
+    /// `signal`, `stop_loss` or `take_profit`
+    pub reason: String,
+    /// After slippage
+    pub price: Decimal,
+    pub quantity: Decimal,
+    pub notional_usd: Decimal,
+    /// Swap fee plus network fee
+    pub fee_usd: Decimal,
+    pub slippage_usd: Decimal,
+    /// Round trip PnL net of fees (sells only)
+    pub realized_pnl_usd: Option<Decimal>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EquityPoint {
+    pub timestamp: DateTime<Utc>,
+    pub equity: Decimal,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BacktestResponse {
+    pub symbol: String,
+    pub algorithm: String,
+    /// The algorithm's parameters after persona and strictness defaults
+    pub parameters: serde_json::Value,
+    pub candles: usize,
+    pub from: DateTime<Utc>,
+    pub to: DateTime<Utc>,
+    pub slippage_bps: u32,
+    pub fee_bps: u32,
+    /// Execution frictions the run used, defaults filled in
+    pub execution: BacktestExecution,
+    pub initial_cash_usd: Decimal,
+    pub final_equity_usd: Decimal,
+    pub total_return_pct: Decimal,
+    pub max_drawdown_pct: Decimal,
+    pub closed_trades: i64,
+    pub win_rate: Option<f64>,
+    pub fees_usd: Decimal,
+    pub slippage_usd: Decimal,
+    /// Signal fills the risk caps stopped, by cap
+    pub blocked: std::collections::BTreeMap<String, i64>,
+    pub trades: Vec<BacktestTrade>,
+    pub equity_curve: Vec<EquityPoint>,
+    /// Present when requested and at least one trade closed
+    #[serde(default)]
+    pub monte_carlo: Option<MonteCarloReport>,
+}
+
+/// Body for `POST /bots`
+#[derive(Debug, Clone, Serialize)]
+pub struct CreateBotRequest {
+    pub name: String,
+    pub persona: Persona,
+    pub algorithm_mode: AlgorithmMode,
+    pub asset_focus: AssetFocus,
+    pub strictness: Strictness,
+    pub trading_mode: TradingMode,
+    pub risk_caps: RiskCaps,
+    /// Sell once a position falls this many percent below its high
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub trailing_stop_percent: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub asset_limits: Option<AssetLimits>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub trading_window: Option<TradingWindow>,
+    pub llm_provider: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub llm_model: Option<String>,
+    /// Encrypted at rest by the control plane
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub llm_api_key: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub custom_assets: Option<Vec<String>>,
+    pub telegram_enabled: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub telegram_bot_token: Option<String>,
+    pub provisioning: ProvisioningMode,
+    /// Encrypt the runner's state files on disk with a per-bot key
+    pub encrypt_state: bool,
+}
+
+/// New configuration for `PATCH /bots/:id/config`
+#[derive(Debug, Clone, Serialize)]
+pub struct BotConfigInput {
+    pub name: String,
+    pub persona: Persona,
+    pub asset_focus: AssetFocus,
+    pub algorithm_mode: AlgorithmMode,
+    pub strictness: Strictness,
+    pub trading_mode: TradingMode,
+    pub risk_caps: RiskCaps,
+    /// Sell once a position falls this many percent below its high
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub trailing_stop_percent: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub asset_limits: Option<AssetLimits>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub trading_window: Option<TradingWindow>,
+    pub llm_provider: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub llm_model: Option<String>,
+    /// Encrypted at rest by the control plane
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub llm_api_key: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub custom_assets: Option<Vec<String>>,
+    pub telegram_enabled: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub telegram_bot_token: Option<String>,
+}
+
+/// The account kill switch, while engaged
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KillSwitch {
+    pub engaged_at: DateTime<Utc>,
+    pub reason: Option<String>,
+    /// Whether positions were also being sold off
+    pub flatten: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KillSwitchResponse {
+    pub engaged: bool,
+    pub kill_switch: Option<KillSwitch>,
+    pub bots_paused: usize,
+    /// Bots that had their pending intents cancelled
+    pub bots_notified: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub(crate) struct KillSwitchRequest<'a> {
+    pub flatten: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<&'a str>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub(crate) struct UpdateBotConfigRequest<'a> {
+    pub config: &'a BotConfigInput,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub(crate) struct BotActionRequest {
+    pub action: BotAction,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decodes_control_plane_json() {
+        let metrics: MetricsResponse = serde_json::from_value(serde_json::json!({
+            "metrics": [{
+                "id": "6f1c2a5e-6a43-4b8e-9a55-0d7c1b1f3e21",
+                "bot_id": "0b7c6f1e-2d0a-4e63-8a4f-3c1e5b9d2a10",
+                "timestamp": "2026-01-05T12:00:00Z",
+                "equity": "10250.50",
+                "pnl": "250.50",
+                "synthetic": false
+            }],
+            "range": "7d"
+        }))
+        .unwrap();
+        assert_eq!(metrics.metrics[0].equity, Decimal::new(1025050, 2));
+
+        // Values added to the API later don't break older clients
+        let status: BotStatus = serde_json::from_str(r#""Hibernating""#).unwrap();
+        assert_eq!(status, BotStatus::Unknown);
+        assert_eq!(
+            serde_json::to_value(BotAction::Redeploy).unwrap(),
+            "redeploy"
+        );
+    }
+}